@@ -93,21 +93,41 @@ fn main() {
             result.timing.lexing_us,
             100.0 * result.timing.lexing_us as f64 / result.timing.total_us as f64
         );
+        println!(
+            "    Expansion:     {:>6} μs ({:.1}%)",
+            result.timing.macro_expansion_us,
+            100.0 * result.timing.macro_expansion_us as f64 / result.timing.total_us as f64
+        );
         println!(
             "    Parsing:       {:>6} μs ({:.1}%)",
             result.timing.parsing_us,
             100.0 * result.timing.parsing_us as f64 / result.timing.total_us as f64
         );
+        println!(
+            "    Modules:       {:>6} μs ({:.1}%)",
+            result.timing.module_loading_us,
+            100.0 * result.timing.module_loading_us as f64 / result.timing.total_us as f64
+        );
         println!(
             "    Type check:    {:>6} μs ({:.1}%)",
             result.timing.type_checking_us,
             100.0 * result.timing.type_checking_us as f64 / result.timing.total_us as f64
         );
+        println!(
+            "    Monomorphize:  {:>6} μs ({:.1}%)",
+            result.timing.monomorphization_us,
+            100.0 * result.timing.monomorphization_us as f64 / result.timing.total_us as f64
+        );
         println!(
             "    MIR building:  {:>6} μs ({:.1}%)",
             result.timing.mir_building_us,
             100.0 * result.timing.mir_building_us as f64 / result.timing.total_us as f64
         );
+        println!(
+            "    Borrow check:  {:>6} μs ({:.1}%)",
+            result.timing.borrow_checking_us,
+            100.0 * result.timing.borrow_checking_us as f64 / result.timing.total_us as f64
+        );
         println!(
             "    Optimization:  {:>6} μs ({:.1}%)",
             result.timing.optimization_us,
@@ -14,12 +14,21 @@
 //! - Integer args: X0-X7
 //! - Float args: V0-V7 (SIMD/FP registers)
 //! - Return: X0 (int), V0 (float)
+//! - Enum values: one register per word of their layout, as arguments and
+//!   as results (X0:X1). A larger result is written to memory at the
+//!   address the caller passes in X8, the indirect result register
 //! - Callee-saved: X19-X28, X29 (FP), X30 (LR)
 
-use super::AsmEmitter;
+use super::{
+    can_overflow, frame_layout, operand_type, operand_words, operation_size, param_words,
+    place_layout, place_type, result_address_slot, returns_in_memory, traps_on_overflow,
+    word_places, AsmEmitter,
+};
+use crate::mir::layout::TagEncoding;
 use crate::mir::types::{
-    BinaryOp, FloatBinaryOp, FloatCmp, MirConstant, MirFunction, MirInstruction, MirOperand,
-    MirPlace, MirRvalue, MirTerminator, RegisterClass, SimdOp, SimdWidth, UnaryOp,
+    AggregateKind, BinaryOp, FloatBinaryOp, FloatCmp, FloatSize, IntSize, MirConstant, MirFunction,
+    MirInstruction, MirOperand, MirPlace, MirRvalue, MirTerminator, MirType, Overflow,
+    PlaceProjection, RegisterClass, SimdOp, SimdWidth, UnaryOp,
 };
 use std::collections::HashMap;
 
@@ -35,6 +44,10 @@ pub struct AArch64Emitter {
     current_func: String,
     /// Label counter
     label_counter: usize,
+    /// Types of the current function's locals, which lay out enum values
+    local_types: HashMap<usize, MirType>,
+    /// What integer overflow does in the current function
+    overflow: Overflow,
 }
 
 /// AArch64 registers
@@ -434,6 +447,8 @@ impl AArch64Emitter {
             reg_alloc: AArch64RegAlloc::new(),
             current_func: String::new(),
             label_counter: 0,
+            local_types: HashMap::new(),
+            overflow: Overflow::Wrap,
         }
     }
 
@@ -453,8 +468,13 @@ impl AArch64Emitter {
         self.instructions.push(directive.to_string());
     }
 
+    /// Label of block `id` of the current function
+    fn block_label(&self, id: usize) -> String {
+        format!(".L{}_bb{}", self.current_func, id)
+    }
+
     fn new_label(&mut self, prefix: &str) -> String {
-        let label = format!(".L{}_{}", prefix, self.label_counter);
+        let label = format!(".L{}_{}_{}", prefix, self.current_func, self.label_counter);
         self.label_counter += 1;
         label
     }
@@ -471,15 +491,22 @@ impl AArch64Emitter {
     }
 
     /// Emit place to memory reference
+    ///
+    /// Field and constant index projections address the slots of a
+    /// multi-slot local, upwards from its base, and payload fields the
+    /// bytes their enum's layout gives them.
     fn place_to_str(&self, place: &MirPlace) -> String {
+        format!("[x29, #{}]", self.place_offset(place))
+    }
+
+    /// Frame-pointer-relative offset of a place
+    fn place_offset(&self, place: &MirPlace) -> i64 {
         if let Some(offset) = self.reg_alloc.get_local_offset(place.local) {
-            if offset >= 0 {
-                format!("[x29, #{}]", offset)
-            } else {
-                format!("[x29, #{}]", offset)
-            }
+            let ty = self.local_types.get(&place.local);
+            let ty = ty.cloned().unwrap_or(MirType::Int(IntSize::I64));
+            offset + place_layout(place, &ty).0
         } else {
-            format!("[x29, #-{}]", (place.local + 1) * 8)
+            -(((place.local + 1) * 8) as i64)
         }
     }
 
@@ -540,14 +567,99 @@ impl AArch64Emitter {
         self.emit(&format!("str {}, {}", reg.name(), dest));
     }
 
-    /// Emit binary operation
+    /// Tag encoding of the enum value at `place`, if it is one
+    fn tag_encoding(&self, place: &MirPlace) -> Option<TagEncoding> {
+        let ty = place_type(place, &self.local_types);
+        ty.enum_layout().map(|layout| layout.tag.clone())
+    }
+
+    /// Record in the enum value at `place` that it holds `variant`
+    fn emit_set_discriminant(&mut self, place: &MirPlace, variant: usize) {
+        self.emit_comment(&format!("Set discriminant to {}", variant));
+        let dest = self.place_to_str(place);
+        match self.tag_encoding(place) {
+            // A null reference stands for the empty variant
+            Some(TagEncoding::Niche { empty, .. }) if variant == empty => {
+                self.emit(&format!("str xzr, {}", dest));
+            }
+            // The reference stored as the payload says it all
+            Some(TagEncoding::Niche { .. }) => {}
+            Some(TagEncoding::Direct { width }) => {
+                let store = match width {
+                    1 => "strb",
+                    2 => "strh",
+                    _ => "str",
+                };
+                self.emit(&format!("mov w0, #{}", variant));
+                self.emit(&format!("{} w0, {}", store, dest));
+            }
+            None => {
+                self.emit(&format!("mov x0, #{}", variant));
+                self.emit(&format!("str x0, {}", dest));
+            }
+        }
+    }
+
+    /// Load the index of the variant the enum value at `place` holds into X0
+    fn load_discriminant(&mut self, place: &MirPlace) {
+        let src = self.place_to_str(place);
+        match self.tag_encoding(place) {
+            Some(TagEncoding::Niche { dataful, empty }) => {
+                self.emit(&format!("ldr x0, {}", src));
+                self.emit("cmp x0, #0");
+                self.emit(&format!("mov x0, #{}", empty));
+                self.emit(&format!("mov x1, #{}", dataful));
+                self.emit("csel x0, x1, x0, ne");
+            }
+            // Narrow loads zero the rest of the register
+            Some(TagEncoding::Direct { width }) => {
+                let load = match width {
+                    1 => "ldrb",
+                    2 => "ldrh",
+                    _ => "ldr",
+                };
+                self.emit(&format!("{} w0, {}", load, src));
+            }
+            None => self.emit(&format!("ldr x0, {}", src)),
+        }
+    }
+
+    /// Copy the `words` words of the value at `src` to `dest`
+    fn copy_words(&mut self, src: &MirPlace, dest: &MirPlace, words: usize) {
+        for (from, to) in word_places(src, words).iter().zip(word_places(dest, words)) {
+            self.load_operand(&MirOperand::Copy(from.clone()), AArch64Reg::X0);
+            self.store_to_place(AArch64Reg::X0, &to);
+        }
+    }
+
+    /// Build the enum value of `variant` with payload `operands` at `dest`
+    fn emit_enum(&mut self, dest: &MirPlace, variant: usize, operands: &[MirOperand]) {
+        self.emit_set_discriminant(dest, variant);
+        for (index, operand) in operands.iter().enumerate() {
+            let mut field = dest.clone();
+            field.projection.push(PlaceProjection::Downcast { variant });
+            field.projection.push(PlaceProjection::Field { index });
+            let words = operand_words(operand, &self.local_types);
+            for (word, place) in words.iter().zip(word_places(&field, words.len())) {
+                self.load_operand(word, AArch64Reg::X0);
+                self.store_to_place(AArch64Reg::X0, &place);
+            }
+        }
+    }
+
+    /// Emit binary operation on `size` integers
+    ///
+    /// Unsigned integers divide, shift and compare with the unsigned
+    /// instructions; the rest are the same for both.
     fn emit_binary_op(
         &mut self,
         op: BinaryOp,
+        size: IntSize,
         dest: AArch64Reg,
         left: AArch64Reg,
         right: AArch64Reg,
     ) {
+        let signed = size.is_signed();
         match op {
             BinaryOp::Add => {
                 self.emit(&format!(
@@ -575,7 +687,8 @@ impl AArch64Emitter {
             }
             BinaryOp::Div => {
                 self.emit(&format!(
-                    "sdiv {}, {}, {}",
+                    "{} {}, {}, {}",
+                    if signed { "sdiv" } else { "udiv" },
                     dest.name(),
                     left.name(),
                     right.name()
@@ -583,7 +696,12 @@ impl AArch64Emitter {
             }
             BinaryOp::Rem => {
                 // ARM64: remainder = dividend - (quotient * divisor)
-                self.emit(&format!("sdiv x16, {}, {}", left.name(), right.name()));
+                self.emit(&format!(
+                    "{} x16, {}, {}",
+                    if signed { "sdiv" } else { "udiv" },
+                    left.name(),
+                    right.name()
+                ));
                 self.emit(&format!(
                     "msub {}, x16, {}, {}",
                     dest.name(),
@@ -624,8 +742,10 @@ impl AArch64Emitter {
                 ));
             }
             BinaryOp::Shr => {
+                // Arithmetic shift for signed integers, logical for unsigned
                 self.emit(&format!(
-                    "asr {}, {}, {}",
+                    "{} {}, {}, {}",
+                    if signed { "asr" } else { "lsr" },
                     dest.name(),
                     left.name(),
                     right.name()
@@ -638,13 +758,17 @@ impl AArch64Emitter {
             | BinaryOp::Gt
             | BinaryOp::Ge => {
                 self.emit(&format!("cmp {}, {}", left.name(), right.name()));
-                let cond = match op {
-                    BinaryOp::Eq => "eq",
-                    BinaryOp::Ne => "ne",
-                    BinaryOp::Lt => "lt",
-                    BinaryOp::Le => "le",
-                    BinaryOp::Gt => "gt",
-                    BinaryOp::Ge => "ge",
+                let cond = match (op, signed) {
+                    (BinaryOp::Eq, _) => "eq",
+                    (BinaryOp::Ne, _) => "ne",
+                    (BinaryOp::Lt, true) => "lt",
+                    (BinaryOp::Le, true) => "le",
+                    (BinaryOp::Gt, true) => "gt",
+                    (BinaryOp::Ge, true) => "ge",
+                    (BinaryOp::Lt, false) => "lo",
+                    (BinaryOp::Le, false) => "ls",
+                    (BinaryOp::Gt, false) => "hi",
+                    (BinaryOp::Ge, false) => "hs",
                     _ => unreachable!(),
                 };
                 self.emit(&format!("cset {}, {}", dest.name(), cond));
@@ -652,6 +776,120 @@ impl AArch64Emitter {
         }
     }
 
+    /// Sign- or zero-extend the low `size` bits of X0 into `dest` (`x0`
+    /// or `x16`)
+    fn emit_extend(&mut self, size: IntSize, dest: &str) {
+        let dest32 = dest.replacen('x', "w", 1);
+        match size {
+            IntSize::I8 => self.emit(&format!("sxtb {}, w0", dest)),
+            IntSize::I16 => self.emit(&format!("sxth {}, w0", dest)),
+            IntSize::I32 => self.emit(&format!("sxtw {}, w0", dest)),
+            IntSize::U8 => self.emit(&format!("uxtb {}, w0", dest32)),
+            IntSize::U16 => self.emit(&format!("uxth {}, w0", dest32)),
+            // A 32-bit move zeroes the upper half
+            IntSize::U32 => self.emit(&format!("mov {}, w0", dest32)),
+            IntSize::I64 | IntSize::U64 => {
+                if dest != "x0" {
+                    self.emit(&format!("mov {}, x0", dest));
+                }
+            }
+        }
+    }
+
+    /// Bring the result `op` left in X0, from X1 and X2, back into `size`
+    ///
+    /// The result wraps to the width, or, in a function that traps on
+    /// overflow, must be unchanged by that. A 64-bit result has nothing
+    /// beyond its width, so the operands are tested instead.
+    fn emit_overflow(&mut self, op: BinaryOp, size: IntSize) {
+        if !can_overflow(op, size) {
+            return;
+        }
+        if self.overflow == Overflow::Wrap || !traps_on_overflow(op, size) {
+            self.emit_extend(size, "x0");
+            return;
+        }
+        let pass_label = self.new_label("no_overflow");
+        let signed = size.is_signed();
+        match op {
+            _ if size.bits() < 64 => {
+                self.emit_extend(size, "x16");
+                self.emit("cmp x16, x0");
+                self.emit(&format!("b.eq {}", pass_label));
+            }
+            BinaryOp::Add => {
+                self.emit("cmn x1, x2");
+                let cond = if signed { "b.vc" } else { "b.cc" };
+                self.emit(&format!("{} {}", cond, pass_label));
+            }
+            BinaryOp::Sub => {
+                self.emit("cmp x1, x2");
+                let cond = if signed { "b.vc" } else { "b.hs" };
+                self.emit(&format!("{} {}", cond, pass_label));
+            }
+            // The high half of the product is the sign of the low, or zero
+            BinaryOp::Mul if signed => {
+                self.emit("smulh x16, x1, x2");
+                self.emit("cmp x16, x0, asr #63");
+                self.emit(&format!("b.eq {}", pass_label));
+            }
+            BinaryOp::Mul => {
+                self.emit("umulh x16, x1, x2");
+                self.emit(&format!("cbz x16, {}", pass_label));
+            }
+            // Only the most negative number divided by -1 overflows
+            BinaryOp::Div if signed => {
+                self.emit("cmn x2, #1");
+                self.emit(&format!("b.ne {}", pass_label));
+                self.emit("negs xzr, x1");
+                self.emit(&format!("b.vc {}", pass_label));
+            }
+            _ => return,
+        }
+        self.emit_comment("Integer overflow");
+        self.emit("brk #1");
+        self.emit_label(&pass_label);
+    }
+
+    /// Convert the `from` value in X0 to `to`, in X0
+    ///
+    /// An integer is truncated or extended to the target's width, as `as`
+    /// says; a float is truncated towards zero. Floats are held as their
+    /// bits, so they pass through V0.
+    fn emit_cast(&mut self, from: &MirType, to: &MirType) {
+        let float_reg = |size: &FloatSize| match size {
+            FloatSize::F64 => ("d0", "x0"),
+            FloatSize::F32 => ("s0", "w0"),
+        };
+        match (from, to) {
+            (MirType::Float(from), MirType::Float(to)) if from != to => {
+                let (from_reg, from_bits) = float_reg(from);
+                let (to_reg, to_bits) = float_reg(to);
+                self.emit(&format!("fmov {}, {}", from_reg, from_bits));
+                self.emit(&format!("fcvt {}, {}", to_reg, from_reg));
+                self.emit(&format!("fmov {}, {}", to_bits, to_reg));
+            }
+            (MirType::Float(from), MirType::Int(size)) => {
+                let (reg, bits) = float_reg(from);
+                self.emit(&format!("fmov {}, {}", reg, bits));
+                let convert = if size.is_signed() { "fcvtzs" } else { "fcvtzu" };
+                self.emit(&format!("{} x0, {}", convert, reg));
+                self.emit_extend(*size, "x0");
+            }
+            (_, MirType::Float(to)) if !matches!(from, MirType::Float(_)) => {
+                let (reg, bits) = float_reg(to);
+                let convert = match from {
+                    MirType::Int(size) if !size.is_signed() => "ucvtf",
+                    _ => "scvtf",
+                };
+                self.emit(&format!("{} {}, x0", convert, reg));
+                self.emit(&format!("fmov {}, {}", bits, reg));
+            }
+            (_, MirType::Int(size)) => self.emit_extend(*size, "x0"),
+            _ => {}
+        }
+    }
+
     /// Load float operand into V register
     fn load_float_operand(&mut self, operand: &MirOperand, reg: VReg, is_double: bool) {
         let reg_name = if is_double {
@@ -799,7 +1037,7 @@ impl AsmEmitter for AArch64Emitter {
         }
 
         // Allocate stack space for locals (aligned to 16 bytes)
-        let locals_space = func.locals.len() * 8;
+        let (offsets, locals_space) = frame_layout(func, 0);
         let aligned_space = (locals_space + 15) & !15;
         if aligned_space > 0 {
             self.emit(&format!("sub sp, sp, #{}", aligned_space));
@@ -807,24 +1045,34 @@ impl AsmEmitter for AArch64Emitter {
         }
 
         // Assign stack offsets to locals
-        for (i, local) in func.locals.iter().enumerate() {
-            let offset = -(((i + 1) * 8) as i64);
-            self.reg_alloc.set_local_offset(local.index, offset);
+        for (local, offset) in offsets {
+            self.reg_alloc.set_local_offset(local, offset);
+        }
+        self.local_types = func
+            .locals
+            .iter()
+            .map(|local| (local.index, local.ty.clone()))
+            .collect();
+        self.overflow = func.overflow;
+
+        // Keep the address of an in-memory result
+        if let Some(slot) = result_address_slot(func, 0) {
+            self.emit(&format!("str x8, [x29, #{}]", slot));
         }
 
-        // Move arguments from registers to stack
-        for (i, _param) in func.params.iter().enumerate() {
+        // Move arguments from registers to their locals
+        for (i, place) in param_words(func).iter().enumerate() {
             if let Some(reg) = AArch64Reg::arg_register(i) {
-                let offset = -(((func.locals.len() + i + 1) * 8) as i64);
                 self.emit_comment(&format!("Store arg {} from {}", i, reg.name()));
-                self.emit(&format!("str {}, [x29, #{}]", reg.name(), offset));
+                self.store_to_place(reg, place);
             }
         }
     }
 
     fn emit_body(&mut self, func: &MirFunction) {
         for block in &func.blocks {
-            self.emit_label(&format!(".L{}", block.id));
+            let label = self.block_label(block.id);
+            self.emit_label(&label);
 
             for instr in &block.instructions {
                 self.emit_mir_instruction(instr);
@@ -838,6 +1086,27 @@ impl AsmEmitter for AArch64Emitter {
         // Emit epilogue label
         self.emit_label(&format!(".L{}_epilogue", self.current_func));
 
+        // An enum result is returned in X0:X1 from its local, `_0`, or
+        // copied to the address the caller passed
+        if let Some(layout) = func.return_type.enum_layout() {
+            let result = MirPlace {
+                local: 0,
+                projection: vec![],
+            };
+            let words = word_places(&result, layout.words());
+            if let Some(slot) = result_address_slot(func, 0) {
+                self.emit(&format!("ldr x8, [x29, #{}]", slot));
+                for (i, place) in words.iter().enumerate() {
+                    self.load_operand(&MirOperand::Copy(place.clone()), AArch64Reg::X0);
+                    self.emit(&format!("str x0, [x8, #{}]", i * 8));
+                }
+            } else {
+                for (place, reg) in words.iter().zip([AArch64Reg::X0, AArch64Reg::X1]) {
+                    self.load_operand(&MirOperand::Copy(place.clone()), reg);
+                }
+            }
+        }
+
         // Restore stack
         if self.stack_offset > 0 {
             self.emit(&format!("add sp, sp, #{}", self.stack_offset));
@@ -871,10 +1140,14 @@ impl AsmEmitter for AArch64Emitter {
         output.push_str("// Generated by jagc compiler\n");
         output.push_str(".text\n\n");
 
-        output.push_str(&self.instructions.join("\n"));
+        output.push_str(&self.get_body());
         output
     }
 
+    fn get_body(&self) -> String {
+        self.instructions.join("\n")
+    }
+
     fn get_machine_code(&self) -> Vec<u8> {
         // Would use an assembler to convert to machine code
         Vec::new()
@@ -901,9 +1174,7 @@ impl AArch64Emitter {
                 self.store_to_place(AArch64Reg::X0, dest);
             }
             MirInstruction::SetDiscriminant { place, variant } => {
-                self.emit_comment(&format!("Set discriminant to {}", variant));
-                self.emit(&format!("mov x0, #{}", variant));
-                self.store_to_place(AArch64Reg::X0, place);
+                self.emit_set_discriminant(place, *variant);
             }
             MirInstruction::BoundsCheck {
                 index,
@@ -939,22 +1210,64 @@ impl AArch64Emitter {
     }
 
     fn emit_rvalue(&mut self, rvalue: &MirRvalue, dest: &MirPlace) {
+        // Enum values span the words of their layout
+        if let Some(layout) = place_type(dest, &self.local_types).enum_layout() {
+            match rvalue {
+                MirRvalue::Use(MirOperand::Copy(src) | MirOperand::Move(src)) => {
+                    self.copy_words(src, dest, layout.words());
+                    return;
+                }
+                MirRvalue::Aggregate {
+                    kind: AggregateKind::Enum { variant, .. },
+                    operands,
+                } => {
+                    self.emit_enum(dest, *variant, operands);
+                    return;
+                }
+                _ => {}
+            }
+        }
         match rvalue {
             MirRvalue::Use(operand) => {
                 self.load_operand(operand, AArch64Reg::X0);
                 self.store_to_place(AArch64Reg::X0, dest);
             }
             MirRvalue::BinaryOp { op, left, right } => {
+                let size = operation_size(*op, left, right, dest, &self.local_types);
                 self.load_operand(left, AArch64Reg::X1);
                 self.load_operand(right, AArch64Reg::X2);
-                self.emit_binary_op(*op, AArch64Reg::X0, AArch64Reg::X1, AArch64Reg::X2);
+                self.emit_binary_op(*op, size, AArch64Reg::X0, AArch64Reg::X1, AArch64Reg::X2);
+                self.emit_overflow(*op, size);
                 self.store_to_place(AArch64Reg::X0, dest);
             }
             MirRvalue::UnaryOp { op, operand } => {
-                self.load_operand(operand, AArch64Reg::X0);
-                match op {
-                    UnaryOp::Not => self.emit("mvn x0, x0"),
-                    UnaryOp::Neg => self.emit("neg x0, x0"),
+                match (op, place_type(dest, &self.local_types)) {
+                    // Negation is subtraction from zero, and overflows as it
+                    (UnaryOp::Neg, MirType::Int(size)) => {
+                        self.load_operand(operand, AArch64Reg::X2);
+                        self.emit("mov x1, xzr");
+                        self.emit_binary_op(
+                            BinaryOp::Sub,
+                            size,
+                            AArch64Reg::X0,
+                            AArch64Reg::X1,
+                            AArch64Reg::X2,
+                        );
+                        self.emit_overflow(BinaryOp::Sub, size);
+                    }
+                    (UnaryOp::Neg, _) => {
+                        self.load_operand(operand, AArch64Reg::X0);
+                        self.emit("neg x0, x0");
+                    }
+                    // The complement of a zero-extended value sets the bits
+                    // beyond its width
+                    (UnaryOp::Not, ty) => {
+                        self.load_operand(operand, AArch64Reg::X0);
+                        self.emit("mvn x0, x0");
+                        if let MirType::Int(size) = ty {
+                            self.emit_extend(size, "x0");
+                        }
+                    }
                 }
                 self.store_to_place(AArch64Reg::X0, dest);
             }
@@ -991,25 +1304,12 @@ impl AArch64Emitter {
                 }
             }
             MirRvalue::Index { base, index } => {
+                // base is the address of a `{data, len}` header
                 self.emit_comment("Array index access");
-                let elem_size = 8;
-                match base {
-                    MirOperand::Copy(place) | MirOperand::Move(place) => {
-                        let _src = self.place_to_str(place);
-                        self.emit(&format!(
-                            "add x1, x29, #{}",
-                            self.reg_alloc.get_local_offset(place.local).unwrap_or(0)
-                        ));
-                    }
-                    _ => {
-                        self.load_operand(base, AArch64Reg::X1);
-                    }
-                }
+                self.load_operand(base, AArch64Reg::X1);
+                self.emit("ldr x1, [x1]");
                 self.load_operand(index, AArch64Reg::X2);
-                self.emit(&format!("mov x3, #{}", elem_size));
-                self.emit("mul x2, x2, x3");
-                self.emit("add x1, x1, x2");
-                self.emit("ldr x0, [x1]");
+                self.emit("ldr x0, [x1, x2, lsl #3]");
                 self.store_to_place(AArch64Reg::X0, dest);
             }
             MirRvalue::FloatOp { op, left, right } => {
@@ -1034,14 +1334,15 @@ impl AArch64Emitter {
             MirRvalue::Cast {
                 kind: _,
                 operand,
-                ty: _,
+                ty,
             } => {
+                let from = operand_type(operand, &self.local_types);
                 self.load_operand(operand, AArch64Reg::X0);
+                self.emit_cast(&from, ty);
                 self.store_to_place(AArch64Reg::X0, dest);
             }
             MirRvalue::Discriminant(place) => {
-                let src = self.place_to_str(place);
-                self.emit(&format!("ldr x0, {}", src));
+                self.load_discriminant(place);
                 self.store_to_place(AArch64Reg::X0, dest);
             }
             MirRvalue::Len(place) => {
@@ -1050,6 +1351,38 @@ impl AArch64Emitter {
                 self.emit("ldr x0, [x0, #8]"); // Length at offset 8
                 self.store_to_place(AArch64Reg::X0, dest);
             }
+            MirRvalue::Aggregate {
+                kind: AggregateKind::Closure { function },
+                operands,
+            } => {
+                // Code address, then the captures, upwards from the local's base
+                self.emit_comment(&format!("Closure environment for {}", function));
+                let base = self.reg_alloc.get_local_offset(dest.local).unwrap_or(0);
+                self.emit(&format!("adrp x0, {}", function));
+                self.emit(&format!("add x0, x0, :lo12:{}", function));
+                self.emit(&format!("str x0, [x29, #{}]", base));
+                for (i, operand) in operands.iter().enumerate() {
+                    self.load_operand(operand, AArch64Reg::X0);
+                    let offset = base + ((i + 1) * 8) as i64;
+                    self.emit(&format!("str x0, [x29, #{}]", offset));
+                }
+            }
+            MirRvalue::Aggregate {
+                kind: AggregateKind::Array,
+                operands,
+            } => {
+                // `{data, len}` header, then the elements upwards from it
+                let base = self.reg_alloc.get_local_offset(dest.local).unwrap_or(0);
+                self.emit(&format!("add x0, x29, #{}", base + 16));
+                self.emit(&format!("str x0, [x29, #{}]", base));
+                self.emit(&format!("mov x0, #{}", operands.len()));
+                self.emit(&format!("str x0, [x29, #{}]", base + 8));
+                for (i, operand) in operands.iter().enumerate() {
+                    self.load_operand(operand, AArch64Reg::X0);
+                    let offset = base + 16 + (i * 8) as i64;
+                    self.emit(&format!("str x0, [x29, #{}]", offset));
+                }
+            }
             MirRvalue::Aggregate { kind: _, operands } => {
                 for (i, operand) in operands.iter().enumerate() {
                     self.load_operand(operand, AArch64Reg::X0);
@@ -1066,7 +1399,7 @@ impl AArch64Emitter {
                 self.emit(&format!("b .L{}_epilogue", self.current_func));
             }
             MirTerminator::Goto { target } => {
-                self.emit(&format!("b .L{}", target));
+                self.emit(&format!("b {}", self.block_label(*target)));
             }
             MirTerminator::SwitchInt {
                 discriminant,
@@ -1077,9 +1410,9 @@ impl AArch64Emitter {
                 self.load_operand(discriminant, AArch64Reg::X0);
                 for (value, target) in targets {
                     self.emit(&format!("cmp x0, #{}", value));
-                    self.emit(&format!("b.eq .L{}", target));
+                    self.emit(&format!("b.eq {}", self.block_label(*target)));
                 }
-                self.emit(&format!("b .L{}", otherwise));
+                self.emit(&format!("b {}", self.block_label(*otherwise)));
             }
             MirTerminator::Call {
                 func,
@@ -1088,12 +1421,24 @@ impl AArch64Emitter {
                 target,
             } => {
                 self.emit_comment("Function call");
+                let args: Vec<MirOperand> = args
+                    .iter()
+                    .flat_map(|arg| operand_words(arg, &self.local_types))
+                    .collect();
                 // Pass arguments in registers
                 for (i, arg) in args.iter().enumerate().take(8) {
                     if let Some(reg) = AArch64Reg::arg_register(i) {
                         self.load_operand(arg, reg);
                     }
                 }
+                // And the address of an in-memory result in X8
+                let result_address = destination
+                    .as_ref()
+                    .filter(|dest| returns_in_memory(&place_type(dest, &self.local_types)));
+                if let Some(dest) = result_address {
+                    let offset = self.place_offset(dest);
+                    self.emit(&format!("add x8, x29, #{}", offset));
+                }
                 // Branch and link
                 match func {
                     MirOperand::Constant(MirConstant::String(name)) => {
@@ -1104,12 +1449,20 @@ impl AArch64Emitter {
                         self.emit("blr x9");
                     }
                 }
-                // Store return value
-                if let Some(dest) = destination {
-                    self.store_to_place(AArch64Reg::X0, dest);
+                // Store return value, an enum's from X0:X1
+                if let Some(dest) = destination.as_ref().filter(|_| result_address.is_none()) {
+                    match place_type(dest, &self.local_types).enum_layout() {
+                        Some(layout) => {
+                            let words = word_places(dest, layout.words());
+                            for (place, reg) in words.iter().zip([AArch64Reg::X0, AArch64Reg::X1]) {
+                                self.store_to_place(reg, place);
+                            }
+                        }
+                        None => self.store_to_place(AArch64Reg::X0, dest),
+                    }
                 }
                 // Continue to target block
-                self.emit(&format!("b .L{}", target));
+                self.emit(&format!("b {}", self.block_label(*target)));
             }
             MirTerminator::Unreachable => {
                 self.emit_comment("Unreachable code - trap");
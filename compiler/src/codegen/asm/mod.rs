@@ -6,7 +6,202 @@ pub mod x86_64;
 pub mod aarch64;
 pub mod riscv64;
 
-use crate::mir::types::MirFunction;
+use crate::mir::types::{
+    BinaryOp, IntSize, MirConstant, MirFunction, MirOperand, MirPlace, MirType, PlaceProjection,
+};
+use std::collections::HashMap;
+
+/// Frame-pointer-relative offset of each local, and the bytes they occupy
+///
+/// Locals are laid out downwards from the frame pointer, one 8-byte slot
+/// each, starting `reserved` bytes below it. A local spanning several
+/// slots (a closure environment, or an array with its header) is given
+/// the offset of its lowest slot, so its elements lie at ascending
+/// addresses from there. A function returning its result in memory keeps
+/// the address to write it to in one more slot below the locals.
+pub fn frame_layout(func: &MirFunction, reserved: usize) -> (Vec<(usize, i64)>, usize) {
+    let mut offsets = Vec::with_capacity(func.locals.len());
+    let mut used = reserved;
+    for local in &func.locals {
+        used += local.ty.stack_slots() * 8;
+        offsets.push((local.index, -(used as i64)));
+    }
+    if returns_in_memory(&func.return_type) {
+        used += 8;
+    }
+    (offsets, used - reserved)
+}
+
+/// Whether a value of type `ty` is returned through memory rather than
+/// in two registers: an enum of more than two words. The caller passes
+/// the address the callee writes the value to.
+pub fn returns_in_memory(ty: &MirType) -> bool {
+    ty.enum_layout().is_some_and(|layout| layout.words() > 2)
+}
+
+/// Frame-pointer-relative offset of the slot holding the address `func`
+/// writes its result to, if it returns it in memory
+pub fn result_address_slot(func: &MirFunction, reserved: usize) -> Option<i64> {
+    returns_in_memory(&func.return_type).then(|| {
+        let (_, space) = frame_layout(func, reserved);
+        -((reserved + space) as i64)
+    })
+}
+
+/// Bytes from the base of a local of type `ty` to the value `place`
+/// projects to, and that value's type
+///
+/// Field and constant index projections select a slot of a multi-slot
+/// local. A downcast to a variant followed by a field selects that field
+/// of the variant's payload, where the enum's layout puts it; without a
+/// layout the payload is taken to follow a one-word discriminant. The
+/// rest leave the place where it is.
+pub fn place_layout(place: &MirPlace, ty: &MirType) -> (i64, MirType) {
+    let word = MirType::Int(IntSize::I64);
+    let mut offset = 0;
+    let mut ty = ty.clone();
+    let mut downcast = None;
+    for projection in &place.projection {
+        match projection {
+            PlaceProjection::Downcast { variant } => downcast = Some(*variant),
+            PlaceProjection::Field { index } => {
+                let field = match (downcast.take(), ty.enum_layout()) {
+                    (Some(variant), Some(layout)) => layout
+                        .field(variant, *index)
+                        .map(|(field_offset, field_ty)| (*field_offset, field_ty.clone())),
+                    (Some(_), None) => Some(((index + 1) * 8, word.clone())),
+                    (None, _) => Some((index * 8, word.clone())),
+                };
+                let (field_offset, field_ty) = field.unwrap_or((0, word.clone()));
+                offset += field_offset as i64;
+                ty = field_ty;
+            }
+            PlaceProjection::ConstIndex { offset: slots } => {
+                offset += *slots as i64 * 8;
+                ty = word.clone();
+            }
+            _ => {}
+        }
+    }
+    (offset, ty)
+}
+
+/// Type of the value at `place`, given the types of the locals
+pub fn place_type(place: &MirPlace, locals: &HashMap<usize, MirType>) -> MirType {
+    match locals.get(&place.local) {
+        Some(ty) => place_layout(place, ty).1,
+        None => MirType::Int(IntSize::I64),
+    }
+}
+
+/// Type of the value `operand` gives, given the types of the locals
+pub fn operand_type(operand: &MirOperand, locals: &HashMap<usize, MirType>) -> MirType {
+    match operand {
+        MirOperand::Constant(MirConstant::Int(_, size)) => MirType::Int(*size),
+        MirOperand::Constant(MirConstant::Float(_, size)) => MirType::Float(*size),
+        MirOperand::Constant(MirConstant::Bool(_)) => MirType::Bool,
+        MirOperand::Constant(_) => MirType::Int(IntSize::I64),
+        MirOperand::Copy(place) | MirOperand::Move(place) => place_type(place, locals),
+    }
+}
+
+/// Integer type `op` works in: its operands' for a comparison, otherwise
+/// its result's
+///
+/// Every integer is kept in its 8-byte slot sign- or zero-extended from
+/// its width, so 64-bit instructions compute the low bits of any result
+/// correctly; only the choice of signed or unsigned instructions, and
+/// what is done with bits beyond the width, depend on the type. A value
+/// not known to be an integer is taken to be an `i64`.
+pub fn operation_size(
+    op: BinaryOp,
+    left: &MirOperand,
+    right: &MirOperand,
+    dest: &MirPlace,
+    locals: &HashMap<usize, MirType>,
+) -> IntSize {
+    let size = |ty: MirType| match ty {
+        MirType::Int(size) => Some(size),
+        _ => None,
+    };
+    let found = match op {
+        BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+            size(operand_type(left, locals)).or_else(|| size(operand_type(right, locals)))
+        }
+        _ => size(place_type(dest, locals)),
+    };
+    found.unwrap_or(IntSize::I64)
+}
+
+/// Whether the result of `op` on `size` integers can leave the type's
+/// range, and must be wrapped back into it; unsigned division cannot
+pub fn can_overflow(op: BinaryOp, size: IntSize) -> bool {
+    match op {
+        BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Shl => true,
+        BinaryOp::Div => size.is_signed(),
+        _ => false,
+    }
+}
+
+/// Whether leaving its type's range traps for `op` in a function built
+/// to trap on overflow; a shift only ever wraps
+pub fn traps_on_overflow(op: BinaryOp, size: IntSize) -> bool {
+    can_overflow(op, size) && op != BinaryOp::Shl
+}
+
+/// Places of the `words` consecutive words of the value at `place`
+pub fn word_places(place: &MirPlace, words: usize) -> Vec<MirPlace> {
+    (0..words)
+        .map(|word| {
+            let mut place = place.clone();
+            place
+                .projection
+                .push(PlaceProjection::ConstIndex { offset: word });
+            place
+        })
+        .collect()
+}
+
+/// An operand as the words it spans, in order
+///
+/// An enum value at a place spans the words of its layout, and is passed
+/// to and returned from functions in that many registers. Any other
+/// operand is a single word.
+pub fn operand_words(operand: &MirOperand, locals: &HashMap<usize, MirType>) -> Vec<MirOperand> {
+    let (MirOperand::Copy(place) | MirOperand::Move(place)) = operand else {
+        return vec![operand.clone()];
+    };
+    match place_type(place, locals).enum_layout() {
+        Some(layout) => word_places(place, layout.words())
+            .into_iter()
+            .map(MirOperand::Copy)
+            .collect(),
+        None => vec![operand.clone()],
+    }
+}
+
+/// Places the argument words of `func` are stored to on entry, in order
+pub fn param_words(func: &MirFunction) -> Vec<MirPlace> {
+    let locals: HashMap<usize, MirType> = func
+        .locals
+        .iter()
+        .map(|local| (local.index, local.ty.clone()))
+        .collect();
+    func.params
+        .iter()
+        .flat_map(|param| {
+            let place = MirOperand::Copy(MirPlace {
+                local: param.index,
+                projection: vec![],
+            });
+            operand_words(&place, &locals)
+        })
+        .filter_map(|word| match word {
+            MirOperand::Copy(place) => Some(place),
+            _ => None,
+        })
+        .collect()
+}
 
 /// Assembly emitter trait
 pub trait AsmEmitter {
@@ -22,6 +217,9 @@ pub trait AsmEmitter {
     /// Get generated assembly as string
     fn get_asm(&self) -> String;
 
+    /// Get the emitted functions without the file header
+    fn get_body(&self) -> String;
+
     /// Get generated machine code as bytes
     fn get_machine_code(&self) -> Vec<u8>;
 }
@@ -80,6 +278,16 @@ pub enum Target {
 }
 
 impl Target {
+    /// Target named on the command line or in a `cfg` predicate
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "x86_64" | "x86-64" | "amd64" => Some(Target::X86_64),
+            "aarch64" | "arm64" => Some(Target::AArch64),
+            "riscv64" | "riscv" => Some(Target::RiscV64),
+            _ => None,
+        }
+    }
+
     /// Get pointer size in bytes
     pub fn pointer_size(&self) -> usize {
         8 // All supported targets are 64-bit
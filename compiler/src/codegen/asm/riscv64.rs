@@ -14,12 +14,21 @@
 //! - Integer args: a0-a7 (x10-x17)
 //! - Float args: fa0-fa7 (f10-f17)
 //! - Return: a0 (int), fa0 (float)
+//! - Enum values: one register per word of their layout, as arguments and
+//!   as results (a0:a1). A larger result is written to memory at an
+//!   address passed as a hidden first argument in a0
 //! - Callee-saved: s0-s11 (x8-x9, x18-x27), ra (x1)
 
-use super::AsmEmitter;
+use super::{
+    can_overflow, frame_layout, operand_type, operand_words, operation_size, param_words,
+    place_layout, place_type, result_address_slot, returns_in_memory, traps_on_overflow,
+    word_places, AsmEmitter,
+};
+use crate::mir::layout::TagEncoding;
 use crate::mir::types::{
-    BinaryOp, FloatBinaryOp, FloatCmp, MirConstant, MirFunction, MirInstruction, MirOperand,
-    MirPlace, MirRvalue, MirTerminator, RegisterClass, UnaryOp,
+    AggregateKind, BinaryOp, FloatBinaryOp, FloatCmp, FloatSize, IntSize, MirConstant, MirFunction,
+    MirInstruction, MirOperand, MirPlace, MirRvalue, MirTerminator, MirType, Overflow,
+    PlaceProjection, RegisterClass, UnaryOp,
 };
 use std::collections::HashMap;
 
@@ -35,6 +44,10 @@ pub struct RiscV64Emitter {
     current_func: String,
     /// Label counter
     label_counter: usize,
+    /// Types of the current function's locals, which lay out enum values
+    local_types: HashMap<usize, MirType>,
+    /// What integer overflow does in the current function
+    overflow: Overflow,
 }
 
 /// RISC-V registers
@@ -322,6 +335,8 @@ impl RiscV64Emitter {
             reg_alloc: RiscVRegAlloc::new(),
             current_func: String::new(),
             label_counter: 0,
+            local_types: HashMap::new(),
+            overflow: Overflow::Wrap,
         }
     }
 
@@ -341,18 +356,34 @@ impl RiscV64Emitter {
         self.instructions.push(directive.to_string());
     }
 
+    /// Label of block `id` of the current function
+    fn block_label(&self, id: usize) -> String {
+        format!(".L{}_bb{}", self.current_func, id)
+    }
+
     fn new_label(&mut self, prefix: &str) -> String {
-        let label = format!(".L{}_{}", prefix, self.label_counter);
+        let label = format!(".L{}_{}_{}", prefix, self.current_func, self.label_counter);
         self.label_counter += 1;
         label
     }
 
     /// Emit place to memory reference
+    ///
+    /// Field and constant index projections address the slots of a
+    /// multi-slot local, upwards from its base, and payload fields the
+    /// bytes their enum's layout gives them.
     fn place_to_str(&self, place: &MirPlace) -> String {
+        format!("{}(s0)", self.place_offset(place))
+    }
+
+    /// Frame-pointer-relative offset of a place
+    fn place_offset(&self, place: &MirPlace) -> i64 {
         if let Some(offset) = self.reg_alloc.get_local_offset(place.local) {
-            format!("{}(s0)", offset)
+            let ty = self.local_types.get(&place.local);
+            let ty = ty.cloned().unwrap_or(MirType::Int(IntSize::I64));
+            offset + place_layout(place, &ty).0
         } else {
-            format!("-{}(s0)", (place.local + 1) * 8)
+            -(((place.local + 1) * 8) as i64)
         }
     }
 
@@ -395,8 +426,99 @@ impl RiscV64Emitter {
         self.emit(&format!("sd {}, {}", reg.name(), dest));
     }
 
-    /// Emit binary operation
-    fn emit_binary_op(&mut self, op: BinaryOp, dest: RiscVReg, left: RiscVReg, right: RiscVReg) {
+    /// Tag encoding of the enum value at `place`, if it is one
+    fn tag_encoding(&self, place: &MirPlace) -> Option<TagEncoding> {
+        let ty = place_type(place, &self.local_types);
+        ty.enum_layout().map(|layout| layout.tag.clone())
+    }
+
+    /// Record in the enum value at `place` that it holds `variant`
+    fn emit_set_discriminant(&mut self, place: &MirPlace, variant: usize) {
+        self.emit_comment(&format!("Set discriminant to {}", variant));
+        let dest = self.place_to_str(place);
+        match self.tag_encoding(place) {
+            // A null reference stands for the empty variant
+            Some(TagEncoding::Niche { empty, .. }) if variant == empty => {
+                self.emit(&format!("sd zero, {}", dest));
+            }
+            // The reference stored as the payload says it all
+            Some(TagEncoding::Niche { .. }) => {}
+            Some(TagEncoding::Direct { width }) => {
+                let store = match width {
+                    1 => "sb",
+                    2 => "sh",
+                    _ => "sw",
+                };
+                self.emit(&format!("li t0, {}", variant));
+                self.emit(&format!("{} t0, {}", store, dest));
+            }
+            None => {
+                self.emit(&format!("li t0, {}", variant));
+                self.emit(&format!("sd t0, {}", dest));
+            }
+        }
+    }
+
+    /// Load the index of the variant the enum value at `place` holds into t0
+    fn load_discriminant(&mut self, place: &MirPlace) {
+        let src = self.place_to_str(place);
+        match self.tag_encoding(place) {
+            // The variants are 0 and 1: whether the reference is non-null
+            // gives the index of one or the other
+            Some(TagEncoding::Niche { dataful, .. }) => {
+                self.emit(&format!("ld t0, {}", src));
+                let test = if dataful == 1 { "snez" } else { "seqz" };
+                self.emit(&format!("{} t0, t0", test));
+            }
+            Some(TagEncoding::Direct { width }) => {
+                let load = match width {
+                    1 => "lbu",
+                    2 => "lhu",
+                    _ => "lwu",
+                };
+                self.emit(&format!("{} t0, {}", load, src));
+            }
+            None => self.emit(&format!("ld t0, {}", src)),
+        }
+    }
+
+    /// Copy the `words` words of the value at `src` to `dest`
+    fn copy_words(&mut self, src: &MirPlace, dest: &MirPlace, words: usize) {
+        for (from, to) in word_places(src, words).iter().zip(word_places(dest, words)) {
+            self.load_operand(&MirOperand::Copy(from.clone()), RiscVReg::T0);
+            self.store_to_place(RiscVReg::T0, &to);
+        }
+    }
+
+    /// Build the enum value of `variant` with payload `operands` at `dest`
+    fn emit_enum(&mut self, dest: &MirPlace, variant: usize, operands: &[MirOperand]) {
+        self.emit_set_discriminant(dest, variant);
+        for (index, operand) in operands.iter().enumerate() {
+            let mut field = dest.clone();
+            field.projection.push(PlaceProjection::Downcast { variant });
+            field.projection.push(PlaceProjection::Field { index });
+            let words = operand_words(operand, &self.local_types);
+            for (word, place) in words.iter().zip(word_places(&field, words.len())) {
+                self.load_operand(word, RiscVReg::T0);
+                self.store_to_place(RiscVReg::T0, &place);
+            }
+        }
+    }
+
+    /// Emit binary operation on `size` integers
+    ///
+    /// Unsigned integers divide, shift and compare with the unsigned
+    /// instructions; the rest are the same for both.
+    fn emit_binary_op(
+        &mut self,
+        op: BinaryOp,
+        size: IntSize,
+        dest: RiscVReg,
+        left: RiscVReg,
+        right: RiscVReg,
+    ) {
+        let signed = size.is_signed();
+        let slt = if signed { "slt" } else { "sltu" };
         match op {
             BinaryOp::Add => {
                 self.emit(&format!(
@@ -424,7 +546,8 @@ impl RiscV64Emitter {
             }
             BinaryOp::Div => {
                 self.emit(&format!(
-                    "div {}, {}, {}",
+                    "{} {}, {}, {}",
+                    if signed { "div" } else { "divu" },
                     dest.name(),
                     left.name(),
                     right.name()
@@ -432,7 +555,8 @@ impl RiscV64Emitter {
             }
             BinaryOp::Rem => {
                 self.emit(&format!(
-                    "rem {}, {}, {}",
+                    "{} {}, {}, {}",
+                    if signed { "rem" } else { "remu" },
                     dest.name(),
                     left.name(),
                     right.name()
@@ -472,7 +596,8 @@ impl RiscV64Emitter {
             }
             BinaryOp::Shr => {
                 self.emit(&format!(
-                    "sra {}, {}, {}",
+                    "{} {}, {}, {}",
+                    if signed { "sra" } else { "srl" },
                     dest.name(),
                     left.name(),
                     right.name()
@@ -498,7 +623,8 @@ impl RiscV64Emitter {
             }
             BinaryOp::Lt => {
                 self.emit(&format!(
-                    "slt {}, {}, {}",
+                    "{} {}, {}, {}",
+                    slt,
                     dest.name(),
                     left.name(),
                     right.name()
@@ -507,7 +633,8 @@ impl RiscV64Emitter {
             BinaryOp::Le => {
                 // a <= b  is  !(a > b)  is  !(b < a)
                 self.emit(&format!(
-                    "slt {}, {}, {}",
+                    "{} {}, {}, {}",
+                    slt,
                     dest.name(),
                     right.name(),
                     left.name()
@@ -516,7 +643,8 @@ impl RiscV64Emitter {
             }
             BinaryOp::Gt => {
                 self.emit(&format!(
-                    "slt {}, {}, {}",
+                    "{} {}, {}, {}",
+                    slt,
                     dest.name(),
                     right.name(),
                     left.name()
@@ -525,7 +653,8 @@ impl RiscV64Emitter {
             BinaryOp::Ge => {
                 // a >= b  is  !(a < b)
                 self.emit(&format!(
-                    "slt {}, {}, {}",
+                    "{} {}, {}, {}",
+                    slt,
                     dest.name(),
                     left.name(),
                     right.name()
@@ -535,6 +664,134 @@ impl RiscV64Emitter {
         }
     }
 
+    /// Sign- or zero-extend the low `size` bits of T0 into `dest` (`t0` or
+    /// `t3`)
+    fn emit_extend(&mut self, size: IntSize, dest: &str) {
+        match size {
+            IntSize::I8 => {
+                self.emit(&format!("slli {}, t0, 56", dest));
+                self.emit(&format!("srai {}, {}, 56", dest, dest));
+            }
+            IntSize::I16 => {
+                self.emit(&format!("slli {}, t0, 48", dest));
+                self.emit(&format!("srai {}, {}, 48", dest, dest));
+            }
+            IntSize::I32 => self.emit(&format!("sext.w {}, t0", dest)),
+            IntSize::U8 => self.emit(&format!("andi {}, t0, 255", dest)),
+            IntSize::U16 => {
+                self.emit(&format!("slli {}, t0, 48", dest));
+                self.emit(&format!("srli {}, {}, 48", dest, dest));
+            }
+            IntSize::U32 => {
+                self.emit(&format!("slli {}, t0, 32", dest));
+                self.emit(&format!("srli {}, {}, 32", dest, dest));
+            }
+            IntSize::I64 | IntSize::U64 => {
+                if dest != "t0" {
+                    self.emit(&format!("mv {}, t0", dest));
+                }
+            }
+        }
+    }
+
+    /// Bring the result `op` left in T0, from T1 and T2, back into `size`
+    ///
+    /// The result wraps to the width, or, in a function that traps on
+    /// overflow, must be unchanged by that. RISC-V has no overflow flag, so
+    /// a 64-bit result is tested against its operands.
+    fn emit_overflow(&mut self, op: BinaryOp, size: IntSize) {
+        if !can_overflow(op, size) {
+            return;
+        }
+        if self.overflow == Overflow::Wrap || !traps_on_overflow(op, size) {
+            self.emit_extend(size, "t0");
+            return;
+        }
+        let pass_label = self.new_label("no_overflow");
+        let signed = size.is_signed();
+        match op {
+            _ if size.bits() < 64 => {
+                self.emit_extend(size, "t3");
+                self.emit(&format!("beq t3, t0, {}", pass_label));
+            }
+            // The sum is below the left operand exactly when the right is
+            // negative
+            BinaryOp::Add if signed => {
+                self.emit("slt t3, t0, t1");
+                self.emit("slti t4, t2, 0");
+                self.emit(&format!("beq t3, t4, {}", pass_label));
+            }
+            BinaryOp::Add => self.emit(&format!("bgeu t0, t1, {}", pass_label)),
+            BinaryOp::Sub if signed => {
+                self.emit("slt t3, t0, t1");
+                self.emit("sgtz t4, t2");
+                self.emit(&format!("beq t3, t4, {}", pass_label));
+            }
+            BinaryOp::Sub => self.emit(&format!("bgeu t1, t2, {}", pass_label)),
+            // The high half of the product is the sign of the low, or zero
+            BinaryOp::Mul if signed => {
+                self.emit("mulh t3, t1, t2");
+                self.emit("srai t4, t0, 63");
+                self.emit(&format!("beq t3, t4, {}", pass_label));
+            }
+            BinaryOp::Mul => {
+                self.emit("mulhu t3, t1, t2");
+                self.emit(&format!("beqz t3, {}", pass_label));
+            }
+            // Only the most negative number divided by -1 overflows
+            BinaryOp::Div if signed => {
+                self.emit("li t3, -1");
+                self.emit(&format!("bne t2, t3, {}", pass_label));
+                self.emit("li t3, 1");
+                self.emit("slli t3, t3, 63");
+                self.emit(&format!("bne t1, t3, {}", pass_label));
+            }
+            _ => return,
+        }
+        self.emit_comment("Integer overflow");
+        self.emit("ebreak");
+        self.emit_label(&pass_label);
+    }
+
+    /// Convert the `from` value in T0 to `to`, in T0
+    ///
+    /// An integer is truncated or extended to the target's width, as `as`
+    /// says; a float is truncated towards zero. Floats are held as their
+    /// bits, so they pass through FT0.
+    fn emit_cast(&mut self, from: &MirType, to: &MirType) {
+        let suffix = |size: &FloatSize| match size {
+            FloatSize::F64 => ("d", "d"),
+            FloatSize::F32 => ("s", "w"),
+        };
+        match (from, to) {
+            (MirType::Float(from), MirType::Float(to)) if from != to => {
+                let (from, from_bits) = suffix(from);
+                let (to, to_bits) = suffix(to);
+                self.emit(&format!("fmv.{}.x ft0, t0", from_bits));
+                self.emit(&format!("fcvt.{}.{} ft0, ft0", to, from));
+                self.emit(&format!("fmv.x.{} t0, ft0", to_bits));
+            }
+            (MirType::Float(from), MirType::Int(size)) => {
+                let (from, bits) = suffix(from);
+                self.emit(&format!("fmv.{}.x ft0, t0", bits));
+                let int = if size.is_signed() { "l" } else { "lu" };
+                self.emit(&format!("fcvt.{}.{} t0, ft0, rtz", int, from));
+                self.emit_extend(*size, "t0");
+            }
+            (_, MirType::Float(to)) if !matches!(from, MirType::Float(_)) => {
+                let (to, bits) = suffix(to);
+                let int = match from {
+                    MirType::Int(size) if !size.is_signed() => "lu",
+                    _ => "l",
+                };
+                self.emit(&format!("fcvt.{}.{} ft0, t0", to, int));
+                self.emit(&format!("fmv.x.{} t0, ft0", bits));
+            }
+            (_, MirType::Int(size)) => self.emit_extend(*size, "t0"),
+            _ => {}
+        }
+    }
+
     /// Load float operand into F register
     fn load_float_operand(&mut self, operand: &MirOperand, reg: FReg, is_double: bool) {
         match operand {
@@ -704,8 +961,9 @@ impl AsmEmitter for RiscV64Emitter {
         self.emit_directive(&format!(".type {}, @function", func.name));
         self.emit_label(&func.name);
 
-        // Calculate stack frame size
-        let frame_size = 16 + func.locals.len() * 8; // ra + s0 + locals
+        // Calculate stack frame size: ra + s0, then the locals below them
+        let (offsets, locals_space) = frame_layout(func, 16);
+        let frame_size = 16 + locals_space;
         let aligned_size = (frame_size + 15) & !15;
 
         // Allocate stack frame
@@ -733,24 +991,36 @@ impl AsmEmitter for RiscV64Emitter {
         }
 
         // Assign stack offsets to locals
-        for (i, local) in func.locals.iter().enumerate() {
-            let offset = -(((i + 1) * 8) as i64);
-            self.reg_alloc.set_local_offset(local.index, offset);
+        for (local, offset) in offsets {
+            self.reg_alloc.set_local_offset(local, offset);
+        }
+        self.local_types = func
+            .locals
+            .iter()
+            .map(|local| (local.index, local.ty.clone()))
+            .collect();
+        self.overflow = func.overflow;
+
+        // Keep the address of an in-memory result, which comes before the arguments
+        let result_address = result_address_slot(func, 16);
+        if let Some(slot) = result_address {
+            self.emit(&format!("sd a0, {}(s0)", slot));
         }
 
-        // Move arguments from registers to stack
-        for (i, _param) in func.params.iter().enumerate() {
-            if let Some(reg) = RiscVReg::arg_register(i) {
-                let offset = -(((func.locals.len() + i + 1) * 8) as i64);
+        // Move arguments from registers to their locals
+        let first = usize::from(result_address.is_some());
+        for (i, place) in param_words(func).iter().enumerate() {
+            if let Some(reg) = RiscVReg::arg_register(first + i) {
                 self.emit_comment(&format!("Store arg {} from {}", i, reg.name()));
-                self.emit(&format!("sd {}, {}(s0)", reg.name(), offset));
+                self.store_to_place(reg, place);
             }
         }
     }
 
     fn emit_body(&mut self, func: &MirFunction) {
         for block in &func.blocks {
-            self.emit_label(&format!(".L{}", block.id));
+            let label = self.block_label(block.id);
+            self.emit_label(&label);
 
             for instr in &block.instructions {
                 self.emit_mir_instruction(instr);
@@ -763,8 +1033,29 @@ impl AsmEmitter for RiscV64Emitter {
     fn emit_epilogue(&mut self, func: &MirFunction) {
         self.emit_label(&format!(".L{}_epilogue", self.current_func));
 
-        let frame_size = 16 + func.locals.len() * 8;
-        let aligned_size = (frame_size + 15) & !15;
+        // An enum result is returned in a0:a1 from its local, `_0`, or
+        // copied to the address the caller passed
+        if let Some(layout) = func.return_type.enum_layout() {
+            let result = MirPlace {
+                local: 0,
+                projection: vec![],
+            };
+            let words = word_places(&result, layout.words());
+            if let Some(slot) = result_address_slot(func, 16) {
+                self.emit(&format!("ld t1, {}(s0)", slot));
+                for (i, place) in words.iter().enumerate() {
+                    self.load_operand(&MirOperand::Copy(place.clone()), RiscVReg::T0);
+                    self.emit(&format!("sd t0, {}(t1)", i * 8));
+                }
+            } else {
+                for (place, reg) in words.iter().zip([RiscVReg::A0, RiscVReg::A1]) {
+                    self.load_operand(&MirOperand::Copy(place.clone()), reg);
+                }
+            }
+        }
+
+        // The frame the prologue allocated, multi-slot locals included
+        let aligned_size = self.stack_offset;
 
         // Restore return address and frame pointer
         self.emit(&format!("ld ra, {}(sp)", aligned_size - 8));
@@ -785,10 +1076,14 @@ impl AsmEmitter for RiscV64Emitter {
         output.push_str("# Generated by jagc compiler\n");
         output.push_str(".text\n\n");
 
-        output.push_str(&self.instructions.join("\n"));
+        output.push_str(&self.get_body());
         output
     }
 
+    fn get_body(&self) -> String {
+        self.instructions.join("\n")
+    }
+
     fn get_machine_code(&self) -> Vec<u8> {
         Vec::new()
     }
@@ -814,9 +1109,7 @@ impl RiscV64Emitter {
                 self.store_to_place(RiscVReg::T0, dest);
             }
             MirInstruction::SetDiscriminant { place, variant } => {
-                self.emit_comment(&format!("Set discriminant to {}", variant));
-                self.emit(&format!("li t0, {}", variant));
-                self.store_to_place(RiscVReg::T0, place);
+                self.emit_set_discriminant(place, *variant);
             }
             MirInstruction::BoundsCheck {
                 index,
@@ -853,22 +1146,64 @@ impl RiscV64Emitter {
     }
 
     fn emit_rvalue(&mut self, rvalue: &MirRvalue, dest: &MirPlace) {
+        // Enum values span the words of their layout
+        if let Some(layout) = place_type(dest, &self.local_types).enum_layout() {
+            match rvalue {
+                MirRvalue::Use(MirOperand::Copy(src) | MirOperand::Move(src)) => {
+                    self.copy_words(src, dest, layout.words());
+                    return;
+                }
+                MirRvalue::Aggregate {
+                    kind: AggregateKind::Enum { variant, .. },
+                    operands,
+                } => {
+                    self.emit_enum(dest, *variant, operands);
+                    return;
+                }
+                _ => {}
+            }
+        }
         match rvalue {
             MirRvalue::Use(operand) => {
                 self.load_operand(operand, RiscVReg::T0);
                 self.store_to_place(RiscVReg::T0, dest);
             }
             MirRvalue::BinaryOp { op, left, right } => {
+                let size = operation_size(*op, left, right, dest, &self.local_types);
                 self.load_operand(left, RiscVReg::T1);
                 self.load_operand(right, RiscVReg::T2);
-                self.emit_binary_op(*op, RiscVReg::T0, RiscVReg::T1, RiscVReg::T2);
+                self.emit_binary_op(*op, size, RiscVReg::T0, RiscVReg::T1, RiscVReg::T2);
+                self.emit_overflow(*op, size);
                 self.store_to_place(RiscVReg::T0, dest);
             }
             MirRvalue::UnaryOp { op, operand } => {
-                self.load_operand(operand, RiscVReg::T0);
-                match op {
-                    UnaryOp::Not => self.emit("not t0, t0"),
-                    UnaryOp::Neg => self.emit("neg t0, t0"),
+                match (op, place_type(dest, &self.local_types)) {
+                    // Negation is subtraction from zero, and overflows as it
+                    (UnaryOp::Neg, MirType::Int(size)) => {
+                        self.load_operand(operand, RiscVReg::T2);
+                        self.emit("li t1, 0");
+                        self.emit_binary_op(
+                            BinaryOp::Sub,
+                            size,
+                            RiscVReg::T0,
+                            RiscVReg::T1,
+                            RiscVReg::T2,
+                        );
+                        self.emit_overflow(BinaryOp::Sub, size);
+                    }
+                    (UnaryOp::Neg, _) => {
+                        self.load_operand(operand, RiscVReg::T0);
+                        self.emit("neg t0, t0");
+                    }
+                    // The complement of a zero-extended value sets the bits
+                    // beyond its width
+                    (UnaryOp::Not, ty) => {
+                        self.load_operand(operand, RiscVReg::T0);
+                        self.emit("not t0, t0");
+                        if let MirType::Int(size) = ty {
+                            self.emit_extend(size, "t0");
+                        }
+                    }
                 }
                 self.store_to_place(RiscVReg::T0, dest);
             }
@@ -903,20 +1238,12 @@ impl RiscV64Emitter {
                 }
             }
             MirRvalue::Index { base, index } => {
+                // base is the address of a `{data, len}` header
                 self.emit_comment("Array index access");
-                let elem_size = 8;
-                match base {
-                    MirOperand::Copy(place) | MirOperand::Move(place) => {
-                        let base_offset = self.reg_alloc.get_local_offset(place.local).unwrap_or(0);
-                        self.emit(&format!("addi t1, s0, {}", base_offset));
-                    }
-                    _ => {
-                        self.load_operand(base, RiscVReg::T1);
-                    }
-                }
+                self.load_operand(base, RiscVReg::T1);
+                self.emit("ld t1, 0(t1)");
                 self.load_operand(index, RiscVReg::T2);
-                self.emit(&format!("li t3, {}", elem_size));
-                self.emit("mul t2, t2, t3");
+                self.emit("slli t2, t2, 3");
                 self.emit("add t1, t1, t2");
                 self.emit("ld t0, 0(t1)");
                 self.store_to_place(RiscVReg::T0, dest);
@@ -944,14 +1271,15 @@ impl RiscV64Emitter {
             MirRvalue::Cast {
                 kind: _,
                 operand,
-                ty: _,
+                ty,
             } => {
+                let from = operand_type(operand, &self.local_types);
                 self.load_operand(operand, RiscVReg::T0);
+                self.emit_cast(&from, ty);
                 self.store_to_place(RiscVReg::T0, dest);
             }
             MirRvalue::Discriminant(place) => {
-                let src = self.place_to_str(place);
-                self.emit(&format!("ld t0, {}", src));
+                self.load_discriminant(place);
                 self.store_to_place(RiscVReg::T0, dest);
             }
             MirRvalue::Len(place) => {
@@ -960,6 +1288,37 @@ impl RiscV64Emitter {
                 self.emit("ld t0, 8(t0)");
                 self.store_to_place(RiscVReg::T0, dest);
             }
+            MirRvalue::Aggregate {
+                kind: AggregateKind::Closure { function },
+                operands,
+            } => {
+                // Code address, then the captures, upwards from the local's base
+                self.emit_comment(&format!("Closure environment for {}", function));
+                let base = self.reg_alloc.get_local_offset(dest.local).unwrap_or(0);
+                self.emit(&format!("la t0, {}", function));
+                self.emit(&format!("sd t0, {}(s0)", base));
+                for (i, operand) in operands.iter().enumerate() {
+                    self.load_operand(operand, RiscVReg::T0);
+                    let offset = base + ((i + 1) * 8) as i64;
+                    self.emit(&format!("sd t0, {}(s0)", offset));
+                }
+            }
+            MirRvalue::Aggregate {
+                kind: AggregateKind::Array,
+                operands,
+            } => {
+                // `{data, len}` header, then the elements upwards from it
+                let base = self.reg_alloc.get_local_offset(dest.local).unwrap_or(0);
+                self.emit(&format!("addi t0, s0, {}", base + 16));
+                self.emit(&format!("sd t0, {}(s0)", base));
+                self.emit(&format!("li t0, {}", operands.len()));
+                self.emit(&format!("sd t0, {}(s0)", base + 8));
+                for (i, operand) in operands.iter().enumerate() {
+                    self.load_operand(operand, RiscVReg::T0);
+                    let offset = base + 16 + (i * 8) as i64;
+                    self.emit(&format!("sd t0, {}(s0)", offset));
+                }
+            }
             MirRvalue::Aggregate { kind: _, operands } => {
                 for (i, operand) in operands.iter().enumerate() {
                     self.load_operand(operand, RiscVReg::T0);
@@ -976,7 +1335,7 @@ impl RiscV64Emitter {
                 self.emit(&format!("j .L{}_epilogue", self.current_func));
             }
             MirTerminator::Goto { target } => {
-                self.emit(&format!("j .L{}", target));
+                self.emit(&format!("j {}", self.block_label(*target)));
             }
             MirTerminator::SwitchInt {
                 discriminant,
@@ -987,9 +1346,9 @@ impl RiscV64Emitter {
                 self.load_operand(discriminant, RiscVReg::T0);
                 for (value, target) in targets {
                     self.emit(&format!("li t1, {}", value));
-                    self.emit(&format!("beq t0, t1, .L{}", target));
+                    self.emit(&format!("beq t0, t1, {}", self.block_label(*target)));
                 }
-                self.emit(&format!("j .L{}", otherwise));
+                self.emit(&format!("j {}", self.block_label(*otherwise)));
             }
             MirTerminator::Call {
                 func,
@@ -998,11 +1357,24 @@ impl RiscV64Emitter {
                 target,
             } => {
                 self.emit_comment("Function call");
-                for (i, arg) in args.iter().enumerate().take(8) {
-                    if let Some(reg) = RiscVReg::arg_register(i) {
+                let args: Vec<MirOperand> = args
+                    .iter()
+                    .flat_map(|arg| operand_words(arg, &self.local_types))
+                    .collect();
+                // An in-memory result takes the first register for its address
+                let result_address = destination
+                    .as_ref()
+                    .filter(|dest| returns_in_memory(&place_type(dest, &self.local_types)));
+                let first = usize::from(result_address.is_some());
+                for (i, arg) in args.iter().enumerate().take(8 - first) {
+                    if let Some(reg) = RiscVReg::arg_register(first + i) {
                         self.load_operand(arg, reg);
                     }
                 }
+                if let Some(dest) = result_address {
+                    let offset = self.place_offset(dest);
+                    self.emit(&format!("addi a0, s0, {}", offset));
+                }
                 // Call function
                 match func {
                     MirOperand::Constant(MirConstant::String(name)) => {
@@ -1013,12 +1385,20 @@ impl RiscV64Emitter {
                         self.emit("jalr ra, t0, 0");
                     }
                 }
-                // Store return value
-                if let Some(dest) = destination {
-                    self.store_to_place(RiscVReg::A0, dest);
+                // Store return value, an enum's from a0:a1
+                if let Some(dest) = destination.as_ref().filter(|_| result_address.is_none()) {
+                    match place_type(dest, &self.local_types).enum_layout() {
+                        Some(layout) => {
+                            let words = word_places(dest, layout.words());
+                            for (place, reg) in words.iter().zip([RiscVReg::A0, RiscVReg::A1]) {
+                                self.store_to_place(reg, place);
+                            }
+                        }
+                        None => self.store_to_place(RiscVReg::A0, dest),
+                    }
                 }
                 // Continue to target block
-                self.emit(&format!("j .L{}", target));
+                self.emit(&format!("j {}", self.block_label(*target)));
             }
             MirTerminator::Unreachable => {
                 self.emit_comment("Unreachable code - trap");
@@ -14,12 +14,24 @@
 //! - Integer args: RDI, RSI, RDX, RCX, R8, R9
 //! - Float args: XMM0-XMM7
 //! - Return: RAX (int), XMM0 (float)
+//! - Enum values: one register per word of their layout, as arguments and
+//!   as results (RAX:RDX). A larger result is written to memory at an
+//!   address passed as a hidden first argument in RDI, and returned in RAX
 //! - Callee-saved: RBX, RBP, R12-R15
 
-use super::AsmEmitter;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use super::{
+    can_overflow, frame_layout, operand_type, operand_words, operation_size, param_words,
+    place_layout, place_type, result_address_slot, returns_in_memory, traps_on_overflow,
+    word_places, AsmEmitter,
+};
+use crate::mir::layout::TagEncoding;
 use crate::mir::types::{
-    BinaryOp, FloatBinaryOp, FloatCmp, MirConstant, MirFunction, MirInstruction, MirOperand,
-    MirPlace, MirRvalue, MirTerminator, PlaceProjection, RegisterClass, SimdOp, SimdWidth, UnaryOp,
+    AggregateKind, BinaryOp, FloatBinaryOp, FloatCmp, FloatSize, IntSize, MirConstant, MirFunction,
+    MirInstruction, MirOperand, MirPlace, MirRvalue, MirTerminator, MirType, Overflow,
+    PlaceProjection, RegisterClass, SimdOp, SimdWidth, UnaryOp,
 };
 
 /// x86-64 assembly emitter
@@ -34,6 +46,13 @@ pub struct X86_64Emitter {
     current_func: String,
     /// Label counter for unique labels
     label_counter: usize,
+    /// String constants of the current function, emitted to `.rodata`
+    /// after it as `.LS_<function>_<index>`
+    strings: RefCell<Vec<String>>,
+    /// Types of the current function's locals, which lay out enum values
+    local_types: HashMap<usize, MirType>,
+    /// What integer overflow does in the current function
+    overflow: Overflow,
 }
 
 /// x86-64 registers
@@ -518,6 +537,9 @@ impl X86_64Emitter {
             reg_alloc: X86RegAlloc::new(),
             current_func: String::new(),
             label_counter: 0,
+            strings: RefCell::new(Vec::new()),
+            local_types: HashMap::new(),
+            overflow: Overflow::Wrap,
         }
     }
 
@@ -537,9 +559,14 @@ impl X86_64Emitter {
         self.instructions.push(directive.to_string());
     }
 
+    /// Label of block `id` of the current function
+    fn block_label(&self, id: usize) -> String {
+        format!(".L{}_bb{}", self.current_func, id)
+    }
+
     /// Generate unique label
     fn new_label(&mut self, prefix: &str) -> String {
-        let label = format!(".L{}_{}", prefix, self.label_counter);
+        let label = format!(".L{}_{}_{}", prefix, self.current_func, self.label_counter);
         self.label_counter += 1;
         label
     }
@@ -556,18 +583,49 @@ impl X86_64Emitter {
     fn const_to_str(&self, constant: &MirConstant) -> String {
         match constant {
             MirConstant::Int(val, _) => format!("{}", val),
-            MirConstant::Float(val, _) => format!("{}", val),
+            // As its bit pattern, which moves through integer registers
+            MirConstant::Float(val, FloatSize::F32) => format!("{}", (*val as f32).to_bits()),
+            MirConstant::Float(val, FloatSize::F64) => format!("{}", val.to_bits()),
             MirConstant::Bool(b) => if *b { "1" } else { "0" }.to_string(),
             MirConstant::Unit => "0".to_string(),
-            MirConstant::String(s) => format!("OFFSET .LC_{}", s.len()), // Would need string pool
+            MirConstant::String(s) => {
+                let mut strings = self.strings.borrow_mut();
+                let index = match strings.iter().position(|pooled| pooled == s) {
+                    Some(index) => index,
+                    None => {
+                        strings.push(s.clone());
+                        strings.len() - 1
+                    }
+                };
+                format!("OFFSET .LS_{}_{}", self.current_func, index)
+            }
         }
     }
 
     /// Emit place to stack reference
+    ///
+    /// Field and constant index projections address the slots of a
+    /// multi-slot local, upwards from its base, and payload fields the
+    /// bytes their enum's layout gives them.
     fn place_to_str(&self, place: &MirPlace) -> String {
+        self.sized_place_to_str(place, 8)
+    }
+
+    /// Emit place to a stack reference `width` bytes wide
+    fn sized_place_to_str(&self, place: &MirPlace, width: usize) -> String {
+        let size = match width {
+            1 => "BYTE",
+            2 => "WORD",
+            4 => "DWORD",
+            _ => "QWORD",
+        };
         if let Some(offset) = self.reg_alloc.get_local_offset(place.local) {
+            let ty = self.local_types.get(&place.local);
+            let ty = ty.cloned().unwrap_or(MirType::Int(IntSize::I64));
+            let offset = offset + place_layout(place, &ty).0;
             format!(
-                "QWORD PTR [rbp{}]",
+                "{} PTR [rbp{}]",
+                size,
                 if offset >= 0 {
                     format!("+{}", offset)
                 } else {
@@ -575,7 +633,84 @@ impl X86_64Emitter {
                 }
             )
         } else {
-            format!("QWORD PTR [rbp-{}]", (place.local + 1) * 8)
+            format!("{} PTR [rbp-{}]", size, (place.local + 1) * 8)
+        }
+    }
+
+    /// Tag encoding of the enum value at `place`, if it is one
+    fn tag_encoding(&self, place: &MirPlace) -> Option<TagEncoding> {
+        let ty = place_type(place, &self.local_types);
+        ty.enum_layout().map(|layout| layout.tag.clone())
+    }
+
+    /// Record in the enum value at `place` that it holds `variant`
+    fn emit_set_discriminant(&mut self, place: &MirPlace, variant: usize) {
+        self.emit_comment(&format!("Set discriminant to {}", variant));
+        match self.tag_encoding(place) {
+            // A null reference stands for the empty variant
+            Some(TagEncoding::Niche { empty, .. }) if variant == empty => {
+                let dest = self.place_to_str(place);
+                self.emit(&format!("mov {}, 0", dest));
+            }
+            // The reference stored as the payload says it all
+            Some(TagEncoding::Niche { .. }) => {}
+            Some(TagEncoding::Direct { width }) => {
+                let dest = self.sized_place_to_str(place, width);
+                self.emit(&format!("mov {}, {}", dest, variant));
+            }
+            None => {
+                let dest = self.place_to_str(place);
+                self.emit(&format!("mov {}, {}", dest, variant));
+            }
+        }
+    }
+
+    /// Load the index of the variant the enum value at `place` holds
+    fn load_discriminant(&mut self, place: &MirPlace, reg: X86Reg) {
+        match self.tag_encoding(place) {
+            Some(TagEncoding::Niche { dataful, empty }) => {
+                let src = self.place_to_str(place);
+                self.emit(&format!("cmp {}, 0", src));
+                self.emit(&format!("mov {}, {}", reg.name(), empty));
+                self.emit(&format!("mov r11, {}", dataful));
+                self.emit(&format!("cmovne {}, r11", reg.name()));
+            }
+            // A 32-bit load zeroes the upper half itself
+            Some(TagEncoding::Direct { width: 4 }) => {
+                let src = self.sized_place_to_str(place, 4);
+                self.emit(&format!("mov {}, {}", reg.name32(), src));
+            }
+            Some(TagEncoding::Direct { width }) if width < 8 => {
+                let src = self.sized_place_to_str(place, width);
+                self.emit(&format!("movzx {}, {}", reg.name(), src));
+            }
+            _ => {
+                let src = self.place_to_str(place);
+                self.emit(&format!("mov {}, {}", reg.name(), src));
+            }
+        }
+    }
+
+    /// Copy the `words` words of the value at `src` to `dest`
+    fn copy_words(&mut self, src: &MirPlace, dest: &MirPlace, words: usize) {
+        for (from, to) in word_places(src, words).iter().zip(word_places(dest, words)) {
+            self.load_operand(&MirOperand::Copy(from.clone()), X86Reg::RAX);
+            self.store_to_place(X86Reg::RAX, &to);
+        }
+    }
+
+    /// Build the enum value of `variant` with payload `operands` at `dest`
+    fn emit_enum(&mut self, dest: &MirPlace, variant: usize, operands: &[MirOperand]) {
+        self.emit_set_discriminant(dest, variant);
+        for (index, operand) in operands.iter().enumerate() {
+            let mut field = dest.clone();
+            field.projection.push(PlaceProjection::Downcast { variant });
+            field.projection.push(PlaceProjection::Field { index });
+            let words = operand_words(operand, &self.local_types);
+            for (word, place) in words.iter().zip(word_places(&field, words.len())) {
+                self.load_operand(word, X86Reg::RAX);
+                self.store_to_place(X86Reg::RAX, &place);
+            }
         }
     }
 
@@ -599,8 +734,19 @@ impl X86_64Emitter {
         self.emit(&format!("mov {}, {}", dest, reg.name()));
     }
 
-    /// Emit binary operation
-    fn emit_binary_op(&mut self, op: BinaryOp, dest: X86Reg, left: X86Reg, right: X86Reg) {
+    /// Emit binary operation on `size` integers
+    ///
+    /// Unsigned integers divide, shift and compare with the unsigned
+    /// instructions; the rest are the same for both.
+    fn emit_binary_op(
+        &mut self,
+        op: BinaryOp,
+        size: IntSize,
+        dest: X86Reg,
+        left: X86Reg,
+        right: X86Reg,
+    ) {
+        let signed = size.is_signed();
         match op {
             BinaryOp::Add => {
                 if dest != left {
@@ -616,24 +762,31 @@ impl X86_64Emitter {
             }
             BinaryOp::Mul => {
                 self.emit(&format!("mov rax, {}", left.name()));
-                self.emit(&format!("imul {}", right.name()));
+                self.emit(&format!(
+                    "{} {}",
+                    if signed { "imul" } else { "mul" },
+                    right.name()
+                ));
                 if dest != X86Reg::RAX {
                     self.emit(&format!("mov {}, rax", dest.name()));
                 }
             }
-            BinaryOp::Div => {
+            BinaryOp::Div | BinaryOp::Rem => {
+                // The dividend takes RDX:RAX, so the divisor moves out of RDX
+                self.emit(&format!("mov r11, {}", right.name()));
                 self.emit(&format!("mov rax, {}", left.name()));
-                self.emit("cqo"); // Sign extend RAX into RDX:RAX
-                self.emit(&format!("idiv {}", right.name()));
-                if dest != X86Reg::RAX {
-                    self.emit(&format!("mov {}, rax", dest.name()));
+                if signed {
+                    self.emit("cqo"); // Sign extend RAX into RDX:RAX
+                    self.emit("idiv r11");
+                } else {
+                    self.emit("xor edx, edx");
+                    self.emit("div r11");
+                }
+                // Quotient in RAX, remainder in RDX
+                let result = if op == BinaryOp::Div { "rax" } else { "rdx" };
+                if dest.name() != result {
+                    self.emit(&format!("mov {}, {}", dest.name(), result));
                 }
-            }
-            BinaryOp::Rem => {
-                self.emit(&format!("mov rax, {}", left.name()));
-                self.emit("cqo");
-                self.emit(&format!("idiv {}", right.name()));
-                self.emit(&format!("mov {}, rdx", dest.name())); // Remainder in RDX
             }
             BinaryOp::BitAnd => {
                 if dest != left {
@@ -665,7 +818,12 @@ impl X86_64Emitter {
                     self.emit(&format!("mov {}, {}", dest.name(), left.name()));
                 }
                 self.emit(&format!("mov rcx, {}", right.name()));
-                self.emit(&format!("sar {}, cl", dest.name())); // Arithmetic shift
+                // Arithmetic shift for signed integers, logical for unsigned
+                self.emit(&format!(
+                    "{} {}, cl",
+                    if signed { "sar" } else { "shr" },
+                    dest.name()
+                ));
             }
             BinaryOp::Eq
             | BinaryOp::Ne
@@ -674,13 +832,17 @@ impl X86_64Emitter {
             | BinaryOp::Gt
             | BinaryOp::Ge => {
                 self.emit(&format!("cmp {}, {}", left.name(), right.name()));
-                let set_instr = match op {
-                    BinaryOp::Eq => "sete",
-                    BinaryOp::Ne => "setne",
-                    BinaryOp::Lt => "setl",
-                    BinaryOp::Le => "setle",
-                    BinaryOp::Gt => "setg",
-                    BinaryOp::Ge => "setge",
+                let set_instr = match (op, signed) {
+                    (BinaryOp::Eq, _) => "sete",
+                    (BinaryOp::Ne, _) => "setne",
+                    (BinaryOp::Lt, true) => "setl",
+                    (BinaryOp::Le, true) => "setle",
+                    (BinaryOp::Gt, true) => "setg",
+                    (BinaryOp::Ge, true) => "setge",
+                    (BinaryOp::Lt, false) => "setb",
+                    (BinaryOp::Le, false) => "setbe",
+                    (BinaryOp::Gt, false) => "seta",
+                    (BinaryOp::Ge, false) => "setae",
                     _ => unreachable!(),
                 };
                 self.emit(&format!("{} {}", set_instr, dest.name8()));
@@ -689,6 +851,92 @@ impl X86_64Emitter {
         }
     }
 
+    /// Sign- or zero-extend the low `size` bits of RAX into `dest`
+    fn emit_extend(&mut self, size: IntSize, dest: X86Reg) {
+        match size {
+            IntSize::I8 => self.emit(&format!("movsx {}, al", dest.name())),
+            IntSize::I16 => self.emit(&format!("movsx {}, ax", dest.name())),
+            IntSize::I32 => self.emit(&format!("movsxd {}, eax", dest.name())),
+            IntSize::U8 => self.emit(&format!("movzx {}, al", dest.name32())),
+            IntSize::U16 => self.emit(&format!("movzx {}, ax", dest.name32())),
+            // A 32-bit move zeroes the upper half
+            IntSize::U32 => self.emit(&format!("mov {}, eax", dest.name32())),
+            IntSize::I64 | IntSize::U64 => {
+                if dest != X86Reg::RAX {
+                    self.emit(&format!("mov {}, rax", dest.name()));
+                }
+            }
+        }
+    }
+
+    /// Bring the result `op` left in RAX back into `size`
+    ///
+    /// The result wraps to the width, or, in a function that traps on
+    /// overflow, must be unchanged by that. A 64-bit result has nothing
+    /// beyond its width, so the flags of the operation tell instead;
+    /// `idiv` faults on the one quotient that does not fit.
+    fn emit_overflow(&mut self, op: BinaryOp, size: IntSize) {
+        if !can_overflow(op, size) {
+            return;
+        }
+        if self.overflow == Overflow::Wrap || !traps_on_overflow(op, size) {
+            self.emit_extend(size, X86Reg::RAX);
+            return;
+        }
+        // `idiv` faults on the one 64-bit quotient that overflows
+        if size.bits() == 64 && op == BinaryOp::Div {
+            return;
+        }
+        let pass_label = self.new_label("no_overflow");
+        if size.bits() == 64 {
+            // `mul` and `imul` set OF, unsigned `add` and `sub` CF
+            let flag = if size.is_signed() || op == BinaryOp::Mul {
+                "jno"
+            } else {
+                "jnc"
+            };
+            self.emit(&format!("{} {}", flag, pass_label));
+        } else {
+            self.emit_extend(size, X86Reg::RCX);
+            self.emit("cmp rcx, rax");
+            self.emit(&format!("je {}", pass_label));
+        }
+        self.emit_comment("Integer overflow");
+        self.emit("ud2");
+        self.emit_label(&pass_label);
+    }
+
+    /// Convert the `from` value in RAX to `to`, in RAX
+    ///
+    /// An integer is truncated or extended to the target's width, as `as`
+    /// says; a float is truncated towards zero. Floats are held as their
+    /// bits, so they pass through XMM0.
+    fn emit_cast(&mut self, from: &MirType, to: &MirType) {
+        match (from, to) {
+            (MirType::Float(from), MirType::Float(to)) if from != to => {
+                self.emit("movq xmm0, rax");
+                match to {
+                    FloatSize::F64 => self.emit("cvtss2sd xmm0, xmm0"),
+                    FloatSize::F32 => self.emit("cvtsd2ss xmm0, xmm0"),
+                }
+                self.emit("movq rax, xmm0");
+            }
+            (MirType::Float(from), MirType::Int(size)) => {
+                self.emit("movq xmm0, rax");
+                let suffix = if *from == FloatSize::F64 { "sd" } else { "ss" };
+                self.emit(&format!("cvtt{}2si rax, xmm0", suffix));
+                self.emit_extend(*size, X86Reg::RAX);
+            }
+            (_, MirType::Float(to)) if !matches!(from, MirType::Float(_)) => {
+                let suffix = if *to == FloatSize::F64 { "sd" } else { "ss" };
+                self.emit(&format!("cvtsi2{} xmm0, rax", suffix));
+                self.emit("movq rax, xmm0");
+            }
+            (_, MirType::Int(size)) => self.emit_extend(*size, X86Reg::RAX),
+            _ => {}
+        }
+    }
+
     /// Emit floating-point binary operation (SSE)
     fn emit_float_binary_op(
         &mut self,
@@ -875,7 +1123,7 @@ impl AsmEmitter for X86_64Emitter {
         }
 
         // Calculate stack space for locals (aligned to 16 bytes)
-        let locals_space = func.locals.len() * 8;
+        let (offsets, locals_space) = frame_layout(func, 0);
         let aligned_space = (locals_space + 15) & !15;
         if aligned_space > 0 {
             self.emit(&format!("sub rsp, {}", aligned_space));
@@ -883,24 +1131,36 @@ impl AsmEmitter for X86_64Emitter {
         }
 
         // Assign stack offsets to locals
-        for (i, local) in func.locals.iter().enumerate() {
-            let offset = -(((i + 1) * 8) as i64);
-            self.reg_alloc.set_local_offset(local.index, offset);
+        for (local, offset) in offsets {
+            self.reg_alloc.set_local_offset(local, offset);
+        }
+        self.local_types = func
+            .locals
+            .iter()
+            .map(|local| (local.index, local.ty.clone()))
+            .collect();
+        self.overflow = func.overflow;
+
+        // Keep the address of an in-memory result, which comes before the arguments
+        let result_address = result_address_slot(func, 0);
+        if let Some(slot) = result_address {
+            self.emit(&format!("mov QWORD PTR [rbp{}], rdi", slot));
         }
 
-        // Move arguments from registers to stack
-        for (i, _param) in func.params.iter().enumerate() {
-            if let Some(reg) = X86Reg::arg_register(i) {
-                let offset = -(((func.locals.len() + i + 1) * 8) as i64);
+        // Move arguments from registers to their locals
+        let first = usize::from(result_address.is_some());
+        for (i, place) in param_words(func).iter().enumerate() {
+            if let Some(reg) = X86Reg::arg_register(first + i) {
                 self.emit_comment(&format!("Store arg {} from {}", i, reg.name()));
-                self.emit(&format!("mov QWORD PTR [rbp{}], {}", offset, reg.name()));
+                self.store_to_place(reg, place);
             }
         }
     }
 
     fn emit_body(&mut self, func: &MirFunction) {
         for block in &func.blocks {
-            self.emit_label(&format!(".L{}", block.id));
+            let label = self.block_label(block.id);
+            self.emit_label(&label);
 
             for instr in &block.instructions {
                 self.emit_mir_instruction(instr);
@@ -910,10 +1170,32 @@ impl AsmEmitter for X86_64Emitter {
         }
     }
 
-    fn emit_epilogue(&mut self, _func: &MirFunction) {
+    fn emit_epilogue(&mut self, func: &MirFunction) {
         // Emit epilogue label for multiple return points
         self.emit_label(&format!(".L{}_epilogue", self.current_func));
 
+        // An enum result is returned in RAX:RDX from its local, `_0`, or
+        // copied to the address the caller passed
+        if let Some(layout) = func.return_type.enum_layout() {
+            let result = MirPlace {
+                local: 0,
+                projection: vec![],
+            };
+            let words = word_places(&result, layout.words());
+            if let Some(slot) = result_address_slot(func, 0) {
+                self.emit(&format!("mov r11, QWORD PTR [rbp{}]", slot));
+                for (i, place) in words.iter().enumerate() {
+                    self.load_operand(&MirOperand::Copy(place.clone()), X86Reg::RAX);
+                    self.emit(&format!("mov QWORD PTR [r11+{}], rax", i * 8));
+                }
+                self.emit("mov rax, r11");
+            } else {
+                for (place, reg) in words.iter().zip([X86Reg::RAX, X86Reg::RDX]) {
+                    self.load_operand(&MirOperand::Copy(place.clone()), reg);
+                }
+            }
+        }
+
         // Restore stack
         if self.stack_offset > 0 {
             self.emit(&format!("add rsp, {}", self.stack_offset));
@@ -938,6 +1220,17 @@ impl AsmEmitter for X86_64Emitter {
         // ELF-specific .size directive (skip on Windows)
         #[cfg(not(target_os = "windows"))]
         self.emit_directive(&format!(".size {}, .-{}", func.name, func.name));
+
+        // String pool, as NUL-terminated data the runtime reads directly
+        let strings = self.strings.take();
+        if !strings.is_empty() {
+            self.emit_directive(".section .rodata");
+            for (index, s) in strings.iter().enumerate() {
+                self.emit_label(&format!(".LS_{}_{}", self.current_func, index));
+                self.emit(&format!(".asciz \"{}\"", escape_asciz(s)));
+            }
+            self.emit_directive(".text");
+        }
     }
 
     fn get_asm(&self) -> String {
@@ -950,11 +1243,15 @@ impl AsmEmitter for X86_64Emitter {
         output.push_str(".text\n\n");
 
         // Instructions
-        output.push_str(&self.instructions.join("\n"));
+        output.push_str(&self.get_body());
 
         output
     }
 
+    fn get_body(&self) -> String {
+        self.instructions.join("\n")
+    }
+
     fn get_machine_code(&self) -> Vec<u8> {
         // Would use an assembler like NASM or integrate with Cranelift
         Vec::new()
@@ -989,10 +1286,7 @@ impl X86_64Emitter {
                 self.store_to_place(X86Reg::RAX, dest);
             }
             MirInstruction::SetDiscriminant { place, variant } => {
-                // Set enum discriminant (first field of enum)
-                self.emit_comment(&format!("Set discriminant to {}", variant));
-                let dest = self.place_to_str(place);
-                self.emit(&format!("mov QWORD PTR {}, {}", dest, variant));
+                self.emit_set_discriminant(place, *variant);
             }
             MirInstruction::BoundsCheck {
                 index,
@@ -1043,22 +1337,60 @@ impl X86_64Emitter {
     }
 
     fn emit_rvalue(&mut self, rvalue: &MirRvalue, dest: &MirPlace) {
+        // Enum values span the words of their layout
+        if let Some(layout) = place_type(dest, &self.local_types).enum_layout() {
+            match rvalue {
+                MirRvalue::Use(MirOperand::Copy(src) | MirOperand::Move(src)) => {
+                    self.copy_words(src, dest, layout.words());
+                    return;
+                }
+                MirRvalue::Aggregate {
+                    kind: AggregateKind::Enum { variant, .. },
+                    operands,
+                } => {
+                    self.emit_enum(dest, *variant, operands);
+                    return;
+                }
+                _ => {}
+            }
+        }
         match rvalue {
             MirRvalue::Use(operand) => {
                 self.load_operand(operand, X86Reg::RAX);
                 self.store_to_place(X86Reg::RAX, dest);
             }
             MirRvalue::BinaryOp { op, left, right } => {
+                let size = operation_size(*op, left, right, dest, &self.local_types);
                 self.load_operand(left, X86Reg::RCX);
                 self.load_operand(right, X86Reg::RDX);
-                self.emit_binary_op(*op, X86Reg::RAX, X86Reg::RCX, X86Reg::RDX);
+                self.emit_binary_op(*op, size, X86Reg::RAX, X86Reg::RCX, X86Reg::RDX);
+                self.emit_overflow(*op, size);
                 self.store_to_place(X86Reg::RAX, dest);
             }
             MirRvalue::UnaryOp { op, operand } => {
                 self.load_operand(operand, X86Reg::RAX);
-                match op {
-                    UnaryOp::Not => self.emit("not rax"),
-                    UnaryOp::Neg => self.emit("neg rax"),
+                match (op, place_type(dest, &self.local_types)) {
+                    // Negation is subtraction from zero, and overflows as it
+                    (UnaryOp::Neg, MirType::Int(size)) => {
+                        self.emit("mov rdx, rax");
+                        self.emit("xor eax, eax");
+                        self.emit_binary_op(
+                            BinaryOp::Sub,
+                            size,
+                            X86Reg::RAX,
+                            X86Reg::RAX,
+                            X86Reg::RDX,
+                        );
+                        self.emit_overflow(BinaryOp::Sub, size);
+                    }
+                    (UnaryOp::Neg, _) => self.emit("neg rax"),
+                    // The complement of a zero-extended value sets the bits
+                    // beyond its width
+                    (UnaryOp::Not, MirType::Int(size)) => {
+                        self.emit("not rax");
+                        self.emit_extend(size, X86Reg::RAX);
+                    }
+                    (UnaryOp::Not, _) => self.emit("not rax"),
                 }
                 self.store_to_place(X86Reg::RAX, dest);
             }
@@ -1119,30 +1451,13 @@ impl X86_64Emitter {
                 }
             }
             MirRvalue::Index { base, index } => {
-                // Array/slice indexing
+                // Array/slice indexing: base is the address of a
+                // `{data, len}` header
                 self.emit_comment("Array index access");
-                let elem_size = 8; // Assume 8-byte elements
-
-                // Load base address
-                match base {
-                    MirOperand::Copy(place) | MirOperand::Move(place) => {
-                        let base_str = self.place_to_str(place);
-                        self.emit(&format!("lea rcx, {}", base_str));
-                    }
-                    _ => {
-                        self.load_operand(base, X86Reg::RCX);
-                    }
-                }
-
-                // Load index and compute offset
+                self.load_operand(base, X86Reg::RCX);
+                self.emit("mov rcx, QWORD PTR [rcx]");
                 self.load_operand(index, X86Reg::RDX);
-                self.emit(&format!("imul rdx, {}", elem_size));
-
-                // Add base + index*size
-                self.emit("add rcx, rdx");
-
-                // Load the element
-                self.emit("mov rax, QWORD PTR [rcx]");
+                self.emit("mov rax, QWORD PTR [rcx+rdx*8]");
                 self.store_to_place(X86Reg::RAX, dest);
             }
             MirRvalue::FloatOp { op, left, right } => {
@@ -1167,16 +1482,15 @@ impl X86_64Emitter {
             MirRvalue::Cast {
                 kind: _,
                 operand,
-                ty: _,
+                ty,
             } => {
-                // For now, just copy (proper casting would need type info)
+                let from = operand_type(operand, &self.local_types);
                 self.load_operand(operand, X86Reg::RAX);
+                self.emit_cast(&from, ty);
                 self.store_to_place(X86Reg::RAX, dest);
             }
             MirRvalue::Discriminant(place) => {
-                // Load discriminant (first field of enum)
-                let src = self.place_to_str(place);
-                self.emit(&format!("mov rax, {}", src));
+                self.load_discriminant(place, X86Reg::RAX);
                 self.store_to_place(X86Reg::RAX, dest);
             }
             MirRvalue::Len(place) => {
@@ -1186,6 +1500,40 @@ impl X86_64Emitter {
                 self.emit("mov rax, QWORD PTR [rax+8]"); // Length at offset 8
                 self.store_to_place(X86Reg::RAX, dest);
             }
+            MirRvalue::Aggregate {
+                kind: AggregateKind::Closure { function },
+                operands,
+            } => {
+                // Code address, then the captures, upwards from the local's base
+                self.emit_comment(&format!("Closure environment for {}", function));
+                let base = self.reg_alloc.get_local_offset(dest.local).unwrap_or(0);
+                self.emit(&format!("lea rax, [rip + {}]", function));
+                self.emit(&format!("mov QWORD PTR [rbp{:+}], rax", base));
+                for (i, operand) in operands.iter().enumerate() {
+                    self.load_operand(operand, X86Reg::RAX);
+                    let offset = base + ((i + 1) * 8) as i64;
+                    self.emit(&format!("mov QWORD PTR [rbp{:+}], rax", offset));
+                }
+            }
+            MirRvalue::Aggregate {
+                kind: AggregateKind::Array,
+                operands,
+            } => {
+                // `{data, len}` header, then the elements upwards from it
+                let base = self.reg_alloc.get_local_offset(dest.local).unwrap_or(0);
+                self.emit(&format!("lea rax, [rbp{:+}]", base + 16));
+                self.emit(&format!("mov QWORD PTR [rbp{:+}], rax", base));
+                self.emit(&format!(
+                    "mov QWORD PTR [rbp{:+}], {}",
+                    base + 8,
+                    operands.len()
+                ));
+                for (i, operand) in operands.iter().enumerate() {
+                    self.load_operand(operand, X86Reg::RAX);
+                    let offset = base + 16 + (i * 8) as i64;
+                    self.emit(&format!("mov QWORD PTR [rbp{:+}], rax", offset));
+                }
+            }
             MirRvalue::Aggregate { kind: _, operands } => {
                 // Store each operand at consecutive offsets
                 for (i, operand) in operands.iter().enumerate() {
@@ -1204,7 +1552,7 @@ impl X86_64Emitter {
                 self.emit(&format!("jmp .L{}_epilogue", self.current_func));
             }
             MirTerminator::Goto { target } => {
-                self.emit(&format!("jmp .L{}", target));
+                self.emit(&format!("jmp {}", self.block_label(*target)));
             }
             MirTerminator::SwitchInt {
                 discriminant,
@@ -1215,9 +1563,9 @@ impl X86_64Emitter {
                 self.load_operand(discriminant, X86Reg::RAX);
                 for (value, target) in targets {
                     self.emit(&format!("cmp rax, {}", value));
-                    self.emit(&format!("je .L{}", target));
+                    self.emit(&format!("je {}", self.block_label(*target)));
                 }
-                self.emit(&format!("jmp .L{}", otherwise));
+                self.emit(&format!("jmp {}", self.block_label(*otherwise)));
             }
             MirTerminator::Call {
                 func,
@@ -1226,9 +1574,18 @@ impl X86_64Emitter {
                 target,
             } => {
                 self.emit_comment("Function call");
+                let args: Vec<MirOperand> = args
+                    .iter()
+                    .flat_map(|arg| operand_words(arg, &self.local_types))
+                    .collect();
+                // An in-memory result takes the first register for its address
+                let result_address = destination
+                    .as_ref()
+                    .filter(|dest| returns_in_memory(&place_type(dest, &self.local_types)));
+                let first = usize::from(result_address.is_some());
                 // Load arguments into registers
                 for (i, arg) in args.iter().enumerate() {
-                    if let Some(reg) = X86Reg::arg_register(i) {
+                    if let Some(reg) = X86Reg::arg_register(first + i) {
                         self.load_operand(arg, reg);
                     } else {
                         // Push to stack (in reverse order)
@@ -1236,6 +1593,10 @@ impl X86_64Emitter {
                         self.emit("push rax");
                     }
                 }
+                if let Some(dest) = result_address {
+                    let dest = self.place_to_str(dest);
+                    self.emit(&format!("lea rdi, {}", dest));
+                }
                 // Call function
                 match func {
                     MirOperand::Constant(MirConstant::String(name)) => {
@@ -1246,12 +1607,20 @@ impl X86_64Emitter {
                         self.emit("call rax");
                     }
                 }
-                // Store return value
-                if let Some(dest) = destination {
-                    self.store_to_place(X86Reg::RAX, dest);
+                // Store return value, an enum's from RAX:RDX
+                if let Some(dest) = destination.as_ref().filter(|_| result_address.is_none()) {
+                    match place_type(dest, &self.local_types).enum_layout() {
+                        Some(layout) => {
+                            let words = word_places(dest, layout.words());
+                            for (place, reg) in words.iter().zip([X86Reg::RAX, X86Reg::RDX]) {
+                                self.store_to_place(reg, place);
+                            }
+                        }
+                        None => self.store_to_place(X86Reg::RAX, dest),
+                    }
                 }
                 // Continue to target block
-                self.emit(&format!("jmp .L{}", target));
+                self.emit(&format!("jmp {}", self.block_label(*target)));
             }
             MirTerminator::Unreachable => {
                 self.emit_comment("Unreachable code");
@@ -1266,6 +1635,21 @@ impl X86_64Emitter {
     }
 }
 
+/// Escape a string for an `.asciz` directive; bytes outside printable
+/// ASCII are written as octal escapes, so UTF-8 text survives unchanged
+fn escape_asciz(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for &byte in s.as_bytes() {
+        match byte {
+            b'"' => escaped.push_str("\\\""),
+            b'\\' => escaped.push_str("\\\\"),
+            0x20..=0x7e => escaped.push(byte as char),
+            _ => escaped.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+    escaped
+}
+
 impl Default for X86_64Emitter {
     fn default() -> Self {
         Self::new()
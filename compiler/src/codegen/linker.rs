@@ -382,6 +382,8 @@ pub struct BuildPipeline {
     platform: Platform,
     /// Whether to use C runtime
     use_crt: bool,
+    /// Static archive of the Jagannath runtime (`jag_*` routines)
+    runtime: Option<PathBuf>,
 }
 
 impl BuildPipeline {
@@ -399,6 +401,7 @@ impl BuildPipeline {
             linker: Linker::gcc(),
             platform,
             use_crt: true,
+            runtime: find_runtime(),
         }
     }
 
@@ -417,6 +420,7 @@ impl BuildPipeline {
             linker: Linker::clang(),
             platform,
             use_crt: true,
+            runtime: find_runtime(),
         }
     }
 
@@ -424,15 +428,65 @@ impl BuildPipeline {
     pub fn bare() -> Self {
         let mut pipeline = Self::new();
         pipeline.use_crt = false;
+        pipeline.runtime = None;
         pipeline
     }
 
+    /// Link `path` as the runtime archive instead of the one found
+    pub fn with_runtime(mut self, path: &Path) -> Self {
+        self.runtime = Some(path.to_path_buf());
+        self
+    }
+
+    /// Linker for the object of a program whose assembly is `user_asm`,
+    /// with the runtime it calls into
+    fn executable_linker(&self, obj_path: &Path, user_asm: &str) -> Result<Linker, BuildError> {
+        let mut linker = if self.use_crt {
+            Linker::gcc()
+        } else {
+            let mut l = Linker::new();
+            l.add_flag("-nostdlib");
+            l.add_flag("-static");
+            l
+        };
+
+        linker.add_object(obj_path);
+
+        match &self.runtime {
+            Some(runtime) => {
+                // Archives after the objects that need them
+                linker.add_object(runtime);
+                for lib in RUNTIME_LIBRARIES {
+                    linker.add_library(lib);
+                }
+            }
+            None if user_asm.contains("jag_") => {
+                return Err(BuildError::LinkFailed(format!(
+                    "the program calls into the Jagannath runtime, but {} was not found; \
+                     build it with `cargo build -p jagannath-runtime` or set {}",
+                    RUNTIME_ARCHIVE, RUNTIME_ENV
+                )));
+            }
+            None => {}
+        }
+
+        // On Windows with MinGW, GCC automatically links the C runtime
+        // On Linux/Unix, we need to explicitly request libc
+        #[cfg(not(target_os = "windows"))]
+        if self.use_crt {
+            linker.add_library("c"); // C runtime (Linux/Unix)
+        }
+
+        Ok(linker)
+    }
+
     /// Build assembly source to executable with runtime entry
     pub fn build_executable(&self, asm_path: &Path, exe_path: &Path) -> Result<(), BuildError> {
         // Create temporary directory for build artifacts
         let temp_dir = std::env::temp_dir().join("jagannath_build");
         std::fs::create_dir_all(&temp_dir)
             .map_err(|e| BuildError::AssemblyFailed(format!("Failed to create temp dir: {}", e)))?;
+        let prefix = artifact_prefix(exe_path);
 
         // Generate runtime entry point
         let entry = RuntimeEntry {
@@ -443,7 +497,7 @@ impl BuildPipeline {
         let entry_asm = entry.generate();
 
         // Write entry point to temp file
-        let entry_path = temp_dir.join("_entry.s");
+        let entry_path = temp_dir.join(format!("{}_entry.s", prefix));
         std::fs::write(&entry_path, &entry_asm)
             .map_err(|e| BuildError::AssemblyFailed(format!("Failed to write entry: {}", e)))?;
 
@@ -452,36 +506,18 @@ impl BuildPipeline {
             .map_err(|e| BuildError::AssemblyFailed(format!("Failed to read user asm: {}", e)))?;
 
         let combined_asm = format!("{}\n\n{}", entry_asm, user_asm);
-        let combined_path = temp_dir.join("combined.s");
+        let combined_path = temp_dir.join(format!("{}_combined.s", prefix));
         std::fs::write(&combined_path, &combined_asm)
             .map_err(|e| BuildError::AssemblyFailed(format!("Failed to write combined: {}", e)))?;
 
         // Assemble combined file
-        let obj_path = temp_dir.join("combined.o");
+        let obj_path = temp_dir.join(format!("{}_combined.o", prefix));
         self.assembler
             .assemble(&combined_path, &obj_path)
             .map_err(|e| BuildError::AssemblyFailed(format!("{:?}", e)))?;
 
         // Link to executable
-        let mut linker = if self.use_crt {
-            Linker::gcc()
-        } else {
-            let mut l = Linker::new();
-            l.add_flag("-nostdlib");
-            l.add_flag("-static");
-            l
-        };
-
-        linker.add_object(&obj_path);
-
-        // On Windows with MinGW, GCC automatically links the C runtime
-        // On Linux/Unix, we need to explicitly request libc
-        #[cfg(not(target_os = "windows"))]
-        if self.use_crt {
-            linker.add_library("c"); // C runtime (Linux/Unix)
-        }
-
-        linker
+        self.executable_linker(&obj_path, &user_asm)?
             .link(exe_path, LinkOutput::Executable)
             .map_err(|e| BuildError::LinkFailed(format!("{:?}", e)))?;
 
@@ -526,8 +562,9 @@ impl BuildPipeline {
         let user_asm = std::fs::read_to_string(asm_path)
             .map_err(|e| BuildError::AssemblyFailed(format!("Failed to read user asm: {}", e)))?;
 
+        let prefix = artifact_prefix(exe_path);
         let combined_asm = format!("{}\n\n{}", entry_asm, user_asm);
-        let combined_path = temp_dir.join("combined.s");
+        let combined_path = temp_dir.join(format!("{}_combined.s", prefix));
         std::fs::write(&combined_path, &combined_asm)
             .map_err(|e| BuildError::AssemblyFailed(format!("Failed to write combined: {}", e)))?;
 
@@ -535,7 +572,7 @@ impl BuildPipeline {
 
         // Assemble combined file
         let asm_start = std::time::Instant::now();
-        let obj_path = temp_dir.join("combined.o");
+        let obj_path = temp_dir.join(format!("{}_combined.o", prefix));
         self.assembler
             .assemble(&combined_path, &obj_path)
             .map_err(|e| BuildError::AssemblyFailed(format!("{:?}", e)))?;
@@ -543,21 +580,7 @@ impl BuildPipeline {
 
         // Link to executable
         let link_start = std::time::Instant::now();
-        let mut linker = if self.use_crt {
-            Linker::gcc()
-        } else {
-            let mut l = Linker::new();
-            l.add_flag("-nostdlib");
-            l.add_flag("-static");
-            l
-        };
-
-        linker.add_object(&obj_path);
-        if self.use_crt {
-            linker.add_library("c");
-        }
-
-        linker
+        self.executable_linker(&obj_path, &user_asm)?
             .link(exe_path, LinkOutput::Executable)
             .map_err(|e| BuildError::LinkFailed(format!("{:?}", e)))?;
         let link_time = link_start.elapsed();
@@ -576,6 +599,56 @@ impl BuildPipeline {
     }
 }
 
+/// File name of the runtime's static archive
+#[cfg(target_env = "msvc")]
+pub const RUNTIME_ARCHIVE: &str = "jagannath_runtime.lib";
+/// File name of the runtime's static archive
+#[cfg(not(target_env = "msvc"))]
+pub const RUNTIME_ARCHIVE: &str = "libjagannath_runtime.a";
+
+/// Environment variable naming the runtime archive to link
+pub const RUNTIME_ENV: &str = "JAGANNATH_RUNTIME";
+
+/// System libraries the runtime's standard library needs
+#[cfg(target_os = "linux")]
+const RUNTIME_LIBRARIES: &[&str] = &["gcc_s", "util", "rt", "pthread", "m", "dl"];
+#[cfg(target_os = "macos")]
+const RUNTIME_LIBRARIES: &[&str] = &["System", "m"];
+#[cfg(target_os = "windows")]
+const RUNTIME_LIBRARIES: &[&str] = &[
+    "kernel32", "advapi32", "ws2_32", "userenv", "ntdll", "bcrypt",
+];
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+const RUNTIME_LIBRARIES: &[&str] = &["pthread", "m"];
+
+/// The runtime archive: the one `JAGANNATH_RUNTIME` names, else the one
+/// built beside the compiler, as Cargo puts `jagc` and the archive in one
+/// directory (and test binaries a level below it, in `deps/`)
+pub fn find_runtime() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(RUNTIME_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    let exe = std::env::current_exe().ok()?;
+    exe.ancestors()
+        .skip(1)
+        .take(2)
+        .map(|dir| dir.join(RUNTIME_ARCHIVE))
+        .find(|path| path.is_file())
+}
+
+/// Prefix for the temporary files of a build of `exe_path`, so builds
+/// running at once do not share them
+fn artifact_prefix(exe_path: &Path) -> String {
+    format!(
+        "{}_{}",
+        exe_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        std::process::id()
+    )
+}
+
 /// Build information
 #[derive(Debug)]
 pub struct BuildInfo {
@@ -814,6 +814,9 @@ mod tests {
             blocks: vec![],
             locals: vec![],
             karaka_hints: HashMap::new(),
+            inline: Default::default(),
+            overflow: Default::default(),
+            spans: HashMap::new(),
         };
 
         let result = alloc.allocate(&mut func);
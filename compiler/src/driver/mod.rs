@@ -64,8 +64,12 @@ pub struct SourceLocation {
 #[derive(Debug, Default)]
 pub struct CompileTiming {
     pub lexing_us: u64,
+    pub macro_expansion_us: u64,
     pub parsing_us: u64,
+    pub module_loading_us: u64,
     pub type_checking_us: u64,
+    pub monomorphization_us: u64,
+    pub borrow_checking_us: u64,
     pub mir_building_us: u64,
     pub optimization_us: u64,
     pub codegen_us: u64,
@@ -2,6 +2,11 @@
 
 use crate::codegen::asm::Target;
 use crate::philosophy::guna::Guna;
+use crate::semantics::CfgContext;
+use std::path::Path;
+
+/// Package manifest read for build settings
+pub const MANIFEST_FILE: &str = "Jagannath.toml";
 
 /// Compiler options
 #[derive(Debug, Clone)]
@@ -32,8 +37,18 @@ pub struct CompilerOptions {
     pub deterministic: bool,
     /// Emit assembly only (no linking)
     pub emit_asm: bool,
-    /// Enable Nava Durga security analysis (9 goddess protection layers)
+    /// Check that `-guhya` data never flows to a public place
     pub security_check: bool,
+    /// Prove `pūrva`/`paścāt` contracts at compile time where linear
+    /// arithmetic can, warning of the rest; Sattva builds only, as proofs
+    /// need overflow to trap
+    pub prove_contracts: bool,
+    /// Build directory for the incremental cache (`lakṣya/`), if persisted
+    pub incremental_dir: Option<String>,
+    /// Build with `#[parīkṣā]` test functions (`cfg(parīkṣā)` holds)
+    pub test: bool,
+    /// Enabled features, for `cfg(viśeṣatā = "...")`
+    pub features: Vec<String>,
 }
 
 impl CompilerOptions {
@@ -52,7 +67,11 @@ impl CompilerOptions {
             verbose: false,
             deterministic: true,
             emit_asm: false,
-            security_check: true, // Enabled by default - Nava Durga always protects
+            security_check: true, // Enabled by default
+            prove_contracts: false,
+            incremental_dir: None,
+            test: false,
+            features: Vec::new(),
         }
     }
 
@@ -87,6 +106,12 @@ impl CompilerOptions {
         }
     }
 
+    /// Whether contracts are checked at run time: in debug and Sattva
+    /// builds, while release builds drop them
+    pub fn checks_contracts(&self) -> bool {
+        self.debug_info || self.guna == Guna::Sattva
+    }
+
     /// Parse from command line arguments
     pub fn from_args(args: &[String]) -> Result<Self, String> {
         let mut options = Self::new();
@@ -105,6 +130,9 @@ impl CompilerOptions {
                 "--emit-asm" | "-S" => options.emit_asm = true,
                 "--security" | "--durga" => options.security_check = true,
                 "--no-security" => options.security_check = false,
+                "--prove-contracts" => options.prove_contracts = true,
+                "--test" => options.test = true,
+                "--incremental" => options.incremental_dir = Some("lakṣya".to_string()),
                 "--sattva" => options.guna = Guna::Sattva,
                 "--rajas" => options.guna = Guna::Rajas,
                 "--tamas" => options.guna = Guna::Tamas,
@@ -136,6 +164,19 @@ impl CompilerOptions {
                     }
                     options.libraries.push(args[i].clone());
                 }
+                "--features" => {
+                    i += 1;
+                    if i >= args.len() {
+                        return Err("Missing features".to_string());
+                    }
+                    options.features.extend(
+                        args[i]
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|f| !f.is_empty())
+                            .map(String::from),
+                    );
+                }
                 "--target" => {
                     i += 1;
                     if i >= args.len() {
@@ -168,6 +209,50 @@ impl CompilerOptions {
 
         Ok(options)
     }
+
+    /// Settings `cfg` predicates are evaluated against
+    pub fn cfg_context(&self) -> CfgContext {
+        CfgContext {
+            target: self.target,
+            opt_level: self.opt_level,
+            guna: self.guna,
+            features: self.features.clone(),
+            test: self.test,
+        }
+    }
+
+    /// Enable the features listed in the package manifest
+    ///
+    /// `Jagannath.toml` is looked up from the directory of `input` upwards;
+    /// its `[nirmaṇa]` table may list `viśeṣatā = ["simd", ...]`. Without a
+    /// manifest nothing changes.
+    pub fn add_manifest_features(&mut self, input: &Path) -> Result<(), String> {
+        let Some(manifest) = input
+            .ancestors()
+            .skip(1)
+            .map(|dir| dir.join(MANIFEST_FILE))
+            .find(|path| path.is_file())
+        else {
+            return Ok(());
+        };
+        let text = std::fs::read_to_string(&manifest)
+            .map_err(|e| format!("Failed to read {}: {}", manifest.display(), e))?;
+        let value: toml::Value =
+            toml::from_str(&text).map_err(|e| format!("Invalid {}: {}", manifest.display(), e))?;
+        let features = value
+            .get("nirmaṇa")
+            .and_then(|build| build.get("viśeṣatā"))
+            .and_then(|features| features.as_array());
+        for feature in features.into_iter().flatten() {
+            let feature = feature
+                .as_str()
+                .ok_or_else(|| format!("{}: `viśeṣatā` must list strings", manifest.display()))?;
+            if !self.features.iter().any(|f| f == feature) {
+                self.features.push(feature.to_string());
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Default for CompilerOptions {
@@ -3,12 +3,16 @@
 //! Saṃkalana Satra (Compilation Session) - orchestrates the complete
 //! compilation pipeline from source to executable.
 
-use super::{CompileError, CompileResult, CompileTiming, CompilerOptions};
-use crate::codegen::asm::AsmEmitter;
+use super::{CompileError, CompileResult, CompileTiming, CompileWarning, CompilerOptions};
 use crate::codegen::linker::BuildPipeline;
+use crate::lexer::Span;
+use crate::modules::{CrateScope, ModuleContext, ModuleError, ModuleId, ScopeError};
 use crate::philosophy::kala::Kala;
 use crate::philosophy::samkhya::SamkhyaPipeline;
-use std::path::PathBuf;
+use crate::queries::{CompilerDb, PhaseStats, QueryError};
+use crate::r#macro::MacroExpander;
+use crate::semantics::{AttributeError, CfgContext};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
 /// Compiler session state
@@ -23,6 +27,12 @@ pub struct CompilerSession {
     timing: CompileTiming,
     /// Input file path (for deriving output path)
     input_path: Option<PathBuf>,
+    /// Mantra expander, kept for expansion backtraces in later stages
+    macros: MacroExpander,
+    /// Phase queries, reused across compilations of this session
+    db: CompilerDb,
+    /// Lint warnings of the current compilation
+    warnings: Vec<CompileWarning>,
 }
 
 impl CompilerSession {
@@ -35,49 +45,121 @@ impl CompilerSession {
         // Extract input path if available
         let input_path = options.inputs.first().map(|s| PathBuf::from(s));
 
+        let db = match &options.incremental_dir {
+            Some(dir) => CompilerDb::persistent(Path::new(dir)),
+            None => CompilerDb::new(),
+        };
+
         Self {
             options,
             pipeline: SamkhyaPipeline::new(),
             kala: Kala::new(time_budget),
             timing: CompileTiming::default(),
             input_path,
+            macros: MacroExpander::new(),
+            db,
+            warnings: Vec::new(),
         }
     }
 
+    /// Work done and reused by the phase queries of this session
+    pub fn phase_stats(&self) -> PhaseStats {
+        self.db.stats()
+    }
+
     /// Compile source code
     pub fn compile(&mut self, source: &str) -> Result<CompileResult, CompileError> {
         self.kala.start();
         let start = Instant::now();
+        self.warnings.clear();
+
+        // Phases below are queries; only work whose inputs changed is redone
+        let file = self.root_file().display().to_string();
+        self.db.set_source(&file, source);
 
         // Stage 1: Lexing (Śrotra - hearing)
         let lexing_timer = self.kala.begin_phase("lexing");
-        let tokens = self.lex(source)?;
+        self.lex(&file)?;
         self.kala.end_phase(lexing_timer);
 
+        // Stage 1.5: Macro Expansion (Vistāra - elaboration)
+        let expansion_timer = self.kala.begin_phase("macro_expansion");
+        self.expand_macros(&file)?;
+        self.kala.end_phase(expansion_timer);
+
         // Stage 2: Parsing (Tvak - touch)
         let parsing_timer = self.kala.begin_phase("parsing");
-        let ast = self.parse(&tokens)?;
+        let ast = self.parse(&file)?;
         self.kala.end_phase(parsing_timer);
 
-        // Stage 3: Type Checking (Rasana - taste)
+        // Stage 2.2: Attributes (Cihna) - strip what `cfg` rules out
+        let ast = self.configure(ast)?;
+
+        // Stage 2.5: Module Loading (Khaṇḍa Saṅgraha - gathering the parts)
+        let module_timer = self.kala.begin_phase("module_loading");
+        let (mut modules, order, scope) = self.load_modules(ast)?;
+        self.kala.end_phase(module_timer);
+
+        // Stage 2.7: Constant Evaluation (Sthira Gaṇanā), so types see the
+        // array lengths it computes
+        let const_timer = self.kala.begin_phase("const_evaluation");
+        for &id in &order {
+            self.evaluate_constants(&mut modules, &scope, id)?;
+        }
+        self.kala.end_phase(const_timer);
+
+        // Stage 3: Type Checking (Rasana - taste), dependencies first
         let typeck_timer = self.kala.begin_phase("type_checking");
-        self.type_check(&ast)?;
+        for &id in &order {
+            self.type_check_module(&modules, &scope, id)?;
+        }
         self.kala.end_phase(typeck_timer);
 
-        // Stage 3.5: Security Analysis via Nava Durga (9 Goddess Layers)
-        // "Sarva Maṅgala Māṅgalye Śive Sarvārtha Sādhike"
-        // - May the auspicious one protect all our code
+        // One program for the back end
+        let ast = scope
+            .link(&modules, &order)
+            .map_err(|errors| Self::module_error(&modules, &errors))?;
+
+        // Lint levels, deprecation and `#[yama(...)]` vows
+        self.lint(&ast)?;
+
+        // Stage 3.2: Monomorphization (Viśeṣa-karaṇa - particularization)
+        let mono_timer = self.kala.begin_phase("monomorphization");
+        let mut ast = self.monomorphize(&ast)?;
+        self.kala.end_phase(mono_timer);
+
+        // Array lengths computed from the const parameters just substituted
+        let errors = crate::mir::const_eval::evaluate_lengths(&mut ast);
+        if !errors.is_empty() {
+            return Err(Self::const_eval_error(
+                &errors,
+                std::path::Path::new(&ast.file_path),
+            ));
+        }
+
+        // Stage 3.5: Security Analysis - no -guhya data reaches a public place
         if self.options.security_check {
             let security_timer = self.kala.begin_phase("security");
-            self.security_check(source)?;
+            self.security_check(&ast)?;
             self.kala.end_phase(security_timer);
         }
 
+        // Stage 3.7: Contracts (Pratijñā) - proved where they can be, kept
+        // as run-time checks where they are wanted
+        let contracts_timer = self.kala.begin_phase("contracts");
+        let ast = self.contracts(ast);
+        self.kala.end_phase(contracts_timer);
+
         // Stage 4: MIR Building
         let mir_timer = self.kala.begin_phase("mir_building");
         let mir = self.build_mir(&ast)?;
         self.kala.end_phase(mir_timer);
 
+        // Stage 4.5: Borrow Checking (Ṛṇa Parīkṣā - examining debts)
+        let borrowck_timer = self.kala.begin_phase("borrow_checking");
+        self.borrow_check(&ast, &mir)?;
+        self.kala.end_phase(borrowck_timer);
+
         // Stage 5: Optimization
         let opt_timer = self.kala.begin_phase("optimization");
         let optimized_mir = self.optimize(mir)?;
@@ -99,6 +181,13 @@ impl CompilerSession {
             result
         };
 
+        // Keep the work of this build for the next one (lakṣya/)
+        self.db.persist().map_err(|e| CompileError {
+            message: format!("Failed to write incremental cache: {}", e),
+            location: None,
+            notes: Vec::new(),
+        })?;
+
         self.timing.total_us = start.elapsed().as_micros() as u64;
 
         if self.options.verbose {
@@ -107,37 +196,70 @@ impl CompilerSession {
 
         Ok(CompileResult {
             output,
-            warnings: Vec::new(),
+            warnings: std::mem::take(&mut self.warnings),
             timing: std::mem::take(&mut self.timing),
         })
     }
 
-    fn lex(&mut self, source: &str) -> Result<Vec<crate::lexer::Token>, CompileError> {
+    /// The crate root file
+    fn root_file(&self) -> PathBuf {
+        self.input_path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("main.jag"))
+    }
+
+    fn lex(&mut self, file: &str) -> Result<(), CompileError> {
         let start = Instant::now();
 
-        let mut lexer = crate::lexer::Lexer::new(source);
-        let tokens = lexer.tokenize();
+        self.db.lex(file).map_err(Self::query_error)?;
 
         self.timing.lexing_us = start.elapsed().as_micros() as u64;
-        Ok(tokens)
+        Ok(())
     }
 
-    fn parse(
-        &mut self,
-        tokens: &[crate::lexer::Token],
-    ) -> Result<crate::parser::ast::Ast, CompileError> {
+    /// Expand `mantra!` definitions and invocations on the token stream
+    fn expand_macros(&mut self, file: &str) -> Result<(), CompileError> {
+        let start = Instant::now();
+
+        let expanded = self.db.expand(file).map_err(Self::query_error)?;
+        // Definitions are per compilation, not per session
+        self.macros = MacroExpander::from_expansions(expanded.expansions.clone());
+        if let Err(e) = &expanded.tokens {
+            let span = e.span();
+            return Err(CompileError {
+                message: format!("Macro errors (Vistāra Doṣa):\n  ॥ {} ॥", e),
+                location: span.map(|span| crate::driver::SourceLocation {
+                    file: String::new(),
+                    line: span.line,
+                    column: span.column,
+                }),
+                notes: self.expansion_notes(span),
+            });
+        }
+
+        self.timing.macro_expansion_us = start.elapsed().as_micros() as u64;
+        Ok(())
+    }
+
+    /// "in expansion of `name!`" notes for a diagnostic span
+    fn expansion_notes(&self, span: Option<Span>) -> Vec<String> {
+        span.map(|span| self.macros.backtrace(span))
+            .unwrap_or_default()
+    }
+
+    fn parse(&mut self, file: &str) -> Result<crate::parser::ast::Ast, CompileError> {
         let start = Instant::now();
 
-        let mut parser = crate::parser::Parser::new(tokens.to_vec());
-        let ast = parser.parse().map_err(|errors| {
+        let parsed = self.db.parse(file).map_err(Self::query_error)?;
+        let ast = parsed.as_ref().clone().map_err(|errors| {
             let mut msg = String::from("Parse errors:");
-            for e in errors {
+            for e in &errors {
                 msg.push_str(&format!("\n  - {} at {:?}", e.message, e.span));
             }
             CompileError {
                 message: msg,
                 location: None,
-                notes: Vec::new(),
+                notes: self.expansion_notes(errors.first().map(|e| e.span)),
             }
         })?;
 
@@ -145,6 +267,54 @@ impl CompilerSession {
         Ok(ast)
     }
 
+    /// Validate attributes and drop items whose `cfg` does not hold
+    fn configure(
+        &mut self,
+        mut ast: crate::parser::ast::Ast,
+    ) -> Result<crate::parser::ast::Ast, CompileError> {
+        crate::semantics::configure(&mut ast, &self.options.cfg_context())
+            .map_err(|errors| self.attribute_error(&errors))?;
+        Ok(ast)
+    }
+
+    /// Apply lint levels to the linked program
+    ///
+    /// Warnings are kept for the [`CompileResult`]; denied lints and
+    /// broken vows fail the build.
+    fn lint(&mut self, ast: &crate::parser::ast::Ast) -> Result<(), CompileError> {
+        let report = crate::semantics::check_lints(ast);
+        if !report.errors.is_empty() {
+            return Err(self.attribute_error(&report.errors));
+        }
+        self.warnings
+            .extend(report.warnings.into_iter().map(|warning| CompileWarning {
+                message: warning.message,
+                location: Some(crate::driver::SourceLocation {
+                    file: String::new(),
+                    line: warning.span.line,
+                    column: warning.span.column,
+                }),
+            }));
+        Ok(())
+    }
+
+    fn attribute_error(&self, errors: &[AttributeError]) -> CompileError {
+        let mut msg = String::from("Attribute errors (Cihna Doṣa):");
+        for error in errors {
+            let span = error.span();
+            msg.push_str(&format!("\n  ॥ {}:{}: {} ॥", span.line, span.column, error));
+        }
+        CompileError {
+            message: msg,
+            location: errors.first().map(|e| crate::driver::SourceLocation {
+                file: String::new(),
+                line: e.span().line,
+                column: e.span().column,
+            }),
+            notes: self.expansion_notes(errors.first().map(|e| e.span())),
+        }
+    }
+
     /// Type checking via Nyāya 4-pramāṇa inference (Prakāra Parīkṣā)
     ///
     /// Like Yama examining the soul's karma before judgment,
@@ -154,13 +324,21 @@ impl CompilerSession {
     /// - Anumāna (95%): Logical deduction
     /// - Śabda (90%): Testimony from function signatures
     /// - Upamāna (85%): Pattern matching by analogy
-    fn type_check(&mut self, ast: &crate::parser::ast::Ast) -> Result<(), CompileError> {
+    fn type_check(
+        &mut self,
+        ast: &crate::parser::ast::Ast,
+        imports: &[(&str, &crate::parser::ast::Item)],
+        hidden: &[(String, String)],
+        file: &str,
+    ) -> Result<(), CompileError> {
         let start = Instant::now();
 
-        let mut typeck = crate::semantics::TypeChecker::new();
-
-        // Perform Nyāya-based type checking
-        typeck.check(ast).map_err(|errors| {
+        // Perform Nyāya-based type checking, item by item
+        let errors = self
+            .db
+            .check_items(ast, imports, hidden)
+            .map_err(Self::query_error)?;
+        if !errors.is_empty() {
             let mut msg = String::from("Type errors (Prakāra Doṣa):");
             for error in &errors {
                 msg.push_str(&format!("\n  ॥ {} ॥", error));
@@ -171,88 +349,599 @@ impl CompilerSession {
                     errors.len()
                 ));
             }
-            CompileError {
+            return Err(CompileError {
                 message: msg,
                 location: errors.first().and_then(|e| e.span()).map(|span| {
                     crate::driver::SourceLocation {
-                        file: String::new(),
-                        line: span.start,
-                        column: 0,
+                        file: file.to_string(),
+                        line: span.line,
+                        column: span.column,
                     }
                 }),
-                notes: vec![
-                    "Type inference follows Nyāya epistemology".to_string(),
-                    "Add explicit type annotations for Pratyakṣa (100% certainty)".to_string(),
-                ],
+                notes: {
+                    let mut notes = self.expansion_notes(errors.first().and_then(|e| e.span()));
+                    notes.push("Type inference follows Nyāya epistemology".to_string());
+                    notes.push(
+                        "Add explicit type annotations for Pratyakṣa (100% certainty)".to_string(),
+                    );
+                    notes
+                },
+            });
+        }
+
+        self.timing.type_checking_us += start.elapsed().as_micros() as u64;
+        Ok(())
+    }
+
+    /// Instantiate generic functions per concrete type arguments
+    ///
+    /// Bounds were checked at each call site during type checking; what
+    /// remains is type arguments that could not be inferred.
+    fn monomorphize(
+        &mut self,
+        ast: &crate::parser::ast::Ast,
+    ) -> Result<crate::parser::ast::Ast, CompileError> {
+        let start = Instant::now();
+
+        let result = self.db.monomorphize(ast).map_err(Self::query_error)?;
+        let ast = result.as_ref().clone().map_err(|errors| {
+            let mut msg = String::from("Monomorphization errors (Viśeṣa Doṣa):");
+            for error in &errors {
+                let span = error.span();
+                msg.push_str(&format!("\n  ॥ {}:{}: {} ॥", span.line, span.column, error));
+            }
+            CompileError {
+                message: msg,
+                location: errors.first().map(|e| crate::driver::SourceLocation {
+                    file: String::new(),
+                    line: e.span().line,
+                    column: e.span().column,
+                }),
+                notes: self.expansion_notes(errors.first().map(|e| e.span())),
             }
         })?;
 
-        self.timing.type_checking_us = start.elapsed().as_micros() as u64;
-        Ok(())
+        self.timing.monomorphization_us = start.elapsed().as_micros() as u64;
+        Ok(ast)
     }
 
-    /// Security analysis via Nava Durga (9 Goddess Protection Layers)
+    /// Load the files of a multi-file crate (Khaṇḍa Saṅgraha)
     ///
-    /// "सर्वमङ्गलमाङ्गल्ये शिवे सर्वार्थसाधिके ।
-    ///  शरण्ये त्र्यम्बके गौरि नारायणि नमोऽस्तु ते ॥"
-    /// - May the auspicious Goddess protect this code
+    /// The parsed input is the crate root; `vibhāga`/`upayoga` declarations
+    /// are followed to sibling files and the include paths. Returns the
+    /// module context, the modules in dependency order and resolved names.
+    fn load_modules(
+        &mut self,
+        root_ast: crate::parser::ast::Ast,
+    ) -> Result<(ModuleContext, Vec<ModuleId>, CrateScope), CompileError> {
+        let start = Instant::now();
+
+        let root_file = self.root_file();
+        let root_dir = match root_file.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let mut modules = ModuleContext::new(root_dir);
+        for path in &self.options.include_paths {
+            modules.resolver.add_search_path(PathBuf::from(path));
+        }
+
+        let cfg = self.options.cfg_context();
+        let order = modules
+            .load_crate(&root_file, root_ast, |path| {
+                Self::parse_module_file(&self.db, &cfg, path)
+            })
+            .map_err(|e| {
+                let message = match &e {
+                    ModuleError::CircularDependency(cycle) => {
+                        let names: Vec<String> = cycle
+                            .iter()
+                            .map(|id| Self::module_name(&modules, *id))
+                            .collect();
+                        format!("Circular dependency (Cakravyūha): {}", names.join(" → "))
+                    }
+                    other => other.to_string(),
+                };
+                CompileError {
+                    message: format!("Module errors (Khaṇḍa Doṣa):\n  ॥ {} ॥", message),
+                    location: None,
+                    notes: Vec::new(),
+                }
+            })?;
+
+        let crate_name = Self::module_name(&modules, order[order.len() - 1]);
+        let scope = CrateScope::build(&modules, &crate_name);
+        if !scope.errors().is_empty() {
+            return Err(Self::module_error(&modules, scope.errors()));
+        }
+
+        self.timing.module_loading_us = start.elapsed().as_micros() as u64;
+        Ok((modules, order, scope))
+    }
+
+    /// Lex, expand and parse a non-root module file
+    fn parse_module_file(
+        db: &CompilerDb,
+        cfg: &CfgContext,
+        path: &Path,
+    ) -> Result<crate::parser::ast::Ast, ModuleError> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| ModuleError::IoError(path.to_path_buf(), e.to_string()))?;
+        let file = path.display().to_string();
+        db.set_source(&file, &source);
+        let module_error =
+            |message: String| ModuleError::ParseError(path.to_path_buf(), vec![message]);
+        let expanded = db.expand(&file).map_err(|e| module_error(e.to_string()))?;
+        if let Err(e) = &expanded.tokens {
+            return Err(module_error(e.to_string()));
+        }
+        let parsed = db.parse(&file).map_err(|e| module_error(e.to_string()))?;
+        let mut ast = parsed.as_ref().clone().map_err(|errors| {
+            let messages = errors
+                .iter()
+                .map(|e| format!("{} at {}:{}", e.message, e.span.line, e.span.column))
+                .collect();
+            ModuleError::ParseError(path.to_path_buf(), messages)
+        })?;
+        crate::semantics::configure(&mut ast, cfg).map_err(|errors| {
+            let messages = errors
+                .iter()
+                .map(|e| format!("{} at {}:{}", e, e.span().line, e.span().column))
+                .collect();
+            ModuleError::ParseError(path.to_path_buf(), messages)
+        })?;
+        Ok(ast)
+    }
+
+    /// Evaluate the constants and array lengths of one module at compile time
     ///
-    /// The 9 Durgas progressively harden the code:
-    /// 1. Śailaputrī - Hardware security foundation
-    /// 2. Brahmacāriṇī - Authentication checks
-    /// 3. Candraghaṇṭā - Encryption verification
-    /// 4. Kūṣmāṇḍā - Access control analysis
-    /// 5. Skandamātā - Process isolation checks
-    /// 6. Kātyāyanī - Input validation
-    /// 7. Kālarātrī - Intrusion detection
-    /// 8. Mahāgaurī - Audit logging
-    /// 9. Siddhidātrī - Formal verification (perfection)
-    fn security_check(&mut self, source: &str) -> Result<(), CompileError> {
-        use crate::nava_durga::{NavaDurgaDefense, SecurityContext, SecurityResult};
-
-        // Create security context with source
-        let mut context = SecurityContext::new(source);
-
-        // Trust level based on deterministic build flag
-        // Deterministic builds are more trusted as they're reproducible
-        let trust_level = if self.options.deterministic { 0.7 } else { 0.3 };
-        context = context.with_trust(trust_level);
-
-        // Deploy the 9 Durga defense layers
-        let defense = NavaDurgaDefense::new();
-        let result = defense.protect(&mut context);
-
-        match result {
-            SecurityResult::Perfect {
-                layers_passed,
-                trust_level,
-            } => {
-                if self.options.verbose {
-                    eprintln!(
-                        "🛡️  Nava Durga Protection Complete: {} layers passed (trust: {:.0}%)",
-                        layers_passed,
-                        trust_level * 100.0
-                    );
-                    eprintln!("   ॐ सिद्धिदात्र्यै नमः - Siddhidatri grants perfection");
+    /// The `sthira` functions and constants it imports may be used, under
+    /// the names it gives them; those of its dependencies are evaluated by
+    /// now.
+    fn evaluate_constants(
+        &mut self,
+        modules: &mut ModuleContext,
+        scope: &CrateScope,
+        id: ModuleId,
+    ) -> Result<(), CompileError> {
+        use crate::parser::ast::Item;
+
+        let mut imported = Vec::new();
+        for (name, target) in scope.imported_items(id) {
+            let item = modules.graph.modules[&target.module]
+                .ast
+                .iter()
+                .flat_map(|a| &a.items)
+                .find_map(|item| match item {
+                    Item::Function(f) if f.is_const && f.name.name == target.name => {
+                        let mut f = f.clone();
+                        f.name.name = name.to_string();
+                        Some(Item::Function(f))
+                    }
+                    Item::Constant(c) if c.name.name == target.name => {
+                        let mut c = c.clone();
+                        c.name.name = name.to_string();
+                        Some(Item::Constant(c))
+                    }
+                    // For the types of its const parameters
+                    Item::TypeDef(t) if t.name.name == target.name => {
+                        let mut t = t.clone();
+                        t.name.name = name.to_string();
+                        Some(Item::TypeDef(t))
+                    }
+                    _ => None,
+                });
+            imported.extend(item);
+        }
+
+        let module = modules.graph.modules.get_mut(&id).expect("loaded module");
+        let Some(ast) = &mut module.ast else {
+            return Ok(());
+        };
+        let errors = crate::mir::const_eval::evaluate_constants(ast, &imported);
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(Self::const_eval_error(&errors, &module.file_path))
+    }
+
+    /// Report constant evaluation errors found in `file`
+    fn const_eval_error(
+        errors: &[(crate::lexer::Span, crate::mir::const_eval::ConstEvalError)],
+        file: &std::path::Path,
+    ) -> CompileError {
+        let mut msg = String::from("Constant evaluation errors (Sthira Doṣa):");
+        for (span, error) in errors {
+            msg.push_str(&format!(
+                "\n  ॥ {}:{}: {} ॥",
+                span.line, span.column, error.kind
+            ));
+            for frame in &error.backtrace {
+                msg.push_str(&format!("\n      in {}", frame));
+            }
+        }
+        CompileError {
+            message: msg,
+            location: errors
+                .first()
+                .map(|(span, _)| crate::driver::SourceLocation {
+                    file: file.display().to_string(),
+                    line: span.line,
+                    column: span.column,
+                }),
+            notes: vec![
+                "Only `sthira kāryakrama` functions can be called at compile time".to_string(),
+            ],
+        }
+    }
+
+    /// Type-check one module with the items it imports in scope
+    fn type_check_module(
+        &mut self,
+        modules: &ModuleContext,
+        scope: &CrateScope,
+        id: ModuleId,
+    ) -> Result<(), CompileError> {
+        use crate::modules::VisibilityScope;
+        use crate::parser::ast::{Item, Type, TypeBody};
+
+        let module = &modules.graph.modules[&id];
+        let Some(ast) = &module.ast else {
+            return Ok(());
+        };
+        let here = VisibilityScope::new(scope.crate_name().to_string(), module.path.clone());
+
+        // Types and traits visible here, and impls, of everything this module
+        // depends on
+        let mut imports: Vec<(&str, &Item)> = Vec::new();
+        // Fields and inherent methods those modules keep to themselves
+        let mut hidden: Vec<(String, String)> = Vec::new();
+        let mut reachable = vec![id];
+        let mut seen = std::collections::HashSet::new();
+        while let Some(dep) = reachable.pop() {
+            if !seen.insert(dep) {
+                continue;
+            }
+            reachable.extend_from_slice(modules.graph.get_dependencies(dep));
+            if dep == id {
+                continue;
+            }
+            let dep_module = &modules.graph.modules[&dep];
+            let there = VisibilityScope::new(here.crate_name.clone(), dep_module.path.clone());
+            for item in dep_module.ast.iter().flat_map(|a| &a.items) {
+                match item {
+                    Item::TypeDef(t) if t.visibility.allows_access(&here, &there) => {
+                        imports.push((&t.name.name, item));
+                        if let TypeBody::Struct(fields) = &t.body {
+                            hidden.extend(
+                                fields
+                                    .iter()
+                                    .filter(|f| !f.visibility.allows_access(&here, &there))
+                                    .map(|f| (t.name.name.clone(), f.name.name.clone())),
+                            );
+                        }
+                    }
+                    Item::Trait(t) if t.visibility.allows_access(&here, &there) => {
+                        imports.push((&t.name.name, item))
+                    }
+                    Item::Impl(imp) if imp.trait_ref.is_none() => {
+                        let Type::Named { name: owner, .. } = &imp.self_ty else {
+                            continue;
+                        };
+                        hidden.extend(
+                            imp.methods
+                                .iter()
+                                .filter(|m| !m.visibility.allows_access(&here, &there))
+                                .map(|m| (owner.name.clone(), m.name.name.clone())),
+                        );
+                    }
+                    _ => {}
                 }
-                Ok(())
             }
-            SecurityResult::Blocked {
-                layer,
-                goddess,
-                reason,
-            } => Err(CompileError {
+        }
+        let impls: Vec<(&str, &Item)> = seen
+            .iter()
+            .filter(|&&dep| dep != id)
+            .flat_map(|dep| modules.graph.modules[dep].ast.iter().flat_map(|a| &a.items))
+            .filter(|item| matches!(item, Item::Impl(_)))
+            .map(|item| ("", item))
+            .collect();
+        imports.extend(impls);
+
+        // Functions, constants and type paths by the names this module uses
+        // for them
+        for (name, target) in scope.imported_items(id) {
+            let item = modules.graph.modules[&target.module]
+                .ast
+                .iter()
+                .flat_map(|a| &a.items)
+                .find(|item| match item {
+                    Item::Function(f) => f.name.name == target.name,
+                    Item::Constant(c) => c.name.name == target.name,
+                    Item::TypeDef(t) => t.name.name == target.name && name != target.name,
+                    Item::Trait(t) => t.name.name == target.name && name != target.name,
+                    _ => false,
+                });
+            if let Some(item) = item {
+                imports.push((name, item));
+            }
+        }
+
+        let file = module.file_path.display().to_string();
+        self.type_check(ast, &imports, &hidden, &file)
+    }
+
+    /// `a::b` path of a module, or the crate name for the root
+    fn module_name(modules: &ModuleContext, id: ModuleId) -> String {
+        match modules.graph.modules.get(&id) {
+            Some(module) if module.path.is_empty() => module.name.clone(),
+            Some(module) => module.path.join("::"),
+            None => format!("#{}", id.0),
+        }
+    }
+
+    /// Report a failed phase query
+    fn query_error(error: QueryError) -> CompileError {
+        CompileError {
+            message: format!("Internal compiler error (Karma Doṣa): {}", error),
+            location: None,
+            notes: Vec::new(),
+        }
+    }
+
+    /// Report name resolution errors, located in the first offending file
+    fn module_error(modules: &ModuleContext, errors: &[ScopeError]) -> CompileError {
+        let mut msg = String::from("Module errors (Khaṇḍa Doṣa):");
+        for error in errors {
+            msg.push_str(&format!(
+                "\n  ॥ {} ({}) ॥",
+                error,
+                Self::module_name(modules, error.module)
+            ));
+        }
+        CompileError {
+            message: msg,
+            location: errors.first().map(|e| crate::driver::SourceLocation {
+                file: modules.graph.modules[&e.module]
+                    .file_path
+                    .display()
+                    .to_string(),
+                line: e.span.line,
+                column: e.span.column,
+            }),
+            notes: Vec::new(),
+        }
+    }
+
+    /// Information flow analysis (Guhya Parīkṣā)
+    ///
+    /// Follows data labeled `-guhya` through the whole program, across
+    /// calls, fields and returns, and rejects every flow that reaches a
+    /// `-sarvajnika` place or is printed, unless declassified with
+    /// `#[prakāśana]`. Each flow is reported with its path from source to sink.
+    fn security_check(&mut self, ast: &crate::parser::ast::Ast) -> Result<(), CompileError> {
+        let errors = match crate::semantics::SecurityAnalyzer::new(ast).check() {
+            Ok(()) => return Ok(()),
+            Err(errors) => errors,
+        };
+        let mut msg = String::from("Security errors (Guhya Doṣa):");
+        for error in &errors {
+            let span = error.span();
+            msg.push_str(&format!("\n  ॥ {}:{}: {} ॥", span.line, span.column, error));
+        }
+        Err(CompileError {
+            message: msg,
+            location: errors.first().map(|e| crate::driver::SourceLocation {
+                file: String::new(),
+                line: e.span().line,
+                column: e.span().column,
+            }),
+            notes: vec![
+                "Mark the point where a secret may be revealed with `#[prakāśana]`".to_string(),
+                "Use --no-security to bypass (not recommended)".to_string(),
+            ],
+        })
+    }
+
+    /// Prove contracts with `--prove-contracts`, warning of each obligation
+    /// that cannot be and dropping the checks of those that are, and drop
+    /// all of them from builds that do not check them
+    ///
+    /// Proofs take integers to be mathematical, which only Sattva's
+    /// trapping overflow makes true.
+    fn contracts(&mut self, mut ast: crate::parser::ast::Ast) -> crate::parser::ast::Ast {
+        use crate::parser::ast::Item;
+
+        if self.options.prove_contracts
+            && self.options.guna != crate::philosophy::guna::Guna::Sattva
+        {
+            self.warnings.push(CompileWarning {
                 message: format!(
-                    "Security blocked at layer {} ({}):\n  {}",
-                    layer, goddess, reason
+                    "contracts are not proved in {:?} builds, where integers wrap on overflow; \
+                     build with --sattva to prove them",
+                    self.options.guna
                 ),
                 location: None,
-                notes: vec![
-                    format!("Goddess {} protects against this vulnerability", goddess),
-                    "Review security annotations and trust boundaries".to_string(),
-                    "Use --no-security to bypass (not recommended)".to_string(),
-                ],
+            });
+        } else if self.options.prove_contracts {
+            let obligations = crate::semantics::prove_contracts(&ast);
+            let unproved = obligations.iter().filter(|obligation| !obligation.proved);
+            self.warnings
+                .extend(unproved.map(|obligation| CompileWarning {
+                    message: obligation.to_string(),
+                    location: Some(crate::driver::SourceLocation {
+                        file: String::new(),
+                        line: obligation.span.line,
+                        column: obligation.span.column,
+                    }),
+                }));
+            crate::semantics::drop_proved(&mut ast, &obligations);
+        }
+        if !self.options.checks_contracts() {
+            for item in &mut ast.items {
+                let functions = match item {
+                    Item::Function(func) => std::slice::from_mut(func),
+                    Item::Impl(impl_def) => &mut impl_def.methods[..],
+                    _ => continue,
+                };
+                for func in functions {
+                    func.preconditions.clear();
+                    func.postconditions.clear();
+                }
+            }
+        }
+        ast
+    }
+
+    /// Borrow checking via the Ṛṇa (debt) system (Ṛṇa Parīkṣā)
+    ///
+    /// Every borrow is a debt that must be repaid before its owner departs.
+    /// Three analyses run over every function, including impl methods and
+    /// trait default bodies:
+    /// - BorrowChecker: ownership, moves and -l/-b affix discipline
+    /// - LifetimeChecker: ^N regions and dangling references
+    /// - NllChecker: liveness-based region constraints on the MIR
+    fn borrow_check(
+        &mut self,
+        ast: &crate::parser::ast::Ast,
+        mir: &crate::mir::types::MirModule,
+    ) -> Result<(), CompileError> {
+        let start = Instant::now();
+
+        let mut diagnostics = Vec::new();
+        let mut fn_spans = std::collections::HashMap::new();
+        Self::borrow_check_items(&ast.items, &mut diagnostics, &mut fn_spans);
+
+        for func in &mir.functions {
+            let mut nll = crate::mir::NllChecker::new();
+            if let Err(errors) = nll.check_function(func) {
+                // Errors without a recorded statement fall back to the
+                // function, or for a closure to the function it is in
+                let fn_span = std::iter::successors(Some(func.name.as_str()), |name| {
+                    name.rsplit_once("__pāśa").map(|(enclosing, _)| enclosing)
+                })
+                .find_map(|name| fn_spans.get(name))
+                .copied();
+                for error in errors {
+                    let span = error.span().or(fn_span);
+                    diagnostics.push((span, format!("in `{}`: {}", func.name, error)));
+                }
+            }
+        }
+
+        self.timing.borrow_checking_us = start.elapsed().as_micros() as u64;
+
+        // The AST checkers can report the same violation (e.g. a returned
+        // local reference); keep one diagnostic per span and message
+        let mut seen = std::collections::HashSet::new();
+        diagnostics
+            .retain(|(span, msg)| seen.insert((span.map(|s| (s.line, s.column)), msg.clone())));
+        if diagnostics.is_empty() {
+            return Ok(());
+        }
+
+        let mut msg = String::from("Borrow errors (Ṛṇa Doṣa):");
+        for (span, error) in &diagnostics {
+            match span {
+                Some(span) => {
+                    msg.push_str(&format!("\n  ॥ {}:{}: {} ॥", span.line, span.column, error))
+                }
+                None => msg.push_str(&format!("\n  ॥ {} ॥", error)),
+            }
+        }
+        if diagnostics.len() > 1 {
+            msg.push_str(&format!("\n\n  Total: {} unpaid debts", diagnostics.len()));
+        }
+        Err(CompileError {
+            message: msg,
+            location: diagnostics.iter().find_map(|(span, _)| *span).map(|span| {
+                crate::driver::SourceLocation {
+                    file: String::new(),
+                    line: span.line,
+                    column: span.column,
+                }
             }),
+            notes: {
+                let mut notes =
+                    self.expansion_notes(diagnostics.iter().find_map(|(span, _)| *span));
+                notes.push(
+                    "Values without -l/-b affixes are affine: moved on use unless Copy".to_string(),
+                );
+                notes.push("Borrow with & and keep references within their ^N region".to_string());
+                notes
+            },
+        })
+    }
+
+    /// Run the AST-level borrow and lifetime checkers over a list of items,
+    /// noting the span of each function under its MIR symbol
+    fn borrow_check_items(
+        items: &[crate::parser::ast::Item],
+        diagnostics: &mut Vec<(Option<crate::lexer::Span>, String)>,
+        fn_spans: &mut std::collections::HashMap<String, crate::lexer::Span>,
+    ) {
+        use crate::mir::builder::{impl_owner_name, mangle_method, method_symbol};
+        use crate::parser::ast::{FunctionDef, Item};
+
+        let check = |symbol: String,
+                     func: &FunctionDef,
+                     diagnostics: &mut Vec<_>,
+                     fn_spans: &mut std::collections::HashMap<_, _>| {
+            fn_spans.insert(symbol, func.span);
+            if let Err(errors) = crate::semantics::BorrowChecker::new().check_function(func) {
+                for error in errors {
+                    diagnostics.push((Some(error.span()), error.to_string()));
+                }
+            }
+            if let Err(errors) = crate::semantics::LifetimeChecker::new().check_function(func) {
+                for error in errors {
+                    diagnostics.push((error.span(), error.to_string()));
+                }
+            }
+        };
+
+        for item in items {
+            match item {
+                Item::Function(func) => check(func.name.name.clone(), func, diagnostics, fn_spans),
+                Item::Impl(impl_def) => {
+                    let owner = impl_owner_name(&impl_def.self_ty);
+                    for method in &impl_def.methods {
+                        let symbol = method_symbol(impl_def, &owner, &method.name.name);
+                        check(symbol, method, diagnostics, fn_spans);
+                    }
+                }
+                Item::Trait(trait_def) => {
+                    for method in &trait_def.methods {
+                        let Some(body) = &method.default_body else {
+                            continue;
+                        };
+                        check(
+                            mangle_method(&trait_def.name.name, &method.name.name),
+                            &FunctionDef {
+                                name: method.name.clone(),
+                                generics: method.generics.clone(),
+                                receiver: method.receiver,
+                                params: method.params.clone(),
+                                return_type: method.return_type.clone(),
+                                where_clauses: method.where_clauses.clone(),
+                                preconditions: Vec::new(),
+                                postconditions: Vec::new(),
+                                body: body.clone(),
+                                is_const: false,
+                                visibility: trait_def.visibility,
+                                attributes: method.attributes.clone(),
+                                span: method.span,
+                            },
+                            diagnostics,
+                            fn_spans,
+                        );
+                    }
+                }
+                Item::Module(module) => {
+                    Self::borrow_check_items(&module.items, diagnostics, fn_spans);
+                }
+                _ => {}
+            }
         }
     }
 
@@ -262,8 +951,34 @@ impl CompilerSession {
     ) -> Result<crate::mir::types::MirModule, CompileError> {
         let start = Instant::now();
 
-        let mut builder = crate::mir::MirBuilder::new();
-        let mir = builder.build(ast);
+        // Sattva stops on overflow; Rajas and Tamas wrap around
+        let overflow = match self.options.guna {
+            crate::philosophy::guna::Guna::Sattva => crate::mir::types::Overflow::Trap,
+            crate::philosophy::guna::Guna::Rajas | crate::philosophy::guna::Guna::Tamas => {
+                crate::mir::types::Overflow::Wrap
+            }
+        };
+        let mir = self
+            .db
+            .build_mir(ast, overflow)
+            .map_err(Self::query_error)?
+            .map_err(|errors| {
+                let mut msg = String::from("Lowering errors (Avataraṇa Doṣa):");
+                for error in &errors {
+                    msg.push_str(&format!("\n  ॥ {} ॥", error));
+                }
+                CompileError {
+                    message: msg,
+                    location: errors.iter().find_map(|e| e.span).map(|span| {
+                        crate::driver::SourceLocation {
+                            file: ast.file_path.clone(),
+                            line: span.line,
+                            column: span.column,
+                        }
+                    }),
+                    notes: Vec::new(),
+                }
+            })?;
 
         self.timing.mir_building_us = start.elapsed().as_micros() as u64;
         Ok(mir)
@@ -348,36 +1063,11 @@ impl CompilerSession {
     ) -> Result<Vec<u8>, CompileError> {
         let start = Instant::now();
 
-        // Select emitter based on target architecture
-        let asm = match self.options.target {
-            crate::codegen::asm::Target::X86_64 => {
-                let mut emitter = crate::codegen::asm::x86_64::X86_64Emitter::new();
-                for func in &mir.functions {
-                    emitter.emit_prologue(func);
-                    emitter.emit_body(func);
-                    emitter.emit_epilogue(func);
-                }
-                emitter.get_asm()
-            }
-            crate::codegen::asm::Target::AArch64 => {
-                let mut emitter = crate::codegen::asm::aarch64::AArch64Emitter::new();
-                for func in &mir.functions {
-                    emitter.emit_prologue(func);
-                    emitter.emit_body(func);
-                    emitter.emit_epilogue(func);
-                }
-                emitter.get_asm()
-            }
-            crate::codegen::asm::Target::RiscV64 => {
-                let mut emitter = crate::codegen::asm::riscv64::RiscV64Emitter::new();
-                for func in &mir.functions {
-                    emitter.emit_prologue(func);
-                    emitter.emit_body(func);
-                    emitter.emit_epilogue(func);
-                }
-                emitter.get_asm()
-            }
-        };
+        // Functions whose MIR is unchanged reuse their assembly
+        let asm = self
+            .db
+            .generate_code(mir, self.options.target)
+            .map_err(Self::query_error)?;
 
         let output = asm.into_bytes();
 
@@ -420,8 +1110,13 @@ impl CompilerSession {
             exe_path
         };
 
-        // Write assembly to temp file
-        let asm_path = build_dir.join("output.s");
+        // Write assembly to temp file, named for the executable so builds
+        // running at once keep apart
+        let asm_path = build_dir.join(format!(
+            "{}_{}.s",
+            exe_name.file_stem().unwrap_or_default().to_string_lossy(),
+            std::process::id()
+        ));
         std::fs::write(&asm_path, asm_output).map_err(|e| CompileError {
             message: format!("Failed to write assembly: {}", e),
             location: None,
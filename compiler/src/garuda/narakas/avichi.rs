@@ -123,7 +123,7 @@ impl AvichiChecker {
                         }
                     }
                 }
-                Stmt::Match { arms, .. } => {
+                Stmt::Expr(Expr::Match { arms, .. }) => {
                     // Check if any arm returns without recursion
                     for arm in arms {
                         if self.is_non_recursive_return(&arm.body) {
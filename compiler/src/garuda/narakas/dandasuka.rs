@@ -91,7 +91,9 @@ impl DandasukaChecker {
                 self.check_block(then_block, violations);
                 if let Some(eb) = else_block { self.check_block(eb, violations); }
             }
-            Stmt::Loop { body, kind, span } => {
+            Stmt::Loop {
+                body, kind, span, ..
+            } => {
                 let was_in_loop = self.in_loop;
                 self.in_loop = true;
 
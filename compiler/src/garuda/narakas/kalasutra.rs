@@ -103,6 +103,7 @@ impl KalasutraChecker {
                 body,
                 span,
                 kind: _kind,
+                ..
             } => {
                 let locks_before = self.current_locks.len();
                 self.check_block(body, violations);
@@ -167,7 +167,9 @@ impl KrimibhakshaChecker {
     fn looks_like_pointer(&self, expr: &Expr) -> bool {
         match expr {
             Expr::Identifier(id) => id.name.ends_with("_ptr") || id.name.starts_with("p_"),
-            Expr::Unary { op, .. } if *op == UnaryOp::Deref || *op == UnaryOp::Ref => true,
+            Expr::Unary { op, .. } => {
+                matches!(op, UnaryOp::Deref | UnaryOp::Ref | UnaryOp::RefMut)
+            }
             _ => false,
         }
     }
@@ -80,11 +80,6 @@ impl KumbhipakaChecker {
                     self.collect_calls(body, callees);
                 }
                 Stmt::Return { value: Some(v), .. } => self.collect_calls_expr(v, callees),
-                Stmt::Match { arms, .. } => {
-                    for arm in arms {
-                        self.collect_calls_expr(&arm.body, callees);
-                    }
-                }
                 _ => {}
             }
         }
@@ -104,6 +99,14 @@ impl KumbhipakaChecker {
                 self.collect_calls_expr(left, callees);
                 self.collect_calls_expr(right, callees);
             }
+            Expr::Match {
+                scrutinee, arms, ..
+            } => {
+                self.collect_calls_expr(scrutinee, callees);
+                for arm in arms {
+                    self.collect_calls_expr(&arm.body, callees);
+                }
+            }
             _ => {}
         }
     }
@@ -116,7 +119,9 @@ impl KumbhipakaChecker {
 
     fn check_stmt(&mut self, stmt: &Stmt, violations: &mut Vec<Violation>) {
         match stmt {
-            Stmt::Loop { body, span, kind } => {
+            Stmt::Loop {
+                body, span, kind, ..
+            } => {
                 self.loop_depth += 1;
                 // Check for unbounded loop
                 if matches!(kind, LoopKind::Infinite) && !self.has_exit(body) {
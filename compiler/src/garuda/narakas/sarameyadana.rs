@@ -98,7 +98,9 @@ impl SarameyaDanaChecker {
                     self.check_block(eb, violations);
                 }
             }
-            Stmt::Loop { body, kind, span } => {
+            Stmt::Loop {
+                body, kind, span, ..
+            } => {
                 if let LoopKind::While { condition } = kind {
                     self.check_expr(condition, span, violations);
                 }
@@ -205,7 +207,10 @@ impl SarameyaDanaChecker {
 
     fn is_pointer_creation(&self, expr: &Expr) -> bool {
         match expr {
-            Expr::Unary { op, .. } if *op == UnaryOp::Ref => true,
+            Expr::Unary {
+                op: UnaryOp::Ref | UnaryOp::RefMut,
+                ..
+            } => true,
             Expr::Call { callee, .. } => {
                 if let Expr::Identifier(id) = callee.as_ref() {
                     self.ref_fns.contains(id.name.as_str())
@@ -231,7 +236,11 @@ impl SarameyaDanaChecker {
 
     fn get_referenced_local(&self, expr: &Expr) -> Option<String> {
         match expr {
-            Expr::Unary { op, operand, .. } if *op == UnaryOp::Ref => {
+            Expr::Unary {
+                op: UnaryOp::Ref | UnaryOp::RefMut,
+                operand,
+                ..
+            } => {
                 if let Expr::Identifier(id) = operand.as_ref() {
                     if self.locals.contains(&id.name) {
                         return Some(id.name.clone());
@@ -101,7 +101,9 @@ impl SuchimukhaChecker {
                     self.check_block(eb, violations);
                 }
             }
-            Stmt::Loop { body, kind, span } => {
+            Stmt::Loop {
+                body, kind, span, ..
+            } => {
                 // Check for unbounded growth in loops
                 if let LoopKind::Infinite = kind {
                     self.check_loop_growth(body, span, violations);
@@ -50,7 +50,9 @@ impl TaptasurmiChecker {
                 self.check_block(then_block, loop_depth, violations);
                 if let Some(eb) = else_block { self.check_block(eb, loop_depth, violations); }
             }
-            Stmt::Loop { body, kind, span } => {
+            Stmt::Loop {
+                body, kind, span, ..
+            } => {
                 let new_depth = loop_depth + 1;
 
                 // Check for infinite/while loops without yield
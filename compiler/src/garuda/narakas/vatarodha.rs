@@ -67,7 +67,9 @@ impl VatarodhaChecker {
                     self.check_block(eb, in_loop, violations);
                 }
             }
-            Stmt::Loop { body, kind, span } => {
+            Stmt::Loop {
+                body, kind, span, ..
+            } => {
                 // Check for unbounded loops with resource allocation
                 let is_unbounded = matches!(kind, LoopKind::Infinite);
                 if is_unbounded {
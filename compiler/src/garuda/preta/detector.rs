@@ -308,7 +308,7 @@ impl PretaDetector {
                 count
             }
             Stmt::Loop { body, .. } => self.count_blocks(body),
-            Stmt::Match { arms, .. } => arms.len(),
+            Stmt::Expr(Expr::Match { arms, .. }) => arms.len(),
             _ => 0,
         }
     }
@@ -342,6 +342,15 @@ impl PretaDetector {
                     );
                 }
             }
+            Stmt::Expr(Expr::Match { arms, .. }) => {
+                for arm in arms {
+                    if let Expr::Block(block) = &arm.body {
+                        for stmt in &block.stmts {
+                            self.visit_stmt(stmt);
+                        }
+                    }
+                }
+            }
             Stmt::Expr(expr) => {
                 // Check for deallocation calls
                 self.check_deallocation(expr);
@@ -361,15 +370,6 @@ impl PretaDetector {
                     self.visit_block(eb);
                 }
             }
-            Stmt::Match { arms, .. } => {
-                for arm in arms {
-                    if let Expr::Block(block) = &arm.body {
-                        for stmt in &block.stmts {
-                            self.visit_stmt(stmt);
-                        }
-                    }
-                }
-            }
             Stmt::Return {
                 value: Some(value), ..
             } => {
@@ -5,7 +5,8 @@
 use super::{Violation, ViolationKind, Yamaduta};
 use super::yamadutas::{MemoryYamaduta, SecurityYamaduta, ConcurrencyYamaduta};
 use crate::garuda::narakas::Naraka;
-use crate::parser::ast::Ast;
+use crate::parser::ast::{Ast, FunctionDef, Item};
+use crate::yoga::ashtanga::Yama;
 
 /// Yama Dharmaraja - Judge of the dead (code analyzer)
 pub struct YamaDharmaraja {
@@ -70,6 +71,23 @@ impl YamaDharmaraja {
         violations
     }
 
+    /// Judge one function against the yamas it vows (`#[yama(ahiṃsā)]`)
+    ///
+    /// Only violations one of the vows forbids are returned, with that vow.
+    pub fn judge_vows(&self, func: &FunctionDef, vows: &[Yama]) -> Vec<(Yama, Violation)> {
+        let ast = Ast {
+            items: vec![Item::Function(func.clone())],
+            file_path: String::new(),
+        };
+        self.judge(&ast)
+            .into_iter()
+            .filter_map(|violation| {
+                let vow = vows.iter().find(|vow| violation.kind.breaks(**vow))?;
+                Some((*vow, violation))
+            })
+            .collect()
+    }
+
     /// Determine which Naraka a violation belongs to
     pub fn determine_naraka(&self, violation: &Violation) -> Naraka {
         match violation.kind {
@@ -14,6 +14,7 @@ pub use sentence::Sentence;
 pub use yamadutas::{ConcurrencyYamaduta, MemoryYamaduta, SecurityYamaduta, Yamaduta};
 
 use crate::errors::Span;
+use crate::yoga::ashtanga::Yama;
 
 /// Kind of violation detected
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -59,6 +60,47 @@ pub enum ViolationKind {
     CodeSmell,
 }
 
+impl ViolationKind {
+    /// Whether a function under a yama vow (`#[yama(ahiṃsā)]`) may not commit this
+    pub fn breaks(&self, vow: Yama) -> bool {
+        use ViolationKind::*;
+        match vow {
+            // Ahiṃsā: do no harm to memory or the running system
+            Yama::Ahimsa => matches!(
+                self,
+                UseAfterFree
+                    | DoubleFree
+                    | BufferOverflow
+                    | NullDeref
+                    | MemoryCorruption
+                    | StackOverflow
+                    | Panic
+                    | DoS
+            ),
+            // Satya: values and interfaces are what they claim to be
+            Yama::Satya => matches!(
+                self,
+                TypeConfusion | ContractViolation | DataCorruption | CodeInjection
+            ),
+            // Asteya: take nothing that is not owned
+            Yama::Asteya => matches!(
+                self,
+                UseAfterFree | DanglingPointer | RaceCondition | ThreadUnsafe | DataExposure
+            ),
+            // Brahmacarya: moderation in resources
+            Yama::Brahmacarya => matches!(
+                self,
+                MemoryLeak | ResourceExhaustion | Starvation | Deadlock
+            ),
+            // Aparigraha: hold nothing longer than needed
+            Yama::Aparigraha => matches!(
+                self,
+                MemoryLeak | InsecureStorage | ResourceDenial | TaintedData
+            ),
+        }
+    }
+}
+
 /// A detected violation
 #[derive(Debug, Clone)]
 pub struct Violation {
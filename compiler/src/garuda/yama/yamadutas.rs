@@ -183,6 +183,14 @@ impl MemoryYamaduta {
                     self.analyze_expr(else_e, violations);
                 }
             }
+            Expr::Match {
+                scrutinee, arms, ..
+            } => {
+                self.analyze_expr(scrutinee, violations);
+                for arm in arms {
+                    self.analyze_expr(&arm.body, violations);
+                }
+            }
             _ => {}
         }
     }
@@ -244,14 +252,6 @@ impl MemoryYamaduta {
             Stmt::Loop { body, .. } => {
                 self.analyze_block(body, violations);
             }
-            Stmt::Match {
-                scrutinee, arms, ..
-            } => {
-                self.analyze_expr(scrutinee, violations);
-                for arm in arms {
-                    self.analyze_expr(&arm.body, violations);
-                }
-            }
             _ => {}
         }
     }
@@ -0,0 +1,417 @@
+//! Lipi - Script and Transliteration Normalization
+//!
+//! The same word may be typed as Devanagari (`कार्यक्रम`), IAST
+//! (`kāryakrama`, precomposed or with combining marks), Harvard-Kyoto
+//! (`kAryakrama`) or ITRANS (`kaaryakrama`). The lexer normalizes keywords
+//! and Sanskrit identifiers to IAST in Unicode NFC, so all spellings name
+//! the same thing.
+//!
+//! ASCII words are ambiguous: `cala` is spelled the same in every scheme,
+//! `Target` is English and `getSize` is camelCase. A word is read as a
+//! transliteration when it carries a marker of the scheme:
+//! - ITRANS: a doubled vowel (`aa`, `ii`, `uu`), `RRi` or `LLi`
+//! - Harvard-Kyoto: one of `A I U R M H G J T D N S` right after a
+//!   lowercase letter
+//!
+//! Markers only say which keyword an ASCII word may spell. An ASCII
+//! identifier keeps its written spelling, so English names and the symbols
+//! of other languages' code survive, unless the file declares its scheme:
+//!
+//! ```text
+//! #![lipi = "hk"]        // or "harvard-kyoto", "itrans"
+//! ```
+//!
+//! A capital first letter is taken as capitalization, not as a
+//! transliteration letter.
+
+use std::borrow::Cow;
+use unicode_normalization::UnicodeNormalization;
+
+/// Script a word is written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lipi {
+    /// देवनागरी
+    Devanagari,
+    /// IAST, with diacritics
+    Iast,
+    /// Harvard-Kyoto, ASCII with capitals for long vowels and retroflexes
+    HarvardKyoto,
+    /// ITRANS, ASCII with doubled vowels
+    Itrans,
+    /// Plain ASCII, the same in every scheme
+    Ascii,
+}
+
+const VIRAMA: char = '\u{094D}';
+const NUKTA: char = '\u{093C}';
+
+/// Letters that mark a word as Harvard-Kyoto after a lowercase letter
+const HK_CAPITALS: &[u8] = b"AIURMHGJTDNS";
+
+/// Markers of an ITRANS word
+const ITRANS_MARKERS: &[&str] = &["aa", "ii", "uu", "RRi", "RRI", "LLi", "LLI"];
+
+/// Harvard-Kyoto to IAST, longest match first
+const HK: &[(&str, &str)] = &[
+    ("lRR", "ḹ"),
+    ("lR", "ḷ"),
+    ("RR", "ṝ"),
+    ("R", "ṛ"),
+    ("A", "ā"),
+    ("I", "ī"),
+    ("U", "ū"),
+    ("M", "ṃ"),
+    ("H", "ḥ"),
+    ("G", "ṅ"),
+    ("J", "ñ"),
+    ("T", "ṭ"),
+    ("D", "ḍ"),
+    ("N", "ṇ"),
+    ("z", "ś"),
+    ("S", "ṣ"),
+];
+
+/// ITRANS to IAST, longest match first
+const ITRANS: &[(&str, &str)] = &[
+    ("RRI", "ṝ"),
+    ("RRi", "ṛ"),
+    ("LLI", "ḹ"),
+    ("LLi", "ḷ"),
+    ("chh", "ch"),
+    ("shh", "ṣ"),
+    ("kSh", "kṣ"),
+    ("dny", "jñ"),
+    ("aa", "ā"),
+    ("ii", "ī"),
+    ("ee", "ī"),
+    ("uu", "ū"),
+    ("oo", "ū"),
+    ("Ch", "ch"),
+    ("ch", "c"),
+    ("Sh", "ṣ"),
+    ("sh", "ś"),
+    ("GY", "jñ"),
+    ("A", "ā"),
+    ("I", "ī"),
+    ("U", "ū"),
+    ("T", "ṭ"),
+    ("D", "ḍ"),
+    ("N", "ṇ"),
+    ("M", "ṃ"),
+    ("H", "ḥ"),
+    ("x", "kṣ"),
+    ("w", "v"),
+];
+
+/// Whether a character is in the Devanagari block
+pub fn is_devanagari(ch: char) -> bool {
+    ('\u{0900}'..='\u{097F}').contains(&ch)
+}
+
+/// Script of a single word
+pub fn detect(word: &str) -> Lipi {
+    if word.chars().any(is_devanagari) {
+        return Lipi::Devanagari;
+    }
+    if !word.is_ascii() {
+        return Lipi::Iast;
+    }
+    if ITRANS_MARKERS.iter().any(|marker| word.contains(marker)) {
+        return Lipi::Itrans;
+    }
+    let hk = word
+        .as_bytes()
+        .windows(2)
+        .any(|pair| pair[0].is_ascii_lowercase() && HK_CAPITALS.contains(&pair[1]));
+    if hk {
+        Lipi::HarvardKyoto
+    } else {
+        Lipi::Ascii
+    }
+}
+
+/// Canonical spelling of a word: IAST in NFC
+pub fn normalize(word: &str) -> Cow<'_, str> {
+    match detect(word) {
+        Lipi::Ascii => Cow::Borrowed(word),
+        Lipi::HarvardKyoto => Cow::Owned(replace_ascii(word, HK)),
+        Lipi::Itrans => Cow::Owned(replace_ascii(word, ITRANS)),
+        Lipi::Devanagari => Cow::Owned(devanagari_to_iast(&word.nfc().collect::<String>())),
+        // ISO 15919 writes anusvara as ṁ
+        Lipi::Iast => Cow::Owned(word.nfc().collect::<String>().replace('ṁ', "ṃ")),
+    }
+}
+
+/// Spelling of an identifier, in a file whose scheme is `declared`
+///
+/// Devanagari and IAST are normalized. An ASCII identifier is kept as
+/// written unless the file declares Harvard-Kyoto or ITRANS, as a marker
+/// alone does not tell a transliteration from English.
+pub fn identifier(word: &str, declared: Option<Lipi>) -> Cow<'_, str> {
+    match (detect(word), declared) {
+        (Lipi::Devanagari | Lipi::Iast, _) => normalize(word),
+        (_, Some(Lipi::HarvardKyoto)) => Cow::Owned(replace_ascii(word, HK)),
+        (_, Some(Lipi::Itrans)) => Cow::Owned(replace_ascii(word, ITRANS)),
+        _ => Cow::Borrowed(word),
+    }
+}
+
+/// Scheme a `#![lipi = "…"]` line declares
+pub fn pragma(line: &str) -> Option<Lipi> {
+    let value = line
+        .trim()
+        .strip_prefix("#![")?
+        .strip_suffix(']')?
+        .trim()
+        .strip_prefix("lipi")?
+        .trim_start()
+        .strip_prefix('=')?
+        .trim();
+    match value.strip_prefix('"')?.strip_suffix('"')? {
+        "hk" | "harvard-kyoto" => Some(Lipi::HarvardKyoto),
+        "itrans" => Some(Lipi::Itrans),
+        _ => None,
+    }
+}
+
+/// Scheme `source` declares for its ASCII identifiers, if any
+pub fn declared(source: &str) -> Option<Lipi> {
+    source.lines().find_map(pragma)
+}
+
+/// Transliterate an ASCII scheme by longest match; a leading capital is kept
+fn replace_ascii(word: &str, table: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(word.len() + 4);
+    let mut rest = word;
+    if let Some(first) = word.chars().next().filter(char::is_ascii_uppercase) {
+        out.push(first);
+        rest = &word[1..];
+    }
+    'outer: while !rest.is_empty() {
+        for (from, to) in table {
+            if let Some(after) = rest.strip_prefix(from) {
+                out.push_str(to);
+                rest = after;
+                continue 'outer;
+            }
+        }
+        let ch = rest.chars().next().unwrap_or_default();
+        out.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+    out
+}
+
+/// Transliterate Devanagari to IAST; other characters are kept
+pub fn devanagari_to_iast(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if let Some(mut consonant) = consonant(ch) {
+            if chars.peek() == Some(&NUKTA) {
+                chars.next();
+                consonant = with_nukta(consonant);
+            }
+            out.push_str(consonant);
+            // Consonants carry an inherent `a` unless a vowel sign or
+            // virama follows
+            match chars.peek().copied() {
+                Some(VIRAMA) => {
+                    chars.next();
+                }
+                Some(next) => match vowel_sign(next) {
+                    Some(vowel) => {
+                        chars.next();
+                        out.push_str(vowel);
+                    }
+                    None => out.push('a'),
+                },
+                None => out.push('a'),
+            }
+        } else if let Some(vowel) = independent_vowel(ch) {
+            out.push_str(vowel);
+        } else {
+            match ch {
+                '\u{0901}' => out.push_str("m̐"),
+                '\u{0902}' => out.push('ṃ'),
+                '\u{0903}' => out.push('ḥ'),
+                // Avagraha marks an elided `a`
+                '\u{093D}' => {}
+                '\u{0950}' => out.push_str("om"),
+                '\u{0966}'..='\u{096F}' => {
+                    out.push(char::from(b'0' + (ch as u32 - 0x0966) as u8));
+                }
+                other => out.push(other),
+            }
+        }
+    }
+    out
+}
+
+fn consonant(ch: char) -> Option<&'static str> {
+    Some(match ch {
+        'क' => "k",
+        'ख' => "kh",
+        'ग' => "g",
+        'घ' => "gh",
+        'ङ' => "ṅ",
+        'च' => "c",
+        'छ' => "ch",
+        'ज' => "j",
+        'झ' => "jh",
+        'ञ' => "ñ",
+        'ट' => "ṭ",
+        'ठ' => "ṭh",
+        'ड' => "ḍ",
+        'ढ' => "ḍh",
+        'ण' => "ṇ",
+        'त' => "t",
+        'थ' => "th",
+        'द' => "d",
+        'ध' => "dh",
+        'न' | 'ऩ' => "n",
+        'प' => "p",
+        'फ' => "ph",
+        'ब' => "b",
+        'भ' => "bh",
+        'म' => "m",
+        'य' => "y",
+        'र' | 'ऱ' => "r",
+        'ल' => "l",
+        'ळ' | 'ऴ' => "ḷ",
+        'व' => "v",
+        'श' => "ś",
+        'ष' => "ṣ",
+        'स' => "s",
+        'ह' => "h",
+        _ => return None,
+    })
+}
+
+/// Consonants written with a nukta (borrowed sounds)
+fn with_nukta(consonant: &'static str) -> &'static str {
+    match consonant {
+        "k" => "q",
+        "kh" => "x",
+        "g" => "ġ",
+        "j" => "z",
+        "ḍ" => "ṛ",
+        "ḍh" => "ṛh",
+        "ph" => "f",
+        "y" => "ẏ",
+        other => other,
+    }
+}
+
+fn independent_vowel(ch: char) -> Option<&'static str> {
+    Some(match ch {
+        'अ' => "a",
+        'आ' => "ā",
+        'इ' => "i",
+        'ई' => "ī",
+        'उ' => "u",
+        'ऊ' => "ū",
+        'ऋ' => "ṛ",
+        'ॠ' => "ṝ",
+        'ऌ' => "ḷ",
+        'ॡ' => "ḹ",
+        'ए' | 'ऍ' | 'ऎ' => "e",
+        'ऐ' => "ai",
+        'ओ' | 'ऑ' | 'ऒ' => "o",
+        'औ' => "au",
+        _ => return None,
+    })
+}
+
+fn vowel_sign(ch: char) -> Option<&'static str> {
+    Some(match ch {
+        'ा' => "ā",
+        'ि' => "i",
+        'ी' => "ī",
+        'ु' => "u",
+        'ू' => "ū",
+        'ृ' => "ṛ",
+        'ॄ' => "ṝ",
+        'ॢ' => "ḷ",
+        'ॣ' => "ḹ",
+        'े' | 'ॅ' | 'ॆ' => "e",
+        'ै' => "ai",
+        'ो' | 'ॉ' | 'ॊ' => "o",
+        'ौ' => "au",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_schemes_agree() {
+        for spelling in ["कार्यक्रम", "kāryakrama", "kAryakrama", "kaaryakrama"]
+        {
+            assert_eq!(normalize(spelling), "kāryakrama", "{}", spelling);
+        }
+        for spelling in ["परीक्षा", "parIkSA", "pariikShaa"] {
+            assert_eq!(normalize(spelling), "parīkṣā", "{}", spelling);
+        }
+        assert_eq!(normalize("सङ्ख्या"), "saṅkhyā");
+        assert_eq!(normalize("अहिंसा"), "ahiṃsā");
+        assert_eq!(normalize("ahiṁsā"), "ahiṃsā");
+    }
+
+    #[test]
+    fn test_nfc() {
+        let decomposed = "ka\u{0304}ryakrama";
+        assert_eq!(detect(decomposed), Lipi::Iast);
+        assert_eq!(normalize(decomposed), "kāryakrama");
+        // क़ is decomposed by NFC into क and a nukta
+        assert_eq!(normalize("\u{0958}"), "qa");
+        assert_eq!(normalize("क\u{093C}"), "qa");
+    }
+
+    #[test]
+    fn test_plain_ascii_is_kept() {
+        for word in ["cala", "yadi", "Target", "mukhya", "free", "SIMD", "x86_64"] {
+            assert_eq!(detect(word), Lipi::Ascii, "{}", word);
+            assert!(matches!(normalize(word), Cow::Borrowed(_)), "{}", word);
+        }
+    }
+
+    #[test]
+    fn test_ascii_identifiers_are_kept() {
+        // Markers, but English, camelCase or another language's symbols
+        for word in [
+            "getSize",
+            "SDL_GetTicks",
+            "needsUpdate",
+            "toolkit",
+            "kAryakrama",
+        ] {
+            assert_eq!(identifier(word, None), word);
+        }
+        assert_eq!(identifier("गणना", None), "gaṇanā");
+        assert_eq!(identifier("gaNanA", Some(Lipi::HarvardKyoto)), "gaṇanā");
+        assert_eq!(identifier("gaNanaa", Some(Lipi::Itrans)), "gaṇanā");
+    }
+
+    #[test]
+    fn test_pragma() {
+        assert_eq!(pragma("#![lipi = \"hk\"]"), Some(Lipi::HarvardKyoto));
+        assert_eq!(pragma("  #![lipi=\"itrans\"]"), Some(Lipi::Itrans));
+        assert_eq!(pragma("#![lipi = \"devanagari\"]"), None);
+        assert_eq!(pragma("# lipi = \"hk\""), None);
+        assert_eq!(
+            declared("// kośa\n#![lipi = \"harvard-kyoto\"]\n"),
+            Some(Lipi::HarvardKyoto)
+        );
+    }
+
+    #[test]
+    fn test_devanagari_details() {
+        assert_eq!(normalize("यदि"), "yadi");
+        assert_eq!(normalize("क्षेत्र"), "kṣetra");
+        assert_eq!(normalize("गणना२"), "gaṇanā2");
+        assert_eq!(normalize("संख्या"), "saṃkhyā");
+    }
+}
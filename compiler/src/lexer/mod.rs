@@ -4,6 +4,7 @@
 //! - Dhātu (root word) recognition
 //! - Sandhi (phonetic combination) splitting
 //! - Affix (pratyaya) extraction
+//! - Script normalization (lipi): Devanagari, IAST, Harvard-Kyoto, ITRANS
 //! - Token generation
 //!
 //! ## Two-Pass Tokenization
@@ -12,6 +13,7 @@
 
 pub mod affixes;
 pub mod dhatu;
+pub mod lipi;
 pub mod sandhi;
 pub mod scanner;
 pub mod token;
@@ -33,6 +35,8 @@ pub struct Lexer<'src> {
     dhatu_dict: Option<&'src DhatuDictionary>,
     /// Sandhi FST for phonetic splitting (optional)
     sandhi_fst: Option<&'src SandhiFst>,
+    /// Scheme the source declares for ASCII identifiers
+    lipi: Option<lipi::Lipi>,
 }
 
 impl<'src> Lexer<'src> {
@@ -43,6 +47,7 @@ impl<'src> Lexer<'src> {
             source,
             dhatu_dict: None,
             sandhi_fst: None,
+            lipi: lipi::declared(source),
         }
     }
 
@@ -57,6 +62,7 @@ impl<'src> Lexer<'src> {
             source,
             dhatu_dict: Some(dhatu_dict),
             sandhi_fst: Some(sandhi_fst),
+            lipi: lipi::declared(source),
         }
     }
 
@@ -115,8 +121,8 @@ impl<'src> Lexer<'src> {
                             continue;
                         }
                     }
-                } else if ch == '#' {
-                    // Shell-style comment
+                } else if ch == '#' && self.scanner.peek_next() != Some('[') {
+                    // Shell-style comment; `#[` opens an attribute
                     self.scanner.skip_to_eol();
                     continue;
                 }
@@ -305,7 +311,17 @@ impl<'src> Lexer<'src> {
 
             // String literals
             '"' => return Some(self.scan_string()),
-            '\'' => return Some(self.scan_char()),
+            '\'' => {
+                // `'bāhya` is a loop label, `'a'` a character
+                let starts_word = self
+                    .scanner
+                    .peek()
+                    .is_some_and(|c| Scanner::is_sanskrit_letter(c) || c == '_');
+                if starts_word && self.scanner.peek_next() != Some('\'') {
+                    return Some(self.scan_label());
+                }
+                return Some(self.scan_char());
+            }
 
             // Numbers
             '0'..='9' => return Some(self.scan_number(ch)),
@@ -399,6 +415,23 @@ impl<'src> Lexer<'src> {
         }
     }
 
+    /// Scan a loop label: `'` followed by an identifier
+    fn scan_label(&mut self) -> Token {
+        while let Some(ch) = self.scanner.peek() {
+            if Scanner::is_identifier_char(ch) {
+                self.scanner.advance();
+            } else {
+                break;
+            }
+        }
+        let name = lipi::identifier(&self.scanner.token_text()[1..], self.lipi).into_owned();
+        Token {
+            kind: TokenKind::Label(name),
+            span: self.scanner.token_span(),
+            lexeme: self.scanner.token_text().to_string(),
+        }
+    }
+
     /// Scan a number literal
     fn scan_number(&mut self, first: char) -> Token {
         let mut is_float = false;
@@ -549,9 +582,9 @@ impl<'src> Lexer<'src> {
         }
 
         let text = self.scanner.token_text();
-        let marker_name = &text[1..]; // Skip the @
+        let marker_name = lipi::normalize(&text[1..]); // Skip the @
 
-        let kind = match marker_name {
+        let kind = match marker_name.as_ref() {
             "kartṛ" | "kartr" | "agent" => TokenKind::KarakaKartr,
             "karman" | "patient" | "object" => TokenKind::KarakaKarman,
             "karaṇa" | "karana" | "instrument" => TokenKind::KarakaKarana,
@@ -600,13 +633,22 @@ impl<'src> Lexer<'src> {
             }
         }
 
-        let text = self.scanner.token_text();
-        let kind = self.keyword_or_identifier(text);
+        // Every script spells the same keyword; identifiers written in
+        // ASCII keep their spelling unless the source declares a scheme
+        let written = self.scanner.token_text();
+        let keyword = lipi::normalize(written);
+        let (kind, lexeme) = match self.keyword_or_identifier(&keyword) {
+            TokenKind::Identifier(_) => {
+                let name = lipi::identifier(written, self.lipi).into_owned();
+                (TokenKind::Identifier(name.clone()), name)
+            }
+            kind => (kind, keyword.into_owned()),
+        };
 
         Token {
             kind,
             span: self.scanner.token_span(),
-            lexeme: text.to_string(),
+            lexeme,
         }
     }
 
@@ -635,17 +677,15 @@ impl<'src> Lexer<'src> {
             "asatya" | "false" => TokenKind::BoolLiteral(false),
 
             // Type keywords
-            "saṅkhyā" | "sankhya" | "int" => TokenKind::Sankhya,
+            "saṅkhyā" | "saṃkhyā" | "sankhya" | "int" => TokenKind::Sankhya,
             "sūtra" | "sutra" | "string" | "str" => TokenKind::Sutra,
             "sūci" | "suci" | "vec" | "list" => TokenKind::Suci,
             "sāraṇī" | "sarani" | "map" => TokenKind::Sarani,
-            "vikalpa" | "option" => TokenKind::Vikalpa,
-            "phala" | "result" => TokenKind::Phala,
 
             // Control flow
             "for" => TokenKind::For,
             "in" => TokenKind::In,
-            "match" => TokenKind::Match,
+            "pratyabhijñā" | "match" => TokenKind::Match,
             "break" => TokenKind::Break,
             "continue" => TokenKind::Continue,
 
@@ -669,7 +709,7 @@ impl<'src> Lexer<'src> {
             // Other
             "as" => TokenKind::As,
             "ref" => TokenKind::Ref,
-            "unsafe" | "asuraksita" => TokenKind::Unsafe,
+            "unsafe" | "asurakṣita" | "asuraksita" => TokenKind::Unsafe,
             "extern" | "bāhya" => TokenKind::Extern,
             "where" | "yatra" => TokenKind::Where,
 
@@ -686,6 +726,7 @@ impl TokenKind {
         !matches!(
             self,
             TokenKind::Identifier(_)
+                | TokenKind::Label(_)
                 | TokenKind::IntLiteral(_)
                 | TokenKind::FloatLiteral(_)
                 | TokenKind::StringLiteral(_)
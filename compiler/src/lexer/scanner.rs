@@ -120,17 +120,24 @@ impl<'src> Scanner<'src> {
         if ('\u{0900}'..='\u{097F}').contains(&ch) {
             return true;
         }
-        // IAST diacritics
+        // IAST diacritics (ṁ is the ISO 15919 anusvāra)
         matches!(ch,
             'ā' | 'ī' | 'ū' | 'ṛ' | 'ṝ' | 'ḷ' | 'ḹ' |
             'ṅ' | 'ñ' | 'ṭ' | 'ḍ' | 'ṇ' | 'ś' | 'ṣ' |
-            'ḥ' | 'ṃ' | 'a'..='z' | 'A'..='Z'
+            'ḥ' | 'ṃ' | 'ṁ' |
+            'Ā' | 'Ī' | 'Ū' | 'Ṛ' | 'Ṝ' | 'Ḷ' | 'Ḹ' |
+            'Ṅ' | 'Ñ' | 'Ṭ' | 'Ḍ' | 'Ṇ' | 'Ś' | 'Ṣ' |
+            'Ḥ' | 'Ṃ' | 'Ṁ' | 'a'..='z' | 'A'..='Z'
         )
     }
 
     /// Check if character can be part of an identifier
     pub fn is_identifier_char(ch: char) -> bool {
-        Self::is_sanskrit_letter(ch) || ch.is_ascii_digit() || ch == '_'
+        Self::is_sanskrit_letter(ch)
+            || ch.is_ascii_digit()
+            || ch == '_'
+            // Combining diacritics of decomposed (NFD) IAST
+            || ('\u{0300}'..='\u{036F}').contains(&ch)
     }
 
     /// Get current line number
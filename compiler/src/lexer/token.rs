@@ -84,6 +84,8 @@ pub enum TokenKind {
     Identifier(String),
     /// Dhātu (root word) with recognized meaning
     Dhatu { root: String, meaning: String },
+    /// Loop label (`'bāhya`), without the quote
+    Label(String),
 
     // ========================================================================
     // Sanskrit Keywords (v1.0)
@@ -130,14 +132,6 @@ pub enum TokenKind {
     Suci,
     /// Sāraṇī - map/table
     Sarani,
-    /// Vikalpa - option
-    Vikalpa,
-    /// Phala - result
-    Phala,
-    /// Saphala - success
-    Saphala,
-    /// Viphala - failure
-    Viphala,
     /// Truṭi - error
     Truti,
 
@@ -14,10 +14,13 @@
 
 pub mod hygiene;
 pub mod mantra;
+pub mod rules;
 
 pub use hygiene::*;
 pub use mantra::*;
+pub use rules::MantraDef;
 
+use crate::lexer::token::{Span, Token, TokenKind};
 use regex::Regex;
 use std::collections::HashMap;
 
@@ -135,6 +138,21 @@ pub struct MacroExpander {
     max_depth: usize,
     /// Expansion cache for memoization (Smṛti - memory)
     cache: HashMap<String, String>,
+    /// Token-level `mantra!` definitions
+    mantras: HashMap<String, MantraDef>,
+    /// Every token-level expansion performed, in order
+    expansions: Vec<ExpansionRecord>,
+}
+
+/// Record of one token-level expansion, used for diagnostic backtraces
+#[derive(Debug, Clone)]
+pub struct ExpansionRecord {
+    /// Macro name
+    pub name: String,
+    /// Span of the whole invocation `name!(...)`
+    pub call_site: Span,
+    /// Span of the `mantra!` definition
+    pub def_site: Span,
 }
 
 impl MacroExpander {
@@ -166,6 +184,8 @@ impl MacroExpander {
             depth: 0,
             max_depth: 256,
             cache: HashMap::new(),
+            mantras: HashMap::new(),
+            expansions: Vec::new(),
         }
     }
 
@@ -190,7 +210,173 @@ impl MacroExpander {
 
     /// Check if a macro is defined
     pub fn is_defined(&self, name: &str) -> bool {
-        self.macros.iter().any(|m| m.name == name) || self.builtin_mantras.contains_key(name)
+        self.macros.iter().any(|m| m.name == name)
+            || self.mantras.contains_key(name)
+            || self.builtin_mantras.contains_key(name)
+    }
+
+    /// Expand user `mantra!` macros on a token stream (Vistāra before parsing)
+    ///
+    /// Definitions are removed from the stream and every invocation of a
+    /// defined mantra is replaced by its transcription. Built-in mantras are
+    /// left in place for the parser to lower.
+    pub fn expand_tokens(&mut self, tokens: Vec<Token>) -> Result<Vec<Token>, MacroError> {
+        let tokens = self.collect_definitions(tokens)?;
+        self.depth = 0;
+        self.expand_stream(tokens)
+    }
+
+    /// Strip `mantra! name { ... }` items, registering each definition
+    fn collect_definitions(&mut self, tokens: Vec<Token>) -> Result<Vec<Token>, MacroError> {
+        let mut out = Vec::with_capacity(tokens.len());
+        let mut i = 0;
+        while i < tokens.len() {
+            if !rules::is_definition_start(&tokens, i) {
+                out.push(tokens[i].clone());
+                i += 1;
+                continue;
+            }
+            let (def, end) = rules::parse_definition(&tokens, i)?;
+            if let Some(previous) = self.mantras.get(&def.name) {
+                return Err(MacroError::InvalidDefinition {
+                    message: format!(
+                        "mantra `{}` is already defined at line {}",
+                        def.name, previous.span.line
+                    ),
+                    span: def.span,
+                });
+            }
+            self.mantras.insert(def.name.clone(), def);
+            i = end;
+            if matches!(tokens.get(i).map(|t| &t.kind), Some(TokenKind::Semicolon)) {
+                i += 1;
+            }
+        }
+        Ok(out)
+    }
+
+    fn expand_stream(&mut self, tokens: Vec<Token>) -> Result<Vec<Token>, MacroError> {
+        let mut out = Vec::with_capacity(tokens.len());
+        let mut i = 0;
+        while i < tokens.len() {
+            let invoked = match (&tokens[i].kind, tokens.get(i + 1).map(|t| &t.kind)) {
+                (TokenKind::Identifier(name), Some(TokenKind::Bang))
+                    if self.mantras.contains_key(name) =>
+                {
+                    Some(name.clone())
+                }
+                _ => None,
+            };
+            let Some(name) = invoked else {
+                out.push(tokens[i].clone());
+                i += 1;
+                continue;
+            };
+
+            let start = tokens[i].span;
+            let end = match tokens.get(i + 2).map(|t| &t.kind) {
+                Some(TokenKind::LeftParen | TokenKind::LeftBracket | TokenKind::LeftBrace) => {
+                    rules::tree_end(&tokens, i + 2)
+                }
+                _ => None,
+            }
+            .ok_or(MacroError::NoMatchingRule {
+                name: name.clone(),
+                span: start,
+            })?;
+            let call_site = Span::with_location(
+                start.start,
+                tokens[end - 1].span.end,
+                start.line,
+                start.column,
+            );
+            let expanded = self.expand_invocation(&name, &tokens[i + 3..end - 1], call_site)?;
+            // `m!(...);` where the expansion already ends its statements
+            let ends_statement =
+                matches!(expanded.last().map(|t| &t.kind), Some(TokenKind::Semicolon));
+            out.extend(expanded);
+            i = end;
+            if ends_statement
+                && matches!(tokens.get(i).map(|t| &t.kind), Some(TokenKind::Semicolon))
+            {
+                i += 1;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Expand one invocation, then any invocations its transcription contains
+    fn expand_invocation(
+        &mut self,
+        name: &str,
+        args: &[Token],
+        call_site: Span,
+    ) -> Result<Vec<Token>, MacroError> {
+        if self.depth >= self.max_depth {
+            return Err(MacroError::MaxDepthExceeded);
+        }
+        let def = self.mantras[name].clone();
+        let (rule, bindings) = def.match_rules(args).ok_or(MacroError::NoMatchingRule {
+            name: name.to_string(),
+            span: call_site,
+        })?;
+
+        // Locals introduced by the body get fresh names for this expansion
+        self.hygiene_ctx.enter_macro();
+        let mut renames = HashMap::new();
+        for local in rule.local_bindings() {
+            let hygienic = self.hygiene_ctx.gensym(&local);
+            self.hygiene_ctx.bind(&local, &hygienic);
+            renames.insert(local, hygienic);
+        }
+        let body = rule.transcribe(&bindings, &renames, call_site);
+        self.hygiene_ctx.exit_macro();
+
+        self.expansions.push(ExpansionRecord {
+            name: name.to_string(),
+            call_site,
+            def_site: def.span,
+        });
+
+        self.depth += 1;
+        let expanded = self.expand_stream(body?);
+        self.depth -= 1;
+        expanded
+    }
+
+    /// Expansions performed so far
+    pub fn expansions(&self) -> &[ExpansionRecord] {
+        &self.expansions
+    }
+
+    /// Expander holding earlier expansions, for backtraces of cached results
+    pub fn from_expansions(expansions: Vec<ExpansionRecord>) -> Self {
+        Self {
+            expansions,
+            ..Self::new()
+        }
+    }
+
+    /// Backtrace notes for a diagnostic span, innermost expansion first
+    pub fn backtrace(&self, span: Span) -> Vec<String> {
+        if span.end == 0 {
+            return Vec::new();
+        }
+        self.expansions
+            .iter()
+            .rev()
+            .filter(|r| r.call_site.start <= span.start && span.end <= r.call_site.end)
+            .map(|r| {
+                format!(
+                    "in expansion of `{}!` at {}:{} (mantra defined at {}:{})",
+                    r.name,
+                    r.call_site.line,
+                    r.call_site.column,
+                    r.def_site.line,
+                    r.def_site.column
+                )
+            })
+            .collect()
     }
 
     /// Expand all macros in source
@@ -460,6 +646,20 @@ pub enum MacroError {
     HygieneViolation(String),
     /// IO error
     IoError(String),
+    /// Malformed `mantra!` definition
+    InvalidDefinition { message: String, span: Span },
+    /// No rule of a mantra matched its invocation
+    NoMatchingRule { name: String, span: Span },
+}
+
+impl MacroError {
+    /// Source span of the error, when known
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::InvalidDefinition { span, .. } | Self::NoMatchingRule { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Display for MacroError {
@@ -470,6 +670,16 @@ impl std::fmt::Display for MacroError {
             Self::MaxDepthExceeded => write!(f, "Maximum macro expansion depth exceeded"),
             Self::HygieneViolation(msg) => write!(f, "Macro hygiene violation: {}", msg),
             Self::IoError(msg) => write!(f, "IO error during macro expansion: {}", msg),
+            Self::InvalidDefinition { message, span } => write!(
+                f,
+                "Invalid mantra definition at {}:{}: {}",
+                span.line, span.column, message
+            ),
+            Self::NoMatchingRule { name, span } => write!(
+                f,
+                "No rule of `{}!` matches this invocation at {}:{}",
+                name, span.line, span.column
+            ),
         }
     }
 }
@@ -556,4 +766,86 @@ mod tests {
         // The current implementation stops when no changes occur, so this should succeed
         assert!(result.is_ok());
     }
+
+    fn lexemes(tokens: &[Token]) -> Vec<String> {
+        tokens
+            .iter()
+            .filter(|t| t.kind != TokenKind::Eof)
+            .map(|t| t.lexeme.clone())
+            .collect()
+    }
+
+    fn expand(source: &str) -> Result<Vec<String>, MacroError> {
+        let tokens = crate::lexer::Lexer::new(source).tokenize();
+        MacroExpander::new()
+            .expand_tokens(tokens)
+            .map(|t| lexemes(&t))
+    }
+
+    #[test]
+    fn test_mantra_definition_and_expansion() {
+        let result = expand("mantra! vargam { ($x:expr) => { $x * $x }; } vargam!(a + 1)").unwrap();
+        assert_eq!(
+            result,
+            ["(", "a", "+", "1", ")", "*", "(", "a", "+", "1", ")"]
+        );
+    }
+
+    #[test]
+    fn test_mantra_rules_tried_in_order() {
+        let source = "mantra! m { () => { 0 }; ($x:ident) => { $x }; } m!() m!(y)";
+        assert_eq!(expand(source).unwrap(), ["0", "y"]);
+    }
+
+    #[test]
+    fn test_mantra_repetition() {
+        let source = "mantra! yoga { ($($x:expr),*) => { 0 $(+ $x)* }; } yoga!(1, 2, 3)";
+        assert_eq!(expand(source).unwrap(), ["0", "+", "1", "+", "2", "+", "3"]);
+    }
+
+    #[test]
+    fn test_mantra_hygiene() {
+        let source = "mantra! m { ($e:expr) => { let t = $e; t }; } m!(t)";
+        let result = expand(source).unwrap();
+        assert_ne!(result[1], "t");
+        assert!(result[1].starts_with("t__hygiene_"));
+        // The call-site `t` is left alone, the body's `t` follows the rename
+        assert_eq!(result[3], "t");
+        assert_eq!(result[5], result[1]);
+    }
+
+    #[test]
+    fn test_nested_mantra_backtrace() {
+        let source = "mantra! a { ($x:expr) => { b!($x) }; }\nmantra! b { ($x:expr) => { $x }; }\nlet v = a!(1);";
+        let tokens = crate::lexer::Lexer::new(source).tokenize();
+        let mut expander = MacroExpander::new();
+        expander.expand_tokens(tokens).unwrap();
+        assert_eq!(expander.expansions().len(), 2);
+
+        let call_site = expander.expansions()[0].call_site;
+        let trace = expander.backtrace(call_site);
+        assert_eq!(trace.len(), 2);
+        assert!(trace[0].starts_with("in expansion of `b!` at 3:9"));
+        assert!(trace[1].starts_with("in expansion of `a!` at 3:9"));
+    }
+
+    #[test]
+    fn test_mantra_errors() {
+        let no_match = expand("mantra! m { ($x:ident) => { $x }; } m!(1)");
+        assert!(matches!(no_match, Err(MacroError::NoMatchingRule { .. })));
+
+        let bad_spec = expand("mantra! m { ($x:foo) => { $x }; }");
+        assert!(matches!(
+            bad_spec,
+            Err(MacroError::InvalidDefinition { .. })
+        ));
+
+        let mut expander = MacroExpander::new();
+        expander.max_depth = 8;
+        let tokens = crate::lexer::Lexer::new("mantra! r { () => { r!() }; } r!()").tokenize();
+        assert!(matches!(
+            expander.expand_tokens(tokens),
+            Err(MacroError::MaxDepthExceeded)
+        ));
+    }
 }
@@ -0,0 +1,578 @@
+//! Mantra Rules - Token-level macro definitions
+//!
+//! User macros are declared with the `mantra!` item and expanded on the
+//! token stream before parsing:
+//!
+//! ```text
+//! mantra! vargam {
+//!     ($x:expr) => { $x * $x };
+//!     ($($x:expr),+) => { 0 $(+ $x * $x)+ };
+//! }
+//! ```
+//!
+//! Each rule is a matcher (pratyabhijñā - recognition) and a transcriber
+//! (vistāra - elaboration). Matching is greedy and does not backtrack.
+
+use super::{CaptureKind, MacroError, RepetitionKind};
+use crate::lexer::token::{Span, Token, TokenKind};
+use std::collections::HashMap;
+
+/// Keyword introducing a macro definition item
+pub const DEFINITION_KEYWORD: &str = "mantra";
+
+/// A user-defined macro: a list of rules tried in order
+#[derive(Debug, Clone)]
+pub struct MantraDef {
+    /// Macro name
+    pub name: String,
+    /// Rules, tried top to bottom
+    pub rules: Vec<MantraRule>,
+    /// Definition site
+    pub span: Span,
+}
+
+/// One `(matcher) => { transcriber }` arm
+#[derive(Debug, Clone)]
+pub struct MantraRule {
+    pub matcher: Vec<Matcher>,
+    pub transcriber: Vec<Transcriber>,
+}
+
+/// Matcher element
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    /// Exact token
+    Token(TokenKind),
+    /// `$name:kind`
+    Fragment { name: String, kind: CaptureKind },
+    /// `$( ... ) sep? op`
+    Repeat {
+        matchers: Vec<Matcher>,
+        separator: Option<TokenKind>,
+        kind: RepetitionKind,
+    },
+}
+
+/// Transcriber element
+#[derive(Debug, Clone)]
+pub enum Transcriber {
+    /// Token copied from the definition body
+    Token(Token),
+    /// `$name`
+    Var(String),
+    /// `$( ... ) sep? op`
+    Repeat {
+        body: Vec<Transcriber>,
+        separator: Option<Token>,
+    },
+}
+
+/// Captured fragment bindings
+#[derive(Debug, Clone)]
+pub enum Binding {
+    Single(Vec<Token>),
+    Seq(Vec<HashMap<String, Binding>>),
+}
+
+/// Does a `mantra! name {` definition start at `pos`?
+pub fn is_definition_start(tokens: &[Token], pos: usize) -> bool {
+    matches!(tokens.get(pos).map(|t| &t.kind), Some(TokenKind::Identifier(n)) if n == DEFINITION_KEYWORD)
+        && matches!(tokens.get(pos + 1).map(|t| &t.kind), Some(TokenKind::Bang))
+        && matches!(
+            tokens.get(pos + 2).map(|t| &t.kind),
+            Some(TokenKind::Identifier(_))
+        )
+        && matches!(
+            tokens.get(pos + 3).map(|t| &t.kind),
+            Some(TokenKind::LeftBrace)
+        )
+}
+
+/// Parse a `mantra!` definition starting at `pos`; returns it with the
+/// index just past its closing brace
+pub fn parse_definition(tokens: &[Token], pos: usize) -> Result<(MantraDef, usize), MacroError> {
+    let span = tokens[pos].span;
+    let name = match &tokens[pos + 2].kind {
+        TokenKind::Identifier(name) => name.clone(),
+        _ => unreachable!("checked by is_definition_start"),
+    };
+    let end = tree_end(tokens, pos + 3).ok_or_else(|| invalid("unclosed `{`", span))?;
+    // Tokens between the outer braces
+    let body = &tokens[pos + 4..end - 1];
+
+    let mut rules = Vec::new();
+    let mut i = 0;
+    while i < body.len() {
+        let matcher_end = delimited_end(body, i)
+            .ok_or_else(|| invalid("expected a delimited matcher", body[i].span))?;
+        let matcher = parse_matchers(&body[i + 1..matcher_end - 1])?;
+        i = matcher_end;
+
+        if !matches!(body.get(i).map(|t| &t.kind), Some(TokenKind::FatArrow)) {
+            return Err(invalid(
+                "expected `=>` after matcher",
+                token_span(body, i, span),
+            ));
+        }
+        i += 1;
+
+        let body_end = delimited_end(body, i).ok_or_else(|| {
+            invalid(
+                "expected a delimited transcriber",
+                token_span(body, i, span),
+            )
+        })?;
+        let transcriber = parse_transcriber(&body[i + 1..body_end - 1])?;
+        i = body_end;
+
+        if matches!(body.get(i).map(|t| &t.kind), Some(TokenKind::Semicolon)) {
+            i += 1;
+        }
+        rules.push(MantraRule {
+            matcher,
+            transcriber,
+        });
+    }
+
+    if rules.is_empty() {
+        return Err(invalid(&format!("mantra `{}` has no rules", name), span));
+    }
+    Ok((MantraDef { name, rules, span }, end))
+}
+
+fn parse_matchers(tokens: &[Token]) -> Result<Vec<Matcher>, MacroError> {
+    let mut matchers = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        if tokens[i].kind != TokenKind::Dollar {
+            matchers.push(Matcher::Token(tokens[i].kind.clone()));
+            i += 1;
+            continue;
+        }
+        match tokens.get(i + 1).map(|t| &t.kind) {
+            Some(TokenKind::Identifier(name)) => {
+                if !matches!(tokens.get(i + 2).map(|t| &t.kind), Some(TokenKind::Colon)) {
+                    return Err(invalid(
+                        &format!("expected `:kind` after `${}`", name),
+                        tokens[i].span,
+                    ));
+                }
+                let spec = tokens
+                    .get(i + 3)
+                    .ok_or_else(|| invalid("missing fragment specifier", tokens[i].span))?;
+                let kind = fragment_kind(&spec.lexeme).ok_or_else(|| {
+                    invalid(
+                        &format!("unknown fragment specifier `{}`", spec.lexeme),
+                        spec.span,
+                    )
+                })?;
+                matchers.push(Matcher::Fragment {
+                    name: name.clone(),
+                    kind,
+                });
+                i += 4;
+            }
+            Some(TokenKind::LeftParen) => {
+                let end = tree_end(tokens, i + 1)
+                    .ok_or_else(|| invalid("unclosed `$(`", tokens[i].span))?;
+                let inner = parse_matchers(&tokens[i + 2..end - 1])?;
+                let (separator, kind, next) = parse_repetition_op(tokens, end)?;
+                matchers.push(Matcher::Repeat {
+                    matchers: inner,
+                    separator: separator.map(|t| t.kind),
+                    kind,
+                });
+                i = next;
+            }
+            _ => return Err(invalid("expected `$name:kind` or `$(`", tokens[i].span)),
+        }
+    }
+    Ok(matchers)
+}
+
+fn parse_transcriber(tokens: &[Token]) -> Result<Vec<Transcriber>, MacroError> {
+    let mut body = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        if tokens[i].kind != TokenKind::Dollar {
+            body.push(Transcriber::Token(tokens[i].clone()));
+            i += 1;
+            continue;
+        }
+        match tokens.get(i + 1).map(|t| &t.kind) {
+            Some(TokenKind::Identifier(name)) => {
+                body.push(Transcriber::Var(name.clone()));
+                i += 2;
+            }
+            Some(TokenKind::LeftParen) => {
+                let end = tree_end(tokens, i + 1)
+                    .ok_or_else(|| invalid("unclosed `$(`", tokens[i].span))?;
+                let inner = parse_transcriber(&tokens[i + 2..end - 1])?;
+                let (separator, _, next) = parse_repetition_op(tokens, end)?;
+                body.push(Transcriber::Repeat {
+                    body: inner,
+                    separator,
+                });
+                i = next;
+            }
+            _ => return Err(invalid("expected `$name` or `$(`", tokens[i].span)),
+        }
+    }
+    Ok(body)
+}
+
+/// Parse `sep? op` after a `$( ... )` group
+fn parse_repetition_op(
+    tokens: &[Token],
+    pos: usize,
+) -> Result<(Option<Token>, RepetitionKind, usize), MacroError> {
+    let op = |t: Option<&Token>| match t.map(|t| &t.kind) {
+        Some(TokenKind::Star) => Some(RepetitionKind::ZeroOrMore),
+        Some(TokenKind::Plus) => Some(RepetitionKind::OneOrMore),
+        Some(TokenKind::Question) => Some(RepetitionKind::ZeroOrOne),
+        _ => None,
+    };
+    if let Some(kind) = op(tokens.get(pos)) {
+        return Ok((None, kind, pos + 1));
+    }
+    if let Some(kind) = op(tokens.get(pos + 1)) {
+        return Ok((Some(tokens[pos].clone()), kind, pos + 2));
+    }
+    Err(invalid(
+        "expected `*`, `+` or `?` after `$( ... )`",
+        token_span(tokens, pos, Span::dummy()),
+    ))
+}
+
+fn fragment_kind(spec: &str) -> Option<CaptureKind> {
+    match spec {
+        "expr" => Some(CaptureKind::Expr),
+        "stmt" => Some(CaptureKind::Stmt),
+        "ty" => Some(CaptureKind::Type),
+        "pat" => Some(CaptureKind::Pat),
+        "ident" => Some(CaptureKind::Ident),
+        "tt" => Some(CaptureKind::TokenTree),
+        _ => None,
+    }
+}
+
+impl MantraDef {
+    /// Try each rule in order against the invocation arguments
+    pub fn match_rules(&self, input: &[Token]) -> Option<(&MantraRule, HashMap<String, Binding>)> {
+        self.rules.iter().find_map(|rule| {
+            let mut bindings = HashMap::new();
+            let end = match_seq(&rule.matcher, input, 0, None, &mut bindings)?;
+            (end == input.len()).then_some((rule, bindings))
+        })
+    }
+}
+
+/// Match a matcher sequence starting at `pos`; `follow` is the token that
+/// may legally come after the sequence (used to end `expr` fragments)
+fn match_seq(
+    matchers: &[Matcher],
+    input: &[Token],
+    mut pos: usize,
+    follow: Option<&TokenKind>,
+    bindings: &mut HashMap<String, Binding>,
+) -> Option<usize> {
+    for (idx, matcher) in matchers.iter().enumerate() {
+        let next_follow = match matchers.get(idx + 1) {
+            Some(Matcher::Token(kind)) => Some(kind),
+            Some(_) => None,
+            None => follow,
+        };
+        match matcher {
+            Matcher::Token(kind) => {
+                if input.get(pos).map(|t| &t.kind) != Some(kind) {
+                    return None;
+                }
+                pos += 1;
+            }
+            Matcher::Fragment { name, kind } => {
+                let end = match_fragment(*kind, input, pos, next_follow)?;
+                let mut captured = input[pos..end].to_vec();
+                // Keep a captured expression one operand after substitution
+                if *kind == CaptureKind::Expr && captured.len() > 1 {
+                    let open = synthetic(TokenKind::LeftParen, "(", input[pos].span);
+                    let close = synthetic(TokenKind::RightParen, ")", input[end - 1].span);
+                    captured.insert(0, open);
+                    captured.push(close);
+                }
+                bindings.insert(name.clone(), Binding::Single(captured));
+                pos = end;
+            }
+            Matcher::Repeat {
+                matchers: inner,
+                separator,
+                kind,
+            } => {
+                let inner_follow = separator.as_ref().or(next_follow);
+                let mut iterations = Vec::new();
+                loop {
+                    if pos >= input.len() || Some(&input[pos].kind) == next_follow {
+                        break;
+                    }
+                    let mut iteration = HashMap::new();
+                    let Some(end) = match_seq(inner, input, pos, inner_follow, &mut iteration)
+                    else {
+                        break;
+                    };
+                    if end == pos {
+                        break;
+                    }
+                    pos = end;
+                    iterations.push(iteration);
+                    if *kind == RepetitionKind::ZeroOrOne {
+                        break;
+                    }
+                    match separator {
+                        Some(sep) if input.get(pos).map(|t| &t.kind) == Some(sep) => pos += 1,
+                        Some(_) => break,
+                        None => {}
+                    }
+                }
+                if *kind == RepetitionKind::OneOrMore && iterations.is_empty() {
+                    return None;
+                }
+                for name in fragment_names(inner) {
+                    let seq = iterations
+                        .iter()
+                        .map(|it| {
+                            let mut single = HashMap::new();
+                            if let Some(b) = it.get(&name) {
+                                single.insert(name.clone(), b.clone());
+                            }
+                            single
+                        })
+                        .collect();
+                    bindings.insert(name, Binding::Seq(seq));
+                }
+            }
+        }
+    }
+    Some(pos)
+}
+
+/// Match one fragment, returning the index just past it
+fn match_fragment(
+    kind: CaptureKind,
+    input: &[Token],
+    pos: usize,
+    follow: Option<&TokenKind>,
+) -> Option<usize> {
+    let first = input.get(pos)?;
+    match kind {
+        CaptureKind::Ident => matches!(first.kind, TokenKind::Identifier(_)).then_some(pos + 1),
+        CaptureKind::TokenTree => tree_end(input, pos),
+        CaptureKind::Expr | CaptureKind::Stmt | CaptureKind::Type | CaptureKind::Pat => {
+            let mut end = pos;
+            while end < input.len() {
+                let kind = &input[end].kind;
+                let stops = matches!(
+                    kind,
+                    TokenKind::Comma | TokenKind::Semicolon | TokenKind::FatArrow
+                ) || Some(kind) == follow;
+                if stops {
+                    break;
+                }
+                end = tree_end(input, end)?;
+            }
+            (end > pos).then_some(end)
+        }
+    }
+}
+
+fn fragment_names(matchers: &[Matcher]) -> Vec<String> {
+    let mut names = Vec::new();
+    for matcher in matchers {
+        match matcher {
+            Matcher::Token(_) => {}
+            Matcher::Fragment { name, .. } => names.push(name.clone()),
+            Matcher::Repeat { matchers, .. } => names.extend(fragment_names(matchers)),
+        }
+    }
+    names
+}
+
+impl MantraRule {
+    /// Names introduced by `let`/`let mut` in the transcriber body; these
+    /// are renamed per expansion so they cannot capture call-site names
+    pub fn local_bindings(&self) -> Vec<String> {
+        fn collect(body: &[Transcriber], names: &mut Vec<String>) {
+            let mut prev_let = false;
+            for item in body {
+                match item {
+                    Transcriber::Token(tok) => match &tok.kind {
+                        TokenKind::Let => prev_let = true,
+                        TokenKind::Mut if prev_let => {}
+                        TokenKind::Identifier(name) if prev_let => {
+                            if !names.contains(name) {
+                                names.push(name.clone());
+                            }
+                            prev_let = false;
+                        }
+                        _ => prev_let = false,
+                    },
+                    Transcriber::Var(_) => prev_let = false,
+                    Transcriber::Repeat { body, .. } => {
+                        prev_let = false;
+                        collect(body, names);
+                    }
+                }
+            }
+        }
+        let mut names = Vec::new();
+        collect(&self.transcriber, &mut names);
+        names
+    }
+
+    /// Substitute bindings into the transcriber. Definition tokens take the
+    /// call-site span; identifiers in `renames` get their hygienic names
+    pub fn transcribe(
+        &self,
+        bindings: &HashMap<String, Binding>,
+        renames: &HashMap<String, String>,
+        call_site: Span,
+    ) -> Result<Vec<Token>, MacroError> {
+        let mut out = Vec::new();
+        transcribe_into(&self.transcriber, bindings, renames, call_site, &mut out)?;
+        Ok(out)
+    }
+}
+
+fn transcribe_into(
+    body: &[Transcriber],
+    bindings: &HashMap<String, Binding>,
+    renames: &HashMap<String, String>,
+    call_site: Span,
+    out: &mut Vec<Token>,
+) -> Result<(), MacroError> {
+    for item in body {
+        match item {
+            Transcriber::Token(tok) => {
+                let mut tok = tok.clone();
+                tok.span = call_site;
+                if let TokenKind::Identifier(name) = &tok.kind {
+                    if let Some(renamed) = renames.get(name) {
+                        tok.lexeme = renamed.clone();
+                        tok.kind = TokenKind::Identifier(renamed.clone());
+                    }
+                }
+                out.push(tok);
+            }
+            Transcriber::Var(name) => match bindings.get(name) {
+                Some(Binding::Single(tokens)) => out.extend(tokens.iter().cloned()),
+                Some(Binding::Seq(_)) => {
+                    return Err(invalid(
+                        &format!("`${}` is still repeating at this depth", name),
+                        call_site,
+                    ))
+                }
+                None => {
+                    return Err(invalid(
+                        &format!("`${}` is not bound by the matcher", name),
+                        call_site,
+                    ))
+                }
+            },
+            Transcriber::Repeat { body, separator } => {
+                let count = repeat_count(body, bindings)
+                    .ok_or_else(|| invalid("repetition uses no repeating `$var`", call_site))?;
+                for i in 0..count {
+                    if i > 0 {
+                        if let Some(sep) = separator {
+                            let mut sep = sep.clone();
+                            sep.span = call_site;
+                            out.push(sep);
+                        }
+                    }
+                    let mut scope = bindings.clone();
+                    for (name, binding) in bindings {
+                        if let Binding::Seq(seq) = binding {
+                            if let Some(inner) = seq.get(i).and_then(|it| it.get(name)) {
+                                scope.insert(name.clone(), inner.clone());
+                            }
+                        }
+                    }
+                    transcribe_into(body, &scope, renames, call_site, out)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Iteration count of a repetition: the length of the first repeating
+/// variable it mentions
+fn repeat_count(body: &[Transcriber], bindings: &HashMap<String, Binding>) -> Option<usize> {
+    body.iter().find_map(|item| match item {
+        Transcriber::Var(name) => match bindings.get(name) {
+            Some(Binding::Seq(seq)) => Some(seq.len()),
+            _ => None,
+        },
+        Transcriber::Repeat { body, .. } => repeat_count(body, bindings),
+        Transcriber::Token(_) => None,
+    })
+}
+
+/// Index just past the token tree starting at `pos`
+pub fn tree_end(tokens: &[Token], pos: usize) -> Option<usize> {
+    let first = tokens.get(pos)?;
+    if !is_open(&first.kind) {
+        return (first.kind != TokenKind::Eof).then_some(pos + 1);
+    }
+    let mut depth = 0usize;
+    for (i, tok) in tokens.iter().enumerate().skip(pos) {
+        if is_open(&tok.kind) {
+            depth += 1;
+        } else if is_close(&tok.kind) {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i + 1);
+            }
+        }
+    }
+    None
+}
+
+/// Like [`tree_end`], but the tree must be delimited
+fn delimited_end(tokens: &[Token], pos: usize) -> Option<usize> {
+    if !is_open(&tokens.get(pos)?.kind) {
+        return None;
+    }
+    tree_end(tokens, pos)
+}
+
+fn is_open(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::LeftParen | TokenKind::LeftBrace | TokenKind::LeftBracket
+    )
+}
+
+fn is_close(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::RightParen | TokenKind::RightBrace | TokenKind::RightBracket
+    )
+}
+
+fn synthetic(kind: TokenKind, lexeme: &str, span: Span) -> Token {
+    Token {
+        kind,
+        span,
+        lexeme: lexeme.to_string(),
+    }
+}
+
+fn token_span(tokens: &[Token], pos: usize, fallback: Span) -> Span {
+    tokens.get(pos).map(|t| t.span).unwrap_or(fallback)
+}
+
+fn invalid(message: &str, span: Span) -> MacroError {
+    MacroError::InvalidDefinition {
+        message: message.to_string(),
+        span,
+    }
+}
@@ -366,7 +366,12 @@ impl JnanaMarga {
     /// Fold a binary operation on constants
     fn fold_binary_op(&self, op: BinaryOp, left: &MirConstant, right: &MirConstant) -> Option<MirConstant> {
         match (left, right) {
-            (MirConstant::Int(l, size), MirConstant::Int(r, _)) => {
+            (MirConstant::Int(l, size), MirConstant::Int(r, rsize)) if size == rsize => {
+                // A `U64` from 2^63 up is kept negative, which signed
+                // arithmetic would misread
+                if !size.is_signed() && (*l < 0 || *r < 0) {
+                    return None;
+                }
                 let result = match op {
                     BinaryOp::Add => l.checked_add(*r)?,
                     BinaryOp::Sub => l.checked_sub(*r)?,
@@ -386,7 +391,8 @@ impl JnanaMarga {
                     BinaryOp::Ge => return Some(MirConstant::Bool(l >= r)),
                     _ => return None,
                 };
-                Some(MirConstant::Int(result, *size))
+                // An overflow is left to the program, to wrap or trap
+                size.fits(result).then_some(MirConstant::Int(result, *size))
             }
             (MirConstant::Bool(l), MirConstant::Bool(r)) => {
                 let result = match op {
@@ -406,9 +412,14 @@ impl JnanaMarga {
     /// Fold a unary operation on a constant
     fn fold_unary_op(&self, op: UnaryOp, val: &MirConstant) -> Option<MirConstant> {
         match (op, val) {
-            (UnaryOp::Neg, MirConstant::Int(n, size)) => Some(MirConstant::Int(-n, *size)),
+            (UnaryOp::Neg, MirConstant::Int(n, size)) => n
+                .checked_neg()
+                .filter(|n| size.fits(*n))
+                .map(|n| MirConstant::Int(n, *size)),
             (UnaryOp::Not, MirConstant::Bool(b)) => Some(MirConstant::Bool(!b)),
-            (UnaryOp::Not, MirConstant::Int(n, size)) => Some(MirConstant::Int(!n, *size)),
+            (UnaryOp::Not, MirConstant::Int(n, size)) => {
+                Some(MirConstant::Int(size.wrap(!n), *size))
+            }
             _ => None,
         }
     }
@@ -4,9 +4,17 @@
 //! This is the core lowering pass that transforms high-level constructs
 //! into a simpler form suitable for optimization and code generation.
 
+use super::decision::{Access, Decision, DecisionCompiler, Test, TypeShapes};
+use super::layout::EnumLayout;
 use super::types::*;
+use crate::lexer::{Affix, Span};
 use crate::parser::ast;
-use std::collections::HashMap;
+use crate::parser::format::{FormatKind, FormatSpec};
+use crate::semantics::attributes::inline_hint;
+use crate::semantics::borrow::OwnershipKind;
+use crate::semantics::closure::{analyze_captures, closure_name, Capture, CaptureMode};
+use crate::semantics::prelude;
+use std::collections::{HashMap, HashSet};
 
 /// MIR Builder - Lowers AST to MIR
 pub struct MirBuilder {
@@ -24,6 +32,102 @@ pub struct MirBuilder {
     blocks: Vec<MirBasicBlock>,
     /// Locals list
     locals: Vec<MirLocal>,
+    /// Method name to mangled impl function names (for method call lowering)
+    method_targets: HashMap<String, Vec<String>>,
+    /// Type of the impl block being lowered (resolves `Self::` paths)
+    current_impl: Option<String>,
+    /// Symbol of the function being lowered (names its closures)
+    function_name: String,
+    /// Ownership of annotated locals, for closure capture modes
+    ownership: HashMap<String, OwnershipKind>,
+    /// Captured variables whose local holds the variable's address
+    by_ref_captures: HashSet<String>,
+    /// Closure bodies still to be lowered as functions of their own
+    pending_closures: Vec<PendingClosure>,
+    /// Values of `sthira`/`static` items, lowered at each use
+    constants: HashMap<String, ast::Expr>,
+    /// Constants whose value is being lowered (guards against cycles)
+    expanding: Vec<String>,
+    /// Field and variant order of declared types, for match lowering
+    shapes: TypeShapes,
+    /// Loops enclosing the code being lowered, innermost last
+    loops: Vec<LoopScope>,
+    /// Declared return types of functions and methods by symbol, for the
+    /// types of calls
+    return_types: HashMap<String, MirType>,
+    /// Enum definitions, the prelude's included, laid out per use
+    enums: HashMap<String, ast::TypeDef>,
+    /// Fields of each struct, for the types of field accesses
+    struct_fields: HashMap<String, Vec<ast::Field>>,
+    /// `From` impls: target type, source type and the symbol of `from`
+    conversions: Vec<(String, MirType, String)>,
+    /// Declared return type of the function being lowered, for `?`
+    return_type: MirType,
+    /// What integer overflow does in the functions built
+    overflow: Overflow,
+    /// Names and expressions lowered to a placeholder value, as nothing
+    /// here gives them one; const evaluation does not run code with any
+    unresolved: Vec<String>,
+    /// Span of the statement being lowered
+    stmt_span: Option<Span>,
+    /// Statement spans of the instructions emitted so far
+    spans: HashMap<(usize, usize), Span>,
+    /// What could not be lowered at all, which fails the build
+    errors: Vec<LoweringError>,
+}
+
+/// Code lowering could not give, as no code would be right
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoweringError {
+    pub message: String,
+    /// Statement it is in, where known
+    pub span: Option<Span>,
+}
+
+impl std::fmt::Display for LoweringError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "{}:{}: {}", span.line, span.column, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Where `break` and `continue` jump to for one loop
+struct LoopScope {
+    label: Option<String>,
+    continue_block: usize,
+    exit_block: usize,
+    /// Local receiving the value of `break value`
+    result: usize,
+}
+
+/// How a `cala x : e` loop walks its iterable
+enum Iteration {
+    /// Array, slice or `Sūci`, by position
+    Sequence,
+    /// Range value, from its start to its end
+    Range,
+    /// Any other type, through its `next` method at this symbol
+    Next(String),
+}
+
+/// One arm of a `yad`: a block from a statement or an expression
+#[derive(Clone, Copy)]
+enum Branch<'a> {
+    Block(&'a ast::Block),
+    Expr(&'a ast::Expr),
+}
+
+/// A closure whose body is lowered after the function defining it
+struct PendingClosure {
+    name: String,
+    params: Vec<ast::Parameter>,
+    body: ast::Expr,
+    /// Captures with their ownership, in environment order
+    captures: Vec<(Capture, OwnershipKind)>,
+    env: MirType,
+    span: Span,
 }
 
 impl MirBuilder {
@@ -36,9 +140,57 @@ impl MirBuilder {
             var_map: HashMap::new(),
             blocks: Vec::new(),
             locals: Vec::new(),
+            method_targets: HashMap::new(),
+            current_impl: None,
+            function_name: String::new(),
+            ownership: HashMap::new(),
+            by_ref_captures: HashSet::new(),
+            pending_closures: Vec::new(),
+            constants: HashMap::new(),
+            expanding: Vec::new(),
+            shapes: TypeShapes::prelude(),
+            loops: Vec::new(),
+            return_types: HashMap::new(),
+            enums: prelude::types()
+                .into_iter()
+                .map(|typedef| (typedef.name.name.clone(), typedef))
+                .collect(),
+            struct_fields: HashMap::new(),
+            conversions: Vec::new(),
+            return_type: MirType::Unit,
+            overflow: Overflow::Wrap,
+            unresolved: Vec::new(),
+            stmt_span: None,
+            spans: HashMap::new(),
+            errors: Vec::new(),
         }
     }
 
+    /// Build functions whose integer arithmetic overflows as `overflow` says
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// What was lowered to a placeholder since the last call, such as an
+    /// identifier naming nothing in the items indexed
+    pub fn take_unresolved(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.unresolved)
+    }
+
+    /// What could not be lowered since the last call
+    pub fn take_errors(&mut self) -> Vec<LoweringError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Fail the build over what is being lowered
+    fn error(&mut self, message: String) {
+        self.errors.push(LoweringError {
+            message,
+            span: self.stmt_span,
+        });
+    }
+
     /// Build MIR from AST
     pub fn build(&mut self, ast: &ast::Ast) -> MirModule {
         let mut module = MirModule {
@@ -48,62 +200,234 @@ impl MirBuilder {
             types: Vec::new(),
         };
 
+        self.index_methods(ast);
+        for item in &ast.items {
+            self.build_item(item, &mut module);
+        }
+
+        module
+    }
+
+    /// Index impl methods so method calls can be lowered to direct calls,
+    /// constants so their uses can be lowered to their values, type
+    /// definitions so patterns can be lowered to field and variant indices,
+    /// and return types so calls can be typed
+    pub fn index_methods(&mut self, ast: &ast::Ast) {
+        // Enums first, as any return type may name one
+        for item in &ast.items {
+            if let ast::Item::TypeDef(typedef) = item {
+                match &typedef.body {
+                    ast::TypeBody::Enum(_) => {
+                        self.enums
+                            .insert(typedef.name.name.clone(), typedef.clone());
+                    }
+                    ast::TypeBody::Struct(fields) => {
+                        self.struct_fields
+                            .insert(typedef.name.name.clone(), fields.clone());
+                    }
+                    ast::TypeBody::Alias(_) => {}
+                }
+            }
+        }
         for item in &ast.items {
             match item {
+                ast::Item::TypeDef(typedef) => self.shapes.add(typedef),
                 ast::Item::Function(func) => {
-                    if let Some(mir_func) = self.build_function(func) {
-                        module.functions.push(mir_func);
+                    if let Some(ty) = &func.return_type {
+                        let ty = self.convert_type(ty);
+                        self.return_types.insert(func.name.name.clone(), ty);
                     }
                 }
-                ast::Item::TypeDef(typedef) => {
-                    if let Some(mir_type) = self.build_typedef(typedef) {
-                        module.types.push(mir_type);
+                ast::Item::Constant(const_def) => {
+                    self.constants
+                        .insert(const_def.name.name.clone(), const_def.value.clone());
+                }
+                ast::Item::Impl(impl_def) => {
+                    let owner = impl_owner_name(&impl_def.self_ty);
+                    if let Some(source) = conversion_source(impl_def) {
+                        let source_type = self.convert_type(source);
+                        self.conversions.push((
+                            owner.clone(),
+                            source_type,
+                            conversion_symbol(&owner, source),
+                        ));
+                    }
+                    for method in &impl_def.methods {
+                        let symbol = method_symbol(impl_def, &owner, &method.name.name);
+                        self.register_method_target(&method.name.name, &owner);
+                        if let Some(ty) = &method.return_type {
+                            let ty = match self.convert_type(ty) {
+                                MirType::Named(name) if name == "Self" => {
+                                    MirType::Named(owner.clone())
+                                }
+                                ty => ty,
+                            };
+                            self.return_types.insert(symbol, ty);
+                        }
                     }
                 }
-                ast::Item::Constant(const_def) => {
-                    if let Some(global) = self.build_const(const_def) {
-                        module.globals.push(global);
+                ast::Item::Trait(trait_def) => {
+                    for method in trait_def
+                        .methods
+                        .iter()
+                        .filter(|m| m.default_body.is_some())
+                    {
+                        self.register_method_target(&method.name.name, &trait_def.name.name);
                     }
                 }
                 _ => {}
             }
         }
+    }
 
-        module
+    /// Lower one item into `module`; `index_methods` must have run first
+    pub fn build_item(&mut self, item: &ast::Item, module: &mut MirModule) {
+        match item {
+            // Lowered per instance once monomorphization has named them
+            ast::Item::Function(func) if !func.generics.is_empty() => {}
+            ast::Item::Function(func) => {
+                if let Some(mir_func) = self.build_function(func) {
+                    module.functions.push(mir_func);
+                }
+                self.build_pending_closures(module);
+            }
+            ast::Item::TypeDef(typedef) => {
+                if let Some(mir_type) = self.build_typedef(typedef) {
+                    module.types.push(mir_type);
+                }
+            }
+            ast::Item::Constant(const_def) => {
+                if let Some(global) = self.build_const(const_def) {
+                    module.globals.push(global);
+                }
+            }
+            ast::Item::Impl(impl_def) => {
+                let owner = impl_owner_name(&impl_def.self_ty);
+                self.current_impl = Some(owner.clone());
+                for method in &impl_def.methods {
+                    if let Some(mut mir_func) = self.build_function(method) {
+                        mir_func.name = method_symbol(impl_def, &owner, &method.name.name);
+                        module.functions.push(mir_func);
+                    }
+                    self.build_pending_closures(module);
+                }
+                self.current_impl = None;
+            }
+            ast::Item::Trait(trait_def) => {
+                // Default methods are lowered once; MIR values are untyped
+                for method in &trait_def.methods {
+                    let Some(body) = &method.default_body else {
+                        continue;
+                    };
+                    let func = ast::FunctionDef {
+                        name: method.name.clone(),
+                        generics: method.generics.clone(),
+                        receiver: method.receiver,
+                        params: method.params.clone(),
+                        return_type: method.return_type.clone(),
+                        where_clauses: method.where_clauses.clone(),
+                        preconditions: Vec::new(),
+                        postconditions: Vec::new(),
+                        body: body.clone(),
+                        is_const: false,
+                        visibility: trait_def.visibility,
+                        attributes: method.attributes.clone(),
+                        span: method.span,
+                    };
+                    if let Some(mut mir_func) = self.build_function(&func) {
+                        mir_func.name = mangle_method(&trait_def.name.name, &method.name.name);
+                        module.functions.push(mir_func);
+                    }
+                    self.build_pending_closures(module);
+                }
+            }
+            _ => {}
+        }
     }
 
-    /// Build MIR for a function
-    fn build_function(&mut self, func: &ast::FunctionDef) -> Option<MirFunction> {
-        // Reset state for new function
+    /// Value of constant `name`, marked as being expanded until the caller
+    /// pops it; `None` for other names and for a constant defined through
+    /// itself, which type checking reports
+    fn constant_value(&mut self, name: &str) -> Option<ast::Expr> {
+        if self.expanding.iter().any(|n| n == name) {
+            return None;
+        }
+        let value = self.constants.get(name)?.clone();
+        self.expanding.push(name.to_string());
+        Some(value)
+    }
+
+    /// Reset per-function state before lowering the function `name`
+    fn start_function(&mut self, name: String) {
         self.next_local = 0;
         self.next_block = 0;
         self.var_map.clear();
         self.blocks.clear();
         self.locals.clear();
+        self.ownership.clear();
+        self.by_ref_captures.clear();
+        self.loops.clear();
+        self.stmt_span = None;
+        self.spans.clear();
+        self.function_name = name;
+    }
 
-        // Build params
-        let params: Vec<MirParam> = func
-            .params
-            .iter()
-            .enumerate()
-            .map(|(i, p)| {
-                let ty = self.convert_type(&p.ty);
-                // Register parameter in var_map
-                let local_idx = self.alloc_local(ty.clone(), Some(p.name.name.clone()));
-                self.var_map.insert(p.name.name.clone(), local_idx);
-                MirParam {
-                    index: i,
-                    ty,
-                    karaka: p.karaka,
-                }
-            })
-            .collect();
+    /// Build MIR for a function
+    fn build_function(&mut self, func: &ast::FunctionDef) -> Option<MirFunction> {
+        // Reset state for new function
+        let name = match &self.current_impl {
+            Some(owner) => mangle_method(owner, &func.name.name),
+            None => func.name.name.clone(),
+        };
+        self.start_function(name);
 
         let return_type = func
             .return_type
             .as_ref()
             .map(|t| self.convert_type(t))
             .unwrap_or(MirType::Unit);
+        self.return_type = return_type.clone();
+        // An enum is returned from a local of its own, `_0`, which the
+        // epilogue loads into the return registers. So is anything else when
+        // the first parameter is an enum, which is more than the one word
+        // `_0` is read back as.
+        let first_param = match func.receiver {
+            Some(_) => None,
+            None => func.params.first().map(|p| self.convert_type(&p.ty)),
+        };
+        if matches!(return_type, MirType::Enum { .. })
+            || matches!(first_param, Some(MirType::Enum { .. }))
+        {
+            self.alloc_local(return_type.clone(), None);
+        }
+
+        // Receiver (sva) is passed as the first argument
+        let mut params = Vec::new();
+        if func.receiver.is_some() {
+            let local_idx = self.alloc_local(MirType::Int(IntSize::I64), Some("sva".to_string()));
+            self.var_map.insert("sva".to_string(), local_idx);
+            params.push(MirParam {
+                index: local_idx,
+                ty: MirType::Int(IntSize::I64),
+                karaka: None,
+            });
+        }
+        let offset = params.len();
+
+        // Build params
+        for p in &func.params {
+            let ty = self.convert_type(&p.ty);
+            // Register parameter in var_map
+            let local_idx = self.alloc_local(ty.clone(), Some(p.name.name.clone()));
+            self.var_map.insert(p.name.name.clone(), local_idx);
+            self.ownership
+                .insert(p.name.name.clone(), OwnershipKind::of_type(&p.ty));
+            params.push(MirParam {
+                index: local_idx,
+                ty,
+                karaka: p.karaka,
+            });
+        }
 
         // Create entry block
         let entry_block_id = self.alloc_block();
@@ -114,8 +438,62 @@ impl MirBuilder {
         });
         self.current_block = 0;
 
-        // Lower function body
-        self.lower_block(&func.body);
+        // Postconditions speak of the arguments as passed, so they are kept
+        // apart from the parameters, which the body may assign and the
+        // result may share a local with
+        let mut entry_values = Vec::new();
+        if !func.postconditions.is_empty() {
+            for p in &func.params {
+                let local = self.var_map[&p.name.name];
+                let ty = self.locals[local].ty.clone();
+                let copy = self.alloc_local(ty, None);
+                self.emit_instruction(MirInstruction::Assign {
+                    dest: MirPlace {
+                        local: copy,
+                        projection: vec![],
+                    },
+                    value: MirRvalue::Use(MirOperand::Copy(MirPlace {
+                        local,
+                        projection: vec![],
+                    })),
+                });
+                entry_values.push((p.name.name.clone(), copy));
+            }
+        }
+        self.emit_contract_checks(&func.preconditions, "precondition");
+
+        // Lower function body; a tail expression is returned like `phera`
+        if func.return_type.is_some() && func.body.tail().is_some() {
+            let value = self.lower_block_value(&func.body);
+            self.emit_instruction(MirInstruction::Assign {
+                dest: MirPlace {
+                    local: 0,
+                    projection: vec![],
+                },
+                value,
+            });
+            self.set_terminator(MirTerminator::Return);
+        } else {
+            self.lower_block(&func.body);
+        }
+
+        // Every return passes through the postconditions, with the result
+        // in `_0`
+        if !func.postconditions.is_empty() {
+            let exit = self.alloc_block();
+            for block in &mut self.blocks {
+                if matches!(block.terminator, MirTerminator::Return) {
+                    block.terminator = MirTerminator::Goto { target: exit };
+                }
+            }
+            self.start_block(exit);
+            self.var_map.extend(entry_values);
+            if return_type != MirType::Unit {
+                self.var_map.insert("phala".to_string(), 0);
+            }
+            self.emit_contract_checks(&func.postconditions, "postcondition");
+            self.set_terminator(MirTerminator::Return);
+        }
 
         // Build karaka hints from parameters
         let mut karaka_hints = HashMap::new();
@@ -130,7 +508,7 @@ impl MirBuilder {
                     ast::Karaka::Adhikarana => RegisterClass::CalleeSaved, // Location = stable
                 };
                 karaka_hints.insert(
-                    i,
+                    params[offset + i].index,
                     KarakaHint {
                         karaka,
                         register_class: reg_class,
@@ -146,9 +524,276 @@ impl MirBuilder {
             blocks: std::mem::take(&mut self.blocks),
             locals: std::mem::take(&mut self.locals),
             karaka_hints,
+            inline: inline_hint(&func.attributes),
+            overflow: self.overflow,
+            spans: std::mem::take(&mut self.spans),
         })
     }
 
+    /// Trap unless each of `contracts` holds, naming the one that does not
+    fn emit_contract_checks(&mut self, contracts: &[ast::Contract], kind: &str) {
+        for contract in contracts {
+            let condition = self.lower_expr_to_operand(&contract.condition);
+            let message = format!(
+                "{}:{}: {} `{}` of `{}` does not hold",
+                contract.span.line, contract.span.column, kind, contract.text, self.function_name
+            );
+            self.emit_instruction(MirInstruction::Assert { condition, message });
+        }
+    }
+
+    /// Lower the closures defined by the last function into `module`
+    ///
+    /// Closures defined inside closures are lowered in turn.
+    fn build_pending_closures(&mut self, module: &mut MirModule) {
+        while !self.pending_closures.is_empty() {
+            for closure in std::mem::take(&mut self.pending_closures) {
+                let mir_func = self.build_closure(closure);
+                module.functions.push(mir_func);
+            }
+        }
+    }
+
+    /// Build the function holding a closure's code
+    ///
+    /// Its first parameter is the environment; the captures are read from
+    /// it into locals of their own before the body runs.
+    fn build_closure(&mut self, closure: PendingClosure) -> MirFunction {
+        self.start_function(closure.name.clone());
+
+        let env_ptr = MirType::Ptr(Box::new(closure.env.clone()));
+        let env_local = self.alloc_local(env_ptr.clone(), None);
+        let mut params = vec![MirParam {
+            index: env_local,
+            ty: env_ptr,
+            karaka: None,
+        }];
+        for p in &closure.params {
+            let ty = match p.ty {
+                ast::Type::Inferred => MirType::Int(IntSize::I64),
+                _ => self.convert_type(&p.ty),
+            };
+            let local_idx = self.alloc_local(ty.clone(), Some(p.name.name.clone()));
+            self.var_map.insert(p.name.name.clone(), local_idx);
+            self.ownership
+                .insert(p.name.name.clone(), OwnershipKind::of_type(&p.ty));
+            params.push(MirParam {
+                index: local_idx,
+                ty,
+                karaka: p.karaka,
+            });
+        }
+
+        let entry_block_id = self.alloc_block();
+        self.blocks.push(MirBasicBlock {
+            id: entry_block_id,
+            instructions: Vec::new(),
+            terminator: MirTerminator::Return,
+        });
+        self.current_block = 0;
+
+        // Captures follow the code address in the environment
+        let capture_types = match &closure.env {
+            MirType::Closure { captures, .. } => captures.clone(),
+            _ => Vec::new(),
+        };
+        for (i, ((capture, ownership), ty)) in
+            closure.captures.iter().zip(capture_types).enumerate()
+        {
+            let slot = self.alloc_local(MirType::Ptr(Box::new(ty.clone())), None);
+            self.emit_instruction(MirInstruction::Assign {
+                dest: MirPlace {
+                    local: slot,
+                    projection: vec![],
+                },
+                value: MirRvalue::BinaryOp {
+                    op: BinaryOp::Add,
+                    left: MirOperand::Copy(MirPlace {
+                        local: env_local,
+                        projection: vec![],
+                    }),
+                    right: MirOperand::Constant(MirConstant::Int(
+                        ((i + 1) * 8) as i64,
+                        IntSize::I64,
+                    )),
+                },
+            });
+            let local_idx = self.alloc_local(ty, Some(capture.name.clone()));
+            self.emit_instruction(MirInstruction::Load {
+                dest: MirPlace {
+                    local: local_idx,
+                    projection: vec![],
+                },
+                ptr: MirOperand::Copy(MirPlace {
+                    local: slot,
+                    projection: vec![],
+                }),
+            });
+            self.var_map.insert(capture.name.clone(), local_idx);
+            self.ownership.insert(capture.name.clone(), *ownership);
+            if capture.mode.is_by_ref() {
+                self.by_ref_captures.insert(capture.name.clone());
+            }
+        }
+
+        // The body's value is returned like `phera`
+        match &closure.body {
+            ast::Expr::Block(block) if block.tail().is_none() => self.lower_block(block),
+            body => self.lower_stmt(&ast::Stmt::Return {
+                value: Some(body.clone()),
+                span: closure.span,
+            }),
+        }
+
+        MirFunction {
+            name: closure.name,
+            params,
+            return_type: MirType::Int(IntSize::I64),
+            blocks: std::mem::take(&mut self.blocks),
+            locals: std::mem::take(&mut self.locals),
+            karaka_hints: HashMap::new(),
+            inline: InlineHint::Auto,
+            overflow: self.overflow,
+            spans: std::mem::take(&mut self.spans),
+        }
+    }
+
+    /// Lower a closure expression to a pointer to its environment
+    ///
+    /// The environment is built in the current frame; the body is queued
+    /// and lowered as a function once the current function is done.
+    fn lower_closure(
+        &mut self,
+        params: &[ast::Parameter],
+        body: &ast::Expr,
+        span: Span,
+    ) -> MirRvalue {
+        let name = closure_name(&self.function_name, span);
+        let analysis = analyze_captures(params, body, |var| {
+            self.var_map.contains_key(var).then(|| {
+                self.ownership
+                    .get(var)
+                    .copied()
+                    .unwrap_or(OwnershipKind::Affine)
+            })
+        });
+
+        let mut operands = Vec::new();
+        let mut capture_types = Vec::new();
+        let mut captures = Vec::new();
+        for capture in analysis.captures {
+            let local = self.var_map[&capture.name];
+            let place = MirPlace {
+                local,
+                projection: vec![],
+            };
+            let ty = self.locals[local].ty.clone();
+            if !capture.mode.is_by_ref() {
+                operands.push(self.variable_operand(&capture.name, local));
+                capture_types.push(ty);
+            } else if self.by_ref_captures.contains(&capture.name) {
+                // Already an address: a captured capture
+                operands.push(MirOperand::Copy(place));
+                capture_types.push(ty);
+            } else {
+                let addr = self.alloc_local(MirType::Ptr(Box::new(ty.clone())), None);
+                self.emit_instruction(MirInstruction::Assign {
+                    dest: MirPlace {
+                        local: addr,
+                        projection: vec![],
+                    },
+                    value: MirRvalue::Ref {
+                        mutable: capture.mode == CaptureMode::MutRef,
+                        place,
+                    },
+                });
+                operands.push(MirOperand::Copy(MirPlace {
+                    local: addr,
+                    projection: vec![],
+                }));
+                capture_types.push(MirType::Ptr(Box::new(ty)));
+            }
+            let ownership = self
+                .ownership
+                .get(&capture.name)
+                .copied()
+                .unwrap_or(OwnershipKind::Affine);
+            captures.push((capture, ownership));
+        }
+
+        let env = MirType::Closure {
+            function: name.clone(),
+            captures: capture_types,
+        };
+        let env_local = self.alloc_local(env.clone(), None);
+        let env_place = MirPlace {
+            local: env_local,
+            projection: vec![],
+        };
+        self.emit_instruction(MirInstruction::Assign {
+            dest: env_place.clone(),
+            value: MirRvalue::Aggregate {
+                kind: AggregateKind::Closure {
+                    function: name.clone(),
+                },
+                operands,
+            },
+        });
+        self.pending_closures.push(PendingClosure {
+            name,
+            params: params.to_vec(),
+            body: body.clone(),
+            captures,
+            env,
+            span,
+        });
+
+        MirRvalue::Ref {
+            mutable: false,
+            place: env_place,
+        }
+    }
+
+    /// Call a closure value: its code gets the environment as first argument
+    fn lower_closure_call(&mut self, env: MirOperand, arg_ops: Vec<MirOperand>) -> MirRvalue {
+        let code = self.alloc_local(MirType::Int(IntSize::I64), None);
+        let code_place = MirPlace {
+            local: code,
+            projection: vec![],
+        };
+        self.emit_instruction(MirInstruction::Load {
+            dest: code_place.clone(),
+            ptr: env.clone(),
+        });
+        let mut args = vec![env];
+        args.extend(arg_ops);
+        self.lower_call(MirOperand::Copy(code_place), args)
+    }
+
+    /// Operand reading variable `name` held in `local`
+    ///
+    /// Variables a closure captured by reference are read through the
+    /// address its environment holds.
+    fn variable_operand(&mut self, name: &str, local: usize) -> MirOperand {
+        let place = MirPlace {
+            local,
+            projection: vec![],
+        };
+        if !self.by_ref_captures.contains(name) {
+            return MirOperand::Copy(place);
+        }
+        let value = self.alloc_local(MirType::Int(IntSize::I64), None);
+        let value_place = MirPlace {
+            local: value,
+            projection: vec![],
+        };
+        self.emit_instruction(MirInstruction::Load {
+            dest: value_place.clone(),
+            ptr: MirOperand::Copy(place),
+        });
+        MirOperand::Copy(value_place)
+    }
+
     /// Lower a block of statements
     fn lower_block(&mut self, block: &ast::Block) {
         for stmt in &block.stmts {
@@ -156,22 +801,40 @@ impl MirBuilder {
         }
     }
 
-    /// Lower a statement to MIR
+    /// Lower a statement to MIR, recording its span against the
+    /// instructions it emits
     fn lower_stmt(&mut self, stmt: &ast::Stmt) {
+        let outer = self.stmt_span.replace(stmt.span());
+        self.lower_stmt_kind(stmt);
+        self.stmt_span = outer;
+    }
+
+    fn lower_stmt_kind(&mut self, stmt: &ast::Stmt) {
         match stmt {
             ast::Stmt::Let {
                 name, ty, value, ..
             } => {
-                let mir_ty = ty
-                    .as_ref()
-                    .map(|t| self.convert_type(t))
-                    .unwrap_or(MirType::Int(IntSize::I64));
+                let mir_ty = match (ty, value) {
+                    (Some(t), _) => self.convert_type(t),
+                    (None, Some(value)) => self.value_type(value),
+                    (None, None) => MirType::Int(IntSize::I64),
+                };
 
-                let local_idx = self.alloc_local(mir_ty, Some(name.name.clone()));
+                let local_idx = self.alloc_local(mir_ty.clone(), Some(name.name.clone()));
                 self.var_map.insert(name.name.clone(), local_idx);
+                self.by_ref_captures.remove(&name.name);
+                let ownership = match (ty, value) {
+                    (Some(t), _) => OwnershipKind::of_type(t),
+                    (None, Some(ast::Expr::Literal(_))) => OwnershipKind::Copy,
+                    (None, _) => OwnershipKind::Affine,
+                };
+                self.ownership.insert(name.name.clone(), ownership);
 
                 if let Some(val_expr) = value {
-                    let rvalue = self.lower_expr_to_rvalue(val_expr);
+                    let rvalue = match self.lower_expr_to_rvalue(val_expr) {
+                        MirRvalue::Use(operand) => MirRvalue::Use(sized(operand, &mir_ty)),
+                        rvalue => rvalue,
+                    };
                     self.emit_instruction(MirInstruction::Assign {
                         dest: MirPlace {
                             local: local_idx,
@@ -196,6 +859,7 @@ impl MirBuilder {
                     });
                 }
                 self.set_terminator(MirTerminator::Return);
+                self.start_dead_block();
             }
 
             ast::Stmt::Expr(expr) => {
@@ -209,304 +873,460 @@ impl MirBuilder {
                 else_block,
                 ..
             } => {
-                let cond_operand = self.lower_expr_to_operand(condition);
+                self.lower_if(
+                    condition,
+                    Branch::Block(then_block),
+                    else_block.as_ref().map(Branch::Block),
+                    None,
+                );
+            }
 
-                let then_block_id = self.alloc_block();
-                let else_block_id = self.alloc_block();
-                let merge_block_id = self.alloc_block();
+            ast::Stmt::Loop {
+                label, kind, body, ..
+            } => {
+                self.lower_loop(label.as_ref(), kind, body);
+            }
 
-                // Branch on condition
-                self.set_terminator(MirTerminator::SwitchInt {
-                    discriminant: cond_operand,
-                    targets: vec![(1, then_block_id)], // 1 = true
-                    otherwise: else_block_id,
-                });
+            ast::Stmt::Break { label, value, .. } => {
+                let Some(scope) = self.find_loop(label.as_ref()) else {
+                    // Reported by the type checker
+                    return;
+                };
+                let (exit_block, result) = (self.loops[scope].exit_block, self.loops[scope].result);
+                if let Some(value) = value {
+                    let value = self.lower_expr_to_rvalue(value);
+                    self.emit_instruction(MirInstruction::Assign {
+                        dest: MirPlace {
+                            local: result,
+                            projection: vec![],
+                        },
+                        value,
+                    });
+                }
+                self.set_terminator(MirTerminator::Goto { target: exit_block });
+                self.start_dead_block();
+            }
 
-                // Then block
-                self.blocks.push(MirBasicBlock {
-                    id: then_block_id,
-                    instructions: Vec::new(),
-                    terminator: MirTerminator::Goto {
-                        target: merge_block_id,
-                    },
-                });
-                self.current_block = self.blocks.len() - 1;
-                self.lower_block(then_block);
-                self.set_terminator(MirTerminator::Goto {
-                    target: merge_block_id,
-                });
+            ast::Stmt::Continue { label, .. } => {
+                let Some(scope) = self.find_loop(label.as_ref()) else {
+                    return;
+                };
+                let target = self.loops[scope].continue_block;
+                self.set_terminator(MirTerminator::Goto { target });
+                self.start_dead_block();
+            }
+        }
+    }
 
-                // Else block
-                self.blocks.push(MirBasicBlock {
-                    id: else_block_id,
-                    instructions: Vec::new(),
-                    terminator: MirTerminator::Goto {
-                        target: merge_block_id,
-                    },
-                });
-                self.current_block = self.blocks.len() - 1;
-                if let Some(else_blk) = else_block {
-                    self.lower_block(else_blk);
-                }
-                self.set_terminator(MirTerminator::Goto {
-                    target: merge_block_id,
-                });
+    /// Lower a block and return its value (see [`ast::Block::tail`])
+    fn lower_block_value(&mut self, block: &ast::Block) -> MirRvalue {
+        let Some(tail) = block.tail() else {
+            self.lower_block(block);
+            return MirRvalue::Use(MirOperand::Constant(MirConstant::Unit));
+        };
+        for stmt in &block.stmts[..block.stmts.len() - 1] {
+            self.lower_stmt(stmt);
+        }
+        match tail {
+            ast::Stmt::Expr(expr) => self.lower_expr_to_rvalue(expr),
+            ast::Stmt::If {
+                condition,
+                then_block,
+                else_block,
+                ..
+            } => {
+                let result = self.alloc_local(MirType::Int(IntSize::I64), None);
+                self.lower_if(
+                    condition,
+                    Branch::Block(then_block),
+                    else_block.as_ref().map(Branch::Block),
+                    Some(result),
+                );
+                MirRvalue::Use(MirOperand::Copy(MirPlace {
+                    local: result,
+                    projection: vec![],
+                }))
+            }
+            ast::Stmt::Loop {
+                label, kind, body, ..
+            } => self.lower_loop(label.as_ref(), kind, body),
+            _ => unreachable!("Block::tail is an expression, yad or cala"),
+        }
+    }
 
-                // Merge block
-                self.blocks.push(MirBasicBlock {
-                    id: merge_block_id,
-                    instructions: Vec::new(),
-                    terminator: MirTerminator::Return,
-                });
-                self.current_block = self.blocks.len() - 1;
+    /// Lower a branch, assigning its value to `result` if given
+    fn lower_branch(&mut self, branch: Branch, result: Option<usize>) {
+        let Some(result) = result else {
+            match branch {
+                Branch::Block(block) => self.lower_block(block),
+                Branch::Expr(expr) => {
+                    let _ = self.lower_expr_to_rvalue(expr);
+                }
             }
+            return;
+        };
+        let value = match branch {
+            Branch::Block(block) => self.lower_block_value(block),
+            Branch::Expr(expr) => self.lower_expr_to_rvalue(expr),
+        };
+        self.emit_instruction(MirInstruction::Assign {
+            dest: MirPlace {
+                local: result,
+                projection: vec![],
+            },
+            value,
+        });
+    }
 
-            ast::Stmt::Loop { kind, body, .. } => {
-                let loop_header_id = self.alloc_block();
-                let loop_body_id = self.alloc_block();
-                let loop_exit_id = self.alloc_block();
+    /// Lower `yad`, statement or expression; with a `result` local, the
+    /// value of the branch taken is assigned to it
+    fn lower_if(
+        &mut self,
+        condition: &ast::Expr,
+        then_branch: Branch,
+        else_branch: Option<Branch>,
+        result: Option<usize>,
+    ) {
+        let cond_operand = self.lower_expr_to_operand(condition);
+
+        let then_block_id = self.alloc_block();
+        let else_block_id = self.alloc_block();
+        let merge_block_id = self.alloc_block();
+
+        // Branch on condition
+        self.set_terminator(MirTerminator::SwitchInt {
+            discriminant: cond_operand,
+            targets: vec![(1, then_block_id)], // 1 = true
+            otherwise: else_block_id,
+        });
 
-                self.set_terminator(MirTerminator::Goto {
-                    target: loop_header_id,
-                });
+        // Then block
+        self.blocks.push(MirBasicBlock {
+            id: then_block_id,
+            instructions: Vec::new(),
+            terminator: MirTerminator::Goto {
+                target: merge_block_id,
+            },
+        });
+        self.current_block = self.blocks.len() - 1;
+        self.lower_branch(then_branch, result);
+        self.set_terminator(MirTerminator::Goto {
+            target: merge_block_id,
+        });
 
-                match kind {
-                    ast::LoopKind::While { condition } => {
-                        // Header: check condition
-                        self.blocks.push(MirBasicBlock {
-                            id: loop_header_id,
-                            instructions: Vec::new(),
-                            terminator: MirTerminator::Return,
-                        });
-                        self.current_block = self.blocks.len() - 1;
-                        let cond = self.lower_expr_to_operand(condition);
-                        self.set_terminator(MirTerminator::SwitchInt {
-                            discriminant: cond,
-                            targets: vec![(1, loop_body_id)],
-                            otherwise: loop_exit_id,
-                        });
-                    }
-                    ast::LoopKind::Range {
-                        binding,
-                        start,
-                        end,
-                        ..
-                    } => {
-                        // Allocate loop variable
-                        let iter_local = self
-                            .alloc_local(MirType::Int(IntSize::I64), Some(binding.name.clone()));
-                        self.var_map.insert(binding.name.clone(), iter_local);
-
-                        // Initialize loop var with start
-                        let start_rval = self.lower_expr_to_rvalue(start);
-                        self.emit_instruction(MirInstruction::Assign {
-                            dest: MirPlace {
-                                local: iter_local,
-                                projection: vec![],
-                            },
-                            value: start_rval,
-                        });
+        // Else block
+        self.blocks.push(MirBasicBlock {
+            id: else_block_id,
+            instructions: Vec::new(),
+            terminator: MirTerminator::Goto {
+                target: merge_block_id,
+            },
+        });
+        self.current_block = self.blocks.len() - 1;
+        if let Some(else_branch) = else_branch {
+            self.lower_branch(else_branch, result);
+        }
+        self.set_terminator(MirTerminator::Goto {
+            target: merge_block_id,
+        });
 
-                        // Header: check i < end
-                        self.blocks.push(MirBasicBlock {
-                            id: loop_header_id,
-                            instructions: Vec::new(),
-                            terminator: MirTerminator::Return,
-                        });
-                        self.current_block = self.blocks.len() - 1;
+        // Merge block
+        self.blocks.push(MirBasicBlock {
+            id: merge_block_id,
+            instructions: Vec::new(),
+            terminator: MirTerminator::Return,
+        });
+        self.current_block = self.blocks.len() - 1;
+    }
 
-                        let end_op = self.lower_expr_to_operand(end);
-                        let iter_op = MirOperand::Copy(MirPlace {
-                            local: iter_local,
-                            projection: vec![],
-                        });
-                        let cmp_local = self.alloc_local(MirType::Bool, None);
-                        self.emit_instruction(MirInstruction::Assign {
-                            dest: MirPlace {
-                                local: cmp_local,
-                                projection: vec![],
-                            },
-                            value: MirRvalue::BinaryOp {
-                                op: BinaryOp::Lt,
-                                left: iter_op,
-                                right: end_op,
-                            },
-                        });
-                        self.set_terminator(MirTerminator::SwitchInt {
-                            discriminant: MirOperand::Copy(MirPlace {
-                                local: cmp_local,
-                                projection: vec![],
-                            }),
-                            targets: vec![(1, loop_body_id)],
-                            otherwise: loop_exit_id,
-                        });
-                    }
-                    ast::LoopKind::Infinite => {
-                        // Header just jumps to body
-                        self.blocks.push(MirBasicBlock {
-                            id: loop_header_id,
-                            instructions: Vec::new(),
-                            terminator: MirTerminator::Goto {
-                                target: loop_body_id,
-                            },
-                        });
-                    }
-                    ast::LoopKind::ForIn { binding: _binding, iterable: _iterable } => {
-                        // Simplified: treat like infinite loop for now
-                        self.blocks.push(MirBasicBlock {
-                            id: loop_header_id,
-                            instructions: Vec::new(),
-                            terminator: MirTerminator::Goto {
-                                target: loop_body_id,
-                            },
-                        });
-                    }
-                }
+    /// Lower `cala`, statement or expression, returning the value it
+    /// breaks with
+    fn lower_loop(
+        &mut self,
+        label: Option<&ast::Identifier>,
+        kind: &ast::LoopKind,
+        body: &ast::Block,
+    ) -> MirRvalue {
+        let loop_header_id = self.alloc_block();
+        let loop_body_id = self.alloc_block();
+        let loop_exit_id = self.alloc_block();
+        let result = self.alloc_local(MirType::Int(IntSize::I64), None);
+
+        self.set_terminator(MirTerminator::Goto {
+            target: loop_header_id,
+        });
 
-                // Body
+        // Local the latch steps by one: the binding of a range loop, or
+        // the hidden position of a loop over a sequence
+        let mut counter = None;
+        // Binding of a `cala x : e` loop and its value, assigned at the
+        // start of the body
+        let mut element = None;
+        match kind {
+            ast::LoopKind::While { condition } => {
+                // Header: check condition
                 self.blocks.push(MirBasicBlock {
-                    id: loop_body_id,
+                    id: loop_header_id,
                     instructions: Vec::new(),
-                    terminator: MirTerminator::Goto {
-                        target: loop_header_id,
-                    },
+                    terminator: MirTerminator::Return,
                 });
                 self.current_block = self.blocks.len() - 1;
-                self.lower_block(body);
-
-                // For range loops: increment the iterator
-                if let ast::LoopKind::Range { binding, .. } = kind {
-                    if let Some(&iter_local) = self.var_map.get(&binding.name) {
-                        let one = MirOperand::Constant(MirConstant::Int(1, IntSize::I64));
-                        let iter_op = MirOperand::Copy(MirPlace {
-                            local: iter_local,
-                            projection: vec![],
-                        });
-                        self.emit_instruction(MirInstruction::Assign {
-                            dest: MirPlace {
-                                local: iter_local,
-                                projection: vec![],
-                            },
-                            value: MirRvalue::BinaryOp {
-                                op: BinaryOp::Add,
-                                left: iter_op,
-                                right: one,
-                            },
-                        });
-                    }
-                }
-
-                self.set_terminator(MirTerminator::Goto {
-                    target: loop_header_id,
+                let cond = self.lower_expr_to_operand(condition);
+                self.set_terminator(MirTerminator::SwitchInt {
+                    discriminant: cond,
+                    targets: vec![(1, loop_body_id)],
+                    otherwise: loop_exit_id,
+                });
+            }
+            ast::LoopKind::Range {
+                binding,
+                start,
+                end,
+                ..
+            } => {
+                // Allocate loop variable
+                let iter_local =
+                    self.alloc_local(MirType::Int(IntSize::I64), Some(binding.name.clone()));
+                self.var_map.insert(binding.name.clone(), iter_local);
+                counter = Some(iter_local);
+
+                // Initialize loop var with start
+                let start_rval = self.lower_expr_to_rvalue(start);
+                self.emit_instruction(MirInstruction::Assign {
+                    dest: MirPlace {
+                        local: iter_local,
+                        projection: vec![],
+                    },
+                    value: start_rval,
                 });
 
-                // Exit
+                // Header: check i < end
                 self.blocks.push(MirBasicBlock {
-                    id: loop_exit_id,
+                    id: loop_header_id,
                     instructions: Vec::new(),
                     terminator: MirTerminator::Return,
                 });
                 self.current_block = self.blocks.len() - 1;
-            }
 
-            ast::Stmt::Break { .. } => {
-                // Would need loop exit block tracking - simplified
-                self.emit_instruction(MirInstruction::Nop);
-            }
-
-            ast::Stmt::Continue { .. } => {
-                // Would need loop header block tracking - simplified
-                self.emit_instruction(MirInstruction::Nop);
-            }
-
-            ast::Stmt::Match {
-                scrutinee, arms, ..
-            } => {
-                // Proper match lowering with pattern matching
-                let scrut_op = self.lower_expr_to_operand(scrutinee);
-
-                // Allocate temp for scrutinee
-                let scrut_local = self.alloc_local(MirType::Int(IntSize::I64), None);
+                let end_op = self.lower_expr_to_operand(end);
+                let iter_op = MirOperand::Copy(MirPlace {
+                    local: iter_local,
+                    projection: vec![],
+                });
+                let cmp_local = self.alloc_local(MirType::Bool, None);
                 self.emit_instruction(MirInstruction::Assign {
                     dest: MirPlace {
-                        local: scrut_local,
+                        local: cmp_local,
                         projection: vec![],
                     },
-                    value: MirRvalue::Use(scrut_op),
+                    value: MirRvalue::BinaryOp {
+                        op: BinaryOp::Lt,
+                        left: iter_op,
+                        right: end_op,
+                    },
                 });
-
-                // Create blocks for each arm and merge block
-                let merge_block_id = self.alloc_block();
-                let mut arm_blocks = Vec::new();
-
-                for _ in arms {
-                    arm_blocks.push(self.alloc_block());
-                }
-
-                // Default/otherwise block
-                let otherwise_block = self.alloc_block();
-
-                // Build switch targets from patterns
-                let mut switch_targets = Vec::new();
-                for (i, arm) in arms.iter().enumerate() {
-                    if let Some(value) = self.pattern_to_int(&arm.pattern) {
-                        switch_targets.push((value, arm_blocks[i]));
-                    }
-                }
-
-                // Set terminator to switch
                 self.set_terminator(MirTerminator::SwitchInt {
                     discriminant: MirOperand::Copy(MirPlace {
-                        local: scrut_local,
+                        local: cmp_local,
                         projection: vec![],
                     }),
-                    targets: switch_targets,
-                    otherwise: otherwise_block,
+                    targets: vec![(1, loop_body_id)],
+                    otherwise: loop_exit_id,
                 });
+            }
+            ast::LoopKind::Infinite => {
+                // Header just jumps to body
+                self.blocks.push(MirBasicBlock {
+                    id: loop_header_id,
+                    instructions: Vec::new(),
+                    terminator: MirTerminator::Goto {
+                        target: loop_body_id,
+                    },
+                });
+            }
+            ast::LoopKind::ForIn { binding, iterable } => match self.iteration(iterable) {
+                Iteration::Sequence => {
+                    // Walk the sequence by position: i = 0; while i < len
+                    let seq = self.sequence_place(iterable);
+                    let position = self.alloc_local(MirType::Int(IntSize::I64), None);
+                    self.emit_instruction(MirInstruction::Assign {
+                        dest: local_place(position),
+                        value: MirRvalue::Use(MirOperand::Constant(MirConstant::Int(
+                            0,
+                            IntSize::I64,
+                        ))),
+                    });
+                    counter = Some(position);
+
+                    self.start_block(loop_header_id);
+                    let len = self.assign_temp(MirRvalue::Len(seq.clone()));
+                    let more = self.assign_temp(MirRvalue::BinaryOp {
+                        op: BinaryOp::Lt,
+                        left: MirOperand::Copy(local_place(position)),
+                        right: MirOperand::Copy(local_place(len)),
+                    });
+                    self.set_terminator(MirTerminator::SwitchInt {
+                        discriminant: MirOperand::Copy(local_place(more)),
+                        targets: vec![(1, loop_body_id)],
+                        otherwise: loop_exit_id,
+                    });
 
-                // Generate each arm block
-                for (i, arm) in arms.iter().enumerate() {
-                    self.blocks.push(MirBasicBlock {
-                        id: arm_blocks[i],
-                        instructions: Vec::new(),
-                        terminator: MirTerminator::Goto {
-                            target: merge_block_id,
-                        },
+                    let binding_local =
+                        self.alloc_local(MirType::Int(IntSize::I64), Some(binding.name.clone()));
+                    self.var_map.insert(binding.name.clone(), binding_local);
+                    let value = MirRvalue::Index {
+                        base: MirOperand::Copy(seq),
+                        index: MirOperand::Copy(local_place(position)),
+                    };
+                    element = Some((binding_local, value));
+                }
+                Iteration::Range => {
+                    // The binding counts from the range's start to its end
+                    let range = self.sequence_place(iterable);
+                    let binding_local =
+                        self.alloc_local(MirType::Int(IntSize::I64), Some(binding.name.clone()));
+                    self.emit_instruction(MirInstruction::Load {
+                        dest: local_place(binding_local),
+                        ptr: MirOperand::Copy(range.clone()),
+                    });
+                    self.var_map.insert(binding.name.clone(), binding_local);
+                    counter = Some(binding_local);
+
+                    self.start_block(loop_header_id);
+                    let end = self.assign_temp(MirRvalue::Len(range));
+                    let more = self.assign_temp(MirRvalue::BinaryOp {
+                        op: BinaryOp::Lt,
+                        left: MirOperand::Copy(local_place(binding_local)),
+                        right: MirOperand::Copy(local_place(end)),
+                    });
+                    self.set_terminator(MirTerminator::SwitchInt {
+                        discriminant: MirOperand::Copy(local_place(more)),
+                        targets: vec![(1, loop_body_id)],
+                        otherwise: loop_exit_id,
+                    });
+                }
+                Iteration::Next(next) => {
+                    // Call `next(&mut it)` until it returns `Vikalpa::Śūnya`
+                    let iterator = match self.value_type(iterable) {
+                        MirType::Ref { .. } => self.lower_expr_to_operand(iterable),
+                        _ => {
+                            let place = match self.lower_expr_to_operand(iterable) {
+                                MirOperand::Copy(place) | MirOperand::Move(place) => place,
+                                operand => local_place(self.assign_temp(MirRvalue::Use(operand))),
+                            };
+                            let iterator = self.assign_temp(MirRvalue::Ref {
+                                mutable: true,
+                                place,
+                            });
+                            MirOperand::Copy(local_place(iterator))
+                        }
+                    };
+
+                    self.start_block(loop_header_id);
+                    let func = MirOperand::Constant(MirConstant::String(next));
+                    let MirRvalue::Use(MirOperand::Copy(item)) =
+                        self.lower_call(func, vec![iterator])
+                    else {
+                        unreachable!("calls yield their result local");
+                    };
+                    let tag = self.assign_temp(MirRvalue::Discriminant(item.clone()));
+                    let some = self
+                        .shapes
+                        .constructor("Vikalpa::Kiñcit")
+                        .map_or(1, |(_, variant)| variant);
+                    self.set_terminator(MirTerminator::SwitchInt {
+                        discriminant: MirOperand::Copy(local_place(tag)),
+                        targets: vec![(some as i64, loop_body_id)],
+                        otherwise: loop_exit_id,
                     });
-                    self.current_block = self.blocks.len() - 1;
 
-                    // Bind pattern variables
-                    self.bind_pattern_variables(&arm.pattern, scrut_local);
+                    let item_type = self
+                        .local_type(item.local)
+                        .enum_layout()
+                        .and_then(|layout| layout.field(some, 0))
+                        .map_or(MirType::Int(IntSize::I64), |(_, ty)| ty.clone());
+                    let binding_local = self.alloc_local(item_type, Some(binding.name.clone()));
+                    self.var_map.insert(binding.name.clone(), binding_local);
+                    let value =
+                        MirRvalue::Use(MirOperand::Copy(payload_place(item.local, some, 0)));
+                    element = Some((binding_local, value));
+                }
+            },
+        }
 
-                    // Evaluate guard if present
-                    if let Some(guard) = &arm.guard {
-                        let _guard_op = self.lower_expr_to_operand(guard);
-                        // If guard fails, jump to next arm or otherwise
-                        // Simplified: always proceed
-                    }
+        // Counted loops step in a latch block, where `continue` goes
+        let latch_id = match counter {
+            Some(_) => self.alloc_block(),
+            None => loop_header_id,
+        };
+        self.loops.push(LoopScope {
+            label: label.map(|l| l.name.clone()),
+            continue_block: latch_id,
+            exit_block: loop_exit_id,
+            result,
+        });
 
-                    // Lower arm body (it's an expression, not a block)
-                    let _body_rvalue = self.lower_expr_to_rvalue(&arm.body);
-                    self.set_terminator(MirTerminator::Goto {
-                        target: merge_block_id,
-                    });
-                }
+        // Body
+        self.blocks.push(MirBasicBlock {
+            id: loop_body_id,
+            instructions: Vec::new(),
+            terminator: MirTerminator::Goto {
+                target: loop_header_id,
+            },
+        });
+        self.current_block = self.blocks.len() - 1;
+        if let Some((binding_local, value)) = element {
+            self.emit_instruction(MirInstruction::Assign {
+                dest: local_place(binding_local),
+                value,
+            });
+        }
+        self.lower_block(body);
+        self.set_terminator(MirTerminator::Goto { target: latch_id });
+        self.loops.pop();
+
+        // Counted loops: step the counter
+        if let Some(counter) = counter {
+            self.blocks.push(MirBasicBlock {
+                id: latch_id,
+                instructions: Vec::new(),
+                terminator: MirTerminator::Goto {
+                    target: loop_header_id,
+                },
+            });
+            self.current_block = self.blocks.len() - 1;
+            let one = MirOperand::Constant(MirConstant::Int(1, IntSize::I64));
+            self.emit_instruction(MirInstruction::Assign {
+                dest: local_place(counter),
+                value: MirRvalue::BinaryOp {
+                    op: BinaryOp::Add,
+                    left: MirOperand::Copy(local_place(counter)),
+                    right: one,
+                },
+            });
+        }
 
-                // Otherwise block (unreachable for exhaustive matches)
-                self.blocks.push(MirBasicBlock {
-                    id: otherwise_block,
-                    instructions: Vec::new(),
-                    terminator: MirTerminator::Unreachable,
-                });
+        // Exit
+        self.blocks.push(MirBasicBlock {
+            id: loop_exit_id,
+            instructions: Vec::new(),
+            terminator: MirTerminator::Return,
+        });
+        self.current_block = self.blocks.len() - 1;
+        MirRvalue::Use(MirOperand::Copy(MirPlace {
+            local: result,
+            projection: vec![],
+        }))
+    }
 
-                // Merge block
-                self.blocks.push(MirBasicBlock {
-                    id: merge_block_id,
-                    instructions: Vec::new(),
-                    terminator: MirTerminator::Return,
-                });
-                self.current_block = self.blocks.len() - 1;
-            }
+    /// Index of the loop a `break`/`continue` refers to, innermost by default
+    fn find_loop(&self, label: Option<&ast::Identifier>) -> Option<usize> {
+        match label {
+            Some(label) => self
+                .loops
+                .iter()
+                .rposition(|scope| scope.label.as_deref() == Some(label.name.as_str())),
+            None => self.loops.len().checked_sub(1),
         }
     }
 
@@ -519,21 +1339,35 @@ impl MirBuilder {
 
             ast::Expr::Identifier(ident) => {
                 if let Some(&local) = self.var_map.get(&ident.name) {
-                    MirRvalue::Use(MirOperand::Copy(MirPlace {
-                        local,
-                        projection: vec![],
-                    }))
+                    MirRvalue::Use(self.variable_operand(&ident.name, local))
+                } else if let Some(value) = self.constant_value(&ident.name) {
+                    let rvalue = self.lower_expr_to_rvalue(&value);
+                    self.expanding.pop();
+                    rvalue
+                } else if let Some(kind) = self.enum_constructor(expr) {
+                    // Unit variant: `Vikalpa::Śūnya`
+                    MirRvalue::Aggregate {
+                        kind,
+                        operands: Vec::new(),
+                    }
                 } else {
                     // Unknown identifier - treat as zero
+                    self.unresolved.push(format!("`{}`", ident.name));
                     MirRvalue::Use(MirOperand::Constant(MirConstant::Int(0, IntSize::I64)))
                 }
             }
 
+            ast::Expr::Binary {
+                left, op, right, ..
+            } if is_assignment(*op) => self.lower_assign(left, *op, right),
+
             ast::Expr::Binary {
                 left, op, right, ..
             } => {
-                let left_op = self.lower_expr_to_operand(left);
-                let right_op = self.lower_expr_to_operand(right);
+                // A literal takes the width of the value it meets
+                let ty = self.operands_type(left, right);
+                let left_op = sized(self.lower_expr_to_operand(left), &ty);
+                let right_op = sized(self.lower_expr_to_operand(right), &ty);
                 let mir_op = self.convert_binary_op(*op);
                 MirRvalue::BinaryOp {
                     op: mir_op,
@@ -542,6 +1376,21 @@ impl MirBuilder {
                 }
             }
 
+            ast::Expr::Cast { expr, ty, .. } => MirRvalue::Cast {
+                kind: CastKind::Numeric,
+                operand: self.lower_expr_to_operand(expr),
+                ty: self.convert_type(ty),
+            },
+
+            // A sequence is already the address of its header
+            ast::Expr::Unary {
+                op: ast::UnaryOp::Ref | ast::UnaryOp::RefMut,
+                operand,
+                ..
+            } if is_sequence(&self.value_type(operand)) => {
+                MirRvalue::Use(self.lower_expr_to_operand(operand))
+            }
+
             ast::Expr::Unary { op, operand, .. } => {
                 let operand_mir = self.lower_expr_to_operand(operand);
                 match op {
@@ -553,11 +1402,11 @@ impl MirBuilder {
                         op: UnaryOp::Neg,
                         operand: operand_mir,
                     },
-                    ast::UnaryOp::Ref => {
+                    ast::UnaryOp::Ref | ast::UnaryOp::RefMut => {
                         // Take reference - get the place from operand
                         if let MirOperand::Copy(place) | MirOperand::Move(place) = operand_mir {
                             MirRvalue::Ref {
-                                mutable: false,
+                                mutable: *op == ast::UnaryOp::RefMut,
                                 place,
                             }
                         } else {
@@ -572,45 +1421,83 @@ impl MirBuilder {
             }
 
             ast::Expr::Call { callee, args, span: _span } => {
-                let func_op = self.lower_expr_to_operand(callee);
+                if let Some(symbol) = suci_constructor(callee) {
+                    let arg_ops = args.iter().map(|a| self.lower_expr_to_operand(a)).collect();
+                    return MirRvalue::Use(self.call_runtime(symbol, arg_ops));
+                }
+                if let Some(kind) = self.enum_constructor(callee) {
+                    let operands = args.iter().map(|a| self.lower_expr_to_operand(a)).collect();
+                    return MirRvalue::Aggregate { kind, operands };
+                }
+                let func_op = match callee.as_ref() {
+                    // Path call: Bindu::nava(...) or Self::nava(...)
+                    ast::Expr::Identifier(ident) if ident.name.contains("::") => {
+                        MirOperand::Constant(MirConstant::String(self.mangle_path(&ident.name)))
+                    }
+                    ast::Expr::Identifier(ident) if !self.var_map.contains_key(&ident.name) => {
+                        MirOperand::Constant(MirConstant::String(runtime_symbol(&ident.name)))
+                    }
+                    // Any other callee evaluates to a closure
+                    _ => {
+                        let env = self.lower_expr_to_operand(callee);
+                        let arg_ops = args.iter().map(|a| self.lower_expr_to_operand(a)).collect();
+                        return self.lower_closure_call(env, arg_ops);
+                    }
+                };
                 let arg_ops: Vec<_> = args.iter().map(|a| self.lower_expr_to_operand(a)).collect();
+                self.lower_call(func_op, arg_ops)
+            }
 
-                // Create temp for result
-                let result_local = self.alloc_local(MirType::Int(IntSize::I64), None);
-                let result_place = MirPlace {
-                    local: result_local,
-                    projection: vec![],
+            ast::Expr::MethodCall {
+                receiver,
+                method,
+                args,
+                ..
+            } => {
+                if let Some(rvalue) = self.lower_sequence_method(receiver, &method.name, args) {
+                    return rvalue;
+                }
+                let Some(target) = self.method_target(receiver, &method.name) else {
+                    self.unresolved.push(format!("method `{}`", method.name));
+                    self.error(format!(
+                        "cannot tell which method `{}` is called: the receiver's type names none of its impls",
+                        method.name
+                    ));
+                    return MirRvalue::Use(MirOperand::Constant(MirConstant::Unit));
                 };
-
-                // Create continuation block
-                let cont_block = self.alloc_block();
-
-                self.set_terminator(MirTerminator::Call {
-                    func: func_op,
-                    args: arg_ops,
-                    destination: Some(result_place.clone()),
-                    target: cont_block,
-                });
-
-                // Continue in new block
-                self.blocks.push(MirBasicBlock {
-                    id: cont_block,
-                    instructions: Vec::new(),
-                    terminator: MirTerminator::Return,
-                });
-                self.current_block = self.blocks.len() - 1;
-
-                MirRvalue::Use(MirOperand::Copy(result_place))
+                let mut arg_ops = vec![self.lower_expr_to_operand(receiver)];
+                arg_ops.extend(args.iter().map(|a| self.lower_expr_to_operand(a)));
+                self.lower_call(MirOperand::Constant(MirConstant::String(target)), arg_ops)
             }
 
             ast::Expr::Array { elements, .. } => {
+                // The elements live in the frame behind a `{data, len}`
+                // header; the array's value is the header's address
+                let element = match elements.first() {
+                    Some(first) => self.value_type(first),
+                    None => MirType::Int(IntSize::I64),
+                };
                 let ops: Vec<_> = elements
                     .iter()
                     .map(|e| self.lower_expr_to_operand(e))
                     .collect();
-                MirRvalue::Aggregate {
-                    kind: AggregateKind::Array,
-                    operands: ops,
+                let storage = self.alloc_local(
+                    MirType::Array {
+                        element: Box::new(element),
+                        size: ops.len(),
+                    },
+                    None,
+                );
+                self.emit_instruction(MirInstruction::Assign {
+                    dest: local_place(storage),
+                    value: MirRvalue::Aggregate {
+                        kind: AggregateKind::Array,
+                        operands: ops,
+                    },
+                });
+                MirRvalue::Ref {
+                    mutable: true,
+                    place: local_place(storage),
                 }
             }
 
@@ -653,58 +1540,85 @@ impl MirBuilder {
             }
 
             ast::Expr::Index { object, index, .. } => {
-                // Proper array/slice indexing
-                let base_rvalue = self.lower_expr_to_rvalue(object);
+                if let ast::Expr::Range {
+                    start,
+                    end,
+                    inclusive,
+                    ..
+                } = index.as_ref()
+                {
+                    return self.lower_slice(object, start.as_deref(), end.as_deref(), *inclusive);
+                }
+                let base = self.sequence_place(object);
                 let idx_op = self.lower_expr_to_operand(index);
 
-                // Create temporary for base
-                let base_local = self.alloc_local(MirType::Int(IntSize::I64), None);
-                self.emit_instruction(MirInstruction::Assign {
-                    dest: MirPlace {
-                        local: base_local,
-                        projection: vec![],
-                    },
-                    value: base_rvalue,
-                });
-
                 // Emit bounds check (Naraka: Asipatravana - buffer overflow prevention)
-                let len_local = self.alloc_local(MirType::Int(IntSize::I64), None);
-                self.emit_instruction(MirInstruction::Assign {
-                    dest: MirPlace {
-                        local: len_local,
-                        projection: vec![],
-                    },
-                    value: MirRvalue::Len(MirPlace {
-                        local: base_local,
-                        projection: vec![],
-                    }),
-                });
-
-                self.emit_instruction(MirInstruction::BoundsCheck {
-                    index: idx_op.clone(),
-                    len: MirOperand::Copy(MirPlace {
-                        local: len_local,
-                        projection: vec![],
-                    }),
-                    message: "Asipatravana: Array index out of bounds".to_string(),
-                });
+                self.bounds_check(
+                    &base,
+                    idx_op.clone(),
+                    "Asipatravana: Array index out of bounds",
+                );
 
                 MirRvalue::Index {
-                    base: MirOperand::Copy(MirPlace {
-                        local: base_local,
-                        projection: vec![],
-                    }),
+                    base: MirOperand::Copy(base),
                     index: idx_op,
                 }
             }
 
-            ast::Expr::Block(block) => {
-                self.lower_block(block);
+            ast::Expr::Range {
+                start,
+                end,
+                inclusive,
+                ..
+            } => self.lower_range(start.as_deref(), end.as_deref(), *inclusive),
+
+            ast::Expr::Block(block) => self.lower_block_value(block),
+
+            ast::Expr::If {
+                condition,
+                then_expr,
+                else_expr,
+                ..
+            } => {
+                let result = self.alloc_local(self.temp_type(then_expr), None);
+                self.lower_if(
+                    condition,
+                    Branch::Expr(then_expr),
+                    else_expr.as_deref().map(Branch::Expr),
+                    Some(result),
+                );
+                MirRvalue::Use(MirOperand::Copy(MirPlace {
+                    local: result,
+                    projection: vec![],
+                }))
+            }
+
+            ast::Expr::Loop {
+                label, kind, body, ..
+            } => self.lower_loop(label.as_ref(), kind, body),
+
+            ast::Expr::Match {
+                scrutinee, arms, ..
+            } => self.lower_match(scrutinee, arms),
+
+            ast::Expr::Lambda { params, body, span } => self.lower_closure(params, body, *span),
+
+            ast::Expr::Format { pieces, args, .. } => {
+                MirRvalue::Use(self.lower_format(pieces, args))
+            }
+
+            ast::Expr::Try { expr, .. } => self.lower_try(expr),
+
+            // Until the runtime has threads a task runs to completion where
+            // it is spawned, one of the orders the thread-safety checks allow
+            ast::Expr::Saha { task, .. } => {
+                let _ = self.lower_expr_to_rvalue(task);
                 MirRvalue::Use(MirOperand::Constant(MirConstant::Unit))
             }
 
             _ => {
                 // Default case for unsupported expressions
+                self.unresolved.push("this expression".to_string());
                 MirRvalue::Use(MirOperand::Constant(MirConstant::Unit))
             }
         }
@@ -716,20 +1630,22 @@ impl MirBuilder {
             ast::Expr::Literal(lit) => MirOperand::Constant(self.lower_literal(lit)),
             ast::Expr::Identifier(ident) => {
                 if let Some(&local) = self.var_map.get(&ident.name) {
-                    MirOperand::Copy(MirPlace {
-                        local,
-                        projection: vec![],
-                    })
+                    self.variable_operand(&ident.name, local)
+                } else if let Some(value) = self.constant_value(&ident.name) {
+                    let operand = self.lower_expr_to_operand(&value);
+                    self.expanding.pop();
+                    operand
                 } else {
                     // Not a local variable - could be a function name
                     // Return as string constant for call targets
+                    self.unresolved.push(format!("`{}`", ident.name));
                     MirOperand::Constant(MirConstant::String(ident.name.clone()))
                 }
             }
             _ => {
                 // For complex expressions, create a temporary
                 let rvalue = self.lower_expr_to_rvalue(expr);
-                let temp = self.alloc_local(MirType::Int(IntSize::I64), None);
+                let temp = self.alloc_local(self.temp_type(expr), None);
                 self.emit_instruction(MirInstruction::Assign {
                     dest: MirPlace {
                         local: temp,
@@ -745,6 +1661,655 @@ impl MirBuilder {
         }
     }
 
+    /// Lower a call, continuing in a fresh block after it returns
+    fn lower_call(&mut self, func_op: MirOperand, arg_ops: Vec<MirOperand>) -> MirRvalue {
+        // Create temp for result, spanning the words of an enum result
+        let result_type = match &func_op {
+            MirOperand::Constant(MirConstant::String(symbol)) => self.return_types.get(symbol),
+            _ => None,
+        };
+        let result_type = match result_type {
+            Some(ty @ MirType::Enum { .. }) => ty.clone(),
+            _ => MirType::Int(IntSize::I64),
+        };
+        let result_local = self.alloc_local(result_type, None);
+        let result_place = MirPlace {
+            local: result_local,
+            projection: vec![],
+        };
+
+        // Create continuation block
+        let cont_block = self.alloc_block();
+
+        self.set_terminator(MirTerminator::Call {
+            func: func_op,
+            args: arg_ops,
+            destination: Some(result_place.clone()),
+            target: cont_block,
+        });
+
+        // Continue in new block
+        self.blocks.push(MirBasicBlock {
+            id: cont_block,
+            instructions: Vec::new(),
+            terminator: MirTerminator::Return,
+        });
+        self.current_block = self.blocks.len() - 1;
+
+        MirRvalue::Use(MirOperand::Copy(result_place))
+    }
+
+    /// Lower an interpolated string to calls into the runtime's formatting
+    /// routines (`jagannath_runtime::vinyasa`)
+    ///
+    /// The arguments are evaluated once, in order. A buffer is then opened,
+    /// each piece appended by the routine for its argument's type, and the
+    /// buffer finished into a sūtra. Values of user types are rendered by
+    /// their `fmt`/`fmt_debug` method and appended as text.
+    fn lower_format(&mut self, pieces: &[ast::FormatPiece], args: &[ast::Expr]) -> MirOperand {
+        let values: Vec<(MirOperand, MirType)> = args
+            .iter()
+            .map(|arg| {
+                let ty = self.value_type(arg);
+                (self.lower_expr_to_operand(arg), ty)
+            })
+            .collect();
+        let buffer = self.call_runtime("jag_vinyasa_arambha", Vec::new());
+        let word =
+            |spec: &FormatSpec| MirOperand::Constant(MirConstant::Int(spec.encode(), IntSize::I64));
+
+        for piece in pieces {
+            let (index, spec) = match piece {
+                ast::FormatPiece::Text(text) => {
+                    let text = MirOperand::Constant(MirConstant::String(text.clone()));
+                    let spec = word(&FormatSpec::default());
+                    self.call_runtime("jag_vinyasa_sutra", vec![buffer.clone(), text, spec]);
+                    continue;
+                }
+                ast::FormatPiece::Arg { index, spec } => (*index, spec),
+            };
+            let (mut value, mut ty) = values[index].clone();
+            if let MirType::Ref { ty: inner, .. } = ty {
+                if let MirOperand::Copy(place) | MirOperand::Move(place) = &mut value {
+                    place.projection.push(PlaceProjection::Deref);
+                }
+                ty = *inner;
+            }
+            if let MirOperand::Constant(MirConstant::Float(f, _)) = value {
+                value = MirOperand::Constant(MirConstant::Int(f.to_bits() as i64, IntSize::I64));
+            }
+            let mut args = vec![buffer.clone(), value, word(spec)];
+            let routine = match ty {
+                MirType::Int(size) => {
+                    let (signed, bits) = int_format(size);
+                    args.push(MirOperand::Constant(MirConstant::Int(bits, IntSize::I64)));
+                    if signed {
+                        "jag_vinyasa_int"
+                    } else {
+                        "jag_vinyasa_uint"
+                    }
+                }
+                MirType::Float(FloatSize::F32) => "jag_vinyasa_float32",
+                MirType::Float(_) => "jag_vinyasa_float",
+                MirType::Bool => "jag_vinyasa_bool",
+                MirType::Unit => "jag_vinyasa_unit",
+                MirType::Named(name) => match name.as_str() {
+                    "satya" => "jag_vinyasa_bool",
+                    "akṣara" | "char" => "jag_vinyasa_char",
+                    "daśamika" => "jag_vinyasa_float",
+                    "sūtra" | "Sūtra" | "str" | "String" => "jag_vinyasa_sutra",
+                    owner => {
+                        // Already checked to implement Display or Debug
+                        let method = match spec.kind {
+                            FormatKind::Debug => "fmt_debug",
+                            _ => "fmt",
+                        };
+                        let target =
+                            MirOperand::Constant(MirConstant::String(mangle_method(owner, method)));
+                        args[1] = match self.lower_call(target, vec![args[1].clone()]) {
+                            MirRvalue::Use(text) => text,
+                            _ => unreachable!("calls yield their result local"),
+                        };
+                        args[2] = word(&FormatSpec {
+                            kind: FormatKind::Display,
+                            ..*spec
+                        });
+                        "jag_vinyasa_sutra"
+                    }
+                },
+                _ => {
+                    args.push(MirOperand::Constant(MirConstant::Int(64, IntSize::I64)));
+                    "jag_vinyasa_int"
+                }
+            };
+            self.call_runtime(routine, args);
+        }
+        self.call_runtime("jag_vinyasa_samapti", vec![buffer])
+    }
+
+    /// Call a function of the runtime library
+    fn call_runtime(&mut self, name: &str, args: Vec<MirOperand>) -> MirOperand {
+        let func = MirOperand::Constant(MirConstant::String(name.to_string()));
+        match self.lower_call(func, args) {
+            MirRvalue::Use(result) => result,
+            _ => unreachable!("calls yield their result local"),
+        }
+    }
+
+    /// Place holding the value of a sequence (array, slice or `Sūci`):
+    /// the address of its header, which starts with `{data, len}`
+    fn sequence_place(&mut self, expr: &ast::Expr) -> MirPlace {
+        match self.lower_expr_to_operand(expr) {
+            MirOperand::Copy(place) | MirOperand::Move(place) if place.projection.is_empty() => {
+                place
+            }
+            operand => local_place(self.assign_temp(MirRvalue::Use(operand))),
+        }
+    }
+
+    /// Trap unless `index` is below the length of the sequence at `base`
+    fn bounds_check(&mut self, base: &MirPlace, index: MirOperand, message: &str) {
+        let len = self.assign_temp(MirRvalue::Len(base.clone()));
+        self.emit_instruction(MirInstruction::BoundsCheck {
+            index,
+            len: MirOperand::Copy(local_place(len)),
+            message: message.to_string(),
+        });
+    }
+
+    /// Address of element `index` of the sequence at `base`, unchecked
+    fn element_address(&mut self, base: &MirPlace, index: MirOperand) -> MirOperand {
+        let data = self.alloc_local(MirType::Int(IntSize::I64), None);
+        self.emit_instruction(MirInstruction::Load {
+            dest: local_place(data),
+            ptr: MirOperand::Copy(base.clone()),
+        });
+        let offset = self.assign_temp(MirRvalue::BinaryOp {
+            op: BinaryOp::Mul,
+            left: index,
+            right: MirOperand::Constant(MirConstant::Int(8, IntSize::I64)),
+        });
+        let address = self.assign_temp(MirRvalue::BinaryOp {
+            op: BinaryOp::Add,
+            left: MirOperand::Copy(local_place(data)),
+            right: MirOperand::Copy(local_place(offset)),
+        });
+        MirOperand::Copy(local_place(address))
+    }
+
+    /// Lower `object[start..end]` to a new `{data, len}` header in the
+    /// frame, over the elements of `object`
+    ///
+    /// Missing bounds are the ends of `object`. The slice borrows the
+    /// elements, so it is valid while `object` is.
+    fn lower_slice(
+        &mut self,
+        object: &ast::Expr,
+        start: Option<&ast::Expr>,
+        end: Option<&ast::Expr>,
+        inclusive: bool,
+    ) -> MirRvalue {
+        let one = || MirOperand::Constant(MirConstant::Int(1, IntSize::I64));
+        let base = self.sequence_place(object);
+        let len = self.assign_temp(MirRvalue::Len(base.clone()));
+        let start = match start {
+            Some(start) => self.lower_expr_to_operand(start),
+            None => MirOperand::Constant(MirConstant::Int(0, IntSize::I64)),
+        };
+        let end = match end {
+            Some(end) if inclusive => {
+                let end = self.lower_expr_to_operand(end);
+                MirOperand::Copy(local_place(self.assign_temp(MirRvalue::BinaryOp {
+                    op: BinaryOp::Add,
+                    left: end,
+                    right: one(),
+                })))
+            }
+            Some(end) => self.lower_expr_to_operand(end),
+            None => MirOperand::Copy(local_place(len)),
+        };
+
+        // start <= end <= len
+        let len_limit = self.assign_temp(MirRvalue::BinaryOp {
+            op: BinaryOp::Add,
+            left: MirOperand::Copy(local_place(len)),
+            right: one(),
+        });
+        self.emit_instruction(MirInstruction::BoundsCheck {
+            index: end.clone(),
+            len: MirOperand::Copy(local_place(len_limit)),
+            message: "Asipatravana: Slice end out of bounds".to_string(),
+        });
+        let end_limit = self.assign_temp(MirRvalue::BinaryOp {
+            op: BinaryOp::Add,
+            left: end.clone(),
+            right: one(),
+        });
+        self.emit_instruction(MirInstruction::BoundsCheck {
+            index: start.clone(),
+            len: MirOperand::Copy(local_place(end_limit)),
+            message: "Asipatravana: Slice starts after its end".to_string(),
+        });
+
+        let data = self.element_address(&base, start.clone());
+        let count = self.assign_temp(MirRvalue::BinaryOp {
+            op: BinaryOp::Sub,
+            left: end,
+            right: start,
+        });
+        let header = self.alloc_local(
+            MirType::Array {
+                element: Box::new(MirType::Int(IntSize::I64)),
+                size: 0,
+            },
+            None,
+        );
+        for (index, value) in [data, MirOperand::Copy(local_place(count))]
+            .into_iter()
+            .enumerate()
+        {
+            self.emit_instruction(MirInstruction::Assign {
+                dest: MirPlace {
+                    local: header,
+                    projection: vec![PlaceProjection::Field { index }],
+                },
+                value: MirRvalue::Use(value),
+            });
+        }
+        MirRvalue::Ref {
+            mutable: true,
+            place: local_place(header),
+        }
+    }
+
+    /// Lower a range value to a `{start, end}` pair in the frame, laid out
+    /// like a `{data, len}` header; the range's value is its address
+    ///
+    /// The end is exclusive. A missing start is 0 and a missing end the
+    /// largest `saṅkhyā64`.
+    fn lower_range(
+        &mut self,
+        start: Option<&ast::Expr>,
+        end: Option<&ast::Expr>,
+        inclusive: bool,
+    ) -> MirRvalue {
+        let start = match start {
+            Some(start) => self.lower_expr_to_operand(start),
+            None => MirOperand::Constant(MirConstant::Int(0, IntSize::I64)),
+        };
+        let end = match end {
+            Some(end) if inclusive => {
+                let end = self.lower_expr_to_operand(end);
+                MirOperand::Copy(local_place(self.assign_temp(MirRvalue::BinaryOp {
+                    op: BinaryOp::Add,
+                    left: end,
+                    right: MirOperand::Constant(MirConstant::Int(1, IntSize::I64)),
+                })))
+            }
+            Some(end) => self.lower_expr_to_operand(end),
+            None => MirOperand::Constant(MirConstant::Int(i64::MAX, IntSize::I64)),
+        };
+        let range = self.alloc_local(
+            MirType::Array {
+                element: Box::new(MirType::Int(IntSize::I64)),
+                size: 0,
+            },
+            None,
+        );
+        for (index, value) in [start, end].into_iter().enumerate() {
+            self.emit_instruction(MirInstruction::Assign {
+                dest: MirPlace {
+                    local: range,
+                    projection: vec![PlaceProjection::Field { index }],
+                },
+                value: MirRvalue::Use(value),
+            });
+        }
+        MirRvalue::Ref {
+            mutable: false,
+            place: local_place(range),
+        }
+    }
+
+    /// Aggregate kind of an `Enum::Variant` callee or unit variant path
+    fn enum_constructor(&self, expr: &ast::Expr) -> Option<AggregateKind> {
+        let ast::Expr::Identifier(ident) = expr else {
+            return None;
+        };
+        if self.var_map.contains_key(&ident.name) {
+            return None;
+        }
+        let (name, variant) = self.shapes.constructor(&ident.name)?;
+        Some(AggregateKind::Enum {
+            name: name.to_string(),
+            variant,
+        })
+    }
+
+    /// Lower `target = value`, or a compound assignment like `target += value`
+    ///
+    /// Targets are variables, including ones captured by reference, and
+    /// elements of sequences, which are stored through their address.
+    fn lower_assign(
+        &mut self,
+        target: &ast::Expr,
+        op: ast::BinaryOp,
+        value: &ast::Expr,
+    ) -> MirRvalue {
+        let unit = MirRvalue::Use(MirOperand::Constant(MirConstant::Unit));
+        // Either a local to assign, or an address to store to
+        let dest = match target {
+            ast::Expr::Identifier(ident) => match self.var_map.get(&ident.name) {
+                Some(&local) if self.by_ref_captures.contains(&ident.name) => {
+                    Err(MirOperand::Copy(local_place(local)))
+                }
+                Some(&local) => Ok(local_place(local)),
+                None => {
+                    self.lower_expr_to_rvalue(value);
+                    return unit;
+                }
+            },
+            ast::Expr::Index { object, index, .. }
+                if !matches!(index.as_ref(), ast::Expr::Range { .. }) =>
+            {
+                let base = self.sequence_place(object);
+                let index = self.lower_expr_to_operand(index);
+                self.bounds_check(
+                    &base,
+                    index.clone(),
+                    "Asipatravana: Array index out of bounds",
+                );
+                Err(self.element_address(&base, index))
+            }
+            _ => {
+                self.lower_expr_to_rvalue(value);
+                return unit;
+            }
+        };
+
+        let mut rvalue = self.lower_expr_to_rvalue(value);
+        if op != ast::BinaryOp::Assign {
+            let current = match &dest {
+                Ok(place) => MirOperand::Copy(place.clone()),
+                Err(address) => {
+                    let current = self.alloc_local(MirType::Int(IntSize::I64), None);
+                    self.emit_instruction(MirInstruction::Load {
+                        dest: local_place(current),
+                        ptr: address.clone(),
+                    });
+                    MirOperand::Copy(local_place(current))
+                }
+            };
+            let value = self.assign_temp(rvalue);
+            rvalue = MirRvalue::BinaryOp {
+                op: self.convert_binary_op(op),
+                left: current,
+                right: MirOperand::Copy(local_place(value)),
+            };
+        }
+        match dest {
+            Ok(place) => self.emit_instruction(MirInstruction::Assign {
+                dest: place,
+                value: rvalue,
+            }),
+            Err(address) => {
+                let value = self.assign_typed(rvalue, self.temp_type(target));
+                self.emit_instruction(MirInstruction::Store {
+                    ptr: address,
+                    value: MirOperand::Copy(local_place(value)),
+                });
+            }
+        }
+        unit
+    }
+
+    /// Lower a built-in method of arrays, slices and `Sūci`
+    ///
+    /// `None` if `receiver` is not a sequence, or has no such method.
+    fn lower_sequence_method(
+        &mut self,
+        receiver: &ast::Expr,
+        method: &str,
+        args: &[ast::Expr],
+    ) -> Option<MirRvalue> {
+        let is_suci = match self.value_type(receiver) {
+            MirType::Ref { ty, .. } => is_suci(&ty),
+            ty if is_sequence(&ty) => is_suci(&ty),
+            _ => return None,
+        };
+        let rvalue = match method {
+            "dīrghatā" | "len" => MirRvalue::Len(self.sequence_place(receiver)),
+            "rikta" | "is_empty" => {
+                let base = self.sequence_place(receiver);
+                let len = self.assign_temp(MirRvalue::Len(base));
+                MirRvalue::BinaryOp {
+                    op: BinaryOp::Eq,
+                    left: MirOperand::Copy(local_place(len)),
+                    right: MirOperand::Constant(MirConstant::Int(0, IntSize::I64)),
+                }
+            }
+            "yojaya" | "push" if is_suci => {
+                let mut arg_ops = vec![self.lower_expr_to_operand(receiver)];
+                arg_ops.extend(args.iter().map(|a| self.lower_expr_to_operand(a)));
+                MirRvalue::Use(self.call_runtime("jag_suci_yojaya", arg_ops))
+            }
+            "niṣkāsaya" | "pop" if is_suci => {
+                let suci = self.lower_expr_to_operand(receiver);
+                MirRvalue::Use(self.call_runtime("jag_suci_nishkasaya", vec![suci]))
+            }
+            _ => return None,
+        };
+        Some(rvalue)
+    }
+
+    /// Type of an expression's value, as far as its shape tells
+    ///
+    /// The builder has no type information, so anything not evident from
+    /// literals, locals and casts is taken to be a 64-bit integer.
+    fn value_type(&self, expr: &ast::Expr) -> MirType {
+        match expr {
+            // As the type checker types an integer literal on its own
+            ast::Expr::Literal(lit) => match self.lower_literal(lit) {
+                MirConstant::Int(value, _) if IntSize::I32.fits(value) => {
+                    MirType::Int(IntSize::I32)
+                }
+                MirConstant::Int(_, size) => MirType::Int(size),
+                MirConstant::Float(_, size) => MirType::Float(size),
+                MirConstant::Bool(_) => MirType::Bool,
+                MirConstant::Unit => MirType::Unit,
+                MirConstant::String(_) => MirType::Named("sūtra".to_string()),
+            },
+            ast::Expr::Identifier(ident) if !self.var_map.contains_key(&ident.name) => self
+                .constructor_type(&ident.name, &[])
+                .unwrap_or(MirType::Int(IntSize::I64)),
+            ast::Expr::Identifier(ident) => self
+                .var_map
+                .get(&ident.name)
+                .and_then(|&local| self.locals.iter().find(|l| l.index == local))
+                .map(|local| local.ty.clone())
+                .unwrap_or(MirType::Int(IntSize::I64)),
+            ast::Expr::Binary { op, .. } if is_comparison(*op) => MirType::Bool,
+            ast::Expr::Binary {
+                left, op, right, ..
+            } if !is_assignment(*op) => self.operands_type(left, right),
+            ast::Expr::Unary {
+                op: ast::UnaryOp::Neg | ast::UnaryOp::Not,
+                operand,
+                ..
+            } => self.value_type(operand),
+            ast::Expr::Unary {
+                op: op @ (ast::UnaryOp::Ref | ast::UnaryOp::RefMut),
+                operand,
+                ..
+            } => MirType::Ref {
+                mutable: *op == ast::UnaryOp::RefMut,
+                ty: Box::new(self.value_type(operand)),
+            },
+            ast::Expr::Array { elements, .. } => MirType::Slice(Box::new(match elements.first() {
+                Some(first) => self.value_type(first),
+                None => MirType::Int(IntSize::I64),
+            })),
+            ast::Expr::Index { object, index, .. } => {
+                let element = match self.value_type(object) {
+                    MirType::Ref { ty, .. } => *ty,
+                    ty => ty,
+                };
+                let element = match element {
+                    MirType::Slice(element) | MirType::Array { element, .. } => *element,
+                    _ => MirType::Int(IntSize::I64),
+                };
+                match index.as_ref() {
+                    ast::Expr::Range { .. } => MirType::Slice(Box::new(element)),
+                    _ => element,
+                }
+            }
+            ast::Expr::FieldAccess { object, field, .. } => {
+                let owner = match self.value_type(object) {
+                    MirType::Ref { ty, .. } => *ty,
+                    ty => ty,
+                };
+                let declared = match &owner {
+                    MirType::Named(name) => self
+                        .struct_fields
+                        .get(name)
+                        .and_then(|fields| fields.iter().find(|f| f.name.name == field.name)),
+                    _ => None,
+                };
+                declared.map_or(MirType::Int(IntSize::I64), |f| self.convert_type(&f.ty))
+            }
+            ast::Expr::Call { callee, .. } if suci_constructor(callee).is_some() => {
+                MirType::Named("Sūci".to_string())
+            }
+            ast::Expr::Call { callee, args, .. } => match callee.as_ref() {
+                ast::Expr::Identifier(ident) => match self.constructor_type(&ident.name, args) {
+                    Some(ty) => ty,
+                    None => self
+                        .return_types
+                        .get(&self.mangle_path(&ident.name))
+                        .cloned()
+                        .unwrap_or(MirType::Int(IntSize::I64)),
+                },
+                _ => MirType::Int(IntSize::I64),
+            },
+            ast::Expr::MethodCall {
+                receiver, method, ..
+            } => self
+                .method_target(receiver, &method.name)
+                .and_then(|target| self.return_types.get(&target).cloned())
+                .unwrap_or(MirType::Int(IntSize::I64)),
+            ast::Expr::Range { .. } => MirType::Named("Range".to_string()),
+            ast::Expr::If { then_expr, .. } => self.value_type(then_expr),
+            ast::Expr::Match { arms, .. } => match arms.first() {
+                Some(arm) => self.value_type(&arm.body),
+                None => MirType::Unit,
+            },
+            ast::Expr::Block(block) => match block.tail() {
+                Some(ast::Stmt::Expr(tail)) => self.value_type(tail),
+                _ => MirType::Int(IntSize::I64),
+            },
+            ast::Expr::Cast { ty, .. } => self.convert_type(ty),
+            ast::Expr::Format { .. } => MirType::Named("sūtra".to_string()),
+            ast::Expr::Try { expr, .. } => {
+                let ty = self.value_type(expr);
+                let ok = self.try_variants(&ty).0;
+                ty.enum_layout()
+                    .and_then(|layout| layout.field(ok, 0))
+                    .map_or(MirType::Int(IntSize::I64), |(_, ty)| ty.clone())
+            }
+            _ => MirType::Int(IntSize::I64),
+        }
+    }
+
+    /// Type of a temporary holding the value of `expr`: an enum's own, as
+    /// its values span several words, an integer's, whose width its
+    /// arithmetic keeps to, otherwise a word
+    fn temp_type(&self, expr: &ast::Expr) -> MirType {
+        match self.value_type(expr) {
+            ty @ (MirType::Enum { .. } | MirType::Int(_)) => ty,
+            _ => MirType::Int(IntSize::I64),
+        }
+    }
+
+    /// Type both operands of a binary operation have: that of the one
+    /// that is not a literal, as a literal takes its type from the other
+    fn operands_type(&self, left: &ast::Expr, right: &ast::Expr) -> MirType {
+        match left {
+            ast::Expr::Literal(_) => self.value_type(right),
+            _ => self.value_type(left),
+        }
+    }
+
+    /// How a `cala x : iterable` loop walks `iterable`, from its type
+    fn iteration(&self, iterable: &ast::Expr) -> Iteration {
+        let ty = match self.value_type(iterable) {
+            MirType::Ref { ty, .. } => *ty,
+            ty => ty,
+        };
+        match ty {
+            MirType::Named(name) if name == "Range" => Iteration::Range,
+            MirType::Named(name) => {
+                let next = mangle_method(&name, "next");
+                let implemented = self
+                    .method_targets
+                    .get("next")
+                    .is_some_and(|targets| targets.contains(&next));
+                if implemented {
+                    Iteration::Next(next)
+                } else {
+                    Iteration::Sequence
+                }
+            }
+            _ => Iteration::Sequence,
+        }
+    }
+
+    /// Record an impl or default method as a method call target
+    fn register_method_target(&mut self, method: &str, owner: &str) {
+        let targets = self.method_targets.entry(method.to_string()).or_default();
+        let mangled = mangle_method(owner, method);
+        if !targets.contains(&mangled) {
+            targets.push(mangled);
+        }
+    }
+
+    /// Function a method call goes to
+    ///
+    /// Calls monomorphization resolved name their target (`Peti__i32::mūlya`);
+    /// otherwise the receiver's type picks the impl, through a reference,
+    /// and a method only one impl has needs no type.
+    fn method_target(&self, receiver: &ast::Expr, method: &str) -> Option<String> {
+        if method.contains("::") {
+            return Some(self.mangle_path(method));
+        }
+        let targets = self.method_targets.get(method)?;
+        let owner = match self.value_type(receiver) {
+            MirType::Ref { ty, .. } => *ty,
+            ty => ty,
+        };
+        if let MirType::Named(name) = owner {
+            let target = mangle_method(&name, method);
+            if targets.contains(&target) {
+                return Some(target);
+            }
+        }
+        match targets.as_slice() {
+            [target] => Some(target.clone()),
+            _ => None,
+        }
+    }
+
+    /// Mangle a `Type::method` path, resolving `Self` to the current impl
+    fn mangle_path(&self, path: &str) -> String {
+        match path.rsplit_once("::") {
+            Some(("Self", method)) => match &self.current_impl {
+                Some(owner) => mangle_method(owner, method),
+                None => mangle_method("Self", method),
+            },
+            Some((owner, method)) => mangle_method(owner, method),
+            None => path.to_string(),
+        }
+    }
+
     /// Lower literal to MIR constant
     fn lower_literal(&self, lit: &ast::Literal) -> MirConstant {
         match lit {
@@ -785,6 +2350,10 @@ impl MirBuilder {
     /// Emit an instruction to the current block
     fn emit_instruction(&mut self, instr: MirInstruction) {
         if let Some(block) = self.blocks.get_mut(self.current_block) {
+            if let Some(span) = self.stmt_span {
+                self.spans
+                    .insert((block.id, block.instructions.len()), span);
+            }
             block.instructions.push(instr);
         }
     }
@@ -822,8 +2391,16 @@ impl MirBuilder {
                         (v.name.name.clone(), ty)
                     })
                     .collect();
+                // Generic enums are laid out per instantiation instead
+                let layout = if typedef.generics.is_empty() {
+                    let ty = self.enum_type(&typedef.name.name, &[], &[]);
+                    ty.enum_layout().cloned()
+                } else {
+                    None
+                };
                 MirTypeDefKind::Enum {
                     variants: mir_variants,
+                    layout,
                 }
             }
             _ => return None,
@@ -843,10 +2420,15 @@ impl MirBuilder {
             .map(|t| self.convert_type(t))
             .unwrap_or(MirType::Unit);
 
+        // Const evaluation has left a literal where it could
+        let init = match &const_def.value {
+            ast::Expr::Literal(lit) => Some(self.lower_literal(lit)),
+            _ => None,
+        };
         Some(MirGlobal {
             name: const_def.name.name.clone(),
             ty,
-            init: None,
+            init,
             mutable: false,
         })
     }
@@ -854,32 +2436,47 @@ impl MirBuilder {
     /// Convert AST type to MIR type
     fn convert_type(&self, ty: &ast::Type) -> MirType {
         match ty {
-            ast::Type::Named { name, generics: _generics, .. } => {
-                let type_name = &name.name;
-                match type_name.as_str() {
-                    "i8" => MirType::Int(IntSize::I8),
-                    "i16" => MirType::Int(IntSize::I16),
-                    "i32" | "saṅkhyā" => MirType::Int(IntSize::I32),
-                    "i64" => MirType::Int(IntSize::I64),
+            ast::Type::Named {
+                name,
+                generics,
+                affixes,
+            } => {
+                let type_name = prelude::alias(&name.name).unwrap_or(&name.name);
+                let ty = match type_name {
+                    "i8" | "saṅkhyā8" => MirType::Int(IntSize::I8),
+                    "i16" | "saṅkhyā16" => MirType::Int(IntSize::I16),
+                    "i32" | "saṅkhyā32" | "saṅkhyā" => MirType::Int(IntSize::I32),
+                    "i64" | "saṅkhyā64" => MirType::Int(IntSize::I64),
                     "u8" => MirType::Int(IntSize::U8),
                     "u16" => MirType::Int(IntSize::U16),
                     "u32" => MirType::Int(IntSize::U32),
                     "u64" => MirType::Int(IntSize::U64),
-                    "f32" => MirType::Float(FloatSize::F32),
-                    "f64" => MirType::Float(FloatSize::F64),
+                    "f32" | "daśamika32" => MirType::Float(FloatSize::F32),
+                    "f64" | "daśamika64" | "daśamika" => MirType::Float(FloatSize::F64),
                     "bool" => MirType::Bool,
                     "()" => MirType::Unit,
-                    _ => MirType::Named(type_name.clone()),
+                    "sūci" | "Sūci" => MirType::Named("Sūci".to_string()),
+                    _ if self.enums.contains_key(type_name) => {
+                        let generics: Vec<MirType> =
+                            generics.iter().map(|g| self.convert_type(g)).collect();
+                        self.enum_type(type_name, &generics, &[])
+                    }
+                    _ => MirType::Named(type_name.to_string()),
+                };
+                match affixes.type_width() {
+                    Some(width) => with_width(ty, width),
+                    None => ty,
                 }
             }
             ast::Type::Reference { inner, mutable, .. } => MirType::Ref {
                 mutable: *mutable,
                 ty: Box::new(self.convert_type(inner)),
             },
-            ast::Type::Array { element, size } => MirType::Array {
-                element: Box::new(self.convert_type(element)),
-                size: size.unwrap_or(0),
-            },
+            // Arrays and slices alike are the address of a `{data, len}`
+            // header; `MirType::Array` is only the storage behind one
+            ast::Type::Array { element, .. } => {
+                MirType::Slice(Box::new(self.convert_type(element)))
+            }
             ast::Type::Tuple(elements) => {
                 MirType::Tuple(elements.iter().map(|t| self.convert_type(t)).collect())
             }
@@ -890,10 +2487,116 @@ impl MirBuilder {
                 params: params.iter().map(|t| self.convert_type(t)).collect(),
                 ret: Box::new(self.convert_type(return_type)),
             },
-            ast::Type::Inferred => MirType::Unit,
+            ast::Type::Const(_) | ast::Type::Inferred => MirType::Unit,
+        }
+    }
+
+    /// Type of enum `name` instantiated at `generics`, with its layout
+    ///
+    /// Generic parameters given no argument are taken to be words. Fields
+    /// naming an enum being laid out (one of `outer`) are words too, as an
+    /// enum can only hold itself behind a reference.
+    fn enum_type(&self, name: &str, generics: &[MirType], outer: &[&str]) -> MirType {
+        let Some(typedef) = self.enums.get(name) else {
+            return MirType::Named(name.to_string());
+        };
+        let ast::TypeBody::Enum(variants) = &typedef.body else {
+            return MirType::Named(name.to_string());
+        };
+        let substitutions: HashMap<&str, MirType> = typedef
+            .generics
+            .iter()
+            .enumerate()
+            .map(|(i, param)| {
+                let ty = generics.get(i).cloned();
+                (
+                    param.name.name.as_str(),
+                    ty.unwrap_or(MirType::Int(IntSize::I64)),
+                )
+            })
+            .collect();
+        let mut outer = outer.to_vec();
+        outer.push(name);
+        let fields: Vec<Vec<MirType>> = variants
+            .iter()
+            .map(|variant| {
+                variant
+                    .fields
+                    .iter()
+                    .flatten()
+                    .map(|field| self.field_type(&field.ty, &substitutions, &outer))
+                    .collect()
+            })
+            .collect();
+        MirType::Enum {
+            name: name.to_string(),
+            layout: Box::new(EnumLayout::compute(&fields)),
+        }
+    }
+
+    /// Type of an enum field declared as `ty`, inside the enums `outer`
+    /// whose generic parameters are `substitutions`
+    fn field_type(
+        &self,
+        ty: &ast::Type,
+        substitutions: &HashMap<&str, MirType>,
+        outer: &[&str],
+    ) -> MirType {
+        match ty {
+            ast::Type::Named { name, generics, .. } => {
+                if let Some(ty) = substitutions.get(name.name.as_str()) {
+                    return ty.clone();
+                }
+                let type_name = prelude::alias(&name.name).unwrap_or(&name.name);
+                if outer.contains(&type_name) {
+                    return MirType::Int(IntSize::I64);
+                }
+                if !self.enums.contains_key(type_name) {
+                    return self.convert_type(ty);
+                }
+                let generics: Vec<MirType> = generics
+                    .iter()
+                    .map(|g| self.field_type(g, substitutions, outer))
+                    .collect();
+                self.enum_type(type_name, &generics, outer)
+            }
+            ast::Type::Reference { inner, mutable, .. } => MirType::Ref {
+                mutable: *mutable,
+                ty: Box::new(self.field_type(inner, substitutions, outer)),
+            },
+            _ => self.convert_type(ty),
         }
     }
 
+    /// Type of the value the constructor at `path` builds from `args`
+    ///
+    /// The enum's generic arguments are read off the arguments given for
+    /// fields declared as a bare parameter; the rest are words.
+    fn constructor_type(&self, path: &str, args: &[ast::Expr]) -> Option<MirType> {
+        let (name, variant) = self.shapes.constructor(path)?;
+        let Some(ast::TypeBody::Enum(variants)) = self.enums.get(name).map(|def| &def.body) else {
+            return Some(MirType::Named(name.to_string()));
+        };
+        let fields = variants
+            .get(variant)
+            .and_then(|v| v.fields.as_deref())
+            .unwrap_or_default();
+        let generics: Vec<MirType> = self.enums[name]
+            .generics
+            .iter()
+            .map(|param| {
+                fields
+                    .iter()
+                    .zip(args)
+                    .find(|(field, _)| {
+                        matches!(&field.ty, ast::Type::Named { name, .. } if name.name == param.name.name)
+                    })
+                    .map_or(MirType::Int(IntSize::I64), |(_, arg)| self.value_type(arg))
+            })
+            .collect();
+        Some(self.enum_type(name, &generics, &[]))
+    }
+
     /// Allocate a new local variable
     fn alloc_local(&mut self, ty: MirType, name: Option<String>) -> usize {
         let index = self.next_local;
@@ -910,6 +2613,146 @@ impl MirBuilder {
     }
 }
 
+/// Runtime constructor behind a `Sūci::nava()`/`Sūci::kṣamatā(n)` callee
+fn suci_constructor(callee: &ast::Expr) -> Option<&'static str> {
+    match callee {
+        ast::Expr::Identifier(ident) => match ident.name.as_str() {
+            "Sūci::nava" => Some("jag_suci_nava"),
+            "Sūci::kṣamatā" => Some("jag_suci_kshamata"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_suci(ty: &MirType) -> bool {
+    matches!(ty, MirType::Named(name) if name == "Sūci")
+}
+
+/// Arrays, slices and `Sūci`, whose values are the address of a header
+fn is_sequence(ty: &MirType) -> bool {
+    matches!(ty, MirType::Slice(_) | MirType::Array { .. }) || is_suci(ty)
+}
+
+fn is_assignment(op: ast::BinaryOp) -> bool {
+    matches!(
+        op,
+        ast::BinaryOp::Assign
+            | ast::BinaryOp::AddAssign
+            | ast::BinaryOp::SubAssign
+            | ast::BinaryOp::MulAssign
+            | ast::BinaryOp::DivAssign
+    )
+}
+
+fn local_place(local: usize) -> MirPlace {
+    MirPlace {
+        local,
+        projection: vec![],
+    }
+}
+
+/// Field `index` of the payload of `variant`, in the enum value at `local`
+fn payload_place(local: usize, variant: usize, index: usize) -> MirPlace {
+    MirPlace {
+        local,
+        projection: vec![
+            PlaceProjection::Downcast { variant },
+            PlaceProjection::Field { index },
+        ],
+    }
+}
+
+/// Symbol of a builtin function in the runtime library, or `name` itself
+fn runtime_symbol(name: &str) -> String {
+    match name {
+        "mudrā" => "jag_mudra".to_string(),
+        "vikṣepa" => "jag_viksepa".to_string(),
+        _ => name.to_string(),
+    }
+}
+
+/// `operand` with an integer constant given the width of `ty`, if an
+/// integer type
+fn sized(operand: MirOperand, ty: &MirType) -> MirOperand {
+    match (operand, ty) {
+        (MirOperand::Constant(MirConstant::Int(value, _)), MirType::Int(size)) => {
+            MirOperand::Constant(MirConstant::Int(value, *size))
+        }
+        (operand, _) => operand,
+    }
+}
+
+/// Signedness and width in bits of an integer size
+fn int_format(size: IntSize) -> (bool, i64) {
+    (size.is_signed(), i64::from(size.bits()))
+}
+
+/// `ty` at the width a `-t8`..`-t64`, `-f32` or `-f64` affix gives it,
+/// keeping its signedness; the type checker rejects any other pairing
+fn with_width(ty: MirType, width: Affix) -> MirType {
+    let bits = match width {
+        Affix::T8 => 8,
+        Affix::T16 => 16,
+        Affix::T32 => 32,
+        Affix::T64 => 64,
+        _ => 0,
+    };
+    match (ty, width) {
+        (MirType::Int(size), _) if bits > 0 => {
+            MirType::Int(IntSize::of(bits, size.is_signed()).unwrap_or(size))
+        }
+        (MirType::Float(_), Affix::F32) => MirType::Float(FloatSize::F32),
+        (MirType::Float(_), Affix::F64) => MirType::Float(FloatSize::F64),
+        (ty, _) => ty,
+    }
+}
+
+fn is_comparison(op: ast::BinaryOp) -> bool {
+    use ast::BinaryOp::*;
+    matches!(op, Eq | Ne | Lt | Le | Gt | Ge | And | Or)
+}
+
+/// Symbol name for a method of an impl or trait (`Bindu__dūrī`)
+pub(crate) fn mangle_method(owner: &str, method: &str) -> String {
+    format!("{}__{}", owner, method)
+}
+
+/// Type a `From` impl converts from (`S` of `From<S> for T`)
+fn conversion_source(impl_def: &ast::ImplDef) -> Option<&ast::Type> {
+    match &impl_def.trait_ref {
+        Some(bound) if bound.trait_name.name == "From" => bound.generics.first(),
+        _ => None,
+    }
+}
+
+/// Symbol of `from` in the `From<source>` impl of `owner`; a type converts
+/// from several, so the symbol names the source too
+fn conversion_symbol(owner: &str, source: &ast::Type) -> String {
+    format!(
+        "{}__{}",
+        mangle_method(owner, "from"),
+        impl_owner_name(source)
+    )
+}
+
+/// Symbol of `method` in the impl block `impl_def` for `owner`
+pub(crate) fn method_symbol(impl_def: &ast::ImplDef, owner: &str, method: &str) -> String {
+    match conversion_source(impl_def) {
+        Some(source) if method == "from" => conversion_symbol(owner, source),
+        _ => mangle_method(owner, method),
+    }
+}
+
+/// Name of the type an impl block is attached to
+pub(crate) fn impl_owner_name(self_ty: &ast::Type) -> String {
+    match self_ty {
+        ast::Type::Named { name, .. } => name.name.clone(),
+        ast::Type::Reference { inner, .. } => impl_owner_name(inner),
+        _ => "anonymous".to_string(),
+    }
+}
+
 impl Default for MirBuilder {
     fn default() -> Self {
         Self::new()
@@ -937,251 +2780,477 @@ impl MirBuilder {
         }
     }
 
-    /// Convert pattern to integer value for switch
-    fn pattern_to_int(&self, pattern: &ast::Pattern) -> Option<i64> {
-        match pattern {
-            ast::Pattern::Literal(lit) => match lit {
-                ast::Literal::Int(n) => Some(*n),
-                ast::Literal::Bool(b) => Some(if *b { 1 } else { 0 }),
-                ast::Literal::Char(c) => Some(*c as i64),
-                _ => None,
-            },
-            ast::Pattern::Identifier(_) => None, // Wildcard-like, don't add to switch
-            ast::Pattern::Wildcard => None,
-            ast::Pattern::Rest => None,
-            ast::Pattern::Constructor { name, .. } => {
-                // Would need enum type info for variant index
-                Some(name.name.chars().next()? as i64)
-            }
-            ast::Pattern::Binding { .. } => None, // Named binding, not a switch value
-            ast::Pattern::Tuple(_) => None,       // Complex pattern
-            ast::Pattern::Struct { .. } => None,  // Complex pattern
-            ast::Pattern::Variant { variant, .. } => {
-                // Enum variant - use first char as discriminant for now
-                Some(variant.name.chars().next()? as i64)
-            }
-            ast::Pattern::Array(_) => None,
-            ast::Pattern::Slice { .. } => None,
-            ast::Pattern::Range { .. } => None, // Would need range comparison
-            ast::Pattern::Or(_) => None,        // Multiple values
-            ast::Pattern::Guard { pattern, .. } => self.pattern_to_int(pattern),
-            ast::Pattern::Ref { pattern, .. } => self.pattern_to_int(pattern),
-        }
-    }
-
-    /// Bind pattern variables in scope
-    fn bind_pattern_variables(&mut self, pattern: &ast::Pattern, scrutinee_local: usize) {
-        match pattern {
-            ast::Pattern::Identifier(ident) => {
-                // Bind the identifier to the scrutinee value
-                let local = self.alloc_local(MirType::Int(IntSize::I64), Some(ident.name.clone()));
-                self.var_map.insert(ident.name.clone(), local);
-                self.emit_instruction(MirInstruction::Assign {
-                    dest: MirPlace {
-                        local,
-                        projection: vec![],
-                    },
-                    value: MirRvalue::Use(MirOperand::Copy(MirPlace {
-                        local: scrutinee_local,
-                        projection: vec![],
-                    })),
+    /// Lower a match through its decision tree (निर्णय वृक्ष)
+    ///
+    /// Every path into an arm assigns the arm's bindings to the same locals,
+    /// so each arm body is lowered once; its value goes to a result local.
+    fn lower_match(&mut self, scrutinee: &ast::Expr, arms: &[ast::MatchArm]) -> MirRvalue {
+        let value = self.lower_expr_to_rvalue(scrutinee);
+        let scrutinee_local = self.assign_typed(value, self.temp_type(scrutinee));
+        let result_type = match arms.first() {
+            Some(arm) => self.temp_type(&arm.body),
+            None => MirType::Int(IntSize::I64),
+        };
+        let result = self.alloc_local(result_type, None);
+        let merge_block_id = self.alloc_block();
+        let arm_blocks: Vec<usize> = arms.iter().map(|_| self.alloc_block()).collect();
+        let mut arm_locals: Vec<HashMap<String, usize>> = vec![HashMap::new(); arms.len()];
+
+        let decision = DecisionCompiler::new(&self.shapes).compile(arms);
+        self.lower_decision(&decision, scrutinee_local, &arm_blocks, &mut arm_locals);
+
+        for (i, arm) in arms.iter().enumerate() {
+            self.start_block(arm_blocks[i]);
+            let shadowed = self.bind_locals(&arm_locals[i]);
+            let value = self.lower_expr_to_rvalue(&arm.body);
+            self.emit_instruction(MirInstruction::Assign {
+                dest: MirPlace {
+                    local: result,
+                    projection: vec![],
+                },
+                value,
+            });
+            // An arm ending in `phera` has already set its terminator
+            if !ends_in_return(&arm.body) {
+                self.set_terminator(MirTerminator::Goto {
+                    target: merge_block_id,
                 });
             }
-            ast::Pattern::Constructor { name: _, fields } => {
-                // Constructor pattern (enum/struct variant)
-                for (i, sub_pattern) in fields.iter().enumerate() {
-                    if let ast::Pattern::Identifier(ident) = sub_pattern {
-                        let local =
-                            self.alloc_local(MirType::Int(IntSize::I64), Some(ident.name.clone()));
-                        self.var_map.insert(ident.name.clone(), local);
-                        // Extract i-th field from the constructor
-                        self.emit_instruction(MirInstruction::Assign {
-                            dest: MirPlace {
-                                local,
-                                projection: vec![],
-                            },
-                            value: MirRvalue::Field {
-                                base: MirOperand::Copy(MirPlace {
-                                    local: scrutinee_local,
-                                    projection: vec![],
-                                }),
-                                index: i,
-                            },
-                        });
-                    } else {
-                        // Recursively bind nested patterns
-                        self.bind_pattern_variables(sub_pattern, scrutinee_local);
+            self.unbind_locals(shadowed);
+        }
+
+        self.blocks.push(MirBasicBlock {
+            id: merge_block_id,
+            instructions: Vec::new(),
+            terminator: MirTerminator::Return,
+        });
+        self.current_block = self.blocks.len() - 1;
+        MirRvalue::Use(MirOperand::Copy(MirPlace {
+            local: result,
+            projection: vec![],
+        }))
+    }
+
+    /// Lower `operand?`: the payload of a `Saphala` or `Kiñcit`, while a
+    /// `Viphala` or `Śūnya` is returned from the function on the spot
+    ///
+    /// An error of another type than the function's is converted by the
+    /// `From` impl of the function's error type.
+    fn lower_try(&mut self, operand: &ast::Expr) -> MirRvalue {
+        let value = self.lower_expr_to_rvalue(operand);
+        let ty = self.temp_type(operand);
+        let (ok, fail) = self.try_variants(&ty);
+        let name = match &ty {
+            MirType::Enum { name, .. } => name.clone(),
+            _ => "Phala".to_string(),
+        };
+        let scrutinee = self.assign_typed(value, ty.clone());
+        let tag = self.assign_temp(MirRvalue::Discriminant(local_place(scrutinee)));
+        let ok_block = self.alloc_block();
+        let fail_block = self.alloc_block();
+        self.set_terminator(MirTerminator::SwitchInt {
+            discriminant: MirOperand::Copy(local_place(tag)),
+            targets: vec![(ok as i64, ok_block)],
+            otherwise: fail_block,
+        });
+
+        self.start_block(fail_block);
+        let field_type = |ty: &MirType, variant: usize| {
+            ty.enum_layout()
+                .and_then(|layout| layout.field(variant, 0))
+                .map(|(_, ty)| ty.clone())
+        };
+        let operands = match field_type(&ty, fail) {
+            Some(error_type) => {
+                let error = MirOperand::Copy(payload_place(scrutinee, fail, 0));
+                // The error the function returns, when `?` must convert to it
+                let conversion = match field_type(&self.return_type, fail) {
+                    Some(target) if target != error_type => match target {
+                        MirType::Named(target) | MirType::Enum { name: target, .. } => self
+                            .conversions
+                            .iter()
+                            .find(|(to, from, _)| *to == target && *from == error_type)
+                            .map(|(_, _, symbol)| symbol.clone()),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                match conversion {
+                    Some(symbol) => {
+                        let func = MirOperand::Constant(MirConstant::String(symbol));
+                        match self.lower_call(func, vec![error]) {
+                            MirRvalue::Use(converted) => vec![converted],
+                            _ => unreachable!("calls yield their result local"),
+                        }
                     }
+                    None => vec![error],
                 }
             }
-            ast::Pattern::Binding { name, subpattern, .. } => {
-                // Named binding - bind the name to scrutinee
-                let local = self.alloc_local(MirType::Int(IntSize::I64), Some(name.name.clone()));
-                self.var_map.insert(name.name.clone(), local);
-                self.emit_instruction(MirInstruction::Assign {
-                    dest: MirPlace {
-                        local,
-                        projection: vec![],
-                    },
-                    value: MirRvalue::Use(MirOperand::Copy(MirPlace {
-                        local: scrutinee_local,
-                        projection: vec![],
-                    })),
-                });
-                // Also bind subpattern if present
-                if let Some(sub) = subpattern {
-                    self.bind_pattern_variables(sub, scrutinee_local);
-                }
+            None => Vec::new(),
+        };
+        self.emit_instruction(MirInstruction::Assign {
+            dest: local_place(0),
+            value: MirRvalue::Aggregate {
+                kind: AggregateKind::Enum {
+                    name,
+                    variant: fail,
+                },
+                operands,
+            },
+        });
+        self.set_terminator(MirTerminator::Return);
+
+        self.start_block(ok_block);
+        let payload = field_type(&ty, ok).unwrap_or(MirType::Int(IntSize::I64));
+        let result = self.assign_typed(
+            MirRvalue::Use(MirOperand::Copy(payload_place(scrutinee, ok, 0))),
+            payload,
+        );
+        MirRvalue::Use(MirOperand::Copy(local_place(result)))
+    }
+
+    /// Variants `?` continues with and returns on, for a value of type `ty`
+    fn try_variants(&self, ty: &MirType) -> (usize, usize) {
+        let variant = |path: &str| self.shapes.constructor(path).map(|(_, v)| v);
+        let (ok, fail) = match ty {
+            MirType::Enum { name, .. } if name == "Vikalpa" => {
+                ("Vikalpa::Kiñcit", "Vikalpa::Śūnya")
             }
-            ast::Pattern::Tuple(patterns) => {
-                for (i, sub_pattern) in patterns.iter().enumerate() {
-                    // Extract i-th element of tuple
-                    let temp = self.alloc_local(MirType::Int(IntSize::I64), None);
+            _ => ("Phala::Saphala", "Phala::Viphala"),
+        };
+        (variant(ok).unwrap_or(0), variant(fail).unwrap_or(1))
+    }
+
+    /// Lower one node of a decision tree into the current block
+    fn lower_decision(
+        &mut self,
+        decision: &Decision,
+        scrutinee: usize,
+        arm_blocks: &[usize],
+        arm_locals: &mut [HashMap<String, usize>],
+    ) {
+        match decision {
+            // Unmatched values are ruled out by exhaustiveness checking
+            Decision::Fail => self.set_terminator(MirTerminator::Unreachable),
+            Decision::Leaf {
+                arm,
+                bindings,
+                guard,
+                otherwise,
+            } => {
+                for (name, access) in bindings {
+                    let source = self.access_local(scrutinee, access);
+                    let local = match arm_locals[*arm].get(name) {
+                        Some(&local) => local,
+                        None => {
+                            let ty = self.local_type(source);
+                            let local = self.alloc_local(ty, Some(name.clone()));
+                            arm_locals[*arm].insert(name.clone(), local);
+                            local
+                        }
+                    };
                     self.emit_instruction(MirInstruction::Assign {
                         dest: MirPlace {
-                            local: temp,
+                            local,
                             projection: vec![],
                         },
-                        value: MirRvalue::Field {
-                            base: MirOperand::Copy(MirPlace {
-                                local: scrutinee_local,
-                                projection: vec![],
-                            }),
-                            index: i,
-                        },
+                        value: MirRvalue::Use(MirOperand::Copy(MirPlace {
+                            local: source,
+                            projection: vec![],
+                        })),
                     });
-                    self.bind_pattern_variables(sub_pattern, temp);
                 }
-            }
-            ast::Pattern::Struct { fields, .. } => {
-                for (i, (_, sub_pattern)) in fields.iter().enumerate() {
-                    let temp = self.alloc_local(MirType::Int(IntSize::I64), None);
-                    self.emit_instruction(MirInstruction::Assign {
-                        dest: MirPlace {
-                            local: temp,
-                            projection: vec![],
-                        },
-                        value: MirRvalue::Field {
-                            base: MirOperand::Copy(MirPlace {
-                                local: scrutinee_local,
-                                projection: vec![],
-                            }),
-                            index: i,
-                        },
+                let Some(guard) = guard else {
+                    self.set_terminator(MirTerminator::Goto {
+                        target: arm_blocks[*arm],
                     });
-                    self.bind_pattern_variables(sub_pattern, temp);
+                    return;
+                };
+
+                let shadowed = self.bind_locals(&arm_locals[*arm]);
+                let condition = self.lower_expr_to_operand(guard);
+                self.unbind_locals(shadowed);
+                let fallback = self.alloc_block();
+                self.set_terminator(MirTerminator::SwitchInt {
+                    discriminant: condition,
+                    targets: vec![(1, arm_blocks[*arm])],
+                    otherwise: fallback,
+                });
+                self.start_block(fallback);
+                match otherwise {
+                    Some(rest) => self.lower_decision(rest, scrutinee, arm_blocks, arm_locals),
+                    None => self.set_terminator(MirTerminator::Unreachable),
                 }
             }
-            ast::Pattern::Variant { fields, .. } => {
-                match fields {
-                    ast::VariantFields::Unit => {}
-                    ast::VariantFields::Tuple(patterns) => {
-                        for (i, sub_pattern) in patterns.iter().enumerate() {
-                            let temp = self.alloc_local(MirType::Int(IntSize::I64), None);
-                            self.emit_instruction(MirInstruction::Assign {
-                                dest: MirPlace {
-                                    local: temp,
-                                    projection: vec![],
-                                },
-                                value: MirRvalue::Field {
-                                    base: MirOperand::Copy(MirPlace {
-                                        local: scrutinee_local,
-                                        projection: vec![],
-                                    }),
-                                    index: i + 1, // +1 to skip discriminant
-                                },
-                            });
-                            self.bind_pattern_variables(sub_pattern, temp);
-                        }
-                    }
-                    ast::VariantFields::Struct(field_patterns) => {
-                        for (i, (_, sub_pattern)) in field_patterns.iter().enumerate() {
-                            let temp = self.alloc_local(MirType::Int(IntSize::I64), None);
-                            self.emit_instruction(MirInstruction::Assign {
-                                dest: MirPlace {
-                                    local: temp,
-                                    projection: vec![],
-                                },
-                                value: MirRvalue::Field {
-                                    base: MirOperand::Copy(MirPlace {
-                                        local: scrutinee_local,
-                                        projection: vec![],
-                                    }),
-                                    index: i + 1,
-                                },
-                            });
-                            self.bind_pattern_variables(sub_pattern, temp);
-                        }
-                    }
+            Decision::Switch {
+                access,
+                discriminant,
+                cases,
+                default,
+            } => {
+                let mut value = self.access_local(scrutinee, access);
+                if *discriminant {
+                    value = self.assign_temp(MirRvalue::Discriminant(MirPlace {
+                        local: value,
+                        projection: vec![],
+                    }));
                 }
-            }
-            ast::Pattern::Array(patterns) => {
-                for (i, sub_pattern) in patterns.iter().enumerate() {
-                    let temp = self.alloc_local(MirType::Int(IntSize::I64), None);
-                    self.emit_instruction(MirInstruction::Assign {
-                        dest: MirPlace {
-                            local: temp,
-                            projection: vec![],
-                        },
-                        value: MirRvalue::Field {
-                            base: MirOperand::Copy(MirPlace {
-                                local: scrutinee_local,
-                                projection: vec![],
-                            }),
-                            index: i,
-                        },
-                    });
-                    self.bind_pattern_variables(sub_pattern, temp);
+                let targets: Vec<(i64, usize)> = cases
+                    .iter()
+                    .map(|(case, _)| (*case, self.alloc_block()))
+                    .collect();
+                let default_block = self.alloc_block();
+                self.set_terminator(MirTerminator::SwitchInt {
+                    discriminant: MirOperand::Copy(MirPlace {
+                        local: value,
+                        projection: vec![],
+                    }),
+                    targets: targets.clone(),
+                    otherwise: default_block,
+                });
+                for ((_, case), (_, block)) in cases.iter().zip(targets) {
+                    self.start_block(block);
+                    self.lower_decision(case, scrutinee, arm_blocks, arm_locals);
                 }
+                self.start_block(default_block);
+                self.lower_decision(default, scrutinee, arm_blocks, arm_locals);
             }
-            ast::Pattern::Slice { before, middle, after } => {
-                // Bind 'before' elements from start
-                for (i, sub_pattern) in before.iter().enumerate() {
-                    let temp = self.alloc_local(MirType::Int(IntSize::I64), None);
-                    self.emit_instruction(MirInstruction::Assign {
-                        dest: MirPlace {
-                            local: temp,
+            Decision::If {
+                access,
+                test,
+                then,
+                otherwise,
+            } => {
+                let condition = self.lower_test(scrutinee, access, test);
+                let then_block = self.alloc_block();
+                let else_block = self.alloc_block();
+                self.set_terminator(MirTerminator::SwitchInt {
+                    discriminant: condition,
+                    targets: vec![(1, then_block)],
+                    otherwise: else_block,
+                });
+                self.start_block(then_block);
+                self.lower_decision(then, scrutinee, arm_blocks, arm_locals);
+                self.start_block(else_block);
+                self.lower_decision(otherwise, scrutinee, arm_blocks, arm_locals);
+            }
+        }
+    }
+
+    /// Compute a two-way test on a sub-value, as a 0/1 operand
+    fn lower_test(&mut self, scrutinee: usize, access: &Access, test: &Test) -> MirOperand {
+        let value = self.access_local(scrutinee, access);
+        let operand = MirOperand::Copy(MirPlace {
+            local: value,
+            projection: vec![],
+        });
+        let compare = |op: BinaryOp, right: MirOperand| MirRvalue::BinaryOp {
+            op,
+            left: operand.clone(),
+            right,
+        };
+        let result = match test {
+            Test::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                let above = start.as_ref().map(|start| {
+                    let bound = self.lower_pattern_bound(start);
+                    self.assign_temp(compare(BinaryOp::Ge, bound))
+                });
+                let below = end.as_ref().map(|end| {
+                    let bound = self.lower_pattern_bound(end);
+                    let op = if *inclusive {
+                        BinaryOp::Le
+                    } else {
+                        BinaryOp::Lt
+                    };
+                    self.assign_temp(compare(op, bound))
+                });
+                match (above, below) {
+                    (Some(above), Some(below)) => self.assign_temp(MirRvalue::BinaryOp {
+                        op: BinaryOp::BitAnd,
+                        left: MirOperand::Copy(MirPlace {
+                            local: above,
                             projection: vec![],
-                        },
-                        value: MirRvalue::Field {
-                            base: MirOperand::Copy(MirPlace {
-                                local: scrutinee_local,
-                                projection: vec![],
-                            }),
-                            index: i,
-                        },
-                    });
-                    self.bind_pattern_variables(sub_pattern, temp);
-                }
-                // Middle binding captures the rest (would need slice operation)
-                if let Some(mid) = middle {
-                    self.bind_pattern_variables(mid, scrutinee_local);
-                }
-                // 'after' elements from end (would need length calculation)
-                for sub_pattern in after {
-                    self.bind_pattern_variables(sub_pattern, scrutinee_local);
+                        }),
+                        right: MirOperand::Copy(MirPlace {
+                            local: below,
+                            projection: vec![],
+                        }),
+                    }),
+                    (Some(only), None) | (None, Some(only)) => only,
+                    (None, None) => return MirOperand::Constant(MirConstant::Bool(true)),
                 }
             }
-            ast::Pattern::Range { .. } => {
-                // Range patterns don't bind variables directly
+            Test::Len { len, exact } => {
+                let length = self.assign_temp(MirRvalue::Len(MirPlace {
+                    local: value,
+                    projection: vec![],
+                }));
+                self.assign_temp(MirRvalue::BinaryOp {
+                    op: if *exact { BinaryOp::Eq } else { BinaryOp::Ge },
+                    left: MirOperand::Copy(MirPlace {
+                        local: length,
+                        projection: vec![],
+                    }),
+                    right: MirOperand::Constant(MirConstant::Int(*len as i64, IntSize::I64)),
+                })
             }
-            ast::Pattern::Or(patterns) => {
-                // For or-patterns, all branches must bind same names
-                // Just use first branch's bindings
-                if let Some(first) = patterns.first() {
-                    self.bind_pattern_variables(first, scrutinee_local);
-                }
+            Test::Equal(literal) => {
+                let constant = MirOperand::Constant(self.lower_literal(literal));
+                self.assign_temp(compare(BinaryOp::Eq, constant))
+            }
+        };
+        MirOperand::Copy(MirPlace {
+            local: result,
+            projection: vec![],
+        })
+    }
+
+    /// Lower a range bound: a literal, or a `sthira` constant
+    fn lower_pattern_bound(&mut self, bound: &ast::Pattern) -> MirOperand {
+        match bound {
+            ast::Pattern::Literal(literal) => MirOperand::Constant(self.lower_literal(literal)),
+            ast::Pattern::Identifier(name) | ast::Pattern::Binding { name, .. } => {
+                self.lower_expr_to_operand(&ast::Expr::Identifier(name.clone()))
+            }
+            _ => MirOperand::Constant(MirConstant::Int(0, IntSize::I64)),
+        }
+    }
+
+    /// Local holding the sub-value of the scrutinee at `access`
+    fn access_local(&mut self, scrutinee: usize, access: &Access) -> usize {
+        let place = |local| {
+            MirOperand::Copy(MirPlace {
+                local,
+                projection: vec![],
+            })
+        };
+        match access {
+            Access::Scrutinee => scrutinee,
+            Access::Field(base, index) => {
+                let base = self.access_local(scrutinee, base);
+                self.assign_temp(MirRvalue::Field {
+                    base: place(base),
+                    index: *index,
+                })
+            }
+            Access::Payload(base, variant, index) => {
+                let base = self.access_local(scrutinee, base);
+                let ty = self
+                    .local_type(base)
+                    .enum_layout()
+                    .and_then(|layout| layout.field(*variant, *index))
+                    .map_or(MirType::Int(IntSize::I64), |(_, ty)| ty.clone());
+                let place = payload_place(base, *variant, *index);
+                self.assign_typed(MirRvalue::Use(MirOperand::Copy(place)), ty)
             }
-            ast::Pattern::Guard { pattern, .. } => {
-                self.bind_pattern_variables(pattern, scrutinee_local);
+            Access::Index(base, index) => {
+                let base = self.access_local(scrutinee, base);
+                self.assign_temp(MirRvalue::Index {
+                    base: place(base),
+                    index: MirOperand::Constant(MirConstant::Int(*index as i64, IntSize::I64)),
+                })
             }
-            ast::Pattern::Ref { pattern, .. } => {
-                // Dereference and bind inner - use projection without MirProjection (simplified)
-                self.bind_pattern_variables(pattern, scrutinee_local);
+            Access::IndexFromEnd(base, from_end) => {
+                let base = self.access_local(scrutinee, base);
+                let length = self.assign_temp(MirRvalue::Len(MirPlace {
+                    local: base,
+                    projection: vec![],
+                }));
+                let index = self.assign_temp(MirRvalue::BinaryOp {
+                    op: BinaryOp::Sub,
+                    left: place(length),
+                    right: MirOperand::Constant(MirConstant::Int(*from_end as i64, IntSize::I64)),
+                });
+                self.assign_temp(MirRvalue::Index {
+                    base: place(base),
+                    index: place(index),
+                })
             }
-            ast::Pattern::Literal(_) | ast::Pattern::Wildcard | ast::Pattern::Rest => {
-                // No binding needed
+        }
+    }
+
+    /// Type of `local`
+    fn local_type(&self, local: usize) -> MirType {
+        self.locals
+            .iter()
+            .find(|l| l.index == local)
+            .map_or(MirType::Int(IntSize::I64), |l| l.ty.clone())
+    }
+
+    /// Assign `value` to a fresh temporary
+    fn assign_temp(&mut self, value: MirRvalue) -> usize {
+        self.assign_typed(value, MirType::Int(IntSize::I64))
+    }
+
+    /// Assign `value` to a fresh temporary of type `ty`
+    fn assign_typed(&mut self, value: MirRvalue, ty: MirType) -> usize {
+        let local = self.alloc_local(ty, None);
+        self.emit_instruction(MirInstruction::Assign {
+            dest: MirPlace {
+                local,
+                projection: vec![],
+            },
+            value,
+        });
+        local
+    }
+
+    /// Start lowering into a new block (its terminator is set by the caller)
+    fn start_block(&mut self, id: usize) {
+        self.blocks.push(MirBasicBlock {
+            id,
+            instructions: Vec::new(),
+            terminator: MirTerminator::Unreachable,
+        });
+        self.current_block = self.blocks.len() - 1;
+    }
+
+    /// Continue after `phera`, `break` or `continue` in a block nothing
+    /// jumps to, so the jump already set is not overwritten
+    fn start_dead_block(&mut self) {
+        let id = self.alloc_block();
+        self.start_block(id);
+    }
+
+    /// Bring pattern bindings into scope, returning what they shadow
+    fn bind_locals(
+        &mut self,
+        locals: &HashMap<String, usize>,
+    ) -> Vec<(String, Option<usize>, bool)> {
+        locals
+            .iter()
+            .map(|(name, &local)| {
+                let by_ref = self.by_ref_captures.remove(name);
+                (
+                    name.clone(),
+                    self.var_map.insert(name.clone(), local),
+                    by_ref,
+                )
+            })
+            .collect()
+    }
+
+    fn unbind_locals(&mut self, shadowed: Vec<(String, Option<usize>, bool)>) {
+        for (name, previous, by_ref) in shadowed {
+            match previous {
+                Some(local) => self.var_map.insert(name.clone(), local),
+                None => self.var_map.remove(&name),
+            };
+            if by_ref {
+                self.by_ref_captures.insert(name);
             }
         }
     }
 }
+
+/// Does an expression always leave the function through `phera`?
+fn ends_in_return(expr: &ast::Expr) -> bool {
+    match expr {
+        ast::Expr::Block(block) => matches!(block.stmts.last(), Some(ast::Stmt::Return { .. })),
+        _ => false,
+    }
+}
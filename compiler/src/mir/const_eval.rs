@@ -0,0 +1,1827 @@
+//! Const Evaluation - Sthira Gaṇanā (स्थिर गणना)
+//!
+//! Runs MIR at compile time, reducing `sthira` values, array lengths and
+//! the arguments given for const parameters to what they stand for. Only `sthira kāryakrama` functions may be called,
+//! and an evaluation is cut off after a number of steps, so a constant
+//! that never finishes is reported rather than hanging the build.
+//!
+//! Values are kept whole rather than as words: a tuple or struct as its
+//! fields, an enum value as its variant and payload. An array sits behind
+//! its `{data, len}` header as the MIR expects, and is reached through a
+//! pointer. Nothing is freed during an evaluation, so a `sthira` function
+//! may return an array it built.
+//!
+//! Integer arithmetic is checked whatever the guṇa: a result its type
+//! cannot hold, or a division by zero, stops evaluation with the chain of
+//! calls that led to it.
+
+use super::builder::{impl_owner_name, method_symbol, MirBuilder};
+use super::types::*;
+use crate::lexer::Span;
+use crate::modules::Visibility;
+use crate::parser::ast;
+use crate::visitor::{
+    walk_expr, walk_expr_mut, walk_stmt_mut, AstVisitor, AstVisitorMut, VisitResult,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::ControlFlow;
+
+/// Steps an evaluation may take before it is taken not to finish
+pub const DEFAULT_STEP_LIMIT: u64 = 1_000_000;
+
+/// Symbol prefix of the function computing a constant's value
+const CONSTANT_THUNK: &str = "sthira ";
+/// Symbol prefix of the function computing an array length
+const LENGTH_THUNK: &str = "dīrghatā ";
+/// Symbol prefix of the function computing a const argument
+const ARGUMENT_THUNK: &str = "prācala ";
+
+/// A value computed at compile time
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstValue {
+    Int(i64, IntSize),
+    Float(f64, FloatSize),
+    Bool(bool),
+    Unit,
+    Str(String),
+    /// Tuple, or the `{start, end}` of a range
+    Tuple(Vec<ConstValue>),
+    Struct {
+        name: String,
+        fields: Vec<ConstValue>,
+    },
+    /// Enum value: its variant's index and payload
+    Variant {
+        name: String,
+        variant: usize,
+        fields: Vec<ConstValue>,
+    },
+    /// Elements of an array, as stored behind its header
+    Array(Vec<ConstValue>),
+    /// Address of a value in the evaluator's memory
+    Pointer(Pointer),
+    /// A place not yet written
+    Uninit,
+}
+
+/// Address of a value: an allocation, then the fields and elements leading
+/// from its value to the one pointed at
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pointer {
+    alloc: usize,
+    path: Vec<usize>,
+}
+
+impl ConstValue {
+    /// Fields or elements of a composite value
+    fn parts(&self) -> Option<&Vec<ConstValue>> {
+        match self {
+            ConstValue::Tuple(parts)
+            | ConstValue::Struct { fields: parts, .. }
+            | ConstValue::Variant { fields: parts, .. }
+            | ConstValue::Array(parts) => Some(parts),
+            _ => None,
+        }
+    }
+
+    fn parts_mut(&mut self) -> Option<&mut Vec<ConstValue>> {
+        match self {
+            ConstValue::Tuple(parts)
+            | ConstValue::Struct { fields: parts, .. }
+            | ConstValue::Variant { fields: parts, .. }
+            | ConstValue::Array(parts) => Some(parts),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ConstValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstValue::Int(value, size) => write!(f, "{}", widen(*value, *size)),
+            ConstValue::Float(value, _) => write!(f, "{}", value),
+            ConstValue::Bool(true) => write!(f, "satya"),
+            ConstValue::Bool(false) => write!(f, "asatya"),
+            ConstValue::Unit => write!(f, "()"),
+            ConstValue::Str(text) => write!(f, "{:?}", text),
+            ConstValue::Tuple(_) => write!(f, "a tuple"),
+            ConstValue::Struct { name, .. } | ConstValue::Variant { name, .. } => {
+                write!(f, "a value of `{}`", name)
+            }
+            ConstValue::Array(_) => write!(f, "an array"),
+            ConstValue::Pointer(_) => write!(f, "a reference"),
+            ConstValue::Uninit => write!(f, "an unset value"),
+        }
+    }
+}
+
+/// Why an evaluation stopped
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstErrorKind {
+    /// An integer result its type cannot hold
+    Overflow {
+        expression: String,
+        ty: IntSize,
+    },
+    /// An integer divided by zero
+    DivisionByZero {
+        expression: String,
+    },
+    /// A value given a type that cannot hold it
+    OutOfRange {
+        value: String,
+        ty: IntSize,
+    },
+    IndexOutOfBounds {
+        index: i64,
+        len: i64,
+    },
+    /// A failed assertion, with its message
+    AssertFailed(String),
+    /// More steps than the limit, which is given
+    StepLimit(u64),
+    /// An array length that is not a non-negative integer
+    InvalidLength(String),
+    /// A const argument that is not an integer
+    InvalidArgument(String),
+    /// Something that cannot be done at compile time, described
+    NotConst(String),
+}
+
+impl fmt::Display for ConstErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstErrorKind::Overflow { expression, ty } => write!(
+                f,
+                "attempt to compute `{}`, which overflows `{}`",
+                expression,
+                int_name(*ty)
+            ),
+            ConstErrorKind::DivisionByZero { expression } => {
+                write!(f, "attempt to compute `{}`, a division by zero", expression)
+            }
+            ConstErrorKind::OutOfRange { value, ty } => {
+                write!(
+                    f,
+                    "the value `{}` does not fit in `{}`",
+                    value,
+                    int_name(*ty)
+                )
+            }
+            ConstErrorKind::IndexOutOfBounds { index, len } => write!(
+                f,
+                "index {} is out of bounds for an array of length {}",
+                index, len
+            ),
+            ConstErrorKind::AssertFailed(message) => write!(f, "assertion failed: {}", message),
+            ConstErrorKind::StepLimit(limit) => {
+                write!(f, "evaluation did not finish within {} steps", limit)
+            }
+            ConstErrorKind::InvalidLength(value) => write!(
+                f,
+                "array length must be a non-negative integer, found `{}`",
+                value
+            ),
+            ConstErrorKind::InvalidArgument(value) => {
+                write!(f, "const argument must be an integer, found `{}`", value)
+            }
+            ConstErrorKind::NotConst(what) => write!(f, "{}", what),
+        }
+    }
+}
+
+/// An evaluation that stopped, and where
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstEvalError {
+    pub kind: ConstErrorKind,
+    /// Functions being evaluated when it stopped, innermost first
+    pub backtrace: Vec<String>,
+}
+
+impl fmt::Display for ConstEvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        for frame in &self.backtrace {
+            write!(f, "\n  in {}", frame)?;
+        }
+        Ok(())
+    }
+}
+
+fn not_const(what: impl Into<String>) -> ConstErrorKind {
+    ConstErrorKind::NotConst(what.into())
+}
+
+/// A function that can be called, with what running it needs
+struct Callee<'m> {
+    function: &'m MirFunction,
+    /// Position of each block in `function.blocks`, by id
+    blocks: HashMap<usize, usize>,
+    /// Type of each local
+    locals: HashMap<usize, &'m MirType>,
+}
+
+/// One call being evaluated
+struct Frame<'e, 'm> {
+    callee: &'e Callee<'m>,
+    /// Allocation of each local
+    locals: HashMap<usize, usize>,
+    block: usize,
+    instruction: usize,
+    /// Where the caller takes the result, and the block it continues in
+    destination: Option<MirPlace>,
+    target: usize,
+}
+
+impl Frame<'_, '_> {
+    fn jump(&mut self, id: usize) -> Result<(), ConstErrorKind> {
+        self.block = *self.callee.blocks.get(&id).ok_or_else(|| {
+            not_const(format!(
+                "`{}` has no block {}",
+                self.callee.function.name, id
+            ))
+        })?;
+        self.instruction = 0;
+        Ok(())
+    }
+
+    /// Integer type of the value written to `place`, if it is a local's
+    fn int_size(&self, place: &MirPlace) -> Option<IntSize> {
+        match self.callee.locals.get(&place.local) {
+            Some(MirType::Int(size)) if place.projection.is_empty() => Some(*size),
+            _ => None,
+        }
+    }
+}
+
+/// Interpreter of MIR functions at compile time (स्थिर गणक)
+pub struct ConstEvaluator<'m> {
+    functions: HashMap<&'m str, Callee<'m>>,
+    /// Functions that cannot be run, with the reason
+    forbidden: HashMap<String, String>,
+    step_limit: u64,
+}
+
+impl<'m> ConstEvaluator<'m> {
+    /// Evaluator that may call the functions of `module`
+    pub fn new(module: &'m MirModule) -> Self {
+        let functions = module
+            .functions
+            .iter()
+            .map(|function| {
+                let callee = Callee {
+                    function,
+                    blocks: function
+                        .blocks
+                        .iter()
+                        .enumerate()
+                        .map(|(position, block)| (block.id, position))
+                        .collect(),
+                    locals: function.locals.iter().map(|l| (l.index, &l.ty)).collect(),
+                };
+                (function.name.as_str(), callee)
+            })
+            .collect();
+        Self {
+            functions,
+            forbidden: HashMap::new(),
+            step_limit: DEFAULT_STEP_LIMIT,
+        }
+    }
+
+    /// Stop each evaluation after `limit` steps
+    pub fn with_step_limit(mut self, limit: u64) -> Self {
+        self.step_limit = limit;
+        self
+    }
+
+    /// Refuse to run function `name`, for `reason`
+    pub fn forbid(&mut self, name: &str, reason: String) {
+        self.forbidden.insert(name.to_string(), reason);
+    }
+
+    /// Call function `name` with `args` and return its result, with any
+    /// array it refers to in place of the reference
+    pub fn call(&self, name: &str, args: Vec<ConstValue>) -> Result<ConstValue, ConstEvalError> {
+        let mut memory = Memory {
+            values: Vec::new(),
+            steps: 0,
+        };
+        let mut frames = Vec::new();
+        let result = self
+            .enter(name, args, None, 0, &mut memory)
+            .and_then(|frame| {
+                frames.push(frame);
+                loop {
+                    if let Some(value) = self.step(&mut frames, &mut memory)? {
+                        return Ok(value);
+                    }
+                }
+            });
+        result
+            .map(|value| memory.resolve(&value))
+            .map_err(|kind| ConstEvalError {
+                kind,
+                backtrace: std::iter::once(name.to_string())
+                    .chain(
+                        frames
+                            .iter()
+                            .skip(1)
+                            .map(|f| f.callee.function.name.clone()),
+                    )
+                    .rev()
+                    .collect(),
+            })
+    }
+
+    /// Frame for a call of `name`, its parameters set to `args`
+    fn enter(
+        &self,
+        name: &str,
+        args: Vec<ConstValue>,
+        destination: Option<MirPlace>,
+        target: usize,
+        memory: &mut Memory,
+    ) -> Result<Frame<'_, 'm>, ConstErrorKind> {
+        if let Some(reason) = self.forbidden.get(name) {
+            return Err(not_const(reason.clone()));
+        }
+        let callee = self.functions.get(name).ok_or_else(|| {
+            not_const(format!(
+                "`{}` is not a `sthira` function, so cannot be called at compile time",
+                name
+            ))
+        })?;
+        let mut frame = Frame {
+            callee,
+            locals: HashMap::new(),
+            block: 0,
+            instruction: 0,
+            destination,
+            target,
+        };
+        for (param, arg) in callee.function.params.iter().zip(args) {
+            let alloc = memory.alloc(arg);
+            frame.locals.insert(param.index, alloc);
+        }
+        Ok(frame)
+    }
+
+    /// Run one instruction or terminator of the innermost call; the result
+    /// once the outermost call returns
+    fn step<'e>(
+        &'e self,
+        frames: &mut Vec<Frame<'e, 'm>>,
+        memory: &mut Memory,
+    ) -> Result<Option<ConstValue>, ConstErrorKind> {
+        memory.steps += 1;
+        if memory.steps > self.step_limit {
+            return Err(ConstErrorKind::StepLimit(self.step_limit));
+        }
+        let frame = frames.last_mut().expect("a call is being evaluated");
+        let block = &frame.callee.function.blocks[frame.block];
+        if let Some(instruction) = block.instructions.get(frame.instruction) {
+            frame.instruction += 1;
+            memory.execute(frame, instruction)?;
+            return Ok(None);
+        }
+
+        match &block.terminator {
+            MirTerminator::Goto { target } => frame.jump(*target)?,
+            MirTerminator::SwitchInt {
+                discriminant,
+                targets,
+                otherwise,
+            } => {
+                let value = match memory.operand(frame, discriminant)? {
+                    ConstValue::Int(value, _) => value,
+                    ConstValue::Bool(value) => value as i64,
+                    other => return Err(not_const(format!("branches on {}", other))),
+                };
+                let target = targets
+                    .iter()
+                    .find(|(case, _)| *case == value)
+                    .map_or(*otherwise, |(_, target)| *target);
+                frame.jump(target)?;
+            }
+            MirTerminator::Call {
+                func,
+                args,
+                destination,
+                target,
+            } => {
+                let name = match func {
+                    MirOperand::Constant(MirConstant::String(name)) => name,
+                    _ => return Err(not_const("closures cannot be called at compile time")),
+                };
+                let args = args
+                    .iter()
+                    .map(|arg| memory.operand(frame, arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let callee = self.enter(name, args, destination.clone(), *target, memory)?;
+                frames.push(callee);
+            }
+            MirTerminator::Return => {
+                let frame = frames.pop().expect("a call is being evaluated");
+                // The result is left in `_0`
+                let value = match frame.locals.get(&0) {
+                    Some(&alloc) => match &memory.values[alloc] {
+                        ConstValue::Uninit => ConstValue::Unit,
+                        value => value.clone(),
+                    },
+                    None => ConstValue::Unit,
+                };
+                let Some(caller) = frames.last_mut() else {
+                    return Ok(Some(value));
+                };
+                if let Some(destination) = &frame.destination {
+                    memory.write(caller, destination, value)?;
+                }
+                caller.jump(frame.target)?;
+            }
+            MirTerminator::Unreachable | MirTerminator::Unwind => {
+                return Err(not_const("evaluation reached unreachable code"))
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// The values of one evaluation, and the steps it has taken
+struct Memory {
+    values: Vec<ConstValue>,
+    steps: u64,
+}
+
+impl Memory {
+    fn alloc(&mut self, value: ConstValue) -> usize {
+        self.values.push(value);
+        self.values.len() - 1
+    }
+
+    /// Allocation of `local` in `frame`, made on first use
+    fn local(&mut self, frame: &mut Frame, local: usize) -> usize {
+        if let Some(&alloc) = frame.locals.get(&local) {
+            return alloc;
+        }
+        let alloc = self.alloc(ConstValue::Uninit);
+        frame.locals.insert(local, alloc);
+        alloc
+    }
+
+    /// Address of `place`
+    fn locate(&mut self, frame: &mut Frame, place: &MirPlace) -> Result<Pointer, ConstErrorKind> {
+        let mut pointer = Pointer {
+            alloc: self.local(frame, place.local),
+            path: Vec::new(),
+        };
+        for projection in &place.projection {
+            match projection {
+                PlaceProjection::Deref => match self.get(&pointer)? {
+                    ConstValue::Pointer(target) => pointer = target.clone(),
+                    other => return Err(not_const(format!("dereferences {}", other))),
+                },
+                PlaceProjection::Field { index } => pointer.path.push(*index),
+                PlaceProjection::ConstIndex { offset } => pointer.path.push(*offset),
+                PlaceProjection::Index { index } => {
+                    let index = self.int(frame, index)?;
+                    pointer.path.push(index as usize);
+                }
+                // Fields of a variant are the payload's
+                PlaceProjection::Downcast { .. } => {}
+                PlaceProjection::FieldNamed { name, .. } => {
+                    return Err(not_const(format!("field `{}` cannot be read here", name)))
+                }
+                PlaceProjection::Subslice { .. } => {
+                    return Err(not_const("slices cannot be taken at compile time"))
+                }
+            }
+        }
+        Ok(pointer)
+    }
+
+    fn get(&self, pointer: &Pointer) -> Result<&ConstValue, ConstErrorKind> {
+        let mut value = &self.values[pointer.alloc];
+        for &index in &pointer.path {
+            value = match value {
+                ConstValue::Array(elements) => {
+                    elements
+                        .get(index)
+                        .ok_or(ConstErrorKind::IndexOutOfBounds {
+                            index: index as i64,
+                            len: elements.len() as i64,
+                        })?
+                }
+                other => other
+                    .parts()
+                    .and_then(|parts| parts.get(index))
+                    .ok_or_else(|| not_const(format!("{} has no field {}", other, index)))?,
+            };
+        }
+        Ok(value)
+    }
+
+    /// The value at `pointer` for writing; an unset value is taken to be
+    /// a tuple growing to the fields written
+    fn get_mut(&mut self, pointer: &Pointer) -> Result<&mut ConstValue, ConstErrorKind> {
+        let mut value = &mut self.values[pointer.alloc];
+        for &index in &pointer.path {
+            if *value == ConstValue::Uninit {
+                *value = ConstValue::Tuple(Vec::new());
+            }
+            value = match value {
+                ConstValue::Array(elements) => {
+                    let len = elements.len() as i64;
+                    elements
+                        .get_mut(index)
+                        .ok_or(ConstErrorKind::IndexOutOfBounds {
+                            index: index as i64,
+                            len,
+                        })?
+                }
+                other => {
+                    let description = other.to_string();
+                    let parts = other
+                        .parts_mut()
+                        .ok_or_else(|| not_const(format!("{} has no fields", description)))?;
+                    if parts.len() <= index {
+                        parts.resize(index + 1, ConstValue::Uninit);
+                    }
+                    &mut parts[index]
+                }
+            };
+        }
+        Ok(value)
+    }
+
+    fn read(&mut self, frame: &mut Frame, place: &MirPlace) -> Result<ConstValue, ConstErrorKind> {
+        let pointer = self.locate(frame, place)?;
+        match self.get(&pointer)? {
+            ConstValue::Uninit => Err(not_const("a value is read before it is set")),
+            value => Ok(value.clone()),
+        }
+    }
+
+    fn write(
+        &mut self,
+        frame: &mut Frame,
+        place: &MirPlace,
+        value: ConstValue,
+    ) -> Result<(), ConstErrorKind> {
+        let pointer = self.locate(frame, place)?;
+        *self.get_mut(&pointer)? = value;
+        Ok(())
+    }
+
+    fn operand(
+        &mut self,
+        frame: &mut Frame,
+        operand: &MirOperand,
+    ) -> Result<ConstValue, ConstErrorKind> {
+        match operand {
+            MirOperand::Constant(constant) => Ok(match constant {
+                MirConstant::Int(value, size) => ConstValue::Int(*value, *size),
+                MirConstant::Float(value, size) => ConstValue::Float(*value, *size),
+                MirConstant::Bool(value) => ConstValue::Bool(*value),
+                MirConstant::Unit => ConstValue::Unit,
+                MirConstant::String(text) => ConstValue::Str(text.clone()),
+            }),
+            MirOperand::Copy(place) | MirOperand::Move(place) => self.read(frame, place),
+        }
+    }
+
+    fn int(&mut self, frame: &mut Frame, operand: &MirOperand) -> Result<i64, ConstErrorKind> {
+        match self.operand(frame, operand)? {
+            ConstValue::Int(value, _) => Ok(value),
+            other => Err(not_const(format!("{} is used as an integer", other))),
+        }
+    }
+
+    /// The pointer `operand` holds, and the value it points at
+    fn pointee(
+        &mut self,
+        frame: &mut Frame,
+        operand: &MirOperand,
+    ) -> Result<(Pointer, ConstValue), ConstErrorKind> {
+        match self.operand(frame, operand)? {
+            ConstValue::Pointer(pointer) => {
+                let value = self.get(&pointer)?.clone();
+                Ok((pointer, value))
+            }
+            other => Err(not_const(format!("{} is used as a reference", other))),
+        }
+    }
+
+    fn execute(
+        &mut self,
+        frame: &mut Frame,
+        instruction: &MirInstruction,
+    ) -> Result<(), ConstErrorKind> {
+        match instruction {
+            MirInstruction::Assign { dest, value } => {
+                let value = self.rvalue(frame, value, dest)?;
+                self.write(frame, dest, value)
+            }
+            MirInstruction::Drop { .. } | MirInstruction::Nop => Ok(()),
+            MirInstruction::Assert { condition, message } => {
+                match self.operand(frame, condition)? {
+                    ConstValue::Bool(true) => Ok(()),
+                    ConstValue::Bool(false) => Err(ConstErrorKind::AssertFailed(message.clone())),
+                    other => Err(not_const(format!("asserts {}", other))),
+                }
+            }
+            MirInstruction::Store { ptr, value } => {
+                let value = self.operand(frame, value)?;
+                let (pointer, _) = self.pointee(frame, ptr)?;
+                *self.get_mut(&pointer)? = value;
+                Ok(())
+            }
+            // The first word behind a pointer: an array's data, a range's start
+            MirInstruction::Load { dest, ptr } => {
+                let (mut pointer, target) = self.pointee(frame, ptr)?;
+                let value = match target {
+                    ConstValue::Array(_) => {
+                        pointer.path.push(0);
+                        ConstValue::Pointer(pointer)
+                    }
+                    ConstValue::Tuple(fields) | ConstValue::Struct { fields, .. } => fields
+                        .into_iter()
+                        .next()
+                        .ok_or_else(|| not_const("an empty value is read"))?,
+                    value => value,
+                };
+                self.write(frame, dest, value)
+            }
+            MirInstruction::SetDiscriminant { place, variant } => {
+                let pointer = self.locate(frame, place)?;
+                match self.get_mut(&pointer)? {
+                    ConstValue::Variant { variant: tag, .. } => *tag = *variant,
+                    value => {
+                        *value = ConstValue::Variant {
+                            name: String::new(),
+                            variant: *variant,
+                            fields: Vec::new(),
+                        }
+                    }
+                }
+                Ok(())
+            }
+            MirInstruction::BoundsCheck { index, len, .. } => {
+                let index = self.int(frame, index)?;
+                let len = self.int(frame, len)?;
+                if (index as u64) < len as u64 {
+                    Ok(())
+                } else {
+                    Err(ConstErrorKind::IndexOutOfBounds { index, len })
+                }
+            }
+        }
+    }
+
+    fn rvalue(
+        &mut self,
+        frame: &mut Frame,
+        rvalue: &MirRvalue,
+        dest: &MirPlace,
+    ) -> Result<ConstValue, ConstErrorKind> {
+        match rvalue {
+            MirRvalue::Use(operand) => self.operand(frame, operand),
+            MirRvalue::Ref { place, .. } | MirRvalue::AddressOf { place, .. } => {
+                Ok(ConstValue::Pointer(self.locate(frame, place)?))
+            }
+            MirRvalue::BinaryOp { op, left, right } => {
+                let left = self.operand(frame, left)?;
+                let right = self.operand(frame, right)?;
+                binary(*op, left, right, frame.int_size(dest))
+            }
+            MirRvalue::UnaryOp { op, operand } => {
+                let operand = self.operand(frame, operand)?;
+                unary(*op, operand)
+            }
+            MirRvalue::Aggregate { kind, operands } => {
+                let parts = operands
+                    .iter()
+                    .map(|operand| self.operand(frame, operand))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(match kind {
+                    AggregateKind::Tuple => ConstValue::Tuple(parts),
+                    AggregateKind::Array => ConstValue::Array(parts),
+                    AggregateKind::Struct { name } => ConstValue::Struct {
+                        name: name.clone(),
+                        fields: parts,
+                    },
+                    AggregateKind::Enum { name, variant } => ConstValue::Variant {
+                        name: name.clone(),
+                        variant: *variant,
+                        fields: parts,
+                    },
+                    AggregateKind::Closure { .. } => {
+                        return Err(not_const("closures cannot be made at compile time"))
+                    }
+                })
+            }
+            MirRvalue::Cast { kind, operand, ty } => {
+                let value = self.operand(frame, operand)?;
+                match kind {
+                    CastKind::Numeric => cast(value, ty),
+                    CastKind::Pointer | CastKind::Reborrow => Ok(value),
+                }
+            }
+            MirRvalue::Discriminant(place) => match self.read(frame, place)? {
+                ConstValue::Variant { variant, .. } => {
+                    Ok(ConstValue::Int(variant as i64, IntSize::I64))
+                }
+                other => Err(not_const(format!("{} has no variant", other))),
+            },
+            // The second word behind a pointer: an array's length, a range's end
+            MirRvalue::Len(place) => {
+                let base = MirOperand::Copy(place.clone());
+                match self.pointee(frame, &base)?.1 {
+                    ConstValue::Array(elements) => {
+                        Ok(ConstValue::Int(elements.len() as i64, IntSize::I64))
+                    }
+                    ConstValue::Tuple(fields) if fields.len() == 2 => Ok(fields[1].clone()),
+                    other => Err(not_const(format!("{} has no length", other))),
+                }
+            }
+            MirRvalue::Field { base, index } => {
+                let value = match self.operand(frame, base)? {
+                    ConstValue::Pointer(pointer) => self.get(&pointer)?.clone(),
+                    value => value,
+                };
+                value
+                    .parts()
+                    .and_then(|parts| parts.get(*index))
+                    .cloned()
+                    .ok_or_else(|| not_const(format!("{} has no field {}", value, index)))
+            }
+            MirRvalue::Index { base, index } => {
+                let (_, target) = self.pointee(frame, base)?;
+                let index = self.int(frame, index)?;
+                match target {
+                    ConstValue::Array(elements) => usize::try_from(index)
+                        .ok()
+                        .and_then(|i| elements.get(i))
+                        .cloned()
+                        .ok_or(ConstErrorKind::IndexOutOfBounds {
+                            index,
+                            len: elements.len() as i64,
+                        }),
+                    other => Err(not_const(format!("{} cannot be indexed", other))),
+                }
+            }
+            MirRvalue::FloatOp { op, left, right } => {
+                let left = self.operand(frame, left)?;
+                let right = self.operand(frame, right)?;
+                let (ConstValue::Float(a, size), ConstValue::Float(b, _)) = (&left, &right) else {
+                    return Err(not_const(format!("{} and {} are not floats", left, right)));
+                };
+                let (a, b) = (*a, *b);
+                Ok(match op {
+                    FloatBinaryOp::Add => float(a + b, *size),
+                    FloatBinaryOp::Sub => float(a - b, *size),
+                    FloatBinaryOp::Mul => float(a * b, *size),
+                    FloatBinaryOp::Div => float(a / b, *size),
+                    FloatBinaryOp::Min => float(a.min(b), *size),
+                    FloatBinaryOp::Max => float(a.max(b), *size),
+                    FloatBinaryOp::Cmp(cmp) => ConstValue::Bool(match cmp {
+                        FloatCmp::Eq => a == b,
+                        FloatCmp::Ne => a != b,
+                        FloatCmp::Lt => a < b,
+                        FloatCmp::Le => a <= b,
+                        FloatCmp::Gt => a > b,
+                        FloatCmp::Ge => a >= b,
+                        FloatCmp::Ord => !a.is_nan() && !b.is_nan(),
+                        FloatCmp::Unord => a.is_nan() || b.is_nan(),
+                    }),
+                })
+            }
+            MirRvalue::SimdOp { .. } => Err(not_const("SIMD cannot be evaluated at compile time")),
+        }
+    }
+
+    /// `value` with every array it refers to in place of the reference
+    fn resolve(&self, value: &ConstValue) -> ConstValue {
+        match value {
+            ConstValue::Pointer(pointer) => match self.get(pointer) {
+                Ok(target @ ConstValue::Array(_)) => self.resolve(target),
+                _ => value.clone(),
+            },
+            ConstValue::Tuple(parts) => {
+                ConstValue::Tuple(parts.iter().map(|p| self.resolve(p)).collect())
+            }
+            ConstValue::Array(parts) => {
+                ConstValue::Array(parts.iter().map(|p| self.resolve(p)).collect())
+            }
+            ConstValue::Struct { name, fields } => ConstValue::Struct {
+                name: name.clone(),
+                fields: fields.iter().map(|f| self.resolve(f)).collect(),
+            },
+            ConstValue::Variant {
+                name,
+                variant,
+                fields,
+            } => ConstValue::Variant {
+                name: name.clone(),
+                variant: *variant,
+                fields: fields.iter().map(|f| self.resolve(f)).collect(),
+            },
+            value => value.clone(),
+        }
+    }
+}
+
+/// Source name of an integer type
+fn int_name(size: IntSize) -> &'static str {
+    match size {
+        IntSize::I8 => "i8",
+        IntSize::I16 => "i16",
+        IntSize::I32 => "i32",
+        IntSize::I64 => "i64",
+        IntSize::U8 => "u8",
+        IntSize::U16 => "u16",
+        IntSize::U32 => "u32",
+        IntSize::U64 => "u64",
+    }
+}
+
+/// The number an integer of `size` kept in its slot as `value` stands for
+fn widen(value: i64, size: IntSize) -> i128 {
+    match size {
+        IntSize::U64 => value as u64 as i128,
+        _ => value as i128,
+    }
+}
+
+/// Whether `size` can hold `value`
+fn holds(size: IntSize, value: i128) -> bool {
+    let bits = size.bits();
+    if size.is_signed() {
+        let bound = 1i128 << (bits - 1);
+        (-bound..bound).contains(&value)
+    } else {
+        (0..1i128 << bits).contains(&value)
+    }
+}
+
+fn float(value: f64, size: FloatSize) -> ConstValue {
+    match size {
+        FloatSize::F32 => ConstValue::Float(value as f32 as f64, size),
+        FloatSize::F64 => ConstValue::Float(value, size),
+    }
+}
+
+fn operator(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Rem => "%",
+        BinaryOp::BitAnd => "&",
+        BinaryOp::BitOr => "|",
+        BinaryOp::BitXor => "^",
+        BinaryOp::Shl => "<<",
+        BinaryOp::Shr => ">>",
+        BinaryOp::Eq => "==",
+        BinaryOp::Ne => "!=",
+        BinaryOp::Lt => "<",
+        BinaryOp::Le => "<=",
+        BinaryOp::Gt => ">",
+        BinaryOp::Ge => ">=",
+    }
+}
+
+/// `left op right`; integer arithmetic is done in `size`, the type of
+/// the place written, or else the left operand's
+fn binary(
+    op: BinaryOp,
+    left: ConstValue,
+    right: ConstValue,
+    size: Option<IntSize>,
+) -> Result<ConstValue, ConstErrorKind> {
+    use ConstValue::{Bool, Float, Int, Pointer as Ptr, Str};
+
+    let compare = |ordering: std::cmp::Ordering| {
+        use std::cmp::Ordering::*;
+        Bool(match op {
+            BinaryOp::Eq => ordering == Equal,
+            BinaryOp::Ne => ordering != Equal,
+            BinaryOp::Lt => ordering == Less,
+            BinaryOp::Le => ordering != Greater,
+            BinaryOp::Gt => ordering == Greater,
+            _ => ordering != Less,
+        })
+    };
+    let is_comparison = matches!(
+        op,
+        BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge
+    );
+
+    match (left, right) {
+        (Int(a, left_size), Int(b, _)) => {
+            let size = match size {
+                Some(size) if !is_comparison => size,
+                _ => left_size,
+            };
+            let (x, y) = (widen(a, size), widen(b, size));
+            if is_comparison {
+                return Ok(compare(x.cmp(&y)));
+            }
+            let expression = || format!("{} {} {}", x, operator(op), y);
+            let result = match op {
+                BinaryOp::Add => x + y,
+                BinaryOp::Sub => x - y,
+                BinaryOp::Mul => x * y,
+                BinaryOp::Div | BinaryOp::Rem if y == 0 => {
+                    return Err(ConstErrorKind::DivisionByZero {
+                        expression: expression(),
+                    })
+                }
+                BinaryOp::Div => x / y,
+                // The one quotient that overflows leaves no remainder to
+                // compute either: `idiv` faults on it
+                BinaryOp::Rem if !holds(size, x / y) => {
+                    return Err(ConstErrorKind::Overflow {
+                        expression: expression(),
+                        ty: size,
+                    })
+                }
+                BinaryOp::Rem => x % y,
+                BinaryOp::BitAnd => x & y,
+                BinaryOp::BitOr => x | y,
+                BinaryOp::BitXor => x ^ y,
+                BinaryOp::Shl | BinaryOp::Shr if !(0..size.bits() as i128).contains(&y) => {
+                    return Err(ConstErrorKind::Overflow {
+                        expression: expression(),
+                        ty: size,
+                    })
+                }
+                // Bits shifted out are lost, as at run time
+                BinaryOp::Shl => widen(size.wrap(a << y), size),
+                BinaryOp::Shr => x >> y,
+                _ => unreachable!("comparisons are handled above"),
+            };
+            if !holds(size, result) {
+                return Err(ConstErrorKind::Overflow {
+                    expression: expression(),
+                    ty: size,
+                });
+            }
+            Ok(Int(size.wrap(result as i64), size))
+        }
+        (Float(a, size), Float(b, _)) => Ok(match op {
+            BinaryOp::Add => float(a + b, size),
+            BinaryOp::Sub => float(a - b, size),
+            BinaryOp::Mul => float(a * b, size),
+            BinaryOp::Div => float(a / b, size),
+            BinaryOp::Rem => float(a % b, size),
+            _ if is_comparison => match a.partial_cmp(&b) {
+                Some(ordering) => compare(ordering),
+                // Only `!=` holds of NaN
+                None => Bool(op == BinaryOp::Ne),
+            },
+            _ => {
+                return Err(not_const(format!(
+                    "`{}` does not apply to floats",
+                    operator(op)
+                )))
+            }
+        }),
+        (Bool(a), Bool(b)) => Ok(Bool(match op {
+            BinaryOp::BitAnd => a & b,
+            BinaryOp::BitOr => a | b,
+            BinaryOp::BitXor => a ^ b,
+            _ if is_comparison => return Ok(compare(a.cmp(&b))),
+            _ => {
+                return Err(not_const(format!(
+                    "`{}` does not apply to bools",
+                    operator(op)
+                )))
+            }
+        })),
+        (Str(a), Str(b)) if is_comparison => Ok(compare(a.cmp(&b))),
+        // The address of an element: `data + index * 8`
+        (Ptr(mut pointer), Int(offset, _))
+            if op == BinaryOp::Add && offset % 8 == 0 && !pointer.path.is_empty() =>
+        {
+            let last = pointer.path.last_mut().expect("path is not empty");
+            *last = usize::try_from(*last as i64 + offset / 8)
+                .map_err(|_| not_const("an address before an array is taken"))?;
+            Ok(Ptr(pointer))
+        }
+        (left, right) => Err(not_const(format!(
+            "`{}` does not apply to {} and {}",
+            operator(op),
+            left,
+            right
+        ))),
+    }
+}
+
+fn unary(op: UnaryOp, operand: ConstValue) -> Result<ConstValue, ConstErrorKind> {
+    match (op, operand) {
+        (UnaryOp::Neg, ConstValue::Int(value, size)) => {
+            let x = widen(value, size);
+            if !holds(size, -x) {
+                return Err(ConstErrorKind::Overflow {
+                    expression: format!("-({})", x),
+                    ty: size,
+                });
+            }
+            Ok(ConstValue::Int(size.wrap(-x as i64), size))
+        }
+        (UnaryOp::Neg, ConstValue::Float(value, size)) => Ok(ConstValue::Float(-value, size)),
+        (UnaryOp::Not, ConstValue::Int(value, size)) => {
+            Ok(ConstValue::Int(size.wrap(!value), size))
+        }
+        (UnaryOp::Not, ConstValue::Bool(value)) => Ok(ConstValue::Bool(!value)),
+        (op, operand) => Err(not_const(format!(
+            "`{:?}` does not apply to {}",
+            op, operand
+        ))),
+    }
+}
+
+/// `value as ty`: integers wrap to the width, floats truncate towards zero
+fn cast(value: ConstValue, ty: &MirType) -> Result<ConstValue, ConstErrorKind> {
+    Ok(match (value, ty) {
+        (ConstValue::Int(value, _), MirType::Int(to)) => ConstValue::Int(to.wrap(value), *to),
+        (ConstValue::Bool(value), MirType::Int(to)) => ConstValue::Int(value as i64, *to),
+        (ConstValue::Float(value, _), MirType::Int(IntSize::U64)) => {
+            ConstValue::Int(value as u64 as i64, IntSize::U64)
+        }
+        (ConstValue::Float(value, _), MirType::Int(to)) => {
+            ConstValue::Int(to.wrap(value as i64), *to)
+        }
+        (ConstValue::Int(value, from), MirType::Float(to)) => float(widen(value, from) as f64, *to),
+        (ConstValue::Float(value, _), MirType::Float(to)) => float(value, *to),
+        (value, ty) => return Err(not_const(format!("{} cannot be cast to {:?}", value, ty))),
+    })
+}
+
+/// What a constant, array length or const argument is evaluated for
+enum Target {
+    /// The constant at this position in the items
+    Constant(usize),
+    /// The array length found at this position in traversal order
+    Length(usize),
+    /// The const argument found at this position in traversal order
+    Argument(usize),
+}
+
+/// A function computing the value of a constant or array length
+struct Thunk {
+    symbol: String,
+    target: Target,
+    span: Span,
+}
+
+/// Evaluate the `sthira` items of `ast` and the values in its types
+///
+/// `imported` are the `sthira` items, functions and types other modules
+/// make visible here, under the names used for them here. A constant
+/// whose value reduces to numbers, bools or arrays of them is replaced by
+/// that value; one that cannot be evaluated, such as one calling a
+/// function not marked `sthira`, is left to be computed where it is used.
+/// An array length must reduce to a non-negative integer, and an argument
+/// for a const parameter to an integer its declared type can hold. Those
+/// using a generic parameter are left for [`evaluate_lengths`].
+pub fn evaluate_constants(
+    ast: &mut ast::Ast,
+    imported: &[ast::Item],
+) -> Vec<(Span, ConstEvalError)> {
+    evaluate(ast, imported, true)
+}
+
+/// Evaluate the values in the types of a monomorphized program
+///
+/// Substituting const parameters leaves lengths such as `[T; N * 2]` to
+/// compute; constants were evaluated before type checking.
+pub fn evaluate_lengths(ast: &mut ast::Ast) -> Vec<(Span, ConstEvalError)> {
+    evaluate(ast, &[], false)
+}
+
+fn evaluate(
+    ast: &mut ast::Ast,
+    imported: &[ast::Item],
+    constants: bool,
+) -> Vec<(Span, ConstEvalError)> {
+    let mut values = TypeValues::new(imported.iter().chain(&ast.items));
+    let _ = values.visit_ast_mut(ast);
+    let mut errors: Vec<_> = values
+        .dependent
+        .drain(..)
+        .map(|(span, parameter)| {
+            let kind = not_const(format!(
+                "a const argument cannot be computed from the generic parameter `{}`; give `{}` itself",
+                parameter, parameter
+            ));
+            (span, ConstEvalError { kind, backtrace: Vec::new() })
+        })
+        .collect();
+    let constants = constants
+        && ast
+            .items
+            .iter()
+            .any(|item| matches!(item, ast::Item::Constant(_)));
+    if values.lengths.is_empty() && values.arguments.is_empty() && !constants {
+        return errors;
+    }
+
+    // A nullary `sthira` function for each value to compute
+    let mut thunks = Vec::new();
+    let mut items: Vec<ast::Item> = imported.iter().chain(&ast.items).cloned().collect();
+    let thunk = |symbol: &str, ty: Option<ast::Type>, value: &ast::Expr, span: Span| {
+        ast::Item::Function(ast::FunctionDef {
+            name: ast::Identifier {
+                name: symbol.to_string(),
+                affixes: Default::default(),
+                span,
+            },
+            generics: Vec::new(),
+            receiver: None,
+            params: Vec::new(),
+            return_type: Some(ty.unwrap_or(ast::Type::Inferred)),
+            where_clauses: Vec::new(),
+            preconditions: Vec::new(),
+            postconditions: Vec::new(),
+            body: ast::Block {
+                stmts: vec![ast::Stmt::Expr(value.clone())],
+                span,
+            },
+            is_const: true,
+            visibility: Visibility::Private,
+            attributes: Vec::new(),
+            span,
+        })
+    };
+    for (position, item) in ast.items.iter().enumerate() {
+        if !constants {
+            break;
+        }
+        if let ast::Item::Constant(constant) = item {
+            let symbol = format!("{}{}", CONSTANT_THUNK, constant.name.name);
+            items.push(thunk(
+                &symbol,
+                constant.ty.clone(),
+                &constant.value,
+                constant.span,
+            ));
+            thunks.push(Thunk {
+                symbol,
+                target: Target::Constant(position),
+                span: constant.span,
+            });
+        }
+    }
+    for (position, length) in values.lengths.iter().enumerate() {
+        let symbol = format!("{}{}", LENGTH_THUNK, position);
+        items.push(thunk(&symbol, None, length, length.span()));
+        thunks.push(Thunk {
+            symbol,
+            target: Target::Length(position),
+            span: length.span(),
+        });
+    }
+    for (position, (argument, ty, span)) in values.arguments.iter().enumerate() {
+        let symbol = format!("{}{}", ARGUMENT_THUNK, position);
+        items.push(thunk(&symbol, ty.clone(), argument, *span));
+        thunks.push(Thunk {
+            symbol,
+            target: Target::Argument(position),
+            span: *span,
+        });
+    }
+
+    // Lower what may run; a function using something lowering cannot
+    // give a value may not
+    let index = ast::Ast {
+        items,
+        file_path: ast.file_path.clone(),
+    };
+    let mut builder = MirBuilder::new();
+    builder.index_methods(&index);
+    let mut module = MirModule {
+        name: "sthira".to_string(),
+        functions: Vec::new(),
+        globals: Vec::new(),
+        types: Vec::new(),
+    };
+    let mut unresolved = Vec::new();
+    let mut locations = HashMap::new();
+    for item in &index.items {
+        let built = module.functions.len();
+        match item {
+            ast::Item::Function(func) if func.is_const => {
+                builder.build_item(item, &mut module);
+                locations.insert(func.name.name.clone(), func.span);
+            }
+            ast::Item::Impl(impl_def) => {
+                let mut impl_def = impl_def.clone();
+                impl_def.methods.retain(|method| method.is_const);
+                let owner = impl_owner_name(&impl_def.self_ty);
+                for method in &impl_def.methods {
+                    let symbol = method_symbol(&impl_def, &owner, &method.name.name);
+                    locations.insert(symbol, method.span);
+                }
+                builder.build_item(&ast::Item::Impl(impl_def), &mut module);
+            }
+            _ => {}
+        }
+        let missing = builder.take_unresolved();
+        if let Some(first) = missing.first() {
+            for function in &module.functions[built..] {
+                unresolved.push((function.name.clone(), first.clone()));
+            }
+        }
+    }
+    let returns: HashMap<String, MirType> = module
+        .functions
+        .iter()
+        .map(|f| (f.name.clone(), f.return_type.clone()))
+        .collect();
+    let mut evaluator = ConstEvaluator::new(&module);
+    for (symbol, missing) in &unresolved {
+        let reason = if [CONSTANT_THUNK, LENGTH_THUNK, ARGUMENT_THUNK]
+            .iter()
+            .any(|prefix| symbol.starts_with(prefix))
+        {
+            format!("{} cannot be evaluated at compile time", missing)
+        } else {
+            format!(
+                "`{}` uses {}, which cannot be evaluated at compile time",
+                symbol, missing
+            )
+        };
+        evaluator.forbid(symbol, reason);
+    }
+
+    let mut sizes = vec![None; values.lengths.len()];
+    let mut arguments = vec![None; values.arguments.len()];
+    for thunk in &thunks {
+        let result = evaluator.call(&thunk.symbol, Vec::new()).and_then(|value| {
+            let ty = &returns[&thunk.symbol];
+            match thunk.target {
+                Target::Constant(_) => literal(&value, ty, thunk.span)
+                    .map(|expr| (None, expr))
+                    .map_err(|kind| ConstEvalError {
+                        kind,
+                        backtrace: vec![thunk.symbol.clone()],
+                    }),
+                Target::Length(_) => match value {
+                    ConstValue::Int(n, size) if widen(n, size) >= 0 => {
+                        Ok((Some(widen(n, size) as usize), None))
+                    }
+                    other => Err(ConstEvalError {
+                        kind: ConstErrorKind::InvalidLength(other.to_string()),
+                        backtrace: vec![thunk.symbol.clone()],
+                    }),
+                },
+                Target::Argument(_) => argument(&value, ty, thunk.span)
+                    .map(|expr| (None, Some(expr)))
+                    .map_err(|kind| ConstEvalError {
+                        kind,
+                        backtrace: vec![thunk.symbol.clone()],
+                    }),
+            }
+        });
+        match (&thunk.target, result) {
+            (Target::Constant(position), Ok((_, Some(value)))) => {
+                if let ast::Item::Constant(constant) = &mut ast.items[*position] {
+                    constant.value = value;
+                }
+            }
+            (Target::Constant(_), Ok(_)) => {}
+            // Left to run where it is used
+            (Target::Constant(_), Err(error))
+                if matches!(error.kind, ConstErrorKind::NotConst(_)) => {}
+            (Target::Length(position), Ok((size, _))) => sizes[*position] = size,
+            (Target::Argument(position), Ok((_, value))) => arguments[*position] = value,
+            (_, Err(mut error)) => {
+                error.backtrace = error
+                    .backtrace
+                    .iter()
+                    .map(|symbol| describe_frame(symbol, &locations))
+                    .collect();
+                errors.push((thunk.span, error));
+            }
+        }
+    }
+
+    values.evaluated = Some(Evaluated { sizes, arguments });
+    values.next = (0, 0);
+    let _ = values.visit_ast_mut(ast);
+    errors
+}
+
+/// A frame of a backtrace as the reader knows it
+fn describe_frame(symbol: &str, locations: &HashMap<String, Span>) -> String {
+    let at = |span: Span| format!("{}:{}", span.line, span.column);
+    if let Some(name) = symbol.strip_prefix(CONSTANT_THUNK) {
+        let span = locations[symbol];
+        return format!("the value of `{}` at {}", name, at(span));
+    }
+    if symbol.starts_with(LENGTH_THUNK) {
+        return format!("the array length at {}", at(locations[symbol]));
+    }
+    if symbol.starts_with(ARGUMENT_THUNK) {
+        return format!("the const argument at {}", at(locations[symbol]));
+    }
+    match locations.get(symbol) {
+        Some(&span) => format!("`{}` at {}", symbol, at(span)),
+        None => format!("`{}`", symbol),
+    }
+}
+
+/// `value` as an expression of type `ty`, if it can be written as one
+///
+/// An integer must fit the type declared for it.
+fn literal(
+    value: &ConstValue,
+    ty: &MirType,
+    span: Span,
+) -> Result<Option<ast::Expr>, ConstErrorKind> {
+    Ok(match (value, ty) {
+        (ConstValue::Int(value, size), MirType::Int(declared)) => {
+            let n = widen(*value, *size);
+            if !holds(*declared, n) {
+                return Err(ConstErrorKind::OutOfRange {
+                    value: n.to_string(),
+                    ty: *declared,
+                });
+            }
+            // A `u64` beyond `i64` has no literal
+            i64::try_from(n)
+                .ok()
+                .map(|n| ast::Expr::Literal(ast::Literal::Int(n)))
+        }
+        (ConstValue::Float(value, _), MirType::Float(_)) => {
+            Some(ast::Expr::Literal(ast::Literal::Float(*value)))
+        }
+        (ConstValue::Bool(value), MirType::Bool) => {
+            Some(ast::Expr::Literal(ast::Literal::Bool(*value)))
+        }
+        (ConstValue::Array(elements), MirType::Slice(element)) => {
+            let elements = elements
+                .iter()
+                .map(|e| literal(e, element, span))
+                .collect::<Result<Option<Vec<_>>, _>>()?;
+            elements.map(|elements| ast::Expr::Array { elements, span })
+        }
+        _ => None,
+    })
+}
+
+/// `value` as the literal given for a const parameter of type `ty`
+fn argument(value: &ConstValue, ty: &MirType, span: Span) -> Result<ast::Expr, ConstErrorKind> {
+    let expr = match (value, ty) {
+        (ConstValue::Int(..), MirType::Int(_)) => literal(value, ty, span)?,
+        // A parameter whose declaration is not in view
+        (ConstValue::Int(n, size), _) => i64::try_from(widen(*n, *size))
+            .ok()
+            .map(|n| ast::Expr::Literal(ast::Literal::Int(n))),
+        _ => None,
+    };
+    expr.ok_or_else(|| ConstErrorKind::InvalidArgument(value.to_string()))
+}
+
+/// Array lengths written as expressions and arguments given for const
+/// parameters, in traversal order: collected first, then given the values
+/// evaluated for them
+///
+/// One using a generic parameter of the item it is in waits for that
+/// parameter to be substituted.
+struct TypeValues {
+    /// Declared types of the generic parameters of each type, `None` for
+    /// a type parameter
+    parameters: HashMap<String, Vec<Option<ast::Type>>>,
+    /// `sthira` items, which a const argument may name without braces
+    constants: HashSet<String>,
+    /// Generic parameters of the item being visited
+    generics: Vec<String>,
+    lengths: Vec<ast::Expr>,
+    /// Const arguments, with their parameter's type and where they are
+    arguments: Vec<(ast::Expr, Option<ast::Type>, Span)>,
+    /// Const arguments computed from a generic parameter, and the parameter
+    dependent: Vec<(Span, String)>,
+    evaluated: Option<Evaluated>,
+    next: (usize, usize),
+}
+
+/// What evaluation gave each array length and const argument, in
+/// traversal order
+struct Evaluated {
+    sizes: Vec<Option<usize>>,
+    arguments: Vec<Option<ast::Expr>>,
+}
+
+impl TypeValues {
+    fn new<'a>(items: impl Iterator<Item = &'a ast::Item>) -> Self {
+        let mut parameters = HashMap::new();
+        let mut constants = HashSet::new();
+        for item in items {
+            match item {
+                ast::Item::TypeDef(typedef) => {
+                    let types = typedef.generics.iter().map(|g| g.ty.clone()).collect();
+                    parameters.insert(typedef.name.name.clone(), types);
+                }
+                ast::Item::Constant(constant) => {
+                    constants.insert(constant.name.name.clone());
+                }
+                _ => {}
+            }
+        }
+        TypeValues {
+            parameters,
+            constants,
+            generics: Vec::new(),
+            lengths: Vec::new(),
+            arguments: Vec::new(),
+            dependent: Vec::new(),
+            evaluated: None,
+            next: (0, 0),
+        }
+    }
+
+    /// The generic parameter of the item being visited `expr` uses, if any
+    fn generic_in(&self, expr: &ast::Expr) -> Option<String> {
+        struct Uses<'g>(&'g [String]);
+        impl AstVisitor for Uses<'_> {
+            type Break = String;
+
+            fn visit_expr(&mut self, expr: &ast::Expr) -> VisitResult<String> {
+                match expr {
+                    ast::Expr::Identifier(id) if self.0.contains(&id.name) => {
+                        ControlFlow::Break(id.name.clone())
+                    }
+                    _ => walk_expr(self, expr),
+                }
+            }
+        }
+        match Uses(&self.generics).visit_expr(expr) {
+            ControlFlow::Break(name) => Some(name),
+            ControlFlow::Continue(()) => None,
+        }
+    }
+
+    /// Visit the items of `generics` with those parameters in scope
+    fn with_generics<T>(
+        &mut self,
+        generics: &[ast::GenericParam],
+        visit: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        let depth = self.generics.len();
+        self.generics
+            .extend(generics.iter().map(|g| g.name.name.clone()));
+        let result = visit(self);
+        self.generics.truncate(depth);
+        result
+    }
+
+    fn visit_type(&mut self, ty: &mut ast::Type) {
+        match ty {
+            ast::Type::Array {
+                element,
+                size,
+                length,
+            } => {
+                self.visit_type(element);
+                if let (None, Some(length)) = (&size, length) {
+                    if self.generic_in(length).is_some() {
+                        return;
+                    }
+                    match &self.evaluated {
+                        None => self.lengths.push((**length).clone()),
+                        Some(evaluated) => {
+                            *size = evaluated.sizes[self.next.0];
+                            self.next.0 += 1;
+                        }
+                    }
+                }
+            }
+            ast::Type::Named { name, generics, .. } => {
+                let declared = self.parameters.get(&name.name).cloned().unwrap_or_default();
+                for (position, arg) in generics.iter_mut().enumerate() {
+                    let declared = declared.get(position).cloned().flatten();
+                    self.visit_argument(arg, declared, name.span);
+                }
+            }
+            ast::Type::Function {
+                params,
+                return_type,
+            } => {
+                params.iter_mut().for_each(|p| self.visit_type(p));
+                self.visit_type(return_type);
+            }
+            ast::Type::Tuple(elements) => elements.iter_mut().for_each(|e| self.visit_type(e)),
+            ast::Type::Reference { inner, .. } => self.visit_type(inner),
+            ast::Type::Const(_) | ast::Type::Inferred => {}
+        }
+    }
+
+    /// A generic argument, for a const parameter of type `declared` if that
+    /// is known, of the type named at `owner`
+    fn visit_argument(&mut self, arg: &mut ast::Type, declared: Option<ast::Type>, owner: Span) {
+        // A `sthira` item given for a const parameter, as in `Sīmita<0, ŚATA>`
+        if let (Some(_), ast::Type::Named { name, generics, .. }) = (&declared, &*arg) {
+            if generics.is_empty()
+                && self.constants.contains(&name.name)
+                && !self.generics.contains(&name.name)
+            {
+                *arg = ast::Type::Const(Box::new(ast::Expr::Identifier(name.clone())));
+            }
+        }
+        let ast::Type::Const(value) = arg else {
+            return self.visit_type(arg);
+        };
+        // A literal has no span of its own
+        let span = match value.span() {
+            span if span == Span::dummy() => owner,
+            span => span,
+        };
+        if let Some(parameter) = self.generic_in(value) {
+            if self.evaluated.is_none() {
+                self.dependent.push((span, parameter));
+            }
+            return;
+        }
+        match &self.evaluated {
+            None => self.arguments.push(((**value).clone(), declared, span)),
+            Some(evaluated) => {
+                if let Some(literal) = &evaluated.arguments[self.next.1] {
+                    **value = literal.clone();
+                }
+                self.next.1 += 1;
+            }
+        }
+    }
+
+    fn visit_signature(
+        &mut self,
+        params: &mut [ast::Parameter],
+        return_type: &mut Option<ast::Type>,
+    ) {
+        for param in params {
+            self.visit_type(&mut param.ty);
+        }
+        if let Some(ty) = return_type {
+            self.visit_type(ty);
+        }
+    }
+}
+
+impl AstVisitorMut for TypeValues {
+    type Break = ();
+
+    fn visit_function_mut(&mut self, func: &mut ast::FunctionDef) -> VisitResult<()> {
+        self.with_generics(&func.generics, |this| {
+            this.visit_signature(&mut func.params, &mut func.return_type);
+            this.visit_block_mut(&mut func.body)
+        })
+    }
+
+    fn visit_typedef_mut(&mut self, typedef: &mut ast::TypeDef) -> VisitResult<()> {
+        self.with_generics(&typedef.generics, |this| match &mut typedef.body {
+            ast::TypeBody::Struct(fields) => {
+                fields.iter_mut().for_each(|f| this.visit_type(&mut f.ty))
+            }
+            ast::TypeBody::Enum(variants) => variants
+                .iter_mut()
+                .flat_map(|v| v.fields.iter_mut().flatten())
+                .for_each(|f| this.visit_type(&mut f.ty)),
+            ast::TypeBody::Alias(ty) => this.visit_type(ty),
+        });
+        ControlFlow::Continue(())
+    }
+
+    fn visit_impl_mut(&mut self, impl_def: &mut ast::ImplDef) -> VisitResult<()> {
+        self.with_generics(&impl_def.generics, |this| {
+            this.visit_type(&mut impl_def.self_ty);
+            for method in &mut impl_def.methods {
+                this.visit_function_mut(method)?;
+            }
+            ControlFlow::Continue(())
+        })
+    }
+
+    fn visit_constant_mut(&mut self, constant: &mut ast::ConstantDef) -> VisitResult<()> {
+        if let Some(ty) = &mut constant.ty {
+            self.visit_type(ty);
+        }
+        self.visit_expr_mut(&mut constant.value)
+    }
+
+    fn visit_trait_mut(&mut self, trait_def: &mut ast::TraitDef) -> VisitResult<()> {
+        self.with_generics(&trait_def.generics, |this| {
+            for method in &mut trait_def.methods {
+                this.visit_signature(&mut method.params, &mut method.return_type);
+                if let Some(body) = &mut method.default_body {
+                    this.visit_block_mut(body)?;
+                }
+            }
+            ControlFlow::Continue(())
+        })
+    }
+
+    fn visit_stmt_mut(&mut self, stmt: &mut ast::Stmt) -> VisitResult<()> {
+        if let ast::Stmt::Let { ty: Some(ty), .. } = stmt {
+            self.visit_type(ty);
+        }
+        walk_stmt_mut(self, stmt)
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut ast::Expr) -> VisitResult<()> {
+        match expr {
+            ast::Expr::Cast { ty, .. } => self.visit_type(ty),
+            ast::Expr::Lambda { params, .. } => {
+                params.iter_mut().for_each(|p| self.visit_type(&mut p.ty))
+            }
+            _ => {}
+        }
+        walk_expr_mut(self, expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn place(local: usize) -> MirPlace {
+        MirPlace {
+            local,
+            projection: vec![],
+        }
+    }
+
+    fn int(value: i64) -> MirOperand {
+        MirOperand::Constant(MirConstant::Int(value, IntSize::I32))
+    }
+
+    fn function(name: &str, params: usize, blocks: Vec<MirBasicBlock>) -> MirFunction {
+        MirFunction {
+            name: name.to_string(),
+            params: (0..params)
+                .map(|index| MirParam {
+                    index,
+                    ty: MirType::Int(IntSize::I32),
+                    karaka: None,
+                })
+                .collect(),
+            return_type: MirType::Int(IntSize::I32),
+            blocks,
+            locals: (0..4)
+                .map(|index| MirLocal {
+                    index,
+                    ty: MirType::Int(IntSize::I32),
+                    name: None,
+                })
+                .collect(),
+            karaka_hints: HashMap::new(),
+            inline: Default::default(),
+            overflow: Overflow::Wrap,
+            spans: HashMap::new(),
+        }
+    }
+
+    fn module(functions: Vec<MirFunction>) -> MirModule {
+        MirModule {
+            name: "test".to_string(),
+            functions,
+            globals: Vec::new(),
+            types: Vec::new(),
+        }
+    }
+
+    /// `f(n) = n * 2`, computed into `_0`
+    fn double() -> MirFunction {
+        function(
+            "dviguṇa",
+            1,
+            vec![MirBasicBlock {
+                id: 0,
+                instructions: vec![MirInstruction::Assign {
+                    dest: place(0),
+                    value: MirRvalue::BinaryOp {
+                        op: BinaryOp::Mul,
+                        left: MirOperand::Copy(place(0)),
+                        right: int(2),
+                    },
+                }],
+                terminator: MirTerminator::Return,
+            }],
+        )
+    }
+
+    #[test]
+    fn test_call_and_overflow() {
+        let module = module(vec![double()]);
+        let evaluator = ConstEvaluator::new(&module);
+        assert_eq!(
+            evaluator.call("dviguṇa", vec![ConstValue::Int(21, IntSize::I32)]),
+            Ok(ConstValue::Int(42, IntSize::I32))
+        );
+        let error = evaluator
+            .call(
+                "dviguṇa",
+                vec![ConstValue::Int(i32::MAX as i64, IntSize::I32)],
+            )
+            .expect_err("2^32 - 2 is beyond an i32");
+        assert_eq!(
+            error.kind.to_string(),
+            "attempt to compute `2147483647 * 2`, which overflows `i32`"
+        );
+        assert_eq!(error.backtrace, vec!["dviguṇa".to_string()]);
+    }
+
+    #[test]
+    fn test_step_limit() {
+        // A block jumping to itself
+        let module = module(vec![function(
+            "cakra",
+            0,
+            vec![MirBasicBlock {
+                id: 0,
+                instructions: vec![MirInstruction::Nop],
+                terminator: MirTerminator::Goto { target: 0 },
+            }],
+        )]);
+        let error = ConstEvaluator::new(&module)
+            .with_step_limit(100)
+            .call("cakra", Vec::new())
+            .expect_err("it never returns");
+        assert_eq!(error.kind, ConstErrorKind::StepLimit(100));
+    }
+
+    #[test]
+    fn test_structs() {
+        // A struct built, then one field read back
+        let module = module(vec![function(
+            "bindu",
+            0,
+            vec![MirBasicBlock {
+                id: 0,
+                instructions: vec![
+                    MirInstruction::Assign {
+                        dest: place(1),
+                        value: MirRvalue::Aggregate {
+                            kind: AggregateKind::Struct {
+                                name: "Bindu".to_string(),
+                            },
+                            operands: vec![int(3), int(4)],
+                        },
+                    },
+                    MirInstruction::Assign {
+                        dest: place(0),
+                        value: MirRvalue::Field {
+                            base: MirOperand::Copy(place(1)),
+                            index: 1,
+                        },
+                    },
+                ],
+                terminator: MirTerminator::Return,
+            }],
+        )]);
+        assert_eq!(
+            ConstEvaluator::new(&module).call("bindu", Vec::new()),
+            Ok(ConstValue::Int(4, IntSize::I32))
+        );
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let u8 = Some(IntSize::U8);
+        let small = |n| ConstValue::Int(n, IntSize::U8);
+        assert_eq!(
+            binary(BinaryOp::Add, small(200), small(55), u8),
+            Ok(small(255))
+        );
+        assert!(matches!(
+            binary(BinaryOp::Add, small(200), small(56), u8),
+            Err(ConstErrorKind::Overflow { .. })
+        ));
+        assert!(matches!(
+            binary(BinaryOp::Sub, small(0), small(1), u8),
+            Err(ConstErrorKind::Overflow { .. })
+        ));
+        assert_eq!(
+            binary(BinaryOp::Rem, small(7), small(0), u8),
+            Err(ConstErrorKind::DivisionByZero {
+                expression: "7 % 0".to_string()
+            })
+        );
+        let min = ConstValue::Int(i64::MIN, IntSize::I64);
+        let minus_one = ConstValue::Int(-1, IntSize::I64);
+        assert!(binary(BinaryOp::Div, min.clone(), minus_one.clone(), None).is_err());
+        assert!(binary(BinaryOp::Rem, min, minus_one, None).is_err());
+        assert!(unary(UnaryOp::Neg, ConstValue::Int(-128, IntSize::I8)).is_err());
+        assert_eq!(
+            cast(
+                ConstValue::Int(-1, IntSize::I32),
+                &MirType::Int(IntSize::U8)
+            ),
+            Ok(small(255))
+        );
+    }
+}
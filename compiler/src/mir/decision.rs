@@ -0,0 +1,740 @@
+//! Decision Trees (निर्णय वृक्ष) - Compiling Pratyabhijñā
+//!
+//! A match is compiled to a tree of tests so that no part of the scrutinee is
+//! examined twice on the way to an arm. Each row of the match is a list of
+//! pending tests, one per sub-value the arm's pattern constrains, plus the
+//! names it binds. The tree branches on a test of the first row:
+//! - enum variants, and integer, character and boolean literals, become one
+//!   multiway switch over every value the rows test at that place
+//! - ranges, slice lengths, strings and floats become a two-way test
+//! - tuples and structs test nothing; their fields become new tests
+//!
+//! A row with no tests left reaches its arm. When the arm has a `yad` guard,
+//! the tree continues with the remaining rows should the guard fail.
+
+use crate::parser::ast;
+use std::collections::HashMap;
+
+/// Where a matched sub-value lives, starting from the scrutinee
+#[derive(Debug, Clone, PartialEq)]
+pub enum Access {
+    Scrutinee,
+    /// Field of a tuple or struct, or of the payload of an undeclared
+    /// variant (after the discriminant)
+    Field(Box<Access>, usize),
+    /// Field of the payload of a variant, by variant index
+    Payload(Box<Access>, usize, usize),
+    /// Element of an array or slice
+    Index(Box<Access>, usize),
+    /// Element counted from the end of a slice (`1` is the last)
+    IndexFromEnd(Box<Access>, usize),
+}
+
+/// Two-way test on a sub-value
+#[derive(Debug, Clone)]
+pub enum Test {
+    /// Within `start..end` or `start..=end`; bounds are literal or constant patterns
+    Range {
+        start: Option<Box<ast::Pattern>>,
+        end: Option<Box<ast::Pattern>>,
+        inclusive: bool,
+    },
+    /// Slice length equal to, or at least, `len`
+    Len { len: usize, exact: bool },
+    /// Equal to a string or float literal
+    Equal(ast::Literal),
+}
+
+/// Compiled match
+#[derive(Debug, Clone)]
+pub enum Decision {
+    /// No arm matches; exhaustiveness checking rules this out
+    Fail,
+    /// Arm `arm` matches once `bindings` are made and its guard, if any, holds
+    Leaf {
+        arm: usize,
+        bindings: Vec<(String, Access)>,
+        guard: Option<Box<ast::Expr>>,
+        /// Where to continue when the guard fails
+        otherwise: Option<Box<Decision>>,
+    },
+    /// Branch on the discriminant (for variants) or the value of a sub-value
+    Switch {
+        access: Access,
+        discriminant: bool,
+        cases: Vec<(i64, Decision)>,
+        default: Box<Decision>,
+    },
+    /// Branch on a two-way test
+    If {
+        access: Access,
+        test: Test,
+        then: Box<Decision>,
+        otherwise: Box<Decision>,
+    },
+}
+
+/// Declared field and variant order, which patterns name but MIR indexes
+#[derive(Debug, Default, Clone)]
+pub struct TypeShapes {
+    /// Enum name → variants and their payload field names
+    pub enums: HashMap<String, Vec<(String, Vec<String>)>>,
+    /// Struct name → field names
+    pub structs: HashMap<String, Vec<String>>,
+}
+
+impl TypeShapes {
+    /// Shapes of the prelude's types, which every program sees
+    pub fn prelude() -> Self {
+        let mut shapes = Self::default();
+        for typedef in crate::semantics::prelude::types() {
+            shapes.add(&typedef);
+        }
+        shapes
+    }
+
+    /// Record the shape of a declared struct or enum
+    pub fn add(&mut self, typedef: &ast::TypeDef) {
+        let names = |fields: &[ast::Field]| fields.iter().map(|f| f.name.name.clone()).collect();
+        match &typedef.body {
+            ast::TypeBody::Struct(fields) => {
+                self.structs
+                    .insert(typedef.name.name.clone(), names(fields));
+            }
+            ast::TypeBody::Enum(variants) => {
+                let variants = variants
+                    .iter()
+                    .map(|v| {
+                        (
+                            v.name.name.clone(),
+                            v.fields.as_deref().map_or(Vec::new(), names),
+                        )
+                    })
+                    .collect();
+                self.enums.insert(typedef.name.name.clone(), variants);
+            }
+            ast::TypeBody::Alias(_) => {}
+        }
+    }
+
+    /// Enum, discriminant and payload field names of a variant
+    ///
+    /// An unqualified name resolves to the enum the match names elsewhere,
+    /// else to the first enum (by name) declaring it.
+    fn variant(
+        &self,
+        enum_name: Option<&str>,
+        variant: &str,
+        preferred: &[String],
+    ) -> Option<(&str, usize, &[String])> {
+        let find = |owner: &str| {
+            let (name, variants) = self.enums.get_key_value(owner)?;
+            variants
+                .iter()
+                .position(|(v, _)| v == variant)
+                .map(|index| (name.as_str(), index, variants[index].1.as_slice()))
+        };
+        if let Some(owner) = enum_name {
+            return find(owner);
+        }
+        if let Some(found) = preferred.iter().find_map(|owner| find(owner)) {
+            return Some(found);
+        }
+        let mut owners: Vec<&String> = self.enums.keys().collect();
+        owners.sort();
+        owners.into_iter().find_map(|owner| find(owner))
+    }
+
+    /// Enum and discriminant of the variant a path `Enum::Variant` names
+    pub fn constructor(&self, path: &str) -> Option<(&str, usize)> {
+        let (Some(owner), variant) = split_path(path) else {
+            return None;
+        };
+        self.variant(Some(owner), variant, &[])
+            .map(|(name, index, _)| (name, index))
+    }
+
+    /// Does an unqualified name refer to a unit variant rather than bind?
+    fn is_unit_variant(&self, name: &str) -> bool {
+        let (owner, variant) = split_path(name);
+        self.enums
+            .iter()
+            .filter(|(enum_name, _)| owner.is_none_or(|o| o == enum_name.as_str()))
+            .any(|(_, variants)| {
+                variants
+                    .iter()
+                    .any(|(v, fields)| v == variant && fields.is_empty())
+            })
+    }
+}
+
+/// One arm's pending tests
+#[derive(Debug, Clone)]
+struct Row {
+    tests: Vec<(Access, ast::Pattern)>,
+    bindings: Vec<(String, Access)>,
+    guard: Option<ast::Expr>,
+    arm: usize,
+}
+
+/// How the tree branches on the first test of a row
+enum Branch {
+    Switch {
+        discriminant: bool,
+        value: i64,
+    },
+    Test(Test),
+    /// Tuples, structs and rest-only slices: only fields to test
+    Expand,
+}
+
+/// Decision tree compiler for one match
+pub struct DecisionCompiler<'a> {
+    shapes: &'a TypeShapes,
+    /// Enums the match names explicitly, for unqualified variants
+    preferred: Vec<String>,
+}
+
+impl<'a> DecisionCompiler<'a> {
+    pub fn new(shapes: &'a TypeShapes) -> Self {
+        Self {
+            shapes,
+            preferred: Vec::new(),
+        }
+    }
+
+    /// Compile the arms of a match on one scrutinee
+    pub fn compile(mut self, arms: &[ast::MatchArm]) -> Decision {
+        for arm in arms {
+            collect_enum_names(&arm.pattern, &mut self.preferred);
+        }
+        let rows = arms
+            .iter()
+            .enumerate()
+            .map(|(arm, a)| Row {
+                tests: vec![(Access::Scrutinee, a.pattern.clone())],
+                bindings: Vec::new(),
+                guard: a.guard.clone(),
+                arm,
+            })
+            .collect();
+        self.decide(rows)
+    }
+
+    fn decide(&self, rows: Vec<Row>) -> Decision {
+        let rows: Vec<Row> = rows
+            .into_iter()
+            .flat_map(|row| self.simplify(row))
+            .collect();
+        let Some(first) = rows.first() else {
+            return Decision::Fail;
+        };
+        let Some((access, pattern)) = first.tests.first().cloned() else {
+            let otherwise = first
+                .guard
+                .as_ref()
+                .map(|_| Box::new(self.decide(rows[1..].to_vec())));
+            return Decision::Leaf {
+                arm: first.arm,
+                bindings: first.bindings.clone(),
+                guard: first.guard.clone().map(Box::new),
+                otherwise,
+            };
+        };
+
+        match self.branch(&pattern) {
+            Branch::Expand => {
+                // A tuple written with `..` takes its arity from the other rows
+                let arity = rows
+                    .iter()
+                    .flat_map(|row| &row.tests)
+                    .filter_map(|(a, p)| match p {
+                        ast::Pattern::Tuple(elements) if *a == access => tuple_arity(elements),
+                        _ => None,
+                    })
+                    .max();
+                let rows = rows
+                    .into_iter()
+                    .map(|mut row| {
+                        if let Some(at) = row.tests.iter().position(|(a, _)| *a == access) {
+                            if matches!(self.branch(&row.tests[at].1), Branch::Expand) {
+                                let (access, pattern) = row.tests.remove(at);
+                                row.tests.extend(self.fields(&access, &pattern, arity));
+                            }
+                        }
+                        row
+                    })
+                    .collect();
+                self.decide(rows)
+            }
+            Branch::Switch { discriminant, .. } => {
+                let mut values: Vec<i64> = Vec::new();
+                for row in &rows {
+                    for (a, p) in &row.tests {
+                        if *a == access {
+                            if let Branch::Switch { value, .. } = self.branch(p) {
+                                if !values.contains(&value) {
+                                    values.push(value);
+                                }
+                            }
+                        }
+                    }
+                }
+                let cases = values
+                    .iter()
+                    .map(|&value| (value, self.decide(self.select(&rows, &access, value))))
+                    .collect();
+                let complete = self.covers_all(&pattern, discriminant, &values);
+                let default = if complete {
+                    Decision::Fail
+                } else {
+                    let rows = rows
+                        .into_iter()
+                        .filter(|row| {
+                            !row.tests.iter().any(|(a, p)| {
+                                *a == access && matches!(self.branch(p), Branch::Switch { .. })
+                            })
+                        })
+                        .collect();
+                    self.decide(rows)
+                };
+                Decision::Switch {
+                    access,
+                    discriminant,
+                    cases,
+                    default: Box::new(default),
+                }
+            }
+            Branch::Test(test) => {
+                let mut then = Vec::new();
+                let mut otherwise = Vec::new();
+                for row in rows {
+                    let same = row.tests.iter().position(|(a, p)| {
+                        *a == access
+                            && matches!(self.branch(p), Branch::Test(t) if same_test(&t, &test))
+                    });
+                    match same {
+                        Some(at) => {
+                            let mut row = row;
+                            let (access, pattern) = row.tests.remove(at);
+                            row.tests.extend(self.fields(&access, &pattern, None));
+                            then.push(row);
+                        }
+                        // Other tests on this value are tried again further down
+                        None => {
+                            then.push(row.clone());
+                            otherwise.push(row);
+                        }
+                    }
+                }
+                Decision::If {
+                    access,
+                    test,
+                    then: Box::new(self.decide(then)),
+                    otherwise: Box::new(self.decide(otherwise)),
+                }
+            }
+        }
+    }
+
+    /// Rows that can match when the sub-value at `access` switches to `value`
+    fn select(&self, rows: &[Row], access: &Access, value: i64) -> Vec<Row> {
+        let mut selected = Vec::new();
+        for row in rows {
+            let mut row = row.clone();
+            let Some(at) = row.tests.iter().position(|(a, _)| a == access) else {
+                selected.push(row);
+                continue;
+            };
+            match self.branch(&row.tests[at].1) {
+                Branch::Switch { value: v, .. } if v == value => {
+                    let (access, pattern) = row.tests.remove(at);
+                    row.tests.extend(self.fields(&access, &pattern, None));
+                }
+                Branch::Switch { .. } => continue,
+                Branch::Test(test) => match range_contains(&test, value) {
+                    Some(true) => {
+                        row.tests.remove(at);
+                    }
+                    Some(false) => continue,
+                    None => {}
+                },
+                Branch::Expand => {}
+            }
+            selected.push(row);
+        }
+        selected
+    }
+
+    /// Do the switch values name every variant, or both booleans?
+    fn covers_all(&self, pattern: &ast::Pattern, discriminant: bool, values: &[i64]) -> bool {
+        if discriminant {
+            let (enum_name, variant) = variant_path(pattern);
+            self.shapes
+                .variant(enum_name.as_deref(), &variant, &self.preferred)
+                .and_then(|(owner, _, _)| self.shapes.enums.get(owner))
+                .is_some_and(|variants| (0..variants.len() as i64).all(|v| values.contains(&v)))
+        } else {
+            matches!(pattern, ast::Pattern::Literal(ast::Literal::Bool(_)))
+                && values.contains(&0)
+                && values.contains(&1)
+        }
+    }
+
+    /// Split or-patterns into rows, and turn bindings and wildcards into bindings
+    fn simplify(&self, mut row: Row) -> Vec<Row> {
+        let mut i = 0;
+        while i < row.tests.len() {
+            let (access, pattern) = row.tests[i].clone();
+            match pattern {
+                ast::Pattern::Wildcard
+                | ast::Pattern::Rest
+                | ast::Pattern::Literal(ast::Literal::Unit) => {
+                    row.tests.remove(i);
+                }
+                ast::Pattern::Identifier(name)
+                | ast::Pattern::Binding {
+                    name,
+                    subpattern: None,
+                    ..
+                } => {
+                    if self.shapes.is_unit_variant(&name.name) {
+                        i += 1;
+                    } else {
+                        row.tests.remove(i);
+                        row.bindings.push((name.name, access));
+                    }
+                }
+                ast::Pattern::Binding {
+                    name,
+                    subpattern: Some(sub),
+                    ..
+                } => {
+                    row.bindings.push((name.name, access.clone()));
+                    row.tests[i] = (access, *sub);
+                }
+                // References are transparent at this level
+                ast::Pattern::Ref { pattern, .. } => row.tests[i] = (access, *pattern),
+                ast::Pattern::Guard { pattern, condition } => {
+                    row.guard = Some(match row.guard.take() {
+                        Some(guard) => ast::Expr::Binary {
+                            span: guard.span(),
+                            left: condition,
+                            op: ast::BinaryOp::And,
+                            right: Box::new(guard),
+                        },
+                        None => *condition,
+                    });
+                    row.tests[i] = (access, *pattern);
+                }
+                ast::Pattern::Or(alternatives) => {
+                    return alternatives
+                        .into_iter()
+                        .flat_map(|alternative| {
+                            let mut split = row.clone();
+                            split.tests[i] = (access.clone(), alternative);
+                            self.simplify(split)
+                        })
+                        .collect();
+                }
+                _ => i += 1,
+            }
+        }
+        vec![row]
+    }
+
+    fn branch(&self, pattern: &ast::Pattern) -> Branch {
+        match pattern {
+            ast::Pattern::Literal(literal) => match literal {
+                ast::Literal::Int(n) => Branch::Switch {
+                    discriminant: false,
+                    value: *n,
+                },
+                ast::Literal::Char(c) => Branch::Switch {
+                    discriminant: false,
+                    value: *c as i64,
+                },
+                ast::Literal::Bool(b) => Branch::Switch {
+                    discriminant: false,
+                    value: *b as i64,
+                },
+                ast::Literal::Unit => Branch::Expand,
+                ast::Literal::String(_) | ast::Literal::Float(_) => {
+                    Branch::Test(Test::Equal(literal.clone()))
+                }
+            },
+            ast::Pattern::Range {
+                start,
+                end,
+                inclusive,
+            } => Branch::Test(Test::Range {
+                start: start.clone(),
+                end: end.clone(),
+                inclusive: *inclusive,
+            }),
+            ast::Pattern::Variant { .. }
+            | ast::Pattern::Constructor { .. }
+            | ast::Pattern::Identifier(_)
+            | ast::Pattern::Binding { .. } => {
+                let (enum_name, variant) = variant_path(pattern);
+                let index = self
+                    .shapes
+                    .variant(enum_name.as_deref(), &variant, &self.preferred)
+                    .map_or(-1, |(_, index, _)| index as i64);
+                Branch::Switch {
+                    discriminant: true,
+                    value: index,
+                }
+            }
+            ast::Pattern::Array(elements) => Branch::Test(Test::Len {
+                len: elements.len(),
+                exact: true,
+            }),
+            ast::Pattern::Slice { before, after, .. } if before.len() + after.len() > 0 => {
+                Branch::Test(Test::Len {
+                    len: before.len() + after.len(),
+                    exact: false,
+                })
+            }
+            _ => Branch::Expand,
+        }
+    }
+
+    /// Tests on the fields of a sub-value once its own test has passed
+    fn fields(
+        &self,
+        access: &Access,
+        pattern: &ast::Pattern,
+        arity: Option<usize>,
+    ) -> Vec<(Access, ast::Pattern)> {
+        let field = |i: usize| Access::Field(Box::new(access.clone()), i);
+        match pattern {
+            ast::Pattern::Tuple(elements) => {
+                positional(elements, &field, arity.unwrap_or(elements.len()))
+            }
+            ast::Pattern::Struct { name, fields, .. } => {
+                let order = self.shapes.structs.get(&name.name);
+                fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (f, p))| {
+                        // Undeclared structs fall back to the order written
+                        let index = order
+                            .and_then(|order| order.iter().position(|n| *n == f.name))
+                            .unwrap_or(i);
+                        (field(index), p.clone())
+                    })
+                    .collect()
+            }
+            ast::Pattern::Variant {
+                enum_name,
+                variant,
+                fields,
+            } => {
+                let shape = self.shapes.variant(
+                    enum_name.as_ref().map(|n| n.name.as_str()),
+                    &variant.name,
+                    &self.preferred,
+                );
+                let payload = |i: usize| match shape {
+                    Some((_, variant, _)) => Access::Payload(Box::new(access.clone()), variant, i),
+                    None => field(i + 1),
+                };
+                match fields {
+                    ast::VariantFields::Unit => Vec::new(),
+                    ast::VariantFields::Tuple(elements) => {
+                        let arity = shape.map_or(elements.len(), |(_, _, names)| names.len());
+                        positional(elements, &payload, arity)
+                    }
+                    ast::VariantFields::Struct(named) => named
+                        .iter()
+                        .enumerate()
+                        .map(|(i, (f, p))| {
+                            let index = shape
+                                .and_then(|(_, _, names)| names.iter().position(|n| *n == f.name))
+                                .unwrap_or(i);
+                            (payload(index), p.clone())
+                        })
+                        .collect(),
+                }
+            }
+            ast::Pattern::Constructor { name, fields } => {
+                let (enum_name, variant) = split_path(&name.name);
+                let shape = self.shapes.variant(enum_name, variant, &self.preferred);
+                let arity = shape.map_or(fields.len(), |(_, _, names)| names.len());
+                let payload = |i: usize| match shape {
+                    Some((_, variant, _)) => Access::Payload(Box::new(access.clone()), variant, i),
+                    None => field(i + 1),
+                };
+                positional(fields, &payload, arity)
+            }
+            ast::Pattern::Array(elements) => elements
+                .iter()
+                .enumerate()
+                .map(|(i, p)| (Access::Index(Box::new(access.clone()), i), p.clone()))
+                .collect(),
+            ast::Pattern::Slice {
+                before,
+                middle,
+                after,
+            } => {
+                let mut tests: Vec<(Access, ast::Pattern)> = before
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| (Access::Index(Box::new(access.clone()), i), p.clone()))
+                    .collect();
+                tests.extend(after.iter().enumerate().map(|(i, p)| {
+                    let from_end = after.len() - i;
+                    (
+                        Access::IndexFromEnd(Box::new(access.clone()), from_end),
+                        p.clone(),
+                    )
+                }));
+                // MIR has no subslices: the rest binds the whole slice
+                if let Some(middle) = middle {
+                    tests.push((access.clone(), (**middle).clone()));
+                }
+                tests
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Positional sub-patterns, with a `..` skipping the middle fields
+fn positional(
+    elements: &[ast::Pattern],
+    field: &dyn Fn(usize) -> Access,
+    arity: usize,
+) -> Vec<(Access, ast::Pattern)> {
+    let rest = elements
+        .iter()
+        .position(|p| matches!(p, ast::Pattern::Rest));
+    elements
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| !matches!(p, ast::Pattern::Rest))
+        .map(|(i, p)| {
+            let index = match rest {
+                Some(rest) if i > rest => (arity + i).saturating_sub(elements.len()),
+                _ => i,
+            };
+            (field(index), p.clone())
+        })
+        .collect()
+}
+
+/// Number of fields of a tuple pattern, unless a `..` hides some
+fn tuple_arity(elements: &[ast::Pattern]) -> Option<usize> {
+    let rest = elements.iter().any(|p| matches!(p, ast::Pattern::Rest));
+    (!rest).then_some(elements.len())
+}
+
+/// Enum and variant a variant-like pattern names
+fn variant_path(pattern: &ast::Pattern) -> (Option<String>, String) {
+    let name = match pattern {
+        ast::Pattern::Variant {
+            enum_name: Some(owner),
+            variant,
+            ..
+        } => return (Some(owner.name.clone()), variant.name.clone()),
+        ast::Pattern::Variant { variant, .. } => &variant.name,
+        ast::Pattern::Constructor { name, .. }
+        | ast::Pattern::Identifier(name)
+        | ast::Pattern::Binding { name, .. } => &name.name,
+        _ => return (None, String::new()),
+    };
+    let (owner, variant) = split_path(name);
+    (owner.map(str::to_string), variant.to_string())
+}
+
+fn split_path(name: &str) -> (Option<&str>, &str) {
+    match name.rsplit_once("::") {
+        Some((owner, last)) => (Some(owner), last),
+        None => (None, name),
+    }
+}
+
+/// Enums a pattern names explicitly (`Rūpa::Vṛtta`)
+fn collect_enum_names(pattern: &ast::Pattern, names: &mut Vec<String>) {
+    let mut push = |name: &str| {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    };
+    match pattern {
+        ast::Pattern::Variant {
+            enum_name: Some(owner),
+            ..
+        } => push(&owner.name),
+        ast::Pattern::Constructor { name, .. } | ast::Pattern::Identifier(name) => {
+            if let (Some(owner), _) = split_path(&name.name) {
+                push(owner);
+            }
+        }
+        _ => {}
+    }
+    match pattern {
+        ast::Pattern::Or(alternatives) => {
+            for p in alternatives {
+                collect_enum_names(p, names);
+            }
+        }
+        ast::Pattern::Binding {
+            subpattern: Some(p),
+            ..
+        }
+        | ast::Pattern::Ref { pattern: p, .. }
+        | ast::Pattern::Guard { pattern: p, .. } => collect_enum_names(p, names),
+        _ => {}
+    }
+}
+
+fn same_test(a: &Test, b: &Test) -> bool {
+    match (a, b) {
+        (
+            Test::Range {
+                start: s1,
+                end: e1,
+                inclusive: i1,
+            },
+            Test::Range {
+                start: s2,
+                end: e2,
+                inclusive: i2,
+            },
+        ) => {
+            let show = |p: &Option<Box<ast::Pattern>>| {
+                p.as_deref().map(crate::semantics::exhaustiveness::describe)
+            };
+            i1 == i2 && show(s1) == show(s2) && show(e1) == show(e2)
+        }
+        (Test::Len { len: l1, exact: x1 }, Test::Len { len: l2, exact: x2 }) => {
+            l1 == l2 && x1 == x2
+        }
+        (Test::Equal(a), Test::Equal(b)) => format!("{:?}", a) == format!("{:?}", b),
+        _ => false,
+    }
+}
+
+/// Whether a range with literal bounds contains `value` (`None` for constants)
+fn range_contains(test: &Test, value: i64) -> Option<bool> {
+    let Test::Range {
+        start,
+        end,
+        inclusive,
+    } = test
+    else {
+        return None;
+    };
+    let bound = |p: &Option<Box<ast::Pattern>>| match p.as_deref() {
+        None => Some(None),
+        Some(ast::Pattern::Literal(ast::Literal::Int(n))) => Some(Some(*n)),
+        Some(ast::Pattern::Literal(ast::Literal::Char(c))) => Some(Some(*c as i64)),
+        Some(_) => None,
+    };
+    let (lo, hi) = (bound(start)?, bound(end)?);
+    let above = lo.is_none_or(|lo| value >= lo);
+    let below = hi.is_none_or(|hi| if *inclusive { value <= hi } else { value < hi });
+    Some(above && below)
+}
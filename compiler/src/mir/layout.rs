@@ -0,0 +1,205 @@
+//! Layout (vinyāsa) of Enum Values
+//!
+//! An enum value is a tagged union: a tag naming the variant it holds,
+//! then that variant's payload, with every variant's payload sharing the
+//! same bytes. The tag is the smallest unsigned integer that can count
+//! the variants and sits at offset 0; payload fields follow it, each on
+//! an 8-byte boundary, as every other value is one 8-byte word.
+//!
+//! An enum of one unit variant and one variant holding just a reference
+//! has no tag at all. A reference is never null, so the null word is left
+//! over to stand for the unit variant - `Vikalpa<&T>` is as small as `&T`.
+//!
+//! Values move between locals, registers and memory in whole words, so a
+//! size is always a multiple of 8.
+
+use super::types::MirType;
+
+/// Bytes in a word
+const WORD: usize = 8;
+
+/// How an enum value says which variant it holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagEncoding {
+    /// An unsigned integer of `width` bytes at offset 0, equal to the
+    /// variant's index
+    Direct { width: usize },
+    /// No tag: variant `dataful` holds a reference, and a null word in its
+    /// place means variant `empty`
+    Niche { dataful: usize, empty: usize },
+}
+
+/// Size, alignment and field offsets of an enum's values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumLayout {
+    /// Bytes a value occupies
+    pub size: usize,
+    /// Alignment in bytes
+    pub align: usize,
+    pub tag: TagEncoding,
+    /// Byte offset and type of each payload field, per variant
+    pub variants: Vec<Vec<(usize, MirType)>>,
+}
+
+impl EnumLayout {
+    /// Lay out an enum whose variants hold fields of these types
+    pub fn compute(variants: &[Vec<MirType>]) -> Self {
+        if let Some((dataful, empty)) = niche(variants) {
+            return Self {
+                size: WORD,
+                align: WORD,
+                tag: TagEncoding::Niche { dataful, empty },
+                variants: variants
+                    .iter()
+                    .map(|fields| fields.iter().map(|ty| (0, ty.clone())).collect())
+                    .collect(),
+            };
+        }
+
+        let width: usize = match variants.len() {
+            0..=0x100 => 1,
+            0x101..=0x1_0000 => 2,
+            _ => 4,
+        };
+        let mut size = width;
+        let mut align = width;
+        let variants: Vec<Vec<(usize, MirType)>> = variants
+            .iter()
+            .map(|fields| {
+                let mut offset = width.next_multiple_of(WORD);
+                let fields: Vec<(usize, MirType)> = fields
+                    .iter()
+                    .map(|ty| {
+                        let field = (offset, ty.clone());
+                        offset += size_of(ty);
+                        field
+                    })
+                    .collect();
+                if !fields.is_empty() {
+                    size = size.max(offset);
+                    align = WORD;
+                }
+                fields
+            })
+            .collect();
+
+        Self {
+            size: size.next_multiple_of(WORD),
+            align,
+            tag: TagEncoding::Direct { width },
+            variants,
+        }
+    }
+
+    /// Words a value occupies
+    pub fn words(&self) -> usize {
+        self.size / WORD
+    }
+
+    /// Offset and type of field `index` of variant `variant`
+    pub fn field(&self, variant: usize, index: usize) -> Option<&(usize, MirType)> {
+        self.variants.get(variant)?.get(index)
+    }
+}
+
+/// Bytes a value of type `ty` occupies as a payload field
+pub fn size_of(ty: &MirType) -> usize {
+    match ty {
+        MirType::Enum { layout, .. } => layout.size,
+        _ => WORD,
+    }
+}
+
+/// Dataful and empty variant of an enum whose tag can live in a null
+/// reference: one variant holding only a reference, the other nothing
+fn niche(variants: &[Vec<MirType>]) -> Option<(usize, usize)> {
+    let [first, second] = variants else {
+        return None;
+    };
+    let holds_reference = |fields: &[MirType]| matches!(fields, [MirType::Ref { .. }]);
+    match (first.as_slice(), second.as_slice()) {
+        ([], fields) if holds_reference(fields) => Some((1, 0)),
+        (fields, []) if holds_reference(fields) => Some((0, 1)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mir::types::IntSize;
+
+    fn word() -> MirType {
+        MirType::Int(IntSize::I64)
+    }
+
+    fn reference() -> MirType {
+        MirType::Ref {
+            mutable: false,
+            ty: Box::new(word()),
+        }
+    }
+
+    #[test]
+    fn test_direct_tag() {
+        // Vikalpa<i64>: a byte of tag, the payload in the next word
+        let layout = EnumLayout::compute(&[vec![], vec![word()]]);
+        assert_eq!(layout.tag, TagEncoding::Direct { width: 1 });
+        assert_eq!((layout.size, layout.align), (16, 8));
+        assert_eq!(layout.field(1, 0), Some(&(8, word())));
+        assert_eq!(layout.field(0, 0), None);
+    }
+
+    #[test]
+    fn test_fieldless() {
+        let layout = EnumLayout::compute(&[vec![], vec![], vec![]]);
+        assert_eq!(layout.tag, TagEncoding::Direct { width: 1 });
+        assert_eq!((layout.size, layout.align, layout.words()), (8, 1, 1));
+    }
+
+    #[test]
+    fn test_wide_tag() {
+        let layout = EnumLayout::compute(&vec![vec![]; 300]);
+        assert_eq!(layout.tag, TagEncoding::Direct { width: 2 });
+    }
+
+    #[test]
+    fn test_payloads_share_bytes() {
+        // The largest variant decides the size
+        let layout = EnumLayout::compute(&[vec![word(), word()], vec![word()]]);
+        assert_eq!(layout.size, 24);
+        assert_eq!(layout.field(0, 1), Some(&(16, word())));
+        assert_eq!(layout.field(1, 0), Some(&(8, word())));
+    }
+
+    #[test]
+    fn test_reference_niche() {
+        // Vikalpa<&T>: no tag, null is Śūnya
+        let layout = EnumLayout::compute(&[vec![], vec![reference()]]);
+        assert_eq!(
+            layout.tag,
+            TagEncoding::Niche {
+                dataful: 1,
+                empty: 0
+            }
+        );
+        assert_eq!(layout.size, 8);
+        assert_eq!(layout.field(1, 0), Some(&(0, reference())));
+
+        // Phala<&T, E> has a payload either way, so it keeps its tag
+        let layout = EnumLayout::compute(&[vec![reference()], vec![word()]]);
+        assert_eq!(layout.tag, TagEncoding::Direct { width: 1 });
+    }
+
+    #[test]
+    fn test_nested() {
+        let inner = EnumLayout::compute(&[vec![], vec![word()]]);
+        let inner = MirType::Enum {
+            name: "Vikalpa".to_string(),
+            layout: Box::new(inner),
+        };
+        let layout = EnumLayout::compute(&[vec![], vec![inner.clone()]]);
+        assert_eq!(layout.size, 24);
+        assert_eq!(layout.field(1, 0), Some(&(8, inner)));
+    }
+}
@@ -5,13 +5,16 @@
 //! are applied at this level.
 
 pub mod builder;
+pub mod const_eval;
+pub mod decision;
+pub mod layout;
 pub mod nll;
 pub mod optimizer;
 pub mod passes;
 pub mod types;
 
 // Re-exports
-pub use builder::MirBuilder;
+pub use builder::{LoweringError, MirBuilder};
 pub use nll::{compute_liveness, LivenessInfo, NllChecker};
 pub use optimizer::MirOptimizer;
 pub use types::{MirBasicBlock, MirFunction, MirInstruction, MirType};
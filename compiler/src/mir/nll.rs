@@ -18,6 +18,7 @@
 //! 5. Check for violations
 
 use super::types::*;
+use crate::lexer::Span;
 use std::collections::{HashMap, HashSet};
 
 // ============================================================================
@@ -65,6 +66,33 @@ impl LocationPoint {
     }
 }
 
+/// A point an error refers to, with the span of the statement lowered to
+/// it when the MIR builder recorded one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorSite {
+    pub point: LocationPoint,
+    pub span: Option<Span>,
+}
+
+impl ErrorSite {
+    /// The site of `point` in `func`
+    pub fn at(func: &MirFunction, point: LocationPoint) -> Self {
+        Self {
+            point,
+            span: func.spans.get(&(point.block, point.statement)).copied(),
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorSite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "line {}:{}", span.line, span.column),
+            None => write!(f, "bb{}", self.point.block),
+        }
+    }
+}
+
 /// Constraint on regions
 #[derive(Debug, Clone)]
 pub enum RegionConstraint {
@@ -410,31 +438,114 @@ pub struct NllChecker {
 pub enum NllError {
     /// Use after move
     UseAfterMove {
-        moved_at: LocationPoint,
-        used_at: LocationPoint,
-        local: usize,
+        moved_at: ErrorSite,
+        used_at: ErrorSite,
+        local: String,
     },
     /// Conflicting borrows
     ConflictingBorrow {
-        first_borrow: LocationPoint,
-        second_borrow: LocationPoint,
-        local: usize,
+        first_borrow: ErrorSite,
+        second_borrow: ErrorSite,
+        local: String,
         first_mutable: bool,
         second_mutable: bool,
     },
     /// Borrow used after value modified
     InvalidatedBorrow {
-        borrow_point: LocationPoint,
-        invalidation_point: LocationPoint,
-        use_point: LocationPoint,
+        borrow_point: ErrorSite,
+        invalidation_point: ErrorSite,
+        use_point: ErrorSite,
     },
     /// Moved value still borrowed
     MovedWhileBorrowed {
-        borrow_point: LocationPoint,
-        move_point: LocationPoint,
+        borrow_point: ErrorSite,
+        move_point: ErrorSite,
     },
 }
 
+impl NllError {
+    /// Span of the statement the error is reported at, if recorded
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            NllError::UseAfterMove { used_at: site, .. }
+            | NllError::ConflictingBorrow {
+                second_borrow: site,
+                ..
+            }
+            | NllError::InvalidatedBorrow {
+                use_point: site, ..
+            }
+            | NllError::MovedWhileBorrowed {
+                move_point: site, ..
+            } => site.span,
+        }
+    }
+}
+
+/// How errors name local `index` of `func`: by its variable name if it has
+/// one, as `_N` otherwise
+fn local_name(func: &MirFunction, index: usize) -> String {
+    match func.locals.iter().find(|l| l.index == index) {
+        Some(MirLocal {
+            name: Some(name), ..
+        }) => format!("`{}`", name),
+        _ => format!("_{}", index),
+    }
+}
+
+impl std::fmt::Display for NllError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NllError::UseAfterMove {
+                moved_at,
+                used_at,
+                local,
+            } => write!(
+                f,
+                "use of moved local {} at {} (moved at {})",
+                local, used_at, moved_at
+            ),
+            NllError::ConflictingBorrow {
+                first_borrow,
+                second_borrow,
+                local,
+                first_mutable,
+                second_mutable,
+            } => {
+                let kind = |mutable: &bool| if *mutable { "mutable" } else { "shared" };
+                write!(
+                    f,
+                    "{} borrow of local {} at {} conflicts with {} borrow at {}",
+                    kind(second_mutable),
+                    local,
+                    second_borrow,
+                    kind(first_mutable),
+                    first_borrow
+                )
+            }
+            NllError::InvalidatedBorrow {
+                borrow_point,
+                invalidation_point,
+                use_point,
+            } => write!(
+                f,
+                "borrow from {} used at {} after being invalidated at {}",
+                borrow_point, use_point, invalidation_point
+            ),
+            NllError::MovedWhileBorrowed {
+                borrow_point,
+                move_point,
+            } => write!(
+                f,
+                "value moved at {} while still borrowed (borrow at {})",
+                move_point, borrow_point
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NllError {}
+
 impl NllChecker {
     pub fn new() -> Self {
         Self {
@@ -595,9 +706,9 @@ impl NllChecker {
                         // Conflict if: both mutable, or one mutable and one shared
                         if !overlap.is_empty() && (b1.mutable || b2.mutable) {
                             self.errors.push(NllError::ConflictingBorrow {
-                                first_borrow: b1.activation_point,
-                                second_borrow: b2.activation_point,
-                                local: b1.borrowed_place.local,
+                                first_borrow: ErrorSite::at(func, b1.activation_point),
+                                second_borrow: ErrorSite::at(func, b2.activation_point),
+                                local: local_name(func, b1.borrowed_place.local),
                                 first_mutable: b1.mutable,
                                 second_mutable: b2.mutable,
                             });
@@ -621,8 +732,8 @@ impl NllChecker {
                                 // If the borrow is still active at this point, error
                                 if region.points.contains(&point) {
                                     self.errors.push(NllError::MovedWhileBorrowed {
-                                        borrow_point: borrow.activation_point,
-                                        move_point: point,
+                                        borrow_point: ErrorSite::at(func, borrow.activation_point),
+                                        move_point: ErrorSite::at(func, point),
                                     });
                                 }
                             }
@@ -700,6 +811,46 @@ mod tests {
         assert_eq!(r2.0, 1);
     }
 
+    #[test]
+    fn test_errors_report_statement_spans() {
+        let source = "kāryakrama f() {\n    let x = 1;\n    let a = &x;\n    let b = &mut x;\n}\n";
+        let ast = crate::parser::Parser::parse_str(source).unwrap();
+        let module = crate::mir::MirBuilder::new().build(&ast);
+        let func = &module.functions[0];
+
+        let borrows: Vec<_> = func.blocks[0]
+            .instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, inst)| {
+                matches!(
+                    inst,
+                    MirInstruction::Assign {
+                        value: MirRvalue::Ref { .. },
+                        ..
+                    }
+                )
+            })
+            .map(|(index, _)| LocationPoint::new(func.blocks[0].id, index))
+            .collect();
+        assert_eq!(borrows.len(), 2);
+
+        // Keep the shared borrow live up to the mutable one
+        let mut checker = NllChecker::new();
+        checker.add_constraint(RegionConstraint::ContainsPoint {
+            region: RegionVar(0),
+            point: borrows[1],
+        });
+        let errors = checker.check_function(func).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        let span = errors[0].span().expect("conflict should have a span");
+        assert_eq!((span.line, span.column), (4, 5));
+        assert_eq!(
+            errors[0].to_string(),
+            "mutable borrow of local `x` at line 4:5 conflicts with shared borrow at line 3:5"
+        );
+    }
+
     #[test]
     fn test_liveness_info_creation() {
         let info = LivenessInfo::new();
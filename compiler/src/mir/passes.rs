@@ -79,6 +79,10 @@ impl DeadCodeElimination {
                     MirInstruction::Drop { place } => {
                         self.mark_place_used(place);
                     }
+                    // Loads are kept, so the address they read must be too
+                    MirInstruction::Load { ptr, .. } => {
+                        self.mark_operand_used(ptr);
+                    }
                     MirInstruction::Assert { condition, .. } => {
                         self.mark_operand_used(condition);
                     }
@@ -208,7 +212,9 @@ impl DeadCodeElimination {
             }
             self.reachable_blocks.insert(block_id);
 
-            if let Some(block) = func.blocks.get(block_id) {
+            // Blocks are looked up by id: a nested match lays them out
+            // out of order
+            if let Some(block) = func.blocks.iter().find(|block| block.id == block_id) {
                 match &block.terminator {
                     MirTerminator::Goto { target } => {
                         worklist.push_back(*target);
@@ -409,6 +415,11 @@ impl ConstantPropagation {
     ) -> Option<MirConstant> {
         match (left, right) {
             (MirConstant::Int(l, ls), MirConstant::Int(r, rs)) if ls == rs => {
+                // A `U64` from 2^63 up is kept negative, which signed
+                // arithmetic would misread
+                if !ls.is_signed() && (*l < 0 || *r < 0) {
+                    return None;
+                }
                 let result = match op {
                     BinaryOp::Add => l.checked_add(*r)?,
                     BinaryOp::Sub => l.checked_sub(*r)?,
@@ -437,7 +448,8 @@ impl ConstantPropagation {
                     BinaryOp::Gt => return Some(MirConstant::Bool(l > r)),
                     BinaryOp::Ge => return Some(MirConstant::Bool(l >= r)),
                 };
-                Some(MirConstant::Int(result, *ls))
+                // An overflow is left to the program, to wrap or trap
+                ls.fits(result).then_some(MirConstant::Int(result, *ls))
             }
             (MirConstant::Float(l, ls), MirConstant::Float(r, rs)) if ls == rs => {
                 let result = match op {
@@ -473,10 +485,13 @@ impl ConstantPropagation {
     /// Evaluate a unary operation on a constant
     fn eval_unary_op(&self, op: &UnaryOp, operand: &MirConstant) -> Option<MirConstant> {
         match (op, operand) {
-            (UnaryOp::Neg, MirConstant::Int(v, s)) => Some(MirConstant::Int(-v, *s)),
+            (UnaryOp::Neg, MirConstant::Int(v, s)) => v
+                .checked_neg()
+                .filter(|v| s.fits(*v))
+                .map(|v| MirConstant::Int(v, *s)),
             (UnaryOp::Neg, MirConstant::Float(v, s)) => Some(MirConstant::Float(-v, *s)),
             (UnaryOp::Not, MirConstant::Bool(v)) => Some(MirConstant::Bool(!v)),
-            (UnaryOp::Not, MirConstant::Int(v, s)) => Some(MirConstant::Int(!v, *s)),
+            (UnaryOp::Not, MirConstant::Int(v, s)) => Some(MirConstant::Int(s.wrap(!v), *s)),
             _ => None,
         }
     }
@@ -839,6 +854,11 @@ impl Inlining {
             return false;
         }
 
+        // `#[samāveśa(na)]` opts the callee out of inlining entirely
+        if callee.inline == InlineHint::Never {
+            return false;
+        }
+
         // Count instructions
         let instruction_count: usize = callee.blocks.iter().map(|b| b.instructions.len()).sum();
 
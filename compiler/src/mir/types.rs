@@ -4,6 +4,10 @@
 
 use std::collections::HashMap;
 
+use super::layout::EnumLayout;
+
+pub use crate::semantics::InlineHint;
+
 /// MIR Module
 pub struct MirModule {
     pub name: String,
@@ -22,6 +26,24 @@ pub struct MirFunction {
     pub locals: Vec<MirLocal>,
     /// Kāraka hints for register allocation
     pub karaka_hints: HashMap<usize, KarakaHint>,
+    /// Inlining hint from `#[samāveśa]`
+    pub inline: InlineHint,
+    /// What integer arithmetic does when its result does not fit
+    pub overflow: Overflow,
+    /// Span of the statement each instruction was lowered from, by block
+    /// id and instruction index, for diagnostics on the built MIR
+    pub spans: HashMap<(usize, usize), crate::lexer::Span>,
+}
+
+/// Behaviour of integer arithmetic whose result does not fit its type,
+/// chosen by the guṇa mode the program is built in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Overflow {
+    /// Keep the low bits, as two's complement arithmetic does (Rajas, Tamas)
+    #[default]
+    Wrap,
+    /// Stop the program (Sattva)
+    Trap,
 }
 
 /// MIR Parameter
@@ -318,6 +340,10 @@ pub enum AggregateKind {
     Array,
     Struct { name: String },
     Enum { name: String, variant: usize },
+    /// Closure environment: the address of `function`, then the captures
+    Closure {
+        function: String,
+    },
 }
 
 /// Cast kind
@@ -354,6 +380,58 @@ pub enum IntSize {
     U64,
 }
 
+impl IntSize {
+    /// Size of `bits` bits, signed or not
+    pub fn of(bits: u32, signed: bool) -> Option<Self> {
+        Some(match (bits, signed) {
+            (8, true) => IntSize::I8,
+            (16, true) => IntSize::I16,
+            (32, true) => IntSize::I32,
+            (64, true) => IntSize::I64,
+            (8, false) => IntSize::U8,
+            (16, false) => IntSize::U16,
+            (32, false) => IntSize::U32,
+            (64, false) => IntSize::U64,
+            _ => return None,
+        })
+    }
+
+    /// Width in bits
+    pub fn bits(self) -> u32 {
+        match self {
+            IntSize::I8 | IntSize::U8 => 8,
+            IntSize::I16 | IntSize::U16 => 16,
+            IntSize::I32 | IntSize::U32 => 32,
+            IntSize::I64 | IntSize::U64 => 64,
+        }
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(
+            self,
+            IntSize::I8 | IntSize::I16 | IntSize::I32 | IntSize::I64
+        )
+    }
+
+    /// `value` reduced to this width: its low bits, sign- or zero-extended
+    /// to 64 as every integer is kept in its 8-byte slot
+    ///
+    /// A `U64` keeps all its bits, so values from 2^63 up read as negative.
+    pub fn wrap(self, value: i64) -> i64 {
+        let shift = 64 - self.bits();
+        if self.is_signed() {
+            (value << shift) >> shift
+        } else {
+            ((value as u64) << shift >> shift) as i64
+        }
+    }
+
+    /// Whether `value` is one of this type's, as kept in a slot
+    pub fn fits(self, value: i64) -> bool {
+        self.wrap(value) == value
+    }
+}
+
 /// Float sizes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FloatSize {
@@ -384,6 +462,40 @@ pub enum MirType {
         params: Vec<MirType>,
         ret: Box<MirType>,
     },
+    /// Environment of a closure whose code is `function`
+    ///
+    /// Laid out as the code address followed by one slot per capture;
+    /// by-reference captures hold the captured variable's address.
+    Closure {
+        function: String,
+        captures: Vec<MirType>,
+    },
+    /// Value of enum `name`, a tagged union laid out by `layout`
+    Enum {
+        name: String,
+        layout: Box<EnumLayout>,
+    },
+}
+
+impl MirType {
+    /// Number of 8-byte stack slots a local of this type occupies
+    pub fn stack_slots(&self) -> usize {
+        match self {
+            MirType::Closure { captures, .. } => 1 + captures.len(),
+            // `{data, len}` header, then the elements
+            MirType::Array { size, .. } => 2 + size,
+            MirType::Enum { layout, .. } => layout.words(),
+            _ => 1,
+        }
+    }
+
+    /// Layout of an enum value
+    pub fn enum_layout(&self) -> Option<&EnumLayout> {
+        match self {
+            MirType::Enum { layout, .. } => Some(layout),
+            _ => None,
+        }
+    }
 }
 
 /// MIR Global
@@ -410,5 +522,7 @@ pub enum MirTypeDefKind {
     },
     Enum {
         variants: Vec<(String, Option<MirType>)>,
+        /// Layout of the enum's values, unless it is generic
+        layout: Option<EnumLayout>,
     },
 }
@@ -23,17 +23,19 @@
 
 pub mod graph;
 pub mod resolver;
+pub mod scope;
 pub mod symbol;
 pub mod typeck_integration;
 pub mod visibility;
 
 pub use graph::{ModuleGraph, ModuleId};
 pub use resolver::{ModuleResolver, ResolveError};
-pub use symbol::{Symbol, SymbolKind, SymbolTable};
+pub use scope::{CrateScope, ScopeError};
+pub use symbol::{Symbol, SymbolKind, SymbolTable, TraitSymbol, TypeKind};
 pub use typeck_integration::{CrossModuleTypeEnv, ResolvedTypeInfo, TypeReference};
 pub use visibility::{Visibility, VisibilityScope};
 
-use crate::parser::ast::{Ast, ImportStmt, Item};
+use crate::parser::ast::{Ast, ImportStmt, Item, TypeBody};
 use std::path::{Path, PathBuf};
 
 /// A compiled module in the module graph
@@ -205,6 +207,321 @@ impl ModuleContext {
     }
 }
 
+impl Module {
+    /// Create a module from a parsed file; its symbol table lists every
+    /// top-level item with the item's declared visibility
+    pub fn from_ast(name: String, path: Vec<String>, file_path: PathBuf, ast: Ast) -> Self {
+        let mut exports = SymbolTable::new();
+        for item in &ast.items {
+            let (symbol, visibility) = match item {
+                Item::Function(func) => (
+                    Symbol::function(
+                        func.name.name.clone(),
+                        func.params.iter().map(|p| p.ty.clone()).collect(),
+                        func.return_type.clone(),
+                        func.span,
+                    ),
+                    func.visibility,
+                ),
+                Item::TypeDef(typedef) => {
+                    let kind = match typedef.body {
+                        TypeBody::Struct(_) => TypeKind::Struct,
+                        TypeBody::Enum(_) => TypeKind::Enum,
+                        TypeBody::Alias(_) => TypeKind::Alias,
+                    };
+                    let generics = typedef
+                        .generics
+                        .iter()
+                        .map(|g| g.name.name.clone())
+                        .collect();
+                    (
+                        Symbol::type_def(typedef.name.name.clone(), kind, generics, typedef.span),
+                        typedef.visibility,
+                    )
+                }
+                Item::Trait(trait_def) => {
+                    let mut symbol = Symbol::type_def(
+                        trait_def.name.name.clone(),
+                        TypeKind::Alias,
+                        Vec::new(),
+                        trait_def.span,
+                    );
+                    symbol.kind = SymbolKind::Trait(TraitSymbol {
+                        methods: Vec::new(),
+                        associated_types: trait_def
+                            .associated_types
+                            .iter()
+                            .map(|a| a.name.name.clone())
+                            .collect(),
+                    });
+                    (symbol, trait_def.visibility)
+                }
+                Item::Constant(constant) => {
+                    let mut symbol = Symbol::function(
+                        constant.name.name.clone(),
+                        Vec::new(),
+                        None,
+                        constant.span,
+                    );
+                    symbol.kind = SymbolKind::Variable;
+                    symbol.ty = constant.ty.clone();
+                    (symbol, constant.visibility)
+                }
+                Item::Module(decl) => {
+                    let mut symbol =
+                        Symbol::function(decl.name.name.clone(), Vec::new(), None, decl.span);
+                    symbol.kind = SymbolKind::Module;
+                    (symbol, decl.visibility)
+                }
+                _ => continue,
+            };
+            let mut symbol = symbol.in_module(path.clone());
+            symbol.visibility = visibility;
+            // Duplicates are reported by the type checker
+            let _ = exports.define_global(symbol);
+        }
+
+        Self {
+            id: ModuleId(0),
+            name,
+            path,
+            file_path,
+            ast: Some(ast),
+            exports,
+            imports: Vec::new(),
+            compiled: false,
+        }
+    }
+}
+
+impl ModuleContext {
+    /// Load a whole crate starting from its root file (Khaṇḍa Saṅgraha)
+    ///
+    /// `vibhāga name;` declarations are followed to `name.jag` (or
+    /// `name/mod.jag`) next to the declaring file, and the first segment of an
+    /// `upayoga` path is loaded from the root directory or the resolver's
+    /// search paths when no module declares it. Each file is parsed with
+    /// `parse`. Returns the modules in dependency order, dependencies first.
+    pub fn load_crate<F>(
+        &mut self,
+        root_file: &Path,
+        root_ast: Ast,
+        mut parse: F,
+    ) -> Result<Vec<ModuleId>, ModuleError>
+    where
+        F: FnMut(&Path) -> Result<Ast, ModuleError>,
+    {
+        let root_name = root_file
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("main")
+            .to_string();
+        let root = self.graph.add_module(Module::from_ast(
+            root_name,
+            Vec::new(),
+            root_file.to_path_buf(),
+            root_ast,
+        ));
+
+        // 1. Load every file reachable through vibhāga/upayoga
+        let mut pending = vec![root];
+        while let Some(id) = pending.pop() {
+            // Inline `vibhāga name { ... }` bodies become modules of their own
+            let module = self.graph.modules.get_mut(&id).expect("pending module");
+            let (path, file) = (module.path.clone(), module.file_path.clone());
+            let inline: Vec<(String, Vec<Item>)> = module
+                .ast
+                .iter_mut()
+                .flat_map(|ast| &mut ast.items)
+                .filter_map(|item| match item {
+                    Item::Module(decl) if decl.inline => {
+                        Some((decl.name.name.clone(), std::mem::take(&mut decl.items)))
+                    }
+                    _ => None,
+                })
+                .collect();
+            for (name, items) in inline {
+                let mut child_path = path.clone();
+                child_path.push(name.clone());
+                if self.graph.find_by_path(&child_path).is_some() {
+                    return Err(ModuleError::DuplicateModule(child_path));
+                }
+                let ast = Ast {
+                    items,
+                    file_path: file.display().to_string(),
+                };
+                let child =
+                    self.graph
+                        .add_module(Module::from_ast(name, child_path, file.clone(), ast));
+                self.graph.add_dependency(id, child);
+                pending.push(child);
+            }
+
+            let module = &self.graph.modules[&id];
+            let mut children = Vec::new();
+            let mut top_level = Vec::new();
+            for item in module.ast.iter().flat_map(|ast| &ast.items) {
+                match item {
+                    Item::Module(decl) if !decl.inline => children.push(decl.name.name.clone()),
+                    Item::Import(import) => {
+                        if let Some(first) = import.path.first() {
+                            top_level.push(first.name.clone());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            for name in children {
+                let mut child_path = path.clone();
+                child_path.push(name.clone());
+                let child = match self.graph.find_by_path(&child_path) {
+                    Some(child) => child,
+                    None => {
+                        let child_file = self.resolve_child_file(&file, id == root, &child_path)?;
+                        let ast = parse(&child_file)?;
+                        let child = self
+                            .graph
+                            .add_module(Module::from_ast(name, child_path, child_file, ast));
+                        pending.push(child);
+                        child
+                    }
+                };
+                self.graph.add_dependency(id, child);
+            }
+
+            for name in top_level {
+                let module_path = vec![name.clone()];
+                if self.graph.find_by_path(&module_path).is_some()
+                    || Self::is_external_import(&module_path)
+                {
+                    continue;
+                }
+                // Not a file: a local item, or reported when imports resolve
+                let Ok(found) = self.resolver.resolve_path(
+                    &module_path,
+                    &self.root_path,
+                    self.stdlib_path.as_deref(),
+                ) else {
+                    continue;
+                };
+                let ast = parse(&found)?;
+                let loaded = self
+                    .graph
+                    .add_module(Module::from_ast(name, module_path, found, ast));
+                pending.push(loaded);
+            }
+        }
+
+        // 2. Resolve imports to modules; each one is a dependency edge
+        let mut ids: Vec<ModuleId> = self.graph.modules.keys().copied().collect();
+        ids.sort_by_key(|id| id.0);
+        for id in ids {
+            let imports: Vec<ImportStmt> = self.graph.modules[&id]
+                .ast
+                .iter()
+                .flat_map(|ast| &ast.items)
+                .filter_map(|item| match item {
+                    Item::Import(import) => Some(import.clone()),
+                    _ => None,
+                })
+                .collect();
+            for import in imports {
+                let Some(decl) = self.resolve_import_decl(id, &import)? else {
+                    continue;
+                };
+                if let Some(target) = self.graph.find_by_path(&decl.path) {
+                    if target != id {
+                        self.graph.add_dependency(id, target);
+                    }
+                }
+                if let Some(module) = self.graph.modules.get_mut(&id) {
+                    module.imports.push(decl);
+                }
+            }
+        }
+
+        // 3. Cakravyūha check, then dependencies before dependents
+        if let Some(cycle) = self.graph.find_cycle() {
+            return Err(ModuleError::CircularDependency(cycle));
+        }
+        self.graph.topological_order()
+    }
+
+    /// Imports served by the Rust-side standard library or the prelude
+    fn is_external_import(path: &[String]) -> bool {
+        ModuleResolver::is_stdlib_import(path)
+            || matches!(
+                path.first().map(|s| s.as_str()),
+                Some("upakrama" | "prelude")
+            )
+    }
+
+    /// Find the file for `vibhāga name;` declared in `parent_file`
+    fn resolve_child_file(
+        &mut self,
+        parent_file: &Path,
+        parent_is_root: bool,
+        child_path: &[String],
+    ) -> Result<PathBuf, ModuleError> {
+        let name = child_path[child_path.len() - 1].clone();
+        // Children of `a.jag` live in `a/`; children of the root or of a
+        // `mod.jag` live next to it
+        let is_dir_module = parent_file.file_name().and_then(|f| f.to_str()) == Some("mod.jag");
+        let relative = match parent_file.file_stem().and_then(|s| s.to_str()) {
+            Some(stem) if !parent_is_root && !is_dir_module => vec![stem.to_string(), name.clone()],
+            _ => vec![name.clone()],
+        };
+        self.resolver
+            .resolve_relative(&relative, parent_file)
+            .or_else(|_| {
+                self.resolver
+                    .resolve_path(&[name], &self.root_path, self.stdlib_path.as_deref())
+            })
+            .map_err(|_| ModuleError::NotFound(child_path.to_vec()))
+    }
+
+    /// Split an `upayoga` path into the module it names and what it imports
+    /// from there. Paths are crate-absolute; `None` means the import does not
+    /// name a crate module (prelude, standard library or a local item).
+    fn resolve_import_decl(
+        &self,
+        from: ModuleId,
+        import: &ImportStmt,
+    ) -> Result<Option<ImportDecl>, ModuleError> {
+        let path: Vec<String> = import.path.iter().map(|i| i.name.clone()).collect();
+        if path.is_empty() || Self::is_external_import(&path) {
+            return Ok(None);
+        }
+
+        for len in (1..=path.len()).rev() {
+            if self.graph.find_by_path(&path[..len]).is_none() {
+                continue;
+            }
+            let rest = &path[len..];
+            let symbols = match (rest, import.glob) {
+                ([], true) => ImportKind::Glob,
+                ([], false) => ImportKind::Module,
+                ([name], false) => ImportKind::Selective(vec![name.clone()]),
+                _ => return Err(ModuleError::NotFound(path)),
+            };
+            return Ok(Some(ImportDecl {
+                path: path[..len].to_vec(),
+                alias: import.alias.as_ref().map(|a| a.name.clone()),
+                symbols,
+                span: import.span,
+            }));
+        }
+
+        // `upayoga Item::Variant` style imports of the module's own items
+        let local = self.graph.modules[&from].exports.lookup_global(&path[0]);
+        if local.is_some() {
+            return Ok(None);
+        }
+        Err(ModuleError::NotFound(path))
+    }
+}
+
 /// Module system errors
 #[derive(Debug, Clone)]
 pub enum ModuleError {
@@ -213,6 +530,8 @@ pub enum ModuleError {
     CircularDependency(Vec<ModuleId>),
     /// Module not found
     NotFound(Vec<String>),
+    /// Two modules declared under the same path
+    DuplicateModule(Vec<String>),
     /// IO error reading module
     IoError(PathBuf, String),
     /// Parse error in module
@@ -248,6 +567,9 @@ impl std::fmt::Display for ModuleError {
             ModuleError::NotFound(path) => {
                 write!(f, "Module not found: {}", path.join("::"))
             }
+            ModuleError::DuplicateModule(path) => {
+                write!(f, "Module `{}` is declared twice", path.join("::"))
+            }
             ModuleError::IoError(path, msg) => {
                 write!(f, "IO error reading {}: {}", path.display(), msg)
             }
@@ -0,0 +1,668 @@
+//! Crate Scope (Kṣetra - क्षेत्र)
+//!
+//! Resolves the names each module of a loaded crate can see, enforces
+//! visibility across module boundaries and links the modules into one AST.
+//!
+//! Names visible in a module:
+//! - its own top-level items
+//! - `upayoga a::b::f` → `f`, `upayoga a::b::*` → every visible item of `a::b`
+//! - `upayoga a::b` → the module itself, so `b::f` works
+//! - qualified paths `a::b::f` through child modules and top-level modules
+//!
+//! Every module on a path must itself be visible: a private `vibhāga b`
+//! inside `a` can only be named from `a` and its children.
+//!
+//! Types and traits are named the same way: a bare name of another
+//! module's type must be imported.
+//!
+//! Linking keeps root functions and constants under their own names (so
+//! `mukhya` stays the entry point) and mangles those of other modules to
+//! `a__b__f`. Types and traits share one crate-wide namespace, so type
+//! paths are linked to the bare names of their types.
+
+use super::visibility::VisibilityChecker;
+use super::{ImportKind, ModuleContext, ModuleId, SymbolKind, VisibilityScope};
+use crate::lexer::Span;
+use crate::parser::ast::{
+    Ast, Expr, FunctionDef, Identifier, ImplDef, Item, ModuleDef, Parameter, Stmt, Type, TypeBody,
+    TypeDef,
+};
+use crate::visitor::{
+    walk_expr, walk_expr_mut, walk_stmt_mut, AstVisitor, AstVisitorMut, VisitResult,
+};
+use std::collections::HashMap;
+
+/// A name bound in a module, pointing at the item it refers to
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopedItem {
+    /// Module defining the item
+    pub module: ModuleId,
+    /// Item name in its defining module
+    pub name: String,
+}
+
+/// Name resolution or visibility error
+#[derive(Debug, Clone)]
+pub struct ScopeError {
+    /// Module where the error occurred
+    pub module: ModuleId,
+    pub message: String,
+    pub span: Span,
+}
+
+impl std::fmt::Display for ScopeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Resolved names of every module in a crate
+pub struct CrateScope {
+    crate_name: String,
+    /// Per module: visible name → item
+    bindings: HashMap<ModuleId, HashMap<String, ScopedItem>>,
+    errors: Vec<ScopeError>,
+}
+
+impl CrateScope {
+    /// Resolve the imports and qualified paths of every loaded module
+    pub fn build(ctx: &ModuleContext, crate_name: &str) -> Self {
+        let mut scope = Self {
+            crate_name: crate_name.to_string(),
+            bindings: HashMap::new(),
+            errors: Vec::new(),
+        };
+
+        let mut ids: Vec<ModuleId> = ctx.graph.modules.keys().copied().collect();
+        ids.sort_by_key(|id| id.0);
+        for id in ids {
+            let bindings = scope.resolve_module(ctx, id);
+            scope.bindings.insert(id, bindings);
+        }
+        scope
+    }
+
+    /// Name of the crate being resolved
+    pub fn crate_name(&self) -> &str {
+        &self.crate_name
+    }
+
+    /// Errors found while resolving
+    pub fn errors(&self) -> &[ScopeError] {
+        &self.errors
+    }
+
+    /// Items of other modules that `module` refers to, with the name used
+    pub fn imported_items(&self, module: ModuleId) -> Vec<(&str, &ScopedItem)> {
+        let mut items: Vec<(&str, &ScopedItem)> = self
+            .bindings
+            .get(&module)
+            .into_iter()
+            .flatten()
+            .filter(|(_, item)| item.module != module)
+            .map(|(name, item)| (name.as_str(), item))
+            .collect();
+        items.sort_by(|a, b| a.0.cmp(b.0));
+        items
+    }
+
+    /// Link-time name of a function or constant: root items keep their name
+    pub fn mangled_name(ctx: &ModuleContext, item: &ScopedItem) -> String {
+        match ctx.graph.modules.get(&item.module) {
+            Some(module) if !module.path.is_empty() => {
+                format!("{}__{}", module.path.join("__"), item.name)
+            }
+            _ => item.name.clone(),
+        }
+    }
+
+    /// Merge all modules into one AST, dependencies first
+    pub fn link(&self, ctx: &ModuleContext, order: &[ModuleId]) -> Result<Ast, Vec<ScopeError>> {
+        let root = order.last().and_then(|id| ctx.graph.modules.get(id));
+        let mut linked = Ast {
+            items: Vec::new(),
+            file_path: root
+                .map(|m| m.file_path.display().to_string())
+                .unwrap_or_default(),
+        };
+        let mut errors = Vec::new();
+        let mut types: HashMap<String, ModuleId> = HashMap::new();
+
+        for &id in order {
+            let Some(ast) = ctx.graph.modules.get(&id).and_then(|m| m.ast.as_ref()) else {
+                continue;
+            };
+            let bindings = self.bindings.get(&id);
+            let linked_names = |kind: fn(&SymbolKind) -> bool| {
+                bindings
+                    .into_iter()
+                    .flatten()
+                    .filter(|(_, item)| symbol_kind(ctx, item).is_some_and(kind))
+                    .map(|(name, item)| (name.clone(), Self::mangled_name(ctx, item)))
+                    .collect()
+            };
+            let mut renamer = CallRenamer {
+                names: linked_names(|kind| matches!(kind, SymbolKind::Function(_))),
+                constants: linked_names(|kind| matches!(kind, SymbolKind::Variable)),
+                types: bindings
+                    .into_iter()
+                    .flatten()
+                    .filter(|(name, item)| {
+                        **name != item.name
+                            && symbol_kind(ctx, item).is_some_and(|kind| {
+                                matches!(kind, SymbolKind::Type(_) | SymbolKind::Trait(_))
+                            })
+                    })
+                    .map(|(name, item)| (name.clone(), item.name.clone()))
+                    .collect(),
+            };
+
+            for item in &ast.items {
+                let mut item = item.clone();
+                match &mut item {
+                    // Inline module bodies were loaded as modules of their own
+                    Item::Import(_) | Item::Module(_) => continue,
+                    Item::Constant(constant) => {
+                        constant.name.name = Self::mangled_name(
+                            ctx,
+                            &ScopedItem {
+                                module: id,
+                                name: constant.name.name.clone(),
+                            },
+                        );
+                    }
+                    Item::Function(func) => {
+                        func.name.name = Self::mangled_name(
+                            ctx,
+                            &ScopedItem {
+                                module: id,
+                                name: func.name.name.clone(),
+                            },
+                        );
+                    }
+                    Item::TypeDef(typedef) => {
+                        let (name, span) = (typedef.name.name.clone(), typedef.span);
+                        self.claim_type(ctx, &mut types, &mut errors, id, name, span);
+                    }
+                    Item::Trait(trait_def) => {
+                        let (name, span) = (trait_def.name.name.clone(), trait_def.span);
+                        self.claim_type(ctx, &mut types, &mut errors, id, name, span);
+                    }
+                    _ => {}
+                }
+                let _ = renamer.visit_item_mut(&mut item);
+                linked.items.push(item);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(linked)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Types and traits are crate-wide; two modules may not define the same name
+    fn claim_type(
+        &self,
+        ctx: &ModuleContext,
+        types: &mut HashMap<String, ModuleId>,
+        errors: &mut Vec<ScopeError>,
+        module: ModuleId,
+        name: String,
+        span: Span,
+    ) {
+        if let Some(&first) = types.get(&name) {
+            errors.push(ScopeError {
+                module,
+                message: format!(
+                    "`{}` is already defined in module `{}`",
+                    name,
+                    module_display(ctx, first, &self.crate_name)
+                ),
+                span,
+            });
+        } else {
+            types.insert(name, module);
+        }
+    }
+
+    fn resolve_module(&mut self, ctx: &ModuleContext, id: ModuleId) -> HashMap<String, ScopedItem> {
+        let module = &ctx.graph.modules[&id];
+        let mut checker = VisibilityChecker::new(VisibilityScope::new(
+            self.crate_name.clone(),
+            module.path.clone(),
+        ));
+        let mut bindings: HashMap<String, ScopedItem> = module
+            .exports
+            .all_symbols()
+            .map(|symbol| {
+                let item = ScopedItem {
+                    module: id,
+                    name: symbol.name.clone(),
+                };
+                (symbol.name.clone(), item)
+            })
+            .collect();
+
+        // Module names usable as the first segment of a path; child modules
+        // shadow top-level ones
+        let mut modules: HashMap<String, ModuleId> = HashMap::new();
+        let mut child_path = module.path.clone();
+        for m in ctx.graph.modules.values().filter(|m| m.path.len() == 1) {
+            modules.insert(m.name.clone(), m.id);
+        }
+        for m in ctx.graph.modules.values() {
+            child_path.truncate(module.path.len());
+            child_path.push(m.name.clone());
+            if m.path == child_path {
+                modules.insert(m.name.clone(), m.id);
+            }
+        }
+
+        for import in &module.imports {
+            let Some(target) = ctx.graph.find_by_path(&import.path) else {
+                continue;
+            };
+            if !self.check_module_path(ctx, &mut checker, &import.path, import.span) {
+                continue;
+            }
+            match &import.symbols {
+                ImportKind::Module => {
+                    let name = import
+                        .alias
+                        .clone()
+                        .unwrap_or_else(|| import.path[import.path.len() - 1].clone());
+                    modules.insert(name, target);
+                }
+                ImportKind::Glob => {
+                    let target_module = &ctx.graph.modules[&target];
+                    let target_scope =
+                        VisibilityScope::new(self.crate_name.clone(), target_module.path.clone());
+                    let here = VisibilityScope::new(self.crate_name.clone(), module.path.clone());
+                    let names = target_module.exports.all_symbols().filter(|symbol| {
+                        symbol.kind != SymbolKind::Module
+                            && symbol.visibility.allows_access(&here, &target_scope)
+                    });
+                    for symbol in names {
+                        bindings.entry(symbol.name.clone()).or_insert(ScopedItem {
+                            module: target,
+                            name: symbol.name.clone(),
+                        });
+                    }
+                }
+                ImportKind::Selective(names) => {
+                    for name in names {
+                        if !self.check_item(ctx, &mut checker, id, target, name, import.span) {
+                            continue;
+                        }
+                        let local = import.alias.clone().unwrap_or_else(|| name.clone());
+                        bindings.insert(
+                            local,
+                            ScopedItem {
+                                module: target,
+                                name: name.clone(),
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        // Qualified paths used in function bodies and types
+        let mut paths = PathCollector {
+            paths: Vec::new(),
+            types: Vec::new(),
+        };
+        if let Some(ast) = &module.ast {
+            let _ = paths.visit_ast(ast);
+        }
+        for (path, span) in paths.paths {
+            let segments: Vec<&str> = path.split("::").collect();
+            let Some(mut target) = modules.get(segments[0]).copied() else {
+                // `Prakāra::nava` and other non-module paths
+                continue;
+            };
+            let mut rest = &segments[1..];
+            let mut visible = true;
+            while rest.len() > 1 {
+                let mut child_path = ctx.graph.modules[&target].path.clone();
+                child_path.push(rest[0].to_string());
+                match ctx.graph.find_by_path(&child_path) {
+                    Some(child) => {
+                        visible &= self.check_module(ctx, &mut checker, target, rest[0], span);
+                        target = child;
+                        rest = &rest[1..];
+                    }
+                    None => break,
+                }
+            }
+            if !visible {
+                continue;
+            }
+            if rest.len() != 1 {
+                self.errors.push(ScopeError {
+                    module: id,
+                    message: format!("Cannot resolve path `{}`", path),
+                    span,
+                });
+                continue;
+            }
+            if self.check_item(ctx, &mut checker, id, target, rest[0], span) {
+                let item = ScopedItem {
+                    module: target,
+                    name: rest[0].to_string(),
+                };
+                bindings.insert(path, item);
+            }
+        }
+
+        // Types and traits of other modules are named by path or imported
+        for (name, span) in paths.types {
+            if bindings.contains_key(&name) {
+                continue;
+            }
+            let defined = ctx.graph.modules.values().find(|m| {
+                m.exports.lookup_global(&name).is_some_and(|symbol| {
+                    matches!(symbol.kind, SymbolKind::Type(_) | SymbolKind::Trait(_))
+                })
+            });
+            if let Some(defined) = defined {
+                self.errors.push(ScopeError {
+                    module: id,
+                    message: format!(
+                        "`{}` of module `{}` is not in scope; name it by path or import it",
+                        name,
+                        module_display(ctx, defined.id, &self.crate_name)
+                    ),
+                    span,
+                });
+            }
+        }
+
+        for violation in checker.violations() {
+            self.errors.push(ScopeError {
+                module: id,
+                message: format!(
+                    "Cannot access {} symbol `{}` of module `{}`",
+                    violation.actual.sanskrit_name(),
+                    violation.symbol,
+                    violation.definition_module.join("::")
+                ),
+                span: violation.access_location,
+            });
+        }
+        bindings
+    }
+
+    /// Check that `name` exists in `target` and is visible from `from`
+    fn check_item(
+        &mut self,
+        ctx: &ModuleContext,
+        checker: &mut VisibilityChecker,
+        from: ModuleId,
+        target: ModuleId,
+        name: &str,
+        span: Span,
+    ) -> bool {
+        let target_module = &ctx.graph.modules[&target];
+        let Some(symbol) = target_module.exports.lookup_global(name) else {
+            self.errors.push(ScopeError {
+                module: from,
+                message: format!(
+                    "`{}` is not defined in module `{}`",
+                    name,
+                    module_display(ctx, target, &self.crate_name)
+                ),
+                span,
+            });
+            return false;
+        };
+        let symbol_scope =
+            VisibilityScope::new(self.crate_name.clone(), target_module.path.clone());
+        checker.check_access(name, symbol.visibility, &symbol_scope, span)
+    }
+
+    /// Check that every module along the crate-absolute `path` is visible
+    fn check_module_path(
+        &mut self,
+        ctx: &ModuleContext,
+        checker: &mut VisibilityChecker,
+        path: &[String],
+        span: Span,
+    ) -> bool {
+        (0..path.len()).all(|len| match ctx.graph.find_by_path(&path[..len]) {
+            Some(parent) => self.check_module(ctx, checker, parent, &path[len], span),
+            None => true,
+        })
+    }
+
+    /// Check that child module `name` of `parent` is visible
+    ///
+    /// Modules found on the search path without a `vibhāga` declaration
+    /// are public.
+    fn check_module(
+        &mut self,
+        ctx: &ModuleContext,
+        checker: &mut VisibilityChecker,
+        parent: ModuleId,
+        name: &str,
+        span: Span,
+    ) -> bool {
+        let parent_module = &ctx.graph.modules[&parent];
+        match parent_module.exports.lookup_global(name) {
+            Some(symbol) if symbol.kind == SymbolKind::Module => {
+                let scope =
+                    VisibilityScope::new(self.crate_name.clone(), parent_module.path.clone());
+                checker.check_access(name, symbol.visibility, &scope, span)
+            }
+            _ => true,
+        }
+    }
+}
+
+fn symbol_kind<'a>(ctx: &'a ModuleContext, item: &ScopedItem) -> Option<&'a SymbolKind> {
+    ctx.graph
+        .modules
+        .get(&item.module)
+        .and_then(|m| m.exports.lookup_global(&item.name))
+        .map(|symbol| &symbol.kind)
+}
+
+fn module_display(ctx: &ModuleContext, id: ModuleId, crate_name: &str) -> String {
+    match ctx.graph.modules.get(&id) {
+        Some(module) if !module.path.is_empty() => module.path.join("::"),
+        _ => crate_name.to_string(),
+    }
+}
+
+/// Collects `a::b` identifiers and type paths with their spans, and the
+/// bare names of types and traits
+struct PathCollector {
+    paths: Vec<(String, Span)>,
+    types: Vec<(String, Span)>,
+}
+
+impl PathCollector {
+    fn name(&mut self, name: &Identifier) {
+        if name.name.contains("::") {
+            self.paths.push((name.name.clone(), name.span));
+        } else {
+            self.types.push((name.name.clone(), name.span));
+        }
+    }
+}
+
+impl AstVisitor for PathCollector {
+    type Break = ();
+
+    // Inline module bodies were loaded as modules of their own
+    fn visit_module(&mut self, _module: &ModuleDef) -> VisitResult<Self::Break> {
+        self.continue_()
+    }
+
+    fn visit_parameter(&mut self, param: &Parameter) -> VisitResult<Self::Break> {
+        self.visit_type(&param.ty)
+    }
+
+    fn visit_impl(&mut self, impl_def: &ImplDef) -> VisitResult<Self::Break> {
+        if let Some(trait_ref) = &impl_def.trait_ref {
+            self.name(&trait_ref.trait_name);
+        }
+        self.visit_type(&impl_def.self_ty)?;
+        for method in &impl_def.methods {
+            self.visit_function(method)?;
+        }
+        self.continue_()
+    }
+
+    fn visit_type(&mut self, ty: &Type) -> VisitResult<Self::Break> {
+        match ty {
+            Type::Named { name, generics, .. } => {
+                self.name(name);
+                for generic in generics {
+                    self.visit_type(generic)?;
+                }
+            }
+            Type::Function {
+                params,
+                return_type,
+            } => {
+                for param in params {
+                    self.visit_type(param)?;
+                }
+                self.visit_type(return_type)?;
+            }
+            Type::Array { element, .. } => self.visit_type(element)?,
+            Type::Tuple(elements) => {
+                for element in elements {
+                    self.visit_type(element)?;
+                }
+            }
+            Type::Reference { inner, .. } => self.visit_type(inner)?,
+            Type::Const(_) | Type::Inferred => {}
+        }
+        self.continue_()
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) -> VisitResult<Self::Break> {
+        if let Expr::Identifier(ident) = expr {
+            if ident.name.contains("::") {
+                self.paths.push((ident.name.clone(), ident.span));
+            }
+        }
+        walk_expr(self, expr)
+    }
+}
+
+/// Rename the type paths in `ty` to the crate-wide names of their types
+fn rename_type(ty: &mut Type, types: &HashMap<String, String>) {
+    match ty {
+        Type::Named { name, generics, .. } => {
+            if let Some(linked) = types.get(&name.name) {
+                name.name = linked.clone();
+            }
+            for generic in generics {
+                rename_type(generic, types);
+            }
+        }
+        Type::Function {
+            params,
+            return_type,
+        } => {
+            for param in params {
+                rename_type(param, types);
+            }
+            rename_type(return_type, types);
+        }
+        Type::Array { element, .. } => rename_type(element, types),
+        Type::Tuple(elements) => {
+            for element in elements {
+                rename_type(element, types);
+            }
+        }
+        Type::Reference { inner, .. } => rename_type(inner, types),
+        Type::Const(_) | Type::Inferred => {}
+    }
+}
+
+/// Rewrites call targets and constants to their link-time names, and
+/// type paths to the names of their types
+struct CallRenamer {
+    names: HashMap<String, String>,
+    constants: HashMap<String, String>,
+    types: HashMap<String, String>,
+}
+
+impl AstVisitorMut for CallRenamer {
+    type Break = ();
+
+    fn visit_function_mut(&mut self, func: &mut FunctionDef) -> VisitResult<Self::Break> {
+        for param in &mut func.params {
+            rename_type(&mut param.ty, &self.types);
+        }
+        if let Some(ret) = &mut func.return_type {
+            rename_type(ret, &self.types);
+        }
+        self.visit_block_mut(&mut func.body)
+    }
+
+    fn visit_typedef_mut(&mut self, typedef: &mut TypeDef) -> VisitResult<Self::Break> {
+        match &mut typedef.body {
+            TypeBody::Struct(fields) => {
+                for field in fields {
+                    rename_type(&mut field.ty, &self.types);
+                }
+            }
+            TypeBody::Enum(variants) => {
+                for field in variants
+                    .iter_mut()
+                    .flat_map(|v| v.fields.iter_mut().flatten())
+                {
+                    rename_type(&mut field.ty, &self.types);
+                }
+            }
+            TypeBody::Alias(ty) => rename_type(ty, &self.types),
+        }
+        self.continue_()
+    }
+
+    fn visit_impl_mut(&mut self, impl_def: &mut ImplDef) -> VisitResult<Self::Break> {
+        if let Some(trait_ref) = &mut impl_def.trait_ref {
+            if let Some(linked) = self.types.get(&trait_ref.trait_name.name) {
+                trait_ref.trait_name.name = linked.clone();
+            }
+        }
+        rename_type(&mut impl_def.self_ty, &self.types);
+        for method in &mut impl_def.methods {
+            self.visit_function_mut(method)?;
+        }
+        self.continue_()
+    }
+
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) -> VisitResult<Self::Break> {
+        if let Stmt::Let { ty: Some(ty), .. } = stmt {
+            rename_type(ty, &self.types);
+        }
+        walk_stmt_mut(self, stmt)
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> VisitResult<Self::Break> {
+        match expr {
+            Expr::Call { callee, .. } => {
+                if let Expr::Identifier(ident) = callee.as_mut() {
+                    if let Some(linked) = self.names.get(&ident.name) {
+                        ident.name = linked.clone();
+                    }
+                }
+            }
+            Expr::Identifier(ident) => {
+                if let Some(linked) = self.constants.get(&ident.name) {
+                    ident.name = linked.clone();
+                }
+            }
+            _ => {}
+        }
+        walk_expr_mut(self, expr)
+    }
+}
@@ -27,7 +27,7 @@ pub enum Visibility {
     Crate,
     /// mitra-gata - Visible to specified modules (friend access)
     Restricted,
-    /// gupya - Private to current module (hidden)
+    /// gupya - Private to current module and its children (hidden)
     Private,
 }
 
@@ -70,7 +70,13 @@ impl Visibility {
                         .allowed_modules
                         .contains(&accessor_scope.module_path)
             }
-            Visibility::Private => accessor_scope.module_path == target_scope.module_path,
+            // Child modules see the private items of their ancestors
+            Visibility::Private => {
+                accessor_scope.crate_name == target_scope.crate_name
+                    && accessor_scope
+                        .module_path
+                        .starts_with(&target_scope.module_path)
+            }
         }
     }
 
@@ -346,6 +352,7 @@ mod tests {
 
         assert!(vis.allows_access(&scope_same, &scope));
         assert!(!vis.allows_access(&scope_diff, &scope));
+        assert!(vis.allows_access(&scope.child("inner"), &scope));
     }
 
     #[test]
@@ -3,6 +3,8 @@
 //! Defines the complete AST structure for Jagannath programs.
 
 use crate::lexer::{AffixSequence, Span};
+use crate::modules::Visibility;
+use crate::parser::format::FormatSpec;
 
 /// Complete AST for a Jagannath source file
 #[derive(Debug, Clone)]
@@ -26,6 +28,13 @@ pub enum Item {
     Constant(ConstantDef),
     /// Module definition
     Module(ModuleDef),
+    /// Trait definition (guṇa)
+    Trait(TraitDef),
+    /// Implementation block (kriyānvaya)
+    Impl(ImplDef),
+    /// Placeholder for an item that failed to parse, covering the skipped
+    /// tokens; only produced by [`Parser::parse_recovering`](super::Parser::parse_recovering)
+    Error(Span),
 }
 
 /// Function definition
@@ -35,22 +44,54 @@ pub struct FunctionDef {
     pub name: Identifier,
     /// Generic parameters
     pub generics: Vec<GenericParam>,
+    /// Method receiver (sva), if this function belongs to an impl or trait
+    pub receiver: Option<SelfParam>,
     /// Parameters with kāraka roles
     pub params: Vec<Parameter>,
     /// Return type
     pub return_type: Option<Type>,
-    /// Preconditions (yatra clauses)
-    pub preconditions: Vec<Expr>,
-    /// Postconditions
-    pub postconditions: Vec<Expr>,
+    /// Where-clause predicates (yatra T: Bound)
+    pub where_clauses: Vec<WherePredicate>,
+    /// Preconditions (`yatra pūrva cond`), checked on entry
+    pub preconditions: Vec<Contract>,
+    /// Postconditions (`yatra paścāt cond`), checked on return, where
+    /// `phala` names the result
+    pub postconditions: Vec<Contract>,
     /// Function body
     pub body: Block,
+    /// Declared `sthira kāryakrama`, so callable at compile time
+    pub is_const: bool,
+    /// Visibility (sārvajanika/pub or private)
+    pub visibility: Visibility,
+    /// Outer attributes (`#[parīkṣā]`, `#[yama(ahiṃsā)]`)
+    pub attributes: Vec<Attribute>,
     /// Source span
     pub span: Span,
 }
 
+/// Contract clause of a function: a condition that must hold on entry or
+/// on return
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contract {
+    pub condition: Expr,
+    /// The condition as written, for the message when it does not hold
+    pub text: String,
+    pub span: Span,
+}
+
+/// Method receiver (sva)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfParam {
+    /// sva - takes ownership
+    Value,
+    /// &sva - shared borrow
+    Ref,
+    /// &mut sva - mutable borrow
+    RefMut,
+}
+
 /// Parameter with kāraka annotation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Parameter {
     /// Parameter name
     pub name: Identifier,
@@ -58,6 +99,8 @@ pub struct Parameter {
     pub ty: Type,
     /// Kāraka role annotation
     pub karaka: Option<Karaka>,
+    /// Outer attributes
+    pub attributes: Vec<Attribute>,
     /// Source span
     pub span: Span,
 }
@@ -88,6 +131,10 @@ pub struct TypeDef {
     pub generics: Vec<GenericParam>,
     /// Type body
     pub body: TypeBody,
+    /// Visibility (sārvajanika/pub or private)
+    pub visibility: Visibility,
+    /// Outer attributes
+    pub attributes: Vec<Attribute>,
     /// Source span
     pub span: Span,
 }
@@ -108,6 +155,8 @@ pub enum TypeBody {
 pub struct Field {
     pub name: Identifier,
     pub ty: Type,
+    pub visibility: Visibility,
+    pub attributes: Vec<Attribute>,
     pub span: Span,
 }
 
@@ -124,6 +173,9 @@ pub struct Variant {
 pub struct GenericParam {
     pub name: Identifier,
     pub bounds: Vec<TypeBound>,
+    /// Type of a const parameter (`sthira N: saṅkhyā`); `None` for a type
+    /// parameter
+    pub ty: Option<Type>,
     pub span: Span,
 }
 
@@ -131,6 +183,84 @@ pub struct GenericParam {
 #[derive(Debug, Clone)]
 pub struct TypeBound {
     pub trait_name: Identifier,
+    /// Generic arguments of the bound trait (e.g. `Prāpta<sūtra>`)
+    pub generics: Vec<Type>,
+    pub span: Span,
+}
+
+/// Where-clause predicate: `yatra T: A + B`
+#[derive(Debug, Clone)]
+pub struct WherePredicate {
+    pub ty: Type,
+    pub bounds: Vec<TypeBound>,
+    pub span: Span,
+}
+
+/// Trait definition (guṇa)
+#[derive(Debug, Clone)]
+pub struct TraitDef {
+    pub name: Identifier,
+    pub generics: Vec<GenericParam>,
+    /// Supertraits (upādhi)
+    pub supertraits: Vec<TypeBound>,
+    pub where_clauses: Vec<WherePredicate>,
+    /// Associated types (svabhāva)
+    pub associated_types: Vec<AssociatedTypeDef>,
+    /// Method signatures, optionally with default bodies
+    pub methods: Vec<TraitMethodDef>,
+    pub visibility: Visibility,
+    pub attributes: Vec<Attribute>,
+    pub span: Span,
+}
+
+/// Associated type declared in a trait: `prakāra Item: Bound = Default;`
+#[derive(Debug, Clone)]
+pub struct AssociatedTypeDef {
+    pub name: Identifier,
+    pub bounds: Vec<TypeBound>,
+    pub default: Option<Type>,
+    pub span: Span,
+}
+
+/// Method declared in a trait
+#[derive(Debug, Clone)]
+pub struct TraitMethodDef {
+    pub name: Identifier,
+    pub generics: Vec<GenericParam>,
+    pub receiver: Option<SelfParam>,
+    pub params: Vec<Parameter>,
+    pub return_type: Option<Type>,
+    pub where_clauses: Vec<WherePredicate>,
+    /// Default implementation
+    pub default_body: Option<Block>,
+    pub attributes: Vec<Attribute>,
+    pub span: Span,
+}
+
+/// Implementation block (kriyānvaya)
+///
+/// Either inherent (`kriyānvaya Bindu { ... }`) or a trait impl
+/// (`kriyānvaya Darśanīya for Bindu { ... }`).
+#[derive(Debug, Clone)]
+pub struct ImplDef {
+    pub generics: Vec<GenericParam>,
+    /// Implemented trait (None for inherent impls)
+    pub trait_ref: Option<TypeBound>,
+    /// Implementing type
+    pub self_ty: Type,
+    pub where_clauses: Vec<WherePredicate>,
+    /// Associated type bindings: `prakāra Item = sūtra;`
+    pub associated_types: Vec<ImplAssociatedTypeDef>,
+    pub methods: Vec<FunctionDef>,
+    pub attributes: Vec<Attribute>,
+    pub span: Span,
+}
+
+/// Associated type binding inside an impl block
+#[derive(Debug, Clone)]
+pub struct ImplAssociatedTypeDef {
+    pub name: Identifier,
+    pub ty: Type,
     pub span: Span,
 }
 
@@ -139,15 +269,24 @@ pub struct TypeBound {
 pub struct ImportStmt {
     pub path: Vec<Identifier>,
     pub alias: Option<Identifier>,
+    /// `upayoga path::*`
+    pub glob: bool,
+    /// `pub upayoga` re-exports the imported names
+    pub visibility: Visibility,
+    pub attributes: Vec<Attribute>,
     pub span: Span,
 }
 
-/// Constant definition
+/// Constant definition: `sthira NAMA: T = value;` or `static NAMA: T = value;`
 #[derive(Debug, Clone)]
 pub struct ConstantDef {
     pub name: Identifier,
     pub ty: Option<Type>,
     pub value: Expr,
+    /// Declared with `static` rather than `sthira`
+    pub is_static: bool,
+    pub visibility: Visibility,
+    pub attributes: Vec<Attribute>,
     pub span: Span,
 }
 
@@ -156,9 +295,33 @@ pub struct ConstantDef {
 pub struct ModuleDef {
     pub name: Identifier,
     pub items: Vec<Item>,
+    /// False for `vibhāga name;`, whose items live in a sibling file
+    pub inline: bool,
+    pub visibility: Visibility,
+    pub attributes: Vec<Attribute>,
     pub span: Span,
 }
 
+/// Outer attribute: `#[name]`, `#[name(args)]` or `#[name = value]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub meta: MetaItem,
+    pub span: Span,
+}
+
+/// Contents of an attribute, nested for list arguments
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetaItem {
+    /// `parīkṣā`
+    Word(Identifier),
+    /// `sūcanā = "..."`
+    NameValue(Identifier, Literal),
+    /// `yama(ahiṃsā, satya)`
+    List(Identifier, Vec<MetaItem>),
+    /// Bare literal argument: `apracalita("...")`
+    Literal(Literal),
+}
+
 /// Type representation
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
@@ -173,13 +336,20 @@ pub enum Type {
         params: Vec<Type>,
         return_type: Box<Type>,
     },
-    /// Array type
+    /// Array type; a slice has neither `size` nor `length`
     Array {
         element: Box<Type>,
+        /// Number of elements of a fixed array, once known
         size: Option<usize>,
+        /// Length written as an expression, which const evaluation
+        /// reduces to `size`
+        length: Option<Box<Expr>>,
     },
     /// Tuple type
     Tuple(Vec<Type>),
+    /// Value given for a const parameter (`Sīmita<0, { śata() }>`), which
+    /// const evaluation reduces to a literal
+    Const(Box<Expr>),
     /// Reference type
     Reference {
         inner: Box<Type>,
@@ -202,55 +372,64 @@ pub struct Identifier {
 }
 
 /// Statement block
-#[derive(Debug, Clone)]
+///
+/// The value of a block is that of its last statement, when that is an
+/// expression, a `yad` with an `anyathā` branch or a `cala` loop; see
+/// [`Block::tail`].
+#[derive(Debug, Clone, PartialEq)]
 pub struct Block {
     pub stmts: Vec<Stmt>,
     pub span: Span,
 }
 
 /// Statement variants
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Stmt {
     /// Let binding
     Let {
         name: Identifier,
         ty: Option<Type>,
         value: Option<Expr>,
+        attributes: Vec<Attribute>,
         span: Span,
     },
     /// Expression statement
     Expr(Expr),
     /// Return statement (phera)
     Return { value: Option<Expr>, span: Span },
-    /// If statement (yad)
+    /// If statement (yad); `anyathā yad` chains nest in `else_block`
     If {
         condition: Expr,
         then_block: Block,
         else_block: Option<Block>,
         span: Span,
     },
-    /// Match expression (yad with patterns)
-    Match {
-        scrutinee: Expr,
-        arms: Vec<MatchArm>,
-        span: Span,
-    },
-    /// Loop statement (cala)
+    /// Loop statement (cala), optionally labeled (`'bāhya: cala ...`)
     Loop {
+        label: Option<Identifier>,
         kind: LoopKind,
         body: Block,
         span: Span,
     },
-    /// Break statement (stha)
-    Break { span: Span },
-    /// Continue statement
-    Continue { span: Span },
+    /// Break statement, leaving the innermost loop or the labeled one;
+    /// a `cala { }` loop takes the value it breaks with
+    Break {
+        label: Option<Identifier>,
+        value: Option<Expr>,
+        span: Span,
+    },
+    /// Continue statement, for the innermost loop or the labeled one
+    Continue {
+        label: Option<Identifier>,
+        span: Span,
+    },
 }
 
-/// Match arm
-#[derive(Debug, Clone)]
+/// Match arm: `pattern yad guard => body`
+#[derive(Debug, Clone, PartialEq)]
 pub struct MatchArm {
     pub pattern: Pattern,
+    /// `yad` condition tested after the pattern binds
     pub guard: Option<Expr>,
     pub body: Expr,
     pub span: Span,
@@ -265,7 +444,7 @@ pub struct MatchArm {
 /// - Or patterns (|) for alternatives
 /// - Guard patterns (if condition)
 /// - Range patterns (0..10)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Pattern {
     /// Wildcard pattern (_) - matches anything, ignores value
     Wildcard,
@@ -345,7 +524,7 @@ pub enum Pattern {
 }
 
 /// Variant pattern fields
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum VariantFields {
     /// No fields: None
     Unit,
@@ -356,7 +535,7 @@ pub enum VariantFields {
 }
 
 /// Loop variants
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum LoopKind {
     /// For-in loop (cala x : iterable)
     ForIn { binding: Identifier, iterable: Expr },
@@ -374,7 +553,7 @@ pub enum LoopKind {
 }
 
 /// Expression variants
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     /// Literal value
     Literal(Literal),
@@ -418,6 +597,13 @@ pub enum Expr {
         index: Box<Expr>,
         span: Span,
     },
+    /// Range (`a..b`, `a..=b`, `..b`, `a..`): a value, or as an index a slice
+    Range {
+        start: Option<Box<Expr>>,
+        end: Option<Box<Expr>>,
+        inclusive: bool,
+        span: Span,
+    },
     /// Struct construction
     StructConstruct {
         name: Identifier,
@@ -436,27 +622,68 @@ pub enum Expr {
     },
     /// Block expression
     Block(Block),
-    /// If expression
+    /// If expression (`yad` used as a value)
     If {
         condition: Box<Expr>,
         then_expr: Box<Expr>,
         else_expr: Option<Box<Expr>>,
         span: Span,
     },
+    /// Loop expression (`cala` used as a value); its value is the one a
+    /// `break` leaves it with
+    Loop {
+        label: Option<Identifier>,
+        kind: Box<LoopKind>,
+        body: Block,
+        span: Span,
+    },
+    /// Match expression (pratyabhijñā); its value is that of the chosen arm
+    Match {
+        scrutinee: Box<Expr>,
+        arms: Vec<MatchArm>,
+        span: Span,
+    },
     /// Try operator (?)
     Try { expr: Box<Expr>, span: Span },
     /// Await expression
     Await { expr: Box<Expr>, span: Span },
+    /// Concurrent task (saha): `saha { .. }` runs a block alongside the code
+    /// after it, sharing the variables it uses with that code; `saha f(..)`
+    /// runs a call, its arguments moved to the new task. Its value is `()`.
+    Saha { task: Box<Expr>, span: Span },
     /// Type cast
     Cast {
         expr: Box<Expr>,
         ty: Type,
         span: Span,
     },
+    /// Interpolated string from `vinyāsa!`/`mudraṇa!`; its value is a sūtra
+    ///
+    /// Each argument is evaluated once, in order, however many placeholders
+    /// refer to it; names captured from the template come after the
+    /// explicit arguments.
+    Format {
+        pieces: Vec<FormatPiece>,
+        args: Vec<Expr>,
+        span: Span,
+    },
+    /// Placeholder for an expression that failed to parse
+    Error(Span),
+}
+
+/// Piece of an interpolated string
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatPiece {
+    Text(String),
+    /// Argument `index` rendered according to its format specification
+    Arg {
+        index: usize,
+        spec: FormatSpec,
+    },
 }
 
 /// Literal values
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
     Int(i64),
     Float(f64),
@@ -499,8 +726,9 @@ pub enum BinaryOp {
 pub enum UnaryOp {
     Neg,   // -
     Not,   // !
-    Ref,   // &
-    Deref, // *
+    Ref,    // &
+    RefMut, // &mut
+    Deref,  // *
 }
 
 /// AST node trait for common operations
@@ -534,6 +762,49 @@ impl Ast {
     }
 }
 
+impl Item {
+    /// Outer attributes of the item
+    pub fn attributes(&self) -> &[Attribute] {
+        match self {
+            Item::Function(f) => &f.attributes,
+            Item::TypeDef(t) => &t.attributes,
+            Item::Import(i) => &i.attributes,
+            Item::Constant(c) => &c.attributes,
+            Item::Module(m) => &m.attributes,
+            Item::Trait(t) => &t.attributes,
+            Item::Impl(i) => &i.attributes,
+            Item::Error(_) => &[],
+        }
+    }
+}
+
+impl Attribute {
+    /// Name of the attribute (`yama` for `#[yama(ahiṃsā)]`)
+    pub fn name(&self) -> &str {
+        self.meta.name().unwrap_or("")
+    }
+
+    /// Arguments of a list attribute, empty otherwise
+    pub fn args(&self) -> &[MetaItem] {
+        match &self.meta {
+            MetaItem::List(_, args) => args,
+            _ => &[],
+        }
+    }
+}
+
+impl MetaItem {
+    /// Name of a word, `name = value` or `name(...)`; None for literals
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            MetaItem::Word(name) | MetaItem::NameValue(name, _) | MetaItem::List(name, _) => {
+                Some(&name.name)
+            }
+            MetaItem::Literal(_) => None,
+        }
+    }
+}
+
 impl FunctionDef {
     /// Get allocations in function (placeholder)
     pub fn allocations(&self) -> Vec<AllocationInfo> {
@@ -566,15 +837,35 @@ impl Expr {
             Expr::MethodCall { span, .. } => *span,
             Expr::FieldAccess { span, .. } => *span,
             Expr::Index { span, .. } => *span,
+            Expr::Range { span, .. } => *span,
             Expr::StructConstruct { span, .. } => *span,
             Expr::Array { span, .. } => *span,
             Expr::Tuple { span, .. } => *span,
             Expr::Lambda { span, .. } => *span,
             Expr::Block(block) => block.span,
             Expr::If { span, .. } => *span,
+            Expr::Loop { span, .. } => *span,
+            Expr::Match { span, .. } => *span,
             Expr::Try { span, .. } => *span,
             Expr::Await { span, .. } => *span,
+            Expr::Saha { span, .. } => *span,
             Expr::Cast { span, .. } => *span,
+            Expr::Format { span, .. } => *span,
+            Expr::Error(span) => *span,
+        }
+    }
+}
+
+impl Block {
+    /// Last statement, if it gives the block its value
+    pub fn tail(&self) -> Option<&Stmt> {
+        match self.stmts.last()? {
+            stmt @ (Stmt::Expr(_) | Stmt::Loop { .. }) => Some(stmt),
+            stmt @ Stmt::If {
+                else_block: Some(_),
+                ..
+            } => Some(stmt),
+            _ => None,
         }
     }
 }
@@ -584,17 +875,12 @@ impl Stmt {
     pub fn span(&self) -> Span {
         match self {
             Stmt::Let { span, .. } => *span,
-            Stmt::Expr(e) => match e {
-                Expr::Binary { span, .. } => *span,
-                Expr::Call { span, .. } => *span,
-                _ => Span::dummy(),
-            },
+            Stmt::Expr(e) => e.span(),
             Stmt::Return { span, .. } => *span,
             Stmt::If { span, .. } => *span,
-            Stmt::Match { span, .. } => *span,
             Stmt::Loop { span, .. } => *span,
-            Stmt::Break { span } => *span,
-            Stmt::Continue { span } => *span,
+            Stmt::Break { span, .. } => *span,
+            Stmt::Continue { span, .. } => *span,
         }
     }
 
@@ -605,6 +891,27 @@ impl Stmt {
     }
 }
 
+impl Type {
+    /// Value of a const argument once reduced to an integer literal
+    pub fn const_value(&self) -> Option<i64> {
+        match self {
+            Type::Const(value) => match &**value {
+                Expr::Literal(Literal::Int(n)) => Some(*n),
+                Expr::Unary {
+                    op: UnaryOp::Neg,
+                    operand,
+                    ..
+                } => match &**operand {
+                    Expr::Literal(Literal::Int(n)) => n.checked_neg(),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
 /// Function call information for analysis
 #[derive(Debug, Clone)]
 pub struct FunctionCallInfo {
@@ -0,0 +1,291 @@
+//! CST Construction
+//!
+//! The recursive-descent [`Parser`](crate::parser::Parser) records where
+//! nodes begin and end while it builds the AST. A node is only recorded
+//! once it parsed successfully, by wrapping everything consumed since a
+//! [`Checkpoint`], so events are balanced by construction and a failed
+//! production just leaves its tokens to the enclosing node.
+//!
+//! The lexer drops whitespace and comments; [`build`] recovers them from
+//! the gaps between token spans, which is what makes the tree lossless.
+
+use std::cmp::Reverse;
+use std::sync::Arc;
+
+use super::green::{GreenElement, GreenNode, GreenToken};
+use super::SyntaxKind;
+use crate::lexer::{Token, TokenKind};
+
+/// Position to wrap a node from, taken before parsing it
+#[derive(Debug, Clone, Copy)]
+pub struct Checkpoint {
+    /// Number of nodes finished when the checkpoint was taken
+    anchor: usize,
+    /// Token position
+    token: usize,
+}
+
+/// A node opened at a checkpoint
+#[derive(Debug)]
+struct Start {
+    kind: SyntaxKind,
+    anchor: usize,
+    token: usize,
+    /// Wrap order; of two nodes opening at the same point, the later one
+    /// is the outer one
+    seq: usize,
+}
+
+/// Node boundaries recorded while parsing
+#[derive(Debug, Default)]
+pub struct Events {
+    starts: Vec<Start>,
+    /// Token position at which each node was finished, in finishing order
+    finishes: Vec<usize>,
+}
+
+impl Events {
+    pub fn checkpoint(&self, token: usize) -> Checkpoint {
+        Checkpoint {
+            anchor: self.finishes.len(),
+            token,
+        }
+    }
+
+    /// Make everything since `checkpoint` a node ending at token `end`
+    pub fn wrap(&mut self, checkpoint: Checkpoint, kind: SyntaxKind, end: usize) {
+        self.starts.push(Start {
+            kind,
+            anchor: checkpoint.anchor,
+            token: checkpoint.token,
+            seq: self.starts.len(),
+        });
+        self.finishes.push(end);
+    }
+}
+
+/// Build the green tree for `source` from its tokens and parse events
+pub fn build(source: &str, tokens: &[Token], events: Events) -> GreenNode {
+    let Events {
+        mut starts,
+        finishes,
+    } = events;
+    starts.sort_by_key(|start| (start.anchor, start.token, Reverse(start.seq)));
+
+    let mut builder = TreeBuilder {
+        source,
+        tokens,
+        next_token: 0,
+        offset: 0,
+        stack: vec![(SyntaxKind::SourceFile, Vec::new())],
+    };
+    let mut starts = starts.into_iter().peekable();
+    for (anchor, end) in finishes.into_iter().enumerate() {
+        while let Some(start) = starts.next_if(|start| start.anchor == anchor) {
+            builder.start_node(start.kind, start.token);
+        }
+        builder.finish_node(end);
+    }
+    for start in starts {
+        // Unreachable for balanced events, but never lose text
+        builder.start_node(start.kind, start.token);
+        builder.finish_node(start.token);
+    }
+    builder.finish()
+}
+
+struct TreeBuilder<'a> {
+    source: &'a str,
+    tokens: &'a [Token],
+    /// First token not yet in the tree
+    next_token: usize,
+    /// First byte not yet in the tree
+    offset: usize,
+    /// Open nodes with the children collected so far
+    stack: Vec<(SyntaxKind, Vec<GreenElement>)>,
+}
+
+impl TreeBuilder<'_> {
+    fn start_node(&mut self, kind: SyntaxKind, token: usize) {
+        self.flush_tokens(token);
+        let gap_end = self.gap_end();
+        let trivia = lex_trivia(&self.source[self.offset..gap_end]);
+        let attached = if kind.is_item() {
+            attached_trivia(&trivia)
+        } else {
+            0
+        };
+        let (outer, inner) = trivia.split_at(trivia.len() - attached);
+        for &(trivia_kind, text) in outer {
+            self.push_token(GreenToken::new(trivia_kind, text, None));
+        }
+        self.stack.push((kind, Vec::new()));
+        for &(trivia_kind, text) in inner {
+            self.push_token(GreenToken::new(trivia_kind, text, None));
+        }
+        self.offset = gap_end;
+    }
+
+    fn finish_node(&mut self, end: usize) {
+        self.flush_tokens(end);
+        if self.stack.len() > 1 {
+            let (kind, children) = self.stack.pop().expect("open node");
+            self.push(GreenElement::Node(Arc::new(GreenNode::new(kind, children))));
+        }
+    }
+
+    fn finish(mut self) -> GreenNode {
+        self.flush_tokens(self.tokens.len());
+        if self.offset < self.source.len() {
+            let rest = &self.source[self.offset..];
+            for (kind, text) in lex_trivia(rest) {
+                self.push_token(GreenToken::new(kind, text, None));
+            }
+            self.offset = self.source.len();
+        }
+        while self.stack.len() > 1 {
+            self.finish_node(self.tokens.len());
+        }
+        let (kind, children) = self.stack.pop().expect("root node");
+        GreenNode::new(kind, children)
+    }
+
+    /// Add every token before position `end`, with the trivia in front of it
+    fn flush_tokens(&mut self, end: usize) {
+        while self.next_token < end.min(self.tokens.len()) {
+            let token = &self.tokens[self.next_token];
+            self.next_token += 1;
+            let start = token.span.start.clamp(self.offset, self.source.len());
+            for (kind, text) in lex_trivia(&self.source[self.offset..start]) {
+                self.push_token(GreenToken::new(kind, text, None));
+            }
+            self.offset = start;
+            let token_end = token.span.end.clamp(start, self.source.len());
+            if token_end > start && token.kind != TokenKind::Eof {
+                let text = &self.source[start..token_end];
+                let kind = SyntaxKind::from_token(&token.kind);
+                self.push_token(GreenToken::new(kind, text, Some(token.kind.clone())));
+                self.offset = token_end;
+            }
+        }
+    }
+
+    /// Where the trivia in front of the next token ends
+    fn gap_end(&self) -> usize {
+        self.tokens
+            .get(self.next_token)
+            .map_or(self.source.len(), |token| token.span.start)
+            .clamp(self.offset, self.source.len())
+    }
+
+    fn push_token(&mut self, token: GreenToken) {
+        self.push(GreenElement::Token(Arc::new(token)));
+    }
+
+    fn push(&mut self, element: GreenElement) {
+        self.stack.last_mut().expect("open node").1.push(element);
+    }
+}
+
+/// How many trailing trivia pieces belong to the item that follows them
+///
+/// Comments directly above an item are its documentation: walk back over
+/// comments and single line breaks, stopping at a blank line or at a `//!`
+/// comment, which documents the enclosing module instead.
+fn attached_trivia(trivia: &[(SyntaxKind, &str)]) -> usize {
+    let mut attached = 0;
+    for (index, &(kind, text)) in trivia.iter().enumerate().rev() {
+        match kind {
+            SyntaxKind::Comment if !text.starts_with("//!") => attached = trivia.len() - index,
+            SyntaxKind::Whitespace if text.matches('\n').count() < 2 => {}
+            _ => break,
+        }
+    }
+    attached
+}
+
+/// Split the text between two tokens into whitespace and comments
+///
+/// Mirrors what the lexer skips: `//` and `#` line comments and nested
+/// `/* */` block comments.
+pub(crate) fn lex_trivia(text: &str) -> Vec<(SyntaxKind, &str)> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let len = if rest.starts_with(char::is_whitespace) {
+            rest.find(|c: char| !c.is_whitespace())
+                .unwrap_or(rest.len())
+        } else if rest.starts_with("/*") {
+            block_comment_len(rest)
+        } else {
+            // `//` and `#` comments, or anything else up to the line end
+            rest.find('\n').unwrap_or(rest.len())
+        };
+        let kind = if rest.starts_with(char::is_whitespace) {
+            SyntaxKind::Whitespace
+        } else {
+            SyntaxKind::Comment
+        };
+        pieces.push((kind, &rest[..len]));
+        rest = &rest[len..];
+    }
+    pieces
+}
+
+/// Length of a possibly nested `/* ... */` comment at the start of `text`
+fn block_comment_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"/*") {
+            depth += 1;
+            i += 2;
+        } else if bytes[i..].starts_with(b"*/") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lex_trivia() {
+        let pieces = lex_trivia("  // a\n/* b /* c */ */\t# d");
+        let kinds: Vec<_> = pieces.iter().map(|(kind, _)| *kind).collect();
+        let texts: Vec<_> = pieces.iter().map(|(_, text)| *text).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SyntaxKind::Whitespace,
+                SyntaxKind::Comment,
+                SyntaxKind::Whitespace,
+                SyntaxKind::Comment,
+                SyntaxKind::Whitespace,
+                SyntaxKind::Comment,
+            ]
+        );
+        assert_eq!(
+            texts,
+            vec!["  ", "// a", "\n", "/* b /* c */ */", "\t", "# d"]
+        );
+    }
+
+    #[test]
+    fn test_attached_trivia_stops_at_blank_line() {
+        let trivia = lex_trivia("// licence\n\n/// docs\n/// more\n");
+        // "/// docs", "\n", "/// more", "\n"
+        assert_eq!(attached_trivia(&trivia), 4);
+        let trivia = lex_trivia("//! module docs\n");
+        assert_eq!(attached_trivia(&trivia), 0);
+    }
+}
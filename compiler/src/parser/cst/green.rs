@@ -0,0 +1,129 @@
+//! Green Tree - immutable, position-independent syntax
+//!
+//! Green nodes know their kind, their children and their text length, but
+//! not where they sit in the file or who their parent is. That keeps them
+//! cheap to share between trees; [`super::SyntaxNode`] adds the positions.
+
+use std::fmt;
+use std::sync::Arc;
+
+use super::SyntaxKind;
+use crate::lexer::TokenKind;
+
+/// Leaf of the green tree: a token or a piece of trivia
+#[derive(Debug, Clone, PartialEq)]
+pub struct GreenToken {
+    kind: SyntaxKind,
+    text: String,
+    /// Lexer kind; `None` for trivia
+    token: Option<TokenKind>,
+}
+
+impl GreenToken {
+    /// Create a token with its exact source text
+    pub fn new(kind: SyntaxKind, text: impl Into<String>, token: Option<TokenKind>) -> Self {
+        Self {
+            kind,
+            text: text.into(),
+            token,
+        }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Kind the lexer gave this token, if it is not trivia
+    pub fn token_kind(&self) -> Option<&TokenKind> {
+        self.token.as_ref()
+    }
+
+    pub fn text_len(&self) -> usize {
+        self.text.len()
+    }
+}
+
+/// Child of a green node
+#[derive(Debug, Clone, PartialEq)]
+pub enum GreenElement {
+    Node(Arc<GreenNode>),
+    Token(Arc<GreenToken>),
+}
+
+impl GreenElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            GreenElement::Node(node) => node.kind(),
+            GreenElement::Token(token) => token.kind(),
+        }
+    }
+
+    pub fn text_len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.text_len(),
+            GreenElement::Token(token) => token.text_len(),
+        }
+    }
+}
+
+/// Interior node of the green tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct GreenNode {
+    kind: SyntaxKind,
+    text_len: usize,
+    children: Vec<GreenElement>,
+}
+
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> Self {
+        let text_len = children.iter().map(GreenElement::text_len).sum();
+        Self {
+            kind,
+            text_len,
+            children,
+        }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn text_len(&self) -> usize {
+        self.text_len
+    }
+
+    pub fn children(&self) -> &[GreenElement] {
+        &self.children
+    }
+
+    /// Copy of this node with child `index` replaced
+    ///
+    /// Siblings are shared with the original, so an edit only rebuilds the
+    /// path from the changed node up to the root.
+    pub fn replace_child(&self, index: usize, child: GreenElement) -> GreenNode {
+        let mut children = self.children.clone();
+        children[index] = child;
+        GreenNode::new(self.kind, children)
+    }
+
+    /// Write the exact source text of this node
+    fn write_text(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        for child in &self.children {
+            match child {
+                GreenElement::Node(node) => node.write_text(out)?,
+                GreenElement::Token(token) => out.write_str(token.text())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for GreenNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_text(f)
+    }
+}
@@ -0,0 +1,402 @@
+//! Syntax Kinds
+//!
+//! One kind per CST node and one per token class. Tokens keep the lexer's
+//! [`TokenKind`] alongside, so the coarse classes here are enough for
+//! highlighting while typed accessors can still tell `(` from `)`.
+
+use crate::lexer::TokenKind;
+
+/// Kind of a node or token in the concrete syntax tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    // ========================================================================
+    // Trivia
+    // ========================================================================
+    /// Spaces, tabs and newlines
+    Whitespace,
+    /// `// ...`, `/* ... */` or `# ...`, including doc comments
+    Comment,
+
+    // ========================================================================
+    // Tokens
+    // ========================================================================
+    /// Identifier (any script)
+    Ident,
+    /// Loop label (`'bāhya`)
+    Label,
+    /// Keyword (`kāryakrama`, `yad`, `saṅkhyā`, ...)
+    Keyword,
+    /// Integer or character literal
+    IntLiteral,
+    /// Float literal
+    FloatLiteral,
+    /// String literal, quotes included
+    StringLiteral,
+    /// `satya` / `asatya`
+    BoolLiteral,
+    /// Operator or delimiter
+    Punct,
+    /// Kāraka marker (`@kartṛ`, ...)
+    KarakaMarker,
+    /// Character the lexer could not make sense of
+    ErrorToken,
+
+    // ========================================================================
+    // Items
+    // ========================================================================
+    /// Root of every tree
+    SourceFile,
+    /// Tokens the parser skipped while recovering from an error
+    Error,
+    /// `#[name(args)]`
+    Attribute,
+    /// `pub`, `pub(saṃkula)`, `pub(uttara)`
+    Visibility,
+    /// Name at a definition site
+    Name,
+    /// `kāryakrama name<T>(params) -> R { ... }`, also trait methods
+    FnDef,
+    /// `(sva, a: T, ...)` or closure `|a, b|`
+    ParamList,
+    /// `a: T`
+    Param,
+    /// `sva`, `&sva`, `&mut sva`
+    SelfParam,
+    /// `-> T`
+    RetType,
+    /// `<T: Bound, U>`
+    GenericParamList,
+    /// `T: Bound`
+    GenericParam,
+    /// `yatra T: A + B, ...`
+    WhereClause,
+    /// `T: A + B`
+    WherePredicate,
+    /// `pūrva cond` or `paścāt cond` in a `yatra` clause
+    Contract,
+    /// `Trait<Args>` in a bound list
+    TypeBound,
+    /// `prakāra Name { ... }`
+    TypeDef,
+    /// `name: T` in a struct or variant payload
+    Field,
+    /// Enum variant
+    Variant,
+    /// `(T, U)` or `{ a: T }` after a variant name
+    VariantFieldList,
+    /// `upayoga a::b;` / `āyāti a::b`
+    Use,
+    /// `vibhāga name;` / `vibhāga name { ... }`
+    Module,
+    /// `sthira NAME: T = value;` / `static ...`
+    ConstDef,
+    /// `guṇa Name { ... }`
+    TraitDef,
+    /// `kriyānvaya ... { ... }`
+    ImplDef,
+    /// `prakāra Name: Bound = T;` inside a trait or impl
+    AssocType,
+
+    // ========================================================================
+    // Statements
+    // ========================================================================
+    /// `{ stmts }`
+    Block,
+    /// `let name: T = value;`
+    LetStmt,
+    /// `phera value;`
+    ReturnStmt,
+    /// `yad cond { ... } anyathā { ... }`
+    IfStmt,
+    /// `'label: cala ...  { ... }`
+    LoopStmt,
+    /// `break 'label value;`
+    BreakStmt,
+    /// `continue 'label;`
+    ContinueStmt,
+    /// Expression followed by an optional `;`
+    ExprStmt,
+
+    // ========================================================================
+    // Expressions
+    // ========================================================================
+    /// Literal value
+    Literal,
+    /// `name`, `a::b`, `sva`
+    PathExpr,
+    /// `a op b`, including assignment
+    BinExpr,
+    /// `-a`, `!a`, `&a`
+    PrefixExpr,
+    /// `value as T`
+    CastExpr,
+    /// `callee(args)`
+    CallExpr,
+    /// `(args)` of a call
+    ArgList,
+    /// `receiver.method(args)`
+    MethodCallExpr,
+    /// `object.field`
+    FieldExpr,
+    /// `object[index]`
+    IndexExpr,
+    /// `expr?`
+    TryExpr,
+    /// `start..end`, a value or a slice bound
+    RangeExpr,
+    /// `(expr)`
+    ParenExpr,
+    /// `[a, b, c]`
+    ArrayExpr,
+    /// `name!(args)`
+    MacroCall,
+    /// `|params| body`
+    ClosureExpr,
+    /// `pratyabhijñā scrutinee { arms }`
+    MatchExpr,
+    /// `pattern yad guard => body,`
+    MatchArm,
+    /// `yad guard` of a match arm
+    MatchGuard,
+    /// `yad` used as a value
+    IfExpr,
+    /// `cala` used as a value
+    LoopExpr,
+    /// `saha { body }` or `saha f(args)`
+    SahaExpr,
+
+    // ========================================================================
+    // Types
+    // ========================================================================
+    /// `Name<Args>-affixes`, `Self::Item`
+    PathType,
+    /// `&T`, `&mut T`
+    RefType,
+    /// `kāryakrama(A, B) -> R`
+    FnType,
+    /// `[T; N]`
+    ArrayType,
+    /// `[T]`
+    SliceType,
+    /// `<A, B>` after a type name
+    TypeArgList,
+    /// `3`, `-3` or `{ expr }` given for a const parameter
+    ConstArg,
+
+    // ========================================================================
+    // Patterns
+    // ========================================================================
+    /// `_`
+    WildcardPat,
+    /// `..` or `..name`
+    RestPat,
+    /// `1`, `-1`, `"s"`, `satya`
+    LiteralPat,
+    /// `a..b`, `a..=b`, `..=b`
+    RangePat,
+    /// `(a, b)`
+    TuplePat,
+    /// `(pattern)`
+    ParenPat,
+    /// `[a, .., b]`
+    SlicePat,
+    /// `&pattern`
+    RefPat,
+    /// `name`, `mut name`, `name @ pattern`
+    IdentPat,
+    /// `a::B` without a payload
+    PathPat,
+    /// `Name(a, b)`
+    TupleStructPat,
+    /// `Name { a, b: pattern, .. }`
+    RecordPat,
+    /// `field: pattern` in a record pattern
+    RecordPatField,
+    /// `a | b`
+    OrPat,
+}
+
+impl SyntaxKind {
+    /// Token class of a lexer token
+    pub fn from_token(kind: &TokenKind) -> SyntaxKind {
+        match kind {
+            TokenKind::Identifier(_) | TokenKind::Dhatu { .. } => SyntaxKind::Ident,
+            TokenKind::Label(_) => SyntaxKind::Label,
+            TokenKind::IntLiteral(_) => SyntaxKind::IntLiteral,
+            TokenKind::FloatLiteral(_) => SyntaxKind::FloatLiteral,
+            TokenKind::StringLiteral(_) => SyntaxKind::StringLiteral,
+            TokenKind::BoolLiteral(_) => SyntaxKind::BoolLiteral,
+            TokenKind::Comment(_) => SyntaxKind::Comment,
+            TokenKind::Newline => SyntaxKind::Whitespace,
+            TokenKind::Error(_) => SyntaxKind::ErrorToken,
+            TokenKind::KarakaKartr
+            | TokenKind::KarakaKarman
+            | TokenKind::KarakaKarana
+            | TokenKind::KarakaSampradana
+            | TokenKind::KarakaApadana
+            | TokenKind::KarakaAdhikarana => SyntaxKind::KarakaMarker,
+            kind if kind.is_keyword() && Self::is_word(kind) => SyntaxKind::Keyword,
+            _ => SyntaxKind::Punct,
+        }
+    }
+
+    /// Keywords spelled with letters, as opposed to operators
+    fn is_word(kind: &TokenKind) -> bool {
+        !matches!(
+            kind,
+            TokenKind::Plus
+                | TokenKind::Minus
+                | TokenKind::Star
+                | TokenKind::Slash
+                | TokenKind::Percent
+                | TokenKind::Equals
+                | TokenKind::EqualsEquals
+                | TokenKind::NotEquals
+                | TokenKind::LessThan
+                | TokenKind::LessEquals
+                | TokenKind::GreaterThan
+                | TokenKind::GreaterEquals
+                | TokenKind::AmpAmp
+                | TokenKind::PipePipe
+                | TokenKind::Bang
+                | TokenKind::Tilde
+                | TokenKind::Ampersand
+                | TokenKind::Pipe
+                | TokenKind::Caret
+                | TokenKind::Arrow
+                | TokenKind::FatArrow
+                | TokenKind::Question
+                | TokenKind::Colon
+                | TokenKind::ColonColon
+                | TokenKind::Dot
+                | TokenKind::DotDot
+                | TokenKind::DotDotDot
+                | TokenKind::DotDotEquals
+                | TokenKind::Hash
+                | TokenKind::HashHash
+                | TokenKind::At
+                | TokenKind::Dollar
+                | TokenKind::LeftShift
+                | TokenKind::RightShift
+                | TokenKind::PlusEquals
+                | TokenKind::MinusEquals
+                | TokenKind::StarEquals
+                | TokenKind::SlashEquals
+                | TokenKind::PercentEquals
+                | TokenKind::LeftParen
+                | TokenKind::RightParen
+                | TokenKind::LeftBrace
+                | TokenKind::RightBrace
+                | TokenKind::LeftBracket
+                | TokenKind::RightBracket
+                | TokenKind::LeftAngle
+                | TokenKind::RightAngle
+                | TokenKind::Comma
+                | TokenKind::Semicolon
+                | TokenKind::Underscore
+                | TokenKind::Eof
+                | TokenKind::Affix(_)
+        )
+    }
+
+    /// Whitespace and comments
+    pub fn is_trivia(self) -> bool {
+        matches!(self, SyntaxKind::Whitespace | SyntaxKind::Comment)
+    }
+
+    /// Leaf kinds; everything from [`SyntaxKind::SourceFile`] on is a node
+    pub fn is_token(self) -> bool {
+        (self as u16) < (SyntaxKind::SourceFile as u16)
+    }
+
+    /// Items, which pull the comments right above them inside the node
+    pub fn is_item(self) -> bool {
+        matches!(
+            self,
+            SyntaxKind::FnDef
+                | SyntaxKind::TypeDef
+                | SyntaxKind::Use
+                | SyntaxKind::Module
+                | SyntaxKind::ConstDef
+                | SyntaxKind::TraitDef
+                | SyntaxKind::ImplDef
+                | SyntaxKind::AssocType
+                | SyntaxKind::Field
+                | SyntaxKind::Variant
+        )
+    }
+
+    /// Statement nodes
+    pub fn is_stmt(self) -> bool {
+        matches!(
+            self,
+            SyntaxKind::LetStmt
+                | SyntaxKind::ReturnStmt
+                | SyntaxKind::IfStmt
+                | SyntaxKind::LoopStmt
+                | SyntaxKind::BreakStmt
+                | SyntaxKind::ContinueStmt
+                | SyntaxKind::ExprStmt
+        )
+    }
+
+    /// Expression nodes
+    pub fn is_expr(self) -> bool {
+        matches!(
+            self,
+            SyntaxKind::Literal
+                | SyntaxKind::PathExpr
+                | SyntaxKind::BinExpr
+                | SyntaxKind::PrefixExpr
+                | SyntaxKind::CastExpr
+                | SyntaxKind::CallExpr
+                | SyntaxKind::MethodCallExpr
+                | SyntaxKind::FieldExpr
+                | SyntaxKind::IndexExpr
+                | SyntaxKind::TryExpr
+                | SyntaxKind::RangeExpr
+                | SyntaxKind::ParenExpr
+                | SyntaxKind::ArrayExpr
+                | SyntaxKind::MacroCall
+                | SyntaxKind::ClosureExpr
+                | SyntaxKind::MatchExpr
+                | SyntaxKind::IfExpr
+                | SyntaxKind::LoopExpr
+                | SyntaxKind::SahaExpr
+                | SyntaxKind::Block
+        )
+    }
+
+    /// Type nodes
+    pub fn is_type(self) -> bool {
+        matches!(
+            self,
+            SyntaxKind::PathType
+                | SyntaxKind::RefType
+                | SyntaxKind::FnType
+                | SyntaxKind::ArrayType
+                | SyntaxKind::SliceType
+                | SyntaxKind::ConstArg
+        )
+    }
+
+    /// Pattern nodes
+    pub fn is_pattern(self) -> bool {
+        matches!(
+            self,
+            SyntaxKind::WildcardPat
+                | SyntaxKind::RestPat
+                | SyntaxKind::LiteralPat
+                | SyntaxKind::RangePat
+                | SyntaxKind::TuplePat
+                | SyntaxKind::ParenPat
+                | SyntaxKind::SlicePat
+                | SyntaxKind::RefPat
+                | SyntaxKind::IdentPat
+                | SyntaxKind::PathPat
+                | SyntaxKind::TupleStructPat
+                | SyntaxKind::RecordPat
+                | SyntaxKind::OrPat
+        )
+    }
+}
@@ -0,0 +1,86 @@
+//! Concrete Syntax Tree - lossless syntax for tooling
+//!
+//! The AST in [`super::ast`] is what the compiler wants: desugared, with
+//! comments and layout thrown away. Formatters, documentation generators
+//! and editors need the opposite, so the parser can also produce a CST:
+//! - **Green tree** ([`GreenNode`]): immutable nodes holding every token,
+//!   whitespace and comment, so the text round-trips byte for byte
+//! - **Red tree** ([`SyntaxNode`]): cursors with parents and offsets,
+//!   created on demand while walking
+//! - **Typed view** ([`nodes`]): `FnDef`, `TypeDef`, `MatchArm`, ... with
+//!   accessors like `name()`, `param_list()` and `doc_comments()`
+//!
+//! Whitespace and comments go to the innermost node enclosing them, except
+//! that comments directly above an item belong to the item.
+//!
+//! ```text
+//! let parse = cst::parse(source);
+//! for item in parse.tree().items() { ... }
+//! assert_eq!(parse.syntax().text(), source);
+//! ```
+
+mod build;
+pub mod green;
+pub mod kind;
+pub mod nodes;
+pub mod red;
+
+pub use build::{Checkpoint, Events};
+pub use green::{GreenElement, GreenNode, GreenToken};
+pub use kind::SyntaxKind;
+pub use nodes::{
+    HasAttributes, HasDocComments, HasName, HasVisibility, Item, SourceFile, TypedNode,
+};
+pub use red::{SyntaxElement, SyntaxNode, SyntaxToken};
+
+use std::sync::Arc;
+
+use super::grammar::{ParseError, Parser};
+use crate::lexer::Token;
+
+/// Result of parsing a file into a CST
+///
+/// The tree is always complete: tokens the parser could not place end up
+/// in [`SyntaxKind::Error`] nodes, and the errors are reported alongside.
+#[derive(Debug, Clone)]
+pub struct Parse {
+    green: Arc<GreenNode>,
+    errors: Vec<ParseError>,
+}
+
+impl Parse {
+    pub(crate) fn new(
+        source: &str,
+        tokens: &[Token],
+        events: Events,
+        errors: Vec<ParseError>,
+    ) -> Self {
+        Self {
+            green: Arc::new(build::build(source, tokens, events)),
+            errors,
+        }
+    }
+
+    pub fn green(&self) -> &Arc<GreenNode> {
+        &self.green
+    }
+
+    /// Root of the red tree
+    pub fn syntax(&self) -> SyntaxNode {
+        SyntaxNode::new_root(self.green.clone())
+    }
+
+    /// Typed view of the root
+    pub fn tree(&self) -> SourceFile {
+        SourceFile::cast(self.syntax()).expect("root is a SourceFile")
+    }
+
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+}
+
+/// Parse `source` into a lossless concrete syntax tree
+pub fn parse(source: &str) -> Parse {
+    Parser::parse_cst(source)
+}
@@ -0,0 +1,616 @@
+//! Typed View - AST-shaped accessors over the CST
+//!
+//! Each type wraps a [`SyntaxNode`] of one kind and finds its parts by
+//! looking at the children, so nothing is copied out of the tree and
+//! every accessor still has a source range. Missing parts (in code that
+//! did not parse) come back as `None` rather than an error.
+
+use std::ops::Range;
+
+use super::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
+use crate::lexer::{lipi, TokenKind};
+
+/// A typed wrapper around a syntax node of a particular kind
+pub trait TypedNode: Sized {
+    /// Wrap `node` if it has the right kind
+    fn cast(node: SyntaxNode) -> Option<Self>;
+
+    /// The underlying syntax node
+    fn syntax(&self) -> &SyntaxNode;
+
+    /// Byte range of the node
+    fn text_range(&self) -> Range<usize> {
+        self.syntax().text_range()
+    }
+}
+
+macro_rules! typed_nodes {
+    ($($(#[$doc:meta])* $name:ident,)*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+            pub struct $name(SyntaxNode);
+
+            impl TypedNode for $name {
+                fn cast(node: SyntaxNode) -> Option<Self> {
+                    (node.kind() == SyntaxKind::$name).then_some(Self(node))
+                }
+
+                fn syntax(&self) -> &SyntaxNode {
+                    &self.0
+                }
+            }
+        )*
+    };
+}
+
+typed_nodes! {
+    /// A whole file
+    SourceFile,
+    /// `#[...]`
+    Attribute,
+    /// `pub`, `pub(saṃkula)`, `pub(uttara)`
+    Visibility,
+    /// Name at a definition site
+    Name,
+    /// Function or method
+    FnDef,
+    /// Parameters of a function or closure
+    ParamList,
+    /// One parameter
+    Param,
+    /// `sva`, `&sva` or `&mut sva`
+    SelfParam,
+    /// `-> T`
+    RetType,
+    /// `<T: Bound>`
+    GenericParamList,
+    /// `prakāra` struct or enum
+    TypeDef,
+    /// Struct field or variant payload field
+    Field,
+    /// Enum variant
+    Variant,
+    /// Variant payload
+    VariantFieldList,
+    /// Import
+    Use,
+    /// `vibhāga`
+    Module,
+    /// `sthira` / `static`
+    ConstDef,
+    /// `guṇa`
+    TraitDef,
+    /// `kriyānvaya`
+    ImplDef,
+    /// Associated type in a trait or impl
+    AssocType,
+    /// `{ ... }`
+    Block,
+    /// `let` binding
+    LetStmt,
+    /// Literal expression
+    Literal,
+    /// `pratyabhijñā`
+    MatchExpr,
+    /// Arm of a match
+    MatchArm,
+    /// `yad guard` of a match arm
+    MatchGuard,
+}
+
+/// Any top-level or module-level item
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Item {
+    Fn(FnDef),
+    Type(TypeDef),
+    Use(Use),
+    Module(Module),
+    Const(ConstDef),
+    Trait(TraitDef),
+    Impl(ImplDef),
+}
+
+impl TypedNode for Item {
+    fn cast(node: SyntaxNode) -> Option<Self> {
+        Some(match node.kind() {
+            SyntaxKind::FnDef => Item::Fn(FnDef(node)),
+            SyntaxKind::TypeDef => Item::Type(TypeDef(node)),
+            SyntaxKind::Use => Item::Use(Use(node)),
+            SyntaxKind::Module => Item::Module(Module(node)),
+            SyntaxKind::ConstDef => Item::Const(ConstDef(node)),
+            SyntaxKind::TraitDef => Item::Trait(TraitDef(node)),
+            SyntaxKind::ImplDef => Item::Impl(ImplDef(node)),
+            _ => return None,
+        })
+    }
+
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Item::Fn(item) => item.syntax(),
+            Item::Type(item) => item.syntax(),
+            Item::Use(item) => item.syntax(),
+            Item::Module(item) => item.syntax(),
+            Item::Const(item) => item.syntax(),
+            Item::Trait(item) => item.syntax(),
+            Item::Impl(item) => item.syntax(),
+        }
+    }
+}
+
+/// Wrapper for any node whose kind satisfies a category test
+macro_rules! category_nodes {
+    ($($(#[$doc:meta])* $name:ident => $test:ident,)*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+            pub struct $name(SyntaxNode);
+
+            impl TypedNode for $name {
+                fn cast(node: SyntaxNode) -> Option<Self> {
+                    node.kind().$test().then_some(Self(node))
+                }
+
+                fn syntax(&self) -> &SyntaxNode {
+                    &self.0
+                }
+            }
+
+            impl $name {
+                pub fn kind(&self) -> SyntaxKind {
+                    self.0.kind()
+                }
+            }
+        )*
+    };
+}
+
+category_nodes! {
+    /// Any statement
+    Stmt => is_stmt,
+    /// Any expression
+    Expr => is_expr,
+    /// Any type
+    Type => is_type,
+    /// Any pattern
+    Pat => is_pattern,
+}
+
+/// First child that casts to `N`
+fn child<N: TypedNode>(node: &SyntaxNode) -> Option<N> {
+    node.children().find_map(N::cast)
+}
+
+/// Every child that casts to `N`
+fn children<N: TypedNode>(node: &SyntaxNode) -> Vec<N> {
+    node.children().filter_map(N::cast).collect()
+}
+
+/// First non-trivia child token with the given lexer kind
+fn token(node: &SyntaxNode, kind: &TokenKind) -> Option<SyntaxToken> {
+    node.child_tokens().find(|token| {
+        token
+            .token_kind()
+            .is_some_and(|k| std::mem::discriminant(k) == std::mem::discriminant(kind))
+    })
+}
+
+/// Scheme the file holding `node` declares with a `lipi` pragma
+fn declared_lipi(node: &SyntaxNode) -> Option<lipi::Lipi> {
+    let root = node.ancestors().last()?;
+    lipi::declared(&root.text())
+}
+
+/// Comment text with its markers removed
+fn comment_body<'a>(text: &'a str, marker: &str) -> &'a str {
+    let body = text.strip_prefix(marker).unwrap_or(text);
+    body.strip_prefix(' ').unwrap_or(body)
+}
+
+/// Nodes that have a name
+pub trait HasName: TypedNode {
+    fn name(&self) -> Option<Name> {
+        child(self.syntax())
+    }
+}
+
+/// Items that can carry `///` documentation
+pub trait HasDocComments: TypedNode {
+    /// Lines of the `///` and `/** */` comments above the item
+    fn doc_comments(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for token in self.syntax().child_tokens() {
+            if !token.is_trivia() {
+                break;
+            }
+            let text = token.text();
+            if text.starts_with("///") && !text.starts_with("////") {
+                lines.push(comment_body(text, "///").trim_end().to_string());
+            } else if let Some(body) = text.strip_prefix("/**").and_then(|t| t.strip_suffix("*/")) {
+                lines.extend(body.lines().map(|line| {
+                    let line = line.trim();
+                    line.strip_prefix('*').unwrap_or(line).trim().to_string()
+                }));
+            }
+        }
+        lines
+    }
+
+    /// The doc comment lines joined with newlines, if there are any
+    fn doc_string(&self) -> Option<String> {
+        let lines = self.doc_comments();
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+}
+
+/// Items that can carry attributes
+pub trait HasAttributes: TypedNode {
+    fn attributes(&self) -> Vec<Attribute> {
+        children(self.syntax())
+    }
+}
+
+/// Items that can be marked `pub`
+pub trait HasVisibility: TypedNode {
+    fn visibility(&self) -> Option<Visibility> {
+        child(self.syntax())
+    }
+
+    /// Whether the item has any `pub` marker
+    fn is_public(&self) -> bool {
+        self.visibility().is_some()
+    }
+}
+
+macro_rules! impl_traits {
+    ($($name:ident: $($trait:ident),*;)*) => {
+        $($(impl $trait for $name {})*)*
+    };
+}
+
+impl_traits! {
+    FnDef: HasName, HasDocComments, HasAttributes, HasVisibility;
+    TypeDef: HasName, HasDocComments, HasAttributes, HasVisibility;
+    Use: HasDocComments, HasAttributes, HasVisibility;
+    Module: HasName, HasDocComments, HasAttributes, HasVisibility;
+    ConstDef: HasName, HasDocComments, HasAttributes, HasVisibility;
+    TraitDef: HasName, HasDocComments, HasAttributes, HasVisibility;
+    ImplDef: HasDocComments, HasAttributes;
+    AssocType: HasName, HasDocComments, HasAttributes;
+    Field: HasName, HasDocComments, HasAttributes, HasVisibility;
+    Variant: HasName, HasDocComments, HasAttributes;
+    Param: HasName, HasAttributes;
+    LetStmt: HasName, HasAttributes;
+}
+
+impl HasDocComments for Item {}
+impl HasAttributes for Item {}
+impl HasVisibility for Item {}
+
+impl SourceFile {
+    pub fn items(&self) -> Vec<Item> {
+        children(&self.0)
+    }
+
+    /// Lines of the `//!` comments documenting the file itself
+    pub fn module_docs(&self) -> Vec<String> {
+        inner_docs(&self.0)
+    }
+
+    /// Every comment in the file, in source order
+    pub fn comments(&self) -> Vec<SyntaxToken> {
+        self.0
+            .descendant_tokens()
+            .into_iter()
+            .filter(|token| token.kind() == SyntaxKind::Comment)
+            .collect()
+    }
+}
+
+/// `//!` lines among the direct children of `node`
+fn inner_docs(node: &SyntaxNode) -> Vec<String> {
+    node.child_tokens()
+        .filter(|token| token.text().starts_with("//!"))
+        .map(|token| comment_body(token.text(), "//!").trim_end().to_string())
+        .collect()
+}
+
+impl Name {
+    /// Identifier as written in the source
+    pub fn text(&self) -> String {
+        self.ident()
+            .map(|token| token.text().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Identifier in the spelling the compiler sees: IAST for Sanskrit,
+    /// as written for ASCII outside a `lipi` pragma
+    pub fn normalized(&self) -> String {
+        lipi::identifier(&self.text(), declared_lipi(&self.0)).into_owned()
+    }
+
+    pub fn ident(&self) -> Option<SyntaxToken> {
+        self.0.child_tokens().find(|token| !token.is_trivia())
+    }
+}
+
+impl Attribute {
+    /// Attribute name: `inline` in `#[inline(always)]`
+    pub fn name(&self) -> Option<String> {
+        self.0
+            .child_tokens()
+            .filter(|token| !token.is_trivia())
+            .nth(2)
+            .map(|token| lipi::identifier(token.text(), declared_lipi(&self.0)).into_owned())
+    }
+}
+
+impl Visibility {
+    /// `saṃkula`/`uttara` restriction, if any
+    pub fn restriction(&self) -> Option<SyntaxToken> {
+        token(&self.0, &TokenKind::Crate).or_else(|| token(&self.0, &TokenKind::Super))
+    }
+}
+
+impl FnDef {
+    pub fn generic_param_list(&self) -> Option<GenericParamList> {
+        child(&self.0)
+    }
+
+    pub fn param_list(&self) -> Option<ParamList> {
+        child(&self.0)
+    }
+
+    pub fn ret_type(&self) -> Option<RetType> {
+        child(&self.0)
+    }
+
+    /// Body; trait method declarations have none
+    pub fn body(&self) -> Option<Block> {
+        child(&self.0)
+    }
+
+    /// Header from the `kāryakrama` keyword up to the body
+    pub fn signature(&self) -> String {
+        item_header(&self.0)
+    }
+}
+
+/// Source text of an item from its keyword up to its body or `;`
+///
+/// Docs, attributes, visibility and comments are left out; the layout of
+/// what remains is kept.
+pub fn item_header(node: &SyntaxNode) -> String {
+    let mut text = String::new();
+    for element in node.children_with_tokens() {
+        let tokens = match element {
+            SyntaxElement::Node(node) => match node.kind() {
+                SyntaxKind::Block => break,
+                SyntaxKind::Attribute | SyntaxKind::Visibility => continue,
+                _ => node.descendant_tokens(),
+            },
+            SyntaxElement::Token(token) if matches!(token.text(), "{" | ";") => break,
+            SyntaxElement::Token(token) => vec![token],
+        };
+        for token in tokens {
+            if token.kind() == SyntaxKind::Comment || (token.is_trivia() && text.is_empty()) {
+                continue;
+            }
+            text.push_str(token.text());
+        }
+    }
+    text.trim_end().to_string()
+}
+
+impl ParamList {
+    pub fn self_param(&self) -> Option<SelfParam> {
+        child(&self.0)
+    }
+
+    pub fn params(&self) -> Vec<Param> {
+        children(&self.0)
+    }
+}
+
+impl Param {
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.0)
+    }
+
+    /// Kāraka role, from `@karaṇa name: T` or `name[karaṇa]: T`
+    pub fn karaka(&self) -> Option<String> {
+        let tokens: Vec<SyntaxToken> = self
+            .0
+            .child_tokens()
+            .filter(|token| !token.is_trivia())
+            .collect();
+        if let Some(marker) = tokens
+            .iter()
+            .find(|token| token.kind() == SyntaxKind::KarakaMarker)
+        {
+            return Some(marker.text().trim_start_matches('@').to_string());
+        }
+        let open = tokens.iter().position(|token| token.text() == "[")?;
+        tokens.get(open + 1).map(|token| token.text().to_string())
+    }
+}
+
+impl RetType {
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.0)
+    }
+}
+
+impl GenericParamList {
+    /// Names of the type parameters
+    pub fn names(&self) -> Vec<Name> {
+        self.0
+            .children()
+            .filter(|node| node.kind() == SyntaxKind::GenericParam)
+            .filter_map(|node| child(&node))
+            .collect()
+    }
+}
+
+impl TypeDef {
+    pub fn generic_param_list(&self) -> Option<GenericParamList> {
+        child(&self.0)
+    }
+
+    /// Struct fields; empty for an enum
+    pub fn fields(&self) -> Vec<Field> {
+        children(&self.0)
+    }
+
+    /// Enum variants; empty for a struct
+    pub fn variants(&self) -> Vec<Variant> {
+        children(&self.0)
+    }
+
+    pub fn is_enum(&self) -> bool {
+        !self.variants().is_empty()
+    }
+}
+
+impl Field {
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.0)
+    }
+}
+
+impl Variant {
+    pub fn field_list(&self) -> Option<VariantFieldList> {
+        child(&self.0)
+    }
+}
+
+impl VariantFieldList {
+    pub fn fields(&self) -> Vec<Field> {
+        children(&self.0)
+    }
+}
+
+impl Use {
+    /// Path segments as written: `["a", "b", "*"]`
+    pub fn segments(&self) -> Vec<String> {
+        self.0
+            .child_tokens()
+            .filter(|token| matches!(token.kind(), SyntaxKind::Ident) || token.text() == "*")
+            .filter(|token| lipi::normalize(token.text()) != "āyāti")
+            .map(|token| token.text().to_string())
+            .collect()
+    }
+}
+
+impl Module {
+    /// Items of an inline module; empty for `vibhāga name;`
+    pub fn items(&self) -> Vec<Item> {
+        children(&self.0)
+    }
+
+    /// `//!` lines at the top of an inline module
+    pub fn module_docs(&self) -> Vec<String> {
+        inner_docs(&self.0)
+    }
+}
+
+impl ConstDef {
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.0)
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn is_static(&self) -> bool {
+        token(&self.0, &TokenKind::Static).is_some()
+    }
+}
+
+impl TraitDef {
+    pub fn generic_param_list(&self) -> Option<GenericParamList> {
+        child(&self.0)
+    }
+
+    pub fn methods(&self) -> Vec<FnDef> {
+        children(&self.0)
+    }
+
+    pub fn assoc_types(&self) -> Vec<AssocType> {
+        children(&self.0)
+    }
+}
+
+impl ImplDef {
+    /// Implemented trait and self type, in source order: `[Trait, Type]`
+    /// for a trait impl, `[Type]` for an inherent one
+    pub fn types(&self) -> Vec<Type> {
+        children(&self.0)
+    }
+
+    pub fn methods(&self) -> Vec<FnDef> {
+        children(&self.0)
+    }
+
+    pub fn assoc_types(&self) -> Vec<AssocType> {
+        children(&self.0)
+    }
+}
+
+impl Block {
+    pub fn statements(&self) -> Vec<Stmt> {
+        children(&self.0)
+    }
+}
+
+impl LetStmt {
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.0)
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl Literal {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        self.0.child_tokens().find(|token| !token.is_trivia())
+    }
+
+    /// Lexer kind of the literal, which carries its value
+    pub fn value(&self) -> Option<TokenKind> {
+        self.token()?.token_kind().cloned()
+    }
+}
+
+impl MatchExpr {
+    pub fn scrutinee(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn arms(&self) -> Vec<MatchArm> {
+        children(&self.0)
+    }
+}
+
+impl MatchArm {
+    pub fn pattern(&self) -> Option<Pat> {
+        child(&self.0)
+    }
+
+    pub fn guard(&self) -> Option<MatchGuard> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl MatchGuard {
+    pub fn condition(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
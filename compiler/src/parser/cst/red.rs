@@ -0,0 +1,375 @@
+//! Red Tree - positioned cursors over the green tree
+//!
+//! A [`SyntaxNode`] is a green node plus its byte offset and a pointer to
+//! its parent, so tools can walk up and down the tree and map any node
+//! back to a source range. Red nodes are created on demand while walking.
+
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use super::green::{GreenElement, GreenNode, GreenToken};
+use super::SyntaxKind;
+use crate::lexer::TokenKind;
+
+struct NodeData {
+    green: Arc<GreenNode>,
+    parent: Option<SyntaxNode>,
+    /// Position among the parent's children
+    index: usize,
+    /// Byte offset of the node's first character
+    offset: usize,
+}
+
+/// Node of the concrete syntax tree with its position in the file
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+/// Token of the concrete syntax tree with its position in the file
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: Arc<GreenToken>,
+    parent: SyntaxNode,
+    index: usize,
+    offset: usize,
+}
+
+/// Either a node or a token
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    /// Root cursor over a green tree
+    pub fn new_root(green: Arc<GreenNode>) -> Self {
+        SyntaxNode(Rc::new(NodeData {
+            green,
+            parent: None,
+            index: 0,
+            offset: 0,
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind()
+    }
+
+    pub fn green(&self) -> &Arc<GreenNode> {
+        &self.0.green
+    }
+
+    /// Byte range of the node, trivia inside it included
+    pub fn text_range(&self) -> Range<usize> {
+        self.0.offset..self.0.offset + self.0.green.text_len()
+    }
+
+    /// Exact source text of the node
+    pub fn text(&self) -> String {
+        self.0.green.to_string()
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    /// Next node under the same parent, skipping tokens
+    pub fn next_sibling(&self) -> Option<SyntaxNode> {
+        self.parent()?
+            .children_with_tokens()
+            .into_iter()
+            .skip(self.0.index + 1)
+            .find_map(SyntaxElement::into_node)
+    }
+
+    /// Previous node under the same parent, skipping tokens
+    pub fn prev_sibling(&self) -> Option<SyntaxNode> {
+        self.parent()?
+            .children_with_tokens()
+            .into_iter()
+            .take(self.0.index)
+            .filter_map(SyntaxElement::into_node)
+            .last()
+    }
+
+    /// This node, its parent, its grandparent, ... up to the root
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode> {
+        std::iter::successors(Some(self.clone()), SyntaxNode::parent)
+    }
+
+    /// Child nodes and tokens in source order
+    pub fn children_with_tokens(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.0.offset;
+        let mut elements = Vec::with_capacity(self.0.green.children().len());
+        for (index, child) in self.0.green.children().iter().enumerate() {
+            elements.push(match child {
+                GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                    green: green.clone(),
+                    parent: Some(self.clone()),
+                    index,
+                    offset,
+                }))),
+                GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                    green: green.clone(),
+                    parent: self.clone(),
+                    index,
+                    offset,
+                }),
+            });
+            offset += child.text_len();
+        }
+        elements
+    }
+
+    /// Child nodes, skipping tokens
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode> {
+        self.children_with_tokens()
+            .into_iter()
+            .filter_map(SyntaxElement::into_node)
+    }
+
+    /// Child tokens, skipping nodes
+    pub fn child_tokens(&self) -> impl Iterator<Item = SyntaxToken> {
+        self.children_with_tokens()
+            .into_iter()
+            .filter_map(SyntaxElement::into_token)
+    }
+
+    /// This node and every node below it, in preorder
+    pub fn descendants(&self) -> Vec<SyntaxNode> {
+        let mut nodes = Vec::new();
+        let mut stack = vec![self.clone()];
+        while let Some(node) = stack.pop() {
+            let children: Vec<_> = node.children().collect();
+            stack.extend(children.into_iter().rev());
+            nodes.push(node);
+        }
+        nodes
+    }
+
+    /// Every token below this node, trivia included, in source order
+    pub fn descendant_tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        for element in self.children_with_tokens() {
+            match element {
+                SyntaxElement::Node(node) => tokens.extend(node.descendant_tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+
+    pub fn first_token(&self) -> Option<SyntaxToken> {
+        self.children_with_tokens()
+            .into_iter()
+            .find_map(|element| match element {
+                SyntaxElement::Node(node) => node.first_token(),
+                SyntaxElement::Token(token) => Some(token),
+            })
+    }
+
+    /// Token covering `offset`; at the end of the node, the last token
+    pub fn token_at_offset(&self, offset: usize) -> Option<SyntaxToken> {
+        let range = self.text_range();
+        if offset < range.start || offset > range.end {
+            return None;
+        }
+        let children = self.children_with_tokens();
+        let child = children
+            .iter()
+            .find(|child| child.text_range().contains(&offset))
+            .or_else(|| children.last())?;
+        match child {
+            SyntaxElement::Node(node) => node.token_at_offset(offset),
+            SyntaxElement::Token(token) => Some(token.clone()),
+        }
+    }
+
+    /// Indented dump of the tree, one element per line
+    ///
+    /// ```text
+    /// SourceFile@0..13
+    ///   FnDef@0..13
+    ///     Keyword@0..11 "kāryakrama"
+    /// ```
+    pub fn debug_dump(&self) -> String {
+        let mut out = String::new();
+        self.dump_into(&mut out, 0);
+        out
+    }
+
+    fn dump_into(&self, out: &mut String, depth: usize) {
+        let range = self.text_range();
+        out.push_str(&format!(
+            "{:indent$}{:?}@{}..{}\n",
+            "",
+            self.kind(),
+            range.start,
+            range.end,
+            indent = depth * 2
+        ));
+        for element in self.children_with_tokens() {
+            match element {
+                SyntaxElement::Node(node) => node.dump_into(out, depth + 1),
+                SyntaxElement::Token(token) => {
+                    let range = token.text_range();
+                    out.push_str(&format!(
+                        "{:indent$}{:?}@{}..{} {:?}\n",
+                        "",
+                        token.kind(),
+                        range.start,
+                        range.end,
+                        token.text(),
+                        indent = (depth + 1) * 2
+                    ));
+                }
+            }
+        }
+    }
+}
+
+impl PartialEq for SyntaxNode {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0.green, &other.0.green) && self.0.offset == other.0.offset
+    }
+}
+
+impl Eq for SyntaxNode {}
+
+impl std::hash::Hash for SyntaxNode {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0.green).hash(state);
+        self.0.offset.hash(state);
+    }
+}
+
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let range = self.text_range();
+        write!(f, "{:?}@{}..{}", self.kind(), range.start, range.end)
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.0.green, f)
+    }
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> SyntaxKind {
+        self.green.kind()
+    }
+
+    /// Exact source text of the token
+    pub fn text(&self) -> &str {
+        self.green.text()
+    }
+
+    /// Kind the lexer gave this token, if it is not trivia
+    pub fn token_kind(&self) -> Option<&TokenKind> {
+        self.green.token_kind()
+    }
+
+    pub fn text_range(&self) -> Range<usize> {
+        self.offset..self.offset + self.green.text_len()
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        self.kind().is_trivia()
+    }
+
+    /// Next sibling element under the same parent
+    pub fn next_sibling_or_token(&self) -> Option<SyntaxElement> {
+        self.parent
+            .children_with_tokens()
+            .into_iter()
+            .nth(self.index + 1)
+    }
+
+    /// Previous sibling element under the same parent
+    pub fn prev_sibling_or_token(&self) -> Option<SyntaxElement> {
+        let index = self.index.checked_sub(1)?;
+        self.parent.children_with_tokens().into_iter().nth(index)
+    }
+}
+
+impl PartialEq for SyntaxToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.green, &other.green) && self.offset == other.offset
+    }
+}
+
+impl Eq for SyntaxToken {}
+
+impl std::hash::Hash for SyntaxToken {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.green).hash(state);
+        self.offset.hash(state);
+    }
+}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let range = self.text_range();
+        write!(
+            f,
+            "{:?}@{}..{} {:?}",
+            self.kind(),
+            range.start,
+            range.end,
+            self.text()
+        )
+    }
+}
+
+impl fmt::Display for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text())
+    }
+}
+
+impl SyntaxElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            SyntaxElement::Node(node) => node.kind(),
+            SyntaxElement::Token(token) => token.kind(),
+        }
+    }
+
+    pub fn text_range(&self) -> Range<usize> {
+        match self {
+            SyntaxElement::Node(node) => node.text_range(),
+            SyntaxElement::Token(token) => token.text_range(),
+        }
+    }
+
+    pub fn into_node(self) -> Option<SyntaxNode> {
+        match self {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        }
+    }
+
+    pub fn into_token(self) -> Option<SyntaxToken> {
+        match self {
+            SyntaxElement::Node(_) => None,
+            SyntaxElement::Token(token) => Some(token),
+        }
+    }
+}
+
+impl fmt::Debug for SyntaxElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyntaxElement::Node(node) => fmt::Debug::fmt(node, f),
+            SyntaxElement::Token(token) => fmt::Debug::fmt(token, f),
+        }
+    }
+}
@@ -0,0 +1,401 @@
+//! Format Strings (Vinyāsa)
+//!
+//! Parses the template of `mudraṇa!` and `vinyāsa!`:
+//!
+//! ```text
+//! "nāma = {nāma:>10}, {} of {1:.2}, {:#x}, {{literal braces}}"
+//! ```
+//!
+//! A placeholder names its argument (`{nāma}`, taken from the enclosing
+//! scope), gives its position (`{1}`) or takes the next one (`{}`), and
+//! may add a specification after `:`:
+//! `[[fill]align][+][#][0][width][.precision][type]`, where align is `<`,
+//! `^` or `>` and type is `?` (debug), `x`/`X` (hex), `b`, `o` or `e`.
+
+use crate::lexer::lipi;
+
+/// Piece of a parsed template
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplatePiece {
+    /// Literal text, `{{` and `}}` already unescaped
+    Text(String),
+    /// `{arg:spec}`
+    Placeholder { arg: ArgRef, spec: FormatSpec },
+}
+
+/// Which argument a placeholder formats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgRef {
+    /// `{}`: the argument after the previous `{}`
+    Next,
+    /// `{1}`
+    Index(usize),
+    /// `{nāma}`: a variable in scope, in canonical spelling; an ASCII
+    /// name is taken as written, as a `lipi` pragma does not reach inside
+    /// string literals
+    Name(String),
+}
+
+/// Alignment within the field width
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// How a value is rendered, and so which trait it needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FormatKind {
+    #[default]
+    Display,
+    Debug,
+    LowerHex,
+    UpperHex,
+    Binary,
+    Octal,
+    LowerExp,
+}
+
+impl FormatKind {
+    /// Formatting trait a value must implement to be rendered this way
+    pub fn trait_name(self) -> &'static str {
+        match self {
+            FormatKind::Display => "Display",
+            FormatKind::Debug => "Debug",
+            FormatKind::LowerHex => "LowerHex",
+            FormatKind::UpperHex => "UpperHex",
+            FormatKind::Binary => "Binary",
+            FormatKind::Octal => "Octal",
+            FormatKind::LowerExp => "LowerExp",
+        }
+    }
+
+    fn from_type_char(c: char) -> Option<Self> {
+        Some(match c {
+            '?' => FormatKind::Debug,
+            'x' => FormatKind::LowerHex,
+            'X' => FormatKind::UpperHex,
+            'b' => FormatKind::Binary,
+            'o' => FormatKind::Octal,
+            'e' => FormatKind::LowerExp,
+            _ => return None,
+        })
+    }
+}
+
+/// Specification after the `:` of a placeholder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FormatSpec {
+    pub fill: char,
+    pub align: Option<Align>,
+    /// `+`: always print the sign
+    pub sign_plus: bool,
+    /// `#`: `0x`/`0b`/`0o` prefixes, pretty debug output
+    pub alternate: bool,
+    /// `0`: pad numbers with zeros after the sign
+    pub zero_pad: bool,
+    pub width: Option<u16>,
+    pub precision: Option<u16>,
+    pub kind: FormatKind,
+}
+
+impl Default for FormatSpec {
+    fn default() -> Self {
+        Self {
+            fill: ' ',
+            align: None,
+            sign_plus: false,
+            alternate: false,
+            zero_pad: false,
+            width: None,
+            precision: None,
+            kind: FormatKind::Display,
+        }
+    }
+}
+
+impl FormatSpec {
+    /// Pack the specification into the word the runtime's `jag_vinyasa_*`
+    /// routines take, so each formatted value costs a single argument
+    ///
+    /// From the low bits: kind (3), align (2; none, `<`, `^`, `>`), `+`,
+    /// `#`, `0`, has-precision, width (16), precision (16), fill (21).
+    /// `jagannath_runtime::vinyasa::Spec::decode` reads the same layout.
+    pub fn encode(&self) -> i64 {
+        let kind = match self.kind {
+            FormatKind::Display => 0,
+            FormatKind::Debug => 1,
+            FormatKind::LowerHex => 2,
+            FormatKind::UpperHex => 3,
+            FormatKind::Binary => 4,
+            FormatKind::Octal => 5,
+            FormatKind::LowerExp => 6,
+        };
+        let align = match self.align {
+            None => 0,
+            Some(Align::Left) => 1,
+            Some(Align::Center) => 2,
+            Some(Align::Right) => 3,
+        };
+        kind | align << 3
+            | (self.sign_plus as i64) << 5
+            | (self.alternate as i64) << 6
+            | (self.zero_pad as i64) << 7
+            | (self.precision.is_some() as i64) << 8
+            | (self.width.unwrap_or(0) as i64) << 9
+            | (self.precision.unwrap_or(0) as i64) << 25
+            | (self.fill as i64) << 41
+    }
+}
+
+/// Malformed template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatError {
+    pub message: String,
+    /// Byte offset in the template
+    pub offset: usize,
+}
+
+impl FormatError {
+    fn new(message: impl Into<String>, offset: usize) -> Self {
+        Self {
+            message: message.into(),
+            offset,
+        }
+    }
+}
+
+/// Split a template into literal text and placeholders
+pub fn parse_template(template: &str) -> Result<Vec<TemplatePiece>, FormatError> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '{' if chars.next_if(|&(_, c)| c == '{').is_some() => text.push('{'),
+            '}' if chars.next_if(|&(_, c)| c == '}').is_some() => text.push('}'),
+            '{' => {
+                let Some(len) = template[offset..].find('}') else {
+                    return Err(FormatError::new("unclosed `{` in format string", offset));
+                };
+                let inner = &template[offset + 1..offset + len];
+                if !text.is_empty() {
+                    pieces.push(TemplatePiece::Text(std::mem::take(&mut text)));
+                }
+                pieces.push(parse_placeholder(inner, offset + 1)?);
+                while chars.next_if(|&(i, _)| i <= offset + len).is_some() {}
+            }
+            '}' => {
+                return Err(FormatError::new(
+                    "unmatched `}` in format string; write `}}` for a literal brace",
+                    offset,
+                ))
+            }
+            _ => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(TemplatePiece::Text(text));
+    }
+    Ok(pieces)
+}
+
+/// Parse `arg:spec`, the inside of a placeholder starting at `offset`
+fn parse_placeholder(inner: &str, offset: usize) -> Result<TemplatePiece, FormatError> {
+    let (arg, spec) = match inner.split_once(':') {
+        Some((arg, spec)) => (arg, Some(spec)),
+        None => (inner, None),
+    };
+    let arg = if arg.is_empty() {
+        ArgRef::Next
+    } else if arg.chars().all(|c| c.is_ascii_digit()) {
+        ArgRef::Index(
+            arg.parse()
+                .map_err(|_| FormatError::new("argument index is too large", offset))?,
+        )
+    } else if arg.starts_with(|c: char| c.is_ascii_digit())
+        || arg.contains(|c: char| c.is_whitespace() || "{}.,()".contains(c))
+    {
+        return Err(FormatError::new(
+            format!("invalid argument name `{}` in format string", arg),
+            offset,
+        ));
+    } else {
+        ArgRef::Name(lipi::identifier(arg, None).into_owned())
+    };
+    let spec = match spec {
+        Some(spec) => parse_spec(spec, offset + inner.len() - spec.len())?,
+        None => FormatSpec::default(),
+    };
+    Ok(TemplatePiece::Placeholder { arg, spec })
+}
+
+/// Parse `[[fill]align][+][#][0][width][.precision][type]`
+fn parse_spec(text: &str, offset: usize) -> Result<FormatSpec, FormatError> {
+    let mut spec = FormatSpec::default();
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+    let align = |c: char| match c {
+        '<' => Some(Align::Left),
+        '^' => Some(Align::Center),
+        '>' => Some(Align::Right),
+        _ => None,
+    };
+    if let Some(a) = chars.get(1).copied().and_then(align) {
+        spec.fill = chars[0];
+        spec.align = Some(a);
+        i = 2;
+    } else if let Some(a) = chars.first().copied().and_then(align) {
+        spec.align = Some(a);
+        i = 1;
+    }
+    if chars.get(i) == Some(&'+') {
+        spec.sign_plus = true;
+        i += 1;
+    }
+    if chars.get(i) == Some(&'#') {
+        spec.alternate = true;
+        i += 1;
+    }
+    if chars.get(i) == Some(&'0') {
+        spec.zero_pad = true;
+        i += 1;
+    }
+    let count = |i: &mut usize, what: &str| -> Result<Option<u16>, FormatError> {
+        let start = *i;
+        while chars.get(*i).is_some_and(|c| c.is_ascii_digit()) {
+            *i += 1;
+        }
+        if start == *i {
+            return Ok(None);
+        }
+        let digits: String = chars[start..*i].iter().collect();
+        digits
+            .parse()
+            .map(Some)
+            .map_err(|_| FormatError::new(format!("{} {} is too large", what, digits), offset))
+    };
+    spec.width = count(&mut i, "width")?;
+    if chars.get(i) == Some(&'.') {
+        i += 1;
+        spec.precision = count(&mut i, "precision")?;
+        if spec.precision.is_none() {
+            return Err(FormatError::new("expected a precision after `.`", offset));
+        }
+    }
+    if let Some(&c) = chars.get(i) {
+        spec.kind = FormatKind::from_type_char(c)
+            .ok_or_else(|| FormatError::new(format!("unknown format type `{}`", c), offset))?;
+        i += 1;
+    }
+    if i < chars.len() {
+        let rest: String = chars[i..].iter().collect();
+        return Err(FormatError::new(
+            format!("unexpected `{}` in format specification", rest),
+            offset,
+        ));
+    }
+    Ok(spec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholder(arg: ArgRef, spec: FormatSpec) -> TemplatePiece {
+        TemplatePiece::Placeholder { arg, spec }
+    }
+
+    #[test]
+    fn test_text_and_placeholders() {
+        let pieces = parse_template("nāma = {nāma:>10}, {} {1} {{x}}").unwrap();
+        assert_eq!(
+            pieces,
+            vec![
+                TemplatePiece::Text("nāma = ".to_string()),
+                placeholder(
+                    ArgRef::Name("nāma".to_string()),
+                    FormatSpec {
+                        align: Some(Align::Right),
+                        width: Some(10),
+                        ..FormatSpec::default()
+                    }
+                ),
+                TemplatePiece::Text(", ".to_string()),
+                placeholder(ArgRef::Next, FormatSpec::default()),
+                TemplatePiece::Text(" ".to_string()),
+                placeholder(ArgRef::Index(1), FormatSpec::default()),
+                TemplatePiece::Text(" {x}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_full_spec() {
+        let pieces = parse_template("{:*^+#012.3x}").unwrap();
+        assert_eq!(
+            pieces,
+            vec![placeholder(
+                ArgRef::Next,
+                FormatSpec {
+                    fill: '*',
+                    align: Some(Align::Center),
+                    sign_plus: true,
+                    alternate: true,
+                    zero_pad: true,
+                    width: Some(12),
+                    precision: Some(3),
+                    kind: FormatKind::LowerHex,
+                }
+            )]
+        );
+        let pieces = parse_template("{x:?}").unwrap();
+        assert!(matches!(
+            &pieces[0],
+            TemplatePiece::Placeholder { spec, .. } if spec.kind == FormatKind::Debug
+        ));
+    }
+
+    #[test]
+    fn test_names_are_normalized() {
+        let pieces = parse_template("{नाम}").unwrap();
+        assert_eq!(
+            pieces,
+            vec![placeholder(
+                ArgRef::Name("nāma".to_string()),
+                FormatSpec::default()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse_template("a {").unwrap_err().offset, 2);
+        assert!(parse_template("a }").is_err());
+        assert!(parse_template("{:q}").unwrap_err().message.contains("`q`"));
+        assert!(parse_template("{:.}").is_err());
+        assert!(parse_template("{:99999}").is_err());
+        assert!(parse_template("{a b}").is_err());
+    }
+
+    #[test]
+    fn test_encode_layout() {
+        assert_eq!(FormatSpec::default().encode(), (' ' as i64) << 41);
+        let spec = FormatSpec {
+            fill: '0',
+            align: Some(Align::Right),
+            width: Some(8),
+            precision: Some(2),
+            kind: FormatKind::UpperHex,
+            ..FormatSpec::default()
+        };
+        let word = spec.encode();
+        assert_eq!(word & 0b111, 3);
+        assert_eq!(word >> 3 & 0b11, 3);
+        assert_eq!(word >> 8 & 1, 1);
+        assert_eq!(word >> 9 & 0xffff, 8);
+        assert_eq!(word >> 25 & 0xffff, 2);
+        assert_eq!(word >> 41, '0' as i64);
+    }
+}
@@ -2,8 +2,13 @@
 //!
 //! Implements recursive descent parsing for the Jagannath grammar.
 
+use std::cell::RefCell;
+
 use super::ast::*;
-use crate::lexer::{AffixSequence, Span, Token, TokenKind};
+use super::cst::{self, Checkpoint, SyntaxKind};
+use super::format::{self, ArgRef, FormatSpec, TemplatePiece};
+use crate::lexer::{Affix, AffixSequence, Span, Token, TokenKind};
+use crate::modules::Visibility;
 
 /// Main parser structure
 pub struct Parser {
@@ -13,6 +18,32 @@ pub struct Parser {
     position: usize,
     /// Errors accumulated during parsing
     errors: Vec<ParseError>,
+    /// Node boundaries, recorded only when building a CST
+    cst: Option<cst::Events>,
+    /// Tokens tried at a position, for the `expected` set of errors there
+    expected: RefCell<(usize, Vec<TokenKind>)>,
+}
+
+/// What a `yatra` clause says: bounds, and for a function its contracts
+#[derive(Default)]
+struct Yatra {
+    predicates: Vec<WherePredicate>,
+    preconditions: Vec<Contract>,
+    postconditions: Vec<Contract>,
+}
+
+impl Yatra {
+    /// Fail on a contract, where only bounds may be given
+    fn reject_contracts(&self) -> Result<(), ParseError> {
+        match self.preconditions.iter().chain(&self.postconditions).next() {
+            Some(contract) => Err(ParseError {
+                message: "`pūrva` and `paścāt` contracts apply to functions only".to_string(),
+                span: contract.span,
+                expected: Vec::new(),
+            }),
+            None => Ok(()),
+        }
+    }
 }
 
 /// Parse error
@@ -20,6 +51,26 @@ pub struct Parser {
 pub struct ParseError {
     pub message: String,
     pub span: Span,
+    /// Tokens that would have been accepted where the error occurred;
+    /// identifiers and literals are listed with an empty payload
+    pub expected: Vec<TokenKind>,
+}
+
+impl ParseError {
+    /// Readable names of the expected tokens: `identifier`, `LeftBrace`, ...
+    pub fn expected_names(&self) -> Vec<String> {
+        self.expected
+            .iter()
+            .map(|kind| match kind {
+                TokenKind::IntLiteral(_) => "integer".to_string(),
+                TokenKind::FloatLiteral(_) => "float".to_string(),
+                TokenKind::StringLiteral(_) => "string".to_string(),
+                TokenKind::BoolLiteral(_) => "boolean".to_string(),
+                TokenKind::Identifier(_) => "identifier".to_string(),
+                kind => format!("{:?}", kind),
+            })
+            .collect()
+    }
 }
 
 impl Parser {
@@ -29,6 +80,8 @@ impl Parser {
             tokens,
             position: 0,
             errors: Vec::new(),
+            cst: None,
+            expected: RefCell::new((0, Vec::new())),
         }
     }
 
@@ -44,75 +97,349 @@ impl Parser {
         parser.parse()
     }
 
+    /// Like [`Parser::parse_str`], but always returns an AST alongside the
+    /// errors; see [`Parser::parse_recovering`]
+    pub fn parse_str_recovering(source: &str) -> (Ast, Vec<ParseError>) {
+        use crate::lexer::Lexer;
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize();
+        let mut parser = Parser::new(tokens);
+        parser.parse_recovering()
+    }
+
+    /// Parse source code into a lossless concrete syntax tree
+    ///
+    /// Runs the same grammar as [`Parser::parse`], recording where each
+    /// node starts and ends; errors do not stop the tree from covering
+    /// the whole source.
+    pub fn parse_cst(source: &str) -> cst::Parse {
+        use crate::lexer::Lexer;
+        let mut lexer = Lexer::new(source);
+        let mut parser = Parser::new(lexer.tokenize());
+        parser.cst = Some(cst::Events::default());
+        let (_, errors) = parser.parse_recovering();
+        let events = parser.cst.take().unwrap_or_default();
+        cst::Parse::new(source, &parser.tokens, events, errors)
+    }
+
     /// Parse the entire source file
     pub fn parse(&mut self) -> Result<Ast, Vec<ParseError>> {
-        let mut items = Vec::new();
+        let (ast, errors) = self.parse_recovering();
+        if errors.is_empty() {
+            Ok(ast)
+        } else {
+            Err(errors)
+        }
+    }
 
+    /// Parse the entire source file, keeping whatever parsed
+    ///
+    /// Always yields an AST, for tools that must keep working on code
+    /// being typed: an item that fails to parse becomes [`Item::Error`],
+    /// and a broken statement, `let` initializer or call argument becomes
+    /// [`Expr::Error`], so the rest of its function survives. The errors
+    /// come back alongside, in source order.
+    pub fn parse_recovering(&mut self) -> (Ast, Vec<ParseError>) {
+        let mut items = Vec::new();
         while !self.is_eof() {
-            match self.parse_item() {
-                Ok(item) => items.push(item),
-                Err(e) => {
-                    self.errors.push(e);
-                    self.synchronize();
-                }
-            }
+            items.push(self.parse_item_recovering(false));
         }
+        let ast = Ast {
+            items,
+            file_path: String::new(),
+        };
+        (ast, self.errors.clone())
+    }
 
-        if self.errors.is_empty() {
-            Ok(Ast {
-                items,
-                file_path: String::new(),
-            })
-        } else {
-            Err(self.errors.clone())
+    /// Parse an item, or skip to the next one and stand in an `Item::Error`
+    fn parse_item_recovering(&mut self, nested: bool) -> Item {
+        let checkpoint = self.checkpoint();
+        let start = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+        match self.parse_item() {
+            Ok(item) => item,
+            Err(e) => {
+                self.errors.push(e);
+                self.synchronize(nested);
+                self.wrap(checkpoint, SyntaxKind::Error);
+                Item::Error(self.span_from(start))
+            }
         }
     }
 
-    fn synchronize(&mut self) {
+    /// Skip to the start of the next item; inside an inline module, also
+    /// stop at the closing brace
+    fn synchronize(&mut self, nested: bool) {
         self.advance();
-        while !self.is_eof() {
-            if let Some(token) = self.peek() {
-                match &token.kind {
-                    TokenKind::Karyakrama
-                    | TokenKind::Prakara
-                    | TokenKind::Use
-                    | TokenKind::Mod
-                    | TokenKind::Const
-                    | TokenKind::Pub => return,
-                    _ => {
-                        self.advance();
-                    }
+        while let Some(token) = self.peek() {
+            match &token.kind {
+                TokenKind::Eof => return,
+                TokenKind::Karyakrama
+                | TokenKind::Prakara
+                | TokenKind::Use
+                | TokenKind::Mod
+                | TokenKind::Const
+                | TokenKind::Trait
+                | TokenKind::Impl
+                | TokenKind::Hash
+                | TokenKind::Pub => return,
+                TokenKind::RightBrace if nested => return,
+                _ => {
+                    self.advance();
                 }
-            } else {
-                break;
             }
         }
     }
 
+    /// Record `error` and skip what is left of a broken expression
+    ///
+    /// Skips up to one of `stops` outside any brackets opened meanwhile,
+    /// or up to a closing bracket that belongs to an enclosing construct,
+    /// and returns an `Expr::Error` covering the skipped tokens.
+    fn recover_expr(
+        &mut self,
+        checkpoint: Checkpoint,
+        start: Span,
+        error: ParseError,
+        stops: &[TokenKind],
+    ) -> Expr {
+        self.errors.push(error);
+        let mut depth = 0usize;
+        while let Some(token) = self.peek() {
+            match &token.kind {
+                TokenKind::Eof => break,
+                TokenKind::LeftParen | TokenKind::LeftBracket | TokenKind::LeftBrace => depth += 1,
+                TokenKind::RightParen | TokenKind::RightBracket | TokenKind::RightBrace
+                    if depth == 0 =>
+                {
+                    break
+                }
+                TokenKind::RightParen | TokenKind::RightBracket | TokenKind::RightBrace => {
+                    depth -= 1
+                }
+                kind if depth == 0
+                    && stops.iter().any(|stop| {
+                        std::mem::discriminant(stop) == std::mem::discriminant(kind)
+                    }) =>
+                {
+                    break
+                }
+                _ => {}
+            }
+            self.advance();
+        }
+        self.wrap(checkpoint, SyntaxKind::Error);
+        Expr::Error(self.span_from(start))
+    }
+
     /// Parse a single item
     pub fn parse_item(&mut self) -> Result<Item, ParseError> {
-        let _is_pub = self.match_token(&TokenKind::Pub);
+        let checkpoint = self.checkpoint();
+        let item = self.parse_item_body()?;
+        let kind = match &item {
+            Item::Function(_) => SyntaxKind::FnDef,
+            Item::TypeDef(_) => SyntaxKind::TypeDef,
+            Item::Import(_) => SyntaxKind::Use,
+            Item::Constant(_) => SyntaxKind::ConstDef,
+            Item::Module(_) => SyntaxKind::Module,
+            Item::Trait(_) => SyntaxKind::TraitDef,
+            Item::Impl(_) => SyntaxKind::ImplDef,
+            Item::Error(_) => SyntaxKind::Error,
+        };
+        self.wrap(checkpoint, kind);
+        Ok(item)
+    }
+
+    /// Attributes, visibility and the item itself
+    fn parse_item_body(&mut self) -> Result<Item, ParseError> {
+        let attributes = self.parse_attributes()?;
+        let visibility = self.parse_visibility()?;
+        let start = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
 
         match self.peek().map(|t| &t.kind) {
-            Some(TokenKind::Karyakrama) => Ok(Item::Function(self.parse_function()?)),
-            Some(TokenKind::Prakara) => Ok(Item::TypeDef(self.parse_type_def()?)),
-            Some(TokenKind::Use) => Ok(Item::Import(self.parse_import()?)),
+            Some(TokenKind::Karyakrama) => {
+                let mut func = self.parse_function()?;
+                func.visibility = visibility;
+                func.attributes = attributes;
+                func.span = self.span_from(start);
+                Ok(Item::Function(func))
+            }
+            // `sthira kāryakrama`: a function const evaluation may call
+            Some(TokenKind::Const) if self.next_is(&TokenKind::Karyakrama) => {
+                self.advance();
+                let mut func = self.parse_function()?;
+                func.is_const = true;
+                func.visibility = visibility;
+                func.attributes = attributes;
+                func.span = self.span_from(start);
+                Ok(Item::Function(func))
+            }
+            Some(TokenKind::Prakara) => {
+                let mut typedef = self.parse_type_def()?;
+                typedef.visibility = visibility;
+                typedef.attributes = attributes;
+                typedef.span = self.span_from(start);
+                Ok(Item::TypeDef(typedef))
+            }
+            Some(TokenKind::Use) => {
+                let mut import = self.parse_import()?;
+                import.visibility = visibility;
+                import.attributes = attributes;
+                import.span = self.span_from(start);
+                Ok(Item::Import(import))
+            }
+            Some(TokenKind::Mod) => {
+                let mut module = self.parse_module_decl()?;
+                module.visibility = visibility;
+                module.attributes = attributes;
+                module.span = self.span_from(start);
+                Ok(Item::Module(module))
+            }
+            Some(TokenKind::Const) | Some(TokenKind::Static) => {
+                let mut constant = self.parse_constant()?;
+                constant.visibility = visibility;
+                constant.attributes = attributes;
+                constant.span = self.span_from(start);
+                Ok(Item::Constant(constant))
+            }
+            Some(TokenKind::Trait) => {
+                let mut trait_def = self.parse_trait()?;
+                trait_def.visibility = visibility;
+                trait_def.attributes = attributes;
+                trait_def.span = self.span_from(start);
+                Ok(Item::Trait(trait_def))
+            }
+            Some(TokenKind::Impl) => {
+                let mut impl_def = self.parse_impl()?;
+                impl_def.attributes = attributes;
+                impl_def.span = self.span_from(start);
+                Ok(Item::Impl(impl_def))
+            }
             Some(TokenKind::Identifier(s)) if s == "āyāti" => {
+                let span = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
                 self.advance();
-                self.parse_import_path()
+                let mut import = self.parse_import_path(span)?;
+                import.visibility = visibility;
+                import.attributes = attributes;
+                import.span = self.span_from(start);
+                Ok(Item::Import(import))
+            }
+            Some(kind) => {
+                let kind = kind.clone();
+                self.expect_one_of(&[
+                    TokenKind::Karyakrama,
+                    TokenKind::Prakara,
+                    TokenKind::Use,
+                    TokenKind::Mod,
+                    TokenKind::Const,
+                    TokenKind::Static,
+                    TokenKind::Trait,
+                    TokenKind::Impl,
+                ]);
+                Err(self.make_error(format!("Expected item, found {:?}", kind)))
             }
-            Some(kind) => Err(self.make_error(format!("Expected item, found {:?}", kind))),
             None => Err(self.make_error("Unexpected end of file".to_string())),
         }
     }
 
-    fn parse_import_path(&mut self) -> Result<Item, ParseError> {
+    /// Parse outer attributes: `#[name]`, `#[name(args)]`, `#[name = value]`
+    fn parse_attributes(&mut self) -> Result<Vec<Attribute>, ParseError> {
+        let mut attributes = Vec::new();
+        while self.check(&TokenKind::Hash) {
+            let checkpoint = self.checkpoint();
+            let start = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+            self.advance();
+            self.expect(&TokenKind::LeftBracket)?;
+            let meta = self.parse_meta_item()?;
+            let end = self.expect(&TokenKind::RightBracket)?.span.end;
+            self.wrap(checkpoint, SyntaxKind::Attribute);
+            attributes.push(Attribute {
+                meta,
+                span: Span::with_location(start.start, end, start.line, start.column),
+            });
+        }
+        Ok(attributes)
+    }
+
+    /// Parse the contents of an attribute
+    ///
+    /// Names may be keywords (`#[guṇa(rajas)]`), so they are taken from the
+    /// token text.
+    fn parse_meta_item(&mut self) -> Result<MetaItem, ParseError> {
+        if let Some(literal) = self.parse_meta_literal() {
+            return Ok(MetaItem::Literal(literal));
+        }
+        let name = match self.peek().cloned() {
+            Some(Token {
+                kind: TokenKind::Identifier(name),
+                span,
+                ..
+            }) => Identifier {
+                name,
+                affixes: AffixSequence::new(),
+                span,
+            },
+            Some(token) if token.lexeme.chars().next().is_some_and(char::is_alphabetic) => {
+                Identifier {
+                    name: token.lexeme,
+                    affixes: AffixSequence::new(),
+                    span: token.span,
+                }
+            }
+            Some(token) => {
+                return Err(
+                    self.make_error(format!("Expected attribute name, found {:?}", token.kind))
+                )
+            }
+            None => return Err(self.make_error("Expected attribute name".to_string())),
+        };
+        self.advance();
+
+        if self.match_token(&TokenKind::Equals) {
+            return match self.parse_meta_literal() {
+                Some(literal) => Ok(MetaItem::NameValue(name, literal)),
+                None => Err(self.make_error(format!(
+                    "Expected literal after `{} =` in attribute",
+                    name.name
+                ))),
+            };
+        }
+        if !self.match_token(&TokenKind::LeftParen) {
+            return Ok(MetaItem::Word(name));
+        }
+        let mut args = Vec::new();
+        while !self.check(&TokenKind::RightParen) && !self.is_eof() {
+            args.push(self.parse_meta_item()?);
+            if !self.match_token(&TokenKind::Comma) {
+                break;
+            }
+        }
+        self.expect(&TokenKind::RightParen)?;
+        Ok(MetaItem::List(name, args))
+    }
+
+    /// Literal argument of an attribute, if the next token is one
+    fn parse_meta_literal(&mut self) -> Option<Literal> {
+        let literal = match self.peek().map(|t| &t.kind)? {
+            TokenKind::IntLiteral(n) => Literal::Int(*n),
+            TokenKind::FloatLiteral(f) => Literal::Float(*f),
+            TokenKind::StringLiteral(s) => Literal::String(s.clone()),
+            TokenKind::BoolLiteral(b) => Literal::Bool(*b),
+            _ => return None,
+        };
+        self.advance();
+        Some(literal)
+    }
+
+    /// Parse `a::b::c`, `a::b::*` after `upayoga`/`āyāti`
+    fn parse_import_path(&mut self, span: Span) -> Result<ImportStmt, ParseError> {
         let mut path = Vec::new();
+        let mut glob = false;
         loop {
             let name = self.expect_identifier()?;
             path.push(name);
             if self.match_token(&TokenKind::ColonColon) {
                 if self.match_token(&TokenKind::Star) {
+                    glob = true;
                     break;
                 }
             } else {
@@ -120,69 +447,541 @@ impl Parser {
             }
         }
         self.match_token(&TokenKind::Semicolon);
-        Ok(Item::Import(ImportStmt {
+        Ok(ImportStmt {
             path,
             alias: None,
-            span: Span::dummy(),
-        }))
+            glob,
+            visibility: Visibility::Private,
+            attributes: Vec::new(),
+            span,
+        })
+    }
+
+    /// Parse `vibhāga name;` or an inline `vibhāga name { items }`
+    fn parse_module_decl(&mut self) -> Result<ModuleDef, ParseError> {
+        let span = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+        self.expect(&TokenKind::Mod)?;
+        let name = self.expect_name()?;
+        let mut items = Vec::new();
+        let inline = self.match_token(&TokenKind::LeftBrace);
+        if inline {
+            while !self.check(&TokenKind::RightBrace) && !self.is_eof() {
+                items.push(self.parse_item_recovering(true));
+            }
+            // As for blocks, an unclosed module at the end of the file is kept
+            if let Err(e) = self.expect(&TokenKind::RightBrace) {
+                self.errors.push(e);
+            }
+        } else {
+            self.expect(&TokenKind::Semicolon)?;
+        }
+        Ok(ModuleDef {
+            name,
+            items,
+            inline,
+            visibility: Visibility::Private,
+            attributes: Vec::new(),
+            span,
+        })
+    }
+
+    /// Parse `sthira NAMA: T = value;` or `static NAMA: T = value;`
+    fn parse_constant(&mut self) -> Result<ConstantDef, ParseError> {
+        let span = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+        let is_static = self.match_token(&TokenKind::Static);
+        if !is_static {
+            self.expect(&TokenKind::Const)?;
+        }
+        if is_static && self.check(&TokenKind::Mut) {
+            return Err(self.make_error(
+                "Mutable statics are not supported; pass the state explicitly".to_string(),
+            ));
+        }
+        let name = self.expect_name()?;
+        self.expect(&TokenKind::Colon)?;
+        let ty = self.parse_type()?;
+        self.expect(&TokenKind::Equals)?;
+        let value = self.parse_expr()?;
+        self.expect(&TokenKind::Semicolon)?;
+        Ok(ConstantDef {
+            name,
+            ty: Some(ty),
+            value,
+            is_static,
+            visibility: Visibility::Private,
+            attributes: Vec::new(),
+            span,
+        })
+    }
+
+    /// Parse `pub`, `pub(saṃkula)` / `pub(crate)` or `pub(uttara)` / `pub(super)`
+    fn parse_visibility(&mut self) -> Result<Visibility, ParseError> {
+        let checkpoint = self.checkpoint();
+        if !self.match_token(&TokenKind::Pub) {
+            return Ok(Visibility::Private);
+        }
+        if !self.match_token(&TokenKind::LeftParen) {
+            self.wrap(checkpoint, SyntaxKind::Visibility);
+            return Ok(Visibility::Public);
+        }
+        let visibility = match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::Crate) => Visibility::Crate,
+            Some(TokenKind::Super) => Visibility::Restricted,
+            Some(kind) => {
+                return Err(self.make_error(format!(
+                    "Expected `saṃkula` or `uttara` in visibility, found {:?}",
+                    kind
+                )))
+            }
+            None => return Err(self.make_error("Unexpected end of file".to_string())),
+        };
+        self.advance();
+        self.expect(&TokenKind::RightParen)?;
+        self.wrap(checkpoint, SyntaxKind::Visibility);
+        Ok(visibility)
     }
 
     /// Parse function definition
     pub fn parse_function(&mut self) -> Result<FunctionDef, ParseError> {
+        let (sig, contracts) = self.parse_method_signature()?;
+        let body = self.parse_block()?;
+
+        Ok(FunctionDef {
+            name: sig.name,
+            generics: sig.generics,
+            receiver: sig.receiver,
+            params: sig.params,
+            return_type: sig.return_type,
+            where_clauses: sig.where_clauses,
+            preconditions: contracts.preconditions,
+            postconditions: contracts.postconditions,
+            body,
+            is_const: false,
+            visibility: Visibility::Private,
+            attributes: sig.attributes,
+            span: sig.span,
+        })
+    }
+
+    /// Parse a function signature up to (but not including) its body
+    ///
+    /// Shared by free functions, impl methods and trait method declarations;
+    /// the contracts of its `yatra` clause are returned beside it.
+    fn parse_method_signature(&mut self) -> Result<(TraitMethodDef, Yatra), ParseError> {
         let start_span = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
         self.expect(&TokenKind::Karyakrama)?;
-        let name = self.expect_identifier()?;
+        let name = self.expect_name()?;
         let generics = if self.check(&TokenKind::LessThan) {
             self.parse_generics()?
         } else {
             Vec::new()
         };
+        let param_list = self.checkpoint();
         self.expect(&TokenKind::LeftParen)?;
+        let receiver = self.parse_receiver();
+        if receiver.is_some() && !self.check(&TokenKind::RightParen) {
+            self.expect(&TokenKind::Comma)?;
+        }
         let params = self.parse_parameters()?;
         self.expect(&TokenKind::RightParen)?;
+        self.wrap(param_list, SyntaxKind::ParamList);
+        let ret_type = self.checkpoint();
         let return_type = if self.match_token(&TokenKind::Arrow) {
-            Some(self.parse_type()?)
+            let ty = self.parse_type()?;
+            self.wrap(ret_type, SyntaxKind::RetType);
+            Some(ty)
         } else {
             None
         };
-        let body = self.parse_block()?;
+        let mut yatra = self.parse_yatra()?;
+        let where_clauses = std::mem::take(&mut yatra.predicates);
 
-        Ok(FunctionDef {
+        let signature = TraitMethodDef {
             name,
             generics,
+            receiver,
             params,
             return_type,
-            preconditions: Vec::new(),
-            postconditions: Vec::new(),
-            body,
+            where_clauses,
+            default_body: None,
+            attributes: Vec::new(),
+            span: start_span,
+        };
+        Ok((signature, yatra))
+    }
+
+    /// Parse method receiver: `sva`, `&sva` or `&mut sva`
+    fn parse_receiver(&mut self) -> Option<SelfParam> {
+        let checkpoint = self.checkpoint();
+        if self.check(&TokenKind::SelfValue) {
+            self.advance();
+            self.wrap(checkpoint, SyntaxKind::SelfParam);
+            return Some(SelfParam::Value);
+        }
+        if !self.check(&TokenKind::Ampersand) {
+            return None;
+        }
+        let is_mut = matches!(
+            self.tokens.get(self.position + 1).map(|t| &t.kind),
+            Some(TokenKind::Mut)
+        );
+        let self_pos = self.position + if is_mut { 2 } else { 1 };
+        if !matches!(
+            self.tokens.get(self_pos).map(|t| &t.kind),
+            Some(TokenKind::SelfValue)
+        ) {
+            return None;
+        }
+        self.position = self_pos + 1;
+        self.wrap(checkpoint, SyntaxKind::SelfParam);
+        Some(if is_mut {
+            SelfParam::RefMut
+        } else {
+            SelfParam::Ref
+        })
+    }
+
+    /// Parse trait definition (guṇa)
+    ///
+    /// ```text
+    /// guṇa Darśanīya<T>: Upādhi yatra T: Bound {
+    ///     prakāra Phala: Bound = Default;
+    ///     kāryakrama darśaya(&sva) -> sūtra;
+    ///     kāryakrama nāma(&sva) -> sūtra { phera "?" }
+    /// }
+    /// ```
+    pub fn parse_trait(&mut self) -> Result<TraitDef, ParseError> {
+        let start_span = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+        self.expect(&TokenKind::Trait)?;
+        let name = self.expect_name()?;
+        let generics = if self.check(&TokenKind::LessThan) {
+            self.parse_generics()?
+        } else {
+            Vec::new()
+        };
+        let supertraits = if self.match_token(&TokenKind::Colon) {
+            self.parse_bounds()?
+        } else {
+            Vec::new()
+        };
+        let where_clauses = self.parse_where_clauses()?;
+
+        self.expect(&TokenKind::LeftBrace)?;
+        let mut associated_types = Vec::new();
+        let mut methods = Vec::new();
+        while !self.check(&TokenKind::RightBrace) && !self.is_eof() {
+            let checkpoint = self.checkpoint();
+            let attributes = self.parse_attributes()?;
+            // Trait items share the trait's visibility
+            self.parse_visibility()?;
+            match self.peek().map(|t| &t.kind) {
+                Some(TokenKind::Prakara) => {
+                    let assoc_span = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+                    self.advance();
+                    let assoc_name = self.expect_name()?;
+                    let bounds = if self.match_token(&TokenKind::Colon) {
+                        self.parse_bounds()?
+                    } else {
+                        Vec::new()
+                    };
+                    let default = if self.match_token(&TokenKind::Equals) {
+                        Some(self.parse_type()?)
+                    } else {
+                        None
+                    };
+                    self.match_token(&TokenKind::Semicolon);
+                    self.wrap(checkpoint, SyntaxKind::AssocType);
+                    associated_types.push(AssociatedTypeDef {
+                        name: assoc_name,
+                        bounds,
+                        default,
+                        span: assoc_span,
+                    });
+                }
+                Some(TokenKind::Karyakrama) => {
+                    let (mut method, contracts) = self.parse_method_signature()?;
+                    contracts.reject_contracts()?;
+                    method.attributes = attributes;
+                    if self.check(&TokenKind::LeftBrace) {
+                        method.default_body = Some(self.parse_block()?);
+                    } else {
+                        self.expect(&TokenKind::Semicolon)?;
+                    }
+                    self.wrap(checkpoint, SyntaxKind::FnDef);
+                    method.span = self.span_from(method.span);
+                    methods.push(method);
+                }
+                Some(kind) => {
+                    return Err(self.make_error(format!("Expected trait item, found {:?}", kind)));
+                }
+                None => return Err(self.make_error("Unexpected end of file".to_string())),
+            }
+        }
+        self.expect(&TokenKind::RightBrace)?;
+
+        Ok(TraitDef {
+            name,
+            generics,
+            supertraits,
+            where_clauses,
+            associated_types,
+            methods,
+            visibility: Visibility::Private,
+            attributes: Vec::new(),
             span: start_span,
         })
     }
 
+    /// Parse implementation block (kriyānvaya)
+    ///
+    /// ```text
+    /// kriyānvaya<T> Darśanīya for Peṭī<T> yatra T: Darśanīya { ... }
+    /// kriyānvaya Bindu { ... }
+    /// ```
+    pub fn parse_impl(&mut self) -> Result<ImplDef, ParseError> {
+        let start_span = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+        self.expect(&TokenKind::Impl)?;
+        let generics = if self.check(&TokenKind::LessThan) {
+            self.parse_generics()?
+        } else {
+            Vec::new()
+        };
+
+        let first = self.parse_type()?;
+        let (trait_ref, self_ty) =
+            if self.match_token(&TokenKind::For) || self.match_identifier("kṛte") {
+                let bound = match first {
+                    Type::Named { name, generics, .. } => TypeBound {
+                        span: name.span,
+                        trait_name: name,
+                        generics,
+                    },
+                    _ => return Err(self.make_error("Expected trait name in impl".to_string())),
+                };
+                (Some(bound), self.parse_type()?)
+            } else {
+                (None, first)
+            };
+        let where_clauses = self.parse_where_clauses()?;
+
+        self.expect(&TokenKind::LeftBrace)?;
+        let mut associated_types = Vec::new();
+        let mut methods = Vec::new();
+        while !self.check(&TokenKind::RightBrace) && !self.is_eof() {
+            let checkpoint = self.checkpoint();
+            let attributes = self.parse_attributes()?;
+            let visibility = self.parse_visibility()?;
+            match self.peek().map(|t| &t.kind) {
+                Some(TokenKind::Prakara) => {
+                    let assoc_span = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+                    self.advance();
+                    let assoc_name = self.expect_name()?;
+                    self.expect(&TokenKind::Equals)?;
+                    let ty = self.parse_type()?;
+                    self.match_token(&TokenKind::Semicolon);
+                    self.wrap(checkpoint, SyntaxKind::AssocType);
+                    associated_types.push(ImplAssociatedTypeDef {
+                        name: assoc_name,
+                        ty,
+                        span: assoc_span,
+                    });
+                }
+                Some(TokenKind::Karyakrama) | Some(TokenKind::Const) => {
+                    let is_const = self.match_token(&TokenKind::Const);
+                    let mut method = self.parse_function()?;
+                    method.is_const = is_const;
+                    self.wrap(checkpoint, SyntaxKind::FnDef);
+                    method.visibility = visibility;
+                    method.attributes = attributes;
+                    method.span = self.span_from(method.span);
+                    methods.push(method);
+                }
+                Some(kind) => {
+                    return Err(self.make_error(format!("Expected impl item, found {:?}", kind)));
+                }
+                None => return Err(self.make_error("Unexpected end of file".to_string())),
+            }
+        }
+        self.expect(&TokenKind::RightBrace)?;
+
+        Ok(ImplDef {
+            generics,
+            trait_ref,
+            self_ty,
+            where_clauses,
+            associated_types,
+            methods,
+            attributes: Vec::new(),
+            span: start_span,
+        })
+    }
+
+    /// Parse trait bounds: `A + B<T> + C`
+    fn parse_bounds(&mut self) -> Result<Vec<TypeBound>, ParseError> {
+        let mut bounds = Vec::new();
+        loop {
+            let checkpoint = self.checkpoint();
+            let trait_name = self.expect_type_name()?;
+            let arg_list = self.checkpoint();
+            let generics = if self.match_token(&TokenKind::LessThan) {
+                let mut types = Vec::new();
+                while !self.check(&TokenKind::GreaterThan) && !self.is_eof() {
+                    types.push(self.parse_type()?);
+                    if !self.match_token(&TokenKind::Comma) {
+                        break;
+                    }
+                }
+                self.expect(&TokenKind::GreaterThan)?;
+                self.wrap(arg_list, SyntaxKind::TypeArgList);
+                types
+            } else {
+                Vec::new()
+            };
+            self.wrap(checkpoint, SyntaxKind::TypeBound);
+            bounds.push(TypeBound {
+                span: trait_name.span,
+                trait_name,
+                generics,
+            });
+            if !self.match_token(&TokenKind::Plus) {
+                break;
+            }
+        }
+        Ok(bounds)
+    }
+
+    /// Parse optional where-clause: `yatra T: A + B, U: C`
+    /// Parse a `yatra` clause of a trait, impl or type, which takes bounds only
+    fn parse_where_clauses(&mut self) -> Result<Vec<WherePredicate>, ParseError> {
+        let yatra = self.parse_yatra()?;
+        yatra.reject_contracts()?;
+        Ok(yatra.predicates)
+    }
+
+    /// Parse a `yatra` clause: bounds `T: A + B` and contracts
+    /// `pūrva cond` or `paścāt cond`, separated by commas
+    fn parse_yatra(&mut self) -> Result<Yatra, ParseError> {
+        let mut yatra = Yatra::default();
+        let clause = self.checkpoint();
+        if !self.match_token(&TokenKind::Where) {
+            return Ok(yatra);
+        }
+        while !self.check(&TokenKind::LeftBrace)
+            && !self.check(&TokenKind::Semicolon)
+            && !self.is_eof()
+        {
+            let checkpoint = self.checkpoint();
+            let span = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+            // A contract word, unless it is a type being bounded
+            let contract = match self.peek().map(|t| &t.kind) {
+                Some(TokenKind::Identifier(word)) if !self.next_is(&TokenKind::Colon) => {
+                    match word.as_str() {
+                        "pūrva" | "purva" | "requires" => Some(true),
+                        "paścāt" | "pascat" | "ensures" => Some(false),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            if let Some(on_entry) = contract {
+                self.advance();
+                let start = self.position;
+                let condition = self.parse_expr()?;
+                let contract = Contract {
+                    condition,
+                    text: self.source_text(start),
+                    span: self.span_from(span),
+                };
+                self.wrap(checkpoint, SyntaxKind::Contract);
+                if on_entry {
+                    yatra.preconditions.push(contract);
+                } else {
+                    yatra.postconditions.push(contract);
+                }
+            } else {
+                let ty = self.parse_type()?;
+                self.expect(&TokenKind::Colon)?;
+                let bounds = self.parse_bounds()?;
+                self.wrap(checkpoint, SyntaxKind::WherePredicate);
+                yatra.predicates.push(WherePredicate { ty, bounds, span });
+            }
+            if !self.match_token(&TokenKind::Comma) {
+                break;
+            }
+        }
+        self.wrap(clause, SyntaxKind::WhereClause);
+        Ok(yatra)
+    }
+
+    /// Text of the tokens from position `start` up to the current one,
+    /// spaced as in the source
+    fn source_text(&self, start: usize) -> String {
+        let mut text = String::new();
+        let mut end = None;
+        for token in &self.tokens[start..self.position] {
+            if end.is_some_and(|end| end < token.span.start) {
+                text.push(' ');
+            }
+            text.push_str(&token.lexeme);
+            end = Some(token.span.end);
+        }
+        text
+    }
+
     fn parse_generics(&mut self) -> Result<Vec<GenericParam>, ParseError> {
         let mut generics = Vec::new();
+        let list = self.checkpoint();
         self.expect(&TokenKind::LessThan)?;
         while !self.check(&TokenKind::GreaterThan) && !self.is_eof() {
-            let name = self.expect_identifier()?;
+            let checkpoint = self.checkpoint();
+            // Const parameter: `sthira N: saṅkhyā`
+            if self.match_token(&TokenKind::Const) {
+                let name = self.expect_name()?;
+                self.expect(&TokenKind::Colon)?;
+                let ty = self.parse_type()?;
+                self.wrap(checkpoint, SyntaxKind::GenericParam);
+                generics.push(GenericParam {
+                    span: name.span,
+                    name,
+                    bounds: Vec::new(),
+                    ty: Some(ty),
+                });
+                if !self.match_token(&TokenKind::Comma) {
+                    break;
+                }
+                continue;
+            }
+            let name = self.expect_name()?;
+            let bounds = if self.match_token(&TokenKind::Colon) {
+                self.parse_bounds()?
+            } else {
+                Vec::new()
+            };
+            self.wrap(checkpoint, SyntaxKind::GenericParam);
             generics.push(GenericParam {
+                span: name.span,
                 name,
-                bounds: Vec::new(),
-                span: Span::dummy(),
+                bounds,
+                ty: None,
             });
             if !self.match_token(&TokenKind::Comma) {
                 break;
             }
         }
         self.expect(&TokenKind::GreaterThan)?;
+        self.wrap(list, SyntaxKind::GenericParamList);
         Ok(generics)
     }
 
     fn parse_parameters(&mut self) -> Result<Vec<Parameter>, ParseError> {
         let mut params = Vec::new();
         while !self.check(&TokenKind::RightParen) && !self.is_eof() {
+            let checkpoint = self.checkpoint();
+            let attributes = self.parse_attributes()?;
+            let start = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
             // Try kāraka annotation before name (e.g., @kartṛ name: type)
             let karaka_before = self.parse_karaka_annotation();
-            let name = self.expect_identifier()?;
+            let name = self.expect_name()?;
 
             // Check for kāraka annotation in brackets after name (e.g., name[kartṛ]: type)
             let karaka_after = if self.match_token(&TokenKind::LeftBracket) {
@@ -198,11 +997,13 @@ impl Parser {
 
             self.expect(&TokenKind::Colon)?;
             let ty = self.parse_type()?;
+            self.wrap(checkpoint, SyntaxKind::Param);
             params.push(Parameter {
                 name,
                 ty,
                 karaka,
-                span: Span::dummy(),
+                attributes,
+                span: self.span_from(start),
             });
             if !self.match_token(&TokenKind::Comma) {
                 break;
@@ -264,9 +1065,12 @@ impl Parser {
     }
 
     /// Parse type definition
+    ///
+    /// `prakāra Bindu { x: T, y: T }` declares a struct and
+    /// `prakāra Rūpa { Vṛtta(T), Varga { bhuja: T }, Śūnya }` an enum.
     pub fn parse_type_def(&mut self) -> Result<TypeDef, ParseError> {
         self.expect(&TokenKind::Prakara)?;
-        let name = self.expect_identifier()?;
+        let name = self.expect_name()?;
         let generics = if self.check(&TokenKind::LessThan) {
             self.parse_generics()?
         } else {
@@ -274,67 +1078,139 @@ impl Parser {
         };
         self.expect(&TokenKind::LeftBrace)?;
         let mut fields = Vec::new();
+        let mut variants = Vec::new();
         while !self.check(&TokenKind::RightBrace) && !self.is_eof() {
-            let field_name = self.expect_identifier()?;
-            self.expect(&TokenKind::Colon)?;
-            let field_ty = self.parse_type()?;
-            fields.push(Field {
-                name: field_name,
-                ty: field_ty,
-                span: Span::dummy(),
-            });
+            let checkpoint = self.checkpoint();
+            let attributes = self.parse_attributes()?;
+            let start = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+            let visibility = self.parse_visibility()?;
+            let member = self.expect_name()?;
+            if self.match_token(&TokenKind::Colon) {
+                let field_ty = self.parse_type()?;
+                self.wrap(checkpoint, SyntaxKind::Field);
+                fields.push(Field {
+                    name: member,
+                    ty: field_ty,
+                    visibility,
+                    attributes,
+                    span: self.span_from(start),
+                });
+            } else {
+                let variant_fields = self.parse_variant_fields()?;
+                self.wrap(checkpoint, SyntaxKind::Variant);
+                variants.push(Variant {
+                    name: member,
+                    fields: variant_fields,
+                    span: self.span_from(start),
+                });
+            }
+            if !fields.is_empty() && !variants.is_empty() {
+                return Err(ParseError {
+                    message: format!(
+                        "`prakāra {}` mixes struct fields and enum variants",
+                        name.name
+                    ),
+                    span: start,
+                    expected: Vec::new(),
+                });
+            }
             self.match_token(&TokenKind::Comma);
         }
         self.expect(&TokenKind::RightBrace)?;
+        let body = if variants.is_empty() {
+            TypeBody::Struct(fields)
+        } else {
+            TypeBody::Enum(variants)
+        };
         Ok(TypeDef {
             name,
             generics,
-            body: TypeBody::Struct(fields),
+            body,
+            visibility: Visibility::Private,
+            attributes: Vec::new(),
             span: Span::dummy(),
         })
     }
 
-    fn parse_import(&mut self) -> Result<ImportStmt, ParseError> {
-        self.expect(&TokenKind::Use)?;
-        let mut path = Vec::new();
-        loop {
-            let name = self.expect_identifier()?;
-            path.push(name);
-            if self.match_token(&TokenKind::ColonColon) {
-                if self.match_token(&TokenKind::Star) {
-                    break;
+    /// Parse the payload of an enum variant: `(T, U)`, `{ a: T }` or nothing
+    ///
+    /// Tuple payloads get the field names `0`, `1`, ...
+    fn parse_variant_fields(&mut self) -> Result<Option<Vec<Field>>, ParseError> {
+        let list = self.checkpoint();
+        let tuple = self.match_token(&TokenKind::LeftParen);
+        if !tuple && !self.match_token(&TokenKind::LeftBrace) {
+            return Ok(None);
+        }
+        let close = if tuple {
+            TokenKind::RightParen
+        } else {
+            TokenKind::RightBrace
+        };
+        let mut fields = Vec::new();
+        while !self.check(&close) && !self.is_eof() {
+            let checkpoint = self.checkpoint();
+            let start = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+            let name = if tuple {
+                Identifier {
+                    name: fields.len().to_string(),
+                    affixes: AffixSequence::new(),
+                    span: start,
                 }
             } else {
+                let name = self.expect_name()?;
+                self.expect(&TokenKind::Colon)?;
+                name
+            };
+            let ty = self.parse_type()?;
+            self.wrap(checkpoint, SyntaxKind::Field);
+            fields.push(Field {
+                name,
+                ty,
+                visibility: Visibility::Public,
+                attributes: Vec::new(),
+                span: self.span_from(start),
+            });
+            if !self.match_token(&TokenKind::Comma) {
                 break;
             }
         }
-        self.match_token(&TokenKind::Semicolon);
-        Ok(ImportStmt {
-            path,
-            alias: None,
-            span: Span::dummy(),
-        })
+        self.expect(&close)?;
+        self.wrap(list, SyntaxKind::VariantFieldList);
+        Ok(Some(fields))
+    }
+
+    fn parse_import(&mut self) -> Result<ImportStmt, ParseError> {
+        let span = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+        self.expect(&TokenKind::Use)?;
+        self.parse_import_path(span)
     }
 
     fn parse_block(&mut self) -> Result<Block, ParseError> {
+        let block = self.checkpoint();
         self.expect(&TokenKind::LeftBrace)?;
         let mut stmts = Vec::new();
         while !self.check(&TokenKind::RightBrace) && !self.is_eof() {
+            let checkpoint = self.checkpoint();
+            let position = self.position;
+            let start = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
             match self.parse_stmt() {
                 Ok(stmt) => stmts.push(stmt),
                 Err(e) => {
-                    self.errors.push(e);
-                    while !self.is_eof()
-                        && !self.check(&TokenKind::Semicolon)
-                        && !self.check(&TokenKind::RightBrace)
-                    {
+                    let stops = [TokenKind::Semicolon];
+                    let error = self.recover_expr(checkpoint, start, e, &stops);
+                    stmts.push(Stmt::Expr(error));
+                    // A stray closing bracket stops recovery right away
+                    if !self.match_token(&TokenKind::Semicolon) && self.position == position {
                         self.advance();
                     }
-                    self.match_token(&TokenKind::Semicolon);
                 }
             }
         }
-        self.expect(&TokenKind::RightBrace)?;
+        // At the end of the file the block is kept, unclosed
+        if let Err(e) = self.expect(&TokenKind::RightBrace) {
+            self.errors.push(e);
+        }
+        self.wrap(block, SyntaxKind::Block);
         Ok(Block {
             stmts,
             span: Span::dummy(),
@@ -343,24 +1219,65 @@ impl Parser {
 
     /// Parse statement
     pub fn parse_stmt(&mut self) -> Result<Stmt, ParseError> {
+        let checkpoint = self.checkpoint();
+        let stmt = self.parse_stmt_body()?;
+        let kind = match &stmt {
+            Stmt::Let { .. } => SyntaxKind::LetStmt,
+            Stmt::Expr(_) => SyntaxKind::ExprStmt,
+            Stmt::Return { .. } => SyntaxKind::ReturnStmt,
+            Stmt::If { .. } => SyntaxKind::IfStmt,
+            Stmt::Loop { .. } => SyntaxKind::LoopStmt,
+            Stmt::Break { .. } => SyntaxKind::BreakStmt,
+            Stmt::Continue { .. } => SyntaxKind::ContinueStmt,
+        };
+        self.wrap(checkpoint, kind);
+        Ok(stmt)
+    }
+
+    fn parse_stmt_body(&mut self) -> Result<Stmt, ParseError> {
+        if self.check(&TokenKind::Hash) {
+            let attributes = self.parse_attributes()?;
+            if !self.check(&TokenKind::Let) {
+                return Err(self.make_error(
+                    "Attributes on statements are only supported on `let` bindings".to_string(),
+                ));
+            }
+            let mut stmt = self.parse_let_stmt()?;
+            if let Stmt::Let {
+                attributes: attrs, ..
+            } = &mut stmt
+            {
+                *attrs = attributes;
+            }
+            return Ok(stmt);
+        }
         match self.peek().map(|t| &t.kind) {
             Some(TokenKind::Let) => self.parse_let_stmt(),
             Some(TokenKind::Phera) => self.parse_return_stmt(),
             Some(TokenKind::Yad) => self.parse_if_stmt(),
-            Some(TokenKind::Cala) => self.parse_loop_stmt(),
+            Some(TokenKind::Cala) | Some(TokenKind::Label(_)) => self.parse_loop_stmt(),
             Some(TokenKind::Break) => {
-                self.advance();
+                let start = self.expect(&TokenKind::Break)?.span;
+                let label = self.parse_label();
+                // `break value`, unless the statement ends here
+                let value = if self.check(&TokenKind::Semicolon)
+                    || self.check(&TokenKind::RightBrace)
+                    || self.is_eof()
+                {
+                    None
+                } else {
+                    Some(self.parse_expr()?)
+                };
+                let span = self.span_from(start);
                 self.match_token(&TokenKind::Semicolon);
-                Ok(Stmt::Break {
-                    span: Span::dummy(),
-                })
+                Ok(Stmt::Break { label, value, span })
             }
             Some(TokenKind::Continue) => {
-                self.advance();
+                let start = self.expect(&TokenKind::Continue)?.span;
+                let label = self.parse_label();
+                let span = self.span_from(start);
                 self.match_token(&TokenKind::Semicolon);
-                Ok(Stmt::Continue {
-                    span: Span::dummy(),
-                })
+                Ok(Stmt::Continue { label, span })
             }
             _ => {
                 let expr = self.parse_expr()?;
@@ -371,15 +1288,21 @@ impl Parser {
     }
 
     fn parse_let_stmt(&mut self) -> Result<Stmt, ParseError> {
-        self.expect(&TokenKind::Let)?;
-        let name = self.expect_identifier()?;
+        let start = self.expect(&TokenKind::Let)?.span;
+        let name = self.expect_name()?;
         let ty = if self.match_token(&TokenKind::Colon) {
             Some(self.parse_type()?)
         } else {
             None
         };
         let value = if self.match_token(&TokenKind::Equals) {
-            Some(self.parse_expr()?)
+            // Keep the binding even if its initializer is broken
+            let checkpoint = self.checkpoint();
+            let start = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+            Some(match self.parse_expr() {
+                Ok(value) => value,
+                Err(e) => self.recover_expr(checkpoint, start, e, &[TokenKind::Semicolon]),
+            })
         } else {
             None
         };
@@ -388,11 +1311,13 @@ impl Parser {
             name,
             ty,
             value,
-            span: Span::dummy(),
+            attributes: Vec::new(),
+            span: self.span_from(start),
         })
     }
 
     fn parse_return_stmt(&mut self) -> Result<Stmt, ParseError> {
+        let span = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
         self.expect(&TokenKind::Phera)?;
         let value = if !self.check(&TokenKind::Semicolon) && !self.check(&TokenKind::RightBrace) {
             Some(self.parse_expr()?)
@@ -400,18 +1325,27 @@ impl Parser {
             None
         };
         self.match_token(&TokenKind::Semicolon);
-        Ok(Stmt::Return {
-            value,
-            span: Span::dummy(),
-        })
+        Ok(Stmt::Return { value, span })
     }
 
     fn parse_if_stmt(&mut self) -> Result<Stmt, ParseError> {
-        self.expect(&TokenKind::Yad)?;
+        let start = self.expect(&TokenKind::Yad)?.span;
         let condition = self.parse_expr()?;
         let then_block = self.parse_block()?;
         let else_block = if self.match_token(&TokenKind::Anyatha) {
-            Some(self.parse_block()?)
+            if self.check(&TokenKind::Yad) {
+                // `anyathā yad ...`: the chain continues in the else block
+                let checkpoint = self.checkpoint();
+                let start = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+                let nested = self.parse_if_stmt()?;
+                self.wrap(checkpoint, SyntaxKind::IfStmt);
+                Some(Block {
+                    stmts: vec![nested],
+                    span: self.span_from(start),
+                })
+            } else {
+                Some(self.parse_block()?)
+            }
         } else {
             None
         };
@@ -419,11 +1353,109 @@ impl Parser {
             condition,
             then_block,
             else_block,
-            span: Span::dummy(),
+            span: self.span_from(start),
         })
     }
 
+    /// Parse `yad` in expression position: `yad c { a } anyathā { b }`
+    fn parse_if_expr(&mut self) -> Result<Expr, ParseError> {
+        let Stmt::If {
+            condition,
+            then_block,
+            else_block,
+            span,
+        } = self.parse_if_stmt()?
+        else {
+            unreachable!("parse_if_stmt returns Stmt::If");
+        };
+        let else_expr = else_block.map(|block| {
+            Box::new(match block.stmts.as_slice() {
+                // Keep `anyathā yad` chains as nested if expressions
+                [Stmt::If { .. }] => Self::if_stmt_to_expr(block.stmts.into_iter().next().unwrap()),
+                _ => Expr::Block(block),
+            })
+        });
+        Ok(Expr::If {
+            condition: Box::new(condition),
+            then_expr: Box::new(Expr::Block(then_block)),
+            else_expr,
+            span,
+        })
+    }
+
+    /// The expression form of a nested `yad` statement
+    fn if_stmt_to_expr(stmt: Stmt) -> Expr {
+        match stmt {
+            Stmt::If {
+                condition,
+                then_block,
+                else_block,
+                span,
+            } => Expr::If {
+                condition: Box::new(condition),
+                then_expr: Box::new(Expr::Block(then_block)),
+                else_expr: else_block.map(|block| {
+                    Box::new(match block.stmts.as_slice() {
+                        [Stmt::If { .. }] => {
+                            Self::if_stmt_to_expr(block.stmts.into_iter().next().unwrap())
+                        }
+                        _ => Expr::Block(block),
+                    })
+                }),
+                span,
+            },
+            other => Expr::Block(Block {
+                span: other.span(),
+                stmts: vec![other],
+            }),
+        }
+    }
+
+    /// Parse `cala` in expression position, whose value a `break` gives
+    fn parse_loop_expr(&mut self) -> Result<Expr, ParseError> {
+        let Stmt::Loop {
+            label,
+            kind,
+            body,
+            span,
+        } = self.parse_loop_stmt()?
+        else {
+            unreachable!("parse_loop_stmt returns Stmt::Loop");
+        };
+        Ok(Expr::Loop {
+            label,
+            kind: Box::new(kind),
+            body,
+            span,
+        })
+    }
+
+    /// Optional loop label after `break` or `continue`
+    fn parse_label(&mut self) -> Option<Identifier> {
+        match self.peek().cloned() {
+            Some(Token {
+                kind: TokenKind::Label(name),
+                span,
+                ..
+            }) => {
+                self.advance();
+                Some(Identifier {
+                    name,
+                    affixes: AffixSequence::new(),
+                    span,
+                })
+            }
+            _ => None,
+        }
+    }
+
     fn parse_loop_stmt(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+        // `'bāhya: cala ...`
+        let label = self.parse_label();
+        if label.is_some() {
+            self.expect(&TokenKind::Colon)?;
+        }
         self.expect(&TokenKind::Cala)?;
         let kind = if self.check(&TokenKind::LeftBrace) {
             LoopKind::Infinite
@@ -442,11 +1474,11 @@ impl Parser {
                 }
 
                 // Check for range syntax: start..end or start..=end
-                let start = self.parse_primary()?;
+                let start = self.parse_or()?;
 
                 if self.match_token(&TokenKind::DotDotEquals) {
                     // Inclusive range: start..=end
-                    let end = self.parse_primary()?;
+                    let end = self.parse_or()?;
                     LoopKind::Range {
                         binding,
                         start: Box::new(start),
@@ -455,7 +1487,7 @@ impl Parser {
                     }
                 } else if self.match_token(&TokenKind::DotDot) {
                     // Exclusive range: start..end
-                    let end = self.parse_primary()?;
+                    let end = self.parse_or()?;
                     LoopKind::Range {
                         binding,
                         start: Box::new(start),
@@ -478,9 +1510,10 @@ impl Parser {
         };
         let body = self.parse_block()?;
         Ok(Stmt::Loop {
+            label,
             kind,
             body,
-            span: Span::dummy(),
+            span: self.span_from(start),
         })
     }
 
@@ -492,29 +1525,96 @@ impl Parser {
         }
     }
 
+    /// Consume the current token if it is a specific identifier
+    fn match_identifier(&mut self, name: &str) -> bool {
+        if self.check_identifier(name) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
     /// Parse expression
     pub fn parse_expr(&mut self) -> Result<Expr, ParseError> {
         self.parse_assignment()
     }
 
     fn parse_assignment(&mut self) -> Result<Expr, ParseError> {
-        let left = self.parse_or()?;
-        if self.match_token(&TokenKind::Equals) {
-            let right = self.parse_assignment()?;
-            return Ok(Expr::Binary {
-                left: Box::new(left),
-                op: BinaryOp::Assign,
-                right: Box::new(right),
-                span: Span::dummy(),
-            });
+        let checkpoint = self.checkpoint();
+        let left = self.parse_range()?;
+        let op = match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::Equals) => BinaryOp::Assign,
+            Some(TokenKind::PlusEquals) => BinaryOp::AddAssign,
+            Some(TokenKind::MinusEquals) => BinaryOp::SubAssign,
+            Some(TokenKind::StarEquals) => BinaryOp::MulAssign,
+            Some(TokenKind::SlashEquals) => BinaryOp::DivAssign,
+            _ => return Ok(left),
+        };
+        self.advance();
+        let right = self.parse_assignment()?;
+        self.wrap(checkpoint, SyntaxKind::BinExpr);
+        let span = self.span_from(left.span());
+        Ok(Expr::Binary {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+            span,
+        })
+    }
+
+    /// Parse a range (`a..b`, `a..=b`, `..b`, `a..`), binding looser than `||`
+    fn parse_range(&mut self) -> Result<Expr, ParseError> {
+        let checkpoint = self.checkpoint();
+        let start_span = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+        let start = if self.check(&TokenKind::DotDot) || self.check(&TokenKind::DotDotEquals) {
+            None
+        } else {
+            let start = self.parse_or()?;
+            if !self.check(&TokenKind::DotDot) && !self.check(&TokenKind::DotDotEquals) {
+                return Ok(start);
+            }
+            Some(Box::new(start))
+        };
+        let inclusive = !self.match_token(&TokenKind::DotDot);
+        if inclusive {
+            self.expect(&TokenKind::DotDotEquals)?;
         }
-        Ok(left)
+        // An open end is followed by whatever closes the expression
+        let open = matches!(
+            self.peek().map(|t| &t.kind),
+            None | Some(
+                TokenKind::RightBracket
+                    | TokenKind::RightParen
+                    | TokenKind::RightBrace
+                    | TokenKind::LeftBrace
+                    | TokenKind::Comma
+                    | TokenKind::Semicolon
+            )
+        );
+        let end = if open {
+            if inclusive {
+                return Err(self.make_error("`..=` needs an end bound".to_string()));
+            }
+            None
+        } else {
+            Some(Box::new(self.parse_or()?))
+        };
+        self.wrap(checkpoint, SyntaxKind::RangeExpr);
+        Ok(Expr::Range {
+            start,
+            end,
+            inclusive,
+            span: self.span_from(start_span),
+        })
     }
 
     fn parse_or(&mut self) -> Result<Expr, ParseError> {
+        let checkpoint = self.checkpoint();
         let mut left = self.parse_and()?;
         while self.match_token(&TokenKind::PipePipe) {
             let right = self.parse_and()?;
+            self.wrap(checkpoint, SyntaxKind::BinExpr);
             left = Expr::Binary {
                 left: Box::new(left),
                 op: BinaryOp::Or,
@@ -526,9 +1626,11 @@ impl Parser {
     }
 
     fn parse_and(&mut self) -> Result<Expr, ParseError> {
+        let checkpoint = self.checkpoint();
         let mut left = self.parse_equality()?;
         while self.match_token(&TokenKind::AmpAmp) {
             let right = self.parse_equality()?;
+            self.wrap(checkpoint, SyntaxKind::BinExpr);
             left = Expr::Binary {
                 left: Box::new(left),
                 op: BinaryOp::And,
@@ -540,6 +1642,7 @@ impl Parser {
     }
 
     fn parse_equality(&mut self) -> Result<Expr, ParseError> {
+        let checkpoint = self.checkpoint();
         let mut left = self.parse_comparison()?;
         loop {
             let op = if self.match_token(&TokenKind::EqualsEquals) {
@@ -550,6 +1653,7 @@ impl Parser {
                 break;
             };
             let right = self.parse_comparison()?;
+            self.wrap(checkpoint, SyntaxKind::BinExpr);
             left = Expr::Binary {
                 left: Box::new(left),
                 op,
@@ -561,6 +1665,7 @@ impl Parser {
     }
 
     fn parse_comparison(&mut self) -> Result<Expr, ParseError> {
+        let checkpoint = self.checkpoint();
         let mut left = self.parse_term()?;
         loop {
             let op = if self.match_token(&TokenKind::LessThan) {
@@ -575,6 +1680,7 @@ impl Parser {
                 break;
             };
             let right = self.parse_term()?;
+            self.wrap(checkpoint, SyntaxKind::BinExpr);
             left = Expr::Binary {
                 left: Box::new(left),
                 op,
@@ -586,6 +1692,7 @@ impl Parser {
     }
 
     fn parse_term(&mut self) -> Result<Expr, ParseError> {
+        let checkpoint = self.checkpoint();
         let mut left = self.parse_factor()?;
         loop {
             let op = if self.match_token(&TokenKind::Plus) {
@@ -596,6 +1703,7 @@ impl Parser {
                 break;
             };
             let right = self.parse_factor()?;
+            self.wrap(checkpoint, SyntaxKind::BinExpr);
             left = Expr::Binary {
                 left: Box::new(left),
                 op,
@@ -607,7 +1715,8 @@ impl Parser {
     }
 
     fn parse_factor(&mut self) -> Result<Expr, ParseError> {
-        let mut left = self.parse_unary()?;
+        let checkpoint = self.checkpoint();
+        let mut left = self.parse_cast()?;
         loop {
             let op = if self.match_token(&TokenKind::Star) {
                 BinaryOp::Mul
@@ -618,7 +1727,8 @@ impl Parser {
             } else {
                 break;
             };
-            let right = self.parse_unary()?;
+            let right = self.parse_cast()?;
+            self.wrap(checkpoint, SyntaxKind::BinExpr);
             left = Expr::Binary {
                 left: Box::new(left),
                 op,
@@ -629,9 +1739,28 @@ impl Parser {
         Ok(left)
     }
 
+    /// Parse `value as T`, binding tighter than `*` and looser than `-`
+    fn parse_cast(&mut self) -> Result<Expr, ParseError> {
+        let checkpoint = self.checkpoint();
+        let mut expr = self.parse_unary()?;
+        while self.match_token(&TokenKind::As) {
+            let ty = self.parse_type()?;
+            self.wrap(checkpoint, SyntaxKind::CastExpr);
+            let span = self.span_from(expr.span());
+            expr = Expr::Cast {
+                expr: Box::new(expr),
+                ty,
+                span,
+            };
+        }
+        Ok(expr)
+    }
+
     fn parse_unary(&mut self) -> Result<Expr, ParseError> {
+        let checkpoint = self.checkpoint();
         if self.match_token(&TokenKind::Minus) {
             let operand = self.parse_unary()?;
+            self.wrap(checkpoint, SyntaxKind::PrefixExpr);
             return Ok(Expr::Unary {
                 op: UnaryOp::Neg,
                 operand: Box::new(operand),
@@ -640,51 +1769,93 @@ impl Parser {
         }
         if self.match_token(&TokenKind::Bang) {
             let operand = self.parse_unary()?;
+            self.wrap(checkpoint, SyntaxKind::PrefixExpr);
             return Ok(Expr::Unary {
                 op: UnaryOp::Not,
                 operand: Box::new(operand),
                 span: Span::dummy(),
             });
         }
+        if self.check(&TokenKind::Ampersand) {
+            // Borrow: &x, &mut x
+            let span = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+            self.advance();
+            let op = if self.match_token(&TokenKind::Mut) {
+                UnaryOp::RefMut
+            } else {
+                UnaryOp::Ref
+            };
+            let operand = self.parse_unary()?;
+            self.wrap(checkpoint, SyntaxKind::PrefixExpr);
+            return Ok(Expr::Unary {
+                op,
+                operand: Box::new(operand),
+                span,
+            });
+        }
         self.parse_call()
     }
 
     fn parse_call(&mut self) -> Result<Expr, ParseError> {
+        let checkpoint = self.checkpoint();
         let mut expr = self.parse_primary()?;
         loop {
+            let arg_list = self.checkpoint();
             if self.match_token(&TokenKind::LeftParen) {
                 let args = self.parse_args()?;
-                self.expect(&TokenKind::RightParen)?;
+                let close = self.expect(&TokenKind::RightParen)?;
+                self.wrap(arg_list, SyntaxKind::ArgList);
+                self.wrap(checkpoint, SyntaxKind::CallExpr);
+                // From the callee to the closing parenthesis
+                let start = expr.span();
+                let span =
+                    Span::with_location(start.start, close.span.end, start.line, start.column);
                 expr = Expr::Call {
                     callee: Box::new(expr),
                     args,
-                    span: Span::dummy(),
+                    span,
                 };
             } else if self.match_token(&TokenKind::Dot) {
                 let field = self.expect_identifier()?;
+                let arg_list = self.checkpoint();
                 if self.match_token(&TokenKind::LeftParen) {
                     let args = self.parse_args()?;
                     self.expect(&TokenKind::RightParen)?;
+                    self.wrap(arg_list, SyntaxKind::ArgList);
+                    self.wrap(checkpoint, SyntaxKind::MethodCallExpr);
+                    let span = self.span_from(expr.span());
                     expr = Expr::MethodCall {
                         receiver: Box::new(expr),
                         method: field,
                         args,
-                        span: Span::dummy(),
+                        span,
                     };
                 } else {
+                    self.wrap(checkpoint, SyntaxKind::FieldExpr);
+                    let span = self.span_from(expr.span());
                     expr = Expr::FieldAccess {
                         object: Box::new(expr),
                         field,
-                        span: Span::dummy(),
+                        span,
                     };
                 }
             } else if self.match_token(&TokenKind::LeftBracket) {
                 let index = self.parse_expr()?;
                 self.expect(&TokenKind::RightBracket)?;
+                self.wrap(checkpoint, SyntaxKind::IndexExpr);
+                let span = self.span_from(expr.span());
                 expr = Expr::Index {
                     object: Box::new(expr),
                     index: Box::new(index),
-                    span: Span::dummy(),
+                    span,
+                };
+            } else if self.match_token(&TokenKind::Question) {
+                self.wrap(checkpoint, SyntaxKind::TryExpr);
+                // From the operand to the `?`
+                let span = self.span_from(expr.span());
+                expr = Expr::Try {
+                    expr: Box::new(expr),
+                    span,
                 };
             } else {
                 break;
@@ -694,9 +1865,19 @@ impl Parser {
     }
 
     fn parse_args(&mut self) -> Result<Vec<Expr>, ParseError> {
+        self.parse_args_until(&TokenKind::RightParen)
+    }
+
+    /// Comma-separated arguments up to, but not including, `close`
+    fn parse_args_until(&mut self, close: &TokenKind) -> Result<Vec<Expr>, ParseError> {
         let mut args = Vec::new();
-        while !self.check(&TokenKind::RightParen) && !self.is_eof() {
-            args.push(self.parse_expr()?);
+        while !self.check(close) && !self.is_eof() {
+            let checkpoint = self.checkpoint();
+            let start = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+            match self.parse_expr() {
+                Ok(arg) => args.push(arg),
+                Err(e) => args.push(self.recover_expr(checkpoint, start, e, &[TokenKind::Comma])),
+            }
             if !self.match_token(&TokenKind::Comma) {
                 break;
             }
@@ -705,39 +1886,64 @@ impl Parser {
     }
 
     fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        let checkpoint = self.checkpoint();
         let token = self.peek().cloned();
         match token.as_ref().map(|t| &t.kind) {
             Some(TokenKind::IntLiteral(n)) => {
                 let n = *n;
                 self.advance();
+                self.wrap(checkpoint, SyntaxKind::Literal);
                 Ok(Expr::Literal(Literal::Int(n)))
             }
             Some(TokenKind::FloatLiteral(f)) => {
                 let f = *f;
                 self.advance();
+                self.wrap(checkpoint, SyntaxKind::Literal);
                 Ok(Expr::Literal(Literal::Float(f)))
             }
             Some(TokenKind::StringLiteral(s)) => {
                 let s = s.clone();
                 self.advance();
+                self.wrap(checkpoint, SyntaxKind::Literal);
                 Ok(Expr::Literal(Literal::String(s)))
             }
             Some(TokenKind::BoolLiteral(b)) => {
                 let b = *b;
                 self.advance();
+                self.wrap(checkpoint, SyntaxKind::Literal);
                 Ok(Expr::Literal(Literal::Bool(b)))
             }
+            Some(TokenKind::SelfValue) => {
+                let span = token.as_ref().map(|t| t.span).unwrap_or(Span::dummy());
+                self.advance();
+                self.wrap(checkpoint, SyntaxKind::PathExpr);
+                Ok(Expr::Identifier(Identifier {
+                    name: "sva".to_string(),
+                    affixes: AffixSequence::new(),
+                    span,
+                }))
+            }
+            Some(TokenKind::SelfType) => {
+                let span = token.as_ref().map(|t| t.span).unwrap_or(Span::dummy());
+                self.advance();
+                let ident = Identifier {
+                    name: "Self".to_string(),
+                    affixes: AffixSequence::new(),
+                    span,
+                };
+                let path = self.parse_path_rest(ident)?;
+                self.wrap(checkpoint, SyntaxKind::PathExpr);
+                Ok(Expr::Identifier(path))
+            }
             Some(TokenKind::Identifier(_)) => {
                 let ident = self.expect_identifier()?;
+                let ident = self.parse_path_rest(ident)?;
                 if self.match_token(&TokenKind::Bang) {
-                    // Macro call - parse args and treat as a function call
-                    self.parse_macro_args()?;
-                    Ok(Expr::Call {
-                        callee: Box::new(Expr::Identifier(ident)),
-                        args: Vec::new(),
-                        span: Span::dummy(),
-                    })
+                    let call = self.parse_macro_call(ident)?;
+                    self.wrap(checkpoint, SyntaxKind::MacroCall);
+                    Ok(call)
                 } else {
+                    self.wrap(checkpoint, SyntaxKind::PathExpr);
                     Ok(Expr::Identifier(ident))
                 }
             }
@@ -745,6 +1951,7 @@ impl Parser {
                 self.advance();
                 let expr = self.parse_expr()?;
                 self.expect(&TokenKind::RightParen)?;
+                self.wrap(checkpoint, SyntaxKind::ParenExpr);
                 Ok(expr)
             }
             Some(TokenKind::LeftBracket) => {
@@ -757,74 +1964,467 @@ impl Parser {
                     }
                 }
                 self.expect(&TokenKind::RightBracket)?;
+                self.wrap(checkpoint, SyntaxKind::ArrayExpr);
                 Ok(Expr::Array {
                     elements,
                     span: Span::dummy(),
                 })
             }
             Some(TokenKind::Mudrana) => {
+                let span = token.as_ref().map(|t| t.span).unwrap_or(Span::dummy());
                 self.advance();
                 let ident = Identifier {
                     name: "mudraṇa".to_string(),
                     affixes: AffixSequence::new(),
-                    span: Span::dummy(),
+                    span,
                 };
                 if self.match_token(&TokenKind::Bang) {
-                    // Macro call - parse args and treat as a function call
-                    self.parse_macro_args()?;
-                    Ok(Expr::Call {
-                        callee: Box::new(Expr::Identifier(ident)),
-                        args: Vec::new(),
-                        span: Span::dummy(),
-                    })
+                    let call = self.parse_macro_call(ident)?;
+                    self.wrap(checkpoint, SyntaxKind::MacroCall);
+                    Ok(call)
                 } else {
+                    self.wrap(checkpoint, SyntaxKind::PathExpr);
                     Ok(Expr::Identifier(ident))
                 }
             }
-            Some(kind) => Err(self.make_error(format!("Unexpected token: {:?}", kind))),
+            Some(TokenKind::Pipe) | Some(TokenKind::PipePipe) => self.parse_lambda(),
+            Some(TokenKind::Match) => self.parse_match_expr(),
+            Some(TokenKind::Yad) => {
+                let expr = self.parse_if_expr()?;
+                self.wrap(checkpoint, SyntaxKind::IfExpr);
+                Ok(expr)
+            }
+            Some(TokenKind::Cala) | Some(TokenKind::Label(_)) => {
+                let expr = self.parse_loop_expr()?;
+                self.wrap(checkpoint, SyntaxKind::LoopExpr);
+                Ok(expr)
+            }
+            Some(TokenKind::Saha) => {
+                let expr = self.parse_saha_expr()?;
+                self.wrap(checkpoint, SyntaxKind::SahaExpr);
+                Ok(expr)
+            }
+            Some(kind) => {
+                self.expect_one_of(&[
+                    TokenKind::IntLiteral(0),
+                    TokenKind::FloatLiteral(0.0),
+                    TokenKind::StringLiteral(String::new()),
+                    TokenKind::BoolLiteral(false),
+                    TokenKind::Identifier(String::new()),
+                    TokenKind::SelfValue,
+                    TokenKind::SelfType,
+                    TokenKind::LeftParen,
+                    TokenKind::LeftBracket,
+                    TokenKind::Mudrana,
+                    TokenKind::Pipe,
+                    TokenKind::PipePipe,
+                    TokenKind::Match,
+                    TokenKind::Yad,
+                    TokenKind::Cala,
+                    TokenKind::Saha,
+                ]);
+                Err(self.make_error(format!("Unexpected token: {:?}", kind)))
+            }
             None => Err(self.make_error("Unexpected end of file".to_string())),
         }
     }
 
-    fn parse_macro_args(&mut self) -> Result<(), ParseError> {
-        if self.match_token(&TokenKind::LeftParen) {
-            let mut depth = 1;
-            while depth > 0 && !self.is_eof() {
-                match self.peek().map(|t| &t.kind) {
-                    Some(TokenKind::LeftParen) => {
-                        depth += 1;
-                        self.advance();
-                    }
-                    Some(TokenKind::RightParen) => {
-                        depth -= 1;
-                        self.advance();
-                    }
-                    _ => {
-                        self.advance();
+    /// Parse a concurrent task: `saha { body }` or `saha f(args)`
+    fn parse_saha_expr(&mut self) -> Result<Expr, ParseError> {
+        let start = self.expect(&TokenKind::Saha)?.span;
+        let task = if self.check(&TokenKind::LeftBrace) {
+            let mut block = self.parse_block()?;
+            block.span = self.span_from(start);
+            Expr::Block(block)
+        } else {
+            let call = self.parse_call()?;
+            if !matches!(call, Expr::Call { .. } | Expr::MethodCall { .. }) {
+                return Err(self.make_error("`saha` runs a block or a function call".to_string()));
+            }
+            call
+        };
+        Ok(Expr::Saha {
+            task: Box::new(task),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parse a closure: `|x: T, y| body` or `|| body`
+    ///
+    /// Parameter types may be left for inference.
+    fn parse_lambda(&mut self) -> Result<Expr, ParseError> {
+        let closure = self.checkpoint();
+        let start = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+        let mut params = Vec::new();
+        if !self.match_token(&TokenKind::PipePipe) {
+            self.expect(&TokenKind::Pipe)?;
+            while !self.check(&TokenKind::Pipe) && !self.is_eof() {
+                let checkpoint = self.checkpoint();
+                let name = self.expect_name()?;
+                let ty = if self.match_token(&TokenKind::Colon) {
+                    self.parse_type()?
+                } else {
+                    Type::Inferred
+                };
+                self.wrap(checkpoint, SyntaxKind::Param);
+                params.push(Parameter {
+                    span: name.span,
+                    name,
+                    ty,
+                    karaka: None,
+                    attributes: Vec::new(),
+                });
+                if !self.match_token(&TokenKind::Comma) {
+                    break;
+                }
+            }
+            self.expect(&TokenKind::Pipe)?;
+        }
+        self.wrap(closure, SyntaxKind::ParamList);
+        let body = if self.check(&TokenKind::LeftBrace) {
+            Expr::Block(self.parse_block()?)
+        } else {
+            self.parse_expr()?
+        };
+        self.wrap(closure, SyntaxKind::ClosureExpr);
+        let end = self.tokens[self.position - 1].span.end;
+        Ok(Expr::Lambda {
+            params,
+            body: Box::new(body),
+            span: Span::with_location(start.start, end, start.line, start.column),
+        })
+    }
+
+    /// Extend an identifier with `::segment` path components (`Bindu::nava`)
+    fn parse_path_rest(&mut self, mut ident: Identifier) -> Result<Identifier, ParseError> {
+        while self.check(&TokenKind::ColonColon) {
+            self.advance();
+            let segment = self.expect_identifier()?;
+            ident.name.push_str("::");
+            ident.name.push_str(&segment.name);
+        }
+        Ok(ident)
+    }
+
+    /// Lower a built-in mantra call `name!(args)` to ordinary AST.
+    ///
+    /// User `mantra!` macros are already expanded on the token stream, so
+    /// anything left here is a built-in (or an unknown macro, kept as a call):
+    /// - `vinyāsa!`/`format!` → [`Expr::Format`]
+    /// - `mudraṇa!(fmt, a..)` → `mudrā(vinyāsa!(fmt + "\n", a..))`, and
+    ///   `mudraṇa!(e)` prints `e` as if by `mudraṇa!("{}", e)`
+    /// - `dṛḍha!`/`assert!`/`assert_eq!`/`assert_ne!` → `{ yad !cond { vikṣepa(msg) } }`
+    fn parse_macro_call(&mut self, ident: Identifier) -> Result<Expr, ParseError> {
+        let span = ident.span;
+        let arg_list = self.checkpoint();
+        // `name!(…)`, `name![…]` and `name!{…}` all take the same arguments
+        let close = match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::LeftParen) => TokenKind::RightParen,
+            Some(TokenKind::LeftBracket) => TokenKind::RightBracket,
+            Some(TokenKind::LeftBrace) => TokenKind::RightBrace,
+            _ => {
+                self.expect_one_of(&[
+                    TokenKind::LeftParen,
+                    TokenKind::LeftBracket,
+                    TokenKind::LeftBrace,
+                ]);
+                return Err(self.make_error(format!(
+                    "`{}!` takes its arguments in `( )`, `[ ]` or `{{ }}`",
+                    ident.name
+                )));
+            }
+        };
+        self.advance();
+        let template_span = self.peek().map(|t| t.span).unwrap_or(span);
+        let args = self.parse_args_until(&close)?;
+        self.expect(&close)?;
+        self.wrap(arg_list, SyntaxKind::ArgList);
+
+        let call = |name: &str, args: Vec<Expr>| Expr::Call {
+            callee: Box::new(Expr::Identifier(Identifier {
+                name: name.to_string(),
+                affixes: AffixSequence::new(),
+                span,
+            })),
+            args,
+            span,
+        };
+        let assertion = |condition: Expr, message: Option<Expr>, default: String| {
+            let message = message.unwrap_or(Expr::Literal(Literal::String(default)));
+            Expr::Block(Block {
+                stmts: vec![Stmt::If {
+                    condition,
+                    then_block: Block {
+                        stmts: vec![Stmt::Expr(call("vikṣepa", vec![message]))],
+                        span,
+                    },
+                    else_block: None,
+                    span,
+                }],
+                span,
+            })
+        };
+
+        match ident.name.as_str() {
+            "mudraṇa" | "mudrana" | "print" => {
+                let (mut pieces, args) = match &args[..] {
+                    [Expr::Literal(Literal::String(_)), ..] => {
+                        self.parse_format(&ident.name, args, template_span)?
                     }
+                    [_] => (
+                        vec![FormatPiece::Arg {
+                            index: 0,
+                            spec: FormatSpec::default(),
+                        }],
+                        args,
+                    ),
+                    [] => (Vec::new(), args),
+                    _ => return Err(self.template_error(&ident.name, template_span)),
+                };
+                pieces.push(FormatPiece::Text("\n".to_string()));
+                Ok(call("mudrā", vec![Expr::Format { pieces, args, span }]))
+            }
+            "vinyāsa" | "vinyasa" | "format" => {
+                let (pieces, args) = self.parse_format(&ident.name, args, template_span)?;
+                Ok(Expr::Format { pieces, args, span })
+            }
+            "oṁ" | "om" | "dṛḍha" | "assert" | "parikṣā" | "pariksha" | "debug_assert" => {
+                let mut args = args.into_iter();
+                let condition = args.next().ok_or_else(|| {
+                    self.make_error(format!("{}! expects a condition", ident.name))
+                })?;
+                Ok(assertion(
+                    Expr::Unary {
+                        op: UnaryOp::Not,
+                        operand: Box::new(condition),
+                        span,
+                    },
+                    args.next(),
+                    format!("assertion failed at line {}", span.line),
+                ))
+            }
+            "assert_eq" | "assert_ne" => {
+                if args.len() < 2 {
+                    return Err(self.make_error(format!("{}! expects two operands", ident.name)));
+                }
+                let mut args = args.into_iter();
+                let (left, right) = (args.next().unwrap(), args.next().unwrap());
+                let (op, relation) = if ident.name == "assert_eq" {
+                    (BinaryOp::Ne, "==")
+                } else {
+                    (BinaryOp::Eq, "!=")
+                };
+                Ok(assertion(
+                    Expr::Binary {
+                        left: Box::new(left),
+                        op,
+                        right: Box::new(right),
+                        span,
+                    },
+                    args.next(),
+                    format!(
+                        "assertion `left {} right` failed at line {}",
+                        relation, span.line
+                    ),
+                ))
+            }
+            _ => Ok(call(&ident.name, args)),
+        }
+    }
+
+    /// Resolve the template of a formatting mantra against its arguments
+    ///
+    /// `{}` takes the next argument and `{1}` a given one; `{nāma}` captures
+    /// the variable, which becomes an extra argument. Every explicit
+    /// argument must be used.
+    fn parse_format(
+        &self,
+        mantra: &str,
+        args: Vec<Expr>,
+        template_span: Span,
+    ) -> Result<(Vec<FormatPiece>, Vec<Expr>), ParseError> {
+        let mut args = args.into_iter();
+        let Some(Expr::Literal(Literal::String(template))) = args.next() else {
+            return Err(self.template_error(mantra, template_span));
+        };
+        let mut args: Vec<Expr> = args.collect();
+        let explicit = args.len();
+        let error = |message: String| ParseError {
+            message,
+            span: template_span,
+            expected: Vec::new(),
+        };
+        let template = format::parse_template(&template).map_err(|e| {
+            error(format!(
+                "invalid format string: {} (at byte {})",
+                e.message, e.offset
+            ))
+        })?;
+
+        let mut used = vec![false; explicit];
+        let mut next = 0;
+        let mut pieces = Vec::with_capacity(template.len());
+        for piece in template {
+            let (arg, spec) = match piece {
+                TemplatePiece::Text(text) => {
+                    pieces.push(FormatPiece::Text(text));
+                    continue;
+                }
+                TemplatePiece::Placeholder { arg, spec } => (arg, spec),
+            };
+            let index = match arg {
+                ArgRef::Next => {
+                    next += 1;
+                    next - 1
+                }
+                ArgRef::Index(index) => index,
+                ArgRef::Name(name) => {
+                    let captured = args[explicit..]
+                        .iter()
+                        .position(|arg| matches!(arg, Expr::Identifier(id) if id.name == name));
+                    let index = match captured {
+                        Some(i) => explicit + i,
+                        None => {
+                            args.push(Expr::Identifier(Identifier {
+                                name,
+                                affixes: AffixSequence::new(),
+                                span: template_span,
+                            }));
+                            args.len() - 1
+                        }
+                    };
+                    pieces.push(FormatPiece::Arg { index, spec });
+                    continue;
                 }
+            };
+            if index >= explicit {
+                return Err(error(format!(
+                    "format string refers to argument {} but {}! has {} argument{}",
+                    index,
+                    mantra,
+                    explicit,
+                    if explicit == 1 { "" } else { "s" }
+                )));
             }
+            used[index] = true;
+            pieces.push(FormatPiece::Arg { index, spec });
+        }
+        if let Some(unused) = used.iter().position(|used| !used) {
+            return Err(error(format!(
+                "argument {} of {}! is never used in the format string",
+                unused, mantra
+            )));
+        }
+        Ok((pieces, args))
+    }
+
+    fn template_error(&self, mantra: &str, span: Span) -> ParseError {
+        ParseError {
+            message: format!("{}! expects a string literal as its format string", mantra),
+            span,
+            expected: Vec::new(),
         }
-        Ok(())
     }
 
     /// Parse type
     pub fn parse_type(&mut self) -> Result<Type, ParseError> {
-        let name = self.expect_type_name()?;
+        let checkpoint = self.checkpoint();
+        // Function types: kāryakrama(A, B) -> R
+        if self.match_token(&TokenKind::Karyakrama) {
+            self.expect(&TokenKind::LeftParen)?;
+            let mut params = Vec::new();
+            while !self.check(&TokenKind::RightParen) && !self.is_eof() {
+                params.push(self.parse_type()?);
+                if !self.match_token(&TokenKind::Comma) {
+                    break;
+                }
+            }
+            self.expect(&TokenKind::RightParen)?;
+            let return_type = if self.match_token(&TokenKind::Arrow) {
+                self.parse_type()?
+            } else {
+                Type::Named {
+                    name: Identifier {
+                        name: "()".to_string(),
+                        affixes: AffixSequence::new(),
+                        span: Span::dummy(),
+                    },
+                    generics: Vec::new(),
+                    affixes: AffixSequence::new(),
+                }
+            };
+            self.wrap(checkpoint, SyntaxKind::FnType);
+            return Ok(Type::Function {
+                params,
+                return_type: Box::new(return_type),
+            });
+        }
+        // Reference types: &T, &mut T
+        if self.match_token(&TokenKind::Ampersand) {
+            let mutable = self.match_token(&TokenKind::Mut);
+            let inner = self.parse_type()?;
+            self.wrap(checkpoint, SyntaxKind::RefType);
+            return Ok(Type::Reference {
+                inner: Box::new(inner),
+                mutable,
+                lifetime: None,
+            });
+        }
+        // Fixed arrays and slices: [T; N], [T]; a length other than a
+        // literal is left for const evaluation
+        if self.match_token(&TokenKind::LeftBracket) {
+            let element = Box::new(self.parse_type()?);
+            let (size, length) = if self.match_token(&TokenKind::Semicolon) {
+                match self.peek().map(|t| &t.kind) {
+                    Some(TokenKind::IntLiteral(n))
+                        if *n >= 0 && self.next_is(&TokenKind::RightBracket) =>
+                    {
+                        let n = *n as usize;
+                        self.advance();
+                        (Some(n), None)
+                    }
+                    _ => (None, Some(Box::new(self.parse_expr()?))),
+                }
+            } else {
+                (None, None)
+            };
+            self.expect(&TokenKind::RightBracket)?;
+            let kind = if size.is_some() || length.is_some() {
+                SyntaxKind::ArrayType
+            } else {
+                SyntaxKind::SliceType
+            };
+            self.wrap(checkpoint, kind);
+            return Ok(Type::Array {
+                element,
+                size,
+                length,
+            });
+        }
+        let mut name = self.expect_type_name()?;
+        // Associated type paths: Self::Item
+        while self.match_token(&TokenKind::ColonColon) {
+            let segment = self.expect_identifier()?;
+            name.name.push_str("::");
+            name.name.push_str(&segment.name);
+        }
+        let arg_list = self.checkpoint();
         let generics = if self.match_token(&TokenKind::LessThan) {
             let mut types = Vec::new();
             while !self.check(&TokenKind::GreaterThan) && !self.is_eof() {
-                types.push(self.parse_type()?);
+                types.push(self.parse_type_arg()?);
                 if !self.match_token(&TokenKind::Comma) {
                     break;
                 }
             }
             self.expect(&TokenKind::GreaterThan)?;
+            self.wrap(arg_list, SyntaxKind::TypeArgList);
             types
         } else {
             Vec::new()
         };
-        let affixes = self.parse_type_affixes();
+        let affixes = self.parse_type_affixes()?;
+        self.wrap(checkpoint, SyntaxKind::PathType);
         Ok(Type::Named {
             name,
             generics,
@@ -832,6 +2432,27 @@ impl Parser {
         })
     }
 
+    /// A type argument: a type, or the value of a const parameter written
+    /// as an integer or a block (`{ N * 2 }`)
+    ///
+    /// A bare name may be either; it is parsed as a type, and const
+    /// evaluation takes it as a value when it names a `sthira` item.
+    fn parse_type_arg(&mut self) -> Result<Type, ParseError> {
+        let checkpoint = self.checkpoint();
+        let start = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
+        let value = match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::IntLiteral(_) | TokenKind::Minus) => self.parse_unary()?,
+            Some(TokenKind::LeftBrace) => {
+                let mut block = self.parse_block()?;
+                block.span = self.span_from(start);
+                Expr::Block(block)
+            }
+            _ => return self.parse_type(),
+        };
+        self.wrap(checkpoint, SyntaxKind::ConstArg);
+        Ok(Type::Const(Box::new(value)))
+    }
+
     /// Expect a type name (identifier or type keyword like saṅkhyā, sūtra, etc.)
     fn expect_type_name(&mut self) -> Result<Identifier, ParseError> {
         if let Some(token) = self.peek().cloned() {
@@ -842,11 +2463,8 @@ impl Parser {
                 TokenKind::Sutra => ("sūtra".to_string(), token.span),
                 TokenKind::Suci => ("sūci".to_string(), token.span),
                 TokenKind::Sarani => ("sāraṇī".to_string(), token.span),
-                TokenKind::Vikalpa => ("vikalpa".to_string(), token.span),
-                TokenKind::Phala => ("phala".to_string(), token.span),
-                TokenKind::Saphala => ("saphala".to_string(), token.span),
-                TokenKind::Viphala => ("viphala".to_string(), token.span),
                 TokenKind::Truti => ("truṭi".to_string(), token.span),
+                TokenKind::SelfType => ("Self".to_string(), token.span),
                 _ => {
                     return Err(
                         self.make_error(format!("Expected type name, found {:?}", token.kind))
@@ -864,15 +2482,27 @@ impl Parser {
         }
     }
 
-    fn parse_type_affixes(&mut self) -> AffixSequence {
+    /// Parse `-a-k-t32` style affixes after a type name
+    ///
+    /// Words that are not known affixes are skipped.
+    fn parse_type_affixes(&mut self) -> Result<AffixSequence, ParseError> {
+        let mut affixes = AffixSequence::new();
         while self.match_token(&TokenKind::Minus) {
-            if let Some(TokenKind::Identifier(_)) = self.peek().map(|t| &t.kind) {
-                self.advance();
-            } else {
+            let Some(token) = self.peek().cloned() else {
                 break;
+            };
+            // `sūtra` is also a keyword, so affixes are read from the token text
+            let word = match token.kind {
+                TokenKind::Identifier(word) => word,
+                _ if Affix::parse(&token.lexeme).is_some() => token.lexeme,
+                _ => break,
+            };
+            self.advance();
+            if let Some(affix) = Affix::parse(&word) {
+                affixes.push(affix).map_err(|msg| self.make_error(msg))?;
             }
         }
-        AffixSequence::new()
+        Ok(affixes)
     }
 
     /// Parse identifier
@@ -881,6 +2511,30 @@ impl Parser {
     }
 
     // Helpers
+
+    /// Remember the current position as the start of a CST node
+    fn checkpoint(&self) -> Checkpoint {
+        match &self.cst {
+            Some(events) => events.checkpoint(self.position),
+            None => cst::Events::default().checkpoint(self.position),
+        }
+    }
+
+    /// Record everything consumed since `checkpoint` as a `kind` node
+    fn wrap(&mut self, checkpoint: Checkpoint, kind: SyntaxKind) {
+        if let Some(events) = &mut self.cst {
+            events.wrap(checkpoint, kind, self.position);
+        }
+    }
+
+    /// Identifier at a definition site, recorded as a `Name` node
+    fn expect_name(&mut self) -> Result<Identifier, ParseError> {
+        let checkpoint = self.checkpoint();
+        let name = self.expect_identifier()?;
+        self.wrap(checkpoint, SyntaxKind::Name);
+        Ok(name)
+    }
+
     fn is_eof(&self) -> bool {
         self.position >= self.tokens.len()
             || matches!(self.peek().map(|t| &t.kind), Some(TokenKind::Eof))
@@ -890,18 +2544,55 @@ impl Parser {
         self.tokens.get(self.position)
     }
 
+    /// Span from `start` to the end of the last consumed token
+    fn span_from(&self, start: Span) -> Span {
+        let end = self
+            .position
+            .checked_sub(1)
+            .and_then(|i| self.tokens.get(i))
+            .map_or(start.end, |token| token.span.end);
+        Span::with_location(start.start, end.max(start.end), start.line, start.column)
+    }
+
     fn advance(&mut self) -> Option<&Token> {
         let token = self.tokens.get(self.position);
         self.position += 1;
         token
     }
 
+    /// Whether the token after the current one is of `kind`
+    fn next_is(&self, kind: &TokenKind) -> bool {
+        self.tokens
+            .get(self.position + 1)
+            .is_some_and(|t| std::mem::discriminant(&t.kind) == std::mem::discriminant(kind))
+    }
+
     fn check(&self, kind: &TokenKind) -> bool {
+        self.expect_one_of(std::slice::from_ref(kind));
         self.peek()
             .map(|t| std::mem::discriminant(&t.kind) == std::mem::discriminant(kind))
             .unwrap_or(false)
     }
 
+    /// Note `kinds` as acceptable at the current position, for the
+    /// `expected` set of an error reported here
+    fn expect_one_of(&self, kinds: &[TokenKind]) {
+        let mut expected = self.expected.borrow_mut();
+        if expected.0 != self.position {
+            *expected = (self.position, Vec::new());
+        }
+        for kind in kinds {
+            let discriminant = std::mem::discriminant(kind);
+            if !expected
+                .1
+                .iter()
+                .any(|seen| std::mem::discriminant(seen) == discriminant)
+            {
+                expected.1.push(kind.clone());
+            }
+        }
+    }
+
     fn match_token(&mut self, kind: &TokenKind) -> bool {
         if self.check(kind) {
             self.advance();
@@ -924,6 +2615,7 @@ impl Parser {
     }
 
     fn expect_identifier(&mut self) -> Result<Identifier, ParseError> {
+        self.expect_one_of(&[TokenKind::Identifier(String::new())]);
         match self.peek().map(|t| t.clone()) {
             Some(Token {
                 kind: TokenKind::Identifier(name),
@@ -957,8 +2649,6 @@ impl Parser {
     /// Convert certain keywords to identifier names when used in variable contexts
     fn keyword_as_identifier(kind: &TokenKind) -> Option<String> {
         match kind {
-            TokenKind::Phala => Some("phala".to_string()),
-            TokenKind::Vikalpa => Some("vikalpa".to_string()),
             TokenKind::Sankhya => Some("sankhya".to_string()),
             TokenKind::Sutra => Some("sutra".to_string()),
             TokenKind::Suci => Some("suci".to_string()),
@@ -968,9 +2658,15 @@ impl Parser {
     }
 
     fn make_error(&self, message: String) -> ParseError {
+        let expected = self.expected.borrow();
         ParseError {
             message,
             span: self.peek().map(|t| t.span).unwrap_or(Span::dummy()),
+            expected: if expected.0 == self.position {
+                expected.1.clone()
+            } else {
+                Vec::new()
+            },
         }
     }
 
@@ -979,49 +2675,42 @@ impl Parser {
     // ========================================================================
 
     /// Parse a pattern (pratyabhijñā)
-    /// Grammar: pattern := or_pattern
+    /// Grammar: pattern := or_pattern ('yad' expr)?
     pub fn parse_pattern(&mut self) -> Result<Pattern, ParseError> {
-        self.parse_or_pattern()
+        let pattern = self.parse_or_pattern()?;
+        if self.match_token(&TokenKind::Yad) {
+            let condition = self.parse_expr()?;
+            Ok(Pattern::Guard {
+                pattern: Box::new(pattern),
+                condition: Box::new(condition),
+            })
+        } else {
+            Ok(pattern)
+        }
     }
 
     /// Parse or-pattern: pattern | pattern | ...
     fn parse_or_pattern(&mut self) -> Result<Pattern, ParseError> {
-        let mut patterns = vec![self.parse_guard_pattern()?];
+        let checkpoint = self.checkpoint();
+        let mut patterns = vec![self.parse_binding_pattern()?];
 
         while self.match_token(&TokenKind::Pipe) {
-            patterns.push(self.parse_guard_pattern()?);
+            patterns.push(self.parse_binding_pattern()?);
         }
 
         if patterns.len() == 1 {
             Ok(patterns.remove(0))
         } else {
+            self.wrap(checkpoint, SyntaxKind::OrPat);
             Ok(Pattern::Or(patterns))
         }
     }
 
-    /// Parse guard pattern: pattern if condition
-    fn parse_guard_pattern(&mut self) -> Result<Pattern, ParseError> {
-        let pattern = self.parse_binding_pattern()?;
-
-        // Check for guard: 'if' or 'yadi' or 'yad'
-        if self.match_token(&TokenKind::Yad) || self.check_identifier("if") {
-            if self.check_identifier("if") {
-                self.advance();
-            }
-            let condition = self.parse_expr()?;
-            Ok(Pattern::Guard {
-                pattern: Box::new(pattern),
-                condition: Box::new(condition),
-            })
-        } else {
-            Ok(pattern)
-        }
-    }
-
     /// Parse binding pattern: name @ pattern or just primary_pattern
     fn parse_binding_pattern(&mut self) -> Result<Pattern, ParseError> {
-        // Check for mutable binding: mut name
-        let mutable = self.match_token(&TokenKind::Let);
+        let checkpoint = self.checkpoint();
+        // Check for mutable binding: parivartya name
+        let mutable = self.match_token(&TokenKind::Mut);
 
         // Try to parse as identifier with possible @ subpattern
         if let Some(Token {
@@ -1030,10 +2719,12 @@ impl Parser {
         }) = self.peek()
         {
             let name = self.expect_identifier()?;
+            let name = self.parse_path_rest(name)?;
 
             // Check for @ subpattern
             if self.match_token(&TokenKind::At) {
                 let subpattern = self.parse_primary_pattern()?;
+                self.wrap(checkpoint, SyntaxKind::IdentPat);
                 return Ok(Pattern::Binding {
                     name,
                     mutable,
@@ -1043,10 +2734,21 @@ impl Parser {
 
             // Check if this is a constructor pattern
             if self.check(&TokenKind::LeftParen) || self.check(&TokenKind::LeftBrace) {
-                return self.parse_constructor_pattern_rest(name);
+                let pattern = self.parse_constructor_pattern_rest(name)?;
+                self.wrap(checkpoint, Self::pattern_kind(&pattern));
+                return Ok(pattern);
+            }
+
+            // A constant as the start of a range
+            if self.check(&TokenKind::DotDot) || self.check(&TokenKind::DotDotEquals) {
+                self.wrap(checkpoint, SyntaxKind::PathPat);
+                let pattern = self.parse_range_pattern_rest(Some(Pattern::Identifier(name)))?;
+                self.wrap(checkpoint, SyntaxKind::RangePat);
+                return Ok(pattern);
             }
 
-            // Simple binding or identifier
+            // Binding, or a unit variant: resolved by the type checker
+            self.wrap(checkpoint, SyntaxKind::IdentPat);
             return Ok(Pattern::Binding {
                 name,
                 mutable,
@@ -1059,13 +2761,27 @@ impl Parser {
 
     /// Parse primary pattern
     fn parse_primary_pattern(&mut self) -> Result<Pattern, ParseError> {
+        let checkpoint = self.checkpoint();
         match self.peek().map(|t| &t.kind) {
             // Wildcard: _
             Some(TokenKind::Underscore) => {
                 self.advance();
+                self.wrap(checkpoint, SyntaxKind::WildcardPat);
                 Ok(Pattern::Wildcard)
             }
 
+            // Range with no start: ..end or ..=end
+            Some(TokenKind::DotDot) | Some(TokenKind::DotDotEquals)
+                if matches!(
+                    self.tokens.get(self.position + 1).map(|t| &t.kind),
+                    Some(TokenKind::IntLiteral(_) | TokenKind::Minus)
+                ) =>
+            {
+                let pattern = self.parse_range_pattern_rest(None)?;
+                self.wrap(checkpoint, SyntaxKind::RangePat);
+                Ok(pattern)
+            }
+
             // Rest pattern: ..
             Some(TokenKind::DotDot) => {
                 self.advance();
@@ -1075,40 +2791,48 @@ impl Parser {
                     ..
                 }) = self.peek()
                 {
-                    let name = self.expect_identifier()?;
+                    let name = self.expect_name()?;
+                    self.wrap(checkpoint, SyntaxKind::RestPat);
                     Ok(Pattern::Binding {
                         name,
                         mutable: false,
                         subpattern: Some(Box::new(Pattern::Rest)),
                     })
                 } else {
+                    self.wrap(checkpoint, SyntaxKind::RestPat);
                     Ok(Pattern::Rest)
                 }
             }
 
             // Literal patterns
-            Some(TokenKind::IntLiteral(n)) => {
-                let n = *n;
-                self.advance();
+            Some(TokenKind::IntLiteral(_)) | Some(TokenKind::Minus) => {
+                let n = self.parse_int_pattern_literal()?;
+                self.wrap(checkpoint, SyntaxKind::LiteralPat);
                 // Check for range pattern
                 if self.check(&TokenKind::DotDot) || self.check(&TokenKind::DotDotEquals) {
-                    return self.parse_range_pattern_rest(Some(Pattern::Literal(Literal::Int(n))));
+                    let pattern =
+                        self.parse_range_pattern_rest(Some(Pattern::Literal(Literal::Int(n))))?;
+                    self.wrap(checkpoint, SyntaxKind::RangePat);
+                    return Ok(pattern);
                 }
                 Ok(Pattern::Literal(Literal::Int(n)))
             }
             Some(TokenKind::FloatLiteral(f)) => {
                 let f = *f;
                 self.advance();
+                self.wrap(checkpoint, SyntaxKind::LiteralPat);
                 Ok(Pattern::Literal(Literal::Float(f)))
             }
             Some(TokenKind::StringLiteral(s)) => {
                 let s = s.clone();
                 self.advance();
+                self.wrap(checkpoint, SyntaxKind::LiteralPat);
                 Ok(Pattern::Literal(Literal::String(s)))
             }
             Some(TokenKind::BoolLiteral(b)) => {
                 let b = *b;
                 self.advance();
+                self.wrap(checkpoint, SyntaxKind::LiteralPat);
                 Ok(Pattern::Literal(Literal::Bool(b)))
             }
 
@@ -1118,25 +2842,28 @@ impl Parser {
                 if self.check(&TokenKind::RightParen) {
                     // Unit pattern ()
                     self.advance();
+                    self.wrap(checkpoint, SyntaxKind::TuplePat);
                     return Ok(Pattern::Tuple(vec![]));
                 }
 
-                let first = self.parse_pattern()?;
+                let first = self.parse_or_pattern()?;
 
                 if self.match_token(&TokenKind::Comma) {
                     // Tuple pattern
                     let mut patterns = vec![first];
                     while !self.check(&TokenKind::RightParen) && !self.is_eof() {
-                        patterns.push(self.parse_pattern()?);
+                        patterns.push(self.parse_or_pattern()?);
                         if !self.match_token(&TokenKind::Comma) {
                             break;
                         }
                     }
                     self.expect(&TokenKind::RightParen)?;
+                    self.wrap(checkpoint, SyntaxKind::TuplePat);
                     Ok(Pattern::Tuple(patterns))
                 } else {
                     // Grouping
                     self.expect(&TokenKind::RightParen)?;
+                    self.wrap(checkpoint, SyntaxKind::ParenPat);
                     Ok(first)
                 }
             }
@@ -1151,6 +2878,7 @@ impl Parser {
 
                 while !self.check(&TokenKind::RightBracket) && !self.is_eof() {
                     if self.check(&TokenKind::DotDot) {
+                        let rest = self.checkpoint();
                         self.advance();
                         seen_rest = true;
                         // Check for binding after ..
@@ -1159,15 +2887,16 @@ impl Parser {
                             ..
                         }) = self.peek()
                         {
-                            let name = self.expect_identifier()?;
+                            let name = self.expect_name()?;
                             middle = Some(Box::new(Pattern::Binding {
                                 name,
                                 mutable: false,
                                 subpattern: None,
                             }));
                         }
+                        self.wrap(rest, SyntaxKind::RestPat);
                     } else {
-                        let pat = self.parse_pattern()?;
+                        let pat = self.parse_or_pattern()?;
                         if seen_rest {
                             after.push(pat);
                         } else {
@@ -1180,6 +2909,7 @@ impl Parser {
                     }
                 }
                 self.expect(&TokenKind::RightBracket)?;
+                self.wrap(checkpoint, SyntaxKind::SlicePat);
 
                 if seen_rest {
                     Ok(Pattern::Slice {
@@ -1195,8 +2925,9 @@ impl Parser {
             // Reference pattern: &x or &mut x
             Some(TokenKind::Ampersand) => {
                 self.advance();
-                let mutable = self.match_token(&TokenKind::Let);
+                let mutable = self.match_token(&TokenKind::Mut);
                 let pattern = self.parse_primary_pattern()?;
+                self.wrap(checkpoint, SyntaxKind::RefPat);
                 Ok(Pattern::Ref {
                     mutable,
                     pattern: Box::new(pattern),
@@ -1206,14 +2937,21 @@ impl Parser {
             // Identifier or constructor pattern
             Some(TokenKind::Identifier(_)) => {
                 let name = self.expect_identifier()?;
+                let name = self.parse_path_rest(name)?;
 
                 // Check for constructor pattern
                 if self.check(&TokenKind::LeftParen) || self.check(&TokenKind::LeftBrace) {
-                    self.parse_constructor_pattern_rest(name)
+                    let pattern = self.parse_constructor_pattern_rest(name)?;
+                    self.wrap(checkpoint, Self::pattern_kind(&pattern));
+                    Ok(pattern)
                 } else if self.check(&TokenKind::DotDot) || self.check(&TokenKind::DotDotEquals) {
                     // Range pattern starting with identifier
-                    self.parse_range_pattern_rest(Some(Pattern::Identifier(name)))
+                    self.wrap(checkpoint, SyntaxKind::PathPat);
+                    let pattern = self.parse_range_pattern_rest(Some(Pattern::Identifier(name)))?;
+                    self.wrap(checkpoint, SyntaxKind::RangePat);
+                    Ok(pattern)
                 } else {
+                    self.wrap(checkpoint, SyntaxKind::PathPat);
                     Ok(Pattern::Identifier(name))
                 }
             }
@@ -1229,16 +2967,17 @@ impl Parser {
             // Tuple-like variant: Some(x) or Point(x, y)
             let mut fields = Vec::new();
             while !self.check(&TokenKind::RightParen) && !self.is_eof() {
-                fields.push(self.parse_pattern()?);
+                fields.push(self.parse_or_pattern()?);
                 if !self.match_token(&TokenKind::Comma) {
                     break;
                 }
             }
             self.expect(&TokenKind::RightParen)?;
 
+            let (enum_name, variant) = Self::split_variant_path(name);
             Ok(Pattern::Variant {
-                enum_name: None,
-                variant: name,
+                enum_name,
+                variant,
                 fields: VariantFields::Tuple(fields),
             })
         } else if self.match_token(&TokenKind::LeftBrace) {
@@ -1252,10 +2991,11 @@ impl Parser {
                     break;
                 }
 
+                let checkpoint = self.checkpoint();
                 let field_name = self.expect_identifier()?;
 
                 let pattern = if self.match_token(&TokenKind::Colon) {
-                    self.parse_pattern()?
+                    self.parse_or_pattern()?
                 } else {
                     // Shorthand: { x } means { x: x }
                     Pattern::Binding {
@@ -1264,6 +3004,7 @@ impl Parser {
                         subpattern: None,
                     }
                 };
+                self.wrap(checkpoint, SyntaxKind::RecordPatField);
 
                 fields.push((field_name, pattern));
 
@@ -1273,13 +3014,65 @@ impl Parser {
             }
             self.expect(&TokenKind::RightBrace)?;
 
-            Ok(Pattern::Struct { name, fields, rest })
+            // `Rang::Bindu { .. }` names a variant, `Bindu { .. }` a struct
+            match Self::split_variant_path(name) {
+                (Some(enum_name), variant) => Ok(Pattern::Variant {
+                    enum_name: Some(enum_name),
+                    variant,
+                    fields: VariantFields::Struct(fields),
+                }),
+                (None, name) => Ok(Pattern::Struct { name, fields, rest }),
+            }
         } else {
             // Just an identifier
             Ok(Pattern::Identifier(name))
         }
     }
 
+    /// CST kind of a pattern parsed by `parse_constructor_pattern_rest`
+    fn pattern_kind(pattern: &Pattern) -> SyntaxKind {
+        match pattern {
+            Pattern::Variant {
+                fields: VariantFields::Tuple(_),
+                ..
+            } => SyntaxKind::TupleStructPat,
+            Pattern::Variant { .. } | Pattern::Struct { .. } => SyntaxKind::RecordPat,
+            _ => SyntaxKind::PathPat,
+        }
+    }
+
+    /// Split `Rang::Lal` into the enum path and the variant name
+    fn split_variant_path(mut name: Identifier) -> (Option<Identifier>, Identifier) {
+        match name.name.rsplit_once("::") {
+            Some((path, variant)) => {
+                let enum_name = Identifier {
+                    name: path.to_string(),
+                    affixes: AffixSequence::new(),
+                    span: name.span,
+                };
+                name.name = variant.to_string();
+                (Some(enum_name), name)
+            }
+            None => (None, name),
+        }
+    }
+
+    /// Parse an integer literal pattern, possibly negated: `-1`
+    fn parse_int_pattern_literal(&mut self) -> Result<i64, ParseError> {
+        let negative = self.match_token(&TokenKind::Minus);
+        match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::IntLiteral(n)) => {
+                let n = *n;
+                self.advance();
+                Ok(if negative { n.wrapping_neg() } else { n })
+            }
+            other => Err(self.make_error(format!(
+                "Expected integer literal in pattern, found {:?}",
+                other
+            ))),
+        }
+    }
+
     /// Parse range pattern after start: start..end or start..=end
     fn parse_range_pattern_rest(&mut self, start: Option<Pattern>) -> Result<Pattern, ParseError> {
         let inclusive = if self.match_token(&TokenKind::DotDotEquals) {
@@ -1291,7 +3084,10 @@ impl Parser {
         };
 
         // Parse end if present
-        let end = if self.check_int_literal() || self.check_identifier_any() {
+        let end = if self.check_int_literal()
+            || self.check(&TokenKind::Minus)
+            || self.check_identifier_any()
+        {
             Some(Box::new(self.parse_primary_pattern()?))
         } else {
             None
@@ -1318,47 +3114,55 @@ impl Parser {
             .unwrap_or(false)
     }
 
-    /// Parse match expression (pratyabhijñā)
+    /// Parse match expression: `pratyabhijñā scrutinee { pattern yad guard => body, ... }`
     pub fn parse_match_expr(&mut self) -> Result<Expr, ParseError> {
-        let start_span = self.peek().map(|t| t.span).unwrap_or(Span::dummy());
-
-        // Expect 'pratyabhijñā' or 'match'
-        if !self.check_identifier("pratyabhijñā") && !self.check_identifier("match") {
-            return Err(self.make_error("Expected 'pratyabhijñā' or 'match'".to_string()));
-        }
-        self.advance();
-
-        // Parse scrutinee
+        let checkpoint = self.checkpoint();
+        let start = self.expect(&TokenKind::Match)?.span;
         let scrutinee = self.parse_expr()?;
 
         self.expect(&TokenKind::LeftBrace)?;
 
         let mut arms = Vec::new();
         while !self.check(&TokenKind::RightBrace) && !self.is_eof() {
-            let pattern = self.parse_pattern()?;
+            let checkpoint = self.checkpoint();
+            let arm_start = self.peek().map(|t| t.span).unwrap_or(start);
+            let pattern = self.parse_or_pattern()?;
+            let guard_start = self.checkpoint();
+            let guard = if self.match_token(&TokenKind::Yad) {
+                let condition = self.parse_expr()?;
+                self.wrap(guard_start, SyntaxKind::MatchGuard);
+                Some(condition)
+            } else {
+                None
+            };
             self.expect(&TokenKind::FatArrow)?;
-            let body = self.parse_expr()?;
+            let body = if self.check(&TokenKind::LeftBrace) {
+                let mut block = self.parse_block()?;
+                block.span = self.span_from(arm_start);
+                Expr::Block(block)
+            } else {
+                self.parse_expr()?
+            };
 
             arms.push(MatchArm {
                 pattern,
-                guard: None, // Guard is embedded in pattern via Pattern::Guard
+                guard,
                 body,
-                span: start_span,
+                span: self.span_from(arm_start),
             });
 
             // Allow trailing comma
             self.match_token(&TokenKind::Comma);
+            self.wrap(checkpoint, SyntaxKind::MatchArm);
         }
 
         self.expect(&TokenKind::RightBrace)?;
+        self.wrap(checkpoint, SyntaxKind::MatchExpr);
 
-        Ok(Expr::Block(Block {
-            stmts: vec![Stmt::Match {
-                scrutinee,
-                arms,
-                span: start_span,
-            }],
-            span: start_span,
-        }))
+        Ok(Expr::Match {
+            scrutinee: Box::new(scrutinee),
+            arms,
+            span: self.span_from(start),
+        })
     }
 }
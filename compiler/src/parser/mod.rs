@@ -4,10 +4,13 @@
 //! - Grammar rules
 //! - Samāsa (compound) resolution
 //! - Expression and statement parsing
+//! - Lossless concrete syntax trees for tooling
 
 pub mod ast;
 pub mod grammar;
 pub mod compounds;
+pub mod cst;
+pub mod format;
 
 // Re-exports
 pub use ast::{Ast, AstNode, Expr, Stmt, Item};
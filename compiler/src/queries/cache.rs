@@ -97,6 +97,15 @@ impl QueryCache {
         result
     }
 
+    /// Mark every remaining entry as verified at `revision`
+    ///
+    /// Called after invalidation has removed the stale entries.
+    pub fn revalidate(&mut self, revision: &Revision) {
+        for entry in self.entries.values_mut() {
+            entry.revision = *revision;
+        }
+    }
+
     /// Clear all entries
     pub fn clear(&mut self) {
         self.entries.clear();
@@ -0,0 +1,121 @@
+//! # Fingerprints - Lakṣaṇa (Distinguishing Mark)
+//!
+//! Stable content hashes used as query keys and on-disk cache keys.
+//!
+//! # Sanskrit Foundation
+//!
+//! **Lakṣaṇa** (लक्षण) - the mark by which a thing is recognised.
+//! Two items with the same lakṣaṇa produce the same fruit (phala).
+//!
+//! FNV-1a is used instead of `DefaultHasher` because fingerprints are
+//! written to disk and must not change between compiler builds.
+
+use std::fmt::Debug;
+
+/// 64-bit content hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Fingerprint(pub u64);
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+impl Fingerprint {
+    /// Fingerprint of the empty input
+    pub const EMPTY: Fingerprint = Fingerprint(FNV_OFFSET);
+
+    /// Hash raw bytes
+    pub fn of_bytes(bytes: &[u8]) -> Self {
+        Self::EMPTY.extend(bytes)
+    }
+
+    /// Hash a string
+    pub fn of_str(text: &str) -> Self {
+        Self::of_bytes(text.as_bytes())
+    }
+
+    /// Hash a value through its `Debug` representation
+    pub fn of_debug<T: Debug + ?Sized>(value: &T) -> Self {
+        Self::of_str(&format!("{:?}", value))
+    }
+
+    /// Like `of_debug`, but ignoring every `Span { .. }` in the value
+    ///
+    /// Moving an item to another line keeps this fingerprint.
+    pub fn of_debug_ignoring_spans<T: Debug + ?Sized>(value: &T) -> Self {
+        Self::of_str(&strip_spans(&format!("{:?}", value)))
+    }
+
+    /// Combine with another fingerprint (order matters)
+    pub fn combine(self, other: Fingerprint) -> Self {
+        self.extend(&other.0.to_le_bytes())
+    }
+
+    fn extend(self, bytes: &[u8]) -> Self {
+        let mut hash = self.0;
+        for &byte in bytes {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+        Fingerprint(hash)
+    }
+
+    /// Hex form used in the on-disk cache
+    pub fn to_hex(self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+impl std::fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+/// Remove `Span { start: .., end: .., line: .., column: .. }` from Debug output
+fn strip_spans(debug: &str) -> String {
+    const MARKER: &str = "Span {";
+    let mut out = String::with_capacity(debug.len());
+    let mut rest = debug;
+    while let Some(pos) = rest.find(MARKER) {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos + MARKER.len()..];
+        // Span has no nested braces
+        match rest.find('}') {
+            Some(end) => rest = &rest[end + 1..],
+            None => rest = "",
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Span;
+
+    #[test]
+    fn test_fingerprint_is_stable() {
+        // FNV-1a of "a"
+        assert_eq!(Fingerprint::of_str("a").0, 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(Fingerprint::of_str("a"), Fingerprint::of_str("b"));
+    }
+
+    #[test]
+    fn test_combine_order() {
+        let a = Fingerprint::of_str("a");
+        let b = Fingerprint::of_str("b");
+        assert_ne!(a.combine(b), b.combine(a));
+    }
+
+    #[test]
+    fn test_ignoring_spans() {
+        let first = (Span::with_location(0, 4, 1, 1), "nāma");
+        let moved = (Span::with_location(40, 44, 3, 5), "nāma");
+        assert_ne!(Fingerprint::of_debug(&first), Fingerprint::of_debug(&moved));
+        assert_eq!(
+            Fingerprint::of_debug_ignoring_spans(&first),
+            Fingerprint::of_debug_ignoring_spans(&moved)
+        );
+    }
+}
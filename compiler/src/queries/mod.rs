@@ -37,11 +37,15 @@
 
 mod cache;
 mod dependency;
+mod fingerprint;
+mod phases;
 mod query;
 mod revision;
 
 pub use cache::{QueryCache, CacheEntry, CacheStats};
 pub use dependency::{DependencyGraph, DependencyTracker};
+pub use fingerprint::Fingerprint;
+pub use phases::{CompilerDb, Expanded, PhaseStats};
 pub use query::{Query, QueryId, QueryKey, QueryResult, QueryError};
 pub use revision::{Revision, RevisionGuard};
 
@@ -106,6 +110,28 @@ pub trait QueryExecutor: Send + Sync {
     fn name(&self) -> &str;
 }
 
+impl<K, V, F> QueryExecutor for Query<K, V, F>
+where
+    K: std::hash::Hash + Eq + Clone + Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    F: Fn(&KarmaKosha, &K) -> QueryResult<V> + Send + Sync,
+{
+    fn execute(&self, db: &KarmaKosha, key: &dyn Any) -> QueryResult<Box<dyn Any + Send + Sync>> {
+        let key = key
+            .downcast_ref::<K>()
+            .ok_or_else(|| QueryError::TypeMismatch {
+                expected: std::any::type_name::<K>().to_string(),
+                query: self.name.clone(),
+            })?;
+        let value = (self.func)(db, key)?;
+        Ok(Box::new(Arc::new(value)))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
 impl KarmaKosha {
     /// Create a new query database
     pub fn new() -> Self {
@@ -132,7 +158,36 @@ impl KarmaKosha {
         V: Send + Sync + 'static,
     {
         let query_id = QueryId::new(id);
-        let query_key = QueryKey::new(query_id.clone(), Box::new(key.clone()));
+        self.memoized(id, key.clone(), |db| {
+            db.execute_query::<K, V>(&query_id, &key)
+        })
+    }
+
+    /// Execute a query computed by `compute` instead of a registered executor
+    ///
+    /// Caching, dependency recording and invalidation work as for `query`;
+    /// the closure may borrow context a registered executor cannot reach.
+    pub fn query_with<K, V, F>(&self, id: &str, key: K, compute: F) -> QueryResult<Arc<V>>
+    where
+        K: std::hash::Hash + Eq + Clone + Send + Sync + 'static,
+        V: Send + Sync + 'static,
+        F: FnOnce(&KarmaKosha) -> QueryResult<V>,
+    {
+        self.memoized(id, key, |db| compute(db).map(Arc::new))
+    }
+
+    /// Look up a cached result, or compute and cache it
+    ///
+    /// The query running when this is called (if any) is recorded as
+    /// depending on `key`, for cache hits as well as misses.
+    fn memoized<K, V, F>(&self, id: &str, key: K, compute: F) -> QueryResult<Arc<V>>
+    where
+        K: std::hash::Hash + Eq + Clone + Send + Sync + 'static,
+        V: Send + Sync + 'static,
+        F: FnOnce(&KarmaKosha) -> QueryResult<Arc<V>>,
+    {
+        let query_id = QueryId::new(id);
+        let query_key = QueryKey::new(query_id.clone(), Box::new(key));
 
         // Update stats
         {
@@ -140,17 +195,27 @@ impl KarmaKosha {
             stats.total_queries += 1;
         }
 
+        // Record the karma-bandha from the active query
+        {
+            let active = self.active_stack.read().unwrap();
+            if let Some(parent) = active.last() {
+                if parent != &query_key {
+                    let mut karma_bandha = self.karma_bandha.write().unwrap();
+                    karma_bandha.add_dependency(parent.clone(), query_key.clone());
+                }
+            }
+        }
+
         // Check cache first
         {
             let smriti = self.smriti.read().unwrap();
             if let Some(entry) = smriti.get(&query_key) {
                 let current_rev = self.revision.read().unwrap().clone();
                 if entry.is_valid(&current_rev) {
-                    let mut stats = self.stats.write().unwrap();
-                    stats.cache_hits += 1;
-
                     // Downcast and return
                     if let Some(value) = entry.value.downcast_ref::<Arc<V>>() {
+                        let mut stats = self.stats.write().unwrap();
+                        stats.cache_hits += 1;
                         return Ok(value.clone());
                     }
                 }
@@ -183,7 +248,7 @@ impl KarmaKosha {
         }
 
         // Execute the query
-        let result = self.execute_query::<K, V>(&query_id, &key);
+        let result = compute(self);
 
         // Pop from active stack
         {
@@ -194,10 +259,15 @@ impl KarmaKosha {
         // Cache the result
         if let Ok(ref value) = result {
             let current_rev = self.revision.read().unwrap().clone();
+            let dependencies = self
+                .karma_bandha
+                .read()
+                .unwrap()
+                .get_dependencies(&query_key);
             let entry = CacheEntry {
                 value: Arc::new(value.clone()) as Arc<dyn Any + Send + Sync>,
                 revision: current_rev,
-                dependencies: Vec::new(), // TODO: track dependencies
+                dependencies,
             };
 
             let mut smriti = self.smriti.write().unwrap();
@@ -213,8 +283,13 @@ impl KarmaKosha {
         K: 'static,
         V: Send + Sync + 'static,
     {
-        let queries = self.queries.read().unwrap();
-        let executor = queries.get(id)
+        // Release the registry before running: executors query recursively
+        let executor = self
+            .queries
+            .read()
+            .unwrap()
+            .get(id)
+            .cloned()
             .ok_or_else(|| QueryError::UnknownQuery(id.name.clone()))?;
 
         let result = executor.execute(self, key as &dyn Any)?;
@@ -228,30 +303,41 @@ impl KarmaKosha {
             })
     }
 
-    /// Invalidate all queries depending on an input
+    /// Invalidate an input and every query depending on it, transitively
+    ///
+    /// Unrelated cached results survive and stay valid at the new revision.
     pub fn invalidate(&self, input_key: &QueryKey) {
-        let mut stats = self.stats.write().unwrap();
-        stats.invalidations += 1;
+        {
+            let mut stats = self.stats.write().unwrap();
+            stats.invalidations += 1;
+        }
 
         // Increment revision
-        {
+        let current_rev = {
             let mut rev = self.revision.write().unwrap();
             rev.increment();
-        }
+            *rev
+        };
 
         // Get dependent queries
         let dependents = {
-            let karma_bandha = self.karma_bandha.read().unwrap();
-            karma_bandha.get_dependents(input_key)
+            let mut karma_bandha = self.karma_bandha.write().unwrap();
+            let dependents = karma_bandha.get_transitive_dependents(input_key);
+            // Re-executed dependents record their dependencies afresh
+            for dep in &dependents {
+                karma_bandha.remove(dep);
+            }
+            dependents
         };
 
         // Invalidate cache entries
         {
             let mut smriti = self.smriti.write().unwrap();
             smriti.remove(input_key);
-            for dep in dependents {
-                smriti.remove(&dep);
+            for dep in &dependents {
+                smriti.remove(dep);
             }
+            smriti.revalidate(&current_rev);
         }
     }
 
@@ -264,6 +350,12 @@ impl KarmaKosha {
         let query_id = QueryId::new(id);
         let query_key = QueryKey::new(query_id, Box::new(key));
 
+        // Invalidate the old value and its dependents first
+        let old_exists = self.smriti.read().unwrap().get(&query_key).is_some();
+        if old_exists {
+            self.invalidate(&query_key);
+        }
+
         // Create cache entry
         let current_rev = self.revision.read().unwrap().clone();
         let entry = CacheEntry {
@@ -272,16 +364,22 @@ impl KarmaKosha {
             dependencies: Vec::new(),
         };
 
-        // Insert and invalidate dependents
-        let old_exists = {
-            let mut smriti = self.smriti.write().unwrap();
-            let old = smriti.insert(query_key.clone(), entry);
-            old.is_some()
-        };
+        let mut smriti = self.smriti.write().unwrap();
+        smriti.insert(query_key, entry);
+    }
 
-        if old_exists {
-            self.invalidate(&query_key);
-        }
+    /// Whether a result for `key` is cached and valid
+    pub fn is_cached<K>(&self, id: &str, key: K) -> bool
+    where
+        K: std::hash::Hash + Send + Sync + 'static,
+    {
+        let query_key = QueryKey::new(QueryId::new(id), Box::new(key));
+        let current_rev = *self.revision.read().unwrap();
+        self.smriti
+            .read()
+            .unwrap()
+            .get(&query_key)
+            .is_some_and(|entry| entry.is_valid(&current_rev))
     }
 
     /// Get statistics
@@ -333,4 +431,26 @@ mod tests {
         kosha.set_input("source", "main.jag".to_string(), "fn main() {}".to_string());
         // Input is now cached
     }
+
+    #[test]
+    fn test_dependent_queries_invalidated() {
+        let kosha = KarmaKosha::new();
+        kosha.set_input("source", "a.jag".to_string(), "a".to_string());
+        kosha.set_input("source", "b.jag".to_string(), "b".to_string());
+        let length = |file: &str| {
+            kosha
+                .query_with("length", file.to_string(), |db| {
+                    let text = db.query::<String, String>("source", file.to_string())?;
+                    Ok(text.len())
+                })
+                .unwrap()
+        };
+        assert_eq!(*length("a.jag"), 1);
+        assert_eq!(*length("b.jag"), 1);
+
+        kosha.set_input("source", "a.jag".to_string(), "aaa".to_string());
+        assert!(!kosha.is_cached("length", "a.jag".to_string()));
+        assert!(kosha.is_cached("length", "b.jag".to_string()));
+        assert_eq!(*length("a.jag"), 3);
+    }
 }
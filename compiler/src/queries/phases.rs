@@ -0,0 +1,592 @@
+//! # Compiler Phases as Queries - Karma Saṃkalana
+//!
+//! Lexing, parsing, per-item type checking, MIR building and per-function
+//! code generation expressed as KarmaKosha queries.
+//!
+//! # Sanskrit Foundation
+//!
+//! **Saṃkalana** (संकलन) - compilation, gathering together.
+//! Each phase is a karman whose phala is remembered; only actions whose
+//! causes changed are performed again.
+//!
+//! # Invalidation
+//!
+//! - `source` is the only input. Changing it invalidates `lex`, `expand`
+//!   and `parse` for that file through the recorded karma-bandha.
+//! - `item_typeck` and `item_mir` are keyed by the fingerprint of the item
+//!   and of the declarations it can see, so an item whose text and
+//!   environment are unchanged is served from smṛti even after a re-parse.
+//!   `item_mir` is keyed by the guṇa's overflow behaviour as well.
+//! - `function_codegen` is keyed by the fingerprint of the MIR function.
+//!
+//! Type-check successes and generated assembly are also written to
+//! `karma-kosha.json` in the build directory (`lakṣya/`), so a fresh
+//! process skips unchanged items too. Failures are never persisted.
+
+use super::{Fingerprint, KarmaKosha, QueryError, QueryResult};
+use crate::codegen::asm::aarch64::AArch64Emitter;
+use crate::codegen::asm::riscv64::RiscV64Emitter;
+use crate::codegen::asm::x86_64::X86_64Emitter;
+use crate::codegen::asm::{AsmEmitter, Target};
+use crate::lexer::{Lexer, Token};
+use crate::mir::types::{MirFunction, MirModule, Overflow};
+use crate::mir::{LoweringError, MirBuilder};
+use crate::parser::ast::{Ast, Item};
+use crate::parser::grammar::ParseError;
+use crate::parser::Parser;
+use crate::r#macro::{ExpansionRecord, MacroError, MacroExpander};
+use crate::semantics::generics::MonoError;
+use crate::semantics::{monomorphize, TypeCheckError, TypeChecker};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Source text of a file (input)
+pub const SOURCE: &str = "source";
+/// Token stream of a file
+pub const LEX: &str = "lex";
+/// Token stream after `mantra!` expansion
+pub const EXPAND: &str = "expand";
+/// AST of a file
+pub const PARSE: &str = "parse";
+/// Type errors of one item
+pub const ITEM_TYPECK: &str = "item_typeck";
+/// Program with generic functions instantiated
+pub const MONOMORPHIZE: &str = "monomorphize";
+/// MIR of one item
+pub const ITEM_MIR: &str = "item_mir";
+/// Assembly of one MIR function
+pub const FUNCTION_CODEGEN: &str = "function_codegen";
+
+/// Name of the persisted cache inside the build directory
+pub const CACHE_FILE: &str = "karma-kosha.json";
+
+/// Result of mantra expansion
+#[derive(Debug, Clone)]
+pub struct Expanded {
+    /// Expanded tokens, or the first expansion error
+    pub tokens: Result<Vec<Token>, MacroError>,
+    /// Expansions performed, for diagnostic backtraces
+    pub expansions: Vec<ExpansionRecord>,
+}
+
+/// Work done versus work reused since the database was created
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PhaseStats {
+    /// Items type-checked
+    pub items_checked: usize,
+    /// Items whose type check was reused
+    pub items_reused: usize,
+    /// Items lowered to MIR
+    pub items_lowered: usize,
+    /// Functions given assembly
+    pub functions_generated: usize,
+    /// Functions whose assembly was reused
+    pub functions_reused: usize,
+}
+
+/// Persisted part of the cache
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DiskCache {
+    /// Compiler version that wrote the cache
+    version: String,
+    /// Span-free fingerprints of items that type-checked cleanly
+    typeck_ok: BTreeSet<String>,
+    /// Function fingerprint → assembly
+    codegen: BTreeMap<String, String>,
+}
+
+impl DiskCache {
+    fn new() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Disk cache as loaded, plus the entries used by the current compilation
+struct DiskState {
+    loaded: DiskCache,
+    live: DiskCache,
+}
+
+/// Query database for the compiler phases (Karma Saṃkalana Kośa)
+pub struct CompilerDb {
+    kosha: KarmaKosha,
+    /// Build directory holding the persisted cache
+    cache_dir: Option<PathBuf>,
+    disk: Mutex<DiskState>,
+    stats: Mutex<PhaseStats>,
+}
+
+impl CompilerDb {
+    /// In-memory database
+    pub fn new() -> Self {
+        Self {
+            kosha: KarmaKosha::new(),
+            cache_dir: None,
+            disk: Mutex::new(DiskState {
+                loaded: DiskCache::new(),
+                live: DiskCache::new(),
+            }),
+            stats: Mutex::new(PhaseStats::default()),
+        }
+    }
+
+    /// Database persisted under `cache_dir` (usually `lakṣya/`)
+    ///
+    /// A missing, unreadable or outdated cache file starts an empty cache.
+    pub fn persistent(cache_dir: &Path) -> Self {
+        let mut db = Self::new();
+        let loaded = std::fs::read_to_string(cache_dir.join(CACHE_FILE))
+            .ok()
+            .and_then(|text| serde_json::from_str::<DiskCache>(&text).ok())
+            .filter(|cache| cache.version == env!("CARGO_PKG_VERSION"));
+        if let Some(loaded) = loaded {
+            db.disk.get_mut().unwrap().loaded = loaded;
+        }
+        db.cache_dir = Some(cache_dir.to_path_buf());
+        db
+    }
+
+    /// The underlying query store
+    pub fn kosha(&self) -> &KarmaKosha {
+        &self.kosha
+    }
+
+    /// Work counters
+    pub fn stats(&self) -> PhaseStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Set the text of `file`; dependents are invalidated only if it changed
+    pub fn set_source(&self, file: &str, text: &str) {
+        if let Ok(old) = self.kosha.query::<String, String>(SOURCE, file.to_string()) {
+            if old.as_str() == text {
+                return;
+            }
+        }
+        self.kosha
+            .set_input(SOURCE, file.to_string(), text.to_string());
+    }
+
+    /// Tokens of `file`
+    pub fn lex(&self, file: &str) -> QueryResult<Arc<Vec<Token>>> {
+        self.kosha.query_with(LEX, file.to_string(), |db| {
+            let source = db.query::<String, String>(SOURCE, file.to_string())?;
+            Ok(Lexer::new(&source).tokenize())
+        })
+    }
+
+    /// Tokens of `file` after `mantra!` expansion
+    pub fn expand(&self, file: &str) -> QueryResult<Arc<Expanded>> {
+        self.kosha.query_with(EXPAND, file.to_string(), |_| {
+            let tokens = self.lex(file)?;
+            // Mantra definitions are file-local
+            let mut expander = MacroExpander::new();
+            let tokens = expander.expand_tokens(tokens.as_ref().clone());
+            Ok(Expanded {
+                tokens,
+                expansions: expander.expansions().to_vec(),
+            })
+        })
+    }
+
+    /// AST of `file`; expansion errors are reported by `expand`
+    pub fn parse(&self, file: &str) -> QueryResult<Arc<Result<Ast, Vec<ParseError>>>> {
+        self.kosha.query_with(PARSE, file.to_string(), |_| {
+            let expanded = self.expand(file)?;
+            let tokens = expanded
+                .tokens
+                .as_ref()
+                .map_err(|e| QueryError::ExecutionFailed {
+                    query: PARSE.to_string(),
+                    message: e.to_string(),
+                })?;
+            Ok(Parser::new(tokens.clone()).parse())
+        })
+    }
+
+    /// Type errors of every item of `ast`
+    ///
+    /// `imports` are the items of other modules in scope, as for
+    /// `TypeChecker::import_item`, and `hidden` the (type, member) pairs
+    /// private to those modules. Unchanged items are not re-checked.
+    pub fn check_items(
+        &self,
+        ast: &Ast,
+        imports: &[(&str, &Item)],
+        hidden: &[(String, String)],
+    ) -> QueryResult<Vec<TypeCheckError>> {
+        let mut env = Fingerprint::of_debug(hidden);
+        for (name, item) in imports {
+            env = env
+                .combine(Fingerprint::of_str(name))
+                .combine(declaration_fingerprint(item));
+        }
+        for item in &ast.items {
+            env = env.combine(declaration_fingerprint(item));
+        }
+
+        // Built on the first item that has to be checked
+        let checker: RefCell<Option<TypeChecker>> = RefCell::new(None);
+        let mut errors = Vec::new();
+        for item in &ast.items {
+            let key = Fingerprint::of_debug(item).combine(env);
+            let persisted = Fingerprint::of_debug_ignoring_spans(item)
+                .combine(env)
+                .to_hex();
+            if self.kosha.is_cached(ITEM_TYPECK, key) || self.persisted_ok(&persisted) {
+                self.stats.lock().unwrap().items_reused += 1;
+            }
+            let item_errors = self.kosha.query_with(ITEM_TYPECK, key, |_| {
+                if self.persisted_ok(&persisted) {
+                    return Ok(Vec::new());
+                }
+                self.stats.lock().unwrap().items_checked += 1;
+                let mut checker = checker.borrow_mut();
+                let checker = checker.get_or_insert_with(|| {
+                    let mut checker = TypeChecker::new();
+                    for (name, item) in imports {
+                        checker.import_item(name, item);
+                    }
+                    for (owner, member) in hidden {
+                        checker.hide_member(owner, member);
+                    }
+                    checker.collect(ast);
+                    checker
+                });
+                Ok(checker.check_single(item))
+            })?;
+            if item_errors.is_empty() {
+                self.disk.lock().unwrap().live.typeck_ok.insert(persisted);
+            }
+            errors.extend(item_errors.iter().cloned());
+        }
+        Ok(errors)
+    }
+
+    /// `ast` with each generic function replaced by its instances
+    pub fn monomorphize(&self, ast: &Ast) -> QueryResult<Arc<Result<Ast, Vec<MonoError>>>> {
+        let key = Fingerprint::of_debug(&ast.items);
+        self.kosha
+            .query_with(MONOMORPHIZE, key, |_| Ok(monomorphize(ast)))
+    }
+
+    /// MIR for `ast`, lowering only items that changed, with integer
+    /// arithmetic overflowing as `overflow` says, or what could not be
+    /// lowered
+    pub fn build_mir(
+        &self,
+        ast: &Ast,
+        overflow: Overflow,
+    ) -> QueryResult<Result<MirModule, Vec<LoweringError>>> {
+        // Every item is lowered against what `MirBuilder::index_methods`
+        // gathers from the whole AST
+        let environment = lowering_environment(ast);
+
+        let builder: RefCell<Option<MirBuilder>> = RefCell::new(None);
+        let mut module = MirModule {
+            name: "main".to_string(),
+            functions: Vec::new(),
+            globals: Vec::new(),
+            types: Vec::new(),
+        };
+        let mut errors = Vec::new();
+        for item in &ast.items {
+            let key = Fingerprint::of_debug(item)
+                .combine(environment)
+                .combine(Fingerprint::of_debug(&overflow));
+            let lowered = self.kosha.query_with(ITEM_MIR, key, |_| {
+                self.stats.lock().unwrap().items_lowered += 1;
+                let mut builder = builder.borrow_mut();
+                let builder = builder.get_or_insert_with(|| {
+                    let mut builder = MirBuilder::new().with_overflow(overflow);
+                    builder.index_methods(ast);
+                    builder
+                });
+                let mut fragment = MirModule {
+                    name: "main".to_string(),
+                    functions: Vec::new(),
+                    globals: Vec::new(),
+                    types: Vec::new(),
+                };
+                builder.build_item(item, &mut fragment);
+                match builder.take_errors() {
+                    item_errors if item_errors.is_empty() => Ok(Ok(fragment)),
+                    item_errors => Ok(Err(item_errors)),
+                }
+            })?;
+            match lowered.as_ref() {
+                Ok(lowered) => {
+                    module.functions.extend(lowered.functions.iter().cloned());
+                    module.globals.extend(lowered.globals.iter().cloned());
+                    module.types.extend(lowered.types.iter().cloned());
+                }
+                Err(item_errors) => errors.extend(item_errors.iter().cloned()),
+            }
+        }
+        if errors.is_empty() {
+            Ok(Ok(module))
+        } else {
+            Ok(Err(errors))
+        }
+    }
+
+    /// Assembly for `mir`, generating only functions that changed
+    pub fn generate_code(&self, mir: &MirModule, target: Target) -> QueryResult<String> {
+        let target_fp = Fingerprint::of_debug(&target);
+        let mut fragments = Vec::with_capacity(mir.functions.len());
+        for func in &mir.functions {
+            let key = function_fingerprint(func).combine(target_fp);
+            let hex = key.to_hex();
+            let asm = self.kosha.query_with(FUNCTION_CODEGEN, key, |_| {
+                let persisted = self.disk.lock().unwrap().loaded.codegen.get(&hex).cloned();
+                if let Some(asm) = persisted {
+                    self.stats.lock().unwrap().functions_reused += 1;
+                    return Ok(asm);
+                }
+                self.stats.lock().unwrap().functions_generated += 1;
+                Ok(emit_function(func, target))
+            })?;
+            self.disk
+                .lock()
+                .unwrap()
+                .live
+                .codegen
+                .insert(hex, asm.as_ref().clone());
+            fragments.push(asm);
+        }
+
+        let mut output = empty_assembly(target);
+        let bodies: Vec<&str> = fragments.iter().map(|asm| asm.as_str()).collect();
+        output.push_str(&bodies.join("\n"));
+        Ok(output)
+    }
+
+    /// Write the entries used since the last save to the build directory
+    ///
+    /// Stale entries are dropped, so the cache tracks the current program.
+    pub fn persist(&self) -> std::io::Result<()> {
+        let Some(dir) = &self.cache_dir else {
+            return Ok(());
+        };
+        let mut disk = self.disk.lock().unwrap();
+        let live = std::mem::replace(&mut disk.live, DiskCache::new());
+        std::fs::create_dir_all(dir)?;
+        let text = serde_json::to_string(&live).map_err(std::io::Error::other)?;
+        std::fs::write(dir.join(CACHE_FILE), text)?;
+        disk.loaded = live;
+        Ok(())
+    }
+
+    fn persisted_ok(&self, fingerprint: &str) -> bool {
+        self.disk
+            .lock()
+            .unwrap()
+            .loaded
+            .typeck_ok
+            .contains(fingerprint)
+    }
+}
+
+impl Default for CompilerDb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Fingerprint of what other items can see of `item`: signatures, not bodies
+fn declaration_fingerprint(item: &Item) -> Fingerprint {
+    match item {
+        Item::Function(func) => Fingerprint::of_debug_ignoring_spans(&(
+            &func.name.name,
+            &func.generics,
+            &func.receiver,
+            &func.params,
+            &func.return_type,
+            &func.where_clauses,
+        )),
+        Item::Impl(impl_def) => {
+            let mut fp = Fingerprint::of_debug_ignoring_spans(&(
+                &impl_def.generics,
+                &impl_def.trait_ref,
+                &impl_def.self_ty,
+                &impl_def.where_clauses,
+                &impl_def.associated_types,
+            ));
+            for method in &impl_def.methods {
+                fp = fp.combine(declaration_fingerprint(&Item::Function(method.clone())));
+            }
+            fp
+        }
+        _ => Fingerprint::of_debug_ignoring_spans(item),
+    }
+}
+
+/// Fingerprint of everything `MirBuilder::index_methods` reads: enums and
+/// struct shapes, constants, the return types of functions and methods,
+/// the methods each impl and trait provides, and `From` conversions
+fn lowering_environment(ast: &Ast) -> Fingerprint {
+    let mut environment = Fingerprint::EMPTY;
+    for item in &ast.items {
+        let fingerprint = match item {
+            Item::TypeDef(typedef) => Fingerprint::of_debug_ignoring_spans(typedef),
+            Item::Constant(constant) => Fingerprint::of_debug_ignoring_spans(constant),
+            Item::Function(func) => {
+                Fingerprint::of_debug_ignoring_spans(&(&func.name.name, &func.return_type))
+            }
+            Item::Impl(impl_def) => {
+                let methods: Vec<_> = impl_def
+                    .methods
+                    .iter()
+                    .map(|m| (&m.name.name, &m.return_type))
+                    .collect();
+                Fingerprint::of_debug_ignoring_spans(&(
+                    &impl_def.self_ty,
+                    &impl_def.trait_ref,
+                    &methods,
+                ))
+            }
+            Item::Trait(trait_def) => {
+                let names: Vec<&str> = trait_def
+                    .methods
+                    .iter()
+                    .filter(|m| m.default_body.is_some())
+                    .map(|m| m.name.name.as_str())
+                    .collect();
+                Fingerprint::of_str(&trait_def.name.name).combine(Fingerprint::of_debug(&names))
+            }
+            _ => continue,
+        };
+        environment = environment.combine(fingerprint);
+    }
+    environment
+}
+
+/// Fingerprint of a MIR function (kāraka hints are hashed in index order)
+fn function_fingerprint(func: &MirFunction) -> Fingerprint {
+    let mut hints: Vec<_> = func.karaka_hints.iter().collect();
+    hints.sort_by_key(|(index, _)| **index);
+    Fingerprint::of_debug(&(
+        &func.name,
+        &func.params,
+        &func.return_type,
+        &func.blocks,
+        &func.locals,
+        &func.overflow,
+    ))
+    .combine(Fingerprint::of_debug(&hints))
+}
+
+/// Assembly of one function, without the file header
+fn emit_function(func: &MirFunction, target: Target) -> String {
+    fn emit<E: AsmEmitter>(mut emitter: E, func: &MirFunction) -> String {
+        emitter.emit_prologue(func);
+        emitter.emit_body(func);
+        emitter.emit_epilogue(func);
+        emitter.get_body()
+    }
+    match target {
+        Target::X86_64 => emit(X86_64Emitter::new(), func),
+        Target::AArch64 => emit(AArch64Emitter::new(), func),
+        Target::RiscV64 => emit(RiscV64Emitter::new(), func),
+    }
+}
+
+/// File header of the target's assembly
+fn empty_assembly(target: Target) -> String {
+    match target {
+        Target::X86_64 => X86_64Emitter::new().get_asm(),
+        Target::AArch64 => AArch64Emitter::new().get_asm(),
+        Target::RiscV64 => RiscV64Emitter::new().get_asm(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE_TEXT: &str = r#"
+kāryakrama eka() -> saṅkhyā-a-k-t32 {
+    phera 1;
+}
+
+kāryakrama dvi() -> saṅkhyā-a-k-t32 {
+    phera 2;
+}
+"#;
+
+    fn compile(db: &CompilerDb, text: &str) -> String {
+        db.set_source("main.jag", text);
+        let ast = db.parse("main.jag").unwrap();
+        let ast = ast.as_ref().as_ref().unwrap();
+        assert!(db.check_items(ast, &[], &[]).unwrap().is_empty());
+        let mir = db.build_mir(ast, Overflow::Wrap).unwrap().unwrap();
+        db.generate_code(&mir, Target::X86_64).unwrap()
+    }
+
+    #[test]
+    fn test_unchanged_source_is_reused() {
+        let db = CompilerDb::new();
+        let first = compile(&db, SOURCE_TEXT);
+        let second = compile(&db, SOURCE_TEXT);
+        assert_eq!(first, second);
+
+        let stats = db.stats();
+        assert_eq!(stats.items_checked, 2);
+        assert_eq!(stats.items_reused, 2);
+        assert_eq!(stats.functions_generated, 2);
+    }
+
+    #[test]
+    fn test_only_changed_item_is_rechecked() {
+        let db = CompilerDb::new();
+        compile(&db, SOURCE_TEXT);
+        let edited = SOURCE_TEXT.replace("phera 2;", "phera 3;");
+        let asm = compile(&db, &edited);
+        assert!(asm.contains("dvi"));
+
+        let stats = db.stats();
+        assert_eq!(stats.items_checked, 3);
+        assert_eq!(stats.items_lowered, 3);
+        assert_eq!(stats.functions_generated, 3);
+    }
+
+    #[test]
+    fn test_matches_whole_module_codegen() {
+        let db = CompilerDb::new();
+        let asm = compile(&db, SOURCE_TEXT);
+
+        let ast = db.parse("main.jag").unwrap();
+        let mir = MirBuilder::new().build(ast.as_ref().as_ref().unwrap());
+        let mut expected = String::new();
+        for (i, func) in mir.functions.iter().enumerate() {
+            if i > 0 {
+                expected.push('\n');
+            }
+            expected.push_str(&emit_function(func, Target::X86_64));
+        }
+        assert_eq!(asm, empty_assembly(Target::X86_64) + &expected);
+    }
+
+    #[test]
+    fn test_cache_persists_across_databases() {
+        let dir = std::env::temp_dir().join(format!("karma-kosha-{}", std::process::id()));
+        let db = CompilerDb::persistent(&dir);
+        let first = compile(&db, SOURCE_TEXT);
+        db.persist().unwrap();
+
+        let db = CompilerDb::persistent(&dir);
+        let second = compile(&db, SOURCE_TEXT);
+        let stats = db.stats();
+        assert_eq!(first, second);
+        assert_eq!(stats.items_checked, 0);
+        assert_eq!(stats.functions_generated, 0);
+        assert_eq!(stats.functions_reused, 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
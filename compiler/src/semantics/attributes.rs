@@ -0,0 +1,1017 @@
+//! Cihna: Attributes
+//!
+//! Gives meaning to the `#[...]` attributes the compiler understands:
+//! - `parīkṣā` (`test`) marks a test function, built only for `--test`
+//! - `samāveśa` (`inline`) records an inlining hint on the MIR function:
+//!   `samāveśa(nitya)` asks for inlining, `samāveśa(na)` forbids it
+//! - `cfg(...)` keeps an item, field, parameter or binding only when its
+//!   predicate holds for this build
+//! - `apracalita` (`deprecated`) warns at every use of the item
+//! - `anumati`/`sāvadhāna`/`niṣedha` (`allow`/`warn`/`deny`) set lint levels
+//! - `yama(...)` vows a function to restraints judged by `garuda::yama`
+//! - `prakāśana` (`declassify`) lets a function's result, or a binding's
+//!   value, be public though it depends on `-guhya` data
+//! - `asurakṣita(sūtra)` (`unsafe(sutra)`) declares a type thread-safe
+//!   without checking its fields
+//!
+//! Annotations read by the philosophy subsystems (`guṇa`, `cakra`, `mārga`,
+//! ...) are accepted as they are; any other name is an error.
+//!
+//! Attributes are checked, and `cfg` evaluated, right after parsing
+//! ([`configure`]). Deprecation and yama vows need the whole program and are
+//! checked after type checking ([`check_lints`]).
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::codegen::asm::Target;
+use crate::garuda::YamaDharmaraja;
+use crate::lexer::Span;
+use crate::parser::ast::{
+    Ast, Attribute, Block, Expr, FunctionDef, Identifier, Item, Literal, MetaItem, Stmt, TypeBody,
+};
+use crate::philosophy::guna::Guna;
+use crate::visitor::{walk_expr, walk_expr_mut, walk_stmt, AstVisitor, AstVisitorMut, VisitResult};
+use crate::yoga::ashtanga::Yama;
+
+/// Annotations of the philosophy subsystems, accepted without checks
+const ANNOTATIONS: &[&str] = &[
+    "niyama",
+    "āsana",
+    "prāṇāyāma",
+    "pratyāhāra",
+    "dhāraṇā",
+    "dhyāna",
+    "samādhi",
+    "guṇa",
+    "cakra",
+    "chakra",
+    "mārga",
+    "astra",
+    "puruṣārtha",
+    "kosha",
+    "kośa",
+    "kuṇḍalinī",
+    "kāla",
+    "karma",
+    "mantra",
+    "maṇḍala",
+    "tantra",
+    "sri_yantra",
+    "simd",
+    "saṃreka",
+    "tattva",
+    "repr",
+];
+
+/// Inlining hint from `#[samāveśa]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum InlineHint {
+    /// Left to the inliner's heuristics
+    #[default]
+    Auto,
+    /// `samāveśa(nitya)`: inline wherever possible
+    Always,
+    /// `samāveśa(na)`: never inline
+    Never,
+}
+
+/// Lints whose level attributes can set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    /// Use of an item marked `#[apracalita]`
+    Deprecated,
+    /// Attribute with no effect (given twice)
+    UnusedAttributes,
+}
+
+impl Lint {
+    /// Lint named in `anumati(...)`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "apracalita" | "deprecated" => Some(Lint::Deprecated),
+            "anupayukta_cihna" | "unused_attributes" => Some(Lint::UnusedAttributes),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::Deprecated => "apracalita",
+            Lint::UnusedAttributes => "anupayukta_cihna",
+        }
+    }
+}
+
+/// Level of a lint: dropped, reported as a warning, or an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+/// What an attribute is attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeTarget {
+    Function,
+    /// Impl or trait method
+    Method,
+    TypeDef,
+    Field,
+    Parameter,
+    Constant,
+    Module,
+    Trait,
+    Impl,
+    Import,
+    Let,
+}
+
+impl AttributeTarget {
+    fn describe(&self) -> &'static str {
+        match self {
+            AttributeTarget::Function => "functions",
+            AttributeTarget::Method => "methods",
+            AttributeTarget::TypeDef => "types",
+            AttributeTarget::Field => "fields",
+            AttributeTarget::Parameter => "parameters",
+            AttributeTarget::Constant => "constants",
+            AttributeTarget::Module => "modules",
+            AttributeTarget::Trait => "traits",
+            AttributeTarget::Impl => "impl blocks",
+            AttributeTarget::Import => "imports",
+            AttributeTarget::Let => "let bindings",
+        }
+    }
+}
+
+/// An attribute the compiler understands
+#[derive(Debug, Clone)]
+pub enum BuiltinAttribute {
+    Test,
+    Inline(InlineHint),
+    /// `cfg(predicate)`
+    Cfg(MetaItem),
+    Deprecated {
+        note: Option<String>,
+    },
+    Lint(LintLevel, Vec<Lint>),
+    Yama(Vec<Yama>),
+    /// `prakāśana`: a declassification point for `security`
+    Declassify,
+    /// `asurakṣita(sūtra)`: a type taken to be thread-safe by `sutra`
+    AssumeThreadSafe,
+    /// Philosophy annotation, see [`ANNOTATIONS`]
+    Annotation,
+}
+
+impl BuiltinAttribute {
+    /// Interpret an attribute, or report why it is not understood
+    pub fn parse(attr: &Attribute) -> Result<Self, AttributeError> {
+        let name = attr.name();
+        let malformed = |expected: &'static str| AttributeError::Malformed {
+            name: name.to_string(),
+            expected,
+            span: attr.span,
+        };
+        match name {
+            "parīkṣā" | "test" => match &attr.meta {
+                MetaItem::Word(_) => Ok(BuiltinAttribute::Test),
+                _ => Err(malformed("`#[parīkṣā]`")),
+            },
+            "samāveśa" | "inline" => match &attr.meta {
+                MetaItem::Word(_) => Ok(BuiltinAttribute::Inline(InlineHint::Auto)),
+                MetaItem::List(_, args) if args.len() == 1 => match args[0].name() {
+                    Some("nitya" | "always") => Ok(BuiltinAttribute::Inline(InlineHint::Always)),
+                    Some("na" | "never") => Ok(BuiltinAttribute::Inline(InlineHint::Never)),
+                    _ => Err(malformed(
+                        "`#[samāveśa]`, `#[samāveśa(nitya)]` or `#[samāveśa(na)]`",
+                    )),
+                },
+                _ => Err(malformed(
+                    "`#[samāveśa]`, `#[samāveśa(nitya)]` or `#[samāveśa(na)]`",
+                )),
+            },
+            "cfg" => match attr.args() {
+                [predicate] => Ok(BuiltinAttribute::Cfg(predicate.clone())),
+                _ => Err(malformed("`#[cfg(predicate)]`")),
+            },
+            "apracalita" | "deprecated" => {
+                let note = match &attr.meta {
+                    MetaItem::Word(_) => None,
+                    MetaItem::NameValue(_, Literal::String(note)) => Some(note.clone()),
+                    MetaItem::List(_, args) => match args.as_slice() {
+                        [MetaItem::Literal(Literal::String(note))] => Some(note.clone()),
+                        [MetaItem::NameValue(key, Literal::String(note))]
+                            if key.name == "sūcanā" || key.name == "note" =>
+                        {
+                            Some(note.clone())
+                        }
+                        _ => return Err(malformed("`#[apracalita(sūcanā = \"...\")]`")),
+                    },
+                    _ => return Err(malformed("`#[apracalita(sūcanā = \"...\")]`")),
+                };
+                Ok(BuiltinAttribute::Deprecated { note })
+            }
+            "anumati" | "allow" | "sāvadhāna" | "warn" | "niṣedha" | "deny" => {
+                let level = match name {
+                    "anumati" | "allow" => LintLevel::Allow,
+                    "sāvadhāna" | "warn" => LintLevel::Warn,
+                    _ => LintLevel::Deny,
+                };
+                if attr.args().is_empty() {
+                    return Err(malformed("a list of lints, as in `#[anumati(apracalita)]`"));
+                }
+                let mut lints = Vec::new();
+                for arg in attr.args() {
+                    let lint_name = match arg {
+                        MetaItem::Word(ident) => &ident.name,
+                        _ => return Err(malformed("a list of lint names")),
+                    };
+                    let lint = Lint::from_name(lint_name).ok_or(AttributeError::UnknownLint {
+                        name: lint_name.clone(),
+                        span: attr.span,
+                    })?;
+                    lints.push(lint);
+                }
+                Ok(BuiltinAttribute::Lint(level, lints))
+            }
+            "yama" => {
+                if attr.args().is_empty() {
+                    return Err(malformed("a list of yamas, as in `#[yama(ahiṃsā)]`"));
+                }
+                let mut vows = Vec::new();
+                for arg in attr.args() {
+                    let vow = match arg {
+                        MetaItem::Word(ident) => {
+                            yama_from_name(&ident.name).ok_or(AttributeError::UnknownYama {
+                                name: ident.name.clone(),
+                                span: attr.span,
+                            })?
+                        }
+                        // `satya` also lexes as the literal `true`
+                        MetaItem::Literal(Literal::Bool(true)) => Yama::Satya,
+                        _ => return Err(malformed("a list of yamas")),
+                    };
+                    vows.push(vow);
+                }
+                Ok(BuiltinAttribute::Yama(vows))
+            }
+            "prakāśana" | "declassify" => match &attr.meta {
+                MetaItem::Word(_) => Ok(BuiltinAttribute::Declassify),
+                _ => Err(malformed("`#[prakāśana]`")),
+            },
+            "asurakṣita" | "unsafe" => match attr.args() {
+                [arg] if matches!(arg.name(), Some("sūtra" | "sutra")) => {
+                    Ok(BuiltinAttribute::AssumeThreadSafe)
+                }
+                _ => Err(malformed("`#[asurakṣita(sūtra)]`")),
+            },
+            _ if ANNOTATIONS.contains(&name) => Ok(BuiltinAttribute::Annotation),
+            _ => Err(AttributeError::Unknown {
+                name: name.to_string(),
+                span: attr.span,
+            }),
+        }
+    }
+
+    /// Whether the attribute means something on this target
+    fn allowed_on(&self, target: AttributeTarget) -> bool {
+        use AttributeTarget::*;
+        match self {
+            BuiltinAttribute::Test => target == Function,
+            BuiltinAttribute::Inline(_) | BuiltinAttribute::Yama(_) => {
+                matches!(target, Function | Method)
+            }
+            BuiltinAttribute::Deprecated { .. } => matches!(target, Function | TypeDef | Constant),
+            BuiltinAttribute::Declassify => matches!(target, Function | Method | Let),
+            BuiltinAttribute::AssumeThreadSafe => target == TypeDef,
+            BuiltinAttribute::Cfg(_)
+            | BuiltinAttribute::Lint(..)
+            | BuiltinAttribute::Annotation => true,
+        }
+    }
+
+    /// Name used in diagnostics
+    fn display_name(&self) -> &'static str {
+        match self {
+            BuiltinAttribute::Test => "parīkṣā",
+            BuiltinAttribute::Inline(_) => "samāveśa",
+            BuiltinAttribute::Cfg(_) => "cfg",
+            BuiltinAttribute::Deprecated { .. } => "apracalita",
+            BuiltinAttribute::Lint(..) => "lint level",
+            BuiltinAttribute::Yama(_) => "yama",
+            BuiltinAttribute::Declassify => "prakāśana",
+            BuiltinAttribute::AssumeThreadSafe => "asurakṣita",
+            BuiltinAttribute::Annotation => "annotation",
+        }
+    }
+}
+
+/// The yama named in `#[yama(...)]`
+fn yama_from_name(name: &str) -> Option<Yama> {
+    match name {
+        "ahiṃsā" | "ahimsā" | "ahimsa" => Some(Yama::Ahimsa),
+        "satya" => Some(Yama::Satya),
+        "asteya" => Some(Yama::Asteya),
+        "brahmacarya" | "brahmacharya" => Some(Yama::Brahmacarya),
+        "aparigraha" => Some(Yama::Aparigraha),
+        _ => None,
+    }
+}
+
+/// Build settings `cfg` predicates are evaluated against
+#[derive(Debug, Clone)]
+pub struct CfgContext {
+    pub target: Target,
+    /// Optimization level (0-3)
+    pub opt_level: u8,
+    pub guna: Guna,
+    /// Features enabled on the command line and in `Jagannath.toml`
+    pub features: Vec<String>,
+    /// Building tests (`--test`)
+    pub test: bool,
+}
+
+impl CfgContext {
+    pub fn new(target: Target) -> Self {
+        Self {
+            target,
+            opt_level: 2,
+            guna: Guna::Rajas,
+            features: Vec::new(),
+            test: false,
+        }
+    }
+
+    /// Evaluate a predicate
+    ///
+    /// - `sarva(...)` (`all`), `kaścit(...)` (`any`), `na(...)` (`not`)
+    /// - `parīkṣā` (`test`)
+    /// - `lakṣya = "aarch64"` (`target`)
+    /// - `anukūlana = 3` (`opt_level`)
+    /// - `guṇa = "sattva"`
+    /// - `viśeṣatā = "simd"` (`feature`)
+    pub fn eval(&self, predicate: &MetaItem) -> Result<bool, AttributeError> {
+        let unknown = |ident: &Identifier, value: &Literal| AttributeError::UnknownCfg {
+            name: match value {
+                Literal::String(value) => format!("{} = \"{}\"", ident.name, value),
+                Literal::Int(value) => format!("{} = {}", ident.name, value),
+                other => format!("{} = {:?}", ident.name, other),
+            },
+            span: ident.span,
+        };
+        match predicate {
+            MetaItem::List(ident, args) => match ident.name.as_str() {
+                "sarva" | "all" => {
+                    let mut holds = true;
+                    for arg in args {
+                        holds &= self.eval(arg)?;
+                    }
+                    Ok(holds)
+                }
+                "kaścit" | "any" => {
+                    let mut holds = false;
+                    for arg in args {
+                        holds |= self.eval(arg)?;
+                    }
+                    Ok(holds)
+                }
+                "na" | "not" => match args.as_slice() {
+                    [arg] => Ok(!self.eval(arg)?),
+                    _ => Err(AttributeError::Malformed {
+                        name: "cfg".to_string(),
+                        expected: "exactly one predicate in `na(...)`",
+                        span: ident.span,
+                    }),
+                },
+                _ => Err(AttributeError::UnknownCfg {
+                    name: ident.name.clone(),
+                    span: ident.span,
+                }),
+            },
+            MetaItem::Word(ident) if matches!(ident.name.as_str(), "parīkṣā" | "test") => {
+                Ok(self.test)
+            }
+            MetaItem::NameValue(key, value) => match (key.name.as_str(), value) {
+                ("lakṣya" | "target", Literal::String(name)) => match Target::from_name(name) {
+                    Some(target) => Ok(target == self.target),
+                    None => Err(unknown(key, value)),
+                },
+                ("anukūlana" | "opt_level", Literal::Int(level)) if (0..=3).contains(level) => {
+                    Ok(*level == i64::from(self.opt_level))
+                }
+                ("anukūlana" | "opt_level", Literal::String(level)) => match level.parse::<u8>() {
+                    Ok(level) if level <= 3 => Ok(level == self.opt_level),
+                    _ => Err(unknown(key, value)),
+                },
+                ("guṇa" | "guna", Literal::String(name)) => Guna::all()
+                    .iter()
+                    .find(|guna| {
+                        guna.iast_name().eq_ignore_ascii_case(name) || guna.sanskrit_name() == name
+                    })
+                    .map(|guna| *guna == self.guna)
+                    .ok_or_else(|| unknown(key, value)),
+                ("viśeṣatā" | "feature", Literal::String(name)) => {
+                    Ok(self.features.iter().any(|feature| feature == name))
+                }
+                _ => Err(unknown(key, value)),
+            },
+            MetaItem::Word(ident) => Err(AttributeError::UnknownCfg {
+                name: ident.name.clone(),
+                span: ident.span,
+            }),
+            MetaItem::Literal(_) => Err(AttributeError::UnknownCfg {
+                name: "literal".to_string(),
+                span: Span::dummy(),
+            }),
+        }
+    }
+}
+
+/// Errors from attribute checking
+#[derive(Debug, Clone)]
+pub enum AttributeError {
+    /// Name not understood by the compiler
+    Unknown {
+        name: String,
+        span: Span,
+    },
+    /// Understood, but with the wrong arguments
+    Malformed {
+        name: String,
+        expected: &'static str,
+        span: Span,
+    },
+    /// Understood, but meaningless where it is
+    Misplaced {
+        name: &'static str,
+        target: &'static str,
+        span: Span,
+    },
+    UnknownLint {
+        name: String,
+        span: Span,
+    },
+    UnknownYama {
+        name: String,
+        span: Span,
+    },
+    UnknownCfg {
+        name: String,
+        span: Span,
+    },
+    /// A lint set to `niṣedha` fired
+    Denied {
+        lint: Lint,
+        message: String,
+        span: Span,
+    },
+    /// A function broke one of its `#[yama(...)]` vows
+    VowBroken {
+        function: String,
+        vow: Yama,
+        evidence: String,
+        span: Span,
+    },
+}
+
+impl AttributeError {
+    pub fn span(&self) -> Span {
+        match self {
+            AttributeError::Unknown { span, .. }
+            | AttributeError::Malformed { span, .. }
+            | AttributeError::Misplaced { span, .. }
+            | AttributeError::UnknownLint { span, .. }
+            | AttributeError::UnknownYama { span, .. }
+            | AttributeError::UnknownCfg { span, .. }
+            | AttributeError::Denied { span, .. }
+            | AttributeError::VowBroken { span, .. } => *span,
+        }
+    }
+}
+
+impl fmt::Display for AttributeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeError::Unknown { name, .. } => {
+                write!(f, "अज्ञात चिह्न (Unknown attribute): `#[{}]`", name)
+            }
+            AttributeError::Malformed { name, expected, .. } => {
+                write!(
+                    f,
+                    "विकृत चिह्न (Malformed attribute) `#[{}]`: expected {}",
+                    name, expected
+                )
+            }
+            AttributeError::Misplaced { name, target, .. } => {
+                write!(
+                    f,
+                    "अस्थान चिह्न (Misplaced attribute): `{}` cannot be used on {}",
+                    name, target
+                )
+            }
+            AttributeError::UnknownLint { name, .. } => {
+                write!(f, "अज्ञात lint (Unknown lint): `{}`", name)
+            }
+            AttributeError::UnknownYama { name, .. } => {
+                write!(
+                    f,
+                    "अज्ञात यम (Unknown yama): `{}`; expected ahiṃsā, satya, asteya, brahmacarya or aparigraha",
+                    name
+                )
+            }
+            AttributeError::UnknownCfg { name, .. } => {
+                write!(f, "अज्ञात cfg (Unknown cfg predicate): `{}`", name)
+            }
+            AttributeError::Denied { lint, message, .. } => {
+                write!(f, "{} (denied by `niṣedha({})`)", message, lint.name())
+            }
+            AttributeError::VowBroken {
+                function,
+                vow,
+                evidence,
+                ..
+            } => {
+                write!(
+                    f,
+                    "यम भङ्ग (Vow broken): `{}` vows {} but {}",
+                    function,
+                    vow.sanskrit(),
+                    evidence
+                )
+            }
+        }
+    }
+}
+
+/// A lint reported at warning level
+#[derive(Debug, Clone)]
+pub struct LintWarning {
+    pub lint: Lint,
+    pub message: String,
+    pub span: Span,
+}
+
+/// Warnings and errors from [`check_lints`]
+#[derive(Debug, Default)]
+pub struct LintReport {
+    pub warnings: Vec<LintWarning>,
+    pub errors: Vec<AttributeError>,
+}
+
+/// Inlining hint of a function
+pub fn inline_hint(attributes: &[Attribute]) -> InlineHint {
+    attributes
+        .iter()
+        .find_map(|attr| match BuiltinAttribute::parse(attr) {
+            Ok(BuiltinAttribute::Inline(hint)) => Some(hint),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Whether a function is marked `#[parīkṣā]`
+pub fn is_test(attributes: &[Attribute]) -> bool {
+    attributes
+        .iter()
+        .any(|attr| matches!(BuiltinAttribute::parse(attr), Ok(BuiltinAttribute::Test)))
+}
+
+/// Check every attribute and drop what `cfg` and `parīkṣā` leave out of
+/// this build
+///
+/// Returns the source regions that were dropped, attributes included, so
+/// editors can gray out inactive code.
+pub fn configure(ast: &mut Ast, cfg: &CfgContext) -> Result<Vec<Span>, Vec<AttributeError>> {
+    let mut configurer = Configurer {
+        cfg,
+        errors: Vec::new(),
+        inactive: Vec::new(),
+    };
+    ast.items.retain_mut(|item| configurer.configure_item(item));
+    if configurer.errors.is_empty() {
+        Ok(configurer.inactive)
+    } else {
+        Err(configurer.errors)
+    }
+}
+
+/// Validates attributes and evaluates `cfg`
+struct Configurer<'a> {
+    cfg: &'a CfgContext,
+    errors: Vec<AttributeError>,
+    /// Regions left out of the build
+    inactive: Vec<Span>,
+}
+
+impl Configurer<'_> {
+    /// Check the attributes of one node; false if it is left out of the build
+    fn keep(&mut self, attributes: &[Attribute], target: AttributeTarget, span: Span) -> bool {
+        let mut keep = true;
+        for attr in attributes {
+            let builtin = match BuiltinAttribute::parse(attr) {
+                Ok(builtin) => builtin,
+                Err(error) => {
+                    self.errors.push(error);
+                    continue;
+                }
+            };
+            if !builtin.allowed_on(target) {
+                self.errors.push(AttributeError::Misplaced {
+                    name: builtin.display_name(),
+                    target: target.describe(),
+                    span: attr.span,
+                });
+                continue;
+            }
+            match builtin {
+                BuiltinAttribute::Cfg(predicate) => match self.cfg.eval(&predicate) {
+                    Ok(holds) => keep &= holds,
+                    Err(error) => self.errors.push(error),
+                },
+                BuiltinAttribute::Test => keep &= self.cfg.test,
+                _ => {}
+            }
+        }
+        if !keep {
+            let start = attributes.first().map_or(span, |attr| attr.span);
+            self.inactive.push(Span {
+                end: span.end.max(start.end),
+                ..start
+            });
+        }
+        keep
+    }
+
+    fn configure_item(&mut self, item: &mut Item) -> bool {
+        match item {
+            Item::Function(func) => {
+                let keep = self.keep(&func.attributes, AttributeTarget::Function, func.span);
+                self.configure_function(func);
+                keep
+            }
+            Item::TypeDef(typedef) => {
+                let keep = self.keep(&typedef.attributes, AttributeTarget::TypeDef, typedef.span);
+                match &mut typedef.body {
+                    TypeBody::Struct(fields) => {
+                        fields.retain(|f| self.keep(&f.attributes, AttributeTarget::Field, f.span))
+                    }
+                    TypeBody::Enum(variants) => {
+                        for fields in variants.iter_mut().filter_map(|v| v.fields.as_mut()) {
+                            fields.retain(|f| {
+                                self.keep(&f.attributes, AttributeTarget::Field, f.span)
+                            });
+                        }
+                    }
+                    TypeBody::Alias(_) => {}
+                }
+                keep
+            }
+            Item::Import(import) => {
+                self.keep(&import.attributes, AttributeTarget::Import, import.span)
+            }
+            Item::Constant(constant) => {
+                let keep = self.keep(
+                    &constant.attributes,
+                    AttributeTarget::Constant,
+                    constant.span,
+                );
+                let _ = self.visit_expr_mut(&mut constant.value);
+                keep
+            }
+            Item::Module(module) => {
+                let keep = self.keep(&module.attributes, AttributeTarget::Module, module.span);
+                module.items.retain_mut(|item| self.configure_item(item));
+                keep
+            }
+            Item::Trait(trait_def) => {
+                let keep = self.keep(
+                    &trait_def.attributes,
+                    AttributeTarget::Trait,
+                    trait_def.span,
+                );
+                trait_def.methods.retain_mut(|method| {
+                    let keep = self.keep(&method.attributes, AttributeTarget::Method, method.span);
+                    method
+                        .params
+                        .retain(|p| self.keep(&p.attributes, AttributeTarget::Parameter, p.span));
+                    if let Some(body) = &mut method.default_body {
+                        let _ = self.visit_block_mut(body);
+                    }
+                    keep
+                });
+                keep
+            }
+            Item::Impl(impl_def) => {
+                let keep = self.keep(&impl_def.attributes, AttributeTarget::Impl, impl_def.span);
+                impl_def.methods.retain_mut(|method| {
+                    let keep = self.keep(&method.attributes, AttributeTarget::Method, method.span);
+                    self.configure_function(method);
+                    keep
+                });
+                keep
+            }
+            Item::Error(_) => true,
+        }
+    }
+
+    fn configure_function(&mut self, func: &mut FunctionDef) {
+        func.params
+            .retain(|p| self.keep(&p.attributes, AttributeTarget::Parameter, p.span));
+        let _ = self.visit_block_mut(&mut func.body);
+    }
+}
+
+impl AstVisitorMut for Configurer<'_> {
+    type Break = ();
+
+    fn visit_block_mut(&mut self, block: &mut Block) -> VisitResult<Self::Break> {
+        block.stmts.retain_mut(|stmt| {
+            let keep = match stmt {
+                Stmt::Let {
+                    attributes, span, ..
+                } => self.keep(attributes, AttributeTarget::Let, *span),
+                _ => true,
+            };
+            let _ = self.visit_stmt_mut(stmt);
+            keep
+        });
+        self.continue_()
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> VisitResult<Self::Break> {
+        if let Expr::Lambda { params, .. } = expr {
+            params.retain(|p| self.keep(&p.attributes, AttributeTarget::Parameter, p.span));
+        }
+        walk_expr_mut(self, expr)
+    }
+}
+
+/// Report uses of deprecated items, unused attributes and broken yama vows
+///
+/// Runs on the linked program, after `configure` has checked every attribute.
+pub fn check_lints(ast: &Ast) -> LintReport {
+    let mut linter = Linter {
+        deprecated: HashMap::new(),
+        levels: Vec::new(),
+        judge: YamaDharmaraja::new(),
+        report: LintReport::default(),
+    };
+    linter.collect_deprecated(&ast.items);
+    linter.lint_items(&ast.items);
+    linter.report
+}
+
+/// Walks the program with the lint levels in force
+struct Linter {
+    /// Deprecated functions, types and constants with their notes
+    deprecated: HashMap<String, Option<String>>,
+    /// Levels set by enclosing attributes, innermost last
+    levels: Vec<HashMap<Lint, LintLevel>>,
+    judge: YamaDharmaraja,
+    report: LintReport,
+}
+
+impl Linter {
+    fn collect_deprecated(&mut self, items: &[Item]) {
+        for item in items {
+            let name = match item {
+                Item::Function(f) => &f.name.name,
+                Item::TypeDef(t) => &t.name.name,
+                Item::Constant(c) => &c.name.name,
+                Item::Module(m) => {
+                    self.collect_deprecated(&m.items);
+                    continue;
+                }
+                _ => continue,
+            };
+            for attr in item.attributes() {
+                if let Ok(BuiltinAttribute::Deprecated { note }) = BuiltinAttribute::parse(attr) {
+                    self.deprecated.insert(name.clone(), note);
+                }
+            }
+        }
+    }
+
+    /// Report a lint at the level in force
+    fn emit(&mut self, lint: Lint, message: String, span: Span) {
+        let level = self
+            .levels
+            .iter()
+            .rev()
+            .find_map(|levels| levels.get(&lint).copied())
+            .unwrap_or(LintLevel::Warn);
+        match level {
+            LintLevel::Allow => {}
+            LintLevel::Warn => self.report.warnings.push(LintWarning {
+                lint,
+                message,
+                span,
+            }),
+            LintLevel::Deny => self.report.errors.push(AttributeError::Denied {
+                lint,
+                message,
+                span,
+            }),
+        }
+    }
+
+    /// Enter the scope of a node's attributes; pair with `levels.pop()`
+    fn enter(&mut self, attributes: &[Attribute]) {
+        let mut levels = HashMap::new();
+        for attr in attributes {
+            if let Ok(BuiltinAttribute::Lint(level, lints)) = BuiltinAttribute::parse(attr) {
+                for lint in lints {
+                    levels.insert(lint, level);
+                }
+            }
+        }
+        self.levels.push(levels);
+        self.check_unused(attributes);
+    }
+
+    /// Attributes that may be given once, given again
+    fn check_unused(&mut self, attributes: &[Attribute]) {
+        let mut seen = Vec::new();
+        for attr in attributes {
+            let once = matches!(
+                BuiltinAttribute::parse(attr),
+                Ok(BuiltinAttribute::Test
+                    | BuiltinAttribute::Inline(_)
+                    | BuiltinAttribute::Deprecated { .. }
+                    | BuiltinAttribute::Declassify
+                    | BuiltinAttribute::AssumeThreadSafe)
+            );
+            if !once {
+                continue;
+            }
+            if seen.contains(&attr.name()) {
+                self.emit(
+                    Lint::UnusedAttributes,
+                    format!("unused attribute: `#[{}]` is given twice", attr.name()),
+                    attr.span,
+                );
+            }
+            seen.push(attr.name());
+        }
+    }
+
+    fn lint_items(&mut self, items: &[Item]) {
+        for item in items {
+            self.enter(item.attributes());
+            match item {
+                Item::Function(func) => self.lint_function(func),
+                Item::Constant(constant) => {
+                    let _ = self.visit_expr(&constant.value);
+                }
+                Item::Module(module) => self.lint_items(&module.items),
+                Item::Impl(impl_def) => {
+                    for method in &impl_def.methods {
+                        self.enter(&method.attributes);
+                        self.lint_function(method);
+                        self.levels.pop();
+                    }
+                }
+                Item::Trait(trait_def) => {
+                    for method in &trait_def.methods {
+                        self.enter(&method.attributes);
+                        if let Some(body) = &method.default_body {
+                            let _ = self.visit_block(body);
+                        }
+                        self.levels.pop();
+                    }
+                }
+                Item::TypeDef(_) | Item::Import(_) | Item::Error(_) => {}
+            }
+            self.levels.pop();
+        }
+    }
+
+    fn lint_function(&mut self, func: &FunctionDef) {
+        let vows: Vec<Yama> = func
+            .attributes
+            .iter()
+            .filter_map(|attr| match BuiltinAttribute::parse(attr) {
+                Ok(BuiltinAttribute::Yama(vows)) => Some(vows),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        if !vows.is_empty() {
+            for (vow, violation) in self.judge.judge_vows(func, &vows) {
+                self.report.errors.push(AttributeError::VowBroken {
+                    function: func.name.name.clone(),
+                    vow,
+                    evidence: violation.evidence.to_lowercase(),
+                    span: func.span,
+                });
+            }
+        }
+        let _ = self.visit_block(&func.body);
+    }
+
+    fn check_use(&mut self, name: &str, span: Span) {
+        if let Some(note) = self.deprecated.get(name).cloned() {
+            let message = match note {
+                Some(note) => format!("use of deprecated `{}`: {}", name, note),
+                None => format!("use of deprecated `{}`", name),
+            };
+            self.emit(Lint::Deprecated, message, span);
+        }
+    }
+}
+
+impl AstVisitor for Linter {
+    type Break = ();
+
+    fn visit_stmt(&mut self, stmt: &Stmt) -> VisitResult<Self::Break> {
+        if let Stmt::Let { attributes, .. } = stmt {
+            self.enter(attributes);
+            let result = walk_stmt(self, stmt);
+            self.levels.pop();
+            return result;
+        }
+        walk_stmt(self, stmt)
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) -> VisitResult<Self::Break> {
+        match expr {
+            Expr::Identifier(ident) => self.check_use(&ident.name, ident.span),
+            Expr::StructConstruct { name, span, .. } => self.check_use(&name.name, *span),
+            _ => {}
+        }
+        walk_expr(self, expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn configured(source: &str, cfg: &CfgContext) -> Result<Ast, Vec<AttributeError>> {
+        let mut ast = Parser::parse_str(source).expect("parse");
+        configure(&mut ast, cfg).map(|_| ast)
+    }
+
+    #[test]
+    fn test_cfg_target_selects_items() {
+        let source = r#"
+#[cfg(lakṣya = "aarch64")]
+kāryakrama vāstu() {}
+#[cfg(lakṣya = "x86_64")]
+kāryakrama vāstu() {}
+"#;
+        let ast = configured(source, &CfgContext::new(Target::X86_64)).unwrap();
+        assert_eq!(ast.items.len(), 1);
+    }
+
+    #[test]
+    fn test_cfg_predicates() {
+        let mut cfg = CfgContext::new(Target::RiscV64);
+        cfg.opt_level = 3;
+        cfg.guna = Guna::Tamas;
+        cfg.features = vec!["simd".to_string()];
+        let holds = |predicate: &str| {
+            let source = format!("#[cfg({})]\nkāryakrama f() {{}}\n", predicate);
+            let ast = Parser::parse_str(&source).expect("parse");
+            cfg.eval(&ast.items[0].attributes()[0].args()[0])
+        };
+        assert!(holds(r#"sarva(lakṣya = "riscv64", anukūlana = 3)"#).unwrap());
+        assert!(!holds(r#"sarva(lakṣya = "riscv64", anukūlana = "0")"#).unwrap());
+        assert!(holds(r#"kaścit(guṇa = "sattva", guṇa = "tamas")"#).unwrap());
+        assert!(holds(r#"na(viśeṣatā = "gpu")"#).unwrap());
+        assert!(holds(r#"all(feature = "simd", not(test))"#).unwrap());
+        assert!(holds(r#"guṇa = "rajasika""#).is_err());
+        assert!(holds(r#"anukūlana = 7"#).is_err());
+        assert!(holds("na(parīkṣā, parīkṣā)").is_err());
+    }
+
+    #[test]
+    fn test_inactive_regions() {
+        let source = "kāryakrama a() {}\n#[cfg(lakṣya = \"aarch64\")]\nkāryakrama b() {}\n";
+        let mut ast = Parser::parse_str(source).unwrap();
+        let inactive = configure(&mut ast, &CfgContext::new(Target::X86_64)).unwrap();
+        assert_eq!(inactive.len(), 1);
+        assert_eq!(inactive[0].line, 2);
+        assert!(source[inactive[0].start..inactive[0].end].starts_with("#[cfg"));
+        assert!(source[inactive[0].start..inactive[0].end].ends_with('}'));
+    }
+
+    #[test]
+    fn test_test_functions_need_test_build() {
+        let source = "#[parīkṣā]\nkāryakrama jāñca() {}\n";
+        let mut cfg = CfgContext::new(Target::X86_64);
+        assert!(configured(source, &cfg).unwrap().items.is_empty());
+        cfg.test = true;
+        assert_eq!(configured(source, &cfg).unwrap().items.len(), 1);
+    }
+
+    #[test]
+    fn test_inline_hint() {
+        let ast = Parser::parse_str("#[samāveśa(na)]\nkāryakrama f() {}\n").unwrap();
+        assert_eq!(inline_hint(ast.items[0].attributes()), InlineHint::Never);
+    }
+}
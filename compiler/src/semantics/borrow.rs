@@ -14,6 +14,7 @@
 //! Reference: Walker (2002) "Substructural Type Systems" in ATTAPL
 //! Reference: Rust Reference on Ownership and Borrowing
 
+use super::closure::{analyze_captures, CaptureMode, ClosureCaptures, ClosureKind};
 use crate::lexer::{Affix, Span};
 use crate::parser::ast::*;
 use std::collections::{HashMap, HashSet};
@@ -32,6 +33,8 @@ pub struct BorrowChecker {
     errors: Vec<BorrowError>,
     /// Function context for return checking
     function_context: Option<FunctionContext>,
+    /// Locals bound to closures that can be called only once
+    once_closures: HashSet<String>,
 }
 
 /// Complete ownership information for a value
@@ -85,6 +88,53 @@ pub enum OwnershipKind {
     Copy,
 }
 
+impl OwnershipKind {
+    /// Whether using the value copies it rather than moving it
+    ///
+    /// Shared borrows and globals can be used any number of times.
+    pub fn is_copy(self) -> bool {
+        matches!(
+            self,
+            OwnershipKind::Copy
+                | OwnershipKind::Global
+                | OwnershipKind::Borrowed { mutable: false }
+        )
+    }
+
+    /// Whether passing the value as an argument reborrows it for the call
+    /// instead of moving it, as with a `&mut` parameter passed on
+    pub fn is_reborrowed(self) -> bool {
+        matches!(self, OwnershipKind::Borrowed { mutable: true })
+    }
+
+    /// Ownership kind declared by a type's affixes
+    pub fn of_type(ty: &Type) -> Self {
+        match ty {
+            Type::Named { name, affixes, .. } => {
+                // Check for -l (linear)
+                if affixes.contains(&Affix::L) {
+                    return OwnershipKind::Linear;
+                }
+                // Check for -b (borrowed)
+                if affixes.contains(&Affix::B) {
+                    let mutable = affixes.contains(&Affix::Aa); // -ā for mutable
+                    return OwnershipKind::Borrowed { mutable };
+                }
+                // Check for -g (global)
+                if affixes.contains(&Affix::G) {
+                    return OwnershipKind::Global;
+                }
+                if is_copy_type_name(&name.name) {
+                    return OwnershipKind::Copy;
+                }
+                OwnershipKind::Affine
+            }
+            Type::Reference { mutable, .. } => OwnershipKind::Borrowed { mutable: *mutable },
+            _ => OwnershipKind::Affine,
+        }
+    }
+}
+
 /// Information about an active borrow
 #[derive(Debug, Clone)]
 pub struct BorrowInfo {
@@ -172,6 +222,7 @@ impl BorrowChecker {
             current_scope: 0,
             errors: Vec::new(),
             function_context: None,
+            once_closures: HashSet::new(),
         }
     }
 
@@ -181,6 +232,7 @@ impl BorrowChecker {
         self.owned.clear();
         self.borrows.clear();
         self.errors.clear();
+        self.once_closures.clear();
         self.scope_stack.clear();
         self.scope_stack.push(ScopeInfo {
             id: 0,
@@ -251,6 +303,7 @@ impl BorrowChecker {
                 ty,
                 value,
                 span,
+                ..
             } => {
                 self.check_let(name, ty.as_ref(), value.as_ref(), span.clone())?;
             }
@@ -268,20 +321,18 @@ impl BorrowChecker {
             } => {
                 self.check_if(condition, then_block, else_block.as_ref(), span.clone())?;
             }
-            Stmt::Match {
-                scrutinee,
-                arms,
-                span,
+            Stmt::Loop {
+                kind, body, span, ..
             } => {
-                self.check_match(scrutinee, arms, span.clone())?;
-            }
-            Stmt::Loop { kind, body, span } => {
                 self.check_loop(kind, body, span.clone())?;
             }
-            Stmt::Break { span: _ } => {
-                // Break is valid - no borrow checking needed
+            Stmt::Break { value, .. } => {
+                // The break value moves out of the loop like any other use
+                if let Some(value) = value {
+                    self.check_expr(value)?;
+                }
             }
-            Stmt::Continue { span: _ } => {
+            Stmt::Continue { .. } => {
                 // Continue is valid - no borrow checking needed
             }
         }
@@ -296,10 +347,12 @@ impl BorrowChecker {
         value: Option<&Expr>,
         span: Span,
     ) -> Result<(), Vec<BorrowError>> {
-        // Determine ownership kind from type affixes
-        let kind = ty
-            .map(|t| self.extract_ownership_kind(t))
-            .unwrap_or(OwnershipKind::Affine);
+        // Determine ownership kind from type affixes, or from the value
+        let kind = match (ty, value) {
+            (Some(t), _) => self.extract_ownership_kind(t),
+            (None, Some(v)) => self.infer_ownership_kind(v),
+            (None, None) => OwnershipKind::Affine,
+        };
         let ty_str = ty
             .map(|t| self.type_to_string(t))
             .unwrap_or_else(|| "unknown".to_string());
@@ -314,6 +367,19 @@ impl BorrowChecker {
             }
         }
 
+        // A closure that consumes its captures is used up by its first call
+        let once = match value {
+            Some(Expr::Lambda { params, body, .. }) => {
+                self.closure_captures(params, body).kind == ClosureKind::FnOnce
+            }
+            _ => false,
+        };
+        if once {
+            self.once_closures.insert(name.name.clone());
+        } else {
+            self.once_closures.remove(&name.name);
+        }
+
         // Record the new owned value
         self.record_owned(name.name.clone(), ty_str, kind, span);
 
@@ -339,11 +405,14 @@ impl BorrowChecker {
             }
 
             Expr::Unary { op, operand, span } => {
+                // The operand must be usable before it can be borrowed
+                self.check_expr(operand)?;
                 // Check for address-of (borrow) operations
                 match op {
-                    UnaryOp::Ref => {
+                    UnaryOp::Ref | UnaryOp::RefMut => {
                         if let Expr::Identifier(id) = operand.as_ref() {
-                            self.record_borrow_from_expr(&id.name, false, span.clone())?;
+                            let mutable = *op == UnaryOp::RefMut;
+                            self.record_borrow_from_expr(&id.name, mutable, *span)?;
                         }
                     }
                     UnaryOp::Deref => {
@@ -351,15 +420,24 @@ impl BorrowChecker {
                     }
                     _ => {}
                 }
-                self.check_expr(operand)?;
                 Ok(OwnershipState::Owned)
             }
 
-            Expr::Call { callee, args, span } => {
+            Expr::Call {
+                callee,
+                args,
+                span: _,
+            } => {
                 // Check callee expression
                 self.check_expr(callee)?;
+                if let Expr::Identifier(id) = callee.as_ref() {
+                    if self.once_closures.contains(&id.name) {
+                        self.handle_move_or_copy(&id.name, "<call>", id.span)?;
+                    }
+                }
 
                 // Check arguments - each may move or borrow
+                let outer_borrows = self.borrow_counts();
                 for arg in args {
                     self.check_expr(arg)?;
 
@@ -367,12 +445,13 @@ impl BorrowChecker {
                     if let Expr::Identifier(id) = arg {
                         // For simplicity, assume non-Copy types are moved
                         if let Some(info) = self.owned.get(&id.name) {
-                            if info.kind != OwnershipKind::Copy {
-                                self.handle_move_or_copy(&id.name, "<function_arg>", span.clone())?;
+                            if !info.kind.is_copy() && !info.kind.is_reborrowed() {
+                                self.handle_move_or_copy(&id.name, "<function_arg>", id.span)?;
                             }
                         }
                     }
                 }
+                self.end_argument_borrows(&outer_borrows);
                 Ok(OwnershipState::Owned)
             }
 
@@ -396,10 +475,17 @@ impl BorrowChecker {
                 Ok(OwnershipState::Owned)
             }
 
+            Expr::Range { start, end, .. } => {
+                for bound in start.iter().chain(end) {
+                    self.check_expr(bound)?;
+                }
+                Ok(OwnershipState::Owned)
+            }
+
             Expr::StructConstruct {
                 name: _,
                 fields,
-                span,
+                span: _,
             } => {
                 for (_, field_expr) in fields {
                     self.check_expr(field_expr)?;
@@ -407,8 +493,8 @@ impl BorrowChecker {
                     // Field values may be moved into struct
                     if let Expr::Identifier(id) = field_expr {
                         if let Some(info) = self.owned.get(&id.name) {
-                            if info.kind != OwnershipKind::Copy {
-                                self.handle_move_or_copy(&id.name, "<struct_field>", span.clone())?;
+                            if !info.kind.is_copy() {
+                                self.handle_move_or_copy(&id.name, "<struct_field>", id.span)?;
                             }
                         }
                     }
@@ -430,12 +516,24 @@ impl BorrowChecker {
                 Ok(OwnershipState::Owned)
             }
 
+            // Formatting only reads its arguments
+            Expr::Format { args, .. } => {
+                for arg in args {
+                    self.check_expr(arg)?;
+                }
+                Ok(OwnershipState::Owned)
+            }
+
             Expr::Lambda {
                 params,
                 body,
                 span: _,
             } => {
-                // Lambda captures - check what's captured
+                let captures = self.closure_captures(params, body);
+
+                // The body runs later; its moves consume the environment, not
+                // the enclosing function's variables
+                let outer_state = self.owned.clone();
                 self.enter_scope(false);
 
                 // Register parameters
@@ -452,6 +550,14 @@ impl BorrowChecker {
                 // Lambda body is an expression
                 self.check_expr(body)?;
                 self.exit_scope();
+                self.owned = outer_state;
+
+                // Values captured by move are no longer usable outside
+                for capture in &captures.captures {
+                    if capture.mode == CaptureMode::Move {
+                        self.handle_move_or_copy(&capture.name, "<pāśa>", capture.span)?;
+                    }
+                }
 
                 Ok(OwnershipState::Owned)
             }
@@ -460,21 +566,23 @@ impl BorrowChecker {
                 receiver,
                 method: _,
                 args,
-                span,
+                span: _,
             } => {
                 self.check_expr(receiver)?;
+                let outer_borrows = self.borrow_counts();
                 for arg in args {
                     self.check_expr(arg)?;
 
                     // Arguments may be moved
                     if let Expr::Identifier(id) = arg {
                         if let Some(info) = self.owned.get(&id.name) {
-                            if info.kind != OwnershipKind::Copy {
-                                self.handle_move_or_copy(&id.name, "<method_arg>", span.clone())?;
+                            if !info.kind.is_copy() && !info.kind.is_reborrowed() {
+                                self.handle_move_or_copy(&id.name, "<method_arg>", id.span)?;
                             }
                         }
                     }
                 }
+                self.end_argument_borrows(&outer_borrows);
                 Ok(OwnershipState::Owned)
             }
 
@@ -500,6 +608,15 @@ impl BorrowChecker {
                 Ok(OwnershipState::Owned)
             }
 
+            Expr::Match {
+                scrutinee,
+                arms,
+                span,
+            } => {
+                self.check_match(scrutinee, arms, *span)?;
+                Ok(OwnershipState::Owned)
+            }
+
             Expr::Block(block) => {
                 self.enter_scope(false);
                 self.check_block(block)?;
@@ -507,6 +624,13 @@ impl BorrowChecker {
                 Ok(OwnershipState::Owned)
             }
 
+            Expr::Loop {
+                kind, body, span, ..
+            } => {
+                self.check_loop(kind, body, *span)?;
+                Ok(OwnershipState::Owned)
+            }
+
             Expr::Cast {
                 expr,
                 ty: _,
@@ -516,6 +640,13 @@ impl BorrowChecker {
             Expr::Try { expr, span: _ } => self.check_expr(expr),
 
             Expr::Await { expr, span: _ } => self.check_expr(expr),
+
+            Expr::Saha { task, span: _ } => {
+                self.check_expr(task)?;
+                Ok(OwnershipState::Owned)
+            }
+
+            Expr::Error(_) => Ok(OwnershipState::Owned),
         }
     }
 
@@ -536,6 +667,7 @@ impl BorrowChecker {
                 OwnershipState::Consumed => {
                     self.errors.push(BorrowError::UseAfterConsume {
                         name: name.to_string(),
+                        span,
                     });
                     Err(std::mem::take(&mut self.errors))
                 }
@@ -647,6 +779,9 @@ impl BorrowChecker {
 
             self.enter_scope(false);
             self.check_pattern(&arm.pattern, span.clone())?;
+            if let Some(guard) = &arm.guard {
+                self.check_expr(guard)?;
+            }
             self.check_expr(&arm.body)?;
             arm_states.push(self.owned.clone());
             self.exit_scope();
@@ -852,7 +987,7 @@ impl BorrowChecker {
             match info.state {
                 OwnershipState::Owned => {
                     // Check if Copy type
-                    if info.kind == OwnershipKind::Copy {
+                    if info.kind.is_copy() {
                         // Copy - original stays valid
                         return Ok(());
                     }
@@ -875,12 +1010,15 @@ impl BorrowChecker {
                 }]),
                 OwnershipState::Consumed => Err(vec![BorrowError::UseAfterConsume {
                     name: from.to_string(),
+                    span,
                 }]),
                 OwnershipState::BorrowedMut => Err(vec![BorrowError::MoveWhileBorrowed {
                     name: from.to_string(),
+                    span,
                 }]),
                 OwnershipState::BorrowedShared => Err(vec![BorrowError::MoveWhileBorrowed {
                     name: from.to_string(),
+                    span,
                 }]),
                 _ => Ok(()),
             }
@@ -908,6 +1046,7 @@ impl BorrowChecker {
                                     owner: owner.to_string(),
                                     existing: borrow.borrower.clone(),
                                     new: "<expr>".to_string(),
+                                    span,
                                 }]);
                             }
                         }
@@ -942,6 +1081,7 @@ impl BorrowChecker {
                 }
                 OwnershipState::Moved => Err(vec![BorrowError::BorrowAfterMove {
                     name: owner.to_string(),
+                    span,
                 }]),
                 OwnershipState::BorrowedMut if mutable => {
                     Err(vec![BorrowError::SecondMutableBorrow {
@@ -960,16 +1100,56 @@ impl BorrowChecker {
         }
     }
 
-    /// Record a borrow with explicit borrower name
+    /// Number of borrows currently held on each owner
+    fn borrow_counts(&self) -> HashMap<String, usize> {
+        self.borrows
+            .iter()
+            .map(|(owner, borrows)| (owner.clone(), borrows.len()))
+            .collect()
+    }
+
+    /// End the borrows taken by call arguments, like `f(&mut a)`, which
+    /// last only as long as the call
+    ///
+    /// `outer` is [`Self::borrow_counts`] from before the arguments.
+    fn end_argument_borrows(&mut self, outer: &HashMap<String, usize>) {
+        for (owner, borrows) in self.borrows.iter_mut() {
+            let held = outer.get(owner).copied().unwrap_or(0);
+            if borrows.len() <= held {
+                continue;
+            }
+            borrows.truncate(held);
+            if let Some(info) = self.owned.get_mut(owner) {
+                info.state = if borrows.is_empty() {
+                    OwnershipState::Owned
+                } else if borrows.iter().any(|b| b.mutable) {
+                    OwnershipState::BorrowedMut
+                } else {
+                    OwnershipState::BorrowedShared
+                };
+            }
+        }
+    }
+
+    /// Record a borrow with explicit borrower name, taken at `span`
     pub fn record_borrow(
         &mut self,
         borrower: String,
         owner: &str,
         mutable: bool,
         region: u8,
+        span: Span,
     ) -> Result<(), BorrowError> {
         match self.owned.get(owner) {
-            Some(info) if info.state == OwnershipState::Owned => {
+            // Already borrowed values reach the conflict check below
+            Some(info)
+                if matches!(
+                    info.state,
+                    OwnershipState::Owned
+                        | OwnershipState::BorrowedShared
+                        | OwnershipState::BorrowedMut
+                ) =>
+            {
                 // Check for conflicting borrows
                 if let Some(existing_borrows) = self.borrows.get(owner) {
                     for borrow in existing_borrows {
@@ -979,6 +1159,7 @@ impl BorrowChecker {
                                     owner: owner.to_string(),
                                     existing: borrow.borrower.clone(),
                                     new: borrower,
+                                    span,
                                 });
                             }
                         }
@@ -991,7 +1172,7 @@ impl BorrowChecker {
                     mutable,
                     region,
                     start_scope: self.current_scope,
-                    span: Span::dummy(),
+                    span,
                     path: BorrowPath::Full,
                 };
 
@@ -1014,9 +1195,13 @@ impl BorrowChecker {
             Some(info) if info.state == OwnershipState::Moved => {
                 Err(BorrowError::BorrowAfterMove {
                     name: owner.to_string(),
+                    span,
                 })
             }
-            _ => Err(BorrowError::UnknownValue(owner.to_string())),
+            _ => Err(BorrowError::UnknownValue {
+                name: owner.to_string(),
+                span,
+            }),
         }
     }
 
@@ -1042,8 +1227,8 @@ impl BorrowChecker {
         }
     }
 
-    /// Consume a linear value
-    pub fn consume_linear(&mut self, name: &str) -> Result<(), BorrowError> {
+    /// Consume a linear value at `span`
+    pub fn consume_linear(&mut self, name: &str, span: Span) -> Result<(), BorrowError> {
         match self.owned.get(name) {
             Some(info) if info.state == OwnershipState::Owned => {
                 if info.kind == OwnershipKind::Linear {
@@ -1062,14 +1247,18 @@ impl BorrowChecker {
             Some(info) if info.state == OwnershipState::Consumed => {
                 Err(BorrowError::DoubleConsume {
                     name: name.to_string(),
+                    span,
                 })
             }
             Some(info) if info.state == OwnershipState::Moved => Err(BorrowError::UseAfterMove {
                 name: name.to_string(),
                 moved_at: info.move_history.last().map(|m| m.span.clone()),
-                used_at: Span::dummy(),
+                used_at: span,
+            }),
+            _ => Err(BorrowError::UnknownValue {
+                name: name.to_string(),
+                span,
             }),
-            _ => Err(BorrowError::UnknownValue(name.to_string())),
         }
     }
 
@@ -1197,24 +1386,34 @@ impl BorrowChecker {
 
     /// Extract ownership kind from type affixes
     fn extract_ownership_kind(&self, ty: &Type) -> OwnershipKind {
-        match ty {
-            Type::Named { affixes, .. } => {
-                // Check for -l (linear)
-                if affixes.contains(&Affix::L) {
-                    return OwnershipKind::Linear;
-                }
-                // Check for -b (borrowed)
-                if affixes.contains(&Affix::B) {
-                    let mutable = affixes.contains(&Affix::Aa); // -ā for mutable
-                    return OwnershipKind::Borrowed { mutable };
-                }
-                // Check for -g (global)
-                if affixes.contains(&Affix::G) {
-                    return OwnershipKind::Global;
-                }
-                OwnershipKind::Affine
-            }
-            Type::Reference { mutable, .. } => OwnershipKind::Borrowed { mutable: *mutable },
+        OwnershipKind::of_type(ty)
+    }
+
+    /// Captures of a closure, from the ownership of the variables it uses
+    fn closure_captures(&self, params: &[Parameter], body: &Expr) -> ClosureCaptures {
+        analyze_captures(params, body, |name| {
+            self.owned.get(name).map(|info| info.kind)
+        })
+    }
+
+    /// Infer ownership kind of an unannotated binding from its value
+    fn infer_ownership_kind(&self, value: &Expr) -> OwnershipKind {
+        match value {
+            Expr::Literal(_) => OwnershipKind::Copy,
+            Expr::Identifier(id) => self
+                .owned
+                .get(&id.name)
+                .map(|info| info.kind)
+                .unwrap_or(OwnershipKind::Affine),
+            Expr::Binary { .. } => OwnershipKind::Copy,
+            Expr::Unary {
+                op: UnaryOp::Neg | UnaryOp::Not,
+                ..
+            } => OwnershipKind::Copy,
+            Expr::Unary {
+                op: UnaryOp::Ref, ..
+            } => OwnershipKind::Borrowed { mutable: false },
+            Expr::Cast { ty, .. } => self.extract_ownership_kind(ty),
             _ => OwnershipKind::Affine,
         }
     }
@@ -1233,6 +1432,10 @@ impl BorrowChecker {
                 let prefix = if *mutable { "&mut " } else { "&" };
                 format!("{}{}", prefix, self.type_to_string(inner))
             }
+            Type::Const(_) => match ty.const_value() {
+                Some(value) => value.to_string(),
+                None => "{..}".to_string(),
+            },
             Type::Inferred => "_".to_string(),
         }
     }
@@ -1253,21 +1456,22 @@ pub enum BorrowError {
         used_at: Span,
     },
     /// Use after the value was consumed (linear type)
-    UseAfterConsume { name: String },
+    UseAfterConsume { name: String, span: Span },
     /// Move while borrowed
-    MoveWhileBorrowed { name: String },
+    MoveWhileBorrowed { name: String, span: Span },
     /// Borrow after move
-    BorrowAfterMove { name: String },
+    BorrowAfterMove { name: String, span: Span },
     /// Conflicting borrow (multiple mutable or mutable+shared)
     ConflictingBorrow {
         owner: String,
         existing: String,
         new: String,
+        span: Span,
     },
     /// Double consumption of linear value
-    DoubleConsume { name: String },
+    DoubleConsume { name: String, span: Span },
     /// Unknown value reference
-    UnknownValue(String),
+    UnknownValue { name: String, span: Span },
     /// Linear value not consumed
     LinearNotConsumed { name: String, span: Span },
     /// Return of local reference
@@ -1286,6 +1490,29 @@ pub enum BorrowError {
     PartialMoveInBranch { name: String, span: Span },
 }
 
+impl BorrowError {
+    /// Get the primary span of the error
+    pub fn span(&self) -> Span {
+        match self {
+            BorrowError::UseAfterMove { used_at, .. } => *used_at,
+            BorrowError::UseAfterConsume { span, .. }
+            | BorrowError::MoveWhileBorrowed { span, .. }
+            | BorrowError::BorrowAfterMove { span, .. }
+            | BorrowError::ConflictingBorrow { span, .. }
+            | BorrowError::DoubleConsume { span, .. }
+            | BorrowError::UnknownValue { span, .. }
+            | BorrowError::LinearNotConsumed { span, .. }
+            | BorrowError::ReturnLocalReference { span, .. }
+            | BorrowError::UseWhileMutablyBorrowed { span, .. }
+            | BorrowError::UseOfPartiallyMoved { span, .. }
+            | BorrowError::UseOfUninitialized { span, .. }
+            | BorrowError::SecondMutableBorrow { span, .. }
+            | BorrowError::BorrowWhileMutablyBorrowed { span, .. }
+            | BorrowError::PartialMoveInBranch { span, .. } => *span,
+        }
+    }
+}
+
 impl std::fmt::Display for BorrowError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -1300,23 +1527,21 @@ impl std::fmt::Display for BorrowError {
                 }
                 Ok(())
             }
-            BorrowError::UseAfterConsume { name } => {
+            BorrowError::UseAfterConsume { name, .. } => {
                 write!(
                     f,
                     "use of consumed linear value `{}` (must be used exactly once)",
                     name
                 )
             }
-            BorrowError::MoveWhileBorrowed { name } => {
+            BorrowError::MoveWhileBorrowed { name, .. } => {
                 write!(f, "cannot move `{}` while it is borrowed", name)
             }
-            BorrowError::BorrowAfterMove { name } => {
+            BorrowError::BorrowAfterMove { name, .. } => {
                 write!(f, "cannot borrow `{}` after it has been moved", name)
             }
             BorrowError::ConflictingBorrow {
-                owner,
-                existing,
-                new: _,
+                owner, existing, ..
             } => {
                 write!(
                     f,
@@ -1324,10 +1549,10 @@ impl std::fmt::Display for BorrowError {
                     owner, existing
                 )
             }
-            BorrowError::DoubleConsume { name } => {
+            BorrowError::DoubleConsume { name, .. } => {
                 write!(f, "linear value `{}` consumed more than once", name)
             }
-            BorrowError::UnknownValue(name) => {
+            BorrowError::UnknownValue { name, .. } => {
                 write!(f, "unknown value `{}`", name)
             }
             BorrowError::LinearNotConsumed { name, span } => {
@@ -1376,6 +1601,36 @@ impl std::fmt::Display for BorrowError {
 
 impl std::error::Error for BorrowError {}
 
+/// Check if a type name denotes a primitive that is copied on use
+pub(crate) fn is_copy_type_name(name: &str) -> bool {
+    matches!(
+        name,
+        "i8" | "i16"
+            | "i32"
+            | "i64"
+            | "u8"
+            | "u16"
+            | "u32"
+            | "u64"
+            | "f32"
+            | "f64"
+            | "bool"
+            | "char"
+            | "()"
+            | "saṅkhyā"
+            | "saṅkhyā8"
+            | "saṅkhyā16"
+            | "saṅkhyā32"
+            | "saṅkhyā64"
+            | "daśamika"
+            | "daśamika32"
+            | "daśamika64"
+            | "satya"
+            | "akṣara"
+            | "śūnya"
+    )
+}
+
 impl Default for BorrowChecker {
     fn default() -> Self {
         Self::new()
@@ -1445,8 +1700,21 @@ mod tests {
         );
 
         // First immutable borrow
-        let result = checker.record_borrow("ref1".to_string(), "x", false, 0);
+        let result = checker.record_borrow("ref1".to_string(), "x", false, 0, Span::dummy());
         assert!(result.is_ok());
+
+        // A mutable borrow conflicts with it, reported where it is taken
+        let at = Span {
+            start: 40,
+            end: 46,
+            line: 3,
+            column: 9,
+        };
+        let error = checker
+            .record_borrow("ref2".to_string(), "x", true, 0, at)
+            .unwrap_err();
+        assert!(matches!(error, BorrowError::ConflictingBorrow { .. }));
+        assert_eq!(error.span(), at);
     }
 
     #[test]
@@ -1461,16 +1729,23 @@ mod tests {
         );
 
         // Consume the linear value
-        let result = checker.consume_linear("resource");
+        let result = checker.consume_linear("resource", Span::dummy());
         assert!(result.is_ok());
         assert_eq!(
             checker.owned.get("resource").unwrap().state,
             OwnershipState::Consumed
         );
 
-        // Double consume should fail
-        let result2 = checker.consume_linear("resource");
-        assert!(result2.is_err());
+        // Double consume should fail, at the second consumption
+        let at = Span {
+            start: 52,
+            end: 60,
+            line: 4,
+            column: 5,
+        };
+        let error = checker.consume_linear("resource", at).unwrap_err();
+        assert!(matches!(error, BorrowError::DoubleConsume { .. }));
+        assert_eq!(error.span(), at);
     }
 
     #[test]
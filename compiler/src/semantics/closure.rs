@@ -0,0 +1,409 @@
+//! Pāśa (पाश) - Closures and Their Captures
+//!
+//! A closure (`|x| x + n`) binds the variables of its enclosing function
+//! that its body uses, as the noose (pāśa) binds what it holds. This module
+//! works out which variables a closure captures and how, and from that which
+//! of the Fn-family traits it implements.
+//!
+//! Capture modes follow the ownership affixes of the captured variable:
+//! - `-l` (linear) values are moved into the closure
+//! - `-b-ā` (unique borrows) are moved too; they cannot be duplicated
+//! - copy types, globals and shared `-b` borrows are copied
+//! - other values are borrowed, mutably if the body assigns to them
+
+use std::collections::HashSet;
+
+use crate::lexer::Span;
+use crate::parser::ast::{BinaryOp, Expr, LoopKind, Parameter, Pattern, Stmt, VariantFields};
+use crate::semantics::borrow::OwnershipKind;
+use crate::visitor::{walk_expr, AstVisitor, VisitResult};
+
+/// How a variable is held by a closure environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CaptureMode {
+    /// Value copied into the environment
+    Copy,
+    /// Shared borrow of the variable
+    Ref,
+    /// Mutable borrow of the variable
+    MutRef,
+    /// Value moved into the environment; the variable is no longer usable
+    Move,
+}
+
+impl CaptureMode {
+    /// Whether the environment holds the variable's address
+    pub fn is_by_ref(self) -> bool {
+        matches!(self, CaptureMode::Ref | CaptureMode::MutRef)
+    }
+}
+
+/// Which Fn-family trait a closure implements (each implies the next)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ClosureKind {
+    /// Callable any number of times through a shared reference
+    Fn,
+    /// Callable many times, mutating its environment
+    FnMut,
+    /// Callable once; consumes its environment
+    FnOnce,
+}
+
+impl ClosureKind {
+    /// Name of the trait this kind corresponds to
+    pub fn trait_name(self) -> &'static str {
+        match self {
+            ClosureKind::Fn => "Fn",
+            ClosureKind::FnMut => "FnMut",
+            ClosureKind::FnOnce => "FnOnce",
+        }
+    }
+
+    /// Whether a closure of this kind implements the trait for `other`
+    ///
+    /// An `Fn` closure is also `FnMut` and `FnOnce`, but not the reverse.
+    pub fn implements(self, other: ClosureKind) -> bool {
+        self <= other
+    }
+}
+
+/// A variable captured by a closure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capture {
+    pub name: String,
+    pub mode: CaptureMode,
+    /// First use inside the closure body
+    pub span: Span,
+}
+
+/// Result of capture analysis for one closure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClosureCaptures {
+    /// Captures in order of first use
+    pub captures: Vec<Capture>,
+    pub kind: ClosureKind,
+}
+
+/// Work out what `|params| body` captures
+///
+/// `outer` gives the ownership kind of each variable of the enclosing
+/// function, or `None` for names that are not local variables (functions,
+/// constants), which are never captured.
+pub fn analyze_captures<F>(params: &[Parameter], body: &Expr, outer: F) -> ClosureCaptures
+where
+    F: Fn(&str) -> Option<OwnershipKind>,
+{
+    let mut collector = CaptureCollector {
+        bound: params.iter().map(|p| p.name.name.clone()).collect(),
+        used: Vec::new(),
+        assigned: HashSet::new(),
+    };
+    let _ = collector.visit_expr(body);
+
+    let mut kind = ClosureKind::Fn;
+    let mut captures = Vec::new();
+    for (name, span) in collector.used {
+        let Some(ownership) = outer(&name) else {
+            continue;
+        };
+        let mutated = collector.assigned.contains(&name);
+        let mode = capture_mode(ownership, mutated);
+        // A linear value may be used once, so the closure may run once
+        if ownership == OwnershipKind::Linear {
+            kind = kind.max(ClosureKind::FnOnce);
+        } else if mutated {
+            kind = kind.max(ClosureKind::FnMut);
+        }
+        captures.push(Capture { name, mode, span });
+    }
+    ClosureCaptures { captures, kind }
+}
+
+/// Capture mode for a variable of the given ownership kind
+pub fn capture_mode(ownership: OwnershipKind, mutated: bool) -> CaptureMode {
+    match ownership {
+        OwnershipKind::Linear | OwnershipKind::Borrowed { mutable: true } => CaptureMode::Move,
+        _ if mutated => CaptureMode::MutRef,
+        OwnershipKind::Copy | OwnershipKind::Global | OwnershipKind::Borrowed { .. } => {
+            CaptureMode::Copy
+        }
+        OwnershipKind::Affine => CaptureMode::Ref,
+    }
+}
+
+/// Name of the closure defined at `span` inside function `enclosing`
+///
+/// Used both as the closure's type name and as the symbol of its code.
+pub fn closure_name(enclosing: &str, span: Span) -> String {
+    format!("{}__pāśa{}_{}", enclosing, span.line, span.column)
+}
+
+/// Collects free variables of a closure body, and which are assigned
+struct CaptureCollector {
+    /// Names bound inside the closure (parameters, lets, patterns)
+    bound: HashSet<String>,
+    /// Free names in order of first use
+    used: Vec<(String, Span)>,
+    assigned: HashSet<String>,
+}
+
+impl CaptureCollector {
+    fn use_name(&mut self, name: &str, span: Span) {
+        if !self.bound.contains(name) && !self.used.iter().any(|(n, _)| n == name) {
+            self.used.push((name.to_string(), span));
+        }
+    }
+
+    /// Bind the names a pattern introduces, visiting guard conditions
+    fn bind_pattern(&mut self, pattern: &Pattern) -> VisitResult<()> {
+        match pattern {
+            Pattern::Identifier(ident) => {
+                self.bound.insert(ident.name.clone());
+            }
+            Pattern::Binding {
+                name, subpattern, ..
+            } => {
+                self.bound.insert(name.name.clone());
+                if let Some(sub) = subpattern {
+                    self.bind_pattern(sub)?;
+                }
+            }
+            Pattern::Tuple(patterns)
+            | Pattern::Array(patterns)
+            | Pattern::Or(patterns)
+            | Pattern::Constructor {
+                fields: patterns, ..
+            } => {
+                for p in patterns {
+                    self.bind_pattern(p)?;
+                }
+            }
+            Pattern::Struct { fields, .. } => {
+                for (_, p) in fields {
+                    self.bind_pattern(p)?;
+                }
+            }
+            Pattern::Variant { fields, .. } => match fields {
+                VariantFields::Unit => {}
+                VariantFields::Tuple(patterns) => {
+                    for p in patterns {
+                        self.bind_pattern(p)?;
+                    }
+                }
+                VariantFields::Struct(fields) => {
+                    for (_, p) in fields {
+                        self.bind_pattern(p)?;
+                    }
+                }
+            },
+            Pattern::Slice {
+                before,
+                middle,
+                after,
+            } => {
+                for p in before.iter().chain(middle.as_deref()).chain(after) {
+                    self.bind_pattern(p)?;
+                }
+            }
+            Pattern::Guard { pattern, condition } => {
+                self.bind_pattern(pattern)?;
+                self.visit_expr(condition)?;
+            }
+            Pattern::Ref { pattern, .. } => self.bind_pattern(pattern)?,
+            Pattern::Wildcard | Pattern::Literal(_) | Pattern::Range { .. } | Pattern::Rest => {}
+        }
+        self.continue_()
+    }
+}
+
+impl AstVisitor for CaptureCollector {
+    type Break = ();
+
+    fn visit_stmt(&mut self, stmt: &Stmt) -> VisitResult<Self::Break> {
+        match stmt {
+            Stmt::Let { name, value, .. } => {
+                if let Some(value) = value {
+                    self.visit_expr(value)?;
+                }
+                self.bound.insert(name.name.clone());
+                self.continue_()
+            }
+            Stmt::Loop { kind, body, .. } => {
+                match kind {
+                    LoopKind::ForIn { binding, iterable } => {
+                        self.visit_expr(iterable)?;
+                        self.bound.insert(binding.name.clone());
+                    }
+                    LoopKind::Range {
+                        binding,
+                        start,
+                        end,
+                        ..
+                    } => {
+                        self.visit_expr(start)?;
+                        self.visit_expr(end)?;
+                        self.bound.insert(binding.name.clone());
+                    }
+                    LoopKind::While { condition } => {
+                        self.visit_expr(condition)?;
+                    }
+                    LoopKind::Infinite => {}
+                }
+                self.visit_block(body)
+            }
+            Stmt::Expr(expr) => self.visit_expr(expr),
+            Stmt::Return { value, .. } => {
+                if let Some(value) = value {
+                    self.visit_expr(value)?;
+                }
+                self.continue_()
+            }
+            Stmt::If {
+                condition,
+                then_block,
+                else_block,
+                ..
+            } => {
+                self.visit_expr(condition)?;
+                self.visit_block(then_block)?;
+                if let Some(else_block) = else_block {
+                    self.visit_block(else_block)?;
+                }
+                self.continue_()
+            }
+            Stmt::Break { .. } | Stmt::Continue { .. } => self.continue_(),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) -> VisitResult<Self::Break> {
+        match expr {
+            Expr::Identifier(ident) => {
+                self.use_name(&ident.name, ident.span);
+                self.continue_()
+            }
+            Expr::Binary {
+                left,
+                op:
+                    BinaryOp::Assign
+                    | BinaryOp::AddAssign
+                    | BinaryOp::SubAssign
+                    | BinaryOp::MulAssign
+                    | BinaryOp::DivAssign,
+                right,
+                ..
+            } => {
+                if let Expr::Identifier(target) = left.as_ref() {
+                    if !self.bound.contains(&target.name) {
+                        self.assigned.insert(target.name.clone());
+                    }
+                }
+                self.visit_expr(left)?;
+                self.visit_expr(right)
+            }
+            // A nested closure's parameters shadow only inside it
+            Expr::Lambda { params, body, .. } => {
+                let saved = self.bound.clone();
+                self.bound
+                    .extend(params.iter().map(|p| p.name.name.clone()));
+                self.visit_expr(body)?;
+                self.bound = saved;
+                self.continue_()
+            }
+            Expr::Match {
+                scrutinee, arms, ..
+            } => {
+                self.visit_expr(scrutinee)?;
+                for arm in arms {
+                    self.bind_pattern(&arm.pattern)?;
+                    if let Some(guard) = &arm.guard {
+                        self.visit_expr(guard)?;
+                    }
+                    self.visit_expr(&arm.body)?;
+                }
+                self.continue_()
+            }
+            _ => walk_expr(self, expr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast::{FunctionDef, Item};
+    use crate::parser::Parser;
+
+    /// Captures of the first closure in `mukhya`, with `outer` as the enclosing locals
+    fn captures_of(body: &str, outer: &[(&str, OwnershipKind)]) -> ClosureCaptures {
+        let source = format!("kāryakrama mukhya() {{\n{}\n}}", body);
+        let ast = Parser::parse_str(&source).expect("parse");
+        let Item::Function(FunctionDef { body, .. }) = &ast.items[0] else {
+            panic!("expected function");
+        };
+        let lambda = body
+            .stmts
+            .iter()
+            .find_map(|stmt| match stmt {
+                Stmt::Let {
+                    value: Some(value @ Expr::Lambda { .. }),
+                    ..
+                } => Some(value),
+                _ => None,
+            })
+            .expect("closure");
+        let Expr::Lambda { params, body, .. } = lambda else {
+            unreachable!();
+        };
+        analyze_captures(params, body, |name| {
+            outer.iter().find(|(n, _)| *n == name).map(|(_, k)| *k)
+        })
+    }
+
+    #[test]
+    fn test_modes_follow_ownership() {
+        let result = captures_of(
+            "let f = |x: saṅkhyā-a-k-t32| x + n + nāma.dīrghatā() + dhana;",
+            &[
+                ("n", OwnershipKind::Copy),
+                ("nāma", OwnershipKind::Affine),
+                ("dhana", OwnershipKind::Linear),
+            ],
+        );
+        let modes: Vec<_> = result
+            .captures
+            .iter()
+            .map(|c| (c.name.as_str(), c.mode))
+            .collect();
+        assert_eq!(
+            modes,
+            vec![
+                ("n", CaptureMode::Copy),
+                ("nāma", CaptureMode::Ref),
+                ("dhana", CaptureMode::Move),
+            ]
+        );
+        assert_eq!(result.kind, ClosureKind::FnOnce);
+    }
+
+    #[test]
+    fn test_assignment_makes_fn_mut() {
+        let result = captures_of(
+            "let f = || gaṇanā = gaṇanā + 1;",
+            &[("gaṇanā", OwnershipKind::Copy)],
+        );
+        assert_eq!(result.captures[0].mode, CaptureMode::MutRef);
+        assert_eq!(result.kind, ClosureKind::FnMut);
+        assert!(!result.kind.implements(ClosureKind::Fn));
+        assert!(result.kind.implements(ClosureKind::FnOnce));
+    }
+
+    #[test]
+    fn test_bound_names_are_not_captured() {
+        let result = captures_of(
+            "let f = |x| { let y = x; phera y + z; };",
+            &[("x", OwnershipKind::Copy), ("y", OwnershipKind::Copy)],
+        );
+        assert!(result.captures.is_empty());
+        assert_eq!(result.kind, ClosureKind::Fn);
+    }
+}
@@ -0,0 +1,1181 @@
+//! Contracts - Pratijñā (प्रतिज्ञा)
+//!
+//! A function promises its `yatra paścāt` postconditions to its callers,
+//! provided they keep its `yatra pūrva` preconditions. Debug and Sattva
+//! builds check both at run time; this module tries to prove them at
+//! compile time instead.
+//!
+//! Each call of a function with preconditions, and each value a function
+//! with postconditions returns, is an obligation. It is proved when its
+//! condition follows from what is known at that point:
+//! - the preconditions of the function it is in,
+//! - the conditions of the `yad` branches it is inside, and the negation of
+//!   an earlier `yad` whose branch always leaves,
+//! - `let` bindings, and the postconditions of the call a binding takes.
+//!
+//! Only linear integer arithmetic is understood: sums, differences and
+//! products with a constant, compared and joined with `&&`, `||` and `!`.
+//! Integers are taken to be mathematical, as they are only in Sattva
+//! builds, where overflow traps, so only those are proved. A fact about a variable that is assigned or bound
+//! twice is not used, as it may no longer hold where it would be.
+//!
+//! Obligations are refuted by Fourier-Motzkin elimination: an obligation
+//! holds when the facts together with its negation have no solution. A
+//! contract proved everywhere it applies is no longer checked at run time.
+
+use crate::lexer::Span;
+use crate::mir::builder::impl_owner_name;
+use crate::modules::Visibility;
+use crate::parser::ast::*;
+use crate::semantics::security::pattern_bindings;
+use crate::visitor::{walk_expr, walk_stmt, AstVisitor, VisitResult};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::ops::ControlFlow;
+
+/// Disjuncts a negated obligation may expand to before it is given up
+const MAX_CASES: usize = 64;
+/// Constraints elimination may produce before it is given up
+const MAX_CONSTRAINTS: usize = 512;
+
+/// What an obligation stems from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObligationKind {
+    /// A precondition of the function called
+    Precondition,
+    /// A postcondition of the function returning
+    Postcondition,
+}
+
+/// A contract as it applies to one call or one returned value
+#[derive(Debug, Clone)]
+pub struct Obligation {
+    pub kind: ObligationKind,
+    /// Function whose contract it is, as `f` or `T::f`
+    pub function: String,
+    /// Position of the contract among the function's preconditions or
+    /// postconditions
+    pub index: usize,
+    /// The condition as written
+    pub condition: String,
+    /// The call or the value returned
+    pub span: Span,
+    pub proved: bool,
+}
+
+impl fmt::Display for Obligation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, place) = match self.kind {
+            ObligationKind::Precondition => ("precondition", "at this call"),
+            ObligationKind::Postcondition => ("postcondition", "for this result"),
+        };
+        let verdict = if self.proved {
+            "holds"
+        } else {
+            "cannot be proved"
+        };
+        write!(
+            f,
+            "{} `{}` of `{}` {} {}",
+            kind, self.condition, self.function, verdict, place
+        )
+    }
+}
+
+/// Every contract obligation of `ast`, each tried at compile time
+///
+/// Proofs take integers to be mathematical, so they hold only where
+/// overflow traps.
+pub fn prove_contracts(ast: &Ast) -> Vec<Obligation> {
+    let prover = Prover {
+        callees: Callees::of(ast),
+        constants: ast
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Constant(ConstantDef {
+                    name,
+                    value: Expr::Literal(Literal::Int(value)),
+                    ..
+                }) => Some((name.name.as_str(), *value as i128)),
+                _ => None,
+            })
+            .collect(),
+    };
+    let mut obligations = Vec::new();
+    for (name, owner, func) in functions(ast) {
+        prover.function(name, owner, func, &mut obligations);
+    }
+    obligations
+}
+
+/// Drop the contracts of `ast` that `obligations` prove wherever they
+/// apply, so they are not checked again at run time
+///
+/// A postcondition goes once every value its function returns is proved
+/// to keep it. A precondition goes once every call is proved to keep it,
+/// provided some call is seen and none can be hidden: the function is not
+/// public, nor named other than to be called.
+pub fn drop_proved(ast: &mut Ast, obligations: &[Obligation]) {
+    let mut proved: HashMap<(&str, ObligationKind, usize), bool> = HashMap::new();
+    for obligation in obligations {
+        *proved
+            .entry((
+                obligation.function.as_str(),
+                obligation.kind,
+                obligation.index,
+            ))
+            .or_insert(true) &= obligation.proved;
+    }
+    let escaping = Escapes::of(ast);
+    let dropped = |name: &str, kind, index| proved.get(&(name, kind, index)) == Some(&true);
+    for item in &mut ast.items {
+        let (owner, functions) = match item {
+            Item::Function(func) => (None, std::slice::from_mut(func)),
+            Item::Impl(impl_def) => (
+                Some(impl_owner_name(&impl_def.self_ty)),
+                &mut impl_def.methods[..],
+            ),
+            _ => continue,
+        };
+        for func in functions {
+            let name = function_name(owner.as_deref(), func);
+            let hidden = matches!(func.visibility, Visibility::Public) || escaping.contains(&name);
+            let mut index = 0..;
+            func.preconditions.retain(|_| {
+                let index = index.next().unwrap_or_default();
+                hidden || !dropped(&name, ObligationKind::Precondition, index)
+            });
+            let mut index = 0..;
+            func.postconditions.retain(|_| {
+                let index = index.next().unwrap_or_default();
+                !dropped(&name, ObligationKind::Postcondition, index)
+            });
+        }
+    }
+}
+
+/// Every function of `ast` with a body, with its name and the type it is
+/// a method of
+fn functions(ast: &Ast) -> Vec<(String, Option<String>, &FunctionDef)> {
+    let mut functions = Vec::new();
+    for item in &ast.items {
+        match item {
+            Item::Function(func) => functions.push((func.name.name.clone(), None, func)),
+            Item::Impl(impl_def) => {
+                let owner = impl_owner_name(&impl_def.self_ty);
+                for method in &impl_def.methods {
+                    functions.push((
+                        function_name(Some(&owner), method),
+                        Some(owner.clone()),
+                        method,
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+    functions
+}
+
+/// `f`, or `T::f` for a method of `T`
+fn function_name(owner: Option<&str>, func: &FunctionDef) -> String {
+    match owner {
+        Some(owner) => format!("{}::{}", owner, func.name.name),
+        None => func.name.name.clone(),
+    }
+}
+
+/// The functions calls can name, resolved as the MIR builder resolves
+/// them: a method call without types only where one impl has the method
+struct Callees<'a> {
+    functions: HashMap<String, &'a FunctionDef>,
+    /// Functions named by each method name
+    methods: HashMap<&'a str, Vec<String>>,
+}
+
+impl<'a> Callees<'a> {
+    fn of(ast: &'a Ast) -> Self {
+        let mut callees = Callees {
+            functions: HashMap::new(),
+            methods: HashMap::new(),
+        };
+        for (name, owner, func) in functions(ast) {
+            if owner.is_some() {
+                callees
+                    .methods
+                    .entry(func.name.name.as_str())
+                    .or_default()
+                    .push(name.clone());
+            }
+            callees.functions.insert(name, func);
+        }
+        callees
+    }
+
+    /// The function a path names, `Self` being `owner`
+    fn path(&self, path: &str, owner: Option<&str>) -> Option<(String, &'a FunctionDef)> {
+        let name = match (path.strip_prefix("Self::"), owner) {
+            (Some(method), Some(owner)) => format!("{}::{}", owner, method),
+            _ => path.to_string(),
+        };
+        let func = self.functions.get(&name).copied()?;
+        Some((name, func))
+    }
+
+    /// The function a call of `callee` runs, if it is known
+    fn call(&self, callee: &Expr, owner: Option<&str>) -> Option<(String, &'a FunctionDef)> {
+        match callee {
+            Expr::Identifier(ident) => self.path(&ident.name, owner),
+            _ => None,
+        }
+    }
+
+    /// The method `method` names, if only one impl has it or
+    /// monomorphization named its owner (`Peti__i32::mūlya`)
+    fn method(&self, method: &str) -> Option<(String, &'a FunctionDef)> {
+        if method.contains("::") {
+            return self.path(method, None);
+        }
+        match self.methods.get(method).map(Vec::as_slice) {
+            Some([name]) => Some((name.clone(), self.functions[name])),
+            _ => None,
+        }
+    }
+}
+
+/// Functions that may be called where no obligation is seen: named as a
+/// value, or called through a method name more than one impl has
+struct Escapes<'a> {
+    callees: Callees<'a>,
+    owner: Option<String>,
+    names: HashSet<String>,
+}
+
+impl Escapes<'_> {
+    fn of(ast: &Ast) -> HashSet<String> {
+        let mut escapes = Escapes {
+            callees: Callees::of(ast),
+            owner: None,
+            names: HashSet::new(),
+        };
+        for (_, owner, func) in functions(ast) {
+            escapes.owner = owner;
+            let _ = escapes.visit_block(&func.body);
+        }
+        for item in &ast.items {
+            if let Item::Constant(constant) = item {
+                escapes.owner = None;
+                let _ = escapes.visit_expr(&constant.value);
+            }
+        }
+        escapes.names
+    }
+}
+
+impl AstVisitor for Escapes<'_> {
+    type Break = ();
+
+    fn visit_expr(&mut self, expr: &Expr) -> VisitResult<()> {
+        match expr {
+            Expr::Call { callee, args, .. }
+                if self.callees.call(callee, self.owner.as_deref()).is_some() =>
+            {
+                for arg in args {
+                    self.visit_expr(arg)?;
+                }
+                return ControlFlow::Continue(());
+            }
+            Expr::Identifier(ident) => {
+                if let Some((name, _)) = self.callees.path(&ident.name, self.owner.as_deref()) {
+                    self.names.insert(name);
+                }
+            }
+            Expr::MethodCall { method, .. } => {
+                if let Some(names) = self.callees.methods.get(method.name.as_str()) {
+                    if names.len() > 1 {
+                        self.names.extend(names.iter().cloned());
+                    }
+                }
+            }
+            _ => {}
+        }
+        walk_expr(self, expr)
+    }
+}
+
+/// `Σ coefficient·atom + constant`, atoms being variables or values
+/// nothing more is known of
+#[derive(Debug, Clone, PartialEq)]
+struct Linear {
+    terms: BTreeMap<String, i128>,
+    constant: i128,
+}
+
+impl Linear {
+    fn constant(value: i128) -> Self {
+        Self {
+            terms: BTreeMap::new(),
+            constant: value,
+        }
+    }
+
+    fn atom(name: String) -> Self {
+        Self {
+            terms: BTreeMap::from([(name, 1)]),
+            constant: 0,
+        }
+    }
+
+    fn plus(mut self, other: &Linear, factor: i128) -> Option<Self> {
+        for (atom, coefficient) in &other.terms {
+            let term = self.terms.entry(atom.clone()).or_insert(0);
+            *term = term.checked_add(coefficient.checked_mul(factor)?)?;
+            if *term == 0 {
+                self.terms.remove(atom);
+            }
+        }
+        self.constant = self
+            .constant
+            .checked_add(other.constant.checked_mul(factor)?)?;
+        Some(self)
+    }
+
+    fn scaled(&self, factor: i128) -> Option<Self> {
+        Linear::constant(0).plus(self, factor)
+    }
+
+    /// `self ≤ 0`, with the coefficients divided by their common factor
+    /// and the constant rounded as integers allow
+    fn at_most_zero(mut self) -> Constraint {
+        let divisor = self.terms.values().fold(0, |g, &c| gcd(g, c.abs()));
+        if divisor > 1 {
+            for coefficient in self.terms.values_mut() {
+                *coefficient /= divisor;
+            }
+            // Σ a·x ≤ -c/g, so Σ a·x ≤ ⌊-c/g⌋
+            self.constant = -(-self.constant).div_euclid(divisor);
+        }
+        self
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// `linear ≤ 0`
+type Constraint = Linear;
+
+/// A condition over linear constraints
+#[derive(Debug, Clone)]
+enum Formula {
+    Holds(Constraint),
+    And(Vec<Formula>),
+    Or(Vec<Formula>),
+    Bool(bool),
+}
+
+impl Formula {
+    fn negated(self) -> Option<Formula> {
+        Some(match self {
+            // ¬(l ≤ 0) is l ≥ 1, so 1 - l ≤ 0
+            Formula::Holds(l) => Formula::Holds(Linear::constant(1).plus(&l, -1)?.at_most_zero()),
+            Formula::And(parts) => Formula::Or(
+                parts
+                    .into_iter()
+                    .map(Formula::negated)
+                    .collect::<Option<_>>()?,
+            ),
+            Formula::Or(parts) => Formula::And(
+                parts
+                    .into_iter()
+                    .map(Formula::negated)
+                    .collect::<Option<_>>()?,
+            ),
+            Formula::Bool(value) => Formula::Bool(!value),
+        })
+    }
+
+    /// The conjunctions of constraints, one of which holds when this does;
+    /// `None` past `MAX_CASES`
+    fn cases(&self) -> Option<Vec<Vec<Constraint>>> {
+        Some(match self {
+            Formula::Holds(l) => vec![vec![l.clone()]],
+            Formula::Bool(true) => vec![vec![]],
+            Formula::Bool(false) => vec![],
+            Formula::Or(parts) => {
+                let mut cases = Vec::new();
+                for part in parts {
+                    cases.extend(part.cases()?);
+                    if cases.len() > MAX_CASES {
+                        return None;
+                    }
+                }
+                cases
+            }
+            Formula::And(parts) => {
+                let mut cases = vec![vec![]];
+                for part in parts {
+                    let alternatives = part.cases()?;
+                    if cases.len() * alternatives.len() > MAX_CASES {
+                        return None;
+                    }
+                    cases = cases
+                        .iter()
+                        .flat_map(|case| {
+                            alternatives.iter().map(move |alternative| {
+                                case.iter().chain(alternative).cloned().collect()
+                            })
+                        })
+                        .collect();
+                }
+                cases
+            }
+        })
+    }
+}
+
+/// Whether `constraints` have no solution, as far as elimination can tell
+fn infeasible(mut constraints: Vec<Constraint>) -> bool {
+    loop {
+        if constraints
+            .iter()
+            .any(|c| c.terms.is_empty() && c.constant > 0)
+        {
+            return true;
+        }
+        constraints.retain(|c| !c.terms.is_empty());
+        let Some(atom) = constraints
+            .iter()
+            .flat_map(|c| c.terms.keys())
+            .next()
+            .cloned()
+        else {
+            return false;
+        };
+        // Pair each upper bound on `atom` with each lower bound
+        let (with, mut without): (Vec<_>, Vec<_>) = constraints
+            .into_iter()
+            .partition(|c| c.terms.contains_key(&atom));
+        let (upper, lower): (Vec<_>, Vec<_>) = with.into_iter().partition(|c| c.terms[&atom] > 0);
+        for u in &upper {
+            for l in &lower {
+                let combined = u
+                    .scaled(-l.terms[&atom])
+                    .and_then(|c| c.plus(l, u.terms[&atom]));
+                match combined {
+                    Some(c) => without.push(c.at_most_zero()),
+                    None => return false,
+                }
+            }
+        }
+        if without.len() > MAX_CONSTRAINTS {
+            return false;
+        }
+        constraints = without;
+    }
+}
+
+/// Whether `goal` follows from `facts`
+fn follows(facts: &[Formula], goal: Formula) -> bool {
+    let Some(refutation) = goal.negated() else {
+        return false;
+    };
+    let mut all = facts.to_vec();
+    all.push(refutation);
+    match Formula::And(all).cases() {
+        Some(cases) => cases.into_iter().all(infeasible),
+        None => false,
+    }
+}
+
+/// What a name stands for where a contract is read
+type Scope<'s> = dyn Fn(&str) -> Option<Linear> + 's;
+
+/// `expr` as a linear form over the names `scope` gives
+fn linear(expr: &Expr, scope: &Scope) -> Option<Linear> {
+    match expr {
+        Expr::Literal(Literal::Int(value)) => Some(Linear::constant(*value as i128)),
+        Expr::Identifier(ident) => scope(&ident.name),
+        Expr::Unary {
+            op: UnaryOp::Neg,
+            operand,
+            ..
+        } => linear(operand, scope)?.scaled(-1),
+        Expr::Binary {
+            left, op, right, ..
+        } => {
+            let left = linear(left, scope)?;
+            let right = linear(right, scope)?;
+            match op {
+                BinaryOp::Add => left.plus(&right, 1),
+                BinaryOp::Sub => left.plus(&right, -1),
+                BinaryOp::Mul if left.terms.is_empty() => right.scaled(left.constant),
+                BinaryOp::Mul if right.terms.is_empty() => left.scaled(right.constant),
+                _ => None,
+            }
+        }
+        Expr::Cast { expr, .. } => linear(expr, scope),
+        _ => None,
+    }
+}
+
+/// `expr` as a condition over the names `scope` gives
+fn formula(expr: &Expr, scope: &Scope) -> Option<Formula> {
+    match expr {
+        Expr::Literal(Literal::Bool(value)) => Some(Formula::Bool(*value)),
+        Expr::Unary {
+            op: UnaryOp::Not,
+            operand,
+            ..
+        } => formula(operand, scope)?.negated(),
+        Expr::Binary {
+            left,
+            op: op @ (BinaryOp::And | BinaryOp::Or),
+            right,
+            ..
+        } => {
+            let parts = vec![formula(left, scope)?, formula(right, scope)?];
+            Some(match op {
+                BinaryOp::And => Formula::And(parts),
+                _ => Formula::Or(parts),
+            })
+        }
+        Expr::Binary {
+            left, op, right, ..
+        } => {
+            let left = linear(left, scope)?;
+            let right = linear(right, scope)?;
+            let less = |a: &Linear, b: &Linear, strict: bool| {
+                let difference = a.clone().plus(b, -1)?;
+                let bound = if strict {
+                    difference.plus(&Linear::constant(1), 1)?
+                } else {
+                    difference
+                };
+                Some(Formula::Holds(bound.at_most_zero()))
+            };
+            match op {
+                BinaryOp::Lt => less(&left, &right, true),
+                BinaryOp::Le => less(&left, &right, false),
+                BinaryOp::Gt => less(&right, &left, true),
+                BinaryOp::Ge => less(&right, &left, false),
+                BinaryOp::Eq => Some(Formula::And(vec![
+                    less(&left, &right, false)?,
+                    less(&right, &left, false)?,
+                ])),
+                BinaryOp::Ne => Some(Formula::Or(vec![
+                    less(&left, &right, true)?,
+                    less(&right, &left, true)?,
+                ])),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Names bound more than once, assigned or borrowed mutably in a function:
+/// what is known of them at one point may not hold at another
+#[derive(Default)]
+struct Unstable {
+    bound: HashSet<String>,
+    names: HashSet<String>,
+}
+
+impl Unstable {
+    fn bind(&mut self, name: &str) {
+        if !self.bound.insert(name.to_string()) {
+            self.names.insert(name.to_string());
+        }
+    }
+
+    fn of(func: &FunctionDef) -> HashSet<String> {
+        let mut unstable = Unstable::default();
+        for param in &func.params {
+            unstable.bind(&param.name.name);
+        }
+        let _ = unstable.visit_block(&func.body);
+        unstable.names
+    }
+}
+
+impl AstVisitor for Unstable {
+    type Break = ();
+
+    fn visit_stmt(&mut self, stmt: &Stmt) -> VisitResult<()> {
+        match stmt {
+            Stmt::Let { name, .. } => self.bind(&name.name),
+            Stmt::Loop {
+                kind: LoopKind::ForIn { binding, .. } | LoopKind::Range { binding, .. },
+                ..
+            } => {
+                self.names.insert(binding.name.clone());
+            }
+            _ => {}
+        }
+        walk_stmt(self, stmt)
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) -> VisitResult<()> {
+        match expr {
+            Expr::Binary {
+                left,
+                op:
+                    BinaryOp::Assign
+                    | BinaryOp::AddAssign
+                    | BinaryOp::SubAssign
+                    | BinaryOp::MulAssign
+                    | BinaryOp::DivAssign,
+                ..
+            }
+            | Expr::Unary {
+                op: UnaryOp::RefMut,
+                operand: left,
+                ..
+            }
+            | Expr::MethodCall { receiver: left, .. } => {
+                if let Expr::Identifier(ident) = &**left {
+                    self.names.insert(ident.name.clone());
+                }
+            }
+            Expr::Loop { kind, .. } => {
+                if let LoopKind::ForIn { binding, .. } | LoopKind::Range { binding, .. } = &**kind {
+                    self.names.insert(binding.name.clone());
+                }
+            }
+            Expr::Match { arms, .. } => {
+                let mut names = Vec::new();
+                for arm in arms {
+                    pattern_bindings(&arm.pattern, &mut names);
+                }
+                self.names.extend(names.into_iter().map(|n| n.name.clone()));
+            }
+            Expr::Lambda { params, .. } => {
+                self.names
+                    .extend(params.iter().map(|p| p.name.name.clone()));
+            }
+            _ => {}
+        }
+        walk_expr(self, expr)
+    }
+}
+
+/// Obligations of the program and what proves them
+struct Prover<'a> {
+    callees: Callees<'a>,
+    /// Integer constants, by name
+    constants: HashMap<&'a str, i128>,
+}
+
+/// The function being examined
+struct Site<'f> {
+    /// Its name, as `f` or `T::f`
+    name: String,
+    /// The type it is a method of
+    owner: Option<String>,
+    func: &'f FunctionDef,
+    unstable: HashSet<String>,
+    /// Facts from its preconditions, over the values it was called with
+    entry: Vec<Formula>,
+}
+
+impl Prover<'_> {
+    fn function(
+        &self,
+        name: String,
+        owner: Option<String>,
+        func: &FunctionDef,
+        obligations: &mut Vec<Obligation>,
+    ) {
+        let mut site = Site {
+            name,
+            owner,
+            func,
+            unstable: Unstable::of(func),
+            entry: Vec::new(),
+        };
+        site.entry = func
+            .preconditions
+            .iter()
+            .filter_map(|c| formula(&c.condition, &|name| self.entry_value(&site, name)))
+            .collect();
+        let mut facts = site.entry.clone();
+        self.block(&site, &func.body, &mut facts, obligations);
+        if func.return_type.is_some() {
+            if let Some(Stmt::Expr(tail)) = func.body.tail() {
+                self.result(&site, tail, &facts, obligations);
+            }
+        }
+    }
+
+    /// A name's value on entry: a parameter assigned later is told apart
+    /// from its value now
+    fn entry_value(&self, site: &Site, name: &str) -> Option<Linear> {
+        if site.func.params.iter().any(|p| p.name.name == name) {
+            return Some(Linear::atom(if site.unstable.contains(name) {
+                format!("{}@entry", name)
+            } else {
+                name.to_string()
+            }));
+        }
+        self.constants
+            .get(name)
+            .map(|&value| Linear::constant(value))
+    }
+
+    /// A name's value where it is used: one nothing is known of when it is
+    /// unstable
+    fn current_value(&self, site: &Site, name: &str) -> Option<Linear> {
+        if let Some(&value) = self.constants.get(name) {
+            if !site.unstable.contains(name) {
+                return Some(Linear::constant(value));
+            }
+        }
+        Some(Linear::atom(if site.unstable.contains(name) {
+            format!("{}@now", name)
+        } else {
+            name.to_string()
+        }))
+    }
+
+    /// A name's value in a fact, which must hold wherever the fact is used
+    fn stable_value(&self, site: &Site, name: &str) -> Option<Linear> {
+        if site.unstable.contains(name) {
+            return None;
+        }
+        self.current_value(site, name)
+    }
+
+    fn block(
+        &self,
+        site: &Site,
+        block: &Block,
+        facts: &mut Vec<Formula>,
+        obligations: &mut Vec<Obligation>,
+    ) {
+        for stmt in &block.stmts {
+            self.stmt(site, stmt, facts, obligations);
+        }
+    }
+
+    fn stmt(
+        &self,
+        site: &Site,
+        stmt: &Stmt,
+        facts: &mut Vec<Formula>,
+        obligations: &mut Vec<Obligation>,
+    ) {
+        match stmt {
+            Stmt::Let {
+                name,
+                value: Some(value),
+                ..
+            } => {
+                self.expr(site, value, facts, obligations);
+                if site.unstable.contains(&name.name) {
+                    return;
+                }
+                let bound = Linear::atom(name.name.clone());
+                if let Some(value) = linear(value, &|n| self.stable_value(site, n)) {
+                    if let Some(difference) = bound.clone().plus(&value, -1) {
+                        facts.push(Formula::Holds(difference.clone().at_most_zero()));
+                        if let Some(negative) = difference.scaled(-1) {
+                            facts.push(Formula::Holds(negative.at_most_zero()));
+                        }
+                    }
+                }
+                facts.extend(self.promised(site, value, bound));
+            }
+            Stmt::Return {
+                value: Some(value), ..
+            } => {
+                self.expr(site, value, facts, obligations);
+                self.result(site, value, facts, obligations);
+            }
+            Stmt::If {
+                condition,
+                then_block,
+                else_block,
+                ..
+            } => {
+                self.expr(site, condition, facts, obligations);
+                let known = formula(condition, &|n| self.stable_value(site, n));
+                let mut then_facts = facts.clone();
+                then_facts.extend(known.clone());
+                self.block(site, then_block, &mut then_facts, obligations);
+                let otherwise = known.and_then(Formula::negated);
+                let mut else_facts = facts.clone();
+                else_facts.extend(otherwise.clone());
+                if let Some(else_block) = else_block {
+                    self.block(site, else_block, &mut else_facts, obligations);
+                } else if then_block.stmts.last().is_some_and(leaves) {
+                    // What follows runs only when the branch was not taken
+                    facts.extend(otherwise);
+                }
+            }
+            Stmt::Loop { kind, body, .. } => {
+                self.loop_kind(site, kind, facts, obligations);
+                self.block(site, body, &mut facts.clone(), obligations);
+            }
+            Stmt::Let { value: None, .. } | Stmt::Return { value: None, .. } => {}
+            Stmt::Break { value, .. } => {
+                if let Some(value) = value {
+                    self.expr(site, value, facts, obligations);
+                }
+            }
+            Stmt::Continue { .. } => {}
+            Stmt::Expr(expr) => self.expr(site, expr, facts, obligations),
+        }
+    }
+
+    fn loop_kind(
+        &self,
+        site: &Site,
+        kind: &LoopKind,
+        facts: &[Formula],
+        obligations: &mut Vec<Obligation>,
+    ) {
+        match kind {
+            LoopKind::ForIn { iterable, .. } => self.expr(site, iterable, facts, obligations),
+            LoopKind::While { condition } => self.expr(site, condition, facts, obligations),
+            LoopKind::Range { start, end, .. } => {
+                self.expr(site, start, facts, obligations);
+                self.expr(site, end, facts, obligations);
+            }
+            LoopKind::Infinite => {}
+        }
+    }
+
+    /// Obligations of the calls in `expr`
+    fn expr(&self, site: &Site, expr: &Expr, facts: &[Formula], obligations: &mut Vec<Obligation>) {
+        match expr {
+            Expr::Call { callee, args, span } => {
+                for arg in args {
+                    self.expr(site, arg, facts, obligations);
+                }
+                match self.callees.call(callee, site.owner.as_deref()) {
+                    Some(callee) => self.call(site, callee, args, *span, facts, obligations),
+                    None => self.expr(site, callee, facts, obligations),
+                }
+            }
+            Expr::If {
+                condition,
+                then_expr,
+                else_expr,
+                ..
+            } => {
+                self.expr(site, condition, facts, obligations);
+                let known = formula(condition, &|n| self.stable_value(site, n));
+                let mut then_facts = facts.to_vec();
+                then_facts.extend(known.clone());
+                self.expr(site, then_expr, &then_facts, obligations);
+                if let Some(else_expr) = else_expr {
+                    let mut else_facts = facts.to_vec();
+                    else_facts.extend(known.and_then(Formula::negated));
+                    self.expr(site, else_expr, &else_facts, obligations);
+                }
+            }
+            Expr::Block(block) => self.block(site, block, &mut facts.to_vec(), obligations),
+            Expr::Loop { kind, body, .. } => {
+                self.loop_kind(site, kind, facts, obligations);
+                self.block(site, body, &mut facts.to_vec(), obligations);
+            }
+            // A closure runs where nothing here is known
+            Expr::Lambda { body, .. } => self.expr(site, body, &[], obligations),
+            Expr::Match {
+                scrutinee, arms, ..
+            } => {
+                self.expr(site, scrutinee, facts, obligations);
+                for arm in arms {
+                    if let Some(guard) = &arm.guard {
+                        self.expr(site, guard, facts, obligations);
+                    }
+                    self.expr(site, &arm.body, facts, obligations);
+                }
+            }
+            Expr::Binary { left, right, .. }
+            | Expr::Index {
+                object: left,
+                index: right,
+                ..
+            } => {
+                self.expr(site, left, facts, obligations);
+                self.expr(site, right, facts, obligations);
+            }
+            Expr::MethodCall {
+                receiver,
+                method,
+                args,
+                span,
+            } => {
+                self.expr(site, receiver, facts, obligations);
+                for arg in args {
+                    self.expr(site, arg, facts, obligations);
+                }
+                if let Some(callee) = self.callees.method(&method.name) {
+                    self.call(site, callee, args, *span, facts, obligations);
+                }
+            }
+            Expr::Array { elements, .. } | Expr::Tuple { elements, .. } => {
+                for element in elements {
+                    self.expr(site, element, facts, obligations);
+                }
+            }
+            Expr::Format { args, .. } => {
+                for arg in args {
+                    self.expr(site, arg, facts, obligations);
+                }
+            }
+            Expr::StructConstruct { fields, .. } => {
+                for (_, value) in fields {
+                    self.expr(site, value, facts, obligations);
+                }
+            }
+            Expr::Range { start, end, .. } => {
+                for bound in start.iter().chain(end) {
+                    self.expr(site, bound, facts, obligations);
+                }
+            }
+            Expr::Unary { operand: inner, .. }
+            | Expr::FieldAccess { object: inner, .. }
+            | Expr::Try { expr: inner, .. }
+            | Expr::Await { expr: inner, .. }
+            | Expr::Saha { task: inner, .. }
+            | Expr::Cast { expr: inner, .. } => self.expr(site, inner, facts, obligations),
+            Expr::Literal(_) | Expr::Identifier(_) | Expr::Error(_) => {}
+        }
+    }
+
+    /// Obligations of the postconditions for `value`, returned from the
+    /// function being examined
+    fn result(
+        &self,
+        site: &Site,
+        value: &Expr,
+        facts: &[Formula],
+        obligations: &mut Vec<Obligation>,
+    ) {
+        match value {
+            // Each branch returns its own value
+            Expr::If {
+                condition,
+                then_expr,
+                else_expr: Some(else_expr),
+                ..
+            } => {
+                let known = formula(condition, &|n| self.stable_value(site, n));
+                let mut then_facts = facts.to_vec();
+                then_facts.extend(known.clone());
+                self.result(site, then_expr, &then_facts, obligations);
+                let mut else_facts = facts.to_vec();
+                else_facts.extend(known.and_then(Formula::negated));
+                self.result(site, else_expr, &else_facts, obligations);
+            }
+            Expr::Block(block) => {
+                let mut facts = facts.to_vec();
+                self.block(site, block, &mut facts, obligations);
+                if let Some(Stmt::Expr(tail)) = block.tail() {
+                    self.result(site, tail, &facts, obligations);
+                }
+            }
+            _ => {
+                let result = linear(value, &|n| self.current_value(site, n));
+                for (index, contract) in site.func.postconditions.iter().enumerate() {
+                    let goal = result.as_ref().and_then(|result| {
+                        formula(&contract.condition, &|name| match name {
+                            "phala" => Some(result.clone()),
+                            _ => self.entry_value(site, name),
+                        })
+                    });
+                    obligations.push(Obligation {
+                        kind: ObligationKind::Postcondition,
+                        function: site.name.clone(),
+                        index,
+                        condition: contract.text.clone(),
+                        span: match value.span() {
+                            span if span.line > 0 => span,
+                            _ => site.func.span,
+                        },
+                        proved: goal.is_some_and(|goal| follows(facts, goal)),
+                    });
+                }
+            }
+        }
+    }
+
+    /// What the postconditions of the call `value` promise of `bound`,
+    /// the variable taking its result
+    fn promised(&self, site: &Site, value: &Expr, bound: Linear) -> Vec<Formula> {
+        let callee = match value {
+            Expr::Call { callee, args, .. } => self
+                .callees
+                .call(callee, site.owner.as_deref())
+                .map(|(_, callee)| (callee, args)),
+            Expr::MethodCall { method, args, .. } => self
+                .callees
+                .method(&method.name)
+                .map(|(_, callee)| (callee, args)),
+            _ => None,
+        };
+        let Some((callee, args)) = callee else {
+            return Vec::new();
+        };
+        let params = self.arguments(callee, args, &|n| self.stable_value(site, n));
+        callee
+            .postconditions
+            .iter()
+            .filter_map(|contract| {
+                formula(&contract.condition, &|name| match name {
+                    "phala" => Some(bound.clone()),
+                    _ => params
+                        .get(name)
+                        .cloned()
+                        .flatten()
+                        .or_else(|| self.constant(name)),
+                })
+            })
+            .collect()
+    }
+
+    /// Obligations of the preconditions of `callee`, called with `args`
+    fn call(
+        &self,
+        site: &Site,
+        (name, callee): (String, &FunctionDef),
+        args: &[Expr],
+        span: Span,
+        facts: &[Formula],
+        obligations: &mut Vec<Obligation>,
+    ) {
+        let params = self.arguments(callee, args, &|n| self.current_value(site, n));
+        for (index, contract) in callee.preconditions.iter().enumerate() {
+            let goal = formula(&contract.condition, &|name| {
+                params
+                    .get(name)
+                    .cloned()
+                    .flatten()
+                    .or_else(|| self.constant(name))
+            });
+            obligations.push(Obligation {
+                kind: ObligationKind::Precondition,
+                function: name.clone(),
+                index,
+                condition: contract.text.clone(),
+                span,
+                proved: goal.is_some_and(|goal| follows(facts, goal)),
+            });
+        }
+    }
+
+    /// Each parameter of `callee` with the argument it is given
+    fn arguments(
+        &self,
+        callee: &FunctionDef,
+        args: &[Expr],
+        scope: &Scope,
+    ) -> HashMap<String, Option<Linear>> {
+        callee
+            .params
+            .iter()
+            .zip(args)
+            .map(|(param, arg)| (param.name.name.clone(), linear(arg, scope)))
+            .collect()
+    }
+
+    fn constant(&self, name: &str) -> Option<Linear> {
+        self.constants
+            .get(name)
+            .map(|&value| Linear::constant(value))
+    }
+}
+
+/// Whether `stmt` always leaves the block it ends
+fn leaves(stmt: &Stmt) -> bool {
+    matches!(
+        stmt,
+        Stmt::Return { .. } | Stmt::Break { .. } | Stmt::Continue { .. }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn condition(source: &str) -> Formula {
+        let program = format!(
+            "kāryakrama f(a: saṅkhyā, b: saṅkhyā) -> bool {{\n    {}\n}}\n",
+            source
+        );
+        let ast = crate::parser::Parser::parse_str(&program).expect("Failed to parse");
+        let Item::Function(func) = &ast.items[0] else {
+            panic!("Expected function declaration");
+        };
+        let Some(Stmt::Expr(expr)) = func.body.stmts.first() else {
+            panic!("Expected a condition");
+        };
+        formula(expr, &|name| Some(Linear::atom(name.to_string()))).expect("linear")
+    }
+
+    #[test]
+    fn test_follows() {
+        let facts = [condition("a > 0 && b >= a")];
+        assert!(follows(&facts, condition("b != 0")));
+        assert!(follows(&facts, condition("2 * b - a >= 1")));
+        assert!(!follows(&facts, condition("b > a")));
+
+        // Integers: nothing lies strictly between 0 and 1
+        let facts = [condition("2 * a > 0")];
+        assert!(follows(&facts, condition("a >= 1")));
+
+        // Cases of a disjunction are refuted one by one
+        let facts = [condition("a == 1 || a == 3")];
+        assert!(follows(&facts, condition("a != 2")));
+        assert!(!follows(&facts, condition("a < 3")));
+    }
+
+    #[test]
+    fn test_nonlinear_unproved() {
+        let ast = crate::parser::Parser::parse_str(
+            "kāryakrama f(a: saṅkhyā) -> saṅkhyā\n    yatra paścāt phala >= 0\n{\n    a * a\n}\n",
+        )
+        .expect("Failed to parse");
+        let obligations = prove_contracts(&ast);
+        assert_eq!(obligations.len(), 1);
+        assert!(!obligations[0].proved);
+        assert_eq!(
+            obligations[0].to_string(),
+            "postcondition `phala >= 0` of `f` cannot be proved for this result"
+        );
+    }
+
+    #[test]
+    fn test_drop_proved() {
+        let source = |caller: &str| {
+            format!(
+                "kāryakrama f(a: saṅkhyā) -> saṅkhyā\n    yatra pūrva a > 0\n{{\n    a\n}}\n\n\
+                 kāryakrama g() -> saṅkhyā {{\n    {}\n}}\n",
+                caller
+            )
+        };
+        let preconditions = |caller: &str| {
+            let mut ast =
+                crate::parser::Parser::parse_str(&source(caller)).expect("Failed to parse");
+            let obligations = prove_contracts(&ast);
+            drop_proved(&mut ast, &obligations);
+            let Item::Function(func) = &ast.items[0] else {
+                panic!("Expected function declaration");
+            };
+            func.preconditions.len()
+        };
+        assert_eq!(preconditions("f(1)"), 0);
+        assert_eq!(preconditions("f(0)"), 1);
+        // Named as a value, `f` may be called where nothing is proved
+        assert_eq!(preconditions("let h = f;\n    f(1)"), 1);
+    }
+}
@@ -0,0 +1,1011 @@
+//! Pūrṇatā (पूर्णता) - Exhaustiveness and Reachability of Match Arms
+//!
+//! A `pratyabhijñā` must recognize every value of its scrutinee, and each arm
+//! must recognize some value the arms before it do not. Both questions reduce
+//! to *usefulness* (Maranget, "Warnings for pattern matching", 2007): a
+//! pattern is useful after a list of rows when some value matches it and none
+//! of the rows. An arm is unreachable when its pattern is not useful after the
+//! earlier arms; the match is exhaustive when `_` is not useful after all of
+//! them. The search that finds `_` useful also builds a witness, a value no
+//! arm matches, which the error shows as a pattern.
+//!
+//! Patterns are lowered to constructors applied to sub-patterns:
+//! - `satya`/`asatya` for booleans
+//! - integer and character ranges (a literal is a one-value range)
+//! - enum variants, by declaration index
+//! - one constructor for tuples, structs and fixed-size arrays
+//! - slice lengths: exactly `n`, or at least `prefix + suffix`
+//!
+//! String and float literals pick one value out of infinitely many, so only a
+//! wildcard covers those types. Arms with a `yad` guard may fail at runtime:
+//! they count for reachability but never for exhaustiveness.
+
+use std::collections::HashMap;
+
+use super::typeck_impl::{
+    substitute_type_params, ResolvedType, TypeBodyResolved, TypeDefInfo, VariantPayload,
+};
+use crate::parser::ast::{Literal, Pattern, VariantFields};
+
+/// What checking the arms of one match found
+#[derive(Debug, Default)]
+pub struct MatchCoverage {
+    /// A value no arm matches, written as a pattern
+    pub missing: Option<String>,
+    /// Arm index and pattern of each arm, or or-alternative, that can never match
+    pub unreachable: Vec<(usize, String)>,
+}
+
+/// Constructor at the head of a lowered pattern
+#[derive(Debug, Clone, PartialEq)]
+enum Ctor {
+    Bool(bool),
+    /// Integers (and character code points) in `lo..=hi`
+    Range(i128, i128),
+    /// Enum variant by declaration index
+    Variant(usize),
+    /// The only constructor of tuples, structs and fixed-size arrays
+    Single,
+    /// Slices of exactly this many elements
+    FixedLen(usize),
+    /// Slices of at least `prefix + suffix` elements
+    VarLen(usize, usize),
+    /// A string or float literal: distinct from every other constructor
+    Opaque,
+}
+
+/// Lowered pattern
+#[derive(Debug, Clone)]
+enum Pat {
+    Wild,
+    Ctor(Ctor, Vec<Pat>),
+    Or(Vec<Pat>),
+}
+
+/// Usefulness checker over the type definitions of a crate
+pub struct MatchChecker<'a> {
+    type_defs: &'a HashMap<String, TypeDefInfo>,
+}
+
+impl<'a> MatchChecker<'a> {
+    pub fn new(type_defs: &'a HashMap<String, TypeDefInfo>) -> Self {
+        Self { type_defs }
+    }
+
+    /// Check arms, given as `(pattern, guarded)`, of a match on `scrutinee`
+    pub fn check(&self, scrutinee: &ResolvedType, arms: &[(&Pattern, bool)]) -> MatchCoverage {
+        let ty = strip_refs(scrutinee);
+        let tys = [ty.clone()];
+        let mut rows: Vec<Vec<Pat>> = Vec::new();
+        let mut coverage = MatchCoverage::default();
+
+        for (index, (pattern, guarded)) in arms.iter().enumerate() {
+            let pattern = strip_guard(pattern);
+            let alternatives: Vec<&Pattern> = match pattern {
+                Pattern::Or(alternatives) => alternatives.iter().collect(),
+                _ => vec![pattern],
+            };
+
+            // Each alternative must match something the earlier arms and
+            // earlier alternatives of this arm do not
+            let mut seen = rows.clone();
+            let mut dead = Vec::new();
+            for alternative in &alternatives {
+                let lowered = self.lower(alternative, &ty);
+                if self
+                    .useful(&seen, std::slice::from_ref(&lowered), &tys)
+                    .is_none()
+                {
+                    dead.push(describe(alternative));
+                }
+                seen.push(vec![lowered]);
+            }
+            if dead.len() == alternatives.len() {
+                coverage.unreachable.push((index, describe(pattern)));
+            } else {
+                coverage
+                    .unreachable
+                    .extend(dead.into_iter().map(|pattern| (index, pattern)));
+            }
+
+            if !guarded {
+                rows.push(vec![self.lower(pattern, &ty)]);
+            }
+        }
+
+        coverage.missing = self
+            .useful(&rows, &[Pat::Wild], &tys)
+            .map(|mut witness| witness.remove(0));
+        coverage
+    }
+
+    /// Index and payload of variant `name` of the enum `ty`
+    pub fn variant(&self, ty: &ResolvedType, name: &str) -> Option<(usize, VariantPayload)> {
+        self.variants(ty)?
+            .into_iter()
+            .enumerate()
+            .find(|(_, (variant, _))| variant == name)
+            .map(|(index, (_, payload))| (index, payload))
+    }
+
+    /// Variants of the enum `ty`, with its type arguments substituted
+    pub fn variants(&self, ty: &ResolvedType) -> Option<Vec<(String, VariantPayload)>> {
+        let (def, subst) = self.definition(ty)?;
+        match &def.body {
+            TypeBodyResolved::Enum(variants) => Some(
+                variants
+                    .iter()
+                    .map(|(name, payload)| {
+                        let payload = payload.as_ref().map(|fields| {
+                            fields
+                                .iter()
+                                .map(|(field, ty)| {
+                                    (field.clone(), substitute_type_params(ty, &subst))
+                                })
+                                .collect()
+                        });
+                        (name.clone(), payload)
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Fields of the struct `ty`, with its type arguments substituted
+    pub fn struct_fields(&self, ty: &ResolvedType) -> Option<Vec<(String, ResolvedType)>> {
+        let (def, subst) = self.definition(ty)?;
+        match &def.body {
+            TypeBodyResolved::Struct(fields) => Some(
+                fields
+                    .iter()
+                    .map(|(name, ty)| (name.clone(), substitute_type_params(ty, &subst)))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Definition of a named type, seen through aliases
+    fn definition<'b>(
+        &'b self,
+        ty: &'b ResolvedType,
+    ) -> Option<(&'b TypeDefInfo, HashMap<&'b str, &'b ResolvedType>)> {
+        let ResolvedType::Named { name, generics } = ty else {
+            return None;
+        };
+        let def = self.type_defs.get(name)?;
+        if let TypeBodyResolved::Alias(target) = &def.body {
+            if target != ty {
+                return self.definition(target);
+            }
+        }
+        let subst = def
+            .generics
+            .iter()
+            .map(String::as_str)
+            .zip(generics.iter())
+            .collect();
+        Some((def, subst))
+    }
+
+    // ------------------------------------------------------------------
+    // Lowering
+    // ------------------------------------------------------------------
+
+    fn lower(&self, pattern: &Pattern, ty: &ResolvedType) -> Pat {
+        let ty = strip_refs(ty);
+        match pattern {
+            Pattern::Wildcard | Pattern::Rest => Pat::Wild,
+            // A bare name is a binding unless it names a unit variant,
+            // which may be qualified (`Vikalpa::Śūnya`)
+            Pattern::Identifier(name)
+            | Pattern::Binding {
+                name,
+                subpattern: None,
+                ..
+            } => match self.variant(&ty, name.name.rsplit("::").next().unwrap_or_default()) {
+                Some((index, None)) => Pat::Ctor(Ctor::Variant(index), Vec::new()),
+                _ => Pat::Wild,
+            },
+            Pattern::Binding {
+                subpattern: Some(sub),
+                ..
+            } => self.lower(sub, &ty),
+            Pattern::Guard { pattern, .. } | Pattern::Ref { pattern, .. } => {
+                self.lower(pattern, &ty)
+            }
+            Pattern::Literal(literal) => match literal {
+                Literal::Bool(value) => Pat::Ctor(Ctor::Bool(*value), Vec::new()),
+                Literal::Int(_) | Literal::Char(_) => {
+                    let value = literal_value(pattern).unwrap_or_default();
+                    Pat::Ctor(Ctor::Range(value, value), Vec::new())
+                }
+                Literal::Unit => Pat::Ctor(Ctor::Single, Vec::new()),
+                Literal::String(_) | Literal::Float(_) => Pat::Ctor(Ctor::Opaque, Vec::new()),
+            },
+            Pattern::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                let (min, max) = int_bounds(&ty).unwrap_or((i64::MIN.into(), i64::MAX.into()));
+                let lo = match start {
+                    Some(start) => literal_value(start),
+                    None => Some(min),
+                };
+                let hi = match end {
+                    Some(end) => literal_value(end).map(|v| if *inclusive { v } else { v - 1 }),
+                    None => Some(max),
+                };
+                match (lo, hi) {
+                    (Some(lo), Some(hi)) if lo <= hi => Pat::Ctor(Ctor::Range(lo, hi), Vec::new()),
+                    // Empty ranges and ranges over named constants
+                    _ => Pat::Ctor(Ctor::Opaque, Vec::new()),
+                }
+            }
+            Pattern::Tuple(patterns) => {
+                let tys = match &ty {
+                    ResolvedType::Tuple(tys) => tys.clone(),
+                    _ => vec![ResolvedType::Unknown; without_rest(patterns).count()],
+                };
+                Pat::Ctor(Ctor::Single, self.lower_positional(patterns, &tys))
+            }
+            Pattern::Struct { fields, .. } => match self.struct_fields(&ty) {
+                Some(defs) => Pat::Ctor(Ctor::Single, self.lower_named(fields, &defs)),
+                // Without the definition the field order is unknown; a struct
+                // has one constructor, so treat it as irrefutable
+                None => Pat::Wild,
+            },
+            Pattern::Variant {
+                variant, fields, ..
+            } => {
+                let Some((index, payload)) = self.variant(&ty, &variant.name) else {
+                    return Pat::Ctor(Ctor::Opaque, Vec::new());
+                };
+                let defs = payload.unwrap_or_default();
+                let fields = match fields {
+                    VariantFields::Unit => vec![Pat::Wild; defs.len()],
+                    VariantFields::Tuple(patterns) => {
+                        let tys: Vec<ResolvedType> = defs.into_iter().map(|(_, ty)| ty).collect();
+                        self.lower_positional(patterns, &tys)
+                    }
+                    VariantFields::Struct(fields) => self.lower_named(fields, &defs),
+                };
+                Pat::Ctor(Ctor::Variant(index), fields)
+            }
+            Pattern::Constructor { name, fields } => {
+                let Some((index, payload)) = self.variant(&ty, &name.name) else {
+                    return Pat::Ctor(Ctor::Opaque, Vec::new());
+                };
+                let tys: Vec<ResolvedType> = payload
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(_, ty)| ty)
+                    .collect();
+                Pat::Ctor(Ctor::Variant(index), self.lower_positional(fields, &tys))
+            }
+            Pattern::Array(patterns) => {
+                let element = element_type(&ty);
+                let fields = patterns.iter().map(|p| self.lower(p, &element)).collect();
+                match &ty {
+                    ResolvedType::Array { size: Some(_), .. } => Pat::Ctor(Ctor::Single, fields),
+                    _ => Pat::Ctor(Ctor::FixedLen(patterns.len()), fields),
+                }
+            }
+            Pattern::Slice { before, after, .. } => {
+                let element = element_type(&ty);
+                let mut fields: Vec<Pat> = before.iter().map(|p| self.lower(p, &element)).collect();
+                let suffix = after.iter().map(|p| self.lower(p, &element));
+                match &ty {
+                    ResolvedType::Array { size: Some(n), .. } => {
+                        let Some(gap) = n.checked_sub(before.len() + after.len()) else {
+                            return Pat::Ctor(Ctor::Opaque, Vec::new());
+                        };
+                        fields.extend(std::iter::repeat_n(Pat::Wild, gap));
+                        fields.extend(suffix);
+                        Pat::Ctor(Ctor::Single, fields)
+                    }
+                    _ => {
+                        fields.extend(suffix);
+                        Pat::Ctor(Ctor::VarLen(before.len(), after.len()), fields)
+                    }
+                }
+            }
+            Pattern::Or(alternatives) => {
+                Pat::Or(alternatives.iter().map(|p| self.lower(p, &ty)).collect())
+            }
+        }
+    }
+
+    /// Lower tuple-like sub-patterns, expanding a `..` to wildcards
+    fn lower_positional(&self, patterns: &[Pattern], tys: &[ResolvedType]) -> Vec<Pat> {
+        let given = without_rest(patterns).count();
+        let rest = patterns.iter().position(|p| matches!(p, Pattern::Rest));
+        let mut fields = Vec::with_capacity(tys.len());
+        for (i, pattern) in patterns.iter().enumerate() {
+            if Some(i) == rest {
+                let gap = tys.len().saturating_sub(given);
+                fields.extend(std::iter::repeat_n(Pat::Wild, gap));
+                continue;
+            }
+            let ty = tys
+                .get(fields.len())
+                .cloned()
+                .unwrap_or(ResolvedType::Unknown);
+            fields.push(self.lower(pattern, &ty));
+        }
+        fields.resize(tys.len().max(given), Pat::Wild);
+        fields
+    }
+
+    /// Lower struct-like sub-patterns into declaration order
+    fn lower_named(
+        &self,
+        fields: &[(crate::parser::ast::Identifier, Pattern)],
+        defs: &[(String, ResolvedType)],
+    ) -> Vec<Pat> {
+        defs.iter()
+            .map(|(name, ty)| {
+                fields
+                    .iter()
+                    .find(|(field, _)| &field.name == name)
+                    .map_or(Pat::Wild, |(_, pattern)| self.lower(pattern, ty))
+            })
+            .collect()
+    }
+
+    // ------------------------------------------------------------------
+    // Usefulness
+    // ------------------------------------------------------------------
+
+    /// Is `v` useful after `rows`? If so, return a witness per column
+    fn useful(&self, rows: &[Vec<Pat>], v: &[Pat], tys: &[ResolvedType]) -> Option<Vec<String>> {
+        let Some((head, tail)) = v.split_first() else {
+            return rows.is_empty().then(Vec::new);
+        };
+        let rows = expand_or(rows);
+        let ty = self.refine(&tys[0], &rows, head);
+        let heads: Vec<&Ctor> = rows
+            .iter()
+            .filter_map(|row| match &row[0] {
+                Pat::Ctor(ctor, _) => Some(ctor),
+                _ => None,
+            })
+            .collect();
+
+        match head {
+            Pat::Or(alternatives) => alternatives.iter().find_map(|alternative| {
+                let mut v = v.to_vec();
+                v[0] = alternative.clone();
+                self.useful(&rows, &v, tys)
+            }),
+            // Opaque values differ from every listed constructor: only rows
+            // starting with a wildcard can match them
+            Pat::Ctor(Ctor::Opaque, _) => {
+                let mut witness = self.useful(&default_rows(&rows), tail, &tys[1..])?;
+                witness.insert(0, "_".to_string());
+                Some(witness)
+            }
+            Pat::Ctor(ctor, _) => self
+                .split(ctor, &heads)
+                .iter()
+                .find_map(|c| self.useful_specialized(&rows, v, tys, &ty, c)),
+            Pat::Wild => {
+                let Some(all) = self.all_ctors(&ty, &heads) else {
+                    let mut witness = self.useful(&default_rows(&rows), tail, &tys[1..])?;
+                    witness.insert(0, "_".to_string());
+                    return Some(witness);
+                };
+                let missing: Vec<&Ctor> = all
+                    .iter()
+                    .filter(|c| !heads.iter().any(|h| covers(h, c)))
+                    .collect();
+                match missing.first() {
+                    None => all
+                        .iter()
+                        .find_map(|c| self.useful_specialized(&rows, v, tys, &ty, c)),
+                    Some(first) => {
+                        let mut witness = self.useful(&default_rows(&rows), tail, &tys[1..])?;
+                        let shown = if heads.is_empty() {
+                            "_".to_string()
+                        } else {
+                            let arity = self.field_types(first, &ty).len();
+                            self.show(first, &vec!["_".to_string(); arity], &ty)
+                        };
+                        witness.insert(0, shown);
+                        Some(witness)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Usefulness of `v` among the values built with constructor `c`
+    fn useful_specialized(
+        &self,
+        rows: &[Vec<Pat>],
+        v: &[Pat],
+        tys: &[ResolvedType],
+        ty: &ResolvedType,
+        c: &Ctor,
+    ) -> Option<Vec<String>> {
+        let field_tys = self.field_types(c, ty);
+        let arity = field_tys.len();
+        let rows: Vec<Vec<Pat>> = rows
+            .iter()
+            .filter_map(|row| specialize(row, c, arity))
+            .collect();
+        let v = specialize(v, c, arity)?;
+        let tys: Vec<ResolvedType> = field_tys
+            .into_iter()
+            .chain(tys[1..].iter().cloned())
+            .collect();
+
+        let mut witness = self.useful(&rows, &v, &tys)?;
+        let fields: Vec<String> = witness.drain(..arity).collect();
+        witness.insert(0, self.show(c, &fields, ty));
+        Some(witness)
+    }
+
+    /// Guess the type of a column the checker knows nothing about from its patterns
+    fn refine(&self, ty: &ResolvedType, rows: &[Vec<Pat>], head: &Pat) -> ResolvedType {
+        if !matches!(
+            ty,
+            ResolvedType::Unknown | ResolvedType::TypeVar(_) | ResolvedType::Error
+        ) {
+            return ty.clone();
+        }
+        let first = rows
+            .iter()
+            .map(|row| &row[0])
+            .chain(std::iter::once(head))
+            .find_map(|pat| match pat {
+                Pat::Ctor(ctor, fields) if *ctor != Ctor::Opaque => Some((ctor, fields.len())),
+                _ => None,
+            });
+        match first {
+            Some((Ctor::Bool(_), _)) => ResolvedType::Bool,
+            Some((Ctor::Range(..), _)) => ResolvedType::Int64,
+            Some((Ctor::Single, arity)) => ResolvedType::Tuple(vec![ResolvedType::Unknown; arity]),
+            Some((Ctor::FixedLen(_) | Ctor::VarLen(..), _)) => ResolvedType::Array {
+                element: Box::new(ResolvedType::Unknown),
+                size: None,
+            },
+            _ => ty.clone(),
+        }
+    }
+
+    /// Every constructor of `ty`, split so each is covered wholly or not at all
+    /// by the `heads`; `None` for types with infinitely many constructors
+    fn all_ctors(&self, ty: &ResolvedType, heads: &[&Ctor]) -> Option<Vec<Ctor>> {
+        if let Some((min, max)) = int_bounds(ty) {
+            return Some(split_range(min, max, heads));
+        }
+        match ty {
+            ResolvedType::Bool => Some(vec![Ctor::Bool(false), Ctor::Bool(true)]),
+            ResolvedType::Unit | ResolvedType::Tuple(_) => Some(vec![Ctor::Single]),
+            ResolvedType::Array { size: Some(_), .. } => Some(vec![Ctor::Single]),
+            ResolvedType::Array { size: None, .. } => Some(self.split(&Ctor::VarLen(0, 0), heads)),
+            ResolvedType::Named { .. } => {
+                if let Some(variants) = self.variants(ty) {
+                    Some((0..variants.len()).map(Ctor::Variant).collect())
+                } else if self.struct_fields(ty).is_some() {
+                    Some(vec![Ctor::Single])
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Split `ctor` into pieces each covered wholly or not at all by the `heads`
+    fn split(&self, ctor: &Ctor, heads: &[&Ctor]) -> Vec<Ctor> {
+        match ctor {
+            Ctor::Range(lo, hi) => split_range(*lo, *hi, heads),
+            Ctor::VarLen(prefix, suffix) => {
+                // Lengths past the longest listed pattern all behave alike
+                let min = prefix + suffix;
+                let longest = heads
+                    .iter()
+                    .filter_map(|h| match h {
+                        Ctor::FixedLen(n) => Some(n + 1),
+                        Ctor::VarLen(p, s) => Some(p + s),
+                        _ => None,
+                    })
+                    .fold(min, usize::max);
+                (min..longest)
+                    .map(Ctor::FixedLen)
+                    .chain(std::iter::once(Ctor::VarLen(longest, 0)))
+                    .collect()
+            }
+            other => vec![other.clone()],
+        }
+    }
+
+    /// Types of the fields of constructor `c` of type `ty`
+    fn field_types(&self, c: &Ctor, ty: &ResolvedType) -> Vec<ResolvedType> {
+        match (c, ty) {
+            (Ctor::Variant(index), _) => self
+                .variants(ty)
+                .and_then(|mut variants| {
+                    (*index < variants.len()).then(|| variants.swap_remove(*index).1)
+                })
+                .flatten()
+                .map(|fields| fields.into_iter().map(|(_, ty)| ty).collect())
+                .unwrap_or_default(),
+            (Ctor::Single, ResolvedType::Tuple(tys)) => tys.clone(),
+            (Ctor::Single, ResolvedType::Array { element, size }) => {
+                vec![(**element).clone(); size.unwrap_or(0)]
+            }
+            (Ctor::Single, ResolvedType::Named { .. }) => self
+                .struct_fields(ty)
+                .map(|fields| fields.into_iter().map(|(_, ty)| ty).collect())
+                .unwrap_or_default(),
+            (Ctor::FixedLen(n), _) => vec![element_type(ty); *n],
+            (Ctor::VarLen(prefix, suffix), _) => vec![element_type(ty); prefix + suffix],
+            _ => Vec::new(),
+        }
+    }
+
+    // ------------------------------------------------------------------
+    // Witnesses
+    // ------------------------------------------------------------------
+
+    /// Write constructor `c` of type `ty` applied to `fields` as a pattern
+    fn show(&self, c: &Ctor, fields: &[String], ty: &ResolvedType) -> String {
+        match c {
+            Ctor::Bool(true) => "satya".to_string(),
+            Ctor::Bool(false) => "asatya".to_string(),
+            Ctor::Range(lo, hi) => {
+                let (min, max) = int_bounds(ty).unwrap_or((i128::MIN, i128::MAX));
+                match (lo == hi, *lo == min, *hi == max) {
+                    (true, _, _) => lo.to_string(),
+                    (_, true, true) => "_".to_string(),
+                    (_, true, false) => format!("..={}", hi),
+                    (_, false, true) => format!("{}..", lo),
+                    _ => format!("{}..={}", lo, hi),
+                }
+            }
+            Ctor::Variant(index) => {
+                let ResolvedType::Named { name, .. } = ty else {
+                    return "_".to_string();
+                };
+                let variants = self.variants(ty).unwrap_or_default();
+                let Some((variant, payload)) = variants.get(*index) else {
+                    return "_".to_string();
+                };
+                let path = format!("{}::{}", name, variant);
+                match payload {
+                    None => path,
+                    Some(defs) if is_tuple_payload(defs) => {
+                        format!("{}({})", path, fields.join(", "))
+                    }
+                    Some(defs) => format!("{} {{ {} }}", path, named_fields(defs, fields)),
+                }
+            }
+            Ctor::Single => match ty {
+                ResolvedType::Unit => "()".to_string(),
+                ResolvedType::Tuple(_) if fields.len() == 1 => format!("({},)", fields[0]),
+                ResolvedType::Tuple(_) => format!("({})", fields.join(", ")),
+                ResolvedType::Array { .. } => format!("[{}]", fields.join(", ")),
+                ResolvedType::Named { name, .. } => {
+                    let defs = self.struct_fields(ty).unwrap_or_default();
+                    format!("{} {{ {} }}", name, named_fields(&defs, fields))
+                }
+                _ => "_".to_string(),
+            },
+            Ctor::FixedLen(_) => format!("[{}]", fields.join(", ")),
+            Ctor::VarLen(prefix, _) => {
+                let mut parts: Vec<&str> = fields[..*prefix].iter().map(String::as_str).collect();
+                parts.push("..");
+                parts.extend(fields[*prefix..].iter().map(String::as_str));
+                format!("[{}]", parts.join(", "))
+            }
+            Ctor::Opaque => "_".to_string(),
+        }
+    }
+}
+
+/// Does constructor `head` match every value built with `c`?
+fn covers(head: &Ctor, c: &Ctor) -> bool {
+    match (head, c) {
+        (Ctor::Range(lo, hi), Ctor::Range(a, b)) => lo <= a && b <= hi,
+        (Ctor::VarLen(p, s), Ctor::FixedLen(n)) => p + s <= *n,
+        (Ctor::VarLen(p, s), Ctor::VarLen(q, t)) => p + s <= q + t,
+        (Ctor::Opaque, _) | (_, Ctor::Opaque) => false,
+        (head, c) => head == c,
+    }
+}
+
+/// Rows matching the values built with `c`, with its fields as new columns
+fn specialize(row: &[Pat], c: &Ctor, arity: usize) -> Option<Vec<Pat>> {
+    let (head, tail) = row.split_first()?;
+    let mut fields = match head {
+        Pat::Wild => vec![Pat::Wild; arity],
+        Pat::Ctor(h, fields) if covers(h, c) => match h {
+            // `[a, .., z]` against a longer slice: wildcards fill the gap
+            Ctor::VarLen(prefix, suffix) => {
+                let mut expanded = fields[..*prefix].to_vec();
+                let gap = arity.saturating_sub(prefix + suffix);
+                expanded.extend(std::iter::repeat_n(Pat::Wild, gap));
+                expanded.extend_from_slice(&fields[*prefix..]);
+                expanded
+            }
+            _ => fields.clone(),
+        },
+        _ => return None,
+    };
+    fields.resize(arity, Pat::Wild);
+    fields.extend_from_slice(tail);
+    Some(fields)
+}
+
+/// Rows starting with a wildcard, without that column
+fn default_rows(rows: &[Vec<Pat>]) -> Vec<Vec<Pat>> {
+    rows.iter()
+        .filter(|row| matches!(row[0], Pat::Wild))
+        .map(|row| row[1..].to_vec())
+        .collect()
+}
+
+/// Replace rows starting with an or-pattern by one row per alternative
+fn expand_or(rows: &[Vec<Pat>]) -> Vec<Vec<Pat>> {
+    let mut expanded = Vec::with_capacity(rows.len());
+    for row in rows {
+        match &row[0] {
+            Pat::Or(alternatives) => {
+                let split: Vec<Vec<Pat>> = alternatives
+                    .iter()
+                    .map(|alternative| {
+                        let mut row = row.clone();
+                        row[0] = alternative.clone();
+                        row
+                    })
+                    .collect();
+                expanded.extend(expand_or(&split));
+            }
+            _ => expanded.push(row.clone()),
+        }
+    }
+    expanded
+}
+
+/// Split `lo..=hi` at every boundary of the ranges among `heads`
+fn split_range(lo: i128, hi: i128, heads: &[&Ctor]) -> Vec<Ctor> {
+    let mut cuts: Vec<i128> = heads
+        .iter()
+        .filter_map(|h| match h {
+            Ctor::Range(a, b) => Some([*a, b + 1]),
+            _ => None,
+        })
+        .flatten()
+        .filter(|cut| lo < *cut && *cut <= hi)
+        .collect();
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    let mut pieces = Vec::with_capacity(cuts.len() + 1);
+    let mut start = lo;
+    for cut in cuts {
+        pieces.push(Ctor::Range(start, cut - 1));
+        start = cut;
+    }
+    pieces.push(Ctor::Range(start, hi));
+    pieces
+}
+
+/// Range of values of an integer or character type
+fn int_bounds(ty: &ResolvedType) -> Option<(i128, i128)> {
+    Some(match ty {
+        ResolvedType::Int8 => (i8::MIN.into(), i8::MAX.into()),
+        ResolvedType::Int16 => (i16::MIN.into(), i16::MAX.into()),
+        ResolvedType::Int32 => (i32::MIN.into(), i32::MAX.into()),
+        ResolvedType::Int64 => (i64::MIN.into(), i64::MAX.into()),
+        ResolvedType::UInt8 => (0, u8::MAX.into()),
+        ResolvedType::UInt16 => (0, u16::MAX.into()),
+        ResolvedType::UInt32 => (0, u32::MAX.into()),
+        ResolvedType::UInt64 => (0, u64::MAX.into()),
+        ResolvedType::Char => (0, char::MAX as i128),
+        _ => return None,
+    })
+}
+
+/// Value of an integer or character literal pattern
+fn literal_value(pattern: &Pattern) -> Option<i128> {
+    match pattern {
+        Pattern::Literal(Literal::Int(n)) => Some((*n).into()),
+        Pattern::Literal(Literal::Char(c)) => Some(*c as i128),
+        _ => None,
+    }
+}
+
+fn element_type(ty: &ResolvedType) -> ResolvedType {
+    match ty {
+        ResolvedType::Array { element, .. } => (**element).clone(),
+        _ => ResolvedType::Unknown,
+    }
+}
+
+fn strip_refs(ty: &ResolvedType) -> ResolvedType {
+    match ty {
+        ResolvedType::Reference { inner, .. } => strip_refs(inner),
+        other => other.clone(),
+    }
+}
+
+fn strip_guard(pattern: &Pattern) -> &Pattern {
+    match pattern {
+        Pattern::Guard { pattern, .. } => strip_guard(pattern),
+        other => other,
+    }
+}
+
+fn without_rest(patterns: &[Pattern]) -> impl Iterator<Item = &Pattern> {
+    patterns.iter().filter(|p| !matches!(p, Pattern::Rest))
+}
+
+fn is_tuple_payload(defs: &[(String, ResolvedType)]) -> bool {
+    defs.iter()
+        .enumerate()
+        .all(|(i, (name, _))| *name == i.to_string())
+}
+
+fn named_fields(defs: &[(String, ResolvedType)], fields: &[String]) -> String {
+    defs.iter()
+        .zip(fields)
+        .map(|((name, _), field)| format!("{}: {}", name, field))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Write a source pattern back out, for diagnostics
+pub fn describe(pattern: &Pattern) -> String {
+    let list = |patterns: &[Pattern]| patterns.iter().map(describe).collect::<Vec<_>>().join(", ");
+    let named = |fields: &[(crate::parser::ast::Identifier, Pattern)]| {
+        fields
+            .iter()
+            .map(|(name, p)| format!("{}: {}", name.name, describe(p)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match pattern {
+        Pattern::Wildcard => "_".to_string(),
+        Pattern::Rest => "..".to_string(),
+        Pattern::Identifier(name) => name.name.clone(),
+        Pattern::Binding {
+            name,
+            mutable,
+            subpattern,
+        } => {
+            let binding = if *mutable {
+                format!("parivartya {}", name.name)
+            } else {
+                name.name.clone()
+            };
+            match subpattern {
+                Some(sub) => format!("{} @ {}", binding, describe(sub)),
+                None => binding,
+            }
+        }
+        Pattern::Literal(literal) => match literal {
+            Literal::Int(n) => n.to_string(),
+            Literal::Float(f) => f.to_string(),
+            Literal::String(s) => format!("{:?}", s),
+            Literal::Bool(true) => "satya".to_string(),
+            Literal::Bool(false) => "asatya".to_string(),
+            Literal::Char(c) => format!("{:?}", c),
+            Literal::Unit => "()".to_string(),
+        },
+        Pattern::Tuple(patterns) => format!("({})", list(patterns)),
+        Pattern::Struct { name, fields, rest } => {
+            let mut inner = named(fields);
+            if *rest {
+                inner = if inner.is_empty() {
+                    "..".to_string()
+                } else {
+                    format!("{}, ..", inner)
+                };
+            }
+            format!("{} {{ {} }}", name.name, inner)
+        }
+        Pattern::Variant {
+            enum_name,
+            variant,
+            fields,
+        } => {
+            let path = match enum_name {
+                Some(enum_name) => format!("{}::{}", enum_name.name, variant.name),
+                None => variant.name.clone(),
+            };
+            match fields {
+                VariantFields::Unit => path,
+                VariantFields::Tuple(patterns) => format!("{}({})", path, list(patterns)),
+                VariantFields::Struct(fields) => format!("{} {{ {} }}", path, named(fields)),
+            }
+        }
+        Pattern::Constructor { name, fields } => format!("{}({})", name.name, list(fields)),
+        Pattern::Array(patterns) => format!("[{}]", list(patterns)),
+        Pattern::Slice {
+            before,
+            middle,
+            after,
+        } => {
+            let mut parts: Vec<String> = before.iter().map(describe).collect();
+            parts.push(match middle {
+                Some(middle) => format!("{} @ ..", describe(middle)),
+                None => "..".to_string(),
+            });
+            parts.extend(after.iter().map(describe));
+            format!("[{}]", parts.join(", "))
+        }
+        Pattern::Range {
+            start,
+            end,
+            inclusive,
+        } => {
+            let bound = |p: &Option<Box<Pattern>>| p.as_deref().map(describe).unwrap_or_default();
+            let op = if *inclusive { "..=" } else { ".." };
+            format!("{}{}{}", bound(start), op, bound(end))
+        }
+        Pattern::Or(alternatives) => alternatives
+            .iter()
+            .map(describe)
+            .collect::<Vec<_>>()
+            .join(" | "),
+        Pattern::Guard { pattern, .. } => format!("{} yad ..", describe(pattern)),
+        Pattern::Ref { mutable, pattern } => {
+            let prefix = if *mutable { "&parivartya " } else { "&" };
+            format!("{}{}", prefix, describe(pattern))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Span;
+    use crate::parser::ast::Identifier;
+
+    fn ident(name: &str) -> Identifier {
+        Identifier {
+            name: name.to_string(),
+            affixes: Default::default(),
+            span: Span::dummy(),
+        }
+    }
+
+    fn int(n: i64) -> Pattern {
+        Pattern::Literal(Literal::Int(n))
+    }
+
+    fn variant(name: &str, fields: Vec<Pattern>) -> Pattern {
+        Pattern::Variant {
+            enum_name: None,
+            variant: ident(name),
+            fields: if fields.is_empty() {
+                VariantFields::Unit
+            } else {
+                VariantFields::Tuple(fields)
+            },
+        }
+    }
+
+    /// `prakāra Rūpa { Vṛtta(saṅkhyā), Varga(satya), Śūnya }`
+    fn type_defs() -> HashMap<String, TypeDefInfo> {
+        let mut defs = HashMap::new();
+        defs.insert(
+            "Rūpa".to_string(),
+            TypeDefInfo {
+                name: "Rūpa".to_string(),
+                generics: Vec::new(),
+                body: TypeBodyResolved::Enum(vec![
+                    (
+                        "Vṛtta".to_string(),
+                        Some(vec![("0".to_string(), ResolvedType::Int32)]),
+                    ),
+                    (
+                        "Varga".to_string(),
+                        Some(vec![("0".to_string(), ResolvedType::Bool)]),
+                    ),
+                    ("Śūnya".to_string(), None),
+                ]),
+            },
+        );
+        defs
+    }
+
+    fn rupa() -> ResolvedType {
+        ResolvedType::Named {
+            name: "Rūpa".to_string(),
+            generics: Vec::new(),
+        }
+    }
+
+    fn check(ty: ResolvedType, arms: &[Pattern]) -> MatchCoverage {
+        let defs = type_defs();
+        let arms: Vec<(&Pattern, bool)> = arms.iter().map(|p| (p, false)).collect();
+        MatchChecker::new(&defs).check(&ty, &arms)
+    }
+
+    #[test]
+    fn test_missing_variant_is_named() {
+        let arms = [
+            variant("Vṛtta", vec![Pattern::Wildcard]),
+            variant("Śūnya", vec![]),
+        ];
+        let coverage = check(rupa(), &arms);
+        assert_eq!(coverage.missing.as_deref(), Some("Rūpa::Varga(_)"));
+        assert!(coverage.unreachable.is_empty());
+    }
+
+    #[test]
+    fn test_nested_missing_value() {
+        let arms = [
+            variant("Varga", vec![Pattern::Literal(Literal::Bool(true))]),
+            variant("Vṛtta", vec![Pattern::Wildcard]),
+            Pattern::Identifier(ident("Śūnya")),
+        ];
+        let coverage = check(rupa(), &arms);
+        assert_eq!(coverage.missing.as_deref(), Some("Rūpa::Varga(asatya)"));
+    }
+
+    #[test]
+    fn test_integer_ranges() {
+        let range = |lo: i64, hi: i64| Pattern::Range {
+            start: Some(Box::new(int(lo))),
+            end: Some(Box::new(int(hi))),
+            inclusive: true,
+        };
+        let below = Pattern::Range {
+            start: None,
+            end: Some(Box::new(int(0))),
+            inclusive: false,
+        };
+        let coverage = check(ResolvedType::UInt8, &[range(0, 9), range(20, 255)]);
+        assert_eq!(coverage.missing.as_deref(), Some("10..=19"));
+
+        let coverage = check(ResolvedType::Int8, &[below, range(0, 127), int(5)]);
+        assert_eq!(coverage.missing, None);
+        assert_eq!(coverage.unreachable, vec![(2, "5".to_string())]);
+    }
+
+    #[test]
+    fn test_unreachable_after_wildcard_and_or_alternative() {
+        let arms = [
+            Pattern::Or(vec![int(1), int(2)]),
+            Pattern::Or(vec![int(2), int(3)]),
+            Pattern::Wildcard,
+            int(4),
+        ];
+        let coverage = check(ResolvedType::Int32, &arms);
+        assert_eq!(coverage.missing, None);
+        assert_eq!(
+            coverage.unreachable,
+            vec![(1, "2".to_string()), (3, "4".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_slices_and_guards() {
+        let slice = |before: usize| Pattern::Slice {
+            before: vec![Pattern::Wildcard; before],
+            middle: None,
+            after: Vec::new(),
+        };
+        let ty = ResolvedType::Array {
+            element: Box::new(ResolvedType::Int32),
+            size: None,
+        };
+        let coverage = check(ty.clone(), &[Pattern::Array(Vec::new()), slice(2)]);
+        assert_eq!(coverage.missing.as_deref(), Some("[_]"));
+
+        // A guarded wildcard does not make the match exhaustive
+        let defs = type_defs();
+        let wild = Pattern::Wildcard;
+        let arms = [(&wild, true)];
+        let coverage = MatchChecker::new(&defs).check(&ResolvedType::Bool, &arms);
+        assert_eq!(coverage.missing.as_deref(), Some("_"));
+    }
+}
@@ -28,10 +28,12 @@
 
 use std::collections::HashMap;
 use std::fmt;
+use std::ops::ControlFlow;
 
 use crate::lexer::Span;
-use crate::parser::ast::{Block, Type};
+use crate::parser::ast::{Block, Expr, Literal, Type};
 use crate::semantics::traits::{TraitBound, TraitId};
+use crate::visitor::{walk_expr_mut, AstVisitorMut, VisitResult};
 
 // ============================================================================
 // PART 1: TYPE PARAMETERS (Prakāra-Cala - Moving Types)
@@ -226,6 +228,14 @@ impl GenericContext {
             .or_else(|| self.parent.as_ref().and_then(|p| p.get_substitution(var)))
     }
 
+    /// Value substituted for const parameter `name`, if it is one
+    pub fn const_value(&self, name: &str) -> Option<&Expr> {
+        match self.get_substitution(self.lookup(name)?.id)? {
+            Type::Const(value) => Some(value),
+            _ => None,
+        }
+    }
+
     /// Apply all substitutions to a type
     pub fn apply(&self, ty: &Type) -> Type {
         match ty {
@@ -257,10 +267,33 @@ impl GenericContext {
                 params: params.iter().map(|p| self.apply(p)).collect(),
                 return_type: Box::new(self.apply(return_type)),
             },
-            Type::Array { element, size } => Type::Array {
-                element: Box::new(self.apply(element)),
-                size: *size,
-            },
+            Type::Array {
+                element,
+                size,
+                length,
+            } => {
+                // A length written with const parameters takes their values
+                let length = length.as_ref().map(|length| {
+                    let mut length = length.clone();
+                    let _ = ConstSubstitution(self).visit_expr_mut(&mut length);
+                    length
+                });
+                let element = Box::new(self.apply(element));
+                match length.as_deref() {
+                    Some(Expr::Literal(Literal::Int(n))) if size.is_none() && *n >= 0 => {
+                        Type::Array {
+                            element,
+                            size: Some(*n as usize),
+                            length: None,
+                        }
+                    }
+                    _ => Type::Array {
+                        element,
+                        size: *size,
+                        length,
+                    },
+                }
+            }
             Type::Tuple(types) => Type::Tuple(types.iter().map(|t| self.apply(t)).collect()),
             Type::Reference {
                 inner,
@@ -271,7 +304,7 @@ impl GenericContext {
                 mutable: *mutable,
                 lifetime: *lifetime,
             },
-            Type::Inferred => Type::Inferred,
+            Type::Const(_) | Type::Inferred => ty.clone(),
         }
     }
 
@@ -357,6 +390,24 @@ impl std::hash::Hash for MonoId {
     }
 }
 
+/// Replaces the const parameters named in expressions by the values
+/// substituted for them
+pub struct ConstSubstitution<'c>(pub &'c GenericContext);
+
+impl AstVisitorMut for ConstSubstitution<'_> {
+    type Break = ();
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> VisitResult<()> {
+        if let Expr::Identifier(name) = expr {
+            if let Some(value) = self.0.const_value(&name.name) {
+                *expr = value.clone();
+                return ControlFlow::Continue(());
+            }
+        }
+        walk_expr_mut(self, expr)
+    }
+}
+
 /// Mangle a type into a string for name generation
 fn mangle_type(ty: &Type) -> String {
     match ty {
@@ -375,7 +426,7 @@ fn mangle_type(ty: &Type) -> String {
                 format!("Ref__{}", mangle_type(inner))
             }
         }
-        Type::Array { element, size } => {
+        Type::Array { element, size, .. } => {
             if let Some(s) = size {
                 format!("Array{}__{}", s, mangle_type(element))
             } else {
@@ -393,6 +444,12 @@ fn mangle_type(ty: &Type) -> String {
             let args: Vec<String> = params.iter().map(mangle_type).collect();
             format!("Fn__{}__{}", args.join("_"), mangle_type(return_type))
         }
+        // `-3` is written `m3`, keeping the name a symbol
+        Type::Const(_) => match ty.const_value() {
+            Some(value) if value < 0 => format!("m{}", value.unsigned_abs()),
+            Some(value) => value.to_string(),
+            None => "Const".to_string(),
+        },
         Type::Inferred => "Inferred".to_string(),
     }
 }
@@ -934,10 +991,12 @@ impl ConstraintSolver {
                 Type::Array {
                     element: e1,
                     size: s1,
+                    ..
                 },
                 Type::Array {
                     element: e2,
                     size: s2,
+                    ..
                 },
             ) => {
                 if s1 != s2 {
@@ -1015,7 +1074,7 @@ impl ConstraintSolver {
             Type::Array { element, .. } => self.occurs(var, element),
             Type::Tuple(types) => types.iter().any(|t| self.occurs(var, t)),
             Type::Reference { inner, .. } => self.occurs(var, inner),
-            Type::Inferred => false,
+            Type::Const(_) | Type::Inferred => false,
         }
     }
 
@@ -1127,6 +1186,21 @@ impl fmt::Display for MonoError {
     }
 }
 
+impl MonoError {
+    /// Get the span of the error
+    pub fn span(&self) -> Span {
+        match self {
+            MonoError::FunctionNotFound { span, .. }
+            | MonoError::TypeNotFound { span, .. }
+            | MonoError::WrongTypeArgCount { span, .. }
+            | MonoError::TypeMismatch { span, .. }
+            | MonoError::InfiniteType { span, .. }
+            | MonoError::BoundNotSatisfied { span, .. }
+            | MonoError::CannotInfer { span, .. } => *span,
+        }
+    }
+}
+
 impl std::error::Error for MonoError {}
 
 // ============================================================================
@@ -162,17 +162,17 @@ impl LifetimeChecker {
         let lifetime_params = HashMap::new();
         let mut param_lifetimes = HashMap::new();
 
+        // Parameters without a lifetime share a region the body is nested
+        // in, so they outlive everything borrowed from them in the body
+        let param_region = self.create_region(Some(func_region), func.span.clone(), true);
+        self.region_stack.push(param_region);
+
         // Process lifetime parameters from function definition
         // In Jagannath, lifetimes are encoded as ^N suffix on types
         for param in &func.params {
             let lifetime = self.extract_lifetime(&param.ty);
-            if let Some(region_id) = lifetime {
-                param_lifetimes.insert(param.name.name.clone(), region_id);
-            } else {
-                // Create implicit lifetime for parameter
-                let region = self.create_region(Some(func_region), param.span.clone(), true);
-                param_lifetimes.insert(param.name.name.clone(), region);
-            }
+            let region = lifetime.unwrap_or(param_region);
+            param_lifetimes.insert(param.name.name.clone(), region);
         }
 
         // Process return type lifetime
@@ -194,7 +194,8 @@ impl LifetimeChecker {
         // Validate all constraints
         self.validate_constraints()?;
 
-        // Pop function region
+        // Pop parameter and function regions
+        self.region_stack.pop();
         self.region_stack.pop();
 
         if self.errors.is_empty() {
@@ -228,6 +229,7 @@ impl LifetimeChecker {
                 ty,
                 value,
                 span,
+                ..
             } => {
                 self.check_let(name, ty.as_ref(), value.as_ref(), span.clone())?;
             }
@@ -249,22 +251,15 @@ impl LifetimeChecker {
                     self.check_block(else_block)?;
                 }
             }
-            Stmt::Match {
-                scrutinee,
-                arms,
-                span,
-            } => {
-                self.check_match(scrutinee, arms, span.clone())?;
-            }
-            Stmt::Loop {
-                kind,
-                body,
-                span: _,
-            } => {
+            Stmt::Loop { kind, body, .. } => {
                 self.check_loop(kind, body)?;
             }
-            Stmt::Break { span: _ } => {}
-            Stmt::Continue { span: _ } => {}
+            Stmt::Break { value, .. } => {
+                if let Some(value) = value {
+                    self.check_expr(value)?;
+                }
+            }
+            Stmt::Continue { .. } => {}
         }
         Ok(())
     }
@@ -352,7 +347,7 @@ impl LifetimeChecker {
 
             Expr::Unary { op, operand, span } => {
                 match op {
-                    UnaryOp::Ref => {
+                    UnaryOp::Ref | UnaryOp::RefMut => {
                         // Taking a reference
                         if let Expr::Identifier(id) = operand.as_ref() {
                             let referent_region = self
@@ -424,6 +419,13 @@ impl LifetimeChecker {
                 Ok(None)
             }
 
+            Expr::Range { start, end, .. } => {
+                for bound in start.iter().chain(end) {
+                    self.check_expr(bound)?;
+                }
+                Ok(None)
+            }
+
             Expr::StructConstruct {
                 name: _,
                 fields,
@@ -449,6 +451,14 @@ impl LifetimeChecker {
                 Ok(None)
             }
 
+            // Formatting only reads its arguments
+            Expr::Format { args, .. } => {
+                for arg in args {
+                    self.check_expr(arg)?;
+                }
+                Ok(None)
+            }
+
             Expr::Lambda {
                 params,
                 body,
@@ -494,6 +504,20 @@ impl LifetimeChecker {
                 Ok(None)
             }
 
+            Expr::Loop { kind, body, .. } => {
+                self.check_loop(kind, body)?;
+                Ok(None)
+            }
+
+            Expr::Match {
+                scrutinee,
+                arms,
+                span,
+            } => {
+                self.check_match(scrutinee, arms, *span)?;
+                Ok(None)
+            }
+
             Expr::Cast {
                 expr,
                 ty: _,
@@ -503,6 +527,13 @@ impl LifetimeChecker {
             Expr::Try { expr, span: _ } => self.check_expr(expr),
 
             Expr::Await { expr, span: _ } => self.check_expr(expr),
+
+            Expr::Saha { task, span: _ } => {
+                self.check_expr(task)?;
+                Ok(None)
+            }
+
+            Expr::Error(_) => Ok(None),
         }
     }
 
@@ -521,11 +552,12 @@ impl LifetimeChecker {
                 if let Expr::Identifier(id) = operand.as_ref() {
                     // Check if referent is local
                     let referent_region = self.get_value_region(&id.name);
-                    let func_region = self.region_stack.get(1).copied().unwrap_or(0);
+                    // The body's region, inside those of the function and its parameters
+                    let body_region = self.region_stack.get(2).copied().unwrap_or(0);
 
                     if let Some(region) = referent_region {
-                        // If referent's region is inside function, it's dangling
-                        if self.is_region_inside(region, func_region) {
+                        // If referent's region is inside the body, it's dangling
+                        if self.is_region_inside(region, body_region) {
                             self.errors.push(LifetimeError::ReturnLocalReference {
                                 name: id.name.clone(),
                                 span,
@@ -570,6 +602,9 @@ impl LifetimeChecker {
 
             // Check pattern bindings
             self.check_pattern(&arm.pattern)?;
+            if let Some(guard) = &arm.guard {
+                self.check_expr(guard)?;
+            }
 
             // Check body
             self.check_expr(&arm.body)?;
@@ -898,6 +933,20 @@ pub enum LifetimeError {
     ConflictingLifetimes { span: Span },
 }
 
+impl LifetimeError {
+    /// Get the primary span of the error, if known
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            LifetimeError::LifetimeTooShort { span, .. }
+            | LifetimeError::ReturnLocalReference { span, .. }
+            | LifetimeError::ReferenceOutlivesReferent { span, .. }
+            | LifetimeError::BorrowedValueTooShort { span, .. }
+            | LifetimeError::ConflictingLifetimes { span } => Some(*span),
+            LifetimeError::UnknownLifetime(_) => None,
+        }
+    }
+}
+
 impl std::fmt::Display for LifetimeError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
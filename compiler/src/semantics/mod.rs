@@ -1,20 +1,31 @@
 //! Semantics Module - Semantic Analysis for Jagannath
 //!
 //! Handles semantic analysis including:
+//! - Attributes and conditional compilation (cihna)
 //! - Kāraka role analysis
 //! - Type checking
 //! - Lifetime/region checking
 //! - Borrow checking (linear types)
+//! - Closure capture analysis (pāśa)
+//! - Match exhaustiveness and reachability (pūrṇatā)
 //! - Information flow analysis
+//! - Thread safety across `saha` tasks (sūtra)
 //! - Trait/interface system (guṇa)
 //! - Generics & monomorphization (sāmānya-viśeṣa)
 
+pub mod attributes;
 pub mod borrow;
+pub mod closure;
+pub mod contracts;
+pub mod exhaustiveness;
 pub mod generics;
 pub mod karaka;
 pub mod lifetime;
+pub mod monomorphize;
 pub mod philosophy_integration;
+pub mod prelude;
 pub mod security;
+pub mod sutra;
 pub mod traits;
 
 // Type checking - split into submodules for better organization
@@ -25,14 +36,25 @@ mod typeck_impl;
 pub mod typeck;
 
 // Re-exports
+pub use attributes::{
+    check_lints, configure, AttributeError, BuiltinAttribute, CfgContext, InlineHint, Lint,
+    LintLevel, LintReport, LintWarning,
+};
 pub use borrow::BorrowChecker;
+pub use closure::{analyze_captures, CaptureMode, ClosureCaptures, ClosureKind};
+pub use contracts::{drop_proved, prove_contracts, Obligation, ObligationKind};
+pub use exhaustiveness::{MatchChecker, MatchCoverage};
 pub use generics::{
     ConstraintSolver, GenericContext, GenericFunction, GenericType, MonoError, MonoId,
     Monomorphizer, TypeVarId, TypeVariable, Variance,
 };
 pub use karaka::KarakaAnalyzer;
 pub use lifetime::LifetimeChecker;
+pub use monomorphize::monomorphize;
 pub use security::SecurityAnalyzer;
+pub use sutra::{Crossing, SutraTable, ThreadSafety};
 pub use traits::{ImplId, TraitDef, TraitError, TraitId, TraitImpl, TraitSolver};
 // Re-export TypeChecker from the impl module
-pub use typeck_impl::TypeChecker;
+pub use typeck_impl::{CallId, Instantiation, TypeChecker};
+// Errors reported by TypeChecker (the modular typeck::TypeError is separate)
+pub use typeck_impl::TypeError as TypeCheckError;
@@ -0,0 +1,1017 @@
+//! Viśeṣa-karaṇa: Instantiating Generics
+//!
+//! Turns a program with generics into one without: every generic function,
+//! type and method is copied once per set of concrete type arguments it is
+//! used with, and each use is redirected to its copy.
+//!
+//! - A generic function `dvi<T>` called with `i32` becomes `dvi__i32`.
+//! - A generic type `Peti<T>` used as `Peti<i32>` becomes `Peti__i32`, its
+//!   fields substituted, with a copy of each generic impl for it
+//!   (`kriyānvaya<T> Peti<T>`); methods called on a `Peti<i32>` become
+//!   `Peti__i32::mūlya`. A generic enum is kept besides its instances, as
+//!   its variants are still named through it (`Khaṇḍa::Eka`).
+//! - A generic method `tulya<U>` called with `bool` becomes `tulya__bool`
+//!   on its owner, in an impl block of its own.
+//!
+//! Type arguments come from the type checker's inference at each call,
+//! which is matched up with its copy by node; copies are checked in turn,
+//! so generics using generics are instantiated transitively. Generics that
+//! are never used produce no code.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::lexer::Span;
+use crate::parser::ast::{
+    Ast, ConstantDef, Expr, FunctionDef, GenericParam, ImplDef, Item, Parameter, Stmt, TraitDef,
+    Type, TypeBody, TypeDef,
+};
+use crate::semantics::generics::{
+    ConstSubstitution, GenericContext, GenericFunction, GenericType, MonoError, MonoId,
+    Monomorphizer, TypeVarId, TypeVariable,
+};
+use crate::semantics::{CallId, Instantiation, TypeChecker};
+use crate::visitor::{walk_expr_mut, walk_stmt_mut, AstVisitorMut, VisitResult};
+
+/// Instances after which instantiation is assumed not to terminate
+const INSTANCE_LIMIT: usize = 1024;
+
+/// Replace every generic function, type and method of `ast` by its
+/// instantiations
+///
+/// Type errors are not reported here; the program has already been checked.
+pub fn monomorphize(ast: &Ast) -> Result<Ast, Vec<MonoError>> {
+    let templates = Templates::of(ast);
+    if templates.is_empty() {
+        return Ok(ast.clone());
+    }
+
+    let mut checker = TypeChecker::new();
+    checker.collect(ast);
+    let mut instantiator = Instantiator {
+        templates: &templates,
+        checker,
+        mono: Monomorphizer::new(),
+        pending: Vec::new(),
+        emitted: HashSet::new(),
+        items: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    // Concrete items are the roots of instantiation
+    for item in &ast.items {
+        match item {
+            Item::Function(func) if templates.functions.contains_key(&func.name.name) => {}
+            // Variants are named through the template (`Khaṇḍa::Eka`), its
+            // numbering shared by every instance
+            Item::TypeDef(typedef) if templates.types.contains_key(&typedef.name.name) => {
+                if matches!(typedef.body, TypeBody::Enum(_)) {
+                    instantiator.items.push(item.clone());
+                }
+            }
+            Item::Impl(impl_def) if !impl_def.generics.is_empty() => {}
+            Item::Impl(impl_def) => {
+                let mut impl_def = impl_def.clone();
+                impl_def.methods.retain(|m| m.generics.is_empty());
+                instantiator.emit(Item::Impl(impl_def));
+            }
+            item => instantiator.emit(item.clone()),
+        }
+    }
+    instantiator.run();
+
+    let Instantiator {
+        mut items,
+        mut checker,
+        errors,
+        ..
+    } = instantiator;
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    // Instances of generic types are named by their copies
+    let names: HashSet<&str> = templates.types.keys().map(String::as_str).collect();
+    let mut renamer = TypeRewriter(|ty: &mut Type| rename_instances(ty, &names, &mut checker));
+    for item in &mut items {
+        let _ = renamer.visit_item_mut(item);
+    }
+    Ok(Ast {
+        items,
+        file_path: ast.file_path.clone(),
+    })
+}
+
+/// The generic items of a program
+struct Templates<'a> {
+    functions: HashMap<String, &'a FunctionDef>,
+    types: HashMap<String, &'a TypeDef>,
+    /// Every impl block, generic or not, as either may hold generic methods
+    impls: Vec<&'a ImplDef>,
+}
+
+impl<'a> Templates<'a> {
+    fn of(ast: &'a Ast) -> Self {
+        let mut templates = Self {
+            functions: HashMap::new(),
+            types: HashMap::new(),
+            impls: Vec::new(),
+        };
+        for item in &ast.items {
+            match item {
+                Item::Function(func) if !func.generics.is_empty() => {
+                    templates.functions.insert(func.name.name.clone(), func);
+                }
+                Item::TypeDef(typedef) if !typedef.generics.is_empty() => {
+                    templates.types.insert(typedef.name.name.clone(), typedef);
+                }
+                Item::Impl(impl_def) => templates.impls.push(impl_def),
+                _ => {}
+            }
+        }
+        templates
+    }
+
+    fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+            && self.types.is_empty()
+            && self
+                .impls
+                .iter()
+                .all(|i| i.generics.is_empty() && i.methods.iter().all(|m| m.generics.is_empty()))
+    }
+}
+
+/// An instance to emit
+enum Request {
+    Function(MonoId),
+    Type(MonoId),
+    /// Generic method `id.name` at `id.type_args` on receivers of `owner`
+    Method {
+        owner: Type,
+        id: MonoId,
+    },
+}
+
+/// Emits instances on demand, checking each to find what it uses
+struct Instantiator<'a> {
+    templates: &'a Templates<'a>,
+    checker: TypeChecker,
+    mono: Monomorphizer,
+    pending: Vec<(Request, Span)>,
+    /// Names of the instances emitted so far (`Peti__i32::tulya__bool`
+    /// for methods)
+    emitted: HashSet<String>,
+    items: Vec<Item>,
+    errors: Vec<MonoError>,
+}
+
+impl<'a> Instantiator<'a> {
+    /// Emit instances until none is wanted that has not been emitted
+    fn run(&mut self) {
+        let templates = self.templates;
+        while !self.pending.is_empty() {
+            // Polymorphic recursion (`f<T>` calling `f<[T]>`) never settles
+            if self.emitted.len() > INSTANCE_LIMIT {
+                let (request, span) = &self.pending[0];
+                let (Request::Function(id) | Request::Type(id) | Request::Method { id, .. }) =
+                    request;
+                self.errors.push(MonoError::InfiniteType {
+                    var_name: id.name.clone(),
+                    span: *span,
+                });
+                return;
+            }
+            let functions = |name: &str| templates.functions.get(name).map(|f| generic_function(f));
+            let types = |name: &str| templates.types.get(name).map(|t| generic_type(t));
+            if let Err(error) = self.mono.process_work_queue(functions, types) {
+                self.errors.push(error);
+                return;
+            }
+            for (request, span) in std::mem::take(&mut self.pending) {
+                match request {
+                    Request::Function(id) => self.emit_function(&id),
+                    Request::Type(id) => self.emit_type(&id),
+                    Request::Method { owner, id } => self.emit_method(&owner, &id, span),
+                }
+            }
+        }
+    }
+
+    fn emit_function(&mut self, id: &MonoId) {
+        if !self.emitted.insert(id.mangled_name()) {
+            return;
+        }
+        let Some(instance) = self.mono.get_function(id) else {
+            return;
+        };
+        let template = self.templates.functions[&instance.original_name];
+        let mut func = template.clone();
+        func.name.name = instance.mangled_name.clone();
+        func.generics.clear();
+        func.where_clauses.clear();
+        for (param, ty) in func.params.iter_mut().zip(&instance.param_types) {
+            param.ty = ty.clone();
+        }
+        if func.return_type.is_some() {
+            func.return_type = Some(instance.return_type.clone());
+        }
+        let ctx = substitution(&template.generics, &instance.type_args);
+        let _ = TypeRewriter(|ty: &mut Type| *ty = ctx.apply(ty)).visit_function_mut(&mut func);
+        let _ = ConstSubstitution(&ctx).visit_function_mut(&mut func);
+        self.emit(Item::Function(func));
+    }
+
+    /// Emit a generic type at its arguments, with the generic impls for it
+    fn emit_type(&mut self, id: &MonoId) {
+        if !self.emitted.insert(id.mangled_name()) {
+            return;
+        }
+        let template = self.templates.types[&id.name];
+        let ctx = substitution(&template.generics, &id.type_args);
+        let mut typedef = template.clone();
+        typedef.name.name = id.mangled_name();
+        typedef.generics.clear();
+        let _ = TypeRewriter(|ty: &mut Type| *ty = ctx.apply(ty)).visit_typedef_mut(&mut typedef);
+        self.emit(Item::TypeDef(typedef));
+
+        // Until renamed at the end, the instance is still `Peti<i32>`
+        let owner = Type::Named {
+            name: template.name.clone(),
+            generics: id.type_args.clone(),
+            affixes: Default::default(),
+        };
+        let templates = self.templates;
+        for impl_def in &templates.impls {
+            if impl_def.generics.is_empty() {
+                continue;
+            }
+            let Some(ctx) = self.bind(impl_def, &owner) else {
+                continue;
+            };
+            let mut copy = (*impl_def).clone();
+            copy.generics.clear();
+            copy.where_clauses.clear();
+            copy.methods.retain(|m| m.generics.is_empty());
+            let _ = TypeRewriter(|ty: &mut Type| *ty = ctx.apply(ty)).visit_impl_mut(&mut copy);
+            let _ = ConstSubstitution(&ctx).visit_impl_mut(&mut copy);
+            self.emit(Item::Impl(copy));
+        }
+    }
+
+    /// Emit a generic method at its arguments, for receivers of `owner`
+    fn emit_method(&mut self, owner: &Type, id: &MonoId, span: Span) {
+        let key = format!("{}::{}", owner_name(owner), id.mangled_name());
+        if !self.emitted.insert(key) {
+            return;
+        }
+        let Some((impl_def, template, mut ctx)) = self.method(owner, &id.name) else {
+            self.errors.push(MonoError::FunctionNotFound {
+                name: id.name.clone(),
+                span,
+            });
+            return;
+        };
+        if template.generics.len() != id.type_args.len() {
+            self.errors.push(MonoError::WrongTypeArgCount {
+                name: id.name.clone(),
+                expected: template.generics.len(),
+                found: id.type_args.len(),
+                span,
+            });
+            return;
+        }
+        for (param, arg) in template.generics.iter().zip(&id.type_args) {
+            let var = ctx.add_type_var(param.name.name.clone(), param.span);
+            ctx.substitute(var, arg.clone());
+        }
+        let mut method = template.clone();
+        method.name.name = id.mangled_name();
+        method.generics.clear();
+        method.where_clauses.clear();
+        let _ = TypeRewriter(|ty: &mut Type| *ty = ctx.apply(ty)).visit_function_mut(&mut method);
+        let _ = ConstSubstitution(&ctx).visit_function_mut(&mut method);
+        self.emit(Item::Impl(ImplDef {
+            generics: Vec::new(),
+            trait_ref: None,
+            self_ty: owner.clone(),
+            where_clauses: Vec::new(),
+            associated_types: Vec::new(),
+            methods: vec![method],
+            attributes: Vec::new(),
+            span: impl_def.span,
+        }));
+    }
+
+    /// The generic method `name` for receivers of type `owner`, with the
+    /// impl's type parameters bound
+    fn method(
+        &mut self,
+        owner: &Type,
+        name: &str,
+    ) -> Option<(&'a ImplDef, &'a FunctionDef, GenericContext)> {
+        let templates = self.templates;
+        templates.impls.iter().copied().find_map(|impl_def| {
+            let method = impl_def
+                .methods
+                .iter()
+                .find(|m| m.name.name == name && !m.generics.is_empty())?;
+            let ctx = self.bind(impl_def, owner)?;
+            Some((impl_def, method, ctx))
+        })
+    }
+
+    /// Substitution making `impl_def` an impl for `owner`, if it is one
+    fn bind(&mut self, impl_def: &ImplDef, owner: &Type) -> Option<GenericContext> {
+        let params: Vec<&str> = impl_def
+            .generics
+            .iter()
+            .map(|g| g.name.name.as_str())
+            .collect();
+        // Written as the checker writes `owner`: `Peti<saṅkhyā>` is `Peti<i32>`
+        let self_ty = self.checker.canonical_type(&impl_def.self_ty);
+        let mut bindings = HashMap::new();
+        if !matches_type(&self_ty, owner, &params, &mut bindings) {
+            return None;
+        }
+        let mut ctx = GenericContext::new();
+        for param in &impl_def.generics {
+            let var = ctx.add_type_var(param.name.name.clone(), param.span);
+            ctx.substitute(var, bindings.get(param.name.name.as_str())?.clone());
+        }
+        Some(ctx)
+    }
+
+    /// Check `item`, request what it uses and redirect its calls there
+    ///
+    /// Calls are matched up by node, so the item is rewritten in place,
+    /// where it was checked.
+    fn emit(&mut self, mut item: Item) {
+        self.checker.check_single(&item);
+        let instantiations = self.checker.take_instantiations();
+        let calls = self.request_all(instantiations);
+        let _ = CallRedirector { calls }.visit_item_mut(&mut item);
+
+        // Every generic type named is wanted at its arguments
+        let templates = self.templates;
+        let mut used = Vec::new();
+        let names: HashSet<&str> = templates.types.keys().map(String::as_str).collect();
+        let _ = TypeRewriter(|ty: &mut Type| collect_instances(ty, &names, &mut used))
+            .visit_item_mut(&mut item);
+        for ty in used {
+            let ty = self.checker.canonical_type(&ty);
+            self.request_type(&ty, Span::dummy());
+        }
+        self.items.push(item);
+    }
+
+    /// Request each instantiation; returns call → name of its target
+    fn request_all(&mut self, instantiations: Vec<Instantiation>) -> HashMap<CallId, String> {
+        let mut calls = HashMap::new();
+        for inst in instantiations {
+            let Some(owner) = &inst.owner else {
+                if inst.type_args.iter().any(contains_inferred) {
+                    self.errors.push(MonoError::CannotInfer {
+                        context: format!("type arguments of `{}`", inst.function),
+                        span: inst.span,
+                    });
+                    continue;
+                }
+                let id = self
+                    .mono
+                    .request_function(&inst.function, inst.type_args, inst.span);
+                calls.insert(inst.call, id.mangled_name());
+                self.pending.push((Request::Function(id), inst.span));
+                continue;
+            };
+
+            // Methods of concrete types need no instance of their own
+            let Type::Named { name, generics, .. } = owner else {
+                continue;
+            };
+            let generic_owner =
+                !generics.is_empty() && self.templates.types.contains_key(&name.name);
+            if !generic_owner && inst.type_args.is_empty() {
+                continue;
+            }
+            if contains_inferred(owner) || inst.type_args.iter().any(contains_inferred) {
+                self.errors.push(MonoError::CannotInfer {
+                    context: format!("type arguments of `{}`", inst.function),
+                    span: inst.span,
+                });
+                continue;
+            }
+            let owner_name = if generic_owner {
+                self.request_type(owner, inst.span)
+            } else {
+                name.name.clone()
+            };
+            let method = if inst.type_args.is_empty() {
+                inst.function
+            } else {
+                let id = MonoId::new(inst.function, inst.type_args);
+                let method = id.mangled_name();
+                let owner = owner.clone();
+                self.pending
+                    .push((Request::Method { owner, id }, inst.span));
+                method
+            };
+            calls.insert(inst.call, format!("{}::{}", owner_name, method));
+        }
+        calls
+    }
+
+    /// Request the instance `ty` of a generic type; returns its name
+    fn request_type(&mut self, ty: &Type, span: Span) -> String {
+        let Type::Named { name, generics, .. } = ty else {
+            return String::new();
+        };
+        let id = self.mono.request_type(&name.name, generics.clone(), span);
+        let mangled = id.mangled_name();
+        if !self.emitted.contains(&mangled) {
+            self.pending.push((Request::Type(id), span));
+        }
+        mangled
+    }
+}
+
+/// Generic function in the form the monomorphizer expects
+fn generic_function(func: &FunctionDef) -> GenericFunction {
+    GenericFunction {
+        name: func.name.name.clone(),
+        type_params: type_variables(&func.generics),
+        param_types: func.params.iter().map(|p| p.ty.clone()).collect(),
+        return_type: func.return_type.clone().unwrap_or(Type::Inferred),
+        body: Some(func.body.clone()),
+        span: func.span,
+    }
+}
+
+/// Generic type in the form the monomorphizer expects
+fn generic_type(typedef: &TypeDef) -> GenericType {
+    let fields = match &typedef.body {
+        TypeBody::Struct(fields) => fields
+            .iter()
+            .map(|f| (f.name.name.clone(), f.ty.clone()))
+            .collect(),
+        TypeBody::Enum(_) | TypeBody::Alias(_) => Vec::new(),
+    };
+    GenericType {
+        name: typedef.name.name.clone(),
+        type_params: type_variables(&typedef.generics),
+        fields,
+        span: typedef.span,
+    }
+}
+
+fn type_variables(generics: &[GenericParam]) -> Vec<TypeVariable> {
+    generics
+        .iter()
+        .enumerate()
+        .map(|(i, g)| {
+            let var = TypeVariable::new(g.name.name.clone(), TypeVarId::new(i as u32), g.span);
+            match g.ty {
+                Some(_) => var.const_generic(),
+                None => var,
+            }
+        })
+        .collect()
+}
+
+/// Substitution of the type parameters `generics` by `type_args`
+fn substitution(generics: &[GenericParam], type_args: &[Type]) -> GenericContext {
+    let mut ctx = GenericContext::new();
+    for (param, arg) in generics.iter().zip(type_args) {
+        let var = ctx.add_type_var(param.name.name.clone(), param.span);
+        ctx.substitute(var, arg.clone());
+    }
+    ctx
+}
+
+/// Whether `concrete` is `pattern` with its type parameters `params`
+/// bound, binding them
+fn matches_type<'p>(
+    pattern: &'p Type,
+    concrete: &Type,
+    params: &[&'p str],
+    bindings: &mut HashMap<&'p str, Type>,
+) -> bool {
+    match (pattern, concrete) {
+        (Type::Named { name, generics, .. }, _)
+            if generics.is_empty() && params.contains(&name.name.as_str()) =>
+        {
+            let bound = bindings
+                .entry(name.name.as_str())
+                .or_insert_with(|| concrete.clone());
+            same_type(bound, concrete)
+        }
+        (
+            Type::Named { name, generics, .. },
+            Type::Named {
+                name: other,
+                generics: args,
+                ..
+            },
+        ) => {
+            name.name == other.name
+                && generics.len() == args.len()
+                && generics
+                    .iter()
+                    .zip(args)
+                    .all(|(g, a)| matches_type(g, a, params, bindings))
+        }
+        (
+            Type::Reference { inner, mutable, .. },
+            Type::Reference {
+                inner: other,
+                mutable: other_mutable,
+                ..
+            },
+        ) => mutable == other_mutable && matches_type(inner, other, params, bindings),
+        (Type::Array { element, .. }, Type::Array { element: other, .. }) => {
+            matches_type(element, other, params, bindings)
+        }
+        (Type::Tuple(elems), Type::Tuple(others)) => {
+            elems.len() == others.len()
+                && elems
+                    .iter()
+                    .zip(others)
+                    .all(|(e, o)| matches_type(e, o, params, bindings))
+        }
+        (Type::Const(_), Type::Const(_)) => {
+            pattern.const_value().is_some() && pattern.const_value() == concrete.const_value()
+        }
+        _ => false,
+    }
+}
+
+/// Whether two types are the same, whatever their spans
+fn same_type(a: &Type, b: &Type) -> bool {
+    matches_type(a, b, &[], &mut HashMap::new())
+}
+
+/// Name of the impls for receivers of `owner`, as the MIR names them
+fn owner_name(owner: &Type) -> String {
+    match owner {
+        Type::Named { name, generics, .. } if !generics.is_empty() => {
+            MonoId::new(name.name.clone(), generics.clone()).mangled_name()
+        }
+        Type::Named { name, .. } => name.name.clone(),
+        _ => String::new(),
+    }
+}
+
+/// Note the instances of the generic types `names` in `ty`, innermost last
+fn collect_instances(ty: &Type, names: &HashSet<&str>, found: &mut Vec<Type>) {
+    match ty {
+        Type::Named { name, generics, .. } => {
+            if !generics.is_empty()
+                && names.contains(name.name.as_str())
+                && !generics.iter().any(contains_inferred)
+            {
+                found.push(ty.clone());
+            }
+            for generic in generics {
+                collect_instances(generic, names, found);
+            }
+        }
+        Type::Reference { inner, .. } => collect_instances(inner, names, found),
+        Type::Array { element, .. } => collect_instances(element, names, found),
+        Type::Tuple(elems) => {
+            for elem in elems {
+                collect_instances(elem, names, found);
+            }
+        }
+        Type::Function {
+            params,
+            return_type,
+        } => {
+            for param in params {
+                collect_instances(param, names, found);
+            }
+            collect_instances(return_type, names, found);
+        }
+        Type::Const(_) | Type::Inferred => {}
+    }
+}
+
+/// Name each instance of the generic types `names` in `ty` by its copy
+fn rename_instances(ty: &mut Type, names: &HashSet<&str>, checker: &mut TypeChecker) {
+    match ty {
+        Type::Named { name, generics, .. } => {
+            if !generics.is_empty() && names.contains(name.name.as_str()) {
+                // Named as requested: `Peti<saṅkhyā>` is `Peti<i32>`
+                let args = std::mem::take(generics)
+                    .iter()
+                    .map(|g| checker.canonical_type(g))
+                    .collect();
+                name.name = MonoId::new(name.name.clone(), args).mangled_name();
+                return;
+            }
+            for generic in generics {
+                rename_instances(generic, names, checker);
+            }
+        }
+        Type::Reference { inner, .. } => rename_instances(inner, names, checker),
+        Type::Array { element, .. } => rename_instances(element, names, checker),
+        Type::Tuple(elems) => {
+            for elem in elems {
+                rename_instances(elem, names, checker);
+            }
+        }
+        Type::Function {
+            params,
+            return_type,
+        } => {
+            for param in params {
+                rename_instances(param, names, checker);
+            }
+            rename_instances(return_type, names, checker);
+        }
+        Type::Const(_) | Type::Inferred => {}
+    }
+}
+
+fn contains_inferred(ty: &Type) -> bool {
+    match ty {
+        Type::Inferred => true,
+        Type::Named { generics, .. } => generics.iter().any(contains_inferred),
+        Type::Reference { inner, .. } => contains_inferred(inner),
+        Type::Array { element, .. } => contains_inferred(element),
+        Type::Tuple(elems) => elems.iter().any(contains_inferred),
+        Type::Function {
+            params,
+            return_type,
+        } => params.iter().any(contains_inferred) || contains_inferred(return_type),
+        Type::Const(_) => false,
+    }
+}
+
+/// Applies a rewrite to every type written in an item: signatures, fields,
+/// impl headers, `let` annotations, casts and closure parameters
+struct TypeRewriter<F>(F);
+
+impl<F: FnMut(&mut Type)> TypeRewriter<F> {
+    fn params(&mut self, params: &mut [Parameter]) {
+        for param in params {
+            (self.0)(&mut param.ty);
+        }
+    }
+}
+
+impl<F: FnMut(&mut Type)> AstVisitorMut for TypeRewriter<F> {
+    type Break = ();
+
+    fn visit_function_mut(&mut self, func: &mut FunctionDef) -> VisitResult<Self::Break> {
+        self.params(&mut func.params);
+        if let Some(ty) = &mut func.return_type {
+            (self.0)(ty);
+        }
+        self.visit_block_mut(&mut func.body)
+    }
+
+    fn visit_typedef_mut(&mut self, typedef: &mut TypeDef) -> VisitResult<Self::Break> {
+        match &mut typedef.body {
+            TypeBody::Struct(fields) => {
+                for field in fields {
+                    (self.0)(&mut field.ty);
+                }
+            }
+            TypeBody::Enum(variants) => {
+                for field in variants
+                    .iter_mut()
+                    .flat_map(|v| v.fields.iter_mut().flatten())
+                {
+                    (self.0)(&mut field.ty);
+                }
+            }
+            TypeBody::Alias(ty) => (self.0)(ty),
+        }
+        self.continue_()
+    }
+
+    fn visit_constant_mut(&mut self, constant: &mut ConstantDef) -> VisitResult<Self::Break> {
+        if let Some(ty) = &mut constant.ty {
+            (self.0)(ty);
+        }
+        self.visit_expr_mut(&mut constant.value)
+    }
+
+    fn visit_trait_mut(&mut self, trait_def: &mut TraitDef) -> VisitResult<Self::Break> {
+        for method in &mut trait_def.methods {
+            self.params(&mut method.params);
+            if let Some(ty) = &mut method.return_type {
+                (self.0)(ty);
+            }
+            if let Some(body) = &mut method.default_body {
+                self.visit_block_mut(body)?;
+            }
+        }
+        self.continue_()
+    }
+
+    fn visit_impl_mut(&mut self, impl_def: &mut ImplDef) -> VisitResult<Self::Break> {
+        (self.0)(&mut impl_def.self_ty);
+        if let Some(trait_ref) = &mut impl_def.trait_ref {
+            trait_ref.generics.iter_mut().for_each(&mut self.0);
+        }
+        for assoc in &mut impl_def.associated_types {
+            (self.0)(&mut assoc.ty);
+        }
+        for method in &mut impl_def.methods {
+            self.visit_function_mut(method)?;
+        }
+        self.continue_()
+    }
+
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) -> VisitResult<Self::Break> {
+        if let Stmt::Let { ty: Some(ty), .. } = stmt {
+            (self.0)(ty);
+        }
+        walk_stmt_mut(self, stmt)
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> VisitResult<Self::Break> {
+        match expr {
+            Expr::Cast { ty, .. } => (self.0)(ty),
+            Expr::Lambda { params, .. } => self.params(params),
+            _ => {}
+        }
+        walk_expr_mut(self, expr)
+    }
+}
+
+/// Redirects calls, found by node, to the instances chosen for them
+///
+/// A function call is renamed to its instance (`dvi__i32`); a method call
+/// to the path of its instance (`Peti__i32::mūlya`), as is a path call
+/// (`Peti::nava` to `Peti__i32::nava`).
+struct CallRedirector {
+    calls: HashMap<CallId, String>,
+}
+
+impl AstVisitorMut for CallRedirector {
+    type Break = ();
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> VisitResult<Self::Break> {
+        if let Some(target) = self.calls.get(&CallId::of(expr)) {
+            match expr {
+                Expr::Call { callee, .. } => {
+                    if let Expr::Identifier(ident) = callee.as_mut() {
+                        ident.name = target.clone();
+                    }
+                }
+                Expr::MethodCall { method, .. } => method.name = target.clone(),
+                _ => {}
+            }
+        }
+        walk_expr_mut(self, expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::ast::Literal;
+    use crate::parser::Parser;
+
+    fn instantiate(source: &str) -> Ast {
+        let tokens = Lexer::new(source).tokenize();
+        let ast = Parser::new(tokens).parse().expect("parse");
+        monomorphize(&ast).expect("monomorphize")
+    }
+
+    fn function_names(source: &str) -> Vec<String> {
+        instantiate(source)
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Function(f) => Some(f.name.name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_instance_per_type_argument() {
+        let names = function_names(
+            r#"
+kāryakrama eva<T>(x: T) -> T {
+    phera x;
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    let a = eva(satya);
+    phera eva(1) + eva(2);
+}
+"#,
+        );
+        assert_eq!(names, vec!["mukhya", "eva__bool", "eva__i32"]);
+    }
+
+    #[test]
+    fn test_transitive_instantiation() {
+        let names = function_names(
+            r#"
+kāryakrama eva<T>(x: T) -> T {
+    phera x;
+}
+
+kāryakrama dvi<U>(x: U) -> U {
+    phera eva(x);
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera dvi(1);
+}
+"#,
+        );
+        assert_eq!(names, vec!["mukhya", "dvi__i32", "eva__i32"]);
+    }
+
+    const PETI: &str = r#"
+prakāra Peti<T> {
+    v: T,
+    w: saṅkhyā,
+}
+
+kriyānvaya<T> Peti<T> {
+    kāryakrama mūlya(&sva) -> T {
+        phera sva.v;
+    }
+
+    kāryakrama tulya<U>(&sva, u: U) -> U {
+        phera u;
+    }
+}
+
+kāryakrama mukhya(p: Peti<saṅkhyā>, q: Peti<bool>) -> saṅkhyā {
+    let b = q.tulya(7);
+    let c = q.tulya(satya);
+    phera p.mūlya() + p.tulya(1) + b;
+}
+"#;
+
+    /// `Owner::method` for every method of the impls of `ast`
+    fn methods(ast: &Ast) -> Vec<String> {
+        ast.items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Impl(impl_def) => Some(impl_def),
+                _ => None,
+            })
+            .flat_map(|impl_def| {
+                let Type::Named { name, .. } = &impl_def.self_ty else {
+                    panic!("impl for {:?}", impl_def.self_ty);
+                };
+                impl_def
+                    .methods
+                    .iter()
+                    .map(move |m| format!("{}::{}", name.name, m.name.name))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_type_instance() {
+        let ast = instantiate(PETI);
+        let types: Vec<&TypeDef> = ast
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::TypeDef(typedef) => Some(typedef),
+                _ => None,
+            })
+            .collect();
+        let names: Vec<&str> = types.iter().map(|t| t.name.name.as_str()).collect();
+        // In the order they are first called on
+        assert_eq!(names, vec!["Peti__bool", "Peti__i32"]);
+        let TypeBody::Struct(fields) = &types[0].body else {
+            panic!("Peti__bool is a struct");
+        };
+        assert!(
+            matches!(&fields[0].ty, Type::Named { name, .. } if name.name == "bool"),
+            "{:?}",
+            fields[0].ty
+        );
+
+        // Uses name the instances, `saṅkhyā` as `i32`
+        let Item::Function(mukhya) = &ast.items[0] else {
+            panic!("mukhya comes first");
+        };
+        let params: Vec<String> = mukhya
+            .params
+            .iter()
+            .map(|p| match &p.ty {
+                Type::Named { name, generics, .. } if generics.is_empty() => name.name.clone(),
+                ty => panic!("{:?} is not renamed", ty),
+            })
+            .collect();
+        assert_eq!(params, vec!["Peti__i32", "Peti__bool"]);
+        assert!(methods(&ast).contains(&"Peti__i32::mūlya".to_string()));
+    }
+
+    #[test]
+    fn test_method_instance() {
+        let ast = instantiate(PETI);
+        let methods = methods(&ast);
+        for method in [
+            "Peti__bool::tulya__i32",
+            "Peti__bool::tulya__bool",
+            "Peti__i32::tulya__i32",
+        ] {
+            assert!(methods.contains(&method.to_string()), "{:?}", methods);
+        }
+        assert!(
+            !methods.iter().any(|m| m.ends_with("::tulya")),
+            "{:?}",
+            methods
+        );
+
+        // Each call is redirected to its own instance
+        let Item::Function(mukhya) = &ast.items[0] else {
+            panic!("mukhya comes first");
+        };
+        let mut calls = Vec::new();
+        let _ = CallCollector(&mut calls).visit_block_mut(&mut mukhya.body.clone());
+        assert_eq!(
+            calls,
+            vec![
+                "Peti__bool::tulya__i32",
+                "Peti__bool::tulya__bool",
+                "Peti__i32::mūlya",
+                "Peti__i32::tulya__i32",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_const_parameters() {
+        let ast = instantiate(
+            r#"
+prakāra Sīmita<sthira NYŪNA: saṅkhyā, sthira ADHIKA: saṅkhyā> {
+    v: [saṅkhyā; ADHIKA],
+}
+
+kriyānvaya<sthira NYŪNA: saṅkhyā, sthira ADHIKA: saṅkhyā> Sīmita<NYŪNA, ADHIKA> {
+    kāryakrama vistāra(&sva) -> saṅkhyā {
+        phera ADHIKA - NYŪNA;
+    }
+}
+
+kāryakrama mukhya(s: Sīmita<-5, 3>) -> saṅkhyā {
+    phera s.vistāra();
+}
+"#,
+        );
+        assert_eq!(methods(&ast), vec!["Sīmita__m5_3::vistāra"]);
+        let Some(Item::TypeDef(typedef)) = ast.items.iter().find(|i| matches!(i, Item::TypeDef(_)))
+        else {
+            panic!("Sīmita is instantiated");
+        };
+        assert_eq!(typedef.name.name, "Sīmita__m5_3");
+        let TypeBody::Struct(fields) = &typedef.body else {
+            panic!("Sīmita__m5_3 is a struct");
+        };
+        // The length given by the parameter is known once it is substituted
+        assert!(
+            matches!(fields[0].ty, Type::Array { size: Some(3), .. }),
+            "{:?}",
+            fields[0].ty
+        );
+
+        // Uses of the parameters in the method are their values
+        let Some(Item::Impl(impl_def)) = ast.items.iter().find(|i| matches!(i, Item::Impl(_)))
+        else {
+            panic!("the impl is instantiated");
+        };
+        let Stmt::Return {
+            value: Some(Expr::Binary { left, right, .. }),
+            ..
+        } = &impl_def.methods[0].body.stmts[0]
+        else {
+            panic!("{:?}", impl_def.methods[0].body.stmts[0]);
+        };
+        assert!(
+            matches!(**left, Expr::Literal(Literal::Int(3))),
+            "{:?}",
+            left
+        );
+        assert_eq!(Type::Const(right.clone()).const_value(), Some(-5));
+    }
+
+    /// Names of the methods called, in order
+    struct CallCollector<'c>(&'c mut Vec<String>);
+
+    impl AstVisitorMut for CallCollector<'_> {
+        type Break = ();
+
+        fn visit_expr_mut(&mut self, expr: &mut Expr) -> VisitResult<Self::Break> {
+            walk_expr_mut(self, expr)?;
+            if let Expr::MethodCall { method, .. } = expr {
+                self.0.push(method.name.clone());
+            }
+            self.continue_()
+        }
+    }
+}
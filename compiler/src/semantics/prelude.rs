@@ -0,0 +1,33 @@
+//! Prelude (upakrama) - Types Every Program Sees
+//!
+//! Written in Jagannath (`prelude.jag`) and registered with the type
+//! checker and the MIR builder ahead of a program's own items, as if
+//! declared at the top of every module.
+
+use crate::parser::ast::{Item, TypeDef};
+use crate::parser::Parser;
+
+/// Source of the prelude: `Vikalpa<T>` and `Phala<T, E>`
+pub const PRELUDE: &str = include_str!("prelude.jag");
+
+/// Type definitions of the prelude
+pub fn types() -> Vec<TypeDef> {
+    let ast = Parser::parse_str(PRELUDE).expect("the prelude parses");
+    ast.items
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::TypeDef(typedef) => Some(typedef),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Prelude type a type name stands for, under its own name or the
+/// lowercase and English spellings (`vikalpa`/`option`, `phala`/`result`)
+pub fn alias(name: &str) -> Option<&'static str> {
+    match name {
+        "Vikalpa" | "vikalpa" | "option" => Some("Vikalpa"),
+        "Phala" | "phala" | "result" => Some("Phala"),
+        _ => None,
+    }
+}
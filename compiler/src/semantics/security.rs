@@ -1,19 +1,35 @@
 //! Security Analysis - Information Flow Control
 //!
-//! Implements taint tracking using:
-//! - -guhya (secret/tainted) - sensitive data
-//! - -sarvajnika (public/clean) - safe to expose
+//! Checks that no secret can be observed from a public place
+//! (non-interference), across the whole program. Labels are type affixes:
+//! - -guhya (secret): a `saṅkhyā-guhya` parameter, field, binding or result
+//!   is a source of secret data
+//! - -sarvajnika (public): whatever a `-sarvajnika` parameter, field,
+//!   binding or result receives must not depend on a secret
+//!
+//! A type without a label takes the label of what flows into it. Flows are
+//! followed through bindings, operators, struct fields, calls and returns,
+//! and through control: a value assigned, returned or printed under a
+//! branch on a secret depends on that secret. Printing (`mudraṇa!`) is a
+//! public sink.
+//!
+//! Each function is summarized by the parameters that reach its result and
+//! those that reach a public sink inside it. Summaries are computed to a
+//! fixed point over the call graph, so a flow is found however many calls
+//! it crosses, and reported with every step from its source to its sink.
+//!
+//! `#[prakāśana]` (declassify) marks the explicit points where a secret may
+//! become public: on a function, its result is public whatever its
+//! arguments; on a `let`, the bound value is.
 
+use crate::lexer::{Affix, Span};
 use crate::parser::ast::*;
-use std::collections::HashMap;
+use crate::semantics::attributes::BuiltinAttribute;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
 
-/// Security analyzer for information flow
-pub struct SecurityAnalyzer {
-    /// Security labels for variables
-    labels: HashMap<String, SecurityLabel>,
-    /// Information flows detected
-    flows: Vec<InformationFlow>,
-}
+/// Rounds of summary refinement before giving up on a fixed point
+const MAX_ROUNDS: usize = 64;
 
 /// Security label
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -24,243 +40,1158 @@ pub enum SecurityLabel {
     Sarvajnika,
 }
 
-/// Information flow record
-#[derive(Debug, Clone)]
-pub struct InformationFlow {
-    pub from: String,
-    pub to: String,
-    pub from_label: SecurityLabel,
-    pub to_label: SecurityLabel,
+impl SecurityLabel {
+    /// Label a type carries, in its own affixes or those of a type it holds
+    /// (`&saṅkhyā-guhya`, `Vikalpa<sūtra-guhya>`)
+    pub fn of_type(ty: &Type) -> Option<Self> {
+        match ty {
+            Type::Named {
+                affixes, generics, ..
+            } => {
+                if affixes.contains(&Affix::Guhya) {
+                    Some(SecurityLabel::Guhya)
+                } else if affixes.contains(&Affix::Sarvajnika) {
+                    Some(SecurityLabel::Sarvajnika)
+                } else if generics
+                    .iter()
+                    .any(|ty| Self::of_type(ty) == Some(SecurityLabel::Guhya))
+                {
+                    Some(SecurityLabel::Guhya)
+                } else {
+                    None
+                }
+            }
+            Type::Reference { inner, .. } => Self::of_type(inner),
+            Type::Array { element, .. } => Self::of_type(element),
+            Type::Tuple(types) => types.iter().filter_map(Self::of_type).min(),
+            Type::Function { .. } | Type::Const(_) | Type::Inferred => None,
+        }
+    }
 }
 
-impl SecurityAnalyzer {
-    pub fn new() -> Self {
+/// One step of an information flow
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowStep {
+    pub description: String,
+    pub span: Span,
+}
+
+impl FlowStep {
+    fn new(description: String, span: Span) -> Self {
+        Self { description, span }
+    }
+}
+
+/// Where a value's secrecy comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Source {
+    /// Data labeled `-guhya`
+    Secret,
+    /// Whatever the caller passes as parameter `index`
+    Param(usize),
+}
+
+/// What a value depends on, with the flow from each source to it
+#[derive(Debug, Clone, Default)]
+struct Taint {
+    sources: BTreeMap<Source, Vec<FlowStep>>,
+}
+
+impl Taint {
+    fn source(source: Source, path: Vec<FlowStep>) -> Self {
         Self {
-            labels: HashMap::new(),
-            flows: Vec::new(),
+            sources: BTreeMap::from([(source, path)]),
+        }
+    }
+
+    fn secret(step: FlowStep) -> Self {
+        Self::source(Source::Secret, vec![step])
+    }
+
+    /// Add the sources of `other`, keeping the flow already known for a source
+    fn join(&mut self, other: &Taint) {
+        for (source, path) in &other.sources {
+            self.sources.entry(*source).or_insert_with(|| path.clone());
+        }
+    }
+
+    fn joined(mut self, other: &Taint) -> Self {
+        self.join(other);
+        self
+    }
+
+    /// The flow continued by one step
+    fn then(mut self, step: FlowStep) -> Self {
+        for path in self.sources.values_mut() {
+            if !path.contains(&step) {
+                path.push(step.clone());
+            }
         }
+        self
     }
 
-    /// Analyze a function for information flow violations
-    /// Uses Guhya (secret) and Sarvajnika (public) labels
-    pub fn analyze_function(&mut self, func: &FunctionDef) -> Result<(), SecurityError> {
-        // Mark parameters with -guhya affix as secret
-        for param in &func.params {
-            let label = if param.name.name.ends_with("_guhya") {
-                SecurityLabel::Guhya
-            } else {
-                SecurityLabel::Sarvajnika
+    /// The flow continued by a path through a callee
+    fn then_all(mut self, steps: &[FlowStep]) -> Self {
+        for step in steps {
+            self = self.then(step.clone());
+        }
+        self
+    }
+
+    fn keys(&self) -> BTreeSet<Source> {
+        self.sources.keys().copied().collect()
+    }
+}
+
+/// What a function does with its parameters, as seen by its callers
+#[derive(Debug, Clone, Default)]
+struct Summary {
+    /// Sources of the result: parameters, with the flow from each to the
+    /// result, and secrets read inside the function
+    returns: Taint,
+    /// Parameters that reach a public sink inside the function, with the
+    /// flow from the parameter to the sink
+    sinks: BTreeMap<usize, Vec<FlowStep>>,
+}
+
+impl Summary {
+    fn shape(&self) -> (BTreeSet<Source>, BTreeSet<usize>) {
+        (self.returns.keys(), self.sinks.keys().copied().collect())
+    }
+}
+
+/// A function or method of the program
+struct FunctionInfo<'a> {
+    def: &'a FunctionDef,
+    /// Implementing type of a method
+    owner: Option<String>,
+    /// `#[prakāśana]`: the result is public
+    declassifies: bool,
+}
+
+impl FunctionInfo<'_> {
+    /// Name used in diagnostics
+    fn display_name(&self) -> String {
+        match &self.owner {
+            Some(owner) => format!("{}::{}", owner, self.def.name.name),
+            None => self.def.name.name.clone(),
+        }
+    }
+
+    /// Name and declared type of each parameter, the receiver first
+    fn params(&self) -> Vec<(&str, Option<&Type>)> {
+        let receiver = self.def.receiver.map(|_| ("sva", None));
+        receiver
+            .into_iter()
+            .chain(
+                self.def
+                    .params
+                    .iter()
+                    .map(|param| (param.name.name.as_str(), Some(&param.ty))),
+            )
+            .collect()
+    }
+}
+
+/// A local variable in scope
+#[derive(Debug, Clone)]
+struct Local {
+    taint: Taint,
+    /// Name of its type, when known, for finding field labels
+    type_name: Option<String>,
+    /// Declared `-sarvajnika`: assignments to it are public sinks
+    public: bool,
+}
+
+/// Security analyzer for information flow
+///
+/// Built over the linked program by [`SecurityAnalyzer::new`]; [`check`]
+/// reports every flow from a secret to a public place.
+///
+/// [`check`]: SecurityAnalyzer::check
+pub struct SecurityAnalyzer<'a> {
+    functions: Vec<FunctionInfo<'a>>,
+    /// Free functions by name
+    by_name: HashMap<String, usize>,
+    /// Methods by name
+    methods: HashMap<String, Vec<usize>>,
+    /// Labels of struct fields by type and field name
+    fields: HashMap<(String, String), SecurityLabel>,
+    /// Labels of struct fields by field name alone, for values of unknown type
+    field_names: HashMap<String, Vec<Option<SecurityLabel>>>,
+    summaries: Vec<Summary>,
+}
+
+/// Walks one function body with the summaries known so far
+struct FunctionAnalysis<'p, 'a> {
+    program: &'p SecurityAnalyzer<'a>,
+    function: usize,
+    scopes: Vec<HashMap<String, Local>>,
+    /// What reaching the current point depends on (the program counter)
+    pc: Vec<Taint>,
+    /// Values of `break`s, innermost loop last
+    breaks: Vec<Taint>,
+    summary: Summary,
+    violations: Vec<SecurityError>,
+}
+
+impl<'a> SecurityAnalyzer<'a> {
+    pub fn new(ast: &'a Ast) -> Self {
+        let mut analyzer = Self {
+            functions: Vec::new(),
+            by_name: HashMap::new(),
+            methods: HashMap::new(),
+            fields: HashMap::new(),
+            field_names: HashMap::new(),
+            summaries: Vec::new(),
+        };
+        analyzer.collect(&ast.items);
+        analyzer.summaries = vec![Summary::default(); analyzer.functions.len()];
+        analyzer
+    }
+
+    fn collect(&mut self, items: &'a [Item]) {
+        for item in items {
+            match item {
+                Item::Function(def) => {
+                    self.by_name
+                        .insert(def.name.name.clone(), self.functions.len());
+                    self.add_function(def, None);
+                }
+                Item::Impl(impl_def) => {
+                    let owner = type_name(&impl_def.self_ty);
+                    for def in &impl_def.methods {
+                        self.methods
+                            .entry(def.name.name.clone())
+                            .or_default()
+                            .push(self.functions.len());
+                        self.add_function(def, owner.clone());
+                    }
+                }
+                Item::TypeDef(typedef) => {
+                    if let TypeBody::Struct(fields) = &typedef.body {
+                        for field in fields {
+                            let label = SecurityLabel::of_type(&field.ty);
+                            if let Some(label) = label {
+                                self.fields.insert(
+                                    (typedef.name.name.clone(), field.name.name.clone()),
+                                    label,
+                                );
+                            }
+                            self.field_names
+                                .entry(field.name.name.clone())
+                                .or_default()
+                                .push(label);
+                        }
+                    }
+                }
+                Item::Module(module) => self.collect(&module.items),
+                _ => {}
+            }
+        }
+    }
+
+    fn add_function(&mut self, def: &'a FunctionDef, owner: Option<String>) {
+        let declassifies = def.attributes.iter().any(|attr| {
+            matches!(
+                BuiltinAttribute::parse(attr),
+                Ok(BuiltinAttribute::Declassify)
+            )
+        });
+        self.functions.push(FunctionInfo {
+            def,
+            owner,
+            declassifies,
+        });
+    }
+
+    /// Label of field `field` of a value of type `owner`, or of every
+    /// field of that name when the type is unknown and they agree
+    fn field_label(&self, owner: Option<&str>, field: &str) -> Option<SecurityLabel> {
+        if let Some(owner) = owner {
+            return self
+                .fields
+                .get(&(owner.to_string(), field.to_string()))
+                .copied();
+        }
+        let labels = self.field_names.get(field)?;
+        let first = *labels.first()?;
+        labels
+            .iter()
+            .all(|label| *label == first)
+            .then_some(first)?
+    }
+
+    /// Check the whole program, reporting each flow from a secret to a
+    /// public place once
+    pub fn check(mut self) -> Result<(), Vec<SecurityError>> {
+        // Summaries only grow, so their shapes settle
+        for _ in 0..MAX_ROUNDS {
+            let mut changed = false;
+            for index in 0..self.functions.len() {
+                let (summary, _) = FunctionAnalysis::run(&self, index);
+                changed |= summary.shape() != self.summaries[index].shape();
+                self.summaries[index] = summary;
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut errors: Vec<SecurityError> = Vec::new();
+        for index in 0..self.functions.len() {
+            let (_, violations) = FunctionAnalysis::run(&self, index);
+            for violation in violations {
+                if !errors.iter().any(|e| e.same_flow(&violation)) {
+                    errors.push(violation);
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The function a call of `name` resolves to
+    fn resolve_function(&self, name: &str) -> Option<usize> {
+        let name = name.rsplit("::").next().unwrap_or(name);
+        self.by_name.get(name).copied()
+    }
+
+    /// The method `name` called on a value of type `owner`; without the
+    /// type, the only method of that name. A name monomorphization
+    /// resolved (`Peti__i32::mūlya`) carries its owner.
+    fn resolve_method(&self, owner: Option<&str>, name: &str) -> Option<usize> {
+        let (owner, name) = match name.rsplit_once("::") {
+            Some((owner, name)) => (Some(owner), name),
+            None => (owner, name),
+        };
+        let candidates = self.methods.get(name)?;
+        match owner {
+            Some(owner) => candidates
+                .iter()
+                .copied()
+                .find(|&index| self.functions[index].owner.as_deref() == Some(owner)),
+            None => match candidates.as_slice() {
+                [only] => Some(*only),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl<'p, 'a> FunctionAnalysis<'p, 'a> {
+    /// Summary of function `index`, and the flows from secrets to public
+    /// places found in it
+    fn run(program: &'p SecurityAnalyzer<'a>, index: usize) -> (Summary, Vec<SecurityError>) {
+        let info = &program.functions[index];
+        let mut analysis = FunctionAnalysis {
+            program,
+            function: index,
+            scopes: vec![HashMap::new()],
+            pc: Vec::new(),
+            breaks: Vec::new(),
+            summary: Summary::default(),
+            violations: Vec::new(),
+        };
+
+        let name = info.display_name();
+        for (position, (param, ty)) in info.params().into_iter().enumerate() {
+            let label = ty.and_then(SecurityLabel::of_type);
+            let span = info
+                .def
+                .params
+                .iter()
+                .find(|p| p.name.name == param)
+                .map_or(info.def.span, |p| p.span);
+            let taint = match label {
+                Some(SecurityLabel::Guhya) => Taint::secret(FlowStep::new(
+                    format!("`{}` is a -guhya parameter of `{}`", param, name),
+                    span,
+                )),
+                // Callers check what they pass
+                Some(SecurityLabel::Sarvajnika) => Taint::default(),
+                None => Taint::source(Source::Param(position), Vec::new()),
             };
-            self.set_label(param.name.name.clone(), label);
+            let type_name = match ty {
+                Some(ty) => type_name(ty),
+                None => info.owner.clone(),
+            };
+            analysis.declare(param, taint, type_name, false);
         }
 
-        // Analyze function body
-        self.analyze_block(&func.body)?;
+        let result = analysis.block(&info.def.body);
+        let span = match info.def.body.tail() {
+            Some(Stmt::Expr(expr)) => expr.span(),
+            Some(stmt) => stmt.span(),
+            None => info.def.body.span,
+        };
+        analysis.returned(result, span);
+        (analysis.summary, analysis.violations)
+    }
+
+    fn info(&self) -> &'p FunctionInfo<'a> {
+        &self.program.functions[self.function]
+    }
+
+    fn declare(&mut self, name: &str, taint: Taint, type_name: Option<String>, public: bool) {
+        let scope = self.scopes.last_mut().expect("a scope is open");
+        scope.insert(
+            name.to_string(),
+            Local {
+                taint,
+                type_name,
+                public,
+            },
+        );
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Local> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn lookup_mut(&mut self, name: &str) -> Option<&mut Local> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+    }
+
+    /// What reaching this point depends on
+    fn pc(&self) -> Taint {
+        self.pc.iter().fold(Taint::default(), Taint::joined)
+    }
 
-        // Check return statement for illegal exposure
-        self.check_block_returns(&func.body)?;
+    /// Run `f` in a branch whose taking depends on `condition`
+    fn branch<T>(&mut self, condition: &Taint, span: Span, f: impl FnOnce(&mut Self) -> T) -> T {
+        let condition = condition
+            .clone()
+            .then(FlowStep::new("decides a branch".to_string(), span));
+        self.pc.push(condition);
+        let result = f(self);
+        self.pc.pop();
+        result
+    }
 
-        Ok(())
+    /// A value reaches a public place, described by `sink`
+    fn sink(&mut self, taint: &Taint, sink: String, span: Span) {
+        let step = FlowStep::new(sink.clone(), span);
+        for (source, path) in &taint.clone().joined(&self.pc()).sources {
+            match source {
+                Source::Secret => {
+                    let mut path = path.clone();
+                    path.push(step.clone());
+                    self.violations.push(SecurityError::IllegalFlow {
+                        sink: sink.clone(),
+                        path,
+                        span,
+                    });
+                }
+                Source::Param(index) => {
+                    let mut path = path.clone();
+                    path.push(step.clone());
+                    self.summary.sinks.entry(*index).or_insert(path);
+                }
+            }
+        }
     }
 
-    /// Analyze a block for information flow
-    fn analyze_block(&mut self, block: &Block) -> Result<(), SecurityError> {
+    /// The function returns a value
+    fn returned(&mut self, value: Taint, span: Span) {
+        let info = self.info();
+        let name = info.display_name();
+        let value = value.joined(&self.pc());
+        match info
+            .def
+            .return_type
+            .as_ref()
+            .and_then(SecurityLabel::of_type)
+        {
+            Some(SecurityLabel::Sarvajnika) => {
+                self.sink(
+                    &value,
+                    format!("returned as the -sarvajnika result of `{}`", name),
+                    span,
+                );
+            }
+            // Callers see the declared label, not what flows in
+            Some(SecurityLabel::Guhya) => {}
+            None => {
+                let value = value.then(FlowStep::new(format!("returned from `{}`", name), span));
+                self.summary.returns.join(&value);
+            }
+        }
+    }
+
+    fn block(&mut self, block: &Block) -> Taint {
+        self.scopes.push(HashMap::new());
+        let mut value = Taint::default();
         for stmt in &block.stmts {
-            self.analyze_stmt(stmt)?;
+            value = self.stmt(stmt);
+        }
+        if block.tail().is_none() {
+            value = Taint::default();
         }
-        Ok(())
+        self.scopes.pop();
+        value
     }
 
-    /// Analyze a statement for information flow
-    fn analyze_stmt(&mut self, stmt: &Stmt) -> Result<(), SecurityError> {
+    /// Taint of a statement's value
+    fn stmt(&mut self, stmt: &Stmt) -> Taint {
         match stmt {
-            Stmt::Let { name, value, .. } => {
-                // Determine label from name suffix or value
-                let label = if name.name.ends_with("_guhya") {
-                    SecurityLabel::Guhya
-                } else if let Some(val) = value {
-                    self.expr_label(val)
-                } else {
-                    SecurityLabel::Sarvajnika
+            Stmt::Let {
+                name,
+                ty,
+                value,
+                attributes,
+                span,
+            } => {
+                let declassified = attributes.iter().any(|attr| {
+                    matches!(
+                        BuiltinAttribute::parse(attr),
+                        Ok(BuiltinAttribute::Declassify)
+                    )
+                });
+                let mut taint = match value {
+                    Some(value) => self.expr(value).joined(&self.pc()),
+                    None => Taint::default(),
                 };
-                self.set_label(name.name.clone(), label);
-
-                // Check for flow from secret to non-secret variable
-                if let Some(val) = value {
-                    if self.expr_label(val) == SecurityLabel::Guhya
-                        && label == SecurityLabel::Sarvajnika
-                    {
-                        // Allow if variable has _guhya suffix or explicit sanitization
-                        if !name.name.ends_with("_guhya") && !name.name.contains("sanitized") {
-                            self.record_flow_from_expr(val, &name.name)?;
-                        }
+                if declassified {
+                    taint = Taint::default();
+                }
+                let label = ty.as_ref().and_then(SecurityLabel::of_type);
+                let mut type_name = ty.as_ref().and_then(type_name);
+                if type_name.is_none() {
+                    type_name = value.as_ref().and_then(|value| self.value_type(value));
+                }
+                match label {
+                    Some(SecurityLabel::Sarvajnika) => {
+                        self.sink(
+                            &taint,
+                            format!("assigned to `{}`, declared -sarvajnika", name.name),
+                            name.span,
+                        );
+                        taint = Taint::default();
+                    }
+                    Some(SecurityLabel::Guhya) => {
+                        taint.join(&Taint::secret(FlowStep::new(
+                            format!("`{}` is declared -guhya", name.name),
+                            name.span,
+                        )));
                     }
+                    None => {}
                 }
+                let taint =
+                    taint.then(FlowStep::new(format!("assigned to `{}`", name.name), *span));
+                self.declare(
+                    &name.name,
+                    taint,
+                    type_name,
+                    label == Some(SecurityLabel::Sarvajnika),
+                );
+                Taint::default()
             }
-            Stmt::Expr(expr) => {
-                // Check function calls for secret data exposure
-                self.check_expr_exposure(expr)?;
+            Stmt::Expr(expr) => self.expr(expr),
+            Stmt::Return { value, span } => {
+                let taint = match value {
+                    Some(value) => self.expr(value),
+                    None => Taint::default(),
+                };
+                self.returned(taint, *span);
+                Taint::default()
             }
             Stmt::If {
                 condition,
                 then_block,
                 else_block,
-                ..
+                span,
             } => {
-                self.check_expr_exposure(condition)?;
-                self.analyze_block(then_block)?;
-                if let Some(eb) = else_block {
-                    self.analyze_block(eb)?;
-                }
+                let condition = self.expr(condition);
+                self.branch(&condition, *span, |this| {
+                    let mut value = this.block(then_block);
+                    if let Some(else_block) = else_block {
+                        value.join(&this.block(else_block));
+                    }
+                    value.joined(&condition)
+                })
             }
-            Stmt::Loop { body, kind, .. } => {
-                if let LoopKind::While { condition } = kind {
-                    self.check_expr_exposure(condition)?;
+            Stmt::Loop {
+                kind, body, span, ..
+            } => self.r#loop(kind, body, *span),
+            Stmt::Break { value, .. } => {
+                let taint = match value {
+                    Some(value) => self.expr(value),
+                    None => Taint::default(),
+                };
+                let taint = taint.joined(&self.pc());
+                if let Some(breaks) = self.breaks.last_mut() {
+                    breaks.join(&taint);
                 }
-                self.analyze_block(body)?;
+                Taint::default()
             }
-            Stmt::Return {
-                value: Some(val), ..
-            } => {
-                // Mark return values - will be checked in check_block_returns
-                if self.expr_label(val) == SecurityLabel::Guhya {
-                    // Allow returning secret if function returns secret type
+            Stmt::Continue { .. } => Taint::default(),
+        }
+    }
+
+    /// Taint of a loop's value; the body is walked until what its
+    /// variables depend on settles
+    fn r#loop(&mut self, kind: &LoopKind, body: &Block, span: Span) -> Taint {
+        self.breaks.push(Taint::default());
+        let mut condition = Taint::default();
+        for _ in 0..MAX_ROUNDS {
+            let before = self.shape();
+            self.scopes.push(HashMap::new());
+            match kind {
+                LoopKind::ForIn { binding, iterable } => {
+                    let items = self.expr(iterable);
+                    condition = items.clone();
+                    let items = items.then(FlowStep::new(
+                        format!("iterated as `{}`", binding.name),
+                        span,
+                    ));
+                    self.declare(&binding.name, items, None, false);
                 }
+                LoopKind::Range {
+                    binding,
+                    start,
+                    end,
+                    ..
+                } => {
+                    let bounds = self.expr(start).joined(&self.expr(end));
+                    condition = bounds.clone();
+                    let bounds = bounds.then(FlowStep::new(
+                        format!("counted by `{}`", binding.name),
+                        span,
+                    ));
+                    self.declare(&binding.name, bounds, None, false);
+                }
+                LoopKind::While { condition: test } => condition = self.expr(test),
+                LoopKind::Infinite => {}
+            }
+            self.branch(&condition.clone(), span, |this| this.block(body));
+            self.scopes.pop();
+            if self.shape() == before {
+                break;
             }
-            _ => {}
         }
-        Ok(())
+        self.breaks.pop().unwrap_or_default()
     }
 
-    /// Get the security label of an expression
-    fn expr_label(&self, expr: &Expr) -> SecurityLabel {
-        match expr {
-            Expr::Identifier(id) => self.get_label(&id.name),
-            Expr::Call { args, .. } => {
-                // If any argument is secret, result is secret
-                args.iter()
-                    .map(|a| self.expr_label(a))
-                    .max()
-                    .unwrap_or(SecurityLabel::Sarvajnika)
-            }
-            Expr::Binary { left, right, .. } => {
-                // Max of both operands
-                self.expr_label(left).max(self.expr_label(right))
-            }
-            Expr::FieldAccess { object, .. } => self.expr_label(object),
-            _ => SecurityLabel::Sarvajnika,
-        }
-    }
-
-    /// Check expression for dangerous exposure (e.g., printing secrets)
-    fn check_expr_exposure(&self, expr: &Expr) -> Result<(), SecurityError> {
-        if let Expr::Call { callee, args, .. } = expr {
-            // Check if calling a printing/logging function with secret data
-            let is_output_fn = if let Expr::Identifier(id) = callee.as_ref() {
-                matches!(
-                    id.name.as_str(),
-                    "print" | "println" | "mudraya" | "log" | "debug" | "write"
-                )
-            } else {
-                false
-            };
+    /// Sources of every variable in scope, to tell when a loop has settled
+    fn shape(&self) -> BTreeSet<(usize, String, BTreeSet<Source>)> {
+        self.scopes
+            .iter()
+            .enumerate()
+            .flat_map(|(depth, scope)| {
+                scope
+                    .iter()
+                    .map(move |(name, local)| (depth, name.clone(), local.taint.keys()))
+            })
+            .collect()
+    }
 
-            if is_output_fn {
-                for arg in args {
-                    if self.expr_label(arg) == SecurityLabel::Guhya {
-                        return Err(SecurityError::ExposingSecret {
-                            name: format!("{:?}", arg),
-                        });
-                    }
-                }
+    /// Type name of the value an expression produces, when evident
+    fn value_type(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Identifier(id) => self.lookup(&id.name)?.type_name.clone(),
+            Expr::Call { callee, .. } => {
+                let Expr::Identifier(id) = callee.as_ref() else {
+                    return None;
+                };
+                let index = self.program.resolve_function(&id.name)?;
+                self.program.functions[index]
+                    .def
+                    .return_type
+                    .as_ref()
+                    .and_then(type_name)
             }
+            Expr::StructConstruct { name, .. } => Some(name.name.clone()),
+            Expr::Unary {
+                op: UnaryOp::Ref | UnaryOp::RefMut | UnaryOp::Deref,
+                operand,
+                ..
+            } => self.value_type(operand),
+            _ => None,
         }
-        Ok(())
     }
 
-    /// Record flow from expression to variable
-    fn record_flow_from_expr(&mut self, expr: &Expr, to: &str) -> Result<(), SecurityError> {
+    fn expr(&mut self, expr: &Expr) -> Taint {
         match expr {
-            Expr::Identifier(id) => self.record_flow(&id.name, to),
-            Expr::Binary { left, right, .. } => {
-                self.record_flow_from_expr(left, to)?;
-                self.record_flow_from_expr(right, to)
+            Expr::Literal(_) | Expr::Error(_) => Taint::default(),
+            Expr::Identifier(id) => self
+                .lookup(&id.name)
+                .map(|local| local.taint.clone())
+                .unwrap_or_default(),
+            Expr::Binary {
+                left,
+                op,
+                right,
+                span,
+            } => match op {
+                BinaryOp::Assign
+                | BinaryOp::AddAssign
+                | BinaryOp::SubAssign
+                | BinaryOp::MulAssign
+                | BinaryOp::DivAssign => {
+                    let mut value = self.expr(right);
+                    if *op != BinaryOp::Assign {
+                        value.join(&self.expr(left));
+                    }
+                    self.assign(left, value, *span);
+                    Taint::default()
+                }
+                _ => self.expr(left).joined(&self.expr(right)),
+            },
+            Expr::Unary { operand, .. }
+            | Expr::Try { expr: operand, .. }
+            | Expr::Await { expr: operand, .. }
+            | Expr::Cast { expr: operand, .. } => {
+                let taint = self.expr(operand);
+                if let Expr::Try { span, .. } = expr {
+                    // The failure is returned as it is
+                    self.returned(taint.clone(), *span);
+                }
+                taint
+            }
+            Expr::Call { callee, args, span } => self.call(callee, args, *span),
+            Expr::MethodCall {
+                receiver,
+                method,
+                args,
+                span,
+            } => {
+                let owner = self.value_type(receiver);
+                match self.program.resolve_method(owner.as_deref(), &method.name) {
+                    Some(index) => {
+                        let mut taints = vec![self.expr(receiver)];
+                        taints.extend(args.iter().map(|arg| self.expr(arg)));
+                        let spans: Vec<Span> = std::iter::once(receiver.as_ref())
+                            .chain(args)
+                            .map(|arg| arg.span())
+                            .collect();
+                        self.apply(index, taints, &spans, *span)
+                    }
+                    None => args.iter().fold(self.expr(receiver), |taint, arg| {
+                        let arg = self.expr(arg);
+                        taint.joined(&arg)
+                    }),
+                }
+            }
+            Expr::FieldAccess {
+                object,
+                field,
+                span,
+            } => {
+                let taint = self.expr(object);
+                let owner = self.value_type(object);
+                match self.program.field_label(owner.as_deref(), &field.name) {
+                    Some(SecurityLabel::Guhya) => taint.joined(&Taint::secret(FlowStep::new(
+                        format!("field `{}` is -guhya", field.name),
+                        *span,
+                    ))),
+                    _ => taint,
+                }
+            }
+            Expr::Index { object, index, .. } => self.expr(object).joined(&self.expr(index)),
+            Expr::Range { start, end, .. } => {
+                let mut taint = Taint::default();
+                for bound in start.iter().chain(end) {
+                    taint.join(&self.expr(bound));
+                }
+                taint
+            }
+            Expr::StructConstruct { name, fields, span } => {
+                let mut taint = Taint::default();
+                for (field, value) in fields {
+                    let value = self.expr(value);
+                    match self.program.field_label(Some(&name.name), &field.name) {
+                        Some(SecurityLabel::Sarvajnika) => self.sink(
+                            &value,
+                            format!(
+                                "stored in -sarvajnika field `{}` of `{}`",
+                                field.name, name.name
+                            ),
+                            *span,
+                        ),
+                        _ => taint.join(&value),
+                    }
+                }
+                taint
+            }
+            Expr::Array { elements, .. } | Expr::Tuple { elements, .. } => {
+                let mut taint = Taint::default();
+                for element in elements {
+                    taint.join(&self.expr(element));
+                }
+                taint
+            }
+            Expr::Format { args, .. } => {
+                let mut taint = Taint::default();
+                for arg in args {
+                    taint.join(&self.expr(arg));
+                }
+                taint
+            }
+            Expr::Lambda { params, body, .. } => {
+                self.scopes.push(HashMap::new());
+                for param in params {
+                    self.declare(
+                        &param.name.name,
+                        Taint::default(),
+                        type_name(&param.ty),
+                        false,
+                    );
+                }
+                // What the closure captures is in what it returns
+                let taint = self.expr(body);
+                self.scopes.pop();
+                taint
+            }
+            // What the task writes is tracked as its stores; it has no value
+            Expr::Saha { task, .. } => {
+                self.expr(task);
+                Taint::default()
+            }
+            Expr::Block(block) => self.block(block),
+            Expr::If {
+                condition,
+                then_expr,
+                else_expr,
+                span,
+            } => {
+                let condition = self.expr(condition);
+                self.branch(&condition, *span, |this| {
+                    let mut value = this.expr(then_expr);
+                    if let Some(else_expr) = else_expr {
+                        value.join(&this.expr(else_expr));
+                    }
+                    value.joined(&condition)
+                })
+            }
+            Expr::Loop {
+                kind, body, span, ..
+            } => self.r#loop(kind, body, *span),
+            Expr::Match {
+                scrutinee,
+                arms,
+                span,
+            } => {
+                let value = self.expr(scrutinee);
+                let result = self.branch(&value, *span, |this| {
+                    let mut result = Taint::default();
+                    for arm in arms {
+                        this.scopes.push(HashMap::new());
+                        let mut names = Vec::new();
+                        pattern_bindings(&arm.pattern, &mut names);
+                        for name in names {
+                            let bound = value.clone().then(FlowStep::new(
+                                format!("matched as `{}`", name.name),
+                                name.span,
+                            ));
+                            this.declare(&name.name, bound, None, false);
+                        }
+                        let guard = match &arm.guard {
+                            Some(guard) => this.expr(guard),
+                            None => Taint::default(),
+                        };
+                        let body = this.branch(&guard, arm.span, |this| this.expr(&arm.body));
+                        result.join(&body.joined(&guard));
+                        this.scopes.pop();
+                    }
+                    result
+                });
+                // Which arm is taken depends on the scrutinee
+                result.joined(&value)
             }
-            _ => Ok(()),
         }
     }
 
-    /// Check block for return statements exposing secrets
-    fn check_block_returns(&self, _block: &Block) -> Result<(), SecurityError> {
-        // For now, allow returns - could add more strict checking
-        Ok(())
+    /// Store `value` into the place `target`
+    fn assign(&mut self, target: &Expr, value: Taint, span: Span) {
+        let value = value.joined(&self.pc());
+        match target {
+            Expr::Identifier(id) => {
+                let public = self.lookup(&id.name).is_some_and(|local| local.public);
+                if public {
+                    self.sink(
+                        &value,
+                        format!("assigned to `{}`, declared -sarvajnika", id.name),
+                        span,
+                    );
+                    return;
+                }
+                let value = value.then(FlowStep::new(format!("assigned to `{}`", id.name), span));
+                if let Some(local) = self.lookup_mut(&id.name) {
+                    // Flow-insensitive: what it held before may still be read
+                    local.taint.join(&value);
+                }
+            }
+            Expr::FieldAccess { object, field, .. } => {
+                let owner = self.value_type(object);
+                if self.program.field_label(owner.as_deref(), &field.name)
+                    == Some(SecurityLabel::Sarvajnika)
+                {
+                    self.sink(
+                        &value,
+                        format!("stored in -sarvajnika field `{}`", field.name),
+                        span,
+                    );
+                    return;
+                }
+                let value = value.then(FlowStep::new(
+                    format!("stored in field `{}`", field.name),
+                    span,
+                ));
+                self.assign_base(object, value);
+            }
+            Expr::Index { object, index, .. } => {
+                let value = value.joined(&self.expr(index));
+                self.assign_base(object, value);
+            }
+            Expr::Unary { operand, .. } => self.assign_base(operand, value),
+            _ => {}
+        }
     }
 
-    /// Set security label for a variable
-    pub fn set_label(&mut self, name: String, label: SecurityLabel) {
-        self.labels.insert(name, label);
+    /// Taint the variable a place is part of
+    fn assign_base(&mut self, place: &Expr, value: Taint) {
+        match place {
+            Expr::Identifier(id) => {
+                if let Some(local) = self.lookup_mut(&id.name) {
+                    local.taint.join(&value);
+                }
+            }
+            Expr::FieldAccess { object, .. }
+            | Expr::Index { object, .. }
+            | Expr::Unary {
+                operand: object, ..
+            } => self.assign_base(object, value),
+            _ => {}
+        }
     }
 
-    /// Get security label for a variable
-    pub fn get_label(&self, name: &str) -> SecurityLabel {
-        self.labels
-            .get(name)
-            .copied()
-            .unwrap_or(SecurityLabel::Sarvajnika)
+    fn call(&mut self, callee: &Expr, args: &[Expr], span: Span) -> Taint {
+        let taints: Vec<Taint> = args.iter().map(|arg| self.expr(arg)).collect();
+        let all = || taints.iter().fold(Taint::default(), Taint::joined);
+        let Expr::Identifier(id) = callee else {
+            return self.expr(callee).joined(&all());
+        };
+        if id.name == "mudrā" {
+            self.sink(&all(), "printed by `mudraṇa!`".to_string(), span);
+            return Taint::default();
+        }
+        if self.lookup(&id.name).is_some() {
+            // A closure held in a variable
+            return self.expr(callee).joined(&all());
+        }
+        match self.program.resolve_function(&id.name) {
+            Some(index) => {
+                let spans: Vec<Span> = args.iter().map(Expr::span).collect();
+                self.apply(index, taints, &spans, span)
+            }
+            // Constructors and built-ins hold or combine their arguments
+            None => all(),
+        }
     }
 
-    /// Record an information flow
-    pub fn record_flow(&mut self, from: &str, to: &str) -> Result<(), SecurityError> {
-        let from_label = self.get_label(from);
-        let to_label = self.get_label(to);
-
-        self.flows.push(InformationFlow {
-            from: from.to_string(),
-            to: to.to_string(),
-            from_label,
-            to_label,
-        });
+    /// Call function `index` with arguments of these taints
+    fn apply(&mut self, index: usize, args: Vec<Taint>, spans: &[Span], span: Span) -> Taint {
+        let program = self.program;
+        let info = &program.functions[index];
+        let summary = &program.summaries[index];
+        let name = info.display_name();
+        let params = info.params();
+        let mut result = Taint::default();
 
-        // Check for illegal flow: guhya → sarvajnika
-        if from_label == SecurityLabel::Guhya && to_label == SecurityLabel::Sarvajnika {
-            return Err(SecurityError::IllegalFlow {
-                from: from.to_string(),
-                to: to.to_string(),
-                reason: "Secret data cannot flow to public output".to_string(),
-            });
+        for (position, arg) in args.iter().enumerate() {
+            let Some((param, ty)) = params.get(position) else {
+                continue;
+            };
+            let arg_span = spans.get(position).copied().unwrap_or(span);
+            let arg_span = if arg_span.line == 0 { span } else { arg_span };
+            let label = ty.and_then(SecurityLabel::of_type);
+            if label == Some(SecurityLabel::Sarvajnika) {
+                self.sink(
+                    arg,
+                    format!("passed as -sarvajnika parameter `{}` of `{}`", param, name),
+                    arg_span,
+                );
+                continue;
+            }
+            let passed = arg.clone().then(FlowStep::new(
+                format!("passed as `{}` to `{}`", param, name),
+                arg_span,
+            ));
+            if let Some(path) = summary.sinks.get(&position) {
+                let reaching = passed.clone().then_all(path);
+                for (source, path) in &reaching.sources {
+                    let Some(last) = path.last() else {
+                        continue;
+                    };
+                    self.sink(
+                        &Taint::source(*source, path[..path.len() - 1].to_vec()),
+                        last.description.clone(),
+                        last.span,
+                    );
+                }
+            }
+            if let Some(path) = summary.returns.sources.get(&Source::Param(position)) {
+                result.join(&passed.then_all(path));
+            }
         }
 
-        Ok(())
+        match info
+            .def
+            .return_type
+            .as_ref()
+            .and_then(SecurityLabel::of_type)
+        {
+            Some(SecurityLabel::Guhya) => Taint::secret(FlowStep::new(
+                format!("`{}` returns a -guhya value", name),
+                span,
+            )),
+            Some(SecurityLabel::Sarvajnika) => Taint::default(),
+            None if info.declassifies => Taint::default(),
+            None => {
+                if let Some(path) = summary.returns.sources.get(&Source::Secret) {
+                    result.join(
+                        &Taint::source(Source::Secret, path.clone()).then(FlowStep::new(
+                            format!("returned by the call to `{}`", name),
+                            span,
+                        )),
+                    );
+                }
+                result
+            }
+        }
     }
+}
 
-    /// Check if a value can be returned/exposed
-    pub fn can_expose(&self, name: &str) -> bool {
-        self.get_label(name) == SecurityLabel::Sarvajnika
+/// Name of the type a declared type names, looking through references
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Named { name, .. } => Some(name.name.clone()),
+        Type::Reference { inner, .. } => type_name(inner),
+        _ => None,
     }
+}
 
-    /// Sanitize a value (downgrade from guhya to sarvajnika)
-    /// Only allowed with explicit sanitization function
-    pub fn sanitize(&mut self, name: &str) {
-        self.labels
-            .insert(name.to_string(), SecurityLabel::Sarvajnika);
+/// Names a pattern binds
+pub(crate) fn pattern_bindings<'e>(pattern: &'e Pattern, names: &mut Vec<&'e Identifier>) {
+    match pattern {
+        Pattern::Binding {
+            name, subpattern, ..
+        } => {
+            names.push(name);
+            if let Some(subpattern) = subpattern {
+                pattern_bindings(subpattern, names);
+            }
+        }
+        Pattern::Identifier(name) => names.push(name),
+        Pattern::Tuple(patterns) | Pattern::Array(patterns) | Pattern::Or(patterns) => {
+            for pattern in patterns {
+                pattern_bindings(pattern, names);
+            }
+        }
+        Pattern::Constructor { fields, .. } => {
+            for pattern in fields {
+                pattern_bindings(pattern, names);
+            }
+        }
+        Pattern::Struct { fields, .. }
+        | Pattern::Variant {
+            fields: VariantFields::Struct(fields),
+            ..
+        } => {
+            for (_, pattern) in fields {
+                pattern_bindings(pattern, names);
+            }
+        }
+        Pattern::Variant {
+            fields: VariantFields::Tuple(patterns),
+            ..
+        } => {
+            for pattern in patterns {
+                pattern_bindings(pattern, names);
+            }
+        }
+        Pattern::Slice {
+            before,
+            middle,
+            after,
+        } => {
+            for pattern in before.iter().chain(middle.as_deref()).chain(after) {
+                pattern_bindings(pattern, names);
+            }
+        }
+        Pattern::Guard { pattern, .. } | Pattern::Ref { pattern, .. } => {
+            pattern_bindings(pattern, names)
+        }
+        Pattern::Wildcard
+        | Pattern::Literal(_)
+        | Pattern::Range { .. }
+        | Pattern::Rest
+        | Pattern::Variant {
+            fields: VariantFields::Unit,
+            ..
+        } => {}
     }
 }
 
 /// Security error
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum SecurityError {
-    /// Illegal information flow
+    /// A secret reaches a public place
     IllegalFlow {
-        from: String,
-        to: String,
-        reason: String,
+        /// The public place
+        sink: String,
+        /// Every step from the secret to the sink, the sink last
+        path: Vec<FlowStep>,
+        span: Span,
     },
-    /// Exposing secret data
-    ExposingSecret { name: String },
 }
 
-impl Default for SecurityAnalyzer {
-    fn default() -> Self {
-        Self::new()
+impl SecurityError {
+    pub fn span(&self) -> Span {
+        match self {
+            SecurityError::IllegalFlow { span, .. } => *span,
+        }
+    }
+
+    /// Whether both report the same secret reaching the same sink
+    fn same_flow(&self, other: &SecurityError) -> bool {
+        let (
+            SecurityError::IllegalFlow {
+                path, span, sink, ..
+            },
+            SecurityError::IllegalFlow {
+                path: other_path,
+                span: other_span,
+                sink: other_sink,
+                ..
+            },
+        ) = (self, other);
+        (span.line, span.column, sink) == (other_span.line, other_span.column, other_sink)
+            && path.first() == other_path.first()
+    }
+}
+
+impl fmt::Display for SecurityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecurityError::IllegalFlow { sink, path, .. } => {
+                write!(f, "गुह्य प्रवाह (Illegal flow): a -guhya value is {}", sink)?;
+                for step in path {
+                    write!(
+                        f,
+                        "\n      {}:{}: {}",
+                        step.span.line, step.span.column, step.description
+                    )?;
+                }
+                Ok(())
+            }
+        }
     }
 }
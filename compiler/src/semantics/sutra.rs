@@ -0,0 +1,475 @@
+//! Sūtra (सूत्र, "thread") - Thread Safety
+//!
+//! Values pass between tasks at `saha`: a `saha { .. }` block shares the
+//! variables it uses with the code after it, and `saha f(..)` moves its
+//! arguments to the new task. Two properties of a type, after Rust's `Send`
+//! and `Sync`, decide what may pass:
+//! - *sendable*: a value may move to another task
+//! - *shareable*: a value may be used by two tasks at once
+//!
+//! Scalars and strings are both. `-sūtra` marks a type whose values
+//! synchronize themselves, as if behind a lock: it is both whatever it holds,
+//! and a variable of such a type is the only kind a task may assign while
+//! other code can see it. `-eka` marks a value tied to one task, which is
+//! neither. Other types derive the properties from what they hold:
+//! - a struct or enum has a property when all its fields do, with its
+//!   generic arguments put in for its parameters
+//! - arrays, slices, `Sūci<T>` and tuples follow their elements
+//! - `&T` is sendable and shareable when `T` is shareable; `&mut T` is
+//!   whatever `T` is
+//! - a type parameter is neither, as nothing is known of its arguments
+//!
+//! A type the derivation cannot see through, such as one guarding `-eka`
+//! fields with a lock of its own, opts in with `#[asurakṣita(sūtra)]`: an
+//! unchecked promise that it is both, taken on trust like an `unsafe impl`.
+
+use std::collections::HashMap;
+
+use crate::lexer::affixes::Affix;
+use crate::parser::ast::{Type, TypeBody, TypeDef};
+use crate::semantics::attributes::BuiltinAttribute;
+use crate::semantics::borrow::is_copy_type_name;
+
+/// What values of a type may do across tasks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThreadSafety {
+    /// May move to another task (Send)
+    pub send: bool,
+    /// May be used by two tasks at once (Sync)
+    pub sync: bool,
+}
+
+impl ThreadSafety {
+    pub const SAFE: Self = Self {
+        send: true,
+        sync: true,
+    };
+    pub const UNSAFE: Self = Self {
+        send: false,
+        sync: false,
+    };
+
+    fn meet(self, other: Self) -> Self {
+        Self {
+            send: self.send && other.send,
+            sync: self.sync && other.sync,
+        }
+    }
+
+    fn has(self, need: Need) -> bool {
+        match need {
+            Need::Send => self.send,
+            Need::Sync => self.sync,
+        }
+    }
+}
+
+/// How a value reaches a `saha` task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Crossing {
+    /// Moved or copied to the task
+    Sent,
+    /// Used by the task while the code around it can use it too
+    Shared,
+    /// Assigned by the task while the code around it can see it
+    Written,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Need {
+    Send,
+    Sync,
+}
+
+/// What a type holds, for deriving its thread safety
+#[derive(Debug, Clone)]
+struct SutraDef {
+    generics: Vec<String>,
+    /// Each field described for diagnostics (``field `x` ``), with its type
+    fields: Vec<(String, Type)>,
+    /// Marked `#[asurakṣita(sūtra)]`
+    assumed: bool,
+}
+
+/// Thread safety of the types of a program
+#[derive(Debug, Clone, Default)]
+pub struct SutraTable {
+    types: HashMap<String, SutraDef>,
+}
+
+impl SutraTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record what a type definition holds
+    pub fn register(&mut self, typedef: &TypeDef) {
+        let fields = match &typedef.body {
+            TypeBody::Struct(fields) => fields
+                .iter()
+                .map(|f| (format!("field `{}`", f.name.name), f.ty.clone()))
+                .collect(),
+            TypeBody::Enum(variants) => variants
+                .iter()
+                .flat_map(|v| {
+                    v.fields
+                        .iter()
+                        .flatten()
+                        .map(move |f| (format!("variant `{}`", v.name.name), f.ty.clone()))
+                })
+                .collect(),
+            TypeBody::Alias(ty) => vec![("alias".to_string(), ty.clone())],
+        };
+        let assumed = typedef.attributes.iter().any(|attr| {
+            matches!(
+                BuiltinAttribute::parse(attr),
+                Ok(BuiltinAttribute::AssumeThreadSafe)
+            )
+        });
+        self.types.insert(
+            typedef.name.name.clone(),
+            SutraDef {
+                generics: typedef
+                    .generics
+                    .iter()
+                    .map(|g| g.name.name.clone())
+                    .collect(),
+                fields,
+                assumed,
+            },
+        );
+    }
+
+    /// Thread safety of values of type `ty`
+    pub fn of_type(&self, ty: &Type) -> ThreadSafety {
+        self.derive(ty, &HashMap::new(), &mut Vec::new())
+    }
+
+    /// Whether a variable of type `ty` synchronizes its own writes, so a
+    /// task may assign it while other code can see it
+    pub fn is_synchronized(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Named { name, affixes, .. } => {
+                affixes.contains(&Affix::Sutra)
+                    || self.types.get(&name.name).is_some_and(|def| def.assumed)
+            }
+            _ => false,
+        }
+    }
+
+    /// Why a value of type `ty` cannot cross into a task as `crossing`, or
+    /// `None` if it can
+    pub fn check(&self, ty: &Type, crossing: Crossing) -> Option<String> {
+        let need = match crossing {
+            Crossing::Sent => Need::Send,
+            Crossing::Shared => Need::Sync,
+            Crossing::Written => {
+                return (!self.is_synchronized(ty)).then(|| "its type is not -sūtra".to_string())
+            }
+        };
+        self.blame(ty, need, "its type", &HashMap::new(), &mut Vec::new())
+    }
+
+    fn derive(
+        &self,
+        ty: &Type,
+        env: &HashMap<String, Type>,
+        visiting: &mut Vec<String>,
+    ) -> ThreadSafety {
+        match ty {
+            Type::Named {
+                name,
+                generics,
+                affixes,
+            } => {
+                if affixes.contains(&Affix::Eka) {
+                    return ThreadSafety::UNSAFE;
+                }
+                if affixes.contains(&Affix::Sutra) {
+                    return ThreadSafety::SAFE;
+                }
+                let name = name.name.as_str();
+                if let Some(arg) = env.get(name) {
+                    return self.derive(arg, &HashMap::new(), visiting);
+                }
+                if is_thread_safe_name(name) {
+                    return ThreadSafety::SAFE;
+                }
+                if matches!(name, "Sūci" | "sūci") {
+                    return generics
+                        .iter()
+                        .map(|g| self.derive(g, env, visiting))
+                        .fold(ThreadSafety::SAFE, ThreadSafety::meet);
+                }
+                let Some(def) = self.types.get(name) else {
+                    return ThreadSafety::UNSAFE;
+                };
+                // A recursive type is whatever its other fields make it
+                if def.assumed || visiting.iter().any(|v| v == name) {
+                    return ThreadSafety::SAFE;
+                }
+                let inner = instantiate(def, generics, env);
+                visiting.push(name.to_string());
+                let safety = def
+                    .fields
+                    .iter()
+                    .map(|(_, field)| self.derive(field, &inner, visiting))
+                    .fold(ThreadSafety::SAFE, ThreadSafety::meet);
+                visiting.pop();
+                safety
+            }
+            Type::Reference { inner, mutable, .. } => {
+                let inner = self.derive(inner, env, visiting);
+                if *mutable {
+                    inner
+                } else {
+                    ThreadSafety {
+                        send: inner.sync,
+                        sync: inner.sync,
+                    }
+                }
+            }
+            Type::Array { element, .. } => self.derive(element, env, visiting),
+            Type::Tuple(elements) => elements
+                .iter()
+                .map(|e| self.derive(e, env, visiting))
+                .fold(ThreadSafety::SAFE, ThreadSafety::meet),
+            Type::Function { .. } | Type::Const(_) | Type::Inferred => ThreadSafety::SAFE,
+        }
+    }
+
+    /// The innermost part of `ty` that lacks `need`, described
+    fn blame(
+        &self,
+        ty: &Type,
+        need: Need,
+        subject: &str,
+        env: &HashMap<String, Type>,
+        visiting: &mut Vec<String>,
+    ) -> Option<String> {
+        if self.derive(ty, env, visiting).has(need) {
+            return None;
+        }
+        match ty {
+            Type::Named { affixes, .. } if affixes.contains(&Affix::Eka) => {
+                Some(format!("{} is -eka", subject))
+            }
+            Type::Named { name, generics, .. } => {
+                let name = name.name.as_str();
+                if let Some(arg) = env.get(name) {
+                    return self.blame(arg, need, subject, &HashMap::new(), visiting);
+                }
+                if matches!(name, "Sūci" | "sūci") {
+                    return generics
+                        .iter()
+                        .find_map(|g| self.blame(g, need, subject, env, visiting));
+                }
+                let Some(def) = self.types.get(name) else {
+                    return Some(format!(
+                        "{} is `{}`, not known to be thread-safe",
+                        subject, name
+                    ));
+                };
+                let inner = instantiate(def, generics, env);
+                visiting.push(name.to_string());
+                let found = def.fields.iter().find_map(|(field, ty)| {
+                    let subject = format!("{} of `{}`", field, name);
+                    self.blame(ty, need, &subject, &inner, visiting)
+                });
+                visiting.pop();
+                found
+            }
+            // A shared reference needs what it refers to be shareable
+            Type::Reference { inner, mutable, .. } => {
+                let need = if *mutable { need } else { Need::Sync };
+                self.blame(inner, need, subject, env, visiting)
+            }
+            Type::Array { element, .. } => self.blame(element, need, subject, env, visiting),
+            Type::Tuple(elements) => elements
+                .iter()
+                .find_map(|e| self.blame(e, need, subject, env, visiting)),
+            Type::Function { .. } | Type::Const(_) | Type::Inferred => None,
+        }
+    }
+}
+
+/// Parameters of `def` bound to its arguments, which are themselves
+/// resolved in `env`; arguments left out are left to inference
+fn instantiate(
+    def: &SutraDef,
+    args: &[Type],
+    env: &HashMap<String, Type>,
+) -> HashMap<String, Type> {
+    def.generics
+        .iter()
+        .enumerate()
+        .map(|(i, param)| {
+            let arg = args
+                .get(i)
+                .map_or(Type::Inferred, |arg| substitute(arg, env));
+            (param.clone(), arg)
+        })
+        .collect()
+}
+
+/// `ty` with the type parameters in `env` replaced
+fn substitute(ty: &Type, env: &HashMap<String, Type>) -> Type {
+    match ty {
+        Type::Named {
+            name,
+            generics,
+            affixes,
+        } => match env.get(&name.name) {
+            // A thread-safety affix on the parameter decides on its own
+            Some(arg)
+                if generics.is_empty()
+                    && !affixes.contains(&Affix::Eka)
+                    && !affixes.contains(&Affix::Sutra) =>
+            {
+                arg.clone()
+            }
+            _ => Type::Named {
+                name: name.clone(),
+                generics: generics.iter().map(|g| substitute(g, env)).collect(),
+                affixes: affixes.clone(),
+            },
+        },
+        Type::Reference {
+            inner,
+            mutable,
+            lifetime,
+        } => Type::Reference {
+            inner: Box::new(substitute(inner, env)),
+            mutable: *mutable,
+            lifetime: *lifetime,
+        },
+        Type::Array {
+            element,
+            size,
+            length,
+        } => Type::Array {
+            element: Box::new(substitute(element, env)),
+            size: *size,
+            length: length.clone(),
+        },
+        Type::Tuple(elements) => Type::Tuple(elements.iter().map(|e| substitute(e, env)).collect()),
+        Type::Function {
+            params,
+            return_type,
+        } => Type::Function {
+            params: params.iter().map(|p| substitute(p, env)).collect(),
+            return_type: Box::new(substitute(return_type, env)),
+        },
+        Type::Const(_) | Type::Inferred => ty.clone(),
+    }
+}
+
+/// Scalars and strings, which hold no reference to anything shared
+fn is_thread_safe_name(name: &str) -> bool {
+    is_copy_type_name(name) || matches!(name, "str" | "sūtra" | "String" | "Sūtra")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast::{Item, Parameter};
+    use crate::parser::Parser;
+
+    /// Table of the types in `source`, and the parameter types of its `f`
+    fn table(source: &str) -> (SutraTable, Vec<Parameter>) {
+        let ast = Parser::parse_str(source).expect("parse");
+        let mut table = SutraTable::new();
+        let mut params = Vec::new();
+        for item in &ast.items {
+            match item {
+                Item::TypeDef(typedef) => table.register(typedef),
+                Item::Function(func) => params = func.params.clone(),
+                _ => {}
+            }
+        }
+        (table, params)
+    }
+
+    #[test]
+    fn test_affixes() {
+        let (table, params) = table("kāryakrama f(a: saṅkhyā, b: saṅkhyā-eka, c: Ajñāta-sūtra) {}");
+        assert_eq!(table.of_type(&params[0].ty), ThreadSafety::SAFE);
+        assert_eq!(table.of_type(&params[1].ty), ThreadSafety::UNSAFE);
+        assert_eq!(table.of_type(&params[2].ty), ThreadSafety::SAFE);
+        assert!(table.is_synchronized(&params[2].ty));
+        assert!(!table.is_synchronized(&params[0].ty));
+    }
+
+    #[test]
+    fn test_derived_from_fields() {
+        let (table, params) = table(
+            r#"
+prakāra Kośa {
+    mūlya: saṅkhyā-eka,
+}
+
+prakāra Peṭī<T> {
+    antar: T,
+}
+
+kāryakrama f(a: Kośa, b: Peṭī<saṅkhyā>, c: Peṭī<Kośa>, d: &mut Kośa, e: Vikalpa<Kośa>) {}
+"#,
+        );
+        assert_eq!(table.of_type(&params[0].ty), ThreadSafety::UNSAFE);
+        assert_eq!(table.of_type(&params[1].ty), ThreadSafety::SAFE);
+        assert_eq!(
+            table.check(&params[2].ty, Crossing::Sent).as_deref(),
+            Some("field `mūlya` of `Kośa` is -eka")
+        );
+        assert!(table.check(&params[3].ty, Crossing::Shared).is_some());
+        // A type not declared here is not known
+        assert_eq!(
+            table.check(&params[4].ty, Crossing::Sent).as_deref(),
+            Some("its type is `Vikalpa`, not known to be thread-safe")
+        );
+    }
+
+    #[test]
+    fn test_references() {
+        let (table, params) = table(
+            r#"
+prakāra Gaṇaka {
+    n: saṅkhyā,
+    kośa: Vikalpa-eka,
+}
+
+kāryakrama f(a: &saṅkhyā, b: &Gaṇaka, c: &mut saṅkhyā) {}
+"#,
+        );
+        assert_eq!(table.of_type(&params[0].ty), ThreadSafety::SAFE);
+        assert_eq!(table.of_type(&params[1].ty), ThreadSafety::UNSAFE);
+        assert_eq!(table.of_type(&params[2].ty), ThreadSafety::SAFE);
+    }
+
+    #[test]
+    fn test_assumed() {
+        let (table, params) = table(
+            r#"
+#[asurakṣita(sūtra)]
+prakāra Tālā {
+    mūlya: saṅkhyā-eka,
+}
+
+prakāra Sūcī {
+    agra: Saṅkalana,
+}
+
+prakāra Saṅkalana {
+    tālā: Tālā,
+    pūrva: Sūcī,
+}
+
+kāryakrama f(a: Tālā, b: Sūcī) {}
+"#,
+        );
+        assert_eq!(table.of_type(&params[0].ty), ThreadSafety::SAFE);
+        assert!(table.is_synchronized(&params[0].ty));
+        // Recursion settles on what the other fields make it
+        assert_eq!(table.of_type(&params[1].ty), ThreadSafety::SAFE);
+    }
+}
@@ -796,7 +796,43 @@ impl TraitSolver {
         );
         self.builtin_traits.default = Some(default_id);
 
-        // Iterator trait (पुनरावर्तक)
+        // Formatting traits, required by `vinyāsa!` placeholders: `{}` needs
+        // Display (प्रदर्शन), `{:?}` needs Debug (विमर्श). Primitives format
+        // without impls; the methods differ in name so that a type can
+        // implement both.
+        for (name, sanskrit, method) in [
+            ("Display", "प्रदर्शन", "fmt"),
+            ("Debug", "विमर्श", "fmt_debug"),
+        ] {
+            let id = self.register_trait(
+                TraitDef::new(name, TraitId(0), Span::dummy())
+                    .with_sanskrit_name(sanskrit)
+                    .with_method(
+                        TraitMethod::new(
+                            method,
+                            Type::Named {
+                                name: Identifier {
+                                    name: "sūtra".to_string(),
+                                    affixes: Default::default(),
+                                    span: Span::dummy(),
+                                },
+                                generics: vec![],
+                                affixes: Default::default(),
+                            },
+                            Span::dummy(),
+                        )
+                        .with_receiver(MethodReceiver::Ref),
+                    ),
+            );
+            if name == "Display" {
+                self.builtin_traits.display = Some(id);
+            } else {
+                self.builtin_traits.debug = Some(id);
+            }
+        }
+
+        // Iterator trait (पुनरावर्तक): `next(&mut self) -> Vikalpa<Self::Item>`, the
+        // protocol `cala x : e` loops over user types desugar to
         let iterator_span = Span::dummy();
         let iterator_id = self.register_trait({
             let mut tr =
@@ -808,7 +844,7 @@ impl TraitSolver {
                     "next",
                     Type::Named {
                         name: Identifier {
-                            name: "Option".to_string(),
+                            name: "Vikalpa".to_string(),
                             affixes: Default::default(),
                             span: Span::dummy(),
                         },
@@ -830,6 +866,69 @@ impl TraitSolver {
             tr
         });
         self.builtin_traits.iterator = Some(iterator_id);
+
+        // From trait (प्राप्त): `from(value: T) -> Self`, which `?` applies to
+        // an error before returning it from a function with another error type
+        let from_span = Span::dummy();
+        let named = |name: &str| Type::Named {
+            name: Identifier {
+                name: name.to_string(),
+                affixes: Default::default(),
+                span: Span::dummy(),
+            },
+            generics: vec![],
+            affixes: Default::default(),
+        };
+        let from_id = self.register_trait({
+            let mut tr = TraitDef::new("From", TraitId(0), from_span).with_sanskrit_name("प्राप्त");
+            tr.generics.push(GenericParam {
+                name: Identifier {
+                    name: "T".to_string(),
+                    affixes: Default::default(),
+                    span: Span::dummy(),
+                },
+                bounds: vec![],
+                ty: None,
+                span: from_span,
+            });
+            tr.methods.push(
+                TraitMethod::new("from", named("Self"), from_span)
+                    .with_receiver(MethodReceiver::None)
+                    .with_param(MethodParam {
+                        name: "value".to_string(),
+                        ty: named("T"),
+                        span: from_span,
+                    }),
+            );
+            tr
+        });
+        self.builtin_traits.from = Some(from_id);
+
+        // Closure traits (āhvāna - calling): every Fn is FnMut, every FnMut is FnOnce
+        let fn_once_id = self.register_trait(
+            TraitDef::new("FnOnce", TraitId(0), Span::dummy()).with_sanskrit_name("सकृद्-आह्वान"),
+        );
+        self.builtin_traits.fn_once = Some(fn_once_id);
+
+        let fn_mut_id = self.register_trait(
+            TraitDef::new("FnMut", TraitId(0), Span::dummy())
+                .with_sanskrit_name("विकारी-आह्वान")
+                .with_supertrait(TraitBound::new(
+                    TraitRef::new(fn_once_id, "FnOnce"),
+                    Span::dummy(),
+                )),
+        );
+        self.builtin_traits.fn_mut = Some(fn_mut_id);
+
+        let fn_id = self.register_trait(
+            TraitDef::new("Fn", TraitId(0), Span::dummy())
+                .with_sanskrit_name("आह्वान")
+                .with_supertrait(TraitBound::new(
+                    TraitRef::new(fn_mut_id, "FnMut"),
+                    Span::dummy(),
+                )),
+        );
+        self.builtin_traits.fn_ = Some(fn_id);
     }
 
     /// Register a new trait
@@ -861,7 +960,7 @@ impl TraitSolver {
                 .push(impl_def);
         } else {
             // Inherent impl
-            let type_name = self.type_name(&impl_def.implementing_type);
+            let type_name = self.type_head(&impl_def.implementing_type);
             self.inherent_impls
                 .entry(type_name)
                 .or_default()
@@ -904,19 +1003,49 @@ impl TraitSolver {
             .find(|imp| self.types_match(&imp.implementing_type, ty))
     }
 
+    /// Find the implementation of a generic trait for a type, with these
+    /// type arguments (`From<E>` for `ty`)
+    pub fn find_impl_with_args(
+        &self,
+        ty: &Type,
+        trait_id: TraitId,
+        type_args: &[Type],
+    ) -> Option<&TraitImpl> {
+        self.impls_by_trait.get(&trait_id)?.iter().find(|imp| {
+            self.types_match(&imp.implementing_type, ty)
+                && imp.trait_ref.as_ref().is_some_and(|trait_ref| {
+                    trait_ref.type_args.len() == type_args.len()
+                        && trait_ref
+                            .type_args
+                            .iter()
+                            .zip(type_args)
+                            .all(|(pattern, arg)| self.types_match(pattern, arg))
+                })
+        })
+    }
+
     /// Resolve a method call
+    ///
+    /// Inherent methods take precedence over trait methods. When a trait impl
+    /// does not override a method, the trait's default body is selected.
     pub fn resolve_method(&self, ty: &Type, method_name: &str) -> Option<ResolvedMethod> {
         // First, check inherent impls
-        let type_name = self.type_name(ty);
+        let type_name = self.type_head(ty);
         if let Some(impls) = self.inherent_impls.get(&type_name) {
             for imp in impls {
+                if !self.types_match(&imp.implementing_type, ty) {
+                    continue;
+                }
                 if let Some(method) = imp.find_method(method_name) {
                     return Some(ResolvedMethod {
                         impl_id: imp.id,
                         trait_id: None,
                         method_name: method_name.to_string(),
                         receiver: method.receiver.clone(),
+                        params: method.params.clone(),
                         return_type: method.return_type.clone(),
+                        generics: method.generics.clone(),
+                        is_default: false,
                     });
                 }
             }
@@ -932,7 +1061,29 @@ impl TraitSolver {
                             trait_id: Some(*trait_id),
                             method_name: method_name.to_string(),
                             receiver: method.receiver.clone(),
+                            params: method.params.clone(),
                             return_type: method.return_type.clone(),
+                            generics: method.generics.clone(),
+                            is_default: false,
+                        });
+                    }
+
+                    // Fall back to the trait's default method
+                    let default = self
+                        .traits
+                        .get(trait_id)
+                        .and_then(|t| t.find_method(method_name))
+                        .filter(|m| m.has_default());
+                    if let Some(method) = default {
+                        return Some(ResolvedMethod {
+                            impl_id: imp.id,
+                            trait_id: Some(*trait_id),
+                            method_name: method_name.to_string(),
+                            receiver: method.receiver.clone(),
+                            params: method.params.clone(),
+                            return_type: method.return_type.clone(),
+                            generics: method.generics.clone(),
+                            is_default: true,
                         });
                     }
                 }
@@ -957,10 +1108,21 @@ impl TraitSolver {
             let existing = self.impls_by_trait.get(&trait_ref.trait_id);
             if let Some(impls) = existing {
                 for existing_impl in impls {
-                    if self.types_overlap(
-                        &impl_def.implementing_type,
-                        &existing_impl.implementing_type,
-                    ) {
+                    // `From<A>` and `From<B>` are different traits to implement
+                    let same_args = existing_impl.trait_ref.as_ref().is_none_or(|existing| {
+                        existing.type_args.len() == trait_ref.type_args.len()
+                            && existing
+                                .type_args
+                                .iter()
+                                .zip(&trait_ref.type_args)
+                                .all(|(a, b)| self.types_overlap(a, b))
+                    });
+                    if same_args
+                        && self.types_overlap(
+                            &impl_def.implementing_type,
+                            &existing_impl.implementing_type,
+                        )
+                    {
                         return Err(TraitError::OverlappingImpl {
                             trait_name: trait_ref.name.clone(),
                             type_name: self.type_name(&impl_def.implementing_type),
@@ -1046,10 +1208,12 @@ impl TraitSolver {
                 Type::Array {
                     element: e1,
                     size: s1,
+                    ..
                 },
                 Type::Array {
                     element: e2,
                     size: s2,
+                    ..
                 },
             ) => s1 == s2 && self.types_match(e1, e2),
             (Type::Tuple(t1), Type::Tuple(t2)) => {
@@ -1088,7 +1252,7 @@ impl TraitSolver {
                     format!("&{}", self.type_name(inner))
                 }
             }
-            Type::Array { element, size } => {
+            Type::Array { element, size, .. } => {
                 if let Some(s) = size {
                     format!("[{}; {}]", self.type_name(element), s)
                 } else {
@@ -1106,10 +1270,23 @@ impl TraitSolver {
                 let args: Vec<_> = params.iter().map(|p| self.type_name(p)).collect();
                 format!("fn({}) -> {}", args.join(", "), self.type_name(return_type))
             }
+            Type::Const(_) => match ty.const_value() {
+                Some(value) => value.to_string(),
+                None => "{..}".to_string(),
+            },
             Type::Inferred => "_".to_string(),
         }
     }
 
+    /// Get the head name of a type, ignoring generic arguments
+    fn type_head(&self, ty: &Type) -> String {
+        match ty {
+            Type::Named { name, .. } => name.name.clone(),
+            Type::Reference { inner, .. } => self.type_head(inner),
+            _ => self.type_name(ty),
+        }
+    }
+
     /// Get all traits a type implements
     pub fn implemented_traits(&self, ty: &Type) -> Vec<TraitId> {
         let mut result = Vec::new();
@@ -1168,7 +1345,12 @@ pub struct ResolvedMethod {
     pub trait_id: Option<TraitId>,
     pub method_name: String,
     pub receiver: MethodReceiver,
+    pub params: Vec<MethodParam>,
     pub return_type: Type,
+    /// Type parameters of the method itself
+    pub generics: Vec<GenericParam>,
+    /// Whether the trait's default body was selected
+    pub is_default: bool,
 }
 
 // ============================================================================
@@ -1240,6 +1422,23 @@ pub enum TraitError {
     },
 }
 
+impl TraitError {
+    /// Get the primary span of the error
+    pub fn span(&self) -> Span {
+        match self {
+            TraitError::UnknownTrait { span, .. } => *span,
+            TraitError::OverlappingImpl { new_span, .. } => *new_span,
+            TraitError::MissingMethod { span, .. } => *span,
+            TraitError::MissingAssociatedType { span, .. } => *span,
+            TraitError::BoundNotSatisfied { span, .. } => *span,
+            TraitError::NegativeBoundViolation { span, .. } => *span,
+            TraitError::MethodNotFound { span, .. } => *span,
+            TraitError::WrongTypeArgCount { span, .. } => *span,
+            TraitError::CyclicTrait { span, .. } => *span,
+        }
+    }
+}
+
 impl fmt::Display for TraitError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -1369,6 +1568,8 @@ mod tests {
         assert!(solver.builtin_traits.clone.is_some());
         assert!(solver.builtin_traits.partial_eq.is_some());
         assert!(solver.builtin_traits.iterator.is_some());
+        assert!(solver.builtin_traits.display.is_some());
+        assert!(solver.builtin_traits.debug.is_some());
     }
 
     #[test]
@@ -1447,6 +1648,40 @@ mod tests {
         assert!(method.trait_id.is_none()); // Inherent method
     }
 
+    #[test]
+    fn test_default_method_resolution() {
+        let mut solver = TraitSolver::new();
+
+        // Trait with a provided (default) method
+        let trait_def = TraitDef::new("Greet", TraitId(0), Span::dummy()).with_method(
+            TraitMethod::new("namaste", make_simple_type("sūtra"), Span::dummy())
+                .with_receiver(MethodReceiver::Ref)
+                .with_default(Block {
+                    stmts: vec![],
+                    span: Span::dummy(),
+                }),
+        );
+        let trait_id = solver.register_trait(trait_def);
+
+        // Impl that does not override it
+        let impl_def = TraitImpl::new(
+            TraitRef::new(trait_id, "Greet"),
+            make_simple_type("Foo"),
+            ImplId(0),
+            Span::dummy(),
+        );
+        solver.register_impl(impl_def).unwrap();
+
+        let method = solver
+            .resolve_method(&make_simple_type("Foo"), "namaste")
+            .unwrap();
+        assert_eq!(method.trait_id, Some(trait_id));
+        assert!(method.is_default);
+        assert!(solver
+            .resolve_method(&make_simple_type("Bar"), "namaste")
+            .is_none());
+    }
+
     #[test]
     fn test_missing_method_error() {
         let mut solver = TraitSolver::new();
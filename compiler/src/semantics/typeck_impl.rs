@@ -9,9 +9,16 @@
 //! The inference algorithm is based on Hindley-Milner Algorithm W with
 //! adaptations for the Nyāya philosophical framework.
 
-use crate::lexer::Span;
+use super::borrow::OwnershipKind;
+use super::closure::{analyze_captures, closure_name, CaptureMode, ClosureKind};
+use super::exhaustiveness::MatchChecker;
+use super::sutra::{Crossing, SutraTable};
+use super::traits::{self, TraitSolver};
+use crate::lexer::{Affix, Span};
 use crate::parser::ast::*;
-use std::collections::HashMap;
+use crate::parser::format::FormatKind;
+use crate::visitor::{walk_expr, AstVisitor, VisitResult};
+use std::collections::{HashMap, HashSet};
 
 // ============================================================================
 // Type Variable System (for Unification)
@@ -41,10 +48,59 @@ pub struct TypeChecker {
     function_sigs: HashMap<String, FunctionSig>,
     /// Method signatures per type (type_name -> method_name -> signature)
     method_sigs: HashMap<String, HashMap<String, MethodSig>>,
+    /// Trait solver for guṇa/kriyānvaya method resolution
+    trait_solver: TraitSolver,
+    /// Type that `Self`/`sva` refers to inside an impl block
+    self_type: Option<ResolvedType>,
+    /// Associated types (`Self::Item`) bound by the current impl block
+    assoc_types: HashMap<String, ResolvedType>,
+    /// Trait bounds on generic parameters in scope (T -> [guṇa])
+    generic_bounds: HashMap<String, Vec<traits::TraitId>>,
+    /// Element types of the sūci made in the function being checked,
+    /// looked at once inference has settled them
+    suci_elements: Vec<(ResolvedType, Span)>,
+    /// Calls that may need an instance: of generic functions, and of
+    /// methods found through impls
+    instantiations: Vec<CallRecord>,
+    /// Type parameters and self type of each generic impl
+    impl_params: HashMap<traits::ImplId, (Vec<String>, ResolvedType)>,
+    /// Closure types by name (pāśa)
+    closures: HashMap<String, ClosureSig>,
+    /// Ownership of annotated locals of the current function, for captures
+    local_ownership: HashMap<String, OwnershipKind>,
+    /// Declared types of annotated locals of the current function, whose
+    /// affixes decide what may cross into a `saha` task
+    local_types: HashMap<String, Type>,
+    /// Thread safety of the types in scope (sūtra)
+    sutra: SutraTable,
+    /// Function whose body is being checked (names its closures)
+    current_function: String,
+    /// Fields and methods private to another module: (type, member)
+    hidden_members: HashSet<(String, String)>,
+    /// Types and traits of other modules named by a path or an alias, by
+    /// the name they are defined under
+    type_paths: HashMap<String, String>,
+    /// Values of constants in scope, to find constants defined via themselves
+    constant_values: HashMap<String, Expr>,
+    /// Loops enclosing the code being checked, innermost last
+    loops: Vec<LoopFrame>,
+    /// Declared return type of the function being checked and the span of
+    /// its signature, for `?`; none inside a closure
+    returns: Option<(ResolvedType, Span)>,
     /// Errors collected during type checking
     errors: Vec<TypeError>,
 }
 
+/// A loop `break` and `continue` can refer to
+#[derive(Debug, Clone)]
+struct LoopFrame {
+    label: Option<String>,
+    /// Only `cala { }` loops can break with a value
+    infinite: bool,
+    /// Type of the values broken with so far
+    break_ty: Option<ResolvedType>,
+}
+
 /// Type information for a symbol
 #[derive(Debug, Clone)]
 pub struct TypeInfo {
@@ -62,11 +118,14 @@ pub struct TypeDefInfo {
     pub body: TypeBodyResolved,
 }
 
+/// Payload fields of an enum variant (tuple payloads are named `0`, `1`, ...)
+pub type VariantPayload = Option<Vec<(String, ResolvedType)>>;
+
 /// Resolved type body
 #[derive(Debug, Clone)]
 pub enum TypeBodyResolved {
     Struct(Vec<(String, ResolvedType)>),
-    Enum(Vec<(String, Option<Vec<ResolvedType>>)>),
+    Enum(Vec<(String, VariantPayload)>),
     Alias(ResolvedType),
 }
 
@@ -77,6 +136,63 @@ pub struct FunctionSig {
     pub params: Vec<(String, ResolvedType)>,
     pub return_type: ResolvedType,
     pub span: Option<Span>,
+    /// Accepts any number of extra arguments after `params`
+    pub variadic: bool,
+    /// Type parameters with their bounds (where-clauses included)
+    pub generics: Vec<(String, Vec<TypeBound>)>,
+}
+
+/// Signature of a closure type
+///
+/// Each closure expression has its own type, named after where it is
+/// defined; it coerces to a `kāryakrama(..) -> R` type of the same shape.
+#[derive(Debug, Clone)]
+pub struct ClosureSig {
+    pub params: Vec<(String, ResolvedType)>,
+    pub return_type: ResolvedType,
+    /// Most general Fn-family trait the closure implements
+    pub kind: ClosureKind,
+}
+
+/// Concrete types of one call to a generic function or to a method
+#[derive(Debug, Clone)]
+pub struct Instantiation {
+    /// Called function, or the method's own name
+    pub function: String,
+    /// Receiver type of a method, without references (`Type::Inferred`
+    /// in place of what is unknown)
+    pub owner: Option<Type>,
+    /// Type arguments, in declaration order (`Type::Inferred` if unknown)
+    pub type_args: Vec<Type>,
+    /// The call expression
+    pub call: CallId,
+    /// Span of the call
+    pub span: Span,
+}
+
+/// Identity of a call expression: its address
+///
+/// Only meaningful while the checked item stays where it is; a caller
+/// rewriting calls must rewrite the very item it had checked, neither
+/// moved nor dropped in between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CallId(usize);
+
+impl CallId {
+    /// Identity of `expr`
+    pub fn of(expr: &Expr) -> Self {
+        Self(expr as *const Expr as usize)
+    }
+}
+
+/// A call recorded for monomorphization, its types not yet final
+#[derive(Debug, Clone)]
+struct CallRecord {
+    function: String,
+    owner: Option<ResolvedType>,
+    type_args: Vec<ResolvedType>,
+    call: CallId,
+    span: Span,
 }
 
 /// Method signature for inference (Vidhayaḥ Pariccheda)
@@ -152,6 +268,9 @@ pub enum ResolvedType {
     /// Tuple type - Yugma Prakāra
     Tuple(Vec<ResolvedType>),
 
+    /// Value given for a const parameter - Sthira
+    Const(i64),
+
     /// Never type (for diverging) - Kadāpi Na
     Never,
 
@@ -506,6 +625,23 @@ impl TypeChecker {
             type_defs: HashMap::new(),
             function_sigs: HashMap::new(),
             method_sigs: HashMap::new(),
+            trait_solver: TraitSolver::new(),
+            self_type: None,
+            assoc_types: HashMap::new(),
+            generic_bounds: HashMap::new(),
+            suci_elements: Vec::new(),
+            instantiations: Vec::new(),
+            impl_params: HashMap::new(),
+            closures: HashMap::new(),
+            local_ownership: HashMap::new(),
+            local_types: HashMap::new(),
+            sutra: SutraTable::new(),
+            current_function: String::new(),
+            hidden_members: HashSet::new(),
+            type_paths: HashMap::new(),
+            constant_values: HashMap::new(),
+            loops: Vec::new(),
+            returns: None,
             errors: Vec::new(),
         };
         // Register builtin types and functions
@@ -515,6 +651,11 @@ impl TypeChecker {
 
     /// Register builtin types and functions
     fn register_builtins(&mut self) {
+        // Prelude types (`Vikalpa`)
+        for typedef in super::prelude::types() {
+            self.collect_type_def(&typedef);
+        }
+
         // Register primitive type constructors
         // print function
         self.function_sigs.insert(
@@ -524,6 +665,8 @@ impl TypeChecker {
                 params: vec![("value".to_string(), ResolvedType::String)],
                 return_type: ResolvedType::Unit,
                 span: None,
+                variadic: false,
+                generics: Vec::new(),
             },
         );
 
@@ -535,6 +678,21 @@ impl TypeChecker {
                 params: vec![("code".to_string(), ResolvedType::Int32)],
                 return_type: ResolvedType::Never,
                 span: None,
+                variadic: false,
+                generics: Vec::new(),
+            },
+        );
+
+        // panic function (target of failed `dṛḍha!`/`assert_eq!`)
+        self.function_sigs.insert(
+            "vikṣepa".to_string(),
+            FunctionSig {
+                name: "vikṣepa".to_string(),
+                params: vec![("message".to_string(), ResolvedType::String)],
+                return_type: ResolvedType::Never,
+                span: None,
+                variadic: false,
+                generics: Vec::new(),
             },
         );
     }
@@ -545,6 +703,60 @@ impl TypeChecker {
 
     /// Check types for an entire AST (Sampūrṇa Parīkṣā)
     pub fn check(&mut self, ast: &Ast) -> Result<(), Vec<TypeError>> {
+        self.collect(ast);
+
+        // Phase 3: Type check all items
+        for item in &ast.items {
+            self.check_item(item);
+        }
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    /// Check one item against the declarations gathered by `collect`
+    ///
+    /// Used by incremental compilation, which re-checks only changed items.
+    pub fn check_single(&mut self, item: &Item) -> Vec<TypeError> {
+        self.check_item(item);
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Calls to generic functions, and to methods found through impls,
+    /// checked since the last call
+    ///
+    /// Used by monomorphization to instantiate each generic function, type
+    /// and method once per set of concrete type arguments.
+    pub fn take_instantiations(&mut self) -> Vec<Instantiation> {
+        std::mem::take(&mut self.instantiations)
+            .into_iter()
+            .map(|record| Instantiation {
+                function: record.function,
+                owner: record
+                    .owner
+                    .map(|t| self.to_ast_type(&strip_references(self.inference.apply(&t)), &[])),
+                type_args: record
+                    .type_args
+                    .iter()
+                    .map(|t| self.to_ast_type(t, &[]))
+                    .collect(),
+                call: record.call,
+                span: record.span,
+            })
+            .collect()
+    }
+
+    /// `ty` as the checker writes it, aliases resolved (`saṅkhyā` is `i32`)
+    pub fn canonical_type(&mut self, ty: &Type) -> Type {
+        let resolved = self.resolve_ast_type(ty);
+        self.to_ast_type(&resolved, &[])
+    }
+
+    /// Register every declaration of the AST without checking bodies
+    pub fn collect(&mut self, ast: &Ast) {
         // Phase 1: Collect all type definitions
         for item in &ast.items {
             if let Item::TypeDef(typedef) = item {
@@ -553,21 +765,94 @@ impl TypeChecker {
         }
 
         // Phase 2: Collect all function signatures (for śabda inference)
+        // and the declared types of constants
         for item in &ast.items {
-            if let Item::Function(func) = item {
-                self.collect_function_sig(func);
+            match item {
+                Item::Function(func) => self.collect_function_sig(func),
+                Item::Constant(constant) => self.collect_constant(constant),
+                _ => {}
             }
         }
 
-        // Phase 3: Type check all items
+        // Phase 2b: Register traits, then impls, with the trait solver
         for item in &ast.items {
-            self.check_item(item);
+            if let Item::Trait(trait_def) = item {
+                self.collect_trait_def(trait_def);
+            }
+        }
+        for item in &ast.items {
+            if let Item::Impl(impl_def) = item {
+                self.collect_impl(impl_def);
+            }
         }
+    }
 
-        if self.errors.is_empty() {
-            Ok(())
-        } else {
-            Err(std::mem::take(&mut self.errors))
+    /// Make an item of another module visible under `name` before `check`
+    ///
+    /// Used for cross-module checking: functions are registered under the
+    /// name they are imported as, types and traits by their own name.
+    pub fn import_item(&mut self, name: &str, item: &Item) {
+        match item {
+            Item::Function(func) => {
+                let mut func = func.clone();
+                func.name.name = name.to_string();
+                self.collect_function_sig(&func);
+            }
+            Item::TypeDef(typedef) if typedef.name.name != name => {
+                self.type_paths
+                    .insert(name.to_string(), typedef.name.name.clone());
+            }
+            Item::Trait(trait_def) if trait_def.name.name != name => {
+                self.type_paths
+                    .insert(name.to_string(), trait_def.name.name.clone());
+            }
+            Item::TypeDef(typedef) => self.collect_type_def(typedef),
+            Item::Trait(trait_def) => self.collect_trait_def(trait_def),
+            Item::Impl(impl_def) => self.collect_impl(impl_def),
+            Item::Constant(constant) => {
+                let mut constant = constant.clone();
+                constant.name.name = name.to_string();
+                self.collect_constant(&constant);
+            }
+            Item::Import(_) | Item::Module(_) | Item::Error(_) => {}
+        }
+    }
+
+    /// Hide a field or method of `owner` that is private to another module
+    pub fn hide_member(&mut self, owner: &str, member: &str) {
+        self.hidden_members
+            .insert((owner.to_string(), member.to_string()));
+    }
+
+    /// Report use of a member hidden by `hide_member`
+    fn check_member_access(&mut self, owner: &str, member: &str, span: Span) {
+        if self
+            .hidden_members
+            .contains(&(owner.to_string(), member.to_string()))
+        {
+            self.errors.push(TypeError::PrivateMember {
+                owner: owner.to_string(),
+                member: member.to_string(),
+                span: Some(span),
+            });
+        }
+    }
+
+    /// Register a constant under its declared type, so items before it can use it
+    fn collect_constant(&mut self, constant: &ConstantDef) {
+        self.constant_values
+            .insert(constant.name.name.clone(), constant.value.clone());
+        if let Some(ty) = &constant.ty {
+            let ty = self.resolve_ast_type(ty);
+            self.add_symbol(
+                constant.name.name.clone(),
+                TypeInfo {
+                    ty,
+                    certainty: Pramana::Pratyaksha.certainty(),
+                    pramana: Pramana::Pratyaksha,
+                    span: Some(constant.span),
+                },
+            );
         }
     }
 
@@ -589,13 +874,14 @@ impl TypeChecker {
                 TypeBodyResolved::Struct(resolved_fields)
             }
             TypeBody::Enum(variants) => {
-                let resolved_variants: Vec<(String, Option<Vec<ResolvedType>>)> = variants
+                let resolved_variants: Vec<(String, VariantPayload)> = variants
                     .iter()
                     .map(|v| {
-                        let fields = v
-                            .fields
-                            .as_ref()
-                            .map(|fs| fs.iter().map(|f| self.resolve_ast_type(&f.ty)).collect());
+                        let fields = v.fields.as_ref().map(|fs| {
+                            fs.iter()
+                                .map(|f| (f.name.name.clone(), self.resolve_ast_type(&f.ty)))
+                                .collect()
+                        });
                         (v.name.name.clone(), fields)
                     })
                     .collect();
@@ -612,6 +898,7 @@ impl TypeChecker {
                 body,
             },
         );
+        self.sutra.register(typedef);
     }
 
     /// Collect function signature
@@ -627,6 +914,18 @@ impl TypeChecker {
             .as_ref()
             .map(|t| self.resolve_ast_type(t))
             .unwrap_or(ResolvedType::Unit);
+        let mut generics: Vec<(String, Vec<TypeBound>)> = func
+            .generics
+            .iter()
+            .map(|g| (g.name.name.clone(), g.bounds.clone()))
+            .collect();
+        for predicate in &func.where_clauses {
+            if let Type::Named { name, .. } = &predicate.ty {
+                if let Some((_, bounds)) = generics.iter_mut().find(|(g, _)| *g == name.name) {
+                    bounds.extend(predicate.bounds.iter().cloned());
+                }
+            }
+        }
 
         self.function_sigs.insert(
             name.clone(),
@@ -635,32 +934,488 @@ impl TypeChecker {
                 params,
                 return_type,
                 span: Some(func.span),
+                variadic: false,
+                generics,
             },
         );
     }
 
+    /// Register a trait definition with the solver (Guṇa Saṅgraha)
+    fn collect_trait_def(&mut self, trait_def: &TraitDef) {
+        let mut def = traits::TraitDef::new(
+            trait_def.name.name.clone(),
+            traits::TraitId(0),
+            trait_def.span,
+        );
+        def.generics = trait_def.generics.clone();
+        def.supertraits = self.lower_bounds(&trait_def.supertraits);
+        def.where_clauses = self.lower_where_clauses(&trait_def.where_clauses);
+
+        for assoc in &trait_def.associated_types {
+            let mut lowered = traits::AssociatedType::new(assoc.name.name.clone(), assoc.span);
+            lowered.bounds = self.lower_bounds(&assoc.bounds);
+            lowered.default = assoc.default.clone();
+            def.associated_types.push(lowered);
+        }
+
+        for method in &trait_def.methods {
+            let return_type = method.return_type.clone().unwrap_or_else(unit_type);
+            let mut lowered =
+                traits::TraitMethod::new(method.name.name.clone(), return_type, method.span)
+                    .with_receiver(lower_receiver(method.receiver));
+            lowered.generics = method.generics.clone();
+            lowered.params = lower_params(&method.params);
+            lowered.where_clauses = self.lower_where_clauses(&method.where_clauses);
+            lowered.default_impl = method.default_body.clone();
+            def.methods.push(lowered);
+        }
+
+        self.trait_solver.register_trait(def);
+    }
+
+    /// Register an impl block with the solver (Kriyānvaya Saṅgraha)
+    fn collect_impl(&mut self, impl_def: &ImplDef) {
+        // Impl generics match any concrete type argument
+        let generic_names: Vec<&str> = impl_def
+            .generics
+            .iter()
+            .map(|g| g.name.name.as_str())
+            .collect();
+        let self_ty = self.resolve_ast_type(&impl_def.self_ty);
+        let implementing_type = self.to_ast_type(&self_ty, &generic_names);
+
+        let mut imp = match &impl_def.trait_ref {
+            Some(bound) => {
+                let Some(lowered) = self.lower_bounds(std::slice::from_ref(bound)).pop() else {
+                    return;
+                };
+                traits::TraitImpl::new(
+                    lowered.trait_ref,
+                    implementing_type,
+                    traits::ImplId(0),
+                    impl_def.span,
+                )
+            }
+            None => {
+                traits::TraitImpl::inherent(implementing_type, traits::ImplId(0), impl_def.span)
+            }
+        };
+        imp.generics = impl_def.generics.clone();
+        imp.where_clauses = self.lower_where_clauses(&impl_def.where_clauses);
+        imp.associated_types = impl_def
+            .associated_types
+            .iter()
+            .map(|a| traits::ImplAssociatedType {
+                name: a.name.name.clone(),
+                ty: a.ty.clone(),
+                span: a.span,
+            })
+            .collect();
+        imp.methods = impl_def
+            .methods
+            .iter()
+            .map(|m| traits::ImplMethod {
+                name: m.name.name.clone(),
+                generics: m.generics.clone(),
+                receiver: lower_receiver(m.receiver),
+                params: lower_params(&m.params),
+                return_type: m.return_type.clone().unwrap_or_else(unit_type),
+                body: m.body.clone(),
+                is_async: false,
+                is_unsafe: false,
+                span: m.span,
+            })
+            .collect();
+
+        match self.trait_solver.register_impl(imp) {
+            Ok(id) if !generic_names.is_empty() => {
+                let names = generic_names.iter().map(|n| n.to_string()).collect();
+                self.impl_params.insert(id, (names, self_ty));
+            }
+            Ok(_) => {}
+            Err(error) => self.errors.push(TypeError::Trait { error }),
+        }
+    }
+
+    /// Lower AST trait bounds to solver bounds, reporting unknown traits
+    fn lower_bounds(&mut self, bounds: &[TypeBound]) -> Vec<traits::TraitBound> {
+        let mut lowered = Vec::new();
+        for bound in bounds {
+            let name = &bound.trait_name.name;
+            let name = self.type_paths.get(name).unwrap_or(name);
+            match self.trait_solver.find_trait(name) {
+                Some(trait_def) => {
+                    let mut trait_ref = traits::TraitRef::new(trait_def.id, trait_def.name.clone());
+                    trait_ref.type_args = bound.generics.clone();
+                    lowered.push(traits::TraitBound::new(trait_ref, bound.span));
+                }
+                None => self.errors.push(TypeError::Trait {
+                    error: traits::TraitError::UnknownTrait {
+                        name: bound.trait_name.name.clone(),
+                        span: bound.span,
+                    },
+                }),
+            }
+        }
+        lowered
+    }
+
+    /// Check the type arguments of a generic call against their bounds
+    ///
+    /// Unsatisfied bounds are reported at the call site. Arguments that are
+    /// themselves generic parameters satisfy a bound through their own bounds.
+    fn check_type_arg_bounds(
+        &mut self,
+        generics: &[(String, Vec<TypeBound>)],
+        type_args: &[ResolvedType],
+        span: Span,
+    ) {
+        for ((_, bounds), arg) in generics.iter().zip(type_args) {
+            let arg = self.inference.apply(arg);
+            if matches!(
+                arg,
+                ResolvedType::TypeVar(_) | ResolvedType::Unknown | ResolvedType::Error
+            ) {
+                continue;
+            }
+            for bound in bounds {
+                // Unknown traits are reported at the declaration
+                let Some(trait_id) = self
+                    .trait_solver
+                    .find_trait(&bound.trait_name.name)
+                    .map(|t| t.id)
+                else {
+                    continue;
+                };
+                if !self.satisfies(&arg, trait_id) {
+                    self.errors.push(TypeError::Trait {
+                        error: traits::TraitError::BoundNotSatisfied {
+                            type_name: self.get_type_name(&arg),
+                            trait_name: bound.trait_name.name.clone(),
+                            span,
+                        },
+                    });
+                }
+            }
+        }
+    }
+
+    /// Whether `ty` implements a trait, through an impl or, for a type
+    /// parameter, through its bounds
+    fn satisfies(&self, ty: &ResolvedType, trait_id: traits::TraitId) -> bool {
+        if let ResolvedType::Named { name, generics } = ty {
+            if let (true, Some(ids)) = (generics.is_empty(), self.generic_bounds.get(name)) {
+                return ids.iter().any(|&id| self.trait_implies(id, trait_id));
+            }
+        }
+        self.trait_solver
+            .implements(&self.to_ast_type(ty, &[]), trait_id)
+    }
+
+    /// Whether a value of type `ty` can be rendered as `kind` asks
+    ///
+    /// Primitives format without impls: all of them have Display and Debug
+    /// (`()` only Debug), the radix forms want integers and `{:e}` floats.
+    /// Other types need a Display or Debug impl.
+    fn is_formattable(&self, ty: &ResolvedType, kind: FormatKind) -> bool {
+        let textual = matches!(kind, FormatKind::Display | FormatKind::Debug);
+        match ty {
+            ResolvedType::Reference { inner, .. } => self.is_formattable(inner, kind),
+            ResolvedType::TypeVar(_)
+            | ResolvedType::Never
+            | ResolvedType::Unknown
+            | ResolvedType::Error => true,
+            ty if is_integer_type(ty) => kind != FormatKind::LowerExp,
+            ResolvedType::Float32 | ResolvedType::Float64 => {
+                textual || kind == FormatKind::LowerExp
+            }
+            ResolvedType::Bool | ResolvedType::Char | ResolvedType::String => textual,
+            ResolvedType::Unit => kind == FormatKind::Debug,
+            ResolvedType::Named { .. } if textual => self
+                .trait_solver
+                .find_trait(kind.trait_name())
+                .is_some_and(|t| self.satisfies(ty, t.id)),
+            _ => false,
+        }
+    }
+
+    /// Type of `operand?`: the payload of the operand's `Saphala` or `Kiñcit`
+    ///
+    /// The function around it must return the same enum. The error of a
+    /// `Phala` must be the function's error type, or convert into it by a
+    /// `From` impl of that type.
+    fn check_try(&mut self, operand: &ResolvedType, span: Span) -> ResolvedType {
+        let operand = self.inference.apply(operand);
+        let (payload, error) = match &operand {
+            ResolvedType::Named { name, generics } if name == "Phala" && generics.len() == 2 => {
+                (generics[0].clone(), Some(generics[1].clone()))
+            }
+            ResolvedType::Named { name, generics } if name == "Vikalpa" && generics.len() == 1 => {
+                (generics[0].clone(), None)
+            }
+            ResolvedType::TypeVar(_) | ResolvedType::Unknown | ResolvedType::Error => {
+                return self.inference.fresh_type_var();
+            }
+            _ => {
+                self.errors.push(TypeError::NotTryable {
+                    ty: operand,
+                    span: Some(span),
+                });
+                return ResolvedType::Error;
+            }
+        };
+
+        let Some((returns, signature)) = self.returns.clone() else {
+            self.errors
+                .push(TypeError::TryInClosure { span: Some(span) });
+            return payload;
+        };
+        let returns = self.inference.apply(&returns);
+        let ResolvedType::Named { name, generics } = &returns else {
+            self.errors.push(TypeError::TryOutsidePhala {
+                function: self.current_function.clone(),
+                returns,
+                span: Some(signature),
+            });
+            return payload;
+        };
+        match (name.as_str(), error) {
+            ("Vikalpa", None) => {}
+            ("Phala", Some(error)) if generics.len() == 2 => {
+                let target = self.inference.apply(&generics[1]);
+                if error != target
+                    && self.inference.unify(&error, &target).is_err()
+                    && !self.converts_from(&target, &error)
+                {
+                    self.errors.push(TypeError::MissingConversion {
+                        from: error,
+                        to: target,
+                        span: Some(span),
+                    });
+                }
+            }
+            ("Vikalpa" | "Phala", _) => self.errors.push(TypeError::Mismatch {
+                expected: returns.clone(),
+                found: operand,
+                span: Some(span),
+                context: format!("`?` in function '{}'", self.current_function),
+            }),
+            _ => self.errors.push(TypeError::TryOutsidePhala {
+                function: self.current_function.clone(),
+                returns,
+                span: Some(signature),
+            }),
+        }
+        payload
+    }
+
+    /// Whether `target` has a `From<source>` impl
+    fn converts_from(&self, target: &ResolvedType, source: &ResolvedType) -> bool {
+        let Some(from) = self.trait_solver.find_trait("From") else {
+            return false;
+        };
+        self.trait_solver
+            .find_impl_with_args(
+                &self.to_ast_type(target, &[]),
+                from.id,
+                &[self.to_ast_type(source, &[])],
+            )
+            .is_some()
+    }
+
+    /// Whether a bound on `have` also provides `want` (directly or as a supertrait)
+    fn trait_implies(&self, have: traits::TraitId, want: traits::TraitId) -> bool {
+        have == want
+            || self.trait_solver.get_trait(have).is_some_and(|t| {
+                t.supertraits
+                    .iter()
+                    .any(|s| self.trait_implies(s.trait_ref.trait_id, want))
+            })
+    }
+
+    /// Lower AST where-clauses to solver where-clauses
+    fn lower_where_clauses(&mut self, predicates: &[WherePredicate]) -> Vec<traits::WhereClause> {
+        predicates
+            .iter()
+            .map(|p| traits::WhereClause {
+                ty: p.ty.clone(),
+                bounds: self.lower_bounds(&p.bounds),
+                span: p.span,
+            })
+            .collect()
+    }
+
+    /// Bring the bounds of generic parameters and where-clauses into scope
+    ///
+    /// Returns the previous bounds so the caller can restore them.
+    fn enter_generic_bounds(
+        &mut self,
+        generics: &[GenericParam],
+        where_clauses: &[WherePredicate],
+    ) -> HashMap<String, Vec<traits::TraitId>> {
+        let saved = self.generic_bounds.clone();
+        let mut predicates: Vec<(String, &[TypeBound])> = generics
+            .iter()
+            .map(|g| (g.name.name.clone(), g.bounds.as_slice()))
+            .collect();
+        for predicate in where_clauses {
+            if let Type::Named { name, .. } = &predicate.ty {
+                predicates.push((name.name.clone(), predicate.bounds.as_slice()));
+            }
+        }
+        for (param, bounds) in predicates {
+            let ids: Vec<traits::TraitId> = bounds
+                .iter()
+                .filter_map(|b| {
+                    self.trait_solver
+                        .find_trait(&b.trait_name.name)
+                        .map(|t| t.id)
+                })
+                .collect();
+            self.generic_bounds.entry(param).or_default().extend(ids);
+        }
+        saved
+    }
+
     /// Check a single item
     fn check_item(&mut self, item: &Item) {
         match item {
             Item::Function(func) => self.check_function(func),
             Item::TypeDef(_) => { /* Already collected */ }
             Item::Constant(constant) => self.check_constant(constant),
-            Item::Import(_) => { /* No type checking needed */ }
+            Item::Import(_) | Item::Error(_) => { /* No type checking needed */ }
             Item::Module(module) => {
                 for sub_item in &module.items {
                     self.check_item(sub_item);
                 }
             }
+            Item::Trait(trait_def) => self.check_trait(trait_def),
+            Item::Impl(impl_def) => self.check_impl(impl_def),
+        }
+    }
+
+    /// Check the default method bodies of a trait
+    fn check_trait(&mut self, trait_def: &TraitDef) {
+        let Some(trait_id) = self
+            .trait_solver
+            .find_trait(&trait_def.name.name)
+            .map(|t| t.id)
+        else {
+            return;
+        };
+
+        // Inside the trait, Self is only known through the trait itself
+        let saved_bounds = self.enter_generic_bounds(&trait_def.generics, &trait_def.where_clauses);
+        self.generic_bounds
+            .entry("Self".to_string())
+            .or_default()
+            .push(trait_id);
+
+        for method in &trait_def.methods {
+            if let Some(body) = &method.default_body {
+                self.check_function(&FunctionDef {
+                    name: method.name.clone(),
+                    generics: method.generics.clone(),
+                    receiver: method.receiver,
+                    params: method.params.clone(),
+                    return_type: method.return_type.clone(),
+                    where_clauses: method.where_clauses.clone(),
+                    preconditions: Vec::new(),
+                    postconditions: Vec::new(),
+                    body: body.clone(),
+                    is_const: false,
+                    visibility: trait_def.visibility,
+                    attributes: method.attributes.clone(),
+                    span: method.span,
+                });
+            }
+        }
+
+        self.generic_bounds = saved_bounds;
+    }
+
+    /// Check the method bodies of an impl block with `Self` bound
+    fn check_impl(&mut self, impl_def: &ImplDef) {
+        let saved_bounds = self.enter_generic_bounds(&impl_def.generics, &impl_def.where_clauses);
+        self.enter_scope();
+        self.declare_const_params(&impl_def.generics);
+        let self_ty = self.resolve_ast_type(&impl_def.self_ty);
+        let mut assoc_types = HashMap::new();
+        for assoc in &impl_def.associated_types {
+            let ty = self.resolve_ast_type(&assoc.ty);
+            assoc_types.insert(assoc.name.name.clone(), ty);
+        }
+        let saved_self = self.self_type.replace(self_ty);
+        let saved_assoc = std::mem::replace(&mut self.assoc_types, assoc_types);
+
+        for method in &impl_def.methods {
+            self.check_function(method);
+        }
+
+        self.self_type = saved_self;
+        self.assoc_types = saved_assoc;
+        self.exit_scope();
+        self.generic_bounds = saved_bounds;
+    }
+
+    /// Bring the const parameters among `generics` into scope as values of
+    /// their declared types
+    fn declare_const_params(&mut self, generics: &[GenericParam]) {
+        for param in generics {
+            let Some(ty) = &param.ty else {
+                continue;
+            };
+            let ty = self.resolve_ast_type(ty);
+            self.add_symbol(
+                param.name.name.clone(),
+                TypeInfo {
+                    ty,
+                    certainty: Pramana::Pratyaksha.certainty(),
+                    pramana: Pramana::Pratyaksha,
+                    span: Some(param.span),
+                },
+            );
         }
     }
 
     /// Check a function definition
     fn check_function(&mut self, func: &FunctionDef) {
+        let saved_bounds = self.enter_generic_bounds(&func.generics, &func.where_clauses);
+        let saved_function = std::mem::replace(&mut self.current_function, func.name.name.clone());
+        self.local_ownership.clear();
+        self.local_types.clear();
         self.enter_scope();
+        self.declare_const_params(&func.generics);
+
+        // Bind the receiver (sva) for methods
+        if let Some(receiver) = func.receiver {
+            let self_ty = self.resolve_ast_type(&self_named_type());
+            let ty = match receiver {
+                SelfParam::Value => self_ty,
+                SelfParam::Ref | SelfParam::RefMut => ResolvedType::Reference {
+                    inner: Box::new(self_ty),
+                    mutable: receiver == SelfParam::RefMut,
+                    lifetime: None,
+                },
+            };
+            self.add_symbol(
+                "sva".to_string(),
+                TypeInfo {
+                    ty,
+                    certainty: Pramana::Pratyaksha.certainty(),
+                    pramana: Pramana::Pratyaksha,
+                    span: Some(func.span),
+                },
+            );
+        }
 
         // Add parameters to scope with explicit types (Pratyakṣa)
         for param in &func.params {
             let ty = self.resolve_ast_type(&param.ty);
+            self.local_ownership
+                .insert(param.name.name.clone(), OwnershipKind::of_type(&param.ty));
+            self.local_types
+                .insert(param.name.name.clone(), param.ty.clone());
             self.add_symbol(
                 param.name.name.clone(),
                 TypeInfo {
@@ -672,6 +1427,10 @@ impl TypeChecker {
             );
         }
 
+        for contract in &func.preconditions {
+            self.check_contract(contract, "precondition");
+        }
+
         // Expected return type
         let expected_return = func
             .return_type
@@ -680,10 +1439,16 @@ impl TypeChecker {
             .unwrap_or(ResolvedType::Unit);
 
         // Check function body
+        let saved_returns = self.returns.replace((expected_return.clone(), func.span));
         let body_type = self.check_block(&func.body);
+        self.returns = saved_returns;
 
         // Verify return type matches
-        if let Err(_e) = self.inference.unify(&body_type, &expected_return) {
+        let checked = match func.body.tail() {
+            Some(Stmt::Expr(tail)) => self.coerce_value(tail, &body_type, &expected_return),
+            _ => self.coerce(&body_type, &expected_return),
+        };
+        if let Err(_e) = checked {
             self.errors.push(TypeError::Mismatch {
                 expected: self.inference.apply(&expected_return),
                 found: self.inference.apply(&body_type),
@@ -692,11 +1457,97 @@ impl TypeChecker {
             });
         }
 
+        // Postconditions see the parameters and the result, as `phala`
+        if !func.postconditions.is_empty() {
+            self.enter_scope();
+            self.add_symbol(
+                "phala".to_string(),
+                TypeInfo {
+                    ty: expected_return,
+                    certainty: Pramana::Pratyaksha.certainty(),
+                    pramana: Pramana::Pratyaksha,
+                    span: Some(func.span),
+                },
+            );
+            for contract in &func.postconditions {
+                self.check_contract(contract, "postcondition");
+            }
+            self.exit_scope();
+        }
+
+        self.check_suci_elements();
         self.exit_scope();
+        self.current_function = saved_function;
+        self.generic_bounds = saved_bounds;
+    }
+
+    /// Reject sūci of values wider than a word, as the runtime moves
+    /// elements a word at a time
+    fn check_suci_elements(&mut self) {
+        for (element, span) in std::mem::take(&mut self.suci_elements) {
+            let element = self.inference.apply(&element);
+            let words = self.words(&element, &mut Vec::new());
+            if words > 1 {
+                self.errors.push(TypeError::WideElement {
+                    ty: element,
+                    words,
+                    span: Some(span),
+                });
+            }
+        }
+    }
+
+    /// Words a value of `ty` takes in compiled code: a field or tuple
+    /// element a slot each, an enum its tag and its widest payload
+    fn words(&self, ty: &ResolvedType, outer: &mut Vec<String>) -> usize {
+        match ty {
+            ResolvedType::Tuple(elements) => elements.iter().map(|e| self.words(e, outer)).sum(),
+            ResolvedType::Named { name, generics } if !outer.contains(name) => {
+                let Some(info) = self.type_defs.get(name) else {
+                    return 1;
+                };
+                let subst: HashMap<&str, &ResolvedType> = info
+                    .generics
+                    .iter()
+                    .map(String::as_str)
+                    .zip(generics)
+                    .collect();
+                let mut words = |fields: &[(String, ResolvedType)], outer: &mut Vec<String>| {
+                    fields
+                        .iter()
+                        .map(|(_, ty)| self.words(&substitute_type_params(ty, &subst), outer))
+                        .sum::<usize>()
+                };
+                outer.push(name.clone());
+                let total = match &info.body {
+                    TypeBodyResolved::Struct(fields) => words(fields, outer).max(1),
+                    TypeBodyResolved::Enum(variants) => {
+                        let payload = variants
+                            .iter()
+                            .filter_map(|(_, payload)| payload.as_deref())
+                            .map(|fields| words(fields, outer))
+                            .max()
+                            .unwrap_or(0);
+                        1 + payload
+                    }
+                    TypeBodyResolved::Alias(ty) => self.words(ty, outer),
+                };
+                outer.pop();
+                total
+            }
+            _ => 1,
+        }
     }
 
     /// Check a constant definition
     fn check_constant(&mut self, constant: &ConstantDef) {
+        if self.constant_is_cyclic(&constant.name.name) {
+            self.errors.push(TypeError::CyclicConstant {
+                name: constant.name.name.clone(),
+                span: Some(constant.span),
+            });
+            return;
+        }
         let value_type = self.infer_expr(&constant.value);
 
         if let Some(declared_ty) = &constant.ty {
@@ -709,9 +1560,31 @@ impl TypeChecker {
                     context: format!("constant '{}'", constant.name.name),
                 });
             }
+        } else {
+            self.add_symbol(constant.name.name.clone(), value_type);
         }
+    }
 
-        self.add_symbol(constant.name.name.clone(), value_type);
+    /// Whether constant `name` refers to itself through other constants
+    fn constant_is_cyclic(&self, name: &str) -> bool {
+        let mut pending = vec![name.to_string()];
+        let mut seen = HashSet::new();
+        while let Some(current) = pending.pop() {
+            let Some(value) = self.constant_values.get(&current) else {
+                continue;
+            };
+            let mut names = ConstantRefs(Vec::new());
+            let _ = names.visit_expr(value);
+            for used in names.0 {
+                if used == name {
+                    return true;
+                }
+                if self.constant_values.contains_key(&used) && seen.insert(used.clone()) {
+                    pending.push(used);
+                }
+            }
+        }
+        false
     }
 
     /// Check a block and return its type
@@ -733,13 +1606,18 @@ impl TypeChecker {
                 ty,
                 value,
                 span,
+                ..
             } => {
                 let (resolved_ty, pramana) = if let Some(explicit_ty) = ty {
                     // Pratyakṣa: Explicit type annotation
                     let ty = self.resolve_ast_type(explicit_ty);
+                    self.local_ownership
+                        .insert(name.name.clone(), OwnershipKind::of_type(explicit_ty));
+                    self.local_types
+                        .insert(name.name.clone(), explicit_ty.clone());
                     if let Some(val) = value {
                         let val_info = self.infer_expr(val);
-                        if let Err(_) = self.inference.unify(&val_info.ty, &ty) {
+                        if self.coerce_value(val, &val_info.ty, &ty).is_err() {
                             self.errors.push(TypeError::Mismatch {
                                 expected: ty.clone(),
                                 found: val_info.ty,
@@ -757,6 +1635,10 @@ impl TypeChecker {
                     // Unknown type - create fresh type variable
                     (self.inference.fresh_type_var(), Pramana::Anumana)
                 };
+                if ty.is_none() {
+                    self.local_ownership.remove(&name.name);
+                    self.local_types.remove(&name.name);
+                }
 
                 self.add_symbol(
                     name.name.clone(),
@@ -774,7 +1656,18 @@ impl TypeChecker {
 
             Stmt::Return { value, span: _span } => {
                 if let Some(val) = value {
-                    self.infer_expr(val).ty
+                    let found = self.infer_expr(val).ty;
+                    // A number converts to the result type where it is returned
+                    match self.returns.clone() {
+                        Some((expected, _))
+                            if self.is_numeric(&found)
+                                && self.is_numeric(&expected)
+                                && self.coerce_value(val, &found, &expected).is_ok() =>
+                        {
+                            expected
+                        }
+                        _ => found,
+                    }
                 } else {
                     ResolvedType::Unit
                 }
@@ -809,278 +1702,745 @@ impl TypeChecker {
                             span: Some(*span),
                         });
                     }
-                    then_type
+                    // A diverging branch (`break`, `continue`) takes the other's type
+                    if self.inference.apply(&then_type) == ResolvedType::Never {
+                        else_type
+                    } else {
+                        then_type
+                    }
                 } else {
                     ResolvedType::Unit
                 }
             }
 
-            Stmt::Match {
-                scrutinee,
-                arms,
-                span: _span,
-            } => {
-                let scrutinee_type = self.infer_expr(scrutinee);
-                let mut arm_type: Option<ResolvedType> = None;
+            Stmt::Loop {
+                label,
+                kind,
+                body,
+                span,
+            } => self.check_loop(label.as_ref(), kind, body, *span),
 
-                for arm in arms {
-                    // Check pattern matches scrutinee type
-                    self.check_pattern(&arm.pattern, &scrutinee_type.ty);
-
-                    // Check arm body
-                    let body_type = self.infer_expr(&arm.body);
-
-                    if let Some(ref expected) = arm_type {
-                        if let Err(_) = self.inference.unify(&body_type.ty, expected) {
-                            self.errors.push(TypeError::BranchMismatch {
-                                then_type: expected.clone(),
-                                else_type: body_type.ty,
-                                span: Some(arm.span),
-                            });
-                        }
-                    } else {
-                        arm_type = Some(body_type.ty);
+            Stmt::Break { label, value, span } => {
+                let Some(index) = self.resolve_loop(label.as_ref(), "break", *span) else {
+                    if let Some(value) = value {
+                        self.infer_expr(value);
                     }
-                }
-
-                arm_type.unwrap_or(ResolvedType::Unit)
-            }
-
-            Stmt::Loop { kind, body, span } => {
-                match kind {
-                    LoopKind::ForIn { binding, iterable } => {
-                        let iter_type = self.infer_expr(iterable);
-                        // Extract element type from iterable
-                        let elem_type = self.extract_element_type(&iter_type.ty);
-                        self.enter_scope();
-                        self.add_symbol(
-                            binding.name.clone(),
-                            TypeInfo {
-                                ty: elem_type,
-                                certainty: Pramana::Anumana.certainty(),
-                                pramana: Pramana::Anumana,
-                                span: Some(*span),
-                            },
-                        );
-                        self.check_block(body);
-                        self.exit_scope();
+                    return ResolvedType::Never;
+                };
+                let value_ty = match value {
+                    Some(value) => {
+                        let ty = self.infer_expr(value).ty;
+                        if !self.loops[index].infinite {
+                            self.errors
+                                .push(TypeError::BreakWithValue { span: Some(*span) });
+                            return ResolvedType::Never;
+                        }
+                        ty
                     }
-                    LoopKind::While { condition } => {
-                        let cond_type = self.infer_expr(condition);
-                        if let Err(_) = self.inference.unify(&cond_type.ty, &ResolvedType::Bool) {
+                    None => ResolvedType::Unit,
+                };
+                match self.loops[index].break_ty.clone() {
+                    Some(expected) => {
+                        if self.inference.unify(&expected, &value_ty).is_err() {
                             self.errors.push(TypeError::Mismatch {
-                                expected: ResolvedType::Bool,
-                                found: cond_type.ty,
+                                expected: self.inference.apply(&expected),
+                                found: self.inference.apply(&value_ty),
                                 span: Some(*span),
-                                context: "while condition".to_string(),
+                                context: "break value".to_string(),
                             });
                         }
-                        self.check_block(body);
-                    }
-                    LoopKind::Range {
-                        binding,
-                        start,
-                        end,
-                        ..
-                    } => {
-                        let start_type = self.infer_expr(start);
-                        let _end_type = self.infer_expr(end);
-                        // Start and end should be integers
-                        if let Err(_) = self.inference.unify(&start_type.ty, &ResolvedType::Int64) {
-                            // Allow any integer type
-                        }
-                        self.enter_scope();
-                        self.add_symbol(
-                            binding.name.clone(),
-                            TypeInfo {
-                                ty: start_type.ty,
-                                certainty: Pramana::Anumana.certainty(),
-                                pramana: Pramana::Anumana,
-                                span: Some(*span),
-                            },
-                        );
-                        self.check_block(body);
-                        self.exit_scope();
-                    }
-                    LoopKind::Infinite => {
-                        self.check_block(body);
                     }
+                    None => self.loops[index].break_ty = Some(value_ty),
                 }
-                ResolvedType::Unit
+                ResolvedType::Never
             }
 
-            Stmt::Break { .. } | Stmt::Continue { .. } => ResolvedType::Unit,
+            Stmt::Continue { label, span } => {
+                self.resolve_loop(label.as_ref(), "continue", *span);
+                ResolvedType::Never
+            }
         }
     }
 
-    /// Check a pattern against an expected type
-    fn check_pattern(&mut self, pattern: &Pattern, expected: &ResolvedType) {
-        match pattern {
-            Pattern::Identifier(id) => {
+    /// Check a loop, statement or expression, and return its type
+    ///
+    /// `cala { }` has the type of the values it breaks with, or never
+    /// finishes without a `break`; the other loops are `()`.
+    fn check_loop(
+        &mut self,
+        label: Option<&Identifier>,
+        kind: &LoopKind,
+        body: &Block,
+        span: Span,
+    ) -> ResolvedType {
+        self.loops.push(LoopFrame {
+            label: label.map(|l| l.name.clone()),
+            infinite: matches!(kind, LoopKind::Infinite),
+            break_ty: None,
+        });
+        match kind {
+            LoopKind::ForIn { binding, iterable } => {
+                let iter_type = self.infer_expr(iterable);
+                let elem_type = self.iteration_item(&iter_type.ty, iter_type.span.unwrap_or(span));
+                self.enter_scope();
                 self.add_symbol(
-                    id.name.clone(),
+                    binding.name.clone(),
                     TypeInfo {
-                        ty: expected.clone(),
-                        certainty: Pramana::Upamana.certainty(),
-                        pramana: Pramana::Upamana,
-                        span: Some(id.span),
+                        ty: elem_type,
+                        certainty: Pramana::Anumana.certainty(),
+                        pramana: Pramana::Anumana,
+                        span: Some(span),
                     },
                 );
+                self.check_block(body);
+                self.exit_scope();
             }
-            Pattern::Literal(lit) => {
-                let lit_type = self.infer_literal(lit);
-                if let Err(_) = self.inference.unify(&lit_type, expected) {
-                    // Pattern type mismatch - could add error
-                }
-            }
-            Pattern::Constructor { name, fields } => {
-                // Check constructor pattern against type definition
-                if let Some(typedef) = self.type_defs.get(&name.name).cloned() {
-                    if let TypeBodyResolved::Enum(variants) = &typedef.body {
-                        // Find matching variant
-                        if let Some((_, variant_fields)) =
-                            variants.iter().find(|(n, _)| n == &name.name)
-                        {
-                            if let Some(field_types) = variant_fields {
-                                for (pattern, ty) in fields.iter().zip(field_types.iter()) {
-                                    self.check_pattern(pattern, ty);
-                                }
-                            }
-                        }
-                    }
+            LoopKind::While { condition } => {
+                let cond_type = self.infer_expr(condition);
+                if self
+                    .inference
+                    .unify(&cond_type.ty, &ResolvedType::Bool)
+                    .is_err()
+                {
+                    self.errors.push(TypeError::Mismatch {
+                        expected: ResolvedType::Bool,
+                        found: cond_type.ty,
+                        span: Some(span),
+                        context: "while condition".to_string(),
+                    });
                 }
+                self.check_block(body);
             }
-            Pattern::Binding { name, mutable: _, subpattern } => {
-                // Named binding with optional subpattern
+            LoopKind::Range {
+                binding,
+                start,
+                end,
+                ..
+            } => {
+                let start_type = self.infer_expr(start);
+                let _end_type = self.infer_expr(end);
+                // Start and end should be integers; any integer type is allowed
+                let _ = self.inference.unify(&start_type.ty, &ResolvedType::Int64);
+                self.enter_scope();
                 self.add_symbol(
-                    name.name.clone(),
+                    binding.name.clone(),
                     TypeInfo {
-                        ty: expected.clone(),
-                        certainty: Pramana::Upamana.certainty(),
-                        pramana: Pramana::Upamana,
-                        span: None,
+                        ty: start_type.ty,
+                        certainty: Pramana::Anumana.certainty(),
+                        pramana: Pramana::Anumana,
+                        span: Some(span),
                     },
                 );
-                if let Some(sub) = subpattern {
-                    self.check_pattern(sub, expected);
-                }
+                self.check_block(body);
+                self.exit_scope();
             }
-            Pattern::Tuple(patterns) => {
-                // Extract tuple element types
-                if let ResolvedType::Tuple(tys) = expected {
-                    for (p, ty) in patterns.iter().zip(tys.iter()) {
-                        self.check_pattern(p, ty);
+            LoopKind::Infinite => {
+                self.check_block(body);
+            }
+        }
+        let frame = self.loops.pop().expect("loop frame pushed above");
+        if frame.infinite {
+            frame.break_ty.unwrap_or(ResolvedType::Never)
+        } else {
+            ResolvedType::Unit
+        }
+    }
+
+    /// Find the loop a `break`/`continue` refers to, innermost by default
+    fn resolve_loop(
+        &mut self,
+        label: Option<&Identifier>,
+        keyword: &str,
+        span: Span,
+    ) -> Option<usize> {
+        let found = match label {
+            Some(label) => self
+                .loops
+                .iter()
+                .rposition(|frame| frame.label.as_deref() == Some(label.name.as_str())),
+            None => self.loops.len().checked_sub(1),
+        };
+        if found.is_none() {
+            self.errors.push(match label {
+                Some(label) => TypeError::UnknownLabel {
+                    label: label.name.clone(),
+                    span: Some(span),
+                },
+                None => TypeError::OutsideLoop {
+                    keyword: keyword.to_string(),
+                    span: Some(span),
+                },
+            });
+        }
+        found
+    }
+
+    /// Check a pattern against an expected type, binding its names in scope
+    fn check_pattern(&mut self, pattern: &Pattern, expected: &ResolvedType) {
+        let expected = self.inference.apply(expected);
+        // Patterns other than bindings and `&p` see through references
+        let target = match &expected {
+            ResolvedType::Reference { inner, .. } => (**inner).clone(),
+            _ => expected.clone(),
+        };
+        match pattern {
+            Pattern::Identifier(name)
+            | Pattern::Binding {
+                name,
+                subpattern: None,
+                ..
+            } => {
+                // A bare name is a binding unless it names a unit variant
+                let (enum_name, variant) = split_path(&name.name);
+                let checker = MatchChecker::new(&self.type_defs);
+                if let Some((_, payload)) = checker.variant(&target, variant) {
+                    if payload.is_some() {
+                        self.errors.push(TypeError::ArityMismatch {
+                            function: name.name.clone(),
+                            expected: payload.map_or(0, |fields| fields.len()),
+                            found: 0,
+                            span: Some(name.span),
+                        });
                     }
-                } else {
-                    // Try to match against unknown
-                    for p in patterns {
-                        self.check_pattern(p, &ResolvedType::Unknown);
+                    return;
+                }
+                if let Some(enum_name) = enum_name {
+                    if self
+                        .resolve_variant(&expected, Some(enum_name), variant, name.span)
+                        .is_none()
+                    {
+                        self.errors.push(TypeError::UnknownIdentifier {
+                            name: name.name.clone(),
+                            span: Some(name.span),
+                        });
                     }
+                    return;
                 }
+                self.bind_pattern_name(name, expected);
             }
-            Pattern::Struct { name: struct_name, fields, rest: _ } => {
-                // Check struct pattern fields
-                if let Some(typedef) = self.type_defs.get(&struct_name.name).cloned() {
-                    if let TypeBodyResolved::Struct(field_defs) = &typedef.body {
-                        for (field_name, pattern) in fields {
-                            // Look up field by name in the struct definition
-                            if let Some((_, field_ty)) = field_defs.iter().find(|(n, _)| n == &field_name.name) {
-                                self.check_pattern(pattern, field_ty);
-                            }
-                        }
+            Pattern::Binding {
+                name,
+                subpattern: Some(sub),
+                ..
+            } => {
+                self.bind_pattern_name(name, expected.clone());
+                self.check_pattern(sub, &expected);
+            }
+            Pattern::Literal(lit) => {
+                let lit_type = self.infer_literal(lit);
+                let integers = matches!(lit, Literal::Int(_)) && is_integer_type(&target);
+                if !integers && self.coerce(&lit_type, &target).is_err() {
+                    self.errors.push(TypeError::Mismatch {
+                        expected: target,
+                        found: lit_type,
+                        span: None,
+                        context: "match pattern".to_string(),
+                    });
+                }
+            }
+            Pattern::Variant {
+                enum_name,
+                variant,
+                fields,
+            } => {
+                let path = describe_path(enum_name.as_ref(), variant);
+                let resolved = self.resolve_variant(
+                    &expected,
+                    enum_name.as_ref().map(|n| n.name.as_str()),
+                    &variant.name,
+                    variant.span,
+                );
+                let Some(payload) = resolved else {
+                    self.errors.push(TypeError::UnknownIdentifier {
+                        name: path,
+                        span: Some(variant.span),
+                    });
+                    self.check_variant_fields(fields, &[], variant.span, "");
+                    return;
+                };
+                let defs = payload.unwrap_or_default();
+                match fields {
+                    VariantFields::Unit if !defs.is_empty() => {
+                        self.errors.push(TypeError::ArityMismatch {
+                            function: path,
+                            expected: defs.len(),
+                            found: 0,
+                            span: Some(variant.span),
+                        });
                     }
+                    _ => self.check_variant_fields(fields, &defs, variant.span, &path),
                 }
             }
-            Pattern::Variant { enum_name: _, variant, fields } => {
-                // Check enum variant pattern
-                if let Some(typedef) = self.type_defs.get(&variant.name).cloned() {
-                    if let TypeBodyResolved::Enum(variants) = &typedef.body {
-                        if let Some((_, variant_tys)) = variants.iter().find(|(n, _)| n == &variant.name) {
-                            match fields {
-                                crate::parser::ast::VariantFields::Unit => {}
-                                crate::parser::ast::VariantFields::Tuple(patterns) => {
-                                    if let Some(tys) = variant_tys {
-                                        for (p, ty) in patterns.iter().zip(tys.iter()) {
-                                            self.check_pattern(p, ty);
-                                        }
-                                    }
-                                }
-                                crate::parser::ast::VariantFields::Struct(field_patterns) => {
-                                    for (_, p) in field_patterns {
-                                        self.check_pattern(p, &ResolvedType::Unknown);
-                                    }
-                                }
-                            }
-                        }
+            Pattern::Constructor { name, fields } => {
+                let (enum_name, variant) = split_path(&name.name);
+                let resolved = self.resolve_variant(&expected, enum_name, variant, name.span);
+                let Some(payload) = resolved else {
+                    self.errors.push(TypeError::UnknownIdentifier {
+                        name: name.name.clone(),
+                        span: Some(name.span),
+                    });
+                    self.check_variant_fields(
+                        &VariantFields::Tuple(fields.clone()),
+                        &[],
+                        name.span,
+                        "",
+                    );
+                    return;
+                };
+                self.check_variant_fields(
+                    &VariantFields::Tuple(fields.clone()),
+                    &payload.unwrap_or_default(),
+                    name.span,
+                    &name.name,
+                );
+            }
+            Pattern::Tuple(patterns) => {
+                let tys = match &target {
+                    ResolvedType::Tuple(tys) => tys.clone(),
+                    ResolvedType::TypeVar(_) if !patterns.iter().any(is_rest) => {
+                        let tys: Vec<ResolvedType> = patterns
+                            .iter()
+                            .map(|_| self.inference.fresh_type_var())
+                            .collect();
+                        let _ = self
+                            .inference
+                            .unify(&target, &ResolvedType::Tuple(tys.clone()));
+                        tys
+                    }
+                    _ => Vec::new(),
+                };
+                self.check_positional(patterns, &tys);
+            }
+            Pattern::Struct {
+                name: struct_name,
+                fields,
+                rest: _,
+            } => {
+                let checker = MatchChecker::new(&self.type_defs);
+                let mut defs = checker.struct_fields(&target);
+                if defs.is_none() {
+                    let named = ResolvedType::Named {
+                        name: struct_name.name.clone(),
+                        generics: Vec::new(),
+                    };
+                    defs = checker.struct_fields(&named);
+                    if defs.is_some() {
+                        let _ = self.inference.unify(&target, &named);
                     }
                 }
+                let Some(defs) = defs else {
+                    self.errors.push(TypeError::UnknownType {
+                        name: struct_name.name.clone(),
+                        span: Some(struct_name.span),
+                    });
+                    for (_, pattern) in fields {
+                        self.check_pattern(pattern, &ResolvedType::Unknown);
+                    }
+                    return;
+                };
+                self.check_named_fields(fields, &defs, &struct_name.name);
             }
             Pattern::Array(patterns) => {
-                let elem_ty = match expected {
-                    ResolvedType::Array { element, .. } => (**element).clone(),
-                    _ => ResolvedType::Unknown,
-                };
+                let elem_ty = self.extract_element_type(&target);
                 for p in patterns {
                     self.check_pattern(p, &elem_ty);
                 }
             }
-            Pattern::Slice { before, middle, after } => {
-                let elem_ty = match expected {
-                    ResolvedType::Array { element, .. } => (**element).clone(),
-                    _ => ResolvedType::Unknown,
-                };
-                for p in before {
+            Pattern::Slice {
+                before,
+                middle,
+                after,
+            } => {
+                let elem_ty = self.extract_element_type(&target);
+                for p in before.iter().chain(after) {
                     self.check_pattern(p, &elem_ty);
                 }
+                // The rest binds the elements between prefix and suffix
                 if let Some(mid) = middle {
-                    self.check_pattern(mid, expected);
-                }
-                for p in after {
-                    self.check_pattern(p, &elem_ty);
+                    let rest = ResolvedType::Array {
+                        element: Box::new(elem_ty),
+                        size: None,
+                    };
+                    self.check_pattern(mid, &rest);
                 }
             }
             Pattern::Range { start, end, .. } => {
-                // Range patterns match numeric types
                 if let Some(p) = start {
-                    self.check_pattern(p, expected);
+                    self.check_pattern(p, &target);
                 }
                 if let Some(p) = end {
-                    self.check_pattern(p, expected);
+                    self.check_pattern(p, &target);
                 }
             }
             Pattern::Or(patterns) => {
-                // All branches must have same type
                 for p in patterns {
-                    self.check_pattern(p, expected);
+                    self.check_pattern(p, &expected);
                 }
             }
-            Pattern::Guard { pattern, condition: _ } => {
-                // Guard expression checked separately
-                self.check_pattern(pattern, expected);
-            }
-            Pattern::Ref { mutable: _, pattern: inner } => {
-                // Reference pattern dereferences expected type
-                let inner_ty = match expected {
-                    ResolvedType::Reference { inner, .. } => (**inner).clone(),
-                    _ => expected.clone(),
-                };
-                self.check_pattern(inner, &inner_ty);
+            Pattern::Guard { pattern, condition } => {
+                self.check_pattern(pattern, &expected);
+                self.check_guard(condition);
             }
+            Pattern::Ref {
+                mutable: _,
+                pattern: inner,
+            } => self.check_pattern(inner, &target),
             Pattern::Wildcard | Pattern::Rest => { /* Match anything */ }
         }
     }
 
-    /// Extract element type from array/iterator type
+    /// Report a match that misses values, and arms that can never match
+    fn check_match_coverage(&mut self, scrutinee: &ResolvedType, arms: &[MatchArm], span: Span) {
+        let scrutinee = self.inference.apply(scrutinee);
+        let rows: Vec<(&Pattern, bool)> = arms
+            .iter()
+            .map(|arm| {
+                let guarded = arm.guard.is_some() || matches!(arm.pattern, Pattern::Guard { .. });
+                (&arm.pattern, guarded)
+            })
+            .collect();
+        let coverage = MatchChecker::new(&self.type_defs).check(&scrutinee, &rows);
+        for (index, pattern) in coverage.unreachable {
+            self.errors.push(TypeError::UnreachableArm {
+                pattern,
+                span: Some(arms[index].span),
+            });
+        }
+        if let Some(missing) = coverage.missing {
+            self.errors.push(TypeError::NonExhaustiveMatch {
+                missing,
+                span: Some(span),
+            });
+        }
+    }
+
+    /// Bind a name introduced by a pattern
+    fn bind_pattern_name(&mut self, name: &Identifier, ty: ResolvedType) {
+        self.add_symbol(
+            name.name.clone(),
+            TypeInfo {
+                ty,
+                certainty: Pramana::Upamana.certainty(),
+                pramana: Pramana::Upamana,
+                span: Some(name.span),
+            },
+        );
+    }
+
+    /// A `pūrva` or `paścāt` condition must be a Bool
+    fn check_contract(&mut self, contract: &Contract, context: &str) {
+        let info = self.infer_expr(&contract.condition);
+        if self.inference.unify(&info.ty, &ResolvedType::Bool).is_err() {
+            self.errors.push(TypeError::Mismatch {
+                expected: ResolvedType::Bool,
+                found: info.ty,
+                span: Some(contract.span),
+                context: context.to_string(),
+            });
+        }
+    }
+
+    /// A `yad` guard must be a Bool
+    fn check_guard(&mut self, condition: &Expr) {
+        let cond_info = self.infer_expr(condition);
+        if self
+            .inference
+            .unify(&cond_info.ty, &ResolvedType::Bool)
+            .is_err()
+        {
+            self.errors.push(TypeError::Mismatch {
+                expected: ResolvedType::Bool,
+                found: cond_info.ty,
+                span: Some(condition.span()),
+                context: "match guard".to_string(),
+            });
+        }
+    }
+
+    /// Find the enum a variant pattern names and return the variant's payload
+    ///
+    /// The scrutinee type decides first; otherwise the written enum name, or
+    /// the only enum declaring such a variant, fixes the scrutinee type.
+    fn resolve_variant(
+        &mut self,
+        expected: &ResolvedType,
+        enum_name: Option<&str>,
+        variant: &str,
+        span: Span,
+    ) -> Option<VariantPayload> {
+        let expected = self.inference.apply(expected);
+        let target = match &expected {
+            ResolvedType::Reference { inner, .. } => (**inner).clone(),
+            _ => expected.clone(),
+        };
+        let checker = MatchChecker::new(&self.type_defs);
+        if let Some((_, payload)) = checker.variant(&target, variant) {
+            return Some(payload);
+        }
+
+        let owner = match enum_name {
+            Some(name) => Some(name.to_string()),
+            None if matches!(target, ResolvedType::TypeVar(_) | ResolvedType::Unknown) => {
+                let mut owners = self.type_defs.values().filter(|def| {
+                    matches!(&def.body, TypeBodyResolved::Enum(variants)
+                        if variants.iter().any(|(name, _)| name == variant))
+                });
+                match (owners.next(), owners.next()) {
+                    (Some(def), None) => Some(def.name.clone()),
+                    _ => None,
+                }
+            }
+            None => None,
+        }?;
+        let generics = self.type_defs.get(&owner)?.generics.len();
+        let enum_ty = ResolvedType::Named {
+            name: owner,
+            generics: (0..generics)
+                .map(|_| self.inference.fresh_type_var())
+                .collect(),
+        };
+        let payload = MatchChecker::new(&self.type_defs)
+            .variant(&enum_ty, variant)?
+            .1;
+        if self.inference.unify(&target, &enum_ty).is_err() {
+            self.errors.push(TypeError::Mismatch {
+                expected: target,
+                found: enum_ty,
+                span: Some(span),
+                context: "match pattern".to_string(),
+            });
+        }
+        Some(payload)
+    }
+
+    /// Enum type and payload of the variant a path `Enum::Variant` names
+    ///
+    /// The enum's type parameters become fresh type variables.
+    fn variant_constructor(&mut self, path: &str) -> Option<(ResolvedType, VariantPayload)> {
+        let (Some(owner), variant) = split_path(path) else {
+            return None;
+        };
+        let def = self.type_defs.get(owner)?;
+        if !matches!(def.body, TypeBodyResolved::Enum(_)) {
+            return None;
+        }
+        let generics = def.generics.len();
+        let enum_ty = ResolvedType::Named {
+            name: owner.to_string(),
+            generics: (0..generics)
+                .map(|_| self.inference.fresh_type_var())
+                .collect(),
+        };
+        let payload = MatchChecker::new(&self.type_defs)
+            .variant(&enum_ty, variant)?
+            .1;
+        Some((enum_ty, payload))
+    }
+
+    /// Check the payload patterns of a variant against its declared fields
+    fn check_variant_fields(
+        &mut self,
+        fields: &VariantFields,
+        defs: &[(String, ResolvedType)],
+        span: Span,
+        path: &str,
+    ) {
+        match fields {
+            VariantFields::Unit => {}
+            VariantFields::Tuple(patterns) => {
+                let given = patterns.iter().filter(|p| !is_rest(p)).count();
+                let has_rest = given < patterns.len();
+                if !path.is_empty() && (given > defs.len() || (!has_rest && given < defs.len())) {
+                    self.errors.push(TypeError::ArityMismatch {
+                        function: path.to_string(),
+                        expected: defs.len(),
+                        found: given,
+                        span: Some(span),
+                    });
+                }
+                let tys: Vec<ResolvedType> = defs.iter().map(|(_, ty)| ty.clone()).collect();
+                self.check_positional(patterns, &tys);
+            }
+            VariantFields::Struct(fields) => {
+                if path.is_empty() {
+                    for (_, pattern) in fields {
+                        self.check_pattern(pattern, &ResolvedType::Unknown);
+                    }
+                } else {
+                    self.check_named_fields(fields, defs, path);
+                }
+            }
+        }
+    }
+
+    /// Check tuple-like sub-patterns, where a `..` skips the middle fields
+    fn check_positional(&mut self, patterns: &[Pattern], tys: &[ResolvedType]) {
+        let rest = patterns.iter().position(is_rest);
+        for (i, pattern) in patterns.iter().enumerate() {
+            let index = match rest {
+                Some(rest) if i > rest => (tys.len() + i).checked_sub(patterns.len()),
+                _ => Some(i),
+            };
+            let ty = index
+                .and_then(|index| tys.get(index).cloned())
+                .unwrap_or(ResolvedType::Unknown);
+            self.check_pattern(pattern, &ty);
+        }
+    }
+
+    /// Check struct-like sub-patterns by field name
+    fn check_named_fields(
+        &mut self,
+        fields: &[(Identifier, Pattern)],
+        defs: &[(String, ResolvedType)],
+        owner: &str,
+    ) {
+        for (field_name, pattern) in fields {
+            match defs.iter().find(|(name, _)| name == &field_name.name) {
+                Some((_, field_ty)) => self.check_pattern(pattern, field_ty),
+                None => {
+                    self.errors.push(TypeError::UnknownIdentifier {
+                        name: format!("{}.{}", owner, field_name.name),
+                        span: Some(field_name.span),
+                    });
+                    self.check_pattern(pattern, &ResolvedType::Unknown);
+                }
+            }
+        }
+    }
+
+    /// Extract element type from array/iterator type, through references
     fn extract_element_type(&self, ty: &ResolvedType) -> ResolvedType {
-        match ty {
-            ResolvedType::Array { element, .. } => (**element).clone(),
-            ResolvedType::Named { name, generics } if name == "Vec" || name == "Iterator" => {
+        match self.inference.apply(ty) {
+            ResolvedType::Reference { inner, .. } => self.extract_element_type(&inner),
+            ResolvedType::Array { element, .. } => *element,
+            ResolvedType::Named { name, generics }
+                if matches!(name.as_str(), "Sūci" | "Vec" | "Iterator" | "Range") =>
+            {
                 generics.first().cloned().unwrap_or(ResolvedType::Unknown)
             }
             _ => ResolvedType::Unknown,
         }
     }
 
+    /// Type of the items a `cala x : iterable` loop binds
+    ///
+    /// Arrays, slices, `Sūci` and ranges are iterated built in; any other
+    /// type through its `next` method (the `Iterator` protocol), which must
+    /// return `Vikalpa<Item>`.
+    fn iteration_item(&mut self, iterable: &ResolvedType, span: Span) -> ResolvedType {
+        let ty = strip_references(self.inference.apply(iterable));
+        match &ty {
+            ResolvedType::Array { .. } => return self.extract_element_type(&ty),
+            ResolvedType::Named { name, .. } if matches!(name.as_str(), "Sūci" | "Range") => {
+                return self.extract_element_type(&ty)
+            }
+            ResolvedType::TypeVar(_) | ResolvedType::Unknown | ResolvedType::Error => {
+                return ResolvedType::Unknown
+            }
+            _ => {}
+        }
+
+        let type_name = self.get_type_name(&ty);
+        let next = match self.lookup_method(&type_name, "next") {
+            Some(sig) => Some(sig),
+            None => self
+                .lookup_trait_method(&ty, "next")
+                .filter(|(_, receiver, _)| receiver.is_method())
+                .map(|(sig, _, _)| sig),
+        };
+        match next.map(|sig| self.inference.apply(&sig.return_type)) {
+            Some(ResolvedType::Named { name, generics }) if name == "Vikalpa" => {
+                generics.into_iter().next().unwrap_or(ResolvedType::Unknown)
+            }
+            _ => {
+                self.errors.push(TypeError::NotIterable {
+                    ty,
+                    span: Some(span),
+                });
+                ResolvedType::Error
+            }
+        }
+    }
+
+    /// Check an index or slice bound is an integer
+    fn check_index(&mut self, index: &Expr) {
+        let info = self.infer_expr(index);
+        let ty = self.inference.apply(&info.ty);
+        if !is_integer_type(&ty)
+            && !matches!(
+                ty,
+                ResolvedType::TypeVar(_) | ResolvedType::Unknown | ResolvedType::Error
+            )
+        {
+            self.errors.push(TypeError::Mismatch {
+                expected: ResolvedType::Int64,
+                found: ty,
+                span: info.span,
+                context: "index".to_string(),
+            });
+        }
+    }
+
+    /// Type of a built-in method of arrays, slices and `Sūci`
+    ///
+    /// `None` if the receiver is not a sequence or has no such method, so
+    /// that the method is looked up as usual. Lengths are `saṅkhyā`, the
+    /// type integer literals take.
+    fn sequence_method(
+        &mut self,
+        receiver: &ResolvedType,
+        method: &Identifier,
+        args: &[Expr],
+        span: Span,
+    ) -> Option<ResolvedType> {
+        let receiver = strip_references(self.inference.apply(receiver));
+        let is_suci = is_suci_type(&receiver);
+        if !is_suci && !matches!(receiver, ResolvedType::Array { .. }) {
+            return None;
+        }
+        let element = self.extract_element_type(&receiver);
+        let (params, return_type) = match method.name.as_str() {
+            "dīrghatā" | "len" => (vec![], ResolvedType::Int32),
+            "rikta" | "is_empty" => (vec![], ResolvedType::Bool),
+            "yojaya" | "push" if is_suci => (vec![element], ResolvedType::Unit),
+            "niṣkāsaya" | "pop" if is_suci => (vec![], element),
+            // Fixed arrays and slices cannot change length
+            "yojaya" | "push" | "niṣkāsaya" | "pop" => {
+                self.errors.push(TypeError::InvalidOperation {
+                    op: method.name.clone(),
+                    ty: receiver,
+                    span: Some(span),
+                });
+                return Some(ResolvedType::Error);
+            }
+            _ => return None,
+        };
+
+        let function = format!("{}.{}", self.get_type_name(&receiver), method.name);
+        if args.len() != params.len() {
+            self.errors.push(TypeError::ArityMismatch {
+                function: function.clone(),
+                expected: params.len(),
+                found: args.len(),
+                span: Some(span),
+            });
+        }
+        for (arg, param) in args.iter().zip(&params) {
+            let arg_info = self.infer_expr(arg);
+            if self.inference.unify(&arg_info.ty, param).is_err() {
+                self.errors.push(TypeError::ArgumentMismatch {
+                    function: function.clone(),
+                    param: "x".to_string(),
+                    expected: param.clone(),
+                    found: arg_info.ty,
+                    span: arg_info.span,
+                });
+            }
+        }
+        Some(return_type)
+    }
+
     /// Infer type of an expression using Nyāya pramāṇas
     pub fn infer_type(&self, expr: &Expr) -> Result<TypeInfo, TypeError> {
         // Try pramāṇas in order of certainty:
@@ -1132,6 +2492,14 @@ impl TypeChecker {
                 // Look up in symbol table (Pratyakṣa or previously inferred)
                 if let Some(info) = self.lookup(&id.name) {
                     info.clone()
+                } else if let Some((enum_ty, None)) = self.variant_constructor(&id.name) {
+                    // `Enum::Variant` of a unit variant
+                    TypeInfo {
+                        ty: enum_ty,
+                        certainty: Pramana::Pratyaksha.certainty(),
+                        pramana: Pramana::Pratyaksha,
+                        span: Some(id.span),
+                    }
                 } else {
                     self.errors.push(TypeError::UnknownIdentifier {
                         name: id.name.clone(),
@@ -1154,10 +2522,33 @@ impl TypeChecker {
             } => {
                 let left_info = self.infer_expr(left);
                 let right_info = self.infer_expr(right);
-                let result_type = self.infer_binary_op(*op, &left_info.ty, &right_info.ty);
+                let mut result_type = self.infer_binary_op(*op, &left_info.ty, &right_info.ty);
+
+                // An integer literal takes the type of the integer it meets
+                let left_ty = self.inference.apply(&left_info.ty);
+                let right_ty = self.inference.apply(&right_info.ty);
+                let literal = match (constant_int(left), constant_int(right)) {
+                    (None, Some(n)) if is_integer_type(&left_ty) => {
+                        Some((n, left_ty.clone(), right))
+                    }
+                    (Some(n), None) if is_integer_type(&right_ty) => {
+                        Some((n, right_ty.clone(), left))
+                    }
+                    _ => None,
+                };
 
-                // Unify operand types for arithmetic
-                if is_arithmetic_op(*op) {
+                if let Some((n, ty, literal)) = literal.filter(|_| takes_literal(*op)) {
+                    self.check_literal_fits(n, &ty, literal.span());
+                    if is_arithmetic_op(*op) {
+                        result_type = ty;
+                    }
+                } else if is_assignment_op(*op) {
+                    // The value must convert to the place assigned
+                    if self.is_numeric(&left_ty) && self.is_numeric(&right_ty) {
+                        let _ = self.coerce_value(right, &right_ty, &left_ty);
+                    }
+                } else if is_arithmetic_op(*op) {
+                    // Unify operand types for arithmetic
                     if let Err(_) = self.inference.unify(&left_info.ty, &right_info.ty) {
                         self.errors.push(TypeError::BinaryOpMismatch {
                             op: format!("{:?}", op),
@@ -1186,14 +2577,113 @@ impl TypeChecker {
                     pramana: Pramana::Anumana,
                     span: Some(*span),
                 }
-            }
+            }
+
+            Expr::Call { callee, args, span } => {
+                // `Sūci::nava()`, `Sūci::kṣamatā(n)`: the element type is
+                // left to inference
+                if let Expr::Identifier(func_id) = callee.as_ref() {
+                    let expected = match func_id.name.as_str() {
+                        "Sūci::nava" => Some(0),
+                        "Sūci::kṣamatā" => Some(1),
+                        _ => None,
+                    };
+                    if let Some(expected) = expected {
+                        if args.len() != expected {
+                            self.errors.push(TypeError::ArityMismatch {
+                                function: func_id.name.clone(),
+                                expected,
+                                found: args.len(),
+                                span: Some(*span),
+                            });
+                        }
+                        for arg in args {
+                            self.check_index(arg);
+                        }
+                        let element = self.inference.fresh_type_var();
+                        self.suci_elements.push((element.clone(), *span));
+                        return TypeInfo {
+                            ty: suci_type(element),
+                            certainty: Pramana::Shabda.certainty(),
+                            pramana: Pramana::Shabda,
+                            span: Some(*span),
+                        };
+                    }
+                }
+                // `Enum::Variant(..)` constructs a tuple variant
+                if let Expr::Identifier(func_id) = callee.as_ref() {
+                    if let Some((enum_ty, payload)) = self.variant_constructor(&func_id.name) {
+                        let fields = payload.unwrap_or_default();
+                        if args.len() != fields.len() {
+                            self.errors.push(TypeError::ArityMismatch {
+                                function: func_id.name.clone(),
+                                expected: fields.len(),
+                                found: args.len(),
+                                span: Some(*span),
+                            });
+                        }
+                        for (arg, (field, field_ty)) in args.iter().zip(&fields) {
+                            let arg_info = self.infer_expr(arg);
+                            if self.coerce_value(arg, &arg_info.ty, field_ty).is_err() {
+                                self.errors.push(TypeError::ArgumentMismatch {
+                                    function: func_id.name.clone(),
+                                    param: field.clone(),
+                                    expected: field_ty.clone(),
+                                    found: arg_info.ty,
+                                    span: Some(*span),
+                                });
+                            }
+                        }
+                        return TypeInfo {
+                            ty: enum_ty,
+                            certainty: Pramana::Shabda.certainty(),
+                            pramana: Pramana::Shabda,
+                            span: Some(*span),
+                        };
+                    }
+                }
+                // Śabda: Use function signature if available (a variable of the
+                // same name shadows the function)
+                let callee_is_local = matches!(
+                    callee.as_ref(),
+                    Expr::Identifier(id) if self.lookup(&id.name).is_some()
+                );
+                if let (Expr::Identifier(func_id), false) = (callee.as_ref(), callee_is_local) {
+                    let mut sig = self.function_sigs.get(&func_id.name).cloned();
+                    let mut method = None;
+                    if sig.is_none() {
+                        if let Some((path_sig, found)) = self.lookup_path_function(&func_id.name) {
+                            sig = Some(path_sig);
+                            method = Some(found);
+                        }
+                    }
+                    if let Some(mut sig) = sig {
+                        // Fresh type variables for the callee's type parameters
+                        let type_args: Vec<ResolvedType> = sig
+                            .generics
+                            .iter()
+                            .map(|_| self.inference.fresh_type_var())
+                            .collect();
+                        if !type_args.is_empty() {
+                            let subst: HashMap<&str, &ResolvedType> = sig
+                                .generics
+                                .iter()
+                                .map(|(name, _)| name.as_str())
+                                .zip(type_args.iter())
+                                .collect();
+                            for (_, ty) in &mut sig.params {
+                                *ty = substitute_type_params(ty, &subst);
+                            }
+                            sig.return_type = substitute_type_params(&sig.return_type, &subst);
+                        }
 
-            Expr::Call { callee, args, span } => {
-                // Śabda: Use function signature if available
-                if let Expr::Identifier(func_id) = callee.as_ref() {
-                    if let Some(sig) = self.function_sigs.get(&func_id.name).cloned() {
                         // Check argument count
-                        if args.len() != sig.params.len() {
+                        let arity_ok = if sig.variadic {
+                            args.len() >= sig.params.len()
+                        } else {
+                            args.len() == sig.params.len()
+                        };
+                        if !arity_ok {
                             self.errors.push(TypeError::ArityMismatch {
                                 function: func_id.name.clone(),
                                 expected: sig.params.len(),
@@ -1205,7 +2695,7 @@ impl TypeChecker {
                         // Check argument types
                         for (arg, (param_name, param_ty)) in args.iter().zip(sig.params.iter()) {
                             let arg_info = self.infer_expr(arg);
-                            if let Err(_) = self.inference.unify(&arg_info.ty, param_ty) {
+                            if self.coerce_value(arg, &arg_info.ty, param_ty).is_err() {
                                 self.errors.push(TypeError::ArgumentMismatch {
                                     function: func_id.name.clone(),
                                     param: param_name.clone(),
@@ -1215,6 +2705,23 @@ impl TypeChecker {
                                 });
                             }
                         }
+                        // Variadic extras are checked on their own
+                        for arg in args.iter().skip(sig.params.len()) {
+                            self.infer_expr(arg);
+                        }
+
+                        if let Some(found) = method {
+                            self.record_method_call(found, &func_id.name, expr, *span);
+                        } else if !type_args.is_empty() {
+                            self.check_type_arg_bounds(&sig.generics, &type_args, *span);
+                            self.instantiations.push(CallRecord {
+                                function: func_id.name.clone(),
+                                owner: None,
+                                type_args,
+                                call: CallId::of(expr),
+                                span: *span,
+                            });
+                        }
 
                         return TypeInfo {
                             ty: sig.return_type,
@@ -1225,21 +2732,33 @@ impl TypeChecker {
                     }
                 }
 
-                // Fall back to inferring callee type
+                // Fall back to inferring callee type (closures and function values)
                 let callee_info = self.infer_expr(callee);
-                match callee_info.ty {
-                    ResolvedType::Function { return_type, .. } => TypeInfo {
-                        ty: *return_type,
-                        certainty: Pramana::Anumana.certainty(),
-                        pramana: Pramana::Anumana,
-                        span: Some(*span),
-                    },
-                    _ => TypeInfo {
-                        ty: ResolvedType::Unknown,
-                        certainty: 0.5,
-                        pramana: Pramana::Upamana,
-                        span: Some(*span),
-                    },
+                let callee_name = match callee.as_ref() {
+                    Expr::Identifier(id) => id.name.clone(),
+                    _ => "<pāśa>".to_string(),
+                };
+                match self.callable_signature(&callee_info.ty, args.len()) {
+                    Some((params, return_type)) => {
+                        self.check_call_args(&callee_name, &params, args, *span);
+                        TypeInfo {
+                            ty: return_type,
+                            certainty: Pramana::Anumana.certainty(),
+                            pramana: Pramana::Anumana,
+                            span: Some(*span),
+                        }
+                    }
+                    None => {
+                        for arg in args {
+                            self.infer_expr(arg);
+                        }
+                        TypeInfo {
+                            ty: ResolvedType::Unknown,
+                            certainty: 0.5,
+                            pramana: Pramana::Upamana,
+                            span: Some(*span),
+                        }
+                    }
                 }
             }
 
@@ -1250,12 +2769,34 @@ impl TypeChecker {
                 span,
             } => {
                 let receiver_info = self.infer_expr(receiver);
+                if let Some(ty) = self.sequence_method(&receiver_info.ty, method, args, *span) {
+                    return TypeInfo {
+                        ty,
+                        certainty: Pramana::Shabda.certainty(),
+                        pramana: Pramana::Shabda,
+                        span: Some(*span),
+                    };
+                }
 
                 // Resolve the type name for method lookup
                 let type_name = self.get_type_name(&receiver_info.ty);
 
-                // Look up method on receiver type
-                if let Some(method_sig) = self.lookup_method(&type_name, &method.name) {
+                // Look up method on receiver type, then through trait impls
+                let mut found = self.lookup_method(&type_name, &method.name);
+                let mut method_use = None;
+                if found.is_none() {
+                    found = self
+                        .lookup_trait_method(&receiver_info.ty, &method.name)
+                        .filter(|(_, receiver, _)| receiver.is_method())
+                        .map(|(sig, _, found)| {
+                            method_use = Some(found);
+                            sig
+                        });
+                }
+                if found.is_some() {
+                    self.check_member_access(&type_name, &method.name, method.span);
+                }
+                if let Some(method_sig) = found {
                     // Type check arguments against method signature
                     for (_i, (arg, (param_name, param_type))) in
                         args.iter().zip(method_sig.params.iter()).enumerate()
@@ -1263,7 +2804,7 @@ impl TypeChecker {
                         let arg_info = self.infer_expr(arg);
 
                         // Unify argument type with parameter type
-                        if let Err(_e) = self.inference.unify(&arg_info.ty, param_type) {
+                        if let Err(_e) = self.coerce_value(arg, &arg_info.ty, param_type) {
                             self.errors.push(TypeError::ArgumentMismatch {
                                 function: format!("{}.{}", type_name, method.name),
                                 param: param_name.clone(),
@@ -1283,6 +2824,9 @@ impl TypeChecker {
                             span: Some(*span),
                         });
                     }
+                    if let Some(found) = method_use {
+                        self.record_method_call(found, &method.name, expr, *span);
+                    }
 
                     TypeInfo {
                         ty: method_sig.return_type.clone(),
@@ -1312,6 +2856,8 @@ impl TypeChecker {
                 span,
             } => {
                 let object_info = self.infer_expr(object);
+                let owner = self.get_type_name(&object_info.ty);
+                self.check_member_access(&owner, &field.name, field.span);
                 // Look up field type in struct definition
                 let field_type = self.lookup_field_type(&object_info.ty, &field.name);
 
@@ -1329,17 +2875,87 @@ impl TypeChecker {
                 span,
             } => {
                 let object_info = self.infer_expr(object);
-                let index_info = self.infer_expr(index);
-
-                // Index should be integer
-                if let Err(_) = self.inference.unify(&index_info.ty, &ResolvedType::Int64) {
-                    // Could also be usize - try other integer types
+                let object_ty = strip_references(self.inference.apply(&object_info.ty));
+                let indexable = matches!(
+                    object_ty,
+                    ResolvedType::Array { .. }
+                        | ResolvedType::Named { .. }
+                        | ResolvedType::TypeVar(_)
+                        | ResolvedType::Unknown
+                        | ResolvedType::Error
+                );
+                if !indexable {
+                    self.errors.push(TypeError::InvalidOperation {
+                        op: "index".to_string(),
+                        ty: object_ty.clone(),
+                        span: Some(*span),
+                    });
                 }
+                let elem_type = self.extract_element_type(&object_ty);
+
+                // Slicing by a range gives a slice of the elements
+                let ty = if let Expr::Range { start, end, .. } = index.as_ref() {
+                    for bound in start.iter().chain(end) {
+                        self.check_index(bound);
+                    }
+                    ResolvedType::Array {
+                        element: Box::new(elem_type),
+                        size: None,
+                    }
+                } else {
+                    self.check_index(index);
+                    // A constant index into a fixed array is checked now
+                    if let (
+                        ResolvedType::Array {
+                            size: Some(len), ..
+                        },
+                        Expr::Literal(Literal::Int(i)),
+                    ) = (&object_ty, index.as_ref())
+                    {
+                        if *i < 0 || *i as usize >= *len {
+                            self.errors.push(TypeError::IndexOutOfBounds {
+                                index: *i,
+                                len: *len,
+                                span: Some(*span),
+                            });
+                        }
+                    }
+                    elem_type
+                };
 
-                let elem_type = self.extract_element_type(&object_info.ty);
+                TypeInfo {
+                    ty,
+                    certainty: Pramana::Anumana.certainty(),
+                    pramana: Pramana::Anumana,
+                    span: Some(*span),
+                }
+            }
 
+            Expr::Range {
+                start, end, span, ..
+            } => {
+                let mut bound_ty = None;
+                for bound in start.iter().chain(end) {
+                    let info = self.infer_expr(bound);
+                    match &bound_ty {
+                        None => bound_ty = Some(info.ty),
+                        Some(ty) => {
+                            if self.inference.unify(&info.ty, ty).is_err() {
+                                self.errors.push(TypeError::Mismatch {
+                                    expected: ty.clone(),
+                                    found: info.ty,
+                                    span: info.span,
+                                    context: "range bound".to_string(),
+                                });
+                            }
+                        }
+                    }
+                }
                 TypeInfo {
-                    ty: elem_type,
+                    ty: ResolvedType::Named {
+                        name: "Range".to_string(),
+                        generics: vec![bound_ty.unwrap_or(ResolvedType::Int32)],
+                    },
                     certainty: Pramana::Anumana.certainty(),
                     pramana: Pramana::Anumana,
                     span: Some(*span),
@@ -1353,6 +2969,10 @@ impl TypeChecker {
                     generics: vec![],
                 };
 
+                for (field_name, _) in fields {
+                    self.check_member_access(&name.name, &field_name.name, field_name.span);
+                }
+
                 // Check field types if struct definition exists
                 if let Some(typedef) = self.type_defs.get(&name.name).cloned() {
                     if let TypeBodyResolved::Struct(struct_fields) = &typedef.body {
@@ -1426,6 +3046,7 @@ impl TypeChecker {
             }
 
             Expr::Lambda { params, body, span } => {
+                let captures = analyze_captures(params, body, |name| self.capture_ownership(name));
                 self.enter_scope();
 
                 // Add parameters to scope
@@ -1446,13 +3067,31 @@ impl TypeChecker {
                     })
                     .collect();
 
+                // `break` cannot leave the closure for a loop around it,
+                // nor `?` return from the function around it
+                let outer_loops = std::mem::take(&mut self.loops);
+                let outer_returns = self.returns.take();
                 let body_info = self.infer_expr(body);
+                self.loops = outer_loops;
+                self.returns = outer_returns;
                 self.exit_scope();
 
+                let name = closure_name(&self.current_function, *span);
+                let sig = ClosureSig {
+                    params: params
+                        .iter()
+                        .map(|p| p.name.name.clone())
+                        .zip(param_types)
+                        .collect(),
+                    return_type: body_info.ty,
+                    kind: captures.kind,
+                };
+                self.register_closure(&name, sig, *span);
+
                 TypeInfo {
-                    ty: ResolvedType::Function {
-                        params: param_types,
-                        return_type: Box::new(body_info.ty),
+                    ty: ResolvedType::Named {
+                        name,
+                        generics: Vec::new(),
                     },
                     certainty: Pramana::Anumana.certainty(),
                     pramana: Pramana::Anumana,
@@ -1473,6 +3112,61 @@ impl TypeChecker {
                 }
             }
 
+            Expr::Loop {
+                label,
+                kind,
+                body,
+                span,
+            } => TypeInfo {
+                ty: self.check_loop(label.as_ref(), kind, body, *span),
+                certainty: Pramana::Anumana.certainty(),
+                pramana: Pramana::Anumana,
+                span: Some(*span),
+            },
+
+            Expr::Match {
+                scrutinee,
+                arms,
+                span,
+            } => {
+                let scrutinee_info = self.infer_expr(scrutinee);
+                let mut result: Option<ResolvedType> = None;
+                for arm in arms {
+                    self.enter_scope();
+                    self.check_pattern(&arm.pattern, &scrutinee_info.ty);
+                    if let Some(guard) = &arm.guard {
+                        self.check_guard(guard);
+                    }
+                    let body = self.infer_expr(&arm.body);
+                    self.exit_scope();
+
+                    // Diverging arms (`phera`, `virāma`) take any type
+                    if self.inference.apply(&body.ty) == ResolvedType::Never {
+                        continue;
+                    }
+                    match &result {
+                        Some(expected) => {
+                            if self.inference.unify(expected, &body.ty).is_err() {
+                                self.errors.push(TypeError::BranchMismatch {
+                                    then_type: expected.clone(),
+                                    else_type: body.ty,
+                                    span: Some(arm.span),
+                                });
+                            }
+                        }
+                        None => result = Some(body.ty),
+                    }
+                }
+                self.check_match_coverage(&scrutinee_info.ty, arms, *span);
+
+                TypeInfo {
+                    ty: result.unwrap_or(ResolvedType::Never),
+                    certainty: Pramana::Anumana.certainty(),
+                    pramana: Pramana::Anumana,
+                    span: Some(*span),
+                }
+            }
+
             Expr::If {
                 condition,
                 then_expr,
@@ -1496,11 +3190,15 @@ impl TypeChecker {
                     if let Err(_) = self.inference.unify(&then_info.ty, &else_info.ty) {
                         self.errors.push(TypeError::BranchMismatch {
                             then_type: then_info.ty.clone(),
-                            else_type: else_info.ty,
+                            else_type: else_info.ty.clone(),
                             span: Some(*span),
                         });
                     }
-                    then_info
+                    if self.inference.apply(&then_info.ty) == ResolvedType::Never {
+                        else_info
+                    } else {
+                        then_info
+                    }
                 } else {
                     TypeInfo {
                         ty: ResolvedType::Unit,
@@ -1513,8 +3211,16 @@ impl TypeChecker {
 
             Expr::Cast { expr, ty, span } => {
                 // Pratyakṣa: Cast has explicit target type
-                let _expr_info = self.infer_expr(expr);
+                let expr_info = self.infer_expr(expr);
+                let source_type = self.inference.apply(&expr_info.ty);
                 let target_type = self.resolve_ast_type(ty);
+                if !is_valid_cast(&source_type, &target_type) {
+                    self.errors.push(TypeError::InvalidCast {
+                        from: source_type,
+                        to: target_type.clone(),
+                        span: Some(*span),
+                    });
+                }
 
                 TypeInfo {
                     ty: target_type,
@@ -1524,12 +3230,52 @@ impl TypeChecker {
                 }
             }
 
+            Expr::Format { pieces, args, span } => {
+                let arg_types: Vec<ResolvedType> = args
+                    .iter()
+                    .map(|arg| {
+                        let ty = self.infer_expr(arg).ty;
+                        self.inference.apply(&ty)
+                    })
+                    .collect();
+                for piece in pieces {
+                    let FormatPiece::Arg { index, spec } = piece else {
+                        continue;
+                    };
+                    let Some(ty) = arg_types.get(*index) else {
+                        continue;
+                    };
+                    if !self.is_formattable(ty, spec.kind) {
+                        self.errors.push(TypeError::Trait {
+                            error: traits::TraitError::BoundNotSatisfied {
+                                type_name: self.get_type_name(ty),
+                                trait_name: spec.kind.trait_name().to_string(),
+                                span: *span,
+                            },
+                        });
+                    }
+                }
+                TypeInfo {
+                    ty: ResolvedType::String,
+                    certainty: Pramana::Pratyaksha.certainty(),
+                    pramana: Pramana::Pratyaksha,
+                    span: Some(*span),
+                }
+            }
+
+            // Already reported by the parser; the error type unifies with
+            // anything, so it causes no follow-up errors
+            Expr::Error(span) => TypeInfo {
+                ty: ResolvedType::Error,
+                certainty: 0.0,
+                pramana: Pramana::Anumana,
+                span: Some(*span),
+            },
+
             Expr::Try { expr, span } => {
                 let expr_info = self.infer_expr(expr);
-                // Try operator unwraps Result/Option types
-                // For now, just return the inner type or Unknown
                 TypeInfo {
-                    ty: expr_info.ty,
+                    ty: self.check_try(&expr_info.ty, *span),
                     certainty: Pramana::Anumana.certainty(),
                     pramana: Pramana::Anumana,
                     span: Some(*span),
@@ -1546,6 +3292,115 @@ impl TypeChecker {
                     span: Some(*span),
                 }
             }
+
+            Expr::Saha { task, span } => {
+                self.check_saha(task);
+                TypeInfo {
+                    ty: ResolvedType::Unit,
+                    certainty: Pramana::Pratyaksha.certainty(),
+                    pramana: Pramana::Pratyaksha,
+                    span: Some(*span),
+                }
+            }
+        }
+    }
+
+    /// Check a `saha` task and what crosses into it
+    ///
+    /// A block shares the variables it uses with the code after it, which
+    /// must be shareable, or -sūtra if the block assigns them; a spawned
+    /// call is handed its arguments, which must be sendable. See
+    /// [`super::sutra`].
+    fn check_saha(&mut self, task: &Expr) {
+        let mut crossings = Vec::new();
+        match task {
+            Expr::Call { callee, args, .. } => {
+                let callee = match &**callee {
+                    Expr::Identifier(id) => id.name.clone(),
+                    _ => "the task".to_string(),
+                };
+                for (i, arg) in args.iter().enumerate() {
+                    let value = match arg {
+                        Expr::Identifier(id) => format!("`{}`", id.name),
+                        _ => format!("argument {} of `{}`", i + 1, callee),
+                    };
+                    crossings.push((value, self.crossing_type(arg), Crossing::Sent, arg.span()));
+                }
+            }
+            Expr::MethodCall {
+                receiver,
+                method,
+                args,
+                ..
+            } => {
+                for (i, arg) in std::iter::once(&**receiver).chain(args).enumerate() {
+                    let value = match arg {
+                        Expr::Identifier(id) => format!("`{}`", id.name),
+                        _ if i == 0 => format!("the receiver of `{}`", method.name),
+                        _ => format!("argument {} of `{}`", i, method.name),
+                    };
+                    crossings.push((value, self.crossing_type(arg), Crossing::Sent, arg.span()));
+                }
+            }
+            _ => {
+                let captures = analyze_captures(&[], task, |name| self.capture_ownership(name));
+                for capture in captures.captures {
+                    let crossing = match capture.mode {
+                        CaptureMode::Copy | CaptureMode::Move => Crossing::Sent,
+                        CaptureMode::Ref => Crossing::Shared,
+                        CaptureMode::MutRef => Crossing::Written,
+                    };
+                    let ty = self.declared_type(&capture.name);
+                    crossings.push((format!("`{}`", capture.name), ty, crossing, capture.span));
+                }
+            }
+        }
+        for (value, ty, crossing, span) in crossings {
+            if let Some(reason) = self.sutra.check(&ty, crossing) {
+                self.errors.push(TypeError::NotThreadSafe {
+                    value,
+                    crossing,
+                    reason,
+                    span: Some(span),
+                });
+            }
+        }
+
+        // The task runs apart: `break` cannot leave it for a loop around
+        // it, nor `?` return from the function around it
+        let outer_loops = std::mem::take(&mut self.loops);
+        let outer_returns = self.returns.take();
+        self.infer_expr(task);
+        self.loops = outer_loops;
+        self.returns = outer_returns;
+    }
+
+    /// Type of a variable as declared, affixes and all, or as inferred
+    fn declared_type(&self, name: &str) -> Type {
+        match self.local_types.get(name) {
+            Some(ty) => ty.clone(),
+            None => self
+                .lookup(name)
+                .map_or(Type::Inferred, |info| self.to_ast_type(&info.ty, &[])),
+        }
+    }
+
+    /// Type of a value handed to a `saha` task
+    fn crossing_type(&self, expr: &Expr) -> Type {
+        match expr {
+            Expr::Identifier(id) if self.lookup(&id.name).is_some() => self.declared_type(&id.name),
+            Expr::Unary {
+                op: op @ (UnaryOp::Ref | UnaryOp::RefMut),
+                operand,
+                ..
+            } => Type::Reference {
+                inner: Box::new(self.crossing_type(operand)),
+                mutable: *op == UnaryOp::RefMut,
+                lifetime: None,
+            },
+            _ => self
+                .infer_type(expr)
+                .map_or(Type::Inferred, |info| self.to_ast_type(&info.ty, &[])),
         }
     }
 
@@ -1757,12 +3612,201 @@ impl TypeChecker {
         None
     }
 
+    /// Ownership of a local variable seen from a closure body
+    ///
+    /// `None` for names outside the enclosing function (the global scope),
+    /// which closures refer to without capturing.
+    fn capture_ownership(&self, name: &str) -> Option<OwnershipKind> {
+        let info = self.scopes.iter().skip(1).rev().find_map(|s| s.get(name))?;
+        if let Some(&kind) = self.local_ownership.get(name) {
+            return Some(kind);
+        }
+        Some(match self.inference.apply(&info.ty) {
+            ResolvedType::Reference { mutable, .. } => OwnershipKind::Borrowed { mutable },
+            ResolvedType::Named { .. }
+            | ResolvedType::String
+            | ResolvedType::Array { .. }
+            | ResolvedType::Tuple(_)
+            | ResolvedType::Function { .. }
+            | ResolvedType::TypeVar(_) => OwnershipKind::Affine,
+            _ => OwnershipKind::Copy,
+        })
+    }
+
+    /// Record a closure type and its Fn-family impls
+    fn register_closure(&mut self, name: &str, sig: ClosureSig, span: Span) {
+        let kind = sig.kind;
+        // Generic instances re-check the same body; impls exist already
+        if self.closures.insert(name.to_string(), sig).is_some() {
+            return;
+        }
+        let ty = self.to_ast_type(
+            &ResolvedType::Named {
+                name: name.to_string(),
+                generics: Vec::new(),
+            },
+            &[],
+        );
+        for fn_trait in [ClosureKind::FnOnce, ClosureKind::FnMut, ClosureKind::Fn] {
+            if !kind.implements(fn_trait) {
+                continue;
+            }
+            let Some(trait_id) = self
+                .trait_solver
+                .find_trait(fn_trait.trait_name())
+                .map(|t| t.id)
+            else {
+                continue;
+            };
+            let trait_ref = traits::TraitRef::new(trait_id, fn_trait.trait_name());
+            let impl_def = traits::TraitImpl::new(trait_ref, ty.clone(), traits::ImplId(0), span);
+            if let Err(error) = self.trait_solver.register_impl(impl_def) {
+                self.errors.push(TypeError::Trait { error });
+            }
+        }
+    }
+
+    /// Parameters and return type of a called value, if it is callable
+    ///
+    /// Closures and `kāryakrama(..)` values have their own signature. A
+    /// generic parameter bounded by an Fn-family trait, or a value of still
+    /// unknown type, is given fresh types for `arity` arguments.
+    fn callable_signature(
+        &mut self,
+        ty: &ResolvedType,
+        arity: usize,
+    ) -> Option<(Vec<(String, ResolvedType)>, ResolvedType)> {
+        let ty = self.inference.apply(ty);
+        match &ty {
+            ResolvedType::Named { name, .. } if self.closures.contains_key(name) => {
+                let sig = &self.closures[name];
+                Some((sig.params.clone(), sig.return_type.clone()))
+            }
+            ResolvedType::Function {
+                params,
+                return_type,
+            } => Some((
+                params
+                    .iter()
+                    .enumerate()
+                    .map(|(i, ty)| (i.to_string(), ty.clone()))
+                    .collect(),
+                (**return_type).clone(),
+            )),
+            ResolvedType::Named { name, generics } if generics.is_empty() => {
+                let fn_once = self.trait_solver.find_trait("FnOnce")?.id;
+                let bounded = self
+                    .generic_bounds
+                    .get(name)?
+                    .iter()
+                    .any(|&id| self.trait_implies(id, fn_once));
+                bounded.then(|| self.fresh_signature(arity))
+            }
+            ResolvedType::TypeVar(_) => {
+                let (params, return_type) = self.fresh_signature(arity);
+                let function = ResolvedType::Function {
+                    params: params.iter().map(|(_, ty)| ty.clone()).collect(),
+                    return_type: Box::new(return_type.clone()),
+                };
+                self.inference.unify(&ty, &function).ok()?;
+                Some((params, return_type))
+            }
+            _ => None,
+        }
+    }
+
+    /// Fresh parameter and return types for a call of unknown signature
+    fn fresh_signature(&mut self, arity: usize) -> (Vec<(String, ResolvedType)>, ResolvedType) {
+        let params = (0..arity)
+            .map(|i| (i.to_string(), self.inference.fresh_type_var()))
+            .collect();
+        (params, self.inference.fresh_type_var())
+    }
+
+    /// Check the arguments of a call against the callee's parameters
+    fn check_call_args(
+        &mut self,
+        function: &str,
+        params: &[(String, ResolvedType)],
+        args: &[Expr],
+        span: Span,
+    ) {
+        if args.len() != params.len() {
+            self.errors.push(TypeError::ArityMismatch {
+                function: function.to_string(),
+                expected: params.len(),
+                found: args.len(),
+                span: Some(span),
+            });
+        }
+        for (arg, (param_name, param_ty)) in args.iter().zip(params) {
+            let arg_info = self.infer_expr(arg);
+            if self.coerce_value(arg, &arg_info.ty, param_ty).is_err() {
+                self.errors.push(TypeError::ArgumentMismatch {
+                    function: function.to_string(),
+                    param: param_name.clone(),
+                    expected: self.inference.apply(param_ty),
+                    found: self.inference.apply(&arg_info.ty),
+                    span: Some(span),
+                });
+            }
+        }
+        for arg in args.iter().skip(params.len()) {
+            self.infer_expr(arg);
+        }
+    }
+
+    /// Unify a value's type with the type expected of it
+    ///
+    /// Like unification, except that a closure is accepted where a
+    /// `kāryakrama(..)` type of the same shape is expected.
+    fn coerce(
+        &mut self,
+        found: &ResolvedType,
+        expected: &ResolvedType,
+    ) -> Result<(), UnificationError> {
+        let found = self.inference.apply(found);
+        let expected = self.inference.apply(expected);
+        if let (
+            ResolvedType::Named { name, .. },
+            ResolvedType::Function {
+                params,
+                return_type,
+            },
+        ) = (&found, &expected)
+        {
+            if let Some(sig) = self.closures.get(name).cloned() {
+                if sig.params.len() != params.len() {
+                    return Err(UnificationError::ArityMismatch {
+                        expected: params.len(),
+                        found: sig.params.len(),
+                    });
+                }
+                for ((_, have), want) in sig.params.iter().zip(params) {
+                    self.inference.unify(have, want)?;
+                }
+                return self.inference.unify(&sig.return_type, return_type);
+            }
+        }
+        self.inference.unify(&found, &expected)
+    }
+
     /// Resolve AST type to ResolvedType
     fn resolve_ast_type(&mut self, ty: &Type) -> ResolvedType {
         match ty {
-            Type::Named { name, generics, .. } => {
+            Type::Named {
+                name,
+                generics,
+                affixes,
+            } => {
+                let defined_name = self
+                    .type_paths
+                    .get(&name.name)
+                    .cloned()
+                    .unwrap_or_else(|| name.name.clone());
+                let type_name = super::prelude::alias(&defined_name).unwrap_or(&defined_name);
                 // Check for primitive types
-                match name.name.as_str() {
+                let resolved = match type_name {
                     "i8" | "saṅkhyā8" => ResolvedType::Int8,
                     "i16" | "saṅkhyā16" => ResolvedType::Int16,
                     "i32" | "saṅkhyā32" | "saṅkhyā" => ResolvedType::Int32,
@@ -1779,14 +3823,49 @@ impl TypeChecker {
                     "str" | "sūtra" => ResolvedType::String,
                     "String" | "Sūtra" => ResolvedType::String,
                     "!" | "kadāpi_na" => ResolvedType::Never,
+                    "sūci" | "Sūci" => {
+                        let element = match generics.first() {
+                            Some(element) => self.resolve_ast_type(element),
+                            None => self.inference.fresh_type_var(),
+                        };
+                        suci_type(element)
+                    }
+                    "Vikalpa" | "Phala" => {
+                        // Generic arguments left out are inferred
+                        let arity = self
+                            .type_defs
+                            .get(type_name)
+                            .map_or(0, |def| def.generics.len());
+                        let generics = (0..arity)
+                            .map(|i| match generics.get(i) {
+                                Some(generic) => self.resolve_ast_type(generic),
+                                None => self.inference.fresh_type_var(),
+                            })
+                            .collect();
+                        ResolvedType::Named {
+                            name: type_name.to_string(),
+                            generics,
+                        }
+                    }
+                    "Self" if self.self_type.is_some() => self.self_type.clone().unwrap(),
+                    assoc
+                        if assoc.starts_with("Self::")
+                            && self.assoc_types.contains_key(&assoc["Self::".len()..]) =>
+                    {
+                        self.assoc_types[&assoc["Self::".len()..]].clone()
+                    }
                     _ => {
                         let resolved_generics: Vec<ResolvedType> =
                             generics.iter().map(|g| self.resolve_ast_type(g)).collect();
                         ResolvedType::Named {
-                            name: name.name.clone(),
+                            name: defined_name.clone(),
                             generics: resolved_generics,
                         }
                     }
+                };
+                match affixes.type_width() {
+                    Some(width) => self.with_width(resolved, width, name.span),
+                    None => resolved,
                 }
             }
             Type::Function {
@@ -1801,7 +3880,7 @@ impl TypeChecker {
                     return_type: Box::new(resolved_return),
                 }
             }
-            Type::Array { element, size } => {
+            Type::Array { element, size, .. } => {
                 let resolved_element = self.resolve_ast_type(element);
                 ResolvedType::Array {
                     element: Box::new(resolved_element),
@@ -1825,10 +3904,108 @@ impl TypeChecker {
                     lifetime: *lifetime,
                 }
             }
+            // Const evaluation has reduced every argument it could
+            Type::Const(_) => match ty.const_value() {
+                Some(value) => ResolvedType::Const(value),
+                None => ResolvedType::Error,
+            },
             Type::Inferred => self.inference.fresh_type_var(),
         }
     }
 
+    /// `ty` at the width a `-t8`..`-t64`, `-f32` or `-f64` affix gives it
+    ///
+    /// An integer keeps its signedness. A width that does not suit the
+    /// type is an error.
+    fn with_width(&mut self, ty: ResolvedType, width: Affix, span: Span) -> ResolvedType {
+        use ResolvedType::*;
+        match (&ty, width) {
+            (Int8 | Int16 | Int32 | Int64, Affix::T8) => Int8,
+            (Int8 | Int16 | Int32 | Int64, Affix::T16) => Int16,
+            (Int8 | Int16 | Int32 | Int64, Affix::T32) => Int32,
+            (Int8 | Int16 | Int32 | Int64, Affix::T64) => Int64,
+            (UInt8 | UInt16 | UInt32 | UInt64, Affix::T8) => UInt8,
+            (UInt8 | UInt16 | UInt32 | UInt64, Affix::T16) => UInt16,
+            (UInt8 | UInt16 | UInt32 | UInt64, Affix::T32) => UInt32,
+            (UInt8 | UInt16 | UInt32 | UInt64, Affix::T64) => UInt64,
+            (Float32 | Float64, Affix::F32) => Float32,
+            (Float32 | Float64, Affix::F64) => Float64,
+            (Bool, Affix::T1) => Bool,
+            (TypeVar(_) | Unknown | Error, _) => ty,
+            _ => {
+                // A signature's types are resolved more than once
+                let reported = self.errors.iter().any(|error| {
+                    matches!(error, TypeError::InvalidWidth { span: at, .. } if *at == Some(span))
+                });
+                if !reported {
+                    self.errors.push(TypeError::InvalidWidth {
+                        width,
+                        ty: ty.clone(),
+                        span: Some(span),
+                    });
+                }
+                ty
+            }
+        }
+    }
+
+    /// Coerce `value`, of type `found`, to the type expected of it
+    ///
+    /// An integer literal takes any integer type it fits in, and a number
+    /// converts implicitly to a type holding every value of its own. A
+    /// narrowing conversion is an error, as it needs `as`; anything else
+    /// coerces as usual.
+    fn coerce_value(
+        &mut self,
+        value: &Expr,
+        found: &ResolvedType,
+        expected: &ResolvedType,
+    ) -> Result<(), UnificationError> {
+        let from = self.inference.apply(found);
+        let to = self.inference.apply(expected);
+        if is_integer_type(&to) {
+            if let Some(n) = constant_int(value) {
+                self.check_literal_fits(n, &to, value.span());
+                return Ok(());
+            }
+        }
+        let integers = is_integer_type(&from) && is_integer_type(&to);
+        let floats = is_float_type(&from) && is_float_type(&to);
+        if (integers || floats) && from != to {
+            if floats && matches!(value, Expr::Literal(Literal::Float(_))) {
+                return Ok(());
+            }
+            if !widens(&from, &to) {
+                self.errors.push(TypeError::LossyConversion {
+                    from,
+                    to,
+                    span: Some(value.span()),
+                });
+            }
+            return Ok(());
+        }
+        self.coerce(found, expected)
+    }
+
+    /// Whether `ty` is known to be an integer or float type
+    fn is_numeric(&self, ty: &ResolvedType) -> bool {
+        let ty = self.inference.apply(ty);
+        is_integer_type(&ty) || is_float_type(&ty)
+    }
+
+    /// Report integer constant `n` if integer type `ty` cannot hold it
+    fn check_literal_fits(&mut self, n: i64, ty: &ResolvedType, span: Span) {
+        if let Some((min, max)) = integer_range(ty) {
+            if !(min..=max).contains(&(n as i128)) {
+                self.errors.push(TypeError::LiteralOutOfRange {
+                    value: n,
+                    ty: ty.clone(),
+                    span: Some(span),
+                });
+            }
+        }
+    }
+
     /// Infer type from literal
     fn infer_literal(&self, lit: &Literal) -> ResolvedType {
         match lit {
@@ -1900,9 +4077,9 @@ impl TypeChecker {
         match op {
             UnaryOp::Neg => operand.clone(),
             UnaryOp::Not => ResolvedType::Bool,
-            UnaryOp::Ref => ResolvedType::Reference {
+            UnaryOp::Ref | UnaryOp::RefMut => ResolvedType::Reference {
                 inner: Box::new(operand.clone()),
-                mutable: false,
+                mutable: op == UnaryOp::RefMut,
                 lifetime: None,
             },
             UnaryOp::Deref => {
@@ -1917,11 +4094,21 @@ impl TypeChecker {
 
     /// Look up field type in a struct
     fn lookup_field_type(&self, ty: &ResolvedType, field_name: &str) -> ResolvedType {
-        if let ResolvedType::Named { name, .. } = ty {
+        if let ResolvedType::Reference { inner, .. } = ty {
+            return self.lookup_field_type(inner, field_name);
+        }
+        if let ResolvedType::Named { name, generics } = ty {
             if let Some(typedef) = self.type_defs.get(name) {
                 if let TypeBodyResolved::Struct(fields) = &typedef.body {
                     if let Some((_, field_ty)) = fields.iter().find(|(n, _)| n == field_name) {
-                        return field_ty.clone();
+                        // `Peti<saṅkhyā>` has the fields of `Peti<T>` at `T = saṅkhyā`
+                        let subst: HashMap<&str, &ResolvedType> = typedef
+                            .generics
+                            .iter()
+                            .map(String::as_str)
+                            .zip(generics)
+                            .collect();
+                        return substitute_type_params(field_ty, &subst);
                     }
                 }
             }
@@ -1974,6 +4161,289 @@ impl TypeChecker {
         }
     }
 
+    /// Look up a method through the trait solver (Guṇa Anveṣaṇa)
+    ///
+    /// Generic parameters are resolved through their declared bounds; concrete
+    /// types through their inherent and trait impls, including default methods.
+    /// The type parameters of a generic impl take the receiver's type
+    /// arguments, and those of a generic method fresh type variables.
+    fn lookup_trait_method(
+        &mut self,
+        receiver: &ResolvedType,
+        method_name: &str,
+    ) -> Option<(MethodSig, traits::MethodReceiver, MethodUse)> {
+        let mut receiver = self.inference.apply(receiver);
+        while let ResolvedType::Reference { inner, .. } = receiver {
+            receiver = *inner;
+        }
+        let self_ast = self.to_ast_type(&receiver, &[]);
+
+        // Generic parameter (or Self inside a trait): consult its bounds
+        if let ResolvedType::Named { name, generics } = &receiver {
+            if generics.is_empty() {
+                if let Some(bounds) = self.generic_bounds.get(name).cloned() {
+                    for trait_id in bounds {
+                        if let Some(method) = self.find_bound_method(trait_id, method_name) {
+                            let sig = self.instantiate_method_sig(
+                                method_name,
+                                &method.params,
+                                &method.return_type,
+                                &self_ast,
+                                &[],
+                                &method.receiver,
+                                method.span,
+                            );
+                            let found = MethodUse {
+                                owner: receiver.clone(),
+                                generics: Vec::new(),
+                                type_args: Vec::new(),
+                            };
+                            return Some((sig, method.receiver, found));
+                        }
+                    }
+                    return None;
+                }
+            }
+        }
+
+        let resolved = self.trait_solver.resolve_method(&self_ast, method_name)?;
+        let assoc: Vec<(String, Type)> = resolved
+            .trait_id
+            .and_then(|id| self.trait_solver.find_impl(&self_ast, id))
+            .map(|imp| {
+                imp.associated_types
+                    .iter()
+                    .map(|a| (a.name.clone(), a.ty.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut sig = self.instantiate_method_sig(
+            method_name,
+            &resolved.params,
+            &resolved.return_type,
+            &self_ast,
+            &assoc,
+            &resolved.receiver,
+            None,
+        );
+
+        // `kriyānvaya<T> Peti<T>` called on a `Peti<saṅkhyā>` binds `T`
+        let mut bindings = HashMap::new();
+        let mut owner = receiver.clone();
+        if let Some((params, pattern)) = self.impl_params.get(&resolved.impl_id).cloned() {
+            bind_type_params(&pattern, &receiver, &params, &mut bindings);
+            for param in &params {
+                if !bindings.contains_key(param) {
+                    bindings.insert(param.clone(), self.inference.fresh_type_var());
+                }
+            }
+            let subst = bindings.iter().map(|(k, v)| (k.as_str(), v)).collect();
+            owner = substitute_type_params(&pattern, &subst);
+        }
+        let generics: Vec<(String, Vec<TypeBound>)> = resolved
+            .generics
+            .iter()
+            .map(|g| (g.name.name.clone(), g.bounds.clone()))
+            .collect();
+        let type_args: Vec<ResolvedType> = generics
+            .iter()
+            .map(|_| self.inference.fresh_type_var())
+            .collect();
+        for ((name, _), arg) in generics.iter().zip(&type_args) {
+            bindings.insert(name.clone(), arg.clone());
+        }
+        if !bindings.is_empty() {
+            let subst: HashMap<&str, &ResolvedType> =
+                bindings.iter().map(|(k, v)| (k.as_str(), v)).collect();
+            for (_, ty) in &mut sig.params {
+                *ty = substitute_type_params(ty, &subst);
+            }
+            sig.return_type = substitute_type_params(&sig.return_type, &subst);
+        }
+        let found = MethodUse {
+            owner,
+            generics,
+            type_args,
+        };
+        Some((sig, resolved.receiver, found))
+    }
+
+    /// Record a call of a method found through an impl, checking the
+    /// bounds on the method's type arguments
+    fn record_method_call(&mut self, found: MethodUse, name: &str, call: &Expr, span: Span) {
+        if !found.type_args.is_empty() {
+            self.check_type_arg_bounds(&found.generics, &found.type_args, span);
+        }
+        let function = name.rsplit_once("::").map_or(name, |(_, m)| m);
+        self.instantiations.push(CallRecord {
+            function: function.to_string(),
+            owner: Some(found.owner),
+            type_args: found.type_args,
+            call: CallId::of(call),
+            span,
+        });
+    }
+
+    /// Find a method in a trait or any of its supertraits
+    fn find_bound_method(
+        &self,
+        trait_id: traits::TraitId,
+        method_name: &str,
+    ) -> Option<BoundMethod> {
+        let trait_def = self.trait_solver.get_trait(trait_id)?;
+        if let Some(method) = trait_def.find_method(method_name) {
+            return Some(BoundMethod {
+                receiver: method.receiver.clone(),
+                params: method.params.clone(),
+                return_type: method.return_type.clone(),
+                span: Some(method.span),
+            });
+        }
+        trait_def
+            .supertraits
+            .iter()
+            .find_map(|s| self.find_bound_method(s.trait_ref.trait_id, method_name))
+    }
+
+    /// Resolve a `Type::function` path call through impls
+    fn lookup_path_function(&mut self, path: &str) -> Option<(FunctionSig, MethodUse)> {
+        let (type_part, method_name) = path.rsplit_once("::")?;
+        let owner = self.resolve_ast_type(&Type::Named {
+            name: Identifier {
+                name: type_part.to_string(),
+                affixes: Default::default(),
+                span: Span::dummy(),
+            },
+            generics: Vec::new(),
+            affixes: Default::default(),
+        });
+        let (sig, receiver, found) = self.lookup_trait_method(&owner, method_name)?;
+        let owner = found.owner.clone();
+
+        // Methods called by path take their receiver as the first argument
+        let mut params = sig.params;
+        if receiver.is_method() {
+            let self_param = match receiver {
+                traits::MethodReceiver::Ref | traits::MethodReceiver::RefMut => {
+                    ResolvedType::Reference {
+                        inner: Box::new(owner),
+                        mutable: receiver == traits::MethodReceiver::RefMut,
+                        lifetime: None,
+                    }
+                }
+                _ => owner,
+            };
+            params.insert(0, ("sva".to_string(), self_param));
+        }
+
+        let sig = FunctionSig {
+            name: path.to_string(),
+            params,
+            return_type: sig.return_type,
+            span: sig.span,
+            variadic: false,
+            generics: Vec::new(),
+        };
+        Some((sig, found))
+    }
+
+    /// Build a method signature with `Self` and `Self::Assoc` substituted
+    #[allow(clippy::too_many_arguments)]
+    fn instantiate_method_sig(
+        &mut self,
+        method_name: &str,
+        params: &[traits::MethodParam],
+        return_type: &Type,
+        self_ty: &Type,
+        assoc: &[(String, Type)],
+        receiver: &traits::MethodReceiver,
+        span: Option<Span>,
+    ) -> MethodSig {
+        let params = params
+            .iter()
+            .map(|p| {
+                let ty = substitute_self(&p.ty, self_ty, assoc);
+                (p.name.clone(), self.resolve_ast_type(&ty))
+            })
+            .collect();
+        let return_type = substitute_self(return_type, self_ty, assoc);
+        MethodSig {
+            name: method_name.to_string(),
+            self_type: match receiver {
+                traits::MethodReceiver::RefMut => SelfType::RefMut,
+                traits::MethodReceiver::Value => SelfType::Value,
+                _ => SelfType::Ref,
+            },
+            params,
+            return_type: self.resolve_ast_type(&return_type),
+            span,
+        }
+    }
+
+    /// Convert a resolved type back to an AST type for the trait solver
+    ///
+    /// Named types listed in `wildcards` (impl generics) become `Type::Inferred`
+    /// so that they match any concrete argument.
+    fn to_ast_type(&self, ty: &ResolvedType, wildcards: &[&str]) -> Type {
+        let named = |name: String, generics: Vec<Type>| Type::Named {
+            name: Identifier {
+                name,
+                affixes: Default::default(),
+                span: Span::dummy(),
+            },
+            generics,
+            affixes: Default::default(),
+        };
+        match self.inference.apply(ty) {
+            ResolvedType::Named { name, .. } if wildcards.contains(&name.as_str()) => {
+                Type::Inferred
+            }
+            ResolvedType::Named { name, generics } => named(
+                name,
+                generics
+                    .iter()
+                    .map(|g| self.to_ast_type(g, wildcards))
+                    .collect(),
+            ),
+            ResolvedType::Reference {
+                inner,
+                mutable,
+                lifetime,
+            } => Type::Reference {
+                inner: Box::new(self.to_ast_type(&inner, wildcards)),
+                mutable,
+                lifetime,
+            },
+            ResolvedType::Array { element, size } => Type::Array {
+                element: Box::new(self.to_ast_type(&element, wildcards)),
+                size,
+                length: None,
+            },
+            ResolvedType::Tuple(elems) => Type::Tuple(
+                elems
+                    .iter()
+                    .map(|e| self.to_ast_type(e, wildcards))
+                    .collect(),
+            ),
+            ResolvedType::Function {
+                params,
+                return_type,
+            } => Type::Function {
+                params: params
+                    .iter()
+                    .map(|p| self.to_ast_type(p, wildcards))
+                    .collect(),
+                return_type: Box::new(self.to_ast_type(&return_type, wildcards)),
+            },
+            ResolvedType::Unit => named("()".to_string(), Vec::new()),
+            ResolvedType::Const(value) => Type::Const(Box::new(Expr::Literal(Literal::Int(value)))),
+            ResolvedType::TypeVar(_) | ResolvedType::Unknown | ResolvedType::Error => {
+                Type::Inferred
+            }
+            other => named(self.get_type_name(&other), Vec::new()),
+        }
+    }
+
     /// Builtin String methods
     fn lookup_string_method(&self, method_name: &str) -> Option<MethodSig> {
         match method_name {
@@ -2168,7 +4638,389 @@ impl TypeChecker {
     }
 }
 
+/// One use of a method found through an impl
+struct MethodUse {
+    /// Receiver type, with the impl's type parameters bound
+    owner: ResolvedType,
+    /// Type parameters of the method itself, with their bounds
+    generics: Vec<(String, Vec<TypeBound>)>,
+    /// Type variables standing for them at this use
+    type_args: Vec<ResolvedType>,
+}
+
+/// Signature of a method found through a generic bound
+struct BoundMethod {
+    receiver: traits::MethodReceiver,
+    params: Vec<traits::MethodParam>,
+    return_type: Type,
+    span: Option<Span>,
+}
+
+/// The `Self` type as written in source
+fn self_named_type() -> Type {
+    Type::Named {
+        name: Identifier {
+            name: "Self".to_string(),
+            affixes: Default::default(),
+            span: Span::dummy(),
+        },
+        generics: Vec::new(),
+        affixes: Default::default(),
+    }
+}
+
+/// The unit type, used for methods without a declared return type
+fn unit_type() -> Type {
+    Type::Named {
+        name: Identifier {
+            name: "()".to_string(),
+            affixes: Default::default(),
+            span: Span::dummy(),
+        },
+        generics: Vec::new(),
+        affixes: Default::default(),
+    }
+}
+
+/// Convert an AST receiver to the solver's receiver kind
+fn lower_receiver(receiver: Option<SelfParam>) -> traits::MethodReceiver {
+    match receiver {
+        None => traits::MethodReceiver::None,
+        Some(SelfParam::Value) => traits::MethodReceiver::Value,
+        Some(SelfParam::Ref) => traits::MethodReceiver::Ref,
+        Some(SelfParam::RefMut) => traits::MethodReceiver::RefMut,
+    }
+}
+
+/// Convert AST parameters to solver method parameters
+fn lower_params(params: &[Parameter]) -> Vec<traits::MethodParam> {
+    params
+        .iter()
+        .map(|p| traits::MethodParam {
+            name: p.name.name.clone(),
+            ty: p.ty.clone(),
+            span: p.span,
+        })
+        .collect()
+}
+
+/// Split `Enum::Variant` into its enum and variant names
+fn split_path(name: &str) -> (Option<&str>, &str) {
+    match name.rsplit_once("::") {
+        Some((owner, last)) => (Some(owner), last),
+        None => (None, name),
+    }
+}
+
+fn describe_path(enum_name: Option<&Identifier>, variant: &Identifier) -> String {
+    match enum_name {
+        Some(owner) => format!("{}::{}", owner.name, variant.name),
+        None => variant.name.clone(),
+    }
+}
+
+fn is_rest(pattern: &Pattern) -> bool {
+    matches!(pattern, Pattern::Rest)
+}
+
+/// `Sūci<element>`
+fn suci_type(element: ResolvedType) -> ResolvedType {
+    ResolvedType::Named {
+        name: "Sūci".to_string(),
+        generics: vec![element],
+    }
+}
+
+fn is_suci_type(ty: &ResolvedType) -> bool {
+    matches!(ty, ResolvedType::Named { name, .. } if name == "Sūci")
+}
+
+/// The type behind any number of references
+fn strip_references(ty: ResolvedType) -> ResolvedType {
+    match ty {
+        ResolvedType::Reference { inner, .. } => strip_references(*inner),
+        ty => ty,
+    }
+}
+
+fn is_integer_type(ty: &ResolvedType) -> bool {
+    matches!(
+        ty,
+        ResolvedType::Int8
+            | ResolvedType::Int16
+            | ResolvedType::Int32
+            | ResolvedType::Int64
+            | ResolvedType::UInt8
+            | ResolvedType::UInt16
+            | ResolvedType::UInt32
+            | ResolvedType::UInt64
+    )
+}
+
+fn is_float_type(ty: &ResolvedType) -> bool {
+    matches!(ty, ResolvedType::Float32 | ResolvedType::Float64)
+}
+
+/// Smallest and largest value of an integer type
+fn integer_range(ty: &ResolvedType) -> Option<(i128, i128)> {
+    let (bits, signed) = match ty {
+        ResolvedType::Int8 => (8, true),
+        ResolvedType::Int16 => (16, true),
+        ResolvedType::Int32 => (32, true),
+        ResolvedType::Int64 => (64, true),
+        ResolvedType::UInt8 => (8, false),
+        ResolvedType::UInt16 => (16, false),
+        ResolvedType::UInt32 => (32, false),
+        ResolvedType::UInt64 => (64, false),
+        _ => return None,
+    };
+    Some(if signed {
+        (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+    } else {
+        (0, (1i128 << bits) - 1)
+    })
+}
+
+/// Whether every value of numeric type `from` is a value of `to`
+fn widens(from: &ResolvedType, to: &ResolvedType) -> bool {
+    match (integer_range(from), integer_range(to)) {
+        (Some((from_min, from_max)), Some((to_min, to_max))) => {
+            to_min <= from_min && from_max <= to_max
+        }
+        _ => matches!((from, to), (ResolvedType::Float32, ResolvedType::Float64)),
+    }
+}
+
+/// Value of an integer literal, or of arithmetic on integer literals,
+/// which takes whatever integer type it fits in
+fn constant_int(expr: &Expr) -> Option<i64> {
+    match expr {
+        Expr::Literal(Literal::Int(n)) => Some(*n),
+        Expr::Unary {
+            op: UnaryOp::Neg,
+            operand,
+            ..
+        } => constant_int(operand)?.checked_neg(),
+        Expr::Binary {
+            left, op, right, ..
+        } => {
+            let (left, right) = (constant_int(left)?, constant_int(right)?);
+            match op {
+                BinaryOp::Add => left.checked_add(right),
+                BinaryOp::Sub => left.checked_sub(right),
+                BinaryOp::Mul => left.checked_mul(right),
+                BinaryOp::Div => left.checked_div(right),
+                BinaryOp::Mod => left.checked_rem(right),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Whether `as` converts a `from` value to `to`
+///
+/// Numbers convert to one another, truncating or extending, and so do
+/// `bool` and `char` to integers and `u8` to `char`.
+fn is_valid_cast(from: &ResolvedType, to: &ResolvedType) -> bool {
+    use ResolvedType::*;
+    let numeric = |ty: &ResolvedType| is_integer_type(ty) || is_float_type(ty);
+    from == to
+        || (numeric(from) && numeric(to))
+        || (matches!(from, Bool | Char) && is_integer_type(to))
+        || matches!((from, to), (UInt8, Char))
+        || matches!(from, TypeVar(_) | Unknown | Error | Never)
+        || matches!(to, TypeVar(_) | Unknown | Error)
+}
+
+/// Replace the type parameters of a generic signature with their arguments
+pub(super) fn substitute_type_params(
+    ty: &ResolvedType,
+    subst: &HashMap<&str, &ResolvedType>,
+) -> ResolvedType {
+    match ty {
+        ResolvedType::Named { name, generics } => match subst.get(name.as_str()) {
+            Some(arg) if generics.is_empty() => (*arg).clone(),
+            _ => ResolvedType::Named {
+                name: name.clone(),
+                generics: generics
+                    .iter()
+                    .map(|g| substitute_type_params(g, subst))
+                    .collect(),
+            },
+        },
+        ResolvedType::Function {
+            params,
+            return_type,
+        } => ResolvedType::Function {
+            params: params
+                .iter()
+                .map(|p| substitute_type_params(p, subst))
+                .collect(),
+            return_type: Box::new(substitute_type_params(return_type, subst)),
+        },
+        ResolvedType::Reference {
+            inner,
+            mutable,
+            lifetime,
+        } => ResolvedType::Reference {
+            inner: Box::new(substitute_type_params(inner, subst)),
+            mutable: *mutable,
+            lifetime: *lifetime,
+        },
+        ResolvedType::Array { element, size } => ResolvedType::Array {
+            element: Box::new(substitute_type_params(element, subst)),
+            size: *size,
+        },
+        ResolvedType::Tuple(elems) => ResolvedType::Tuple(
+            elems
+                .iter()
+                .map(|e| substitute_type_params(e, subst))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Bind the type parameters `params` of `pattern` to the parts of
+/// `concrete` in their place
+fn bind_type_params(
+    pattern: &ResolvedType,
+    concrete: &ResolvedType,
+    params: &[String],
+    bindings: &mut HashMap<String, ResolvedType>,
+) {
+    match (pattern, concrete) {
+        (ResolvedType::Named { name, generics }, _)
+            if generics.is_empty() && params.contains(name) =>
+        {
+            bindings
+                .entry(name.clone())
+                .or_insert_with(|| concrete.clone());
+        }
+        (
+            ResolvedType::Named { name, generics },
+            ResolvedType::Named {
+                name: other,
+                generics: args,
+            },
+        ) if name == other => {
+            for (generic, arg) in generics.iter().zip(args) {
+                bind_type_params(generic, arg, params, bindings);
+            }
+        }
+        (ResolvedType::Reference { inner, .. }, ResolvedType::Reference { inner: other, .. }) => {
+            bind_type_params(inner, other, params, bindings)
+        }
+        (ResolvedType::Array { element, .. }, ResolvedType::Array { element: other, .. }) => {
+            bind_type_params(element, other, params, bindings)
+        }
+        (ResolvedType::Tuple(elems), ResolvedType::Tuple(others)) => {
+            for (elem, other) in elems.iter().zip(others) {
+                bind_type_params(elem, other, params, bindings);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace `Self` and `Self::Assoc` in a trait signature type
+///
+/// Associated types without a binding become `Receiver::Assoc` paths.
+fn substitute_self(ty: &Type, self_ty: &Type, assoc: &[(String, Type)]) -> Type {
+    match ty {
+        Type::Named { name, .. } if name.name == "Self" => self_ty.clone(),
+        Type::Named { name, .. } if name.name.starts_with("Self::") => {
+            let assoc_name = &name.name["Self::".len()..];
+            if let Some((_, bound)) = assoc.iter().find(|(n, _)| n == assoc_name) {
+                return bound.clone();
+            }
+            let owner = match self_ty {
+                Type::Named { name, .. } => name.name.clone(),
+                _ => "Self".to_string(),
+            };
+            Type::Named {
+                name: Identifier {
+                    name: format!("{}::{}", owner, assoc_name),
+                    affixes: Default::default(),
+                    span: name.span,
+                },
+                generics: Vec::new(),
+                affixes: Default::default(),
+            }
+        }
+        Type::Named {
+            name,
+            generics,
+            affixes,
+        } => Type::Named {
+            name: name.clone(),
+            generics: generics
+                .iter()
+                .map(|g| substitute_self(g, self_ty, assoc))
+                .collect(),
+            affixes: affixes.clone(),
+        },
+        Type::Reference {
+            inner,
+            mutable,
+            lifetime,
+        } => Type::Reference {
+            inner: Box::new(substitute_self(inner, self_ty, assoc)),
+            mutable: *mutable,
+            lifetime: *lifetime,
+        },
+        Type::Array {
+            element,
+            size,
+            length,
+        } => Type::Array {
+            element: Box::new(substitute_self(element, self_ty, assoc)),
+            size: *size,
+            length: length.clone(),
+        },
+        Type::Tuple(elems) => Type::Tuple(
+            elems
+                .iter()
+                .map(|e| substitute_self(e, self_ty, assoc))
+                .collect(),
+        ),
+        Type::Function {
+            params,
+            return_type,
+        } => Type::Function {
+            params: params
+                .iter()
+                .map(|p| substitute_self(p, self_ty, assoc))
+                .collect(),
+            return_type: Box::new(substitute_self(return_type, self_ty, assoc)),
+        },
+        Type::Const(_) | Type::Inferred => ty.clone(),
+    }
+}
+
 /// Check if operator is arithmetic
+/// Whether an integer literal operand of `op` takes the other's type
+fn takes_literal(op: BinaryOp) -> bool {
+    is_arithmetic_op(op)
+        || is_assignment_op(op)
+        || matches!(
+            op,
+            BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge
+        )
+}
+
+fn is_assignment_op(op: BinaryOp) -> bool {
+    matches!(
+        op,
+        BinaryOp::Assign
+            | BinaryOp::AddAssign
+            | BinaryOp::SubAssign
+            | BinaryOp::MulAssign
+            | BinaryOp::DivAssign
+    )
+}
+
 fn is_arithmetic_op(op: BinaryOp) -> bool {
     matches!(
         op,
@@ -2274,6 +5126,118 @@ pub enum TypeError {
         ty: ResolvedType,
         span: Option<Span>,
     },
+
+    /// Trait or impl error from the trait solver (Guṇa Doṣa)
+    Trait { error: traits::TraitError },
+
+    /// Constant whose value depends on itself (Cakra Sthira)
+    CyclicConstant { name: String, span: Option<Span> },
+
+    /// Field or method private to another module (Gupya Sadasya)
+    PrivateMember {
+        owner: String,
+        member: String,
+        span: Option<Span>,
+    },
+
+    /// Match that does not recognize every value (Apūrṇa Pratyabhijñā)
+    NonExhaustiveMatch { missing: String, span: Option<Span> },
+
+    /// Match arm that no value can reach (Aprāpya Śākhā)
+    UnreachableArm { pattern: String, span: Option<Span> },
+
+    /// `break` or `continue` outside of any loop (Cala Bāhya)
+    OutsideLoop { keyword: String, span: Option<Span> },
+
+    /// `break 'label` naming no enclosing loop (Ajñāta Cihna)
+    UnknownLabel { label: String, span: Option<Span> },
+
+    /// `break` with a value out of a loop other than `cala { }`
+    BreakWithValue { span: Option<Span> },
+
+    /// Constant index past the end of a fixed array (Asipatravana)
+    IndexOutOfBounds {
+        index: i64,
+        len: usize,
+        span: Option<Span>,
+    },
+
+    /// `cala x : e` over a value with no `next` returning `Vikalpa` (Apunarāvartya)
+    NotIterable {
+        ty: ResolvedType,
+        span: Option<Span>,
+    },
+
+    /// `?` on a value that is neither `Phala` nor `Vikalpa` (Apraśnya)
+    NotTryable {
+        ty: ResolvedType,
+        span: Option<Span>,
+    },
+
+    /// `?` in a function returning neither `Phala` nor `Vikalpa`, reported
+    /// at the function's signature (Phala Bāhya)
+    TryOutsidePhala {
+        function: String,
+        returns: ResolvedType,
+        span: Option<Span>,
+    },
+
+    /// `?` inside a closure, which it cannot return from
+    TryInClosure { span: Option<Span> },
+
+    /// `?` on an error with no `From` conversion into the function's error
+    /// type (Apariṇāmya)
+    MissingConversion {
+        from: ResolvedType,
+        to: ResolvedType,
+        span: Option<Span>,
+    },
+
+    /// Value crossing into a `saha` task that its type does not allow,
+    /// and why (Asūtra)
+    NotThreadSafe {
+        value: String,
+        crossing: Crossing,
+        reason: String,
+        span: Option<Span>,
+    },
+
+    /// Width affix on a type it does not suit (Ayogya Parimāṇa)
+    InvalidWidth {
+        width: Affix,
+        ty: ResolvedType,
+        span: Option<Span>,
+    },
+
+    /// Sūci of values wider than the word each element is kept in
+    /// (Vistṛta Avayava)
+    WideElement {
+        ty: ResolvedType,
+        words: usize,
+        span: Option<Span>,
+    },
+
+    /// Implicit conversion to a number type that cannot hold every value
+    /// of the source (Hāni Parivartana)
+    LossyConversion {
+        from: ResolvedType,
+        to: ResolvedType,
+        span: Option<Span>,
+    },
+
+    /// Integer literal outside the range of the type it takes (Atisaṅkhyā)
+    LiteralOutOfRange {
+        value: i64,
+        ty: ResolvedType,
+        span: Option<Span>,
+    },
+
+    /// `as` between types it does not convert (Aparivartya)
+    InvalidCast {
+        from: ResolvedType,
+        to: ResolvedType,
+        span: Option<Span>,
+    },
 }
 
 impl TypeError {
@@ -2292,6 +5256,26 @@ impl TypeError {
             TypeError::FieldTypeMismatch { span, .. } => *span,
             TypeError::UnificationFailed { span, .. } => *span,
             TypeError::InfiniteType { span, .. } => *span,
+            TypeError::Trait { error } => Some(error.span()),
+            TypeError::CyclicConstant { span, .. } => *span,
+            TypeError::PrivateMember { span, .. } => *span,
+            TypeError::NonExhaustiveMatch { span, .. } => *span,
+            TypeError::UnreachableArm { span, .. } => *span,
+            TypeError::OutsideLoop { span, .. } => *span,
+            TypeError::UnknownLabel { span, .. } => *span,
+            TypeError::BreakWithValue { span } => *span,
+            TypeError::IndexOutOfBounds { span, .. } => *span,
+            TypeError::NotIterable { span, .. } => *span,
+            TypeError::NotTryable { span, .. } => *span,
+            TypeError::TryOutsidePhala { span, .. } => *span,
+            TypeError::TryInClosure { span } => *span,
+            TypeError::MissingConversion { span, .. } => *span,
+            TypeError::NotThreadSafe { span, .. } => *span,
+            TypeError::InvalidWidth { span, .. } => *span,
+            TypeError::WideElement { span, .. } => *span,
+            TypeError::LossyConversion { span, .. } => *span,
+            TypeError::LiteralOutOfRange { span, .. } => *span,
+            TypeError::InvalidCast { span, .. } => *span,
             TypeError::CannotInfer { .. } => None,
         }
     }
@@ -2401,6 +5385,105 @@ impl TypeError {
             TypeError::InfiniteType { var, ty, .. } => {
                 format!("Infinite type: {} occurs in {:?}", var, ty)
             }
+            TypeError::Trait { error } => error.to_string(),
+            TypeError::CyclicConstant { name, .. } => {
+                format!("Constant '{}' is defined in terms of itself", name)
+            }
+            TypeError::PrivateMember { owner, member, .. } => {
+                format!("`{}` of `{}` is private to its module", member, owner)
+            }
+            TypeError::NonExhaustiveMatch { missing, .. } => {
+                format!(
+                    "Match is not exhaustive: pattern `{}` is not covered",
+                    missing
+                )
+            }
+            TypeError::UnreachableArm { pattern, .. } => {
+                format!(
+                    "Unreachable pattern `{}`: earlier arms match every value it does",
+                    pattern
+                )
+            }
+            TypeError::OutsideLoop { keyword, .. } => {
+                format!("`{}` outside of a loop", keyword)
+            }
+            TypeError::UnknownLabel { label, .. } => {
+                format!("No enclosing loop is labeled `'{}`", label)
+            }
+            TypeError::BreakWithValue { .. } => {
+                "Only `cala { }` loops can break with a value".to_string()
+            }
+            TypeError::IndexOutOfBounds { index, len, .. } => {
+                format!(
+                    "Index {} is out of bounds for an array of length {}",
+                    index, len
+                )
+            }
+            TypeError::NotIterable { ty, .. } => {
+                format!(
+                    "`{}` is not iterable: it has no `next` method returning `Vikalpa`",
+                    ty
+                )
+            }
+            TypeError::NotTryable { ty, .. } => {
+                format!("`?` applies to `Phala` and `Vikalpa` values, not `{}`", ty)
+            }
+            TypeError::TryOutsidePhala {
+                function, returns, ..
+            } => {
+                format!(
+                    "`?` needs function '{}' to return `Phala` or `Vikalpa`, but it returns `{}`",
+                    function, returns
+                )
+            }
+            TypeError::TryInClosure { .. } => "`?` cannot be used inside a closure".to_string(),
+            TypeError::MissingConversion { from, to, .. } => {
+                format!(
+                    "`?` cannot convert error `{}` into `{}`: no `From<{}>` impl for `{}`",
+                    from, to, from, to
+                )
+            }
+            TypeError::NotThreadSafe {
+                value,
+                crossing,
+                reason,
+                ..
+            } => {
+                let what = match crossing {
+                    Crossing::Sent => "sent to",
+                    Crossing::Shared => "shared with",
+                    Crossing::Written => "assigned by",
+                };
+                format!("{} cannot be {} a `saha` task: {}", value, what, reason)
+            }
+            TypeError::InvalidWidth { width, ty, .. } => {
+                let affix = match width {
+                    Affix::T8 => "-t8",
+                    Affix::T16 => "-t16",
+                    Affix::T32 => "-t32",
+                    Affix::T64 => "-t64",
+                    Affix::F32 => "-f32",
+                    Affix::F64 => "-f64",
+                    _ => "-t1",
+                };
+                format!("Width affix `{}` does not apply to `{}`", affix, ty)
+            }
+            TypeError::WideElement { ty, words, .. } => format!(
+                "`Sūci` elements are one word each, but `{}` takes {}",
+                ty, words
+            ),
+            TypeError::LossyConversion { from, to, .. } => {
+                format!(
+                    "Implicit conversion from `{}` to `{}` may lose information; convert with `as`",
+                    from, to
+                )
+            }
+            TypeError::LiteralOutOfRange { value, ty, .. } => {
+                format!("Literal `{}` does not fit in `{}`", value, ty)
+            }
+            TypeError::InvalidCast { from, to, .. } => {
+                format!("Cannot cast `{}` as `{}`", from, to)
+            }
         }
     }
 }
@@ -2427,6 +5510,7 @@ impl std::fmt::Display for ResolvedType {
             ResolvedType::Char => write!(f, "char"),
             ResolvedType::String => write!(f, "String"),
             ResolvedType::Never => write!(f, "!"),
+            ResolvedType::Const(value) => write!(f, "{}", value),
             ResolvedType::TypeVar(v) => write!(f, "{}", v),
             ResolvedType::Named { name, generics } => {
                 write!(f, "{}", name)?;
@@ -2485,6 +5569,20 @@ impl std::fmt::Display for ResolvedType {
     }
 }
 
+/// Identifiers used in a constant's value
+struct ConstantRefs(Vec<String>);
+
+impl AstVisitor for ConstantRefs {
+    type Break = ();
+
+    fn visit_expr(&mut self, expr: &Expr) -> VisitResult<Self::Break> {
+        if let Expr::Identifier(ident) = expr {
+            self.0.push(ident.name.clone());
+        }
+        walk_expr(self, expr)
+    }
+}
+
 impl std::fmt::Display for TypeError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.description())
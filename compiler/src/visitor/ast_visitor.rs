@@ -36,6 +36,9 @@ pub trait AstVisitor {
             Item::Import(i) => self.visit_import(i),
             Item::Constant(c) => self.visit_constant(c),
             Item::Module(m) => self.visit_module(m),
+            Item::Trait(t) => self.visit_trait(t),
+            Item::Impl(i) => self.visit_impl(i),
+            Item::Error(_) => self.continue_(),
         }
     }
 
@@ -97,6 +100,31 @@ pub trait AstVisitor {
         self.continue_()
     }
 
+    /// Visit a trait definition
+    fn visit_trait(&mut self, trait_def: &TraitDef) -> VisitResult<Self::Break> {
+        for method in &trait_def.methods {
+            for param in &method.params {
+                self.visit_parameter(param)?;
+            }
+            if let Some(ret) = &method.return_type {
+                self.visit_type(ret)?;
+            }
+            if let Some(body) = &method.default_body {
+                self.visit_block(body)?;
+            }
+        }
+        self.continue_()
+    }
+
+    /// Visit an impl block
+    fn visit_impl(&mut self, impl_def: &ImplDef) -> VisitResult<Self::Break> {
+        self.visit_type(&impl_def.self_ty)?;
+        for method in &impl_def.methods {
+            self.visit_function(method)?;
+        }
+        self.continue_()
+    }
+
     /// Visit a block
     fn visit_block(&mut self, block: &Block) -> VisitResult<Self::Break> {
         for stmt in &block.stmts {
@@ -107,131 +135,12 @@ pub trait AstVisitor {
 
     /// Visit a statement
     fn visit_stmt(&mut self, stmt: &Stmt) -> VisitResult<Self::Break> {
-        match stmt {
-            Stmt::Let { ty, value, .. } => {
-                if let Some(t) = ty {
-                    self.visit_type(t)?;
-                }
-                if let Some(v) = value {
-                    self.visit_expr(v)?;
-                }
-            }
-            Stmt::Expr(expr) => {
-                self.visit_expr(expr)?;
-            }
-            Stmt::Return { value, .. } => {
-                if let Some(v) = value {
-                    self.visit_expr(v)?;
-                }
-            }
-            Stmt::If {
-                condition,
-                then_block,
-                else_block,
-                ..
-            } => {
-                self.visit_expr(condition)?;
-                self.visit_block(then_block)?;
-                if let Some(eb) = else_block {
-                    self.visit_block(eb)?;
-                }
-            }
-            Stmt::Match {
-                scrutinee, arms, ..
-            } => {
-                self.visit_expr(scrutinee)?;
-                for arm in arms {
-                    self.visit_pattern(&arm.pattern)?;
-                    if let Some(guard) = &arm.guard {
-                        self.visit_expr(guard)?;
-                    }
-                    self.visit_expr(&arm.body)?;
-                }
-            }
-            Stmt::Loop { kind, body, .. } => {
-                match kind {
-                    LoopKind::ForIn { iterable, .. } => {
-                        self.visit_expr(iterable)?;
-                    }
-                    LoopKind::While { condition } => {
-                        self.visit_expr(condition)?;
-                    }
-                    LoopKind::Range { start, end, .. } => {
-                        self.visit_expr(start)?;
-                        self.visit_expr(end)?;
-                    }
-                    LoopKind::Infinite => {}
-                }
-                self.visit_block(body)?;
-            }
-            Stmt::Break { .. } | Stmt::Continue { .. } => {}
-        }
-        self.continue_()
+        walk_stmt(self, stmt)
     }
 
     /// Visit an expression
     fn visit_expr(&mut self, expr: &Expr) -> VisitResult<Self::Break> {
-        match expr {
-            Expr::Literal(_) | Expr::Identifier(_) => {}
-            Expr::Binary { left, right, .. } => {
-                self.visit_expr(left)?;
-                self.visit_expr(right)?;
-            }
-            Expr::Unary { operand, .. } => {
-                self.visit_expr(operand)?;
-            }
-            Expr::Call { callee, args, .. } => {
-                self.visit_expr(callee)?;
-                for arg in args {
-                    self.visit_expr(arg)?;
-                }
-            }
-            Expr::MethodCall { receiver, args, .. } => {
-                self.visit_expr(receiver)?;
-                for arg in args {
-                    self.visit_expr(arg)?;
-                }
-            }
-            Expr::FieldAccess { object, .. } => {
-                self.visit_expr(object)?;
-            }
-            Expr::Index { object, index, .. } => {
-                self.visit_expr(object)?;
-                self.visit_expr(index)?;
-            }
-            Expr::StructConstruct { fields, .. } => {
-                for (_, val) in fields {
-                    self.visit_expr(val)?;
-                }
-            }
-            Expr::Array { elements, .. } | Expr::Tuple { elements, .. } => {
-                for el in elements {
-                    self.visit_expr(el)?;
-                }
-            }
-            Expr::Lambda { body, .. } => {
-                self.visit_expr(body)?;
-            }
-            Expr::Block(block) => {
-                self.visit_block(block)?;
-            }
-            Expr::If {
-                condition,
-                then_expr,
-                else_expr,
-                ..
-            } => {
-                self.visit_expr(condition)?;
-                self.visit_expr(then_expr)?;
-                if let Some(eb) = else_expr {
-                    self.visit_expr(eb)?;
-                }
-            }
-            Expr::Try { expr, .. } | Expr::Await { expr, .. } | Expr::Cast { expr, .. } => {
-                self.visit_expr(expr)?;
-            }
-        }
-        self.continue_()
+        walk_expr(self, expr)
     }
 
     /// Visit a pattern
@@ -340,6 +249,9 @@ pub trait AstVisitorMut {
             Item::Import(i) => self.visit_import_mut(i),
             Item::Constant(c) => self.visit_constant_mut(c),
             Item::Module(m) => self.visit_module_mut(m),
+            Item::Trait(t) => self.visit_trait_mut(t),
+            Item::Impl(i) => self.visit_impl_mut(i),
+            Item::Error(_) => self.continue_(),
         }
     }
 
@@ -371,6 +283,24 @@ pub trait AstVisitorMut {
         self.continue_()
     }
 
+    /// Visit and potentially modify a trait definition
+    fn visit_trait_mut(&mut self, trait_def: &mut TraitDef) -> VisitResult<Self::Break> {
+        for method in &mut trait_def.methods {
+            if let Some(body) = &mut method.default_body {
+                self.visit_block_mut(body)?;
+            }
+        }
+        self.continue_()
+    }
+
+    /// Visit and potentially modify an impl block
+    fn visit_impl_mut(&mut self, impl_def: &mut ImplDef) -> VisitResult<Self::Break> {
+        for method in &mut impl_def.methods {
+            self.visit_function_mut(method)?;
+        }
+        self.continue_()
+    }
+
     /// Visit and potentially modify a block
     fn visit_block_mut(&mut self, block: &mut Block) -> VisitResult<Self::Break> {
         for stmt in &mut block.stmts {
@@ -381,115 +311,311 @@ pub trait AstVisitorMut {
 
     /// Visit and potentially modify a statement
     fn visit_stmt_mut(&mut self, stmt: &mut Stmt) -> VisitResult<Self::Break> {
-        match stmt {
-            Stmt::Let { value, .. } => {
-                if let Some(v) = value {
-                    self.visit_expr_mut(v)?;
-                }
+        walk_stmt_mut(self, stmt)
+    }
+
+    /// Visit and potentially modify an expression
+    fn visit_expr_mut(&mut self, expr: &mut Expr) -> VisitResult<Self::Break> {
+        walk_expr_mut(self, expr)
+    }
+}
+
+/// Visit the children of a statement
+///
+/// Overriding `visit_stmt` replaces the default traversal; call this to keep it.
+pub fn walk_stmt<V: AstVisitor + ?Sized>(visitor: &mut V, stmt: &Stmt) -> VisitResult<V::Break> {
+    match stmt {
+        Stmt::Let { ty, value, .. } => {
+            if let Some(t) = ty {
+                visitor.visit_type(t)?;
             }
-            Stmt::Expr(expr) => {
-                self.visit_expr_mut(expr)?;
+            if let Some(v) = value {
+                visitor.visit_expr(v)?;
             }
-            Stmt::Return { value, .. } => {
-                if let Some(v) = value {
-                    self.visit_expr_mut(v)?;
-                }
+        }
+        Stmt::Expr(expr) => {
+            visitor.visit_expr(expr)?;
+        }
+        Stmt::Return { value, .. } => {
+            if let Some(v) = value {
+                visitor.visit_expr(v)?;
             }
-            Stmt::If {
-                condition,
-                then_block,
-                else_block,
-                ..
-            } => {
-                self.visit_expr_mut(condition)?;
-                self.visit_block_mut(then_block)?;
-                if let Some(eb) = else_block {
-                    self.visit_block_mut(eb)?;
-                }
+        }
+        Stmt::If {
+            condition,
+            then_block,
+            else_block,
+            ..
+        } => {
+            visitor.visit_expr(condition)?;
+            visitor.visit_block(then_block)?;
+            if let Some(eb) = else_block {
+                visitor.visit_block(eb)?;
             }
-            Stmt::Match {
-                scrutinee, arms, ..
-            } => {
-                self.visit_expr_mut(scrutinee)?;
-                for arm in arms {
-                    if let Some(guard) = &mut arm.guard {
-                        self.visit_expr_mut(guard)?;
-                    }
-                    self.visit_expr_mut(&mut arm.body)?;
-                }
+        }
+        Stmt::Loop { kind, body, .. } => {
+            walk_loop(visitor, kind, body)?;
+        }
+        Stmt::Break { value, .. } => {
+            if let Some(v) = value {
+                visitor.visit_expr(v)?;
             }
-            Stmt::Loop { body, .. } => {
-                self.visit_block_mut(body)?;
+        }
+        Stmt::Continue { .. } => {}
+    }
+    visitor.continue_()
+}
+
+/// Visit a loop header and body, shared by loop statements and expressions
+fn walk_loop<V: AstVisitor + ?Sized>(
+    visitor: &mut V,
+    kind: &LoopKind,
+    body: &Block,
+) -> VisitResult<V::Break> {
+    match kind {
+        LoopKind::ForIn { iterable, .. } => {
+            visitor.visit_expr(iterable)?;
+        }
+        LoopKind::While { condition } => {
+            visitor.visit_expr(condition)?;
+        }
+        LoopKind::Range { start, end, .. } => {
+            visitor.visit_expr(start)?;
+            visitor.visit_expr(end)?;
+        }
+        LoopKind::Infinite => {}
+    }
+    visitor.visit_block(body)
+}
+
+/// Visit the children of a statement
+///
+/// Overriding `visit_stmt_mut` replaces the default traversal; call this to keep it.
+pub fn walk_stmt_mut<V: AstVisitorMut + ?Sized>(
+    visitor: &mut V,
+    stmt: &mut Stmt,
+) -> VisitResult<V::Break> {
+    match stmt {
+        Stmt::Let { value, .. } => {
+            if let Some(v) = value {
+                visitor.visit_expr_mut(v)?;
             }
-            Stmt::Break { .. } | Stmt::Continue { .. } => {}
         }
-        self.continue_()
+        Stmt::Expr(expr) => {
+            visitor.visit_expr_mut(expr)?;
+        }
+        Stmt::Return { value, .. } => {
+            if let Some(v) = value {
+                visitor.visit_expr_mut(v)?;
+            }
+        }
+        Stmt::If {
+            condition,
+            then_block,
+            else_block,
+            ..
+        } => {
+            visitor.visit_expr_mut(condition)?;
+            visitor.visit_block_mut(then_block)?;
+            if let Some(eb) = else_block {
+                visitor.visit_block_mut(eb)?;
+            }
+        }
+        Stmt::Loop { body, .. } => {
+            visitor.visit_block_mut(body)?;
+        }
+        Stmt::Break { value, .. } => {
+            if let Some(v) = value {
+                visitor.visit_expr_mut(v)?;
+            }
+        }
+        Stmt::Continue { .. } => {}
     }
+    visitor.continue_()
+}
 
-    /// Visit and potentially modify an expression
-    fn visit_expr_mut(&mut self, expr: &mut Expr) -> VisitResult<Self::Break> {
-        match expr {
-            Expr::Literal(_) | Expr::Identifier(_) => {}
-            Expr::Binary { left, right, .. } => {
-                self.visit_expr_mut(left)?;
-                self.visit_expr_mut(right)?;
-            }
-            Expr::Unary { operand, .. } => {
-                self.visit_expr_mut(operand)?;
-            }
-            Expr::Call { callee, args, .. } => {
-                self.visit_expr_mut(callee)?;
-                for arg in args {
-                    self.visit_expr_mut(arg)?;
-                }
+/// Visit the children of an expression
+///
+/// Overriding `visit_expr` replaces the default traversal; call this to keep it.
+pub fn walk_expr<V: AstVisitor + ?Sized>(visitor: &mut V, expr: &Expr) -> VisitResult<V::Break> {
+    match expr {
+        Expr::Literal(_) | Expr::Identifier(_) | Expr::Error(_) => {}
+        Expr::Binary { left, right, .. } => {
+            visitor.visit_expr(left)?;
+            visitor.visit_expr(right)?;
+        }
+        Expr::Unary { operand, .. } => {
+            visitor.visit_expr(operand)?;
+        }
+        Expr::Call { callee, args, .. } => {
+            visitor.visit_expr(callee)?;
+            for arg in args {
+                visitor.visit_expr(arg)?;
             }
-            Expr::MethodCall { receiver, args, .. } => {
-                self.visit_expr_mut(receiver)?;
-                for arg in args {
-                    self.visit_expr_mut(arg)?;
-                }
+        }
+        Expr::MethodCall { receiver, args, .. } => {
+            visitor.visit_expr(receiver)?;
+            for arg in args {
+                visitor.visit_expr(arg)?;
             }
-            Expr::FieldAccess { object, .. } => {
-                self.visit_expr_mut(object)?;
+        }
+        Expr::FieldAccess { object, .. } => {
+            visitor.visit_expr(object)?;
+        }
+        Expr::Index { object, index, .. } => {
+            visitor.visit_expr(object)?;
+            visitor.visit_expr(index)?;
+        }
+        Expr::Range { start, end, .. } => {
+            for bound in start.iter().chain(end) {
+                visitor.visit_expr(bound)?;
             }
-            Expr::Index { object, index, .. } => {
-                self.visit_expr_mut(object)?;
-                self.visit_expr_mut(index)?;
+        }
+        Expr::StructConstruct { fields, .. } => {
+            for (_, val) in fields {
+                visitor.visit_expr(val)?;
             }
-            Expr::StructConstruct { fields, .. } => {
-                for (_, val) in fields {
-                    self.visit_expr_mut(val)?;
-                }
+        }
+        Expr::Array { elements, .. }
+        | Expr::Tuple { elements, .. }
+        | Expr::Format { args: elements, .. } => {
+            for el in elements {
+                visitor.visit_expr(el)?;
+            }
+        }
+        Expr::Lambda { body, .. } => {
+            visitor.visit_expr(body)?;
+        }
+        Expr::Block(block) => {
+            visitor.visit_block(block)?;
+        }
+        Expr::Loop { kind, body, .. } => {
+            walk_loop(visitor, kind, body)?;
+        }
+        Expr::If {
+            condition,
+            then_expr,
+            else_expr,
+            ..
+        } => {
+            visitor.visit_expr(condition)?;
+            visitor.visit_expr(then_expr)?;
+            if let Some(eb) = else_expr {
+                visitor.visit_expr(eb)?;
             }
-            Expr::Array { elements, .. } | Expr::Tuple { elements, .. } => {
-                for el in elements {
-                    self.visit_expr_mut(el)?;
+        }
+        Expr::Match {
+            scrutinee, arms, ..
+        } => {
+            visitor.visit_expr(scrutinee)?;
+            for arm in arms {
+                visitor.visit_pattern(&arm.pattern)?;
+                if let Some(guard) = &arm.guard {
+                    visitor.visit_expr(guard)?;
                 }
+                visitor.visit_expr(&arm.body)?;
             }
-            Expr::Lambda { body, .. } => {
-                self.visit_expr_mut(body)?;
+        }
+        Expr::Try { expr, .. }
+        | Expr::Await { expr, .. }
+        | Expr::Cast { expr, .. }
+        | Expr::Saha { task: expr, .. } => {
+            visitor.visit_expr(expr)?;
+        }
+    }
+    visitor.continue_()
+}
+
+/// Visit and potentially modify the children of an expression
+pub fn walk_expr_mut<V: AstVisitorMut + ?Sized>(
+    visitor: &mut V,
+    expr: &mut Expr,
+) -> VisitResult<V::Break> {
+    match expr {
+        Expr::Literal(_) | Expr::Identifier(_) | Expr::Error(_) => {}
+        Expr::Binary { left, right, .. } => {
+            visitor.visit_expr_mut(left)?;
+            visitor.visit_expr_mut(right)?;
+        }
+        Expr::Unary { operand, .. } => {
+            visitor.visit_expr_mut(operand)?;
+        }
+        Expr::Call { callee, args, .. } => {
+            visitor.visit_expr_mut(callee)?;
+            for arg in args {
+                visitor.visit_expr_mut(arg)?;
             }
-            Expr::Block(block) => {
-                self.visit_block_mut(block)?;
+        }
+        Expr::MethodCall { receiver, args, .. } => {
+            visitor.visit_expr_mut(receiver)?;
+            for arg in args {
+                visitor.visit_expr_mut(arg)?;
             }
-            Expr::If {
-                condition,
-                then_expr,
-                else_expr,
-                ..
-            } => {
-                self.visit_expr_mut(condition)?;
-                self.visit_expr_mut(then_expr)?;
-                if let Some(eb) = else_expr {
-                    self.visit_expr_mut(eb)?;
-                }
+        }
+        Expr::FieldAccess { object, .. } => {
+            visitor.visit_expr_mut(object)?;
+        }
+        Expr::Index { object, index, .. } => {
+            visitor.visit_expr_mut(object)?;
+            visitor.visit_expr_mut(index)?;
+        }
+        Expr::Range { start, end, .. } => {
+            for bound in start.iter_mut().chain(end) {
+                visitor.visit_expr_mut(bound)?;
             }
-            Expr::Try { expr, .. } | Expr::Await { expr, .. } | Expr::Cast { expr, .. } => {
-                self.visit_expr_mut(expr)?;
+        }
+        Expr::StructConstruct { fields, .. } => {
+            for (_, val) in fields {
+                visitor.visit_expr_mut(val)?;
             }
         }
-        self.continue_()
+        Expr::Array { elements, .. }
+        | Expr::Tuple { elements, .. }
+        | Expr::Format { args: elements, .. } => {
+            for el in elements {
+                visitor.visit_expr_mut(el)?;
+            }
+        }
+        Expr::Lambda { body, .. } => {
+            visitor.visit_expr_mut(body)?;
+        }
+        Expr::Block(block) => {
+            visitor.visit_block_mut(block)?;
+        }
+        Expr::Loop { body, .. } => {
+            visitor.visit_block_mut(body)?;
+        }
+        Expr::If {
+            condition,
+            then_expr,
+            else_expr,
+            ..
+        } => {
+            visitor.visit_expr_mut(condition)?;
+            visitor.visit_expr_mut(then_expr)?;
+            if let Some(eb) = else_expr {
+                visitor.visit_expr_mut(eb)?;
+            }
+        }
+        Expr::Match {
+            scrutinee, arms, ..
+        } => {
+            visitor.visit_expr_mut(scrutinee)?;
+            for arm in arms {
+                if let Some(guard) = &mut arm.guard {
+                    visitor.visit_expr_mut(guard)?;
+                }
+                visitor.visit_expr_mut(&mut arm.body)?;
+            }
+        }
+        Expr::Try { expr, .. }
+        | Expr::Await { expr, .. }
+        | Expr::Cast { expr, .. }
+        | Expr::Saha { task: expr, .. } => {
+            visitor.visit_expr_mut(expr)?;
+        }
     }
+    visitor.continue_()
 }
 
 #[cfg(test)]
@@ -516,6 +642,9 @@ mod tests {
                 Item::Import(i) => self.visit_import(i),
                 Item::Constant(c) => self.visit_constant(c),
                 Item::Module(m) => self.visit_module(m),
+                Item::Trait(t) => self.visit_trait(t),
+                Item::Impl(i) => self.visit_impl(i),
+                Item::Error(_) => self.continue_(),
             }
         }
 
@@ -576,8 +705,10 @@ mod tests {
                     span: Span::dummy(),
                 },
                 generics: vec![],
+                receiver: None,
                 params: vec![],
                 return_type: None,
+                where_clauses: vec![],
                 preconditions: vec![],
                 postconditions: vec![],
                 body: Block {
@@ -589,6 +720,9 @@ mod tests {
                     })],
                     span: Span::dummy(),
                 },
+                is_const: false,
+                visibility: Default::default(),
+                attributes: vec![],
                 span: Span::dummy(),
             })],
             file_path: "test.jag".to_string(),
@@ -23,7 +23,9 @@ pub mod ast_visitor;
 pub mod mir_visitor;
 pub mod walk;
 
-pub use ast_visitor::{AstVisitor, AstVisitorMut};
+pub use ast_visitor::{
+    walk_expr, walk_expr_mut, walk_stmt, walk_stmt_mut, AstVisitor, AstVisitorMut,
+};
 pub use mir_visitor::{DefCollector, MirVisitor, MirVisitorMut, UseCollector};
 pub use walk::{mir_post_order, mir_reverse_post_order};
 pub use walk::{walk_basic_block, walk_mir_function, walk_mir_module, walk_rvalue};
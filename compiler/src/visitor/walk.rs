@@ -188,6 +188,9 @@ mod tests {
             ],
             locals: vec![],
             karaka_hints: HashMap::new(),
+            inline: InlineHint::Auto,
+            overflow: Overflow::Wrap,
+            spans: HashMap::new(),
         }
     }
 
@@ -0,0 +1,295 @@
+//! Integration tests for attributes (cihna)
+
+use jagannath_compiler::codegen::asm::Target;
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompileResult, CompilerSession};
+
+fn compile_with(source: &str, options: CompilerOptions) -> Result<CompileResult, CompileError> {
+    let mut options = options;
+    options.emit_asm = true;
+    let mut session = CompilerSession::new(options);
+    session.compile(source)
+}
+
+fn compile(source: &str) -> Result<CompileResult, CompileError> {
+    compile_with(source, CompilerOptions::new())
+}
+
+fn asm(result: &CompileResult) -> String {
+    String::from_utf8_lossy(&result.output).into_owned()
+}
+
+/// Test an unknown attribute is rejected with its name
+#[test]
+fn test_unknown_attribute() {
+    let err = compile(
+        r#"
+#[jādū]
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera 0;
+}
+"#,
+    )
+    .expect_err("Unknown attribute");
+    assert!(err.message.contains("Unknown attribute"), "{}", err.message);
+    assert!(err.message.contains("`#[jādū]`"), "{}", err.message);
+    assert_eq!(err.location.map(|l| l.line), Some(2));
+}
+
+/// Test built-in attributes are checked against what they annotate
+#[test]
+fn test_misplaced_attribute() {
+    let err = compile(
+        r#"
+#[samāveśa]
+prakāra Bindu {
+    x: saṅkhyā-a-k-t32,
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera 0;
+}
+"#,
+    )
+    .expect_err("Inline hint on a struct");
+    assert!(
+        err.message.contains("`samāveśa` cannot be used on types"),
+        "{}",
+        err.message
+    );
+}
+
+/// Test `cfg(lakṣya = ...)` keeps only the item for the build target
+const CFG_TARGET: &str = r#"
+#[cfg(lakṣya = "x86_64")]
+kāryakrama vāstu() -> saṅkhyā-a-k-t32 {
+    phera 64;
+}
+
+#[cfg(lakṣya = "aarch64")]
+kāryakrama vāstu() -> saṅkhyā-a-k-t32 {
+    phera 8;
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera vāstu();
+}
+"#;
+
+#[test]
+fn test_cfg_target() {
+    let x86 = asm(&compile(CFG_TARGET).expect("x86_64 build"));
+    assert!(x86.contains("64"), "{}", x86);
+    assert_eq!(x86.matches("vāstu:").count(), 1, "{}", x86);
+
+    let mut options = CompilerOptions::new();
+    options.target = Target::AArch64;
+    let arm = asm(&compile_with(CFG_TARGET, options).expect("aarch64 build"));
+    assert_eq!(arm.matches("vāstu:").count(), 1, "{}", arm);
+    assert!(arm.contains("#8"), "{}", arm);
+}
+
+/// Test `#[parīkṣā]` functions exist only in test builds
+#[test]
+fn test_test_functions_need_test_build() {
+    let source = r#"
+#[parīkṣā]
+kāryakrama jāñca() -> saṅkhyā-a-k-t32 {
+    phera 1;
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera 0;
+}
+"#;
+    let normal = asm(&compile(source).expect("Normal build"));
+    assert!(!normal.contains("jāñca:"), "{}", normal);
+
+    let mut options = CompilerOptions::new();
+    options.test = true;
+    let test = asm(&compile_with(source, options).expect("Test build"));
+    assert!(test.contains("jāñca:"), "{}", test);
+}
+
+const DEPRECATED: &str = r#"
+#[apracalita(sūcanā = "use navīna")]
+kāryakrama purāṇa() -> saṅkhyā-a-k-t32 {
+    phera 1;
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera purāṇa();
+}
+"#;
+
+/// Test uses of a deprecated function are reported as warnings
+#[test]
+fn test_deprecated_warning() {
+    let result = compile(DEPRECATED).expect("Deprecation only warns");
+    assert_eq!(result.warnings.len(), 1, "{:?}", result.warnings);
+    let warning = &result.warnings[0];
+    assert!(
+        warning
+            .message
+            .contains("use of deprecated `purāṇa`: use navīna"),
+        "{}",
+        warning.message
+    );
+    assert_eq!(warning.location.as_ref().map(|l| l.line), Some(8));
+}
+
+/// Test lint levels silence or escalate the deprecation lint
+#[test]
+fn test_lint_levels() {
+    let allowed = DEPRECATED.replace(
+        "kāryakrama mukhya()",
+        "#[anumati(apracalita)]\nkāryakrama mukhya()",
+    );
+    let result = compile(&allowed).expect("Allowed deprecation");
+    assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+
+    let denied = DEPRECATED.replace(
+        "kāryakrama mukhya()",
+        "#[niṣedha(apracalita)]\nkāryakrama mukhya()",
+    );
+    let err = compile(&denied).expect_err("Denied deprecation");
+    assert!(
+        err.message.contains("denied by `niṣedha(apracalita)`"),
+        "{}",
+        err.message
+    );
+}
+
+/// Test `#[yama(satya)]` holds the function to truthfulness
+#[test]
+fn test_yama_vow_broken() {
+    let source = r#"
+kāryakrama read_input() -> saṅkhyā-a-k-t32 {
+    phera 7;
+}
+
+kāryakrama eval(x: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    phera x;
+}
+
+#[yama(satya)]
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    let x: saṅkhyā-a-k-t32 = read_input();
+    phera eval(x);
+}
+"#;
+    let err = compile(source).expect_err("Evaluating input breaks satya");
+    assert!(err.message.contains("Vow broken"), "{}", err.message);
+    assert!(err.message.contains("`mukhya`"), "{}", err.message);
+
+    // Without the vow the same code builds
+    compile(&source.replace("#[yama(satya)]\n", "")).expect("No vow taken");
+}
+
+/// Test `#[samāveśa(na)]` is accepted on functions
+#[test]
+fn test_inline_never() {
+    let result = compile(
+        r#"
+#[samāveśa(na)]
+kāryakrama dvi(x: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    phera x + x;
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera dvi(3);
+}
+"#,
+    )
+    .expect("Inline hint should compile");
+    assert!(asm(&result).contains("call dvi"), "{}", asm(&result));
+}
+
+const CFG_FEATURE: &str = r#"
+#[cfg(sarva(viśeṣatā = "simd", na(anukūlana = 0)))]
+kāryakrama gati() -> saṅkhyā-a-k-t32 {
+    phera 4242;
+}
+
+#[cfg(kaścit(na(viśeṣatā = "simd"), anukūlana = 0))]
+kāryakrama gati() -> saṅkhyā-a-k-t32 {
+    phera 1717;
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera gati();
+}
+"#;
+
+/// Test `cfg` combinators over features and the opt level
+#[test]
+fn test_cfg_features_and_opt_level() {
+    let plain = asm(&compile(CFG_FEATURE).expect("No features"));
+    assert!(
+        plain.contains("1717") && !plain.contains("4242"),
+        "{}",
+        plain
+    );
+
+    let mut options = CompilerOptions::new();
+    options.features = vec!["simd".to_string()];
+    let simd = asm(&compile_with(CFG_FEATURE, options.clone()).expect("simd"));
+    assert!(simd.contains("4242") && !simd.contains("1717"), "{}", simd);
+
+    options.opt_level = 0;
+    let debug = asm(&compile_with(CFG_FEATURE, options).expect("simd at O0"));
+    assert!(
+        debug.contains("1717") && !debug.contains("4242"),
+        "{}",
+        debug
+    );
+}
+
+/// Test `cfg(guṇa = ...)` follows the guṇa mode
+#[test]
+fn test_cfg_guna() {
+    let source = r#"
+#[cfg(guṇa = "tamas")]
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera 1;
+}
+
+#[cfg(na(guṇa = "tamas"))]
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera 2;
+}
+"#;
+    let result = compile_with(source, CompilerOptions::minimal()).expect("Tamas build");
+    assert_eq!(asm(&result).matches("mukhya:").count(), 1);
+
+    let err = compile(&source.replace("\"tamas\")]\nkāryakrama", "\"tāmasika\")]\nkāryakrama"))
+        .expect_err("Unknown guṇa");
+    assert!(
+        err.message.contains("Unknown cfg predicate"),
+        "{}",
+        err.message
+    );
+}
+
+/// Test features listed in `Jagannath.toml` are enabled
+#[test]
+fn test_manifest_features() {
+    let dir = std::env::temp_dir().join(format!("jag_cfg_manifest_{}", std::process::id()));
+    let src = dir.join("mūla");
+    std::fs::create_dir_all(&src).unwrap();
+    std::fs::write(
+        dir.join("Jagannath.toml"),
+        "[\"nirmaṇa\"]\n\"viśeṣatā\" = [\"simd\"]\n",
+    )
+    .unwrap();
+
+    let mut options = CompilerOptions::new();
+    options
+        .add_manifest_features(&src.join("mukhya.jag"))
+        .expect("Manifest should be read");
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(options.features, vec!["simd".to_string()]);
+
+    let result = asm(&compile_with(CFG_FEATURE, options).expect("Manifest features"));
+    assert!(result.contains("4242"), "{}", result);
+}
@@ -0,0 +1,187 @@
+//! Integration tests for closures (pāśa)
+
+use jagannath_compiler::codegen::asm::Target;
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompilerSession};
+
+fn compile_for_target(source: &str, target: Target) -> Result<String, CompileError> {
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    options.target = target;
+    let mut session = CompilerSession::new(options);
+    session
+        .compile(source)
+        .map(|result| String::from_utf8_lossy(&result.output).into_owned())
+}
+
+fn compile(source: &str) -> Result<String, CompileError> {
+    compile_for_target(source, Target::X86_64)
+}
+
+/// A closure capturing `n`, passed to a function taking a function value
+const LAGU: &str = r#"
+kāryakrama lāgū(f: kāryakrama(saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32, x: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    phera f(x);
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    let n: saṅkhyā-a-k-t32 = 5;
+    let jod = |x: saṅkhyā-a-k-t32| x + n;
+    phera lāgū(jod, 2);
+}
+"#;
+
+/// Test the environment holds the code address and the body is its own function
+#[test]
+fn test_closure_environment_x86_64() {
+    let asm = compile(LAGU).expect("Closure should compile");
+    assert!(asm.contains("mukhya__pāśa8_15:"), "{}", asm);
+    assert!(asm.contains("lea rax, [rip + mukhya__pāśa8_15]"), "{}", asm);
+    // The caller loads the code address from the environment
+    assert!(asm.contains("call rax"), "{}", asm);
+}
+
+#[test]
+fn test_closure_environment_aarch64() {
+    let asm = compile_for_target(LAGU, Target::AArch64).expect("Closure should compile");
+    assert!(asm.contains("mukhya__pāśa8_15:"), "{}", asm);
+    assert!(asm.contains("adrp x0, mukhya__pāśa8_15"), "{}", asm);
+    assert!(
+        asm.contains("add x0, x0, :lo12:mukhya__pāśa8_15"),
+        "{}",
+        asm
+    );
+    assert!(asm.contains("blr x9"), "{}", asm);
+}
+
+#[test]
+fn test_closure_environment_riscv64() {
+    let asm = compile_for_target(LAGU, Target::RiscV64).expect("Closure should compile");
+    assert!(asm.contains("mukhya__pāśa8_15:"), "{}", asm);
+    assert!(asm.contains("la t0, mukhya__pāśa8_15"), "{}", asm);
+    assert!(asm.contains("jalr ra, t0, 0"), "{}", asm);
+}
+
+/// Test a closure can be passed where an Fn-family bound is required
+#[test]
+fn test_closure_satisfies_fn_bound() {
+    let asm = compile(
+        r#"
+kāryakrama lāgū<F: Fn>(f: F, x: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    phera f(x);
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    let n: saṅkhyā-a-k-t32 = 5;
+    phera lāgū(|x: saṅkhyā-a-k-t32| x + n, 2);
+}
+"#,
+    )
+    .expect("A capturing closure implements Fn");
+    assert!(asm.contains("lāgū__mukhya__pāśa8_16:"), "{}", asm);
+}
+
+/// Test a closure that assigns to a capture is only FnMut
+#[test]
+fn test_mutating_closure_is_not_fn() {
+    let err = compile(
+        r#"
+kāryakrama lāgū<F: Fn>(f: F, x: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    phera f(x);
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    let gaṇanā: saṅkhyā-a-k-t32 = 0;
+    phera lāgū(|x: saṅkhyā-a-k-t32| { gaṇanā = gaṇanā + x; phera x; }, 2);
+}
+"#,
+    )
+    .expect_err("FnMut closure passed as Fn");
+    assert!(err.message.contains("`Fn`"), "{}", err.message);
+}
+
+/// Test a linear capture is moved into the closure
+#[test]
+fn test_linear_capture_is_moved() {
+    let err = compile(
+        r#"
+kāryakrama upabhoga(k: saṅkhyā-a-t32-l) -> saṅkhyā-a-k-t32 {
+    phera 1;
+}
+
+kāryakrama mukhya(k: saṅkhyā-a-t32-l) -> saṅkhyā-a-k-t32 {
+    let f = || upabhoga(k);
+    phera upabhoga(k);
+}
+"#,
+    )
+    .expect_err("k was moved into the closure");
+    assert!(
+        err.message.contains("use of moved value `k`"),
+        "{}",
+        err.message
+    );
+}
+
+/// Test a closure consuming its environment can be called only once
+#[test]
+fn test_fn_once_called_twice() {
+    let err = compile(
+        r#"
+kāryakrama upabhoga(k: saṅkhyā-a-t32-l) -> saṅkhyā-a-k-t32 {
+    phera 1;
+}
+
+kāryakrama mukhya(k: saṅkhyā-a-t32-l) -> saṅkhyā-a-k-t32 {
+    let f = || upabhoga(k);
+    let a = f();
+    phera f();
+}
+"#,
+    )
+    .expect_err("FnOnce closure called twice");
+    assert!(
+        err.message.contains("use of moved value `f`"),
+        "{}",
+        err.message
+    );
+}
+
+/// Test calls to closures are checked against their parameters
+#[test]
+fn test_closure_arity() {
+    let err = compile(
+        r#"
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    let f = |x: saṅkhyā-a-k-t32, y: saṅkhyā-a-k-t32| x + y;
+    phera f(1);
+}
+"#,
+    )
+    .expect_err("Closure called with too few arguments");
+    assert!(
+        err.message.contains("expects 2 arguments"),
+        "{}",
+        err.message
+    );
+}
+
+/// Test closures in closures capture through the enclosing environment
+#[test]
+fn test_nested_closures() {
+    let asm = compile(
+        r#"
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    let n: saṅkhyā-a-k-t32 = 5;
+    let jodaka = |x| {
+        let guṇaka = || x + n;
+        phera guṇaka();
+    };
+    phera jodaka(1);
+}
+"#,
+    )
+    .expect("Nested closures should compile");
+    assert!(asm.contains("mukhya__pāśa4_18:"), "{}", asm);
+    assert!(asm.contains("mukhya__pāśa4_18__pāśa5_22:"), "{}", asm);
+}
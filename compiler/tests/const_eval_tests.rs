@@ -0,0 +1,323 @@
+//! Integration tests for const evaluation: `sthira` values and array
+//! lengths computed at compile time, through `sthira kāryakrama` calls
+
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompilerSession};
+use jagannath_compiler::parser::{ast::*, Parser};
+
+fn compile(source: &str) -> Result<String, CompileError> {
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    let mut session = CompilerSession::new(options);
+    session
+        .compile(source)
+        .map(|result| String::from_utf8_lossy(&result.output).into_owned())
+}
+
+/// A factorial by loop and one by recursion
+const FACTORIALS: &str = r#"
+sthira kāryakrama krama(n: saṅkhyā) -> saṅkhyā {
+    let phala = 1;
+    cala i : 2..n + 1 {
+        phala = phala * i;
+    }
+    phera phala;
+}
+
+sthira kāryakrama punaḥ(n: saṅkhyā) -> saṅkhyā {
+    yad n <= 1 {
+        phera 1;
+    }
+    phera n * punaḥ(n - 1);
+}
+"#;
+
+#[test]
+fn test_parse_const_fn() {
+    let source = "sthira kāryakrama f() -> saṅkhyā {\n    phera 2;\n}\n\nkāryakrama g(a: [saṅkhyā; N * 2]) {\n}\n";
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::Function(f) = &ast.items[0] else {
+        panic!("Expected function declaration");
+    };
+    assert!(f.is_const);
+    let Item::Function(g) = &ast.items[1] else {
+        panic!("Expected function declaration");
+    };
+    assert!(!g.is_const);
+    assert!(matches!(
+        &g.params[0].ty,
+        Type::Array { size: None, length: Some(length), .. } if matches!(**length, Expr::Binary { .. })
+    ));
+}
+
+/// Test a constant is computed by calling `sthira` functions, loops and
+/// recursion alike
+#[test]
+fn test_const_fn_call() {
+    let source = format!(
+        "{}\n{}",
+        FACTORIALS,
+        r#"
+sthira A: saṅkhyā = krama(5) + punaḥ(4);
+
+kāryakrama mukhya() -> saṅkhyā {
+    phera A;
+}
+"#
+    );
+    let asm = compile(&source).expect("A is 144");
+    let mukhya = &asm[asm.find("mukhya:").expect("mukhya is emitted")..];
+    let mukhya = &mukhya[..mukhya.find(".size").expect("mukhya ends")];
+    assert!(mukhya.contains("144"), "{}", mukhya);
+    assert!(!mukhya.contains("call krama"), "{}", mukhya);
+}
+
+/// Test an array length written with a constant gives the array its size
+#[test]
+fn test_array_length() {
+    let source = r#"
+sthira N: saṅkhyā = 2;
+
+kāryakrama f(a: [saṅkhyā; N + 1]) {
+}
+
+kāryakrama mukhya() {
+    f([1, 2, 3]);
+}
+"#;
+    compile(source).expect("N + 1 is 3");
+
+    let source = r#"
+sthira N: saṅkhyā = 2;
+
+kāryakrama mukhya() {
+    let a: [saṅkhyā; N * 2] = [1, 2, 3];
+}
+"#;
+    let err = compile(source).expect_err("N * 2 is 4").message;
+    assert!(err.contains("4"), "{}", err);
+    assert!(err.contains("3"), "{}", err);
+}
+
+/// Test overflow is reported with the calls that led to it
+#[test]
+fn test_overflow_backtrace() {
+    let source = format!("{}\n{}", FACTORIALS, "sthira B: saṅkhyā = punaḥ(13);\n");
+    let err = compile(&source).expect_err("13! is beyond an i32");
+    assert!(
+        err.message
+            .contains("attempt to compute `13 * 479001600`, which overflows `i32`"),
+        "{}",
+        err.message
+    );
+    // Innermost first, down to the constant
+    assert!(
+        err.message.contains("in `punaḥ` at 10:1"),
+        "{}",
+        err.message
+    );
+    assert!(
+        err.message.contains("in the value of `B` at 17:1"),
+        "{}",
+        err.message
+    );
+    assert_eq!(err.location.expect("location").line, 17);
+    assert!(err.notes.iter().any(|n| n.contains("`sthira kāryakrama`")));
+}
+
+/// Test division by zero stops evaluation
+#[test]
+fn test_division_by_zero() {
+    let source = r#"
+sthira kāryakrama bhāga(a: saṅkhyā, b: saṅkhyā) -> saṅkhyā {
+    phera a / b;
+}
+
+sthira C: saṅkhyā = bhāga(7, 0);
+"#;
+    let err = compile(source).expect_err("7 / 0").message;
+    assert!(
+        err.contains("attempt to compute `7 / 0`, a division by zero"),
+        "{}",
+        err
+    );
+    assert!(err.contains("in `bhāga` at 2:1"), "{}", err);
+}
+
+/// Test an evaluation that never finishes is cut off
+#[test]
+fn test_step_limit() {
+    let source = r#"
+sthira kāryakrama cakra() -> saṅkhyā {
+    let i = 0;
+    cala {
+        i = i + 0;
+    }
+    phera i;
+}
+
+kāryakrama f(a: [saṅkhyā; cakra()]) {
+}
+"#;
+    let err = compile(source).expect_err("cakra never returns").message;
+    assert!(
+        err.contains("evaluation did not finish within 1000000 steps"),
+        "{}",
+        err
+    );
+}
+
+/// Test a function not marked `sthira` cannot give an array its length,
+/// while a constant calling one is left to run where it is used
+#[test]
+fn test_not_const() {
+    let source = r#"
+kāryakrama tīna() -> saṅkhyā {
+    phera 3;
+}
+
+kāryakrama f(a: [saṅkhyā; tīna()]) {
+}
+"#;
+    let err = compile(source).expect_err("tīna is not sthira").message;
+    assert!(
+        err.contains("`tīna` is not a `sthira` function, so cannot be called at compile time"),
+        "{}",
+        err
+    );
+
+    let source = r#"
+kāryakrama tīna() -> saṅkhyā {
+    phera 3;
+}
+
+sthira T: saṅkhyā = tīna();
+
+kāryakrama mukhya() -> saṅkhyā {
+    phera T;
+}
+"#;
+    compile(source).expect("T is computed at run time");
+}
+
+/// Test a computed constant must fit the type declared for it
+#[test]
+fn test_out_of_range() {
+    let source = format!(
+        "{}\n{}",
+        FACTORIALS, "sthira D: u8 = krama(6) as u8;\nsthira E: saṅkhyā-t8 = 100 + 100;\n"
+    );
+    let err = compile(&source).expect_err("200 is beyond an i8").message;
+    assert!(
+        err.contains("18:1: the value `200` does not fit in `i8`"),
+        "{}",
+        err
+    );
+    // 720 as u8 is 208
+    assert!(!err.contains("`D`"), "{}", err);
+}
+
+/// A type bounded by const parameters, in the manner of
+/// `niyama::bandha::SimitaPurnanka<MIN, MAX>`
+const SIMITA: &str = r#"
+sthira kāryakrama śata() -> saṅkhyā {
+    phera 10 * 10;
+}
+
+sthira ŚATA: saṅkhyā = śata();
+
+prakāra Sīmita<sthira NYŪNA: saṅkhyā, sthira ADHIKA: saṅkhyā> {
+    v: saṅkhyā,
+}
+
+kriyānvaya<sthira NYŪNA: saṅkhyā, sthira ADHIKA: saṅkhyā> Sīmita<NYŪNA, ADHIKA> {
+    kāryakrama sīmā(&sva) -> saṅkhyā {
+        yad sva.v < NYŪNA {
+            phera NYŪNA;
+        }
+        yad sva.v > ADHIKA {
+            phera ADHIKA;
+        }
+        phera sva.v;
+    }
+}
+"#;
+
+/// Test const arguments are computed, whether a literal, a `sthira` item
+/// or a block calling a `sthira` function, and instantiate their type
+#[test]
+fn test_const_generic_arguments() {
+    let source = format!(
+        "{}\n{}",
+        SIMITA,
+        r#"
+kāryakrama pūrṇa<sthira N: saṅkhyā>(s: Sīmita<N, 100>) -> saṅkhyā {
+    let a: [saṅkhyā; N + 1] = [0, 1, 2];
+    phera a[N] + s.sīmā();
+}
+
+kāryakrama mukhya(s: Sīmita<0, { śata() }>, t: Sīmita<-5, ŚATA>, u: Sīmita<2, 100>) -> saṅkhyā {
+    phera s.sīmā() + t.sīmā() + pūrṇa(u);
+}
+"#
+    );
+    let asm = compile(&source).expect("Const generics should compile");
+    // `{ śata() }` and `ŚATA` are both 100
+    for symbol in ["Sīmita__0_100__sīmā", "Sīmita__m5_100__sīmā", "pūrṇa__2"] {
+        assert!(asm.contains(&format!("{}:", symbol)), "{}", asm);
+        assert!(asm.contains(&format!("call {}", symbol)), "{}", asm);
+    }
+    assert!(asm.contains("call Sīmita__2_100__sīmā"), "{}", asm);
+    assert!(!asm.contains("Sīmita__sīmā"), "{}", asm);
+
+    // Lengths using `N` are computed once it is substituted
+    let source = source.replace("[saṅkhyā; N + 1] = [0, 1, 2]", "[saṅkhyā; N - 3] = []");
+    let err = compile(&source).expect_err("N - 3 is -1").message;
+    assert!(
+        err.contains("array length must be a non-negative integer, found `-1`"),
+        "{}",
+        err
+    );
+}
+
+/// Test a const argument must fit its parameter's type, and one that
+/// cannot be computed is reported with the calls that led to it
+#[test]
+fn test_const_argument_errors() {
+    let source = format!(
+        "{}\n{}",
+        SIMITA,
+        "prakāra Aṇu<sthira N: u8> {\n    v: u8,\n}\n\nkāryakrama f(a: Aṇu<{ śata() * 3 }>) {\n}\n"
+    );
+    let err = compile(&source).expect_err("300 is beyond a u8").message;
+    assert!(
+        err.contains("28:21: the value `300` does not fit in `u8`"),
+        "{}",
+        err
+    );
+    assert!(err.contains("in the const argument at 28:21"), "{}", err);
+
+    let source = format!(
+        "{}\n{}",
+        SIMITA,
+        "sthira kāryakrama śūnya() -> saṅkhyā {\n    phera 0;\n}\n\nkāryakrama f(s: Sīmita<{ ŚATA / śūnya() }, 0>) {\n}\n"
+    );
+    let err = compile(&source).expect_err("100 / 0").message;
+    assert!(
+        err.contains("attempt to compute `100 / 0`, a division by zero"),
+        "{}",
+        err
+    );
+    assert!(err.contains("in the const argument at 28:24"), "{}", err);
+
+    let source = format!(
+        "{}\n{}",
+        SIMITA, "kāryakrama f<sthira N: saṅkhyā>(s: Sīmita<{ N + 1 }, 0>) {\n}\n"
+    );
+    let err = compile(&source).expect_err("N + 1 waits for N").message;
+    assert!(
+        err.contains("a const argument cannot be computed from the generic parameter `N`"),
+        "{}",
+        err
+    );
+}
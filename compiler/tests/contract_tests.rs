@@ -0,0 +1,228 @@
+//! Integration tests for contracts: `yatra pūrva` and `yatra paścāt`
+//! checked at run time in debug and Sattva builds, and proved at compile
+//! time with `--prove-contracts`
+
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompileResult, CompilerSession};
+use jagannath_compiler::parser::{ast::*, Parser};
+use jagannath_compiler::philosophy::guna::Guna;
+
+fn compile_with(source: &str, options: CompilerOptions) -> Result<CompileResult, CompileError> {
+    let mut options = options;
+    options.emit_asm = true;
+    let mut session = CompilerSession::new(options);
+    session.compile(source)
+}
+
+fn compile(source: &str, guna: Guna) -> Result<String, CompileError> {
+    let mut options = CompilerOptions::new();
+    options.guna = guna;
+    compile_with(source, options).map(|result| String::from_utf8_lossy(&result.output).into_owned())
+}
+
+/// A division that needs a divisor and a caller that checks for one
+const DIVISION: &str = r#"
+kāryakrama bhāga(a: saṅkhyā, b: saṅkhyā) -> saṅkhyā
+    yatra pūrva b != 0
+{
+    phera a / b;
+}
+
+kāryakrama mukhya(n: saṅkhyā) -> saṅkhyā {
+    yad n > 0 {
+        phera bhāga(10, n);
+    }
+    phera 0;
+}
+"#;
+
+#[test]
+fn test_parse_contracts() {
+    let source = "kāryakrama f(a: saṅkhyā, b: saṅkhyā) -> saṅkhyā\n    yatra pūrva b != 0, paścāt phala >= 0\n{\n    phera a / b;\n}\n";
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function declaration");
+    };
+    assert_eq!(func.preconditions.len(), 1);
+    assert_eq!(func.preconditions[0].text, "b != 0");
+    assert_eq!(func.postconditions.len(), 1);
+    assert_eq!(func.postconditions[0].text, "phala >= 0");
+    assert!(func.where_clauses.is_empty());
+}
+
+/// Test a contract is refused where there is no body to check it in
+#[test]
+fn test_contract_on_trait_method() {
+    let source =
+        "guṇa Bhāga {\n    kāryakrama bhāga(sva, b: saṅkhyā) -> saṅkhyā yatra pūrva b != 0;\n}\n";
+    let err = Parser::parse_str(source).expect_err("trait methods have no contracts");
+    assert!(
+        format!("{:?}", err).contains("`pūrva` and `paścāt` contracts apply to functions only"),
+        "{:?}",
+        err
+    );
+}
+
+/// Test Sattva and debug builds check a precondition on entry, while
+/// release builds drop it
+#[test]
+fn test_runtime_check() {
+    let asm = compile(DIVISION, Guna::Sattva).expect("should compile");
+    assert!(
+        asm.contains("Assert: 3:11: precondition `b != 0` of `bhāga` does not hold"),
+        "{}",
+        asm
+    );
+    assert!(asm.contains("ud2"), "{}", asm);
+
+    let result = compile_with(DIVISION, CompilerOptions::debug()).expect("should compile");
+    let asm = String::from_utf8_lossy(&result.output);
+    assert!(asm.contains("precondition `b != 0`"), "{}", asm);
+
+    let asm = compile(DIVISION, Guna::Rajas).expect("should compile");
+    assert!(!asm.contains("precondition"), "{}", asm);
+}
+
+/// Test a postcondition is checked on every return, `phala` naming the
+/// result
+#[test]
+fn test_postcondition() {
+    let source = r#"
+kāryakrama nirapekṣa(x: saṅkhyā) -> saṅkhyā
+    yatra paścāt phala >= 0, paścāt phala >= x
+{
+    yad x < 0 {
+        phera -x;
+    }
+    phera x;
+}
+"#;
+    let asm = compile(source, Guna::Sattva).expect("should compile");
+    let function = &asm[asm.find("nirapekṣa:").expect("nirapekṣa is emitted")..];
+    let function = &function[..function.find(".size").expect("nirapekṣa ends")];
+    // Checked once, at the one exit both returns lead to
+    assert_eq!(
+        function
+            .matches("postcondition `phala >= 0` of `nirapekṣa` does not hold")
+            .count(),
+        1,
+        "{}",
+        function
+    );
+    assert!(
+        function.contains("postcondition `phala >= x`"),
+        "{}",
+        function
+    );
+}
+
+/// Test a contract must be a condition
+#[test]
+fn test_contract_type() {
+    let source = "kāryakrama f(a: saṅkhyā) -> saṅkhyā\n    yatra pūrva a + 1\n{\n    phera a;\n}\n";
+    let err = compile(source, Guna::Sattva).expect_err("a + 1 is no condition");
+    assert!(err.message.contains("precondition"), "{}", err.message);
+    assert_eq!(err.location.expect("location").line, 2);
+
+    let source =
+        "kāryakrama f(a: saṅkhyā) -> saṅkhyā\n    yatra paścāt phala\n{\n    phera a;\n}\n";
+    let err = compile(source, Guna::Sattva).expect_err("phala is no condition");
+    assert!(err.message.contains("postcondition"), "{}", err.message);
+}
+
+/// Test obligations that follow from what is known are proved, and no
+/// longer checked at run time, while the rest are warned of
+#[test]
+fn test_prove_contracts() {
+    let mut options = CompilerOptions::new();
+    options.guna = Guna::Sattva;
+    options.prove_contracts = true;
+    let result = compile_with(DIVISION, options.clone()).expect("should compile");
+    assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    let asm = String::from_utf8_lossy(&result.output);
+    assert!(!asm.contains("precondition `b != 0`"), "{}", asm);
+
+    let source = r#"
+kāryakrama bhāga(a: saṅkhyā, b: saṅkhyā) -> saṅkhyā
+    yatra pūrva b != 0
+{
+    phera a / b;
+}
+
+kāryakrama vṛddhi(x: saṅkhyā) -> saṅkhyā
+    yatra pūrva x >= 0, paścāt phala > x
+{
+    phera x + 1;
+}
+
+kāryakrama mukhya(n: saṅkhyā) -> saṅkhyā {
+    let m = vṛddhi(5);
+    let k = bhāga(10, m);
+    phera bhāga(k, n);
+}
+"#;
+    let result = compile_with(source, options).expect("should compile");
+    assert_eq!(result.warnings.len(), 1, "{:?}", result.warnings);
+    let warning = &result.warnings[0];
+    assert_eq!(
+        warning.message,
+        "precondition `b != 0` of `bhāga` cannot be proved at this call"
+    );
+    assert_eq!(warning.location.as_ref().expect("location").line, 17);
+    // One call is unproved, so the check stays; `vṛddhi` keeps its promise
+    let asm = String::from_utf8_lossy(&result.output);
+    assert!(asm.contains("precondition `b != 0`"), "{}", asm);
+    assert!(!asm.contains("postcondition `phala > x`"), "{}", asm);
+}
+
+/// Test the contracts of methods are proved at their calls too
+#[test]
+fn test_prove_method_contracts() {
+    let source = r#"
+prakāra Gaṇaka {
+    mūlya: saṅkhyā,
+}
+
+kriyānvaya Gaṇaka {
+    kāryakrama bhāga(&sva, b: saṅkhyā) -> saṅkhyā
+        yatra pūrva b > 0
+    {
+        phera sva.mūlya / b;
+    }
+}
+
+kāryakrama mukhya(g: Gaṇaka, n: saṅkhyā) -> saṅkhyā {
+    let a = g.bhāga(2);
+    phera g.bhāga(n);
+}
+"#;
+    let mut options = CompilerOptions::new();
+    options.guna = Guna::Sattva;
+    options.prove_contracts = true;
+    let result = compile_with(source, options).expect("should compile");
+    assert_eq!(result.warnings.len(), 1, "{:?}", result.warnings);
+    assert_eq!(
+        result.warnings[0].message,
+        "precondition `b > 0` of `Gaṇaka::bhāga` cannot be proved at this call"
+    );
+    assert_eq!(
+        result.warnings[0].location.as_ref().expect("location").line,
+        16
+    );
+}
+
+/// Test nothing is proved where integers wrap, as a proof would not hold
+#[test]
+fn test_prove_needs_trapping_overflow() {
+    let mut options = CompilerOptions::new();
+    options.prove_contracts = true;
+    let result = compile_with(DIVISION, options).expect("should compile");
+    assert_eq!(result.warnings.len(), 1, "{:?}", result.warnings);
+    assert!(
+        result.warnings[0]
+            .message
+            .contains("contracts are not proved in Rajas builds"),
+        "{}",
+        result.warnings[0].message
+    );
+}
@@ -0,0 +1,213 @@
+//! Integration tests for expression-oriented control flow
+//!
+//! `yad` and `cala` as values, labeled `break`/`continue` and loops that
+//! break with a value.
+
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompilerSession};
+use jagannath_compiler::parser::{ast::*, Parser};
+
+fn compile(source: &str) -> Result<String, CompileError> {
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    let mut session = CompilerSession::new(options);
+    session
+        .compile(source)
+        .map(|result| String::from_utf8_lossy(&result.output).into_owned())
+}
+
+/// Compile a function `f(n)` with `body`, returning the error message
+fn compile_error(body: &str) -> String {
+    let source = format!(
+        "kāryakrama f(n: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {{\n{}\n}}\n",
+        body
+    );
+    compile(&source).expect_err("should not type check").message
+}
+
+/// Test labels, break values and `anyathā yad` chains in the AST
+#[test]
+fn test_parse_labeled_loops_and_break_values() {
+    let source = r#"
+kāryakrama f(n: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    'bāhya: cala i : 0..n {
+        cala j : 0..n {
+            yad j > i { continue 'bāhya; }
+            yad i * j > 50 { break 'bāhya; }
+        }
+    }
+    let k = cala { break n * 2; };
+    yad k > 10 { 1 } anyathā yad k > 5 { 2 } anyathā { 3 }
+}
+"#;
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function declaration");
+    };
+    let stmts = &func.body.stmts;
+    assert_eq!(stmts.len(), 3);
+
+    let Stmt::Loop { label, body, .. } = &stmts[0] else {
+        panic!("Expected a loop, got {:?}", stmts[0]);
+    };
+    assert_eq!(label.as_ref().unwrap().name, "bāhya");
+    let Stmt::Loop {
+        label: None,
+        body: inner,
+        ..
+    } = &body.stmts[0]
+    else {
+        panic!("Expected an unlabeled inner loop");
+    };
+    let Stmt::If { then_block, .. } = &inner.stmts[0] else {
+        panic!("Expected yad");
+    };
+    assert!(matches!(
+        &then_block.stmts[0],
+        Stmt::Continue { label: Some(l), .. } if l.name == "bāhya"
+    ));
+
+    let Stmt::Let {
+        value: Some(Expr::Loop { kind, body, .. }),
+        ..
+    } = &stmts[1]
+    else {
+        panic!("Expected a loop expression, got {:?}", stmts[1]);
+    };
+    assert!(matches!(**kind, LoopKind::Infinite));
+    assert!(matches!(
+        &body.stmts[0],
+        Stmt::Break {
+            label: None,
+            value: Some(Expr::Binary { .. }),
+            ..
+        }
+    ));
+
+    // The tail `yad` is the function's value; its else branch nests the chain
+    assert!(func.body.tail().is_some());
+    let Stmt::If {
+        else_block: Some(else_block),
+        ..
+    } = &stmts[2]
+    else {
+        panic!("Expected yad with anyathā");
+    };
+    assert!(matches!(
+        &else_block.stmts[..],
+        [Stmt::If {
+            else_block: Some(_),
+            ..
+        }]
+    ));
+}
+
+/// Test `yad` in expression position, including an `anyathā yad` chain
+#[test]
+fn test_parse_if_expression_chain() {
+    let source =
+        "kāryakrama f(n: saṅkhyā) -> saṅkhyā {\n    let s = yad n < 0 { 0 } anyathā yad n < 10 { 1 } anyathā { 2 };\n    phera s;\n}\n";
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function declaration");
+    };
+    let Stmt::Let {
+        value:
+            Some(Expr::If {
+                then_expr,
+                else_expr: Some(else_expr),
+                ..
+            }),
+        ..
+    } = &func.body.stmts[0]
+    else {
+        panic!("Expected an if expression, got {:?}", func.body.stmts[0]);
+    };
+    assert!(matches!(**then_expr, Expr::Block(_)));
+    assert!(matches!(
+        **else_expr,
+        Expr::If {
+            else_expr: Some(_),
+            ..
+        }
+    ));
+}
+
+/// Test a loop's value is what it breaks with
+#[test]
+fn test_loop_breaks_with_value() {
+    let source = r#"
+kāryakrama f(n: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    let k = cala {
+        yad n > 3 { break 777; }
+        break 555;
+    };
+    phera k;
+}
+"#;
+    let asm = compile(source).expect("Loop expression should compile");
+    assert!(asm.contains("mov rax, 777"), "{}", asm);
+    assert!(asm.contains("mov rax, 555"), "{}", asm);
+}
+
+/// Test block tails give the value of blocks, `yad` and functions
+#[test]
+fn test_block_tail_values() {
+    let source = r#"
+kāryakrama mahat(a: saṅkhyā-a-k-t32, b: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    let m = yad a > b { a } anyathā { b };
+    let d = yad m > 0 {
+        let t = m * 2;
+        t + 1
+    } anyathā {
+        0
+    };
+    yad d > 100 { 100 } anyathā { d }
+}
+"#;
+    compile(source).expect("Tail expressions should type check");
+}
+
+/// Test labeled break and continue across nested loops
+#[test]
+fn test_labeled_break_in_nested_loops() {
+    let source = r#"
+kāryakrama khoja(n: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    let s = 0;
+    'bāhya: cala i : 0..n {
+        cala j : 0..n {
+            yad j > i { continue 'bāhya; }
+            yad i * j == 42 { break 'bāhya; }
+            s = s + j;
+        }
+    }
+    phera s;
+}
+"#;
+    compile(source).expect("Labeled loops should compile");
+}
+
+/// Test a `break` value must suit the loop
+#[test]
+fn test_break_value_errors() {
+    let err =
+        compile_error("    let k = cala { yad n > 0 { break 1; } break satya; };\n    phera 0;");
+    assert!(err.contains("break value"), "{}", err);
+
+    let err = compile_error("    cala i : 0..n { break i; }\n    phera 0;");
+    assert!(err.contains("Only `cala { }` loops"), "{}", err);
+}
+
+/// Test `break`/`continue` outside of loops and unknown labels
+#[test]
+fn test_break_outside_loop_and_unknown_label() {
+    let err = compile_error("    break;\n    phera 0;");
+    assert!(err.contains("`break` outside of a loop"), "{}", err);
+
+    let err = compile_error("    cala { continue 'kutra; }");
+    assert!(err.contains("labeled `'kutra`"), "{}", err);
+
+    // A closure body is not inside the loop around it
+    let err = compile_error("    cala { let g = || { break; }; }");
+    assert!(err.contains("outside of a loop"), "{}", err);
+}
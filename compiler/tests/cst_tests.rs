@@ -0,0 +1,257 @@
+//! Integration tests for the lossless concrete syntax tree
+
+use jagannath_compiler::lexer::TokenKind;
+use jagannath_compiler::parser::cst::nodes::{FnDef, Literal, MatchExpr, Pat, TypeDef};
+use jagannath_compiler::parser::cst::{
+    self, HasAttributes, HasDocComments, HasName, HasVisibility, Item, SyntaxKind, TypedNode,
+};
+
+const SOURCE: &str = r#"//! Geometry helpers
+//! for the examples
+
+// Licence header, not documentation
+
+/// A point
+/// in the plane
+pub prakāra Bindu {
+    /// Horizontal
+    x: saṅkhyā-a-k-t32,
+    y: saṅkhyā-a-k-t32, // trailing
+}
+
+/* block */ #[inline]
+/// Sum of the coordinates
+kāryakrama yoga(b: Bindu, @karaṇa k: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    let s = b.x + b.y * 2;   // precedence
+    phera s + k
+}
+"#;
+
+fn items(source: &str) -> Vec<Item> {
+    cst::parse(source).tree().items()
+}
+
+#[test]
+fn test_roundtrip_is_lossless() {
+    let parse = cst::parse(SOURCE);
+    assert!(parse.errors().is_empty(), "{:?}", parse.errors());
+    assert_eq!(parse.syntax().text(), SOURCE);
+    assert_eq!(parse.syntax().text_range(), 0..SOURCE.len());
+}
+
+#[test]
+fn test_roundtrip_examples() {
+    let root = concat!(env!("CARGO_MANIFEST_DIR"), "/..");
+    let mut checked = 0;
+    for dir in ["examples", "benchmarks/jagannath"] {
+        let Ok(entries) = std::fs::read_dir(format!("{}/{}", root, dir)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "jag") {
+                let source = std::fs::read_to_string(&path).unwrap();
+                let parse = cst::parse(&source);
+                assert_eq!(parse.syntax().text(), source, "{}", path.display());
+                checked += 1;
+            }
+        }
+    }
+    assert!(checked > 0);
+}
+
+#[test]
+fn test_comments_are_kept() {
+    let parse = cst::parse(SOURCE);
+    let comments: Vec<String> = parse
+        .tree()
+        .comments()
+        .iter()
+        .map(|token| token.text().to_string())
+        .collect();
+    assert!(comments.contains(&"// Licence header, not documentation".to_string()));
+    assert!(comments.contains(&"// trailing".to_string()));
+    assert!(comments.contains(&"/* block */".to_string()));
+    assert!(comments.contains(&"// precedence".to_string()));
+    assert_eq!(
+        parse.tree().module_docs(),
+        vec!["Geometry helpers", "for the examples"]
+    );
+}
+
+#[test]
+fn test_doc_comments_attach_to_items() {
+    let items = items(SOURCE);
+    assert_eq!(items.len(), 2);
+
+    let Item::Type(bindu) = &items[0] else {
+        panic!("expected a type, got {:?}", items[0]);
+    };
+    assert_eq!(bindu.doc_comments(), vec!["A point", "in the plane"]);
+    assert!(bindu.is_public());
+    let fields = bindu.fields();
+    assert_eq!(fields.len(), 2);
+    assert_eq!(fields[0].doc_string().as_deref(), Some("Horizontal"));
+    assert!(fields[1].doc_comments().is_empty());
+
+    let Item::Fn(yoga) = &items[1] else {
+        panic!("expected a function, got {:?}", items[1]);
+    };
+    // The block comment sits on the same line, so it is attached too, but
+    // it is not documentation
+    assert_eq!(yoga.doc_comments(), vec!["Sum of the coordinates"]);
+    assert_eq!(yoga.attributes().len(), 1);
+    assert_eq!(yoga.attributes()[0].name().as_deref(), Some("inline"));
+    assert!(!yoga.is_public());
+}
+
+#[test]
+fn test_typed_view_of_function() {
+    let items = items(SOURCE);
+    let func = FnDef::cast(items[1].syntax().clone()).unwrap();
+    assert_eq!(func.name().unwrap().text(), "yoga");
+
+    let params = func.param_list().unwrap().params();
+    assert_eq!(params.len(), 2);
+    assert_eq!(params[0].name().unwrap().text(), "b");
+    assert_eq!(params[0].ty().unwrap().syntax().text(), "Bindu");
+    assert_eq!(params[1].karaka().as_deref(), Some("karaṇa"));
+    assert_eq!(
+        func.ret_type().unwrap().ty().unwrap().syntax().text(),
+        "saṅkhyā-a-k-t32"
+    );
+    assert_eq!(
+        func.signature(),
+        "kāryakrama yoga(b: Bindu, @karaṇa k: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32"
+    );
+
+    let body = func.body().unwrap();
+    let stmts = body.statements();
+    assert_eq!(stmts.len(), 2);
+    assert_eq!(stmts[0].kind(), SyntaxKind::LetStmt);
+    assert_eq!(stmts[1].kind(), SyntaxKind::ReturnStmt);
+}
+
+#[test]
+fn test_expression_structure_and_literal_spans() {
+    let parse = cst::parse(SOURCE);
+    let root = parse.syntax();
+    // `b.x + b.y * 2`: multiplication binds tighter
+    let bin = root
+        .descendants()
+        .into_iter()
+        .find(|node| node.kind() == SyntaxKind::BinExpr)
+        .unwrap();
+    assert_eq!(bin.text(), "b.x + b.y * 2");
+    let operands: Vec<_> = bin.children().map(|node| node.kind()).collect();
+    assert_eq!(operands, vec![SyntaxKind::FieldExpr, SyntaxKind::BinExpr]);
+
+    let literal = root
+        .descendants()
+        .into_iter()
+        .find_map(Literal::cast)
+        .unwrap();
+    assert_eq!(literal.value(), Some(TokenKind::IntLiteral(2)));
+    let range = literal.text_range();
+    assert_eq!(&SOURCE[range], "2");
+}
+
+#[test]
+fn test_match_arms() {
+    let source = "kāryakrama f(n: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {\n    phera pratyabhijñā n {\n        0 | 1 => 1,\n        k yad k < 10 => 2, // small\n        _ => 3,\n    };\n}\n";
+    let parse = cst::parse(source);
+    assert!(parse.errors().is_empty(), "{:?}", parse.errors());
+    assert_eq!(parse.syntax().text(), source);
+
+    let matched = parse
+        .syntax()
+        .descendants()
+        .into_iter()
+        .find_map(MatchExpr::cast)
+        .unwrap();
+    assert_eq!(matched.scrutinee().unwrap().syntax().text(), "n");
+    let arms = matched.arms();
+    assert_eq!(arms.len(), 3);
+    let patterns: Vec<SyntaxKind> = arms
+        .iter()
+        .map(|arm| arm.pattern().map(|p: Pat| p.kind()).unwrap())
+        .collect();
+    assert_eq!(
+        patterns,
+        vec![
+            SyntaxKind::OrPat,
+            SyntaxKind::IdentPat,
+            SyntaxKind::WildcardPat
+        ]
+    );
+    let guard = arms[1].guard().unwrap();
+    assert_eq!(guard.condition().unwrap().syntax().text(), "k < 10");
+    assert_eq!(arms[1].body().unwrap().syntax().text(), "2");
+    assert_eq!(arms[1].syntax().text(), "k yad k < 10 => 2,");
+}
+
+#[test]
+fn test_enum_variants() {
+    let source = "prakāra Rūpa {\n    Vṛtta(saṅkhyā-a-k-t32),\n    Varga { bhuja: saṅkhyā-a-k-t32 },\n    Śūnya,\n}\n";
+    let items = items(source);
+    let ty = TypeDef::cast(items[0].syntax().clone()).unwrap();
+    assert!(ty.is_enum());
+    let variants = ty.variants();
+    let names: Vec<String> = variants.iter().map(|v| v.name().unwrap().text()).collect();
+    assert_eq!(names, vec!["Vṛtta", "Varga", "Śūnya"]);
+    let varga = variants[1].field_list().unwrap().fields();
+    assert_eq!(varga[0].name().unwrap().text(), "bhuja");
+    assert!(variants[2].field_list().is_none());
+}
+
+#[test]
+fn test_names_keep_their_script() {
+    let source = "कार्यक्रम योग() {}\n";
+    let items = items(source);
+    let Item::Fn(func) = &items[0] else {
+        panic!("expected a function");
+    };
+    let name = func.name().unwrap();
+    assert_eq!(name.text(), "योग");
+    assert_eq!(name.normalized(), "yoga");
+    assert_eq!(cst::parse(source).syntax().text(), source);
+}
+
+#[test]
+fn test_errors_keep_the_tree_whole() {
+    let source =
+        "kāryakrama a() { phera 1 }\n\nkāryakrama (broken {\n\n// after\nkāryakrama b() { phera 2 }\n";
+    let parse = cst::parse(source);
+    assert!(!parse.errors().is_empty());
+    assert_eq!(parse.syntax().text(), source);
+    let kinds: Vec<SyntaxKind> = parse.syntax().children().map(|n| n.kind()).collect();
+    assert_eq!(
+        kinds,
+        vec![SyntaxKind::FnDef, SyntaxKind::Error, SyntaxKind::FnDef]
+    );
+    let names: Vec<String> = parse
+        .tree()
+        .items()
+        .iter()
+        .filter_map(|item| match item {
+            Item::Fn(func) => func.name().map(|n| n.text()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(names, vec!["a", "b"]);
+}
+
+#[test]
+fn test_token_at_offset() {
+    let parse = cst::parse(SOURCE);
+    let offset = SOURCE.find("yoga(").unwrap() + 1;
+    let token = parse.syntax().token_at_offset(offset).unwrap();
+    assert_eq!(token.text(), "yoga");
+    assert_eq!(token.kind(), SyntaxKind::Ident);
+    assert_eq!(token.parent().kind(), SyntaxKind::Name);
+    let ancestors: Vec<SyntaxKind> = token.parent().ancestors().map(|n| n.kind()).collect();
+    assert_eq!(
+        ancestors,
+        vec![SyntaxKind::Name, SyntaxKind::FnDef, SyntaxKind::SourceFile]
+    );
+}
@@ -0,0 +1,177 @@
+//! Integration tests for enum values: tagged-union layout, construction
+//! and payload extraction in `pratyabhijñā`
+
+use jagannath_compiler::codegen::asm::Target;
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompilerSession};
+
+fn compile_for_target(source: &str, target: Target) -> Result<String, CompileError> {
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    options.target = target;
+    let mut session = CompilerSession::new(options);
+    session
+        .compile(source)
+        .map(|result| String::from_utf8_lossy(&result.output).into_owned())
+}
+
+fn compile(source: &str) -> Result<String, CompileError> {
+    compile_for_target(source, Target::X86_64)
+}
+
+/// Built and taken apart: a byte of tag, then the payload
+const BHAGA: &str = r#"
+kāryakrama bhaga(a: saṅkhyā, b: saṅkhyā) -> Phala<saṅkhyā, bool> {
+    yad b == 0 {
+        phera Phala::Viphala(satya);
+    }
+    phera Phala::Saphala(a / b);
+}
+
+kāryakrama mulya(p: Phala<saṅkhyā, bool>, d: saṅkhyā) -> saṅkhyā {
+    pratyabhijñā p {
+        Phala::Saphala(x) => x,
+        Phala::Viphala(_) => d,
+    }
+}
+"#;
+
+/// Test constructing an enum writes a one-byte tag and the payload after it
+#[test]
+fn test_construct_x86_64() {
+    let asm = compile(BHAGA).expect("Phala values should compile");
+    assert!(asm.contains("mov BYTE PTR [rbp-16], 1"), "{}", asm);
+    assert!(asm.contains("mov BYTE PTR [rbp-16], 0"), "{}", asm);
+    // Returned in RAX:RDX
+    assert!(asm.contains("mov rax, QWORD PTR [rbp-16]"), "{}", asm);
+    assert!(asm.contains("mov rdx, QWORD PTR [rbp-8]"), "{}", asm);
+}
+
+/// Test `pratyabhijñā` reads the tag alone and the payload from the word after it
+#[test]
+fn test_match_payload_x86_64() {
+    let asm = compile(BHAGA).expect("Matching a Phala should compile");
+    let mulya = &asm[asm.find("mulya:").expect("mulya is emitted")..];
+    // The parameter arrives in two registers
+    assert!(mulya.contains("mov QWORD PTR [rbp-24], rdi"), "{}", mulya);
+    assert!(mulya.contains("mov QWORD PTR [rbp-16], rsi"), "{}", mulya);
+    assert!(mulya.contains("movzx rax, BYTE PTR [rbp-48]"), "{}", mulya);
+    assert!(mulya.contains("mov rax, QWORD PTR [rbp-40]"), "{}", mulya);
+}
+
+#[test]
+fn test_enum_aarch64() {
+    let asm = compile_for_target(BHAGA, Target::AArch64).expect("Phala values should compile");
+    assert!(asm.contains("strb w0, [x29, #-16]"), "{}", asm);
+    assert!(asm.contains("ldrb w0"), "{}", asm);
+    assert!(asm.contains("ldr x1, [x29, #-8]"), "{}", asm);
+}
+
+#[test]
+fn test_enum_riscv64() {
+    let asm = compile_for_target(BHAGA, Target::RiscV64).expect("Phala values should compile");
+    assert!(asm.contains("sb t0, -32(s0)"), "{}", asm);
+    assert!(asm.contains("lbu t0"), "{}", asm);
+    assert!(asm.contains("ld a1, -24(s0)"), "{}", asm);
+}
+
+/// Test `Vikalpa<&T>` has no tag: `Śūnya` is a null reference
+#[test]
+fn test_reference_niche() {
+    let source = r#"
+kāryakrama prathama(v: &saṅkhyā, b: bool) -> Vikalpa<&saṅkhyā> {
+    yad b {
+        phera Vikalpa::Kiñcit(v);
+    }
+    phera Vikalpa::Śūnya;
+}
+
+kāryakrama hai(v: Vikalpa<&saṅkhyā>) -> bool {
+    pratyabhijñā v {
+        Vikalpa::Śūnya => asatya,
+        Vikalpa::Kiñcit(_) => satya,
+    }
+}
+"#;
+    let asm = compile(source).expect("Vikalpa<&T> should compile");
+    assert!(!asm.contains("BYTE PTR"), "{}", asm);
+    assert!(asm.contains("mov QWORD PTR [rbp-8], 0"), "{}", asm);
+    // Only RAX carries the value back
+    assert!(!asm.contains("mov rdx"), "{}", asm);
+    assert!(asm.contains("cmovne"), "{}", asm);
+
+    let asm = compile_for_target(source, Target::AArch64).expect("Vikalpa<&T> should compile");
+    assert!(asm.contains("str xzr"), "{}", asm);
+    let asm = compile_for_target(source, Target::RiscV64).expect("Vikalpa<&T> should compile");
+    assert!(asm.contains("sd zero"), "{}", asm);
+}
+
+/// Test a user enum with several fields in a variant, built and matched
+#[test]
+fn test_user_enum() {
+    let source = r#"
+prakāra Ākāra {
+    Bindu,
+    Vṛtta(saṅkhyā),
+    Āyata(saṅkhyā, saṅkhyā),
+}
+
+kāryakrama kṣetra(a: Ākāra) -> saṅkhyā {
+    pratyabhijñā a {
+        Ākāra::Bindu => 0,
+        Ākāra::Vṛtta(r) => 3 * r * r,
+        Ākāra::Āyata(l, b) => l * b,
+    }
+}
+
+kāryakrama banā(l: saṅkhyā) -> saṅkhyā {
+    let a = Ākāra::Āyata(l, 2);
+    phera kṣetra(a);
+}
+"#;
+    let asm = compile(source).expect("Ākāra should compile");
+    // Three words: the tag, then the two fields of Āyata
+    assert!(asm.contains("mov BYTE PTR [rbp-32], 2"), "{}", asm);
+    assert!(asm.contains("mov QWORD PTR [rbp-16], rax"), "{}", asm);
+    assert!(asm.contains("mov rdx, QWORD PTR [rbp-16]"), "{}", asm);
+    assert!(asm.contains("call kṣetra"), "{}", asm);
+}
+
+/// Test a nested pattern reads the inner payload out of the outer one
+#[test]
+fn test_nested_payload() {
+    let source = r#"
+kāryakrama gabhira(v: Vikalpa<Vikalpa<saṅkhyā> >) -> saṅkhyā {
+    pratyabhijñā v {
+        Vikalpa::Kiñcit(Vikalpa::Kiñcit(x)) => x,
+        _ => 0,
+    }
+}
+"#;
+    let asm = compile(source).expect("Nested Vikalpa should compile");
+    // Both tags are tested, and the arm reaching `x` is kept
+    assert_eq!(asm.matches("movzx rax, BYTE PTR").count(), 2, "{}", asm);
+    assert!(
+        !asm.contains(".Lgabhira_bb2:\n    jmp .Lgabhira_bb2\n"),
+        "{}",
+        asm
+    );
+    assert!(asm.contains("mov rax, QWORD PTR [rbp-32]"), "{}", asm);
+}
+
+/// Test `Vikalpa` and `Phala` are ordinary names, not keywords
+#[test]
+fn test_prelude_enums() {
+    let source = r#"
+kāryakrama f(Vikalpa: saṅkhyā) -> saṅkhyā {
+    phera Vikalpa;
+}
+"#;
+    compile(source).expect("Vikalpa can be shadowed as a value");
+
+    let source = "kāryakrama f(p: Phala<saṅkhyā, bool>) {\n    let x: Phala<bool, bool> = p;\n}\n";
+    let err = compile(source)
+        .expect_err("Phala's arguments are checked")
+        .message;
+    assert!(err.contains("'x'"), "{}", err);
+}
@@ -0,0 +1,162 @@
+//! End-to-end tests: programs built into executables, linked with the
+//! runtime, and run
+
+use jagannath_compiler::codegen::Assembler;
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::CompilerSession;
+use std::process::{Command, Output};
+
+/// Build `source` into an executable named `name` and run it, or `None`
+/// where there is no assembler to build with
+fn run(name: &str, source: &str) -> Option<Output> {
+    if !Assembler::gcc().is_available() {
+        eprintln!("skipping `{}`: no assembler", name);
+        return None;
+    }
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let exe = dir.path().join(name);
+    let mut options = CompilerOptions::new();
+    options.output = Some(exe.to_string_lossy().into_owned());
+    let mut session = CompilerSession::new(options);
+    if let Err(err) = session.compile(source) {
+        panic!("{} should build: {}", name, err.message);
+    }
+    Some(Command::new(&exe).output().expect("the executable runs"))
+}
+
+/// Test `mudraṇa!` prints through the runtime, plain and formatted
+#[test]
+fn test_print() {
+    let source = r#"
+kāryakrama mukhya() -> saṅkhyā {
+    mudraṇa!("namaste");
+    let x = 42;
+    mudraṇa!("x = {}, {:>4}!", x, satya);
+    phera 0;
+}
+"#;
+    let Some(output) = run("mudrana", source) else {
+        return;
+    };
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "namaste\nx = 42, satya!\n"
+    );
+}
+
+/// Test a float held in a local is formatted by precision and exponent
+#[test]
+fn test_print_float_local() {
+    let source = r#"
+kāryakrama mukhya() -> saṅkhyā {
+    let f = 3.14159;
+    mudraṇa!("{:.2} {:e}", f, f);
+    phera 0;
+}
+"#;
+    let Some(output) = run("bhinna", source) else {
+        return;
+    };
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3.14 3.14159e0\n");
+}
+
+/// Test a program of several functions, each with branches of its own
+#[test]
+fn test_functions() {
+    let source = r#"
+kāryakrama kramaguṇita(n: saṅkhyā) -> saṅkhyā {
+    yad n <= 1 {
+        phera 1;
+    }
+    phera n * kramaguṇita(n - 1);
+}
+
+kāryakrama mukhya() -> saṅkhyā {
+    yad kramaguṇita(3) > 5 {
+        mudraṇa!("{}", kramaguṇita(5));
+    }
+    phera 0;
+}
+"#;
+    let Some(output) = run("kramagunita", source) else {
+        return;
+    };
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "120\n");
+}
+
+/// Test a `Sūci` grows, is iterated and shrinks through the runtime
+#[test]
+fn test_suci() {
+    let source = r#"
+kāryakrama mukhya() -> saṅkhyā {
+    let v = Sūci::nava();
+    cala i : 1..21 {
+        v.yojaya(i * i);
+    }
+    let yoga = 0;
+    cala x : v {
+        yoga += x;
+    }
+    let antima = v.niṣkāsaya();
+    mudraṇa!("{} {}", yoga, antima);
+    phera antima - 300;
+}
+"#;
+    let Some(output) = run("suci", source) else {
+        return;
+    };
+    // 1² + … + 20² = 2870; holding 20 values, it grows several times over
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "2870 400\n");
+    assert_eq!(output.status.code(), Some(100));
+}
+
+/// Test popping an empty `Sūci` fails the program like an assertion,
+/// rather than aborting
+#[test]
+fn test_suci_pop_empty() {
+    let source = r#"
+kāryakrama mukhya() -> saṅkhyā {
+    let v = Sūci::nava();
+    v.yojaya(7);
+    mudraṇa!("{}", v.niṣkāsaya());
+    phera v.niṣkāsaya();
+}
+"#;
+    let Some(output) = run("suci_rikta", source) else {
+        return;
+    };
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "7\n");
+    assert_eq!(output.status.code(), Some(101));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("niṣkāsaya on an empty sūci"), "{}", stderr);
+}
+
+/// Test a failed assertion reaches the runtime, which reports it and
+/// exits, after what was printed before it
+#[test]
+fn test_failed_assertion() {
+    let source = r#"
+kāryakrama mukhya() -> saṅkhyā {
+    let x = 6 * 7;
+    assert!(x > 0);
+    mudraṇa!("x = {}", x);
+    assert_eq!(x, 41);
+    mudraṇa!("unreachable");
+    phera 0;
+}
+"#;
+    let Some(output) = run("viksepa", source) else {
+        return;
+    };
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "x = 42\n");
+    assert_eq!(output.status.code(), Some(101));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("assertion `left == right` failed at line 6"),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,188 @@
+//! Integration tests for string interpolation in `mudraṇa!` and `vinyāsa!`
+
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompilerSession};
+use jagannath_compiler::parser::format::{Align, FormatKind};
+use jagannath_compiler::parser::{ast::*, Parser};
+
+fn compile(source: &str) -> Result<String, CompileError> {
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    let mut session = CompilerSession::new(options);
+    session
+        .compile(source)
+        .map(|result| String::from_utf8_lossy(&result.output).into_owned())
+}
+
+/// Parse a function with `body`, returning the parse error messages
+fn parse_errors(body: &str) -> Vec<String> {
+    let source = format!("kāryakrama f(n: saṅkhyā-a-k-t32) {{\n{}\n}}\n", body);
+    Parser::parse_str(&source)
+        .expect_err("should not parse")
+        .into_iter()
+        .map(|e| e.message)
+        .collect()
+}
+
+const BINDU: &str = r#"
+prakāra Bindu {
+    x: saṅkhyā-a-k-t32,
+}
+"#;
+
+/// Test placeholders resolve to arguments, captures and specifications
+#[test]
+fn test_parse_interpolation() {
+    let source = r#"
+kāryakrama f(n: saṅkhyā-a-k-t32, nāma: sūtra) -> sūtra {
+    phera vinyāsa!("{nāma:>10} = {} ({0:#x}), {{n}}", n);
+}
+"#;
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function declaration");
+    };
+    let Stmt::Return {
+        value: Some(Expr::Format { pieces, args, .. }),
+        ..
+    } = &func.body.stmts[0]
+    else {
+        panic!("Expected a format expression, got {:?}", func.body.stmts[0]);
+    };
+
+    // The explicit argument comes first, then the captured `nāma`
+    assert_eq!(args.len(), 2);
+    assert!(matches!(&args[0], Expr::Identifier(id) if id.name == "n"));
+    assert!(matches!(&args[1], Expr::Identifier(id) if id.name == "nāma"));
+
+    let FormatPiece::Arg { index: 1, spec } = &pieces[0] else {
+        panic!("Expected the captured argument, got {:?}", pieces[0]);
+    };
+    assert_eq!(spec.align, Some(Align::Right));
+    assert_eq!(spec.width, Some(10));
+    assert!(matches!(&pieces[1], FormatPiece::Text(t) if t == " = "));
+    assert!(matches!(&pieces[2], FormatPiece::Arg { index: 0, .. }));
+    let FormatPiece::Arg { index: 0, spec } = &pieces[4] else {
+        panic!("Expected `{{0:#x}}`, got {:?}", pieces[4]);
+    };
+    assert_eq!(spec.kind, FormatKind::LowerHex);
+    assert!(spec.alternate);
+    assert!(matches!(pieces.last(), Some(FormatPiece::Text(t)) if t == "), {n}"));
+}
+
+/// Test `mudraṇa!` prints a line, and a lone value as if by `"{}"`
+#[test]
+fn test_parse_mudrana() {
+    let source = "kāryakrama f(n: saṅkhyā-a-k-t32) {\n    mudraṇa!(n);\n}\n";
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function declaration");
+    };
+    let Stmt::Expr(Expr::Call { callee, args, .. }) = &func.body.stmts[0] else {
+        panic!("Expected a call, got {:?}", func.body.stmts[0]);
+    };
+    assert!(matches!(&**callee, Expr::Identifier(id) if id.name == "mudrā"));
+    let [Expr::Format { pieces, args, .. }] = &args[..] else {
+        panic!("Expected mudrā(vinyāsa!(..)), got {:?}", args);
+    };
+    assert_eq!(args.len(), 1);
+    assert!(matches!(
+        &pieces[..],
+        [FormatPiece::Arg { index: 0, .. }, FormatPiece::Text(t)] if t == "\n"
+    ));
+}
+
+/// Test malformed templates and mismatched arguments are parse errors
+#[test]
+fn test_template_errors() {
+    let errors = parse_errors(r#"    mudraṇa!("{} {}", n);"#);
+    assert!(
+        errors.iter().any(|e| e.contains("refers to argument 1")),
+        "{:?}",
+        errors
+    );
+
+    let errors = parse_errors(r#"    mudraṇa!("{}", n, n);"#);
+    assert!(
+        errors
+            .iter()
+            .any(|e| e.contains("argument 1 of mudraṇa! is never used")),
+        "{:?}",
+        errors
+    );
+
+    let errors = parse_errors(r#"    mudraṇa!("{:>q}", n);"#);
+    assert!(
+        errors.iter().any(|e| e.contains("unknown format type `q`")),
+        "{:?}",
+        errors
+    );
+
+    let errors = parse_errors("    let s = vinyāsa!(n);");
+    assert!(
+        errors
+            .iter()
+            .any(|e| e.contains("expects a string literal")),
+        "{:?}",
+        errors
+    );
+}
+
+/// Test each placeholder is checked against its formatting trait
+#[test]
+fn test_formatting_traits_are_checked() {
+    let source = r#"
+kāryakrama f(nāma: sūtra) {
+    mudraṇa!("{nāma:x}");
+}
+"#;
+    let err = compile(source).expect_err("hex of a sūtra should not type check");
+    assert!(err.message.contains("LowerHex"), "{}", err.message);
+
+    let source = format!(
+        "{}\nkāryakrama f(b: Bindu) {{\n    mudraṇa!(\"{{}}\", b);\n}}\n",
+        BINDU
+    );
+    let err = compile(&source).expect_err("Bindu has no Display impl");
+    assert!(
+        err.message.contains("does not implement `Display`"),
+        "{}",
+        err.message
+    );
+}
+
+/// Test values format through runtime routines and user impls
+#[test]
+fn test_lowering_to_runtime_calls() {
+    let source = format!(
+        r#"{}
+kriyānvaya Display for Bindu {{
+    kāryakrama fmt(&sva) -> sūtra {{
+        phera vinyāsa!("({{}})", sva.x);
+    }}
+}}
+
+kāryakrama mukhya(b: Bindu) -> saṅkhyā-a-k-t32 {{
+    let n = 255;
+    mudraṇa!("n = {{n:>8}} {{n:#x}} {{}} {{:?}} नमस्ते", b, "pāṭha");
+    phera 0;
+}}
+"#,
+        BINDU
+    );
+    let asm = compile(&source).expect("Formatting should compile");
+    assert!(asm.contains("call jag_vinyasa_arambha"), "{}", asm);
+    assert!(asm.contains("call jag_vinyasa_int"), "{}", asm);
+    assert!(asm.contains("call Bindu__fmt"), "{}", asm);
+    assert!(asm.contains("call jag_vinyasa_samapti"), "{}", asm);
+    assert!(asm.contains("call jag_mudra"), "{}", asm);
+    // Text lives in .rodata, non-ASCII bytes escaped
+    assert!(asm.contains(".section .rodata"), "{}", asm);
+    assert!(asm.contains(".asciz \"n = \""), "{}", asm);
+    assert!(
+        asm.contains(".asciz \"p\\304\\201\\341\\271\\255ha\""),
+        "{}",
+        asm
+    );
+    assert!(asm.contains("OFFSET .LS_mukhya_0"), "{}", asm);
+}
@@ -0,0 +1,212 @@
+//! Integration tests for generic functions (Sāmānya-Viśeṣa)
+
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompilerSession};
+
+fn compile(source: &str) -> Result<String, CompileError> {
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    options.opt_level = 0;
+    let mut session = CompilerSession::new(options);
+    session
+        .compile(source)
+        .map(|result| String::from_utf8_lossy(&result.output).into_owned())
+}
+
+const GANYA: &str = r#"
+guṇa Gaṇya {
+    kāryakrama gaṇanā(&sva) -> saṅkhyā-a-k-t32;
+}
+
+prakāra Bindu {
+    x: saṅkhyā-a-k-t32,
+}
+
+kriyānvaya Gaṇya for Bindu {
+    kāryakrama gaṇanā(&sva) -> saṅkhyā-a-k-t32 {
+        phera sva.x;
+    }
+}
+
+kāryakrama dvi<T: Gaṇya>(v: T) -> saṅkhyā-a-k-t32 {
+    phera v.gaṇanā() * 2;
+}
+"#;
+
+/// Test one instance is generated per concrete type argument
+#[test]
+fn test_instance_per_type() {
+    let asm = compile(
+        r#"
+kāryakrama eva<T>(x: T) -> T {
+    phera x;
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    let a = eva(satya);
+    let b = eva(1);
+    phera eva(b);
+}
+"#,
+    )
+    .expect("Generic calls should compile");
+    assert!(asm.contains("eva__i32:"), "{}", asm);
+    assert!(asm.contains("eva__bool:"), "{}", asm);
+    assert!(!asm.contains("eva:"), "{}", asm);
+    // Two calls with i32 share one instance
+    assert_eq!(asm.matches("eva__i32:").count(), 1, "{}", asm);
+    assert!(asm.contains("call eva__bool"), "{}", asm);
+    assert_eq!(asm.matches("call eva__i32").count(), 2, "{}", asm);
+}
+
+/// Test generic functions calling generic functions are instantiated
+#[test]
+fn test_nested_instantiation() {
+    let asm = compile(
+        r#"
+kāryakrama eva<T>(x: T) -> T {
+    phera x;
+}
+
+kāryakrama punah<U>(x: U) -> U {
+    phera eva(x);
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera punah(7);
+}
+"#,
+    )
+    .expect("Nested generic calls should compile");
+    assert!(asm.contains("punah__i32:"), "{}", asm);
+    assert!(asm.contains("eva__i32:"), "{}", asm);
+    assert!(asm.contains("call eva__i32"), "{}", asm);
+}
+
+/// Test a satisfied bound compiles
+#[test]
+fn test_bound_satisfied() {
+    let source = format!(
+        "{}\nkāryakrama mukhya(b: Bindu) -> saṅkhyā-a-k-t32 {{\n    phera dvi(b);\n}}\n",
+        GANYA
+    );
+    let asm = compile(&source).expect("Bindu implements Gaṇya");
+    assert!(asm.contains("dvi__Bindu:"), "{}", asm);
+}
+
+/// Test an unsatisfied bound is reported at the call site
+#[test]
+fn test_bound_not_satisfied() {
+    let source = format!(
+        "{}\nkāryakrama mukhya() -> saṅkhyā-a-k-t32 {{\n    let n = 3;\n    phera dvi(n);\n}}\n",
+        GANYA
+    );
+    let err = compile(&source).expect_err("i32 does not implement Gaṇya");
+    assert!(err.message.contains("Gaṇya"), "{}", err.message);
+    let call_line = source
+        .lines()
+        .position(|line| line.contains("dvi(n)"))
+        .unwrap()
+        + 1;
+    assert_eq!(err.location.expect("location").line, call_line);
+}
+
+/// Test a bound is satisfied by the bounds of an enclosing generic parameter
+#[test]
+fn test_bound_through_generic_parameter() {
+    let source = format!(
+        "{}\nkāryakrama catur<S: Gaṇya>(v: S) -> saṅkhyā-a-k-t32 {{\n    phera dvi(v) * 2;\n}}\n\nkāryakrama mukhya(b: Bindu) -> saṅkhyā-a-k-t32 {{\n    phera catur(b);\n}}\n",
+        GANYA
+    );
+    let asm = compile(&source).expect("S: Gaṇya satisfies T: Gaṇya");
+    assert!(asm.contains("catur__Bindu:"), "{}", asm);
+    assert!(asm.contains("dvi__Bindu:"), "{}", asm);
+}
+
+/// Test a generic type is instantiated per type argument, with the
+/// methods of its generic impl, and a generic method per type argument
+/// of each call
+#[test]
+fn test_generic_type_and_method() {
+    let asm = compile(
+        r#"
+prakāra Peti<T> {
+    v: T,
+    w: saṅkhyā,
+}
+
+kriyānvaya<T> Peti<T> {
+    kāryakrama mūlya(&sva) -> T {
+        phera sva.v;
+    }
+
+    kāryakrama tulya<U>(&sva, u: U) -> U {
+        phera u;
+    }
+}
+
+kāryakrama mukhya(p: Peti<saṅkhyā>, q: Peti<bool>) -> saṅkhyā {
+    let b = q.tulya(7);
+    let c = q.tulya(satya);
+    phera p.mūlya() + p.tulya(1) + b + p.w;
+}
+"#,
+    )
+    .expect("Generic types and methods should compile");
+    for symbol in [
+        "Peti__i32__mūlya",
+        "Peti__i32__tulya__i32",
+        "Peti__bool__tulya__i32",
+        "Peti__bool__tulya__bool",
+    ] {
+        assert!(asm.contains(&format!("{}:", symbol)), "{}", asm);
+        assert!(asm.contains(&format!("call {}", symbol)), "{}", asm);
+    }
+    assert!(asm.contains("Peti__bool__mūlya:"), "{}", asm);
+    assert!(!asm.contains("Peti__mūlya"), "{}", asm);
+    assert!(!asm.contains("Peti__tulya"), "{}", asm);
+}
+
+/// Test a generic enum keeps its variants, named through it, with its
+/// methods instantiated
+#[test]
+fn test_generic_enum() {
+    let asm = compile(
+        r#"
+prakāra Khaṇḍa<T> {
+    Eka(T),
+    Śūnya,
+}
+
+kriyānvaya<T> Khaṇḍa<T> {
+    kāryakrama athavā(&sva, d: T) -> T {
+        pratyabhijñā sva {
+            Khaṇḍa::Eka(x) => x,
+            Khaṇḍa::Śūnya => d,
+        }
+    }
+}
+
+kāryakrama mukhya() -> saṅkhyā {
+    let a = Khaṇḍa::Eka(5);
+    phera a.athavā(1);
+}
+"#,
+    )
+    .expect("Generic enums should compile");
+    assert!(asm.contains("call Khaṇḍa__i32__athavā"), "{}", asm);
+    assert!(asm.contains("Khaṇḍa__i32__athavā:"), "{}", asm);
+    assert!(!asm.contains("call Khaṇḍa__Eka"), "{}", asm);
+}
+
+/// Test the bounds of a generic method are checked at its calls
+#[test]
+fn test_method_bound_not_satisfied() {
+    let source = format!(
+        "{}\nkriyānvaya Bindu {{\n    kāryakrama yuta<T: Gaṇya>(&sva, v: T) -> saṅkhyā-a-k-t32 {{\n        phera v.gaṇanā() + sva.x;\n    }}\n}}\n\nkāryakrama mukhya(b: Bindu) -> saṅkhyā-a-k-t32 {{\n    phera b.yuta(b) + b.yuta(3);\n}}\n",
+        GANYA
+    );
+    let err = compile(&source).expect_err("i32 does not implement Gaṇya");
+    assert!(err.message.contains("Gaṇya"), "{}", err.message);
+    assert!(err.message.contains("i32"), "{}", err.message);
+}
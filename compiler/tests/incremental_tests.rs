@@ -0,0 +1,143 @@
+//! Integration tests for incremental compilation (Karma Saṃkalana)
+
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::CompilerSession;
+use std::path::Path;
+
+const PROGRAM: &str = r#"
+kāryakrama yoga(a: saṅkhyā-a-k-t32, b: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    phera a + b;
+}
+
+kāryakrama dviguṇa(a: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    phera a * 2;
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera yoga(1, dviguṇa(2));
+}
+"#;
+
+fn session(cache_dir: Option<&Path>) -> CompilerSession {
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    options.opt_level = 0;
+    options.incremental_dir = cache_dir.map(|dir| dir.display().to_string());
+    CompilerSession::new(options)
+}
+
+fn asm(session: &mut CompilerSession, source: &str) -> String {
+    let result = session.compile(source).expect("Program should compile");
+    String::from_utf8_lossy(&result.output).into_owned()
+}
+
+/// Test recompiling an unchanged program does no new work
+#[test]
+fn test_unchanged_recompile() {
+    let mut session = session(None);
+    let first = asm(&mut session, PROGRAM);
+    let before = session.phase_stats();
+    let second = asm(&mut session, PROGRAM);
+    let after = session.phase_stats();
+
+    assert_eq!(first, second);
+    assert_eq!(after.items_checked, before.items_checked);
+    assert_eq!(after.items_lowered, before.items_lowered);
+    assert_eq!(after.functions_generated, before.functions_generated);
+}
+
+/// Test editing one function body redoes only that function
+#[test]
+fn test_edit_invalidates_one_item() {
+    let mut session = session(None);
+    asm(&mut session, PROGRAM);
+    let before = session.phase_stats();
+
+    let edited = PROGRAM.replace("phera a * 2;", "phera a * 3;");
+    let output = asm(&mut session, &edited);
+    let after = session.phase_stats();
+
+    assert_eq!(after.items_checked - before.items_checked, 1);
+    assert_eq!(after.items_lowered - before.items_lowered, 1);
+    assert_eq!(after.functions_generated - before.functions_generated, 1);
+
+    // Same output as a clean build
+    assert_eq!(output, asm(&mut self::session(None), &edited));
+}
+
+/// Test a signature change re-checks the items that can see it
+#[test]
+fn test_signature_change_rechecks_callers() {
+    let mut session = session(None);
+    asm(&mut session, PROGRAM);
+    let before = session.phase_stats();
+
+    // Items see each other's signatures, so all of them are checked again
+    let edited = PROGRAM.replace(
+        "dviguṇa(a: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {\n    phera a * 2;",
+        "dviguṇa(x: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {\n    phera x * 2;",
+    );
+    assert_ne!(edited, PROGRAM);
+    asm(&mut session, &edited);
+    let after = session.phase_stats();
+    assert_eq!(after.items_checked - before.items_checked, 3);
+}
+
+/// Test editing an enum lowers again the items built against its layout
+#[test]
+fn test_enum_change_relowers_users() {
+    let program = r#"
+prakāra Ākāra {
+    Bindu,
+    Vṛtta(saṅkhyā),
+    Āyata(saṅkhyā, saṅkhyā),
+}
+
+kāryakrama kṣetra(a: Ākāra) -> saṅkhyā {
+    pratyabhijñā a {
+        Ākāra::Bindu => 0,
+        Ākāra::Vṛtta(r) => 3 * r * r,
+        Ākāra::Āyata(l, b) => l * b,
+    }
+}
+
+kāryakrama banā(l: saṅkhyā) -> saṅkhyā {
+    let a = Ākāra::Āyata(l, 2);
+    phera kṣetra(a);
+}
+"#;
+    let mut session = session(None);
+    asm(&mut session, program);
+    let before = session.phase_stats();
+
+    // Moving `Bindu` last renumbers the tags the functions build and test
+    let edited = program.replace(
+        "    Bindu,\n    Vṛtta(saṅkhyā),\n    Āyata(saṅkhyā, saṅkhyā),\n",
+        "    Vṛtta(saṅkhyā),\n    Āyata(saṅkhyā, saṅkhyā),\n    Bindu,\n",
+    );
+    assert_ne!(edited, program);
+    let output = asm(&mut session, &edited);
+    let after = session.phase_stats();
+
+    assert_eq!(after.items_lowered - before.items_lowered, 3);
+    assert_eq!(output, asm(&mut self::session(None), &edited));
+}
+
+/// Test the cache in the build directory is reused by a new session
+#[test]
+fn test_cache_persists_in_build_dir() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let lakshya = dir.path().join("lakṣya");
+
+    let first = asm(&mut session(Some(&lakshya)), PROGRAM);
+    assert!(lakshya.join("karma-kosha.json").exists());
+
+    let mut second_session = session(Some(&lakshya));
+    let second = asm(&mut second_session, PROGRAM);
+    let stats = second_session.phase_stats();
+
+    assert_eq!(first, second);
+    assert_eq!(stats.items_checked, 0);
+    assert_eq!(stats.functions_generated, 0);
+    assert_eq!(stats.functions_reused, 3);
+}
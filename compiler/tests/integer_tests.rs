@@ -0,0 +1,222 @@
+//! Integration tests for integer widths: width affixes, arithmetic that
+//! wraps or traps by guṇa, implicit conversions and `as` casts
+
+use jagannath_compiler::codegen::asm::Target;
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompilerSession};
+use jagannath_compiler::parser::{ast::*, Parser};
+use jagannath_compiler::philosophy::guna::Guna;
+
+fn compile_with(source: &str, target: Target, guna: Guna) -> Result<String, CompileError> {
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    options.target = target;
+    options.guna = guna;
+    let mut session = CompilerSession::new(options);
+    session
+        .compile(source)
+        .map(|result| String::from_utf8_lossy(&result.output).into_owned())
+}
+
+fn compile(source: &str) -> Result<String, CompileError> {
+    compile_with(source, Target::X86_64, Guna::Rajas)
+}
+
+/// An 8-bit sum and a 32-bit unsigned quotient compared
+const ARITHMETIC: &str = r#"
+kāryakrama yoga(a: saṅkhyā-t8, b: saṅkhyā-t8) -> saṅkhyā-t8 {
+    phera a + b;
+}
+
+kāryakrama bhaga(a: u32, b: u32) -> bool {
+    let c: u32 = a / b;
+    phera c < a;
+}
+"#;
+
+#[test]
+fn test_parse_cast() {
+    let source = "kāryakrama f(x: saṅkhyā) -> daśamika {\n    phera -x as daśamika * 2.0;\n}\n";
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function declaration");
+    };
+    // `as` binds tighter than `*` and looser than `-`
+    let Stmt::Return {
+        value: Some(Expr::Binary { left, .. }),
+        ..
+    } = &func.body.stmts[0]
+    else {
+        panic!("Expected a return of a product");
+    };
+    assert!(matches!(
+        left.as_ref(),
+        Expr::Cast { expr, .. } if matches!(**expr, Expr::Unary { .. })
+    ));
+}
+
+/// Test Rajas wraps a narrow result back into its width
+#[test]
+fn test_wrapping() {
+    let asm = compile(ARITHMETIC).expect("arithmetic should compile");
+    let yoga = &asm[asm.find("yoga:").expect("yoga is emitted")..];
+    let yoga = &yoga[..yoga.find(".size").expect("yoga ends")];
+    assert!(yoga.contains("add rax, rdx\n    movsx rax, al"), "{}", yoga);
+    assert!(!yoga.contains("ud2"), "{}", yoga);
+
+    // Unsigned integers divide and compare unsigned
+    let bhaga = &asm[asm.find("bhaga:").expect("bhaga is emitted")..];
+    assert!(bhaga.contains("xor edx, edx\n    div r11"), "{}", bhaga);
+    assert!(bhaga.contains("setb al"), "{}", bhaga);
+}
+
+/// Test Sattva traps on a result its width cannot hold
+#[test]
+fn test_trapping() {
+    let asm = compile_with(ARITHMETIC, Target::X86_64, Guna::Sattva).expect("should compile");
+    let yoga = &asm[asm.find("yoga:").expect("yoga is emitted")..];
+    let yoga = &yoga[..yoga.find(".size").expect("yoga ends")];
+    assert!(yoga.contains("movsx rcx, al\n    cmp rcx, rax"), "{}", yoga);
+    assert!(yoga.contains("ud2\n.Lno_overflow_yoga_0:"), "{}", yoga);
+
+    // An unsigned quotient never overflows
+    let bhaga = &asm[asm.find("bhaga:").expect("bhaga is emitted")..];
+    assert!(!bhaga.contains("ud2"), "{}", bhaga);
+
+    // A 64-bit product is tested by its flags
+    let source = "kāryakrama f(a: saṅkhyā64, b: saṅkhyā64) -> saṅkhyā64 {\n    phera a * b;\n}\n";
+    let asm = compile_with(source, Target::X86_64, Guna::Sattva).expect("should compile");
+    assert!(
+        asm.contains("imul rdx\n    jno .Lno_overflow_f_0"),
+        "{}",
+        asm
+    );
+}
+
+/// Test the other backends keep to the width and trap in Sattva
+#[test]
+fn test_other_targets() {
+    let asm = compile_with(ARITHMETIC, Target::AArch64, Guna::Rajas).expect("should compile");
+    assert!(asm.contains("add x0, x1, x2\n    sxtb x0, w0"), "{}", asm);
+    assert!(asm.contains("udiv x0, x1, x2"), "{}", asm);
+    assert!(asm.contains("cset x0, lo"), "{}", asm);
+    let asm = compile_with(ARITHMETIC, Target::AArch64, Guna::Sattva).expect("should compile");
+    assert!(asm.contains("sxtb x16, w0\n    cmp x16, x0"), "{}", asm);
+    assert!(asm.contains("brk #1"), "{}", asm);
+
+    let asm = compile_with(ARITHMETIC, Target::RiscV64, Guna::Rajas).expect("should compile");
+    assert!(
+        asm.contains("slli t0, t0, 56\n    srai t0, t0, 56"),
+        "{}",
+        asm
+    );
+    assert!(asm.contains("divu t0, t1, t2"), "{}", asm);
+    assert!(asm.contains("sltu t0, t1, t2"), "{}", asm);
+    let asm = compile_with(ARITHMETIC, Target::RiscV64, Guna::Sattva).expect("should compile");
+    assert!(asm.contains("beq t3, t0, .Lno_overflow_yoga_0"), "{}", asm);
+    assert!(asm.contains("ebreak"), "{}", asm);
+}
+
+/// Test a narrowing conversion needs `as`, while widening is implicit
+#[test]
+fn test_lossy_conversion() {
+    let source =
+        "kāryakrama f(a: saṅkhyā64) -> saṅkhyā64 {\n    let b: saṅkhyā-t8 = a;\n    phera a;\n}\n";
+    let err = compile(source).expect_err("an i64 does not fit in an i8");
+    assert!(
+        err.message.contains(
+            "Implicit conversion from `i64` to `i8` may lose information; convert with `as`"
+        ),
+        "{}",
+        err.message
+    );
+    assert_eq!(err.location.expect("location").line, 2);
+
+    // Nor may a signed value become unsigned, wherever it goes
+    let source = r#"
+kāryakrama g(n: u16) {
+}
+
+kāryakrama f(x: saṅkhyā) -> u8 {
+    g(x);
+    phera x;
+}
+"#;
+    let err = compile(source)
+        .expect_err("an i32 is neither u16 nor u8")
+        .message;
+    assert!(err.contains("from `i32` to `u16`"), "{}", err);
+    assert!(err.contains("from `i32` to `u8`"), "{}", err);
+
+    let source = "kāryakrama f(x: u8, y: saṅkhyā-t16) -> saṅkhyā64 {\n    let z: saṅkhyā = x;\n    z = y;\n    phera z;\n}\n";
+    compile(source).expect("every u8 and i16 is an i32, and every i32 an i64");
+}
+
+/// Test an integer literal takes the type it meets, if it fits
+#[test]
+fn test_literal_range() {
+    let source =
+        "kāryakrama f(x: u8) -> u8 {\n    let y: saṅkhyā-t8 = -128;\n    phera x + 1;\n}\n";
+    compile(source).expect("literals fit");
+
+    let source = "kāryakrama f(x: u8) {\n    let y: u8 = 300;\n    x = x + 256;\n}\n";
+    let err = compile(source)
+        .expect_err("300 and 256 are beyond a u8")
+        .message;
+    assert!(
+        err.contains("Literal `300` does not fit in `u8`"),
+        "{}",
+        err
+    );
+    assert!(
+        err.contains("Literal `256` does not fit in `u8`"),
+        "{}",
+        err
+    );
+}
+
+/// Test `as` converts between numbers, extending or truncating
+#[test]
+fn test_cast() {
+    let source = r#"
+kāryakrama f(x: daśamika, n: saṅkhyā64) -> saṅkhyā-t16 {
+    let y: saṅkhyā64 = x as saṅkhyā;
+    phera (n + y) as saṅkhyā-t16;
+}
+"#;
+    let asm = compile(source).expect("casts should compile");
+    assert!(
+        asm.contains("cvttsd2si rax, xmm0\n    movsxd rax, eax"),
+        "{}",
+        asm
+    );
+    assert!(asm.contains("movsx rax, ax"), "{}", asm);
+
+    let asm = compile_with(source, Target::AArch64, Guna::Rajas).expect("should compile");
+    assert!(asm.contains("fcvtzs x0, d0"), "{}", asm);
+    let asm = compile_with(source, Target::RiscV64, Guna::Rajas).expect("should compile");
+    assert!(asm.contains("fcvt.l.d t0, ft0, rtz"), "{}", asm);
+
+    let source = "kāryakrama f(x: bool) -> daśamika {\n    phera x as daśamika;\n}\n";
+    let err = compile(source).expect_err("a bool is not a number").message;
+    assert!(err.contains("Cannot cast `bool` as `f64`"), "{}", err);
+}
+
+/// Test a width affix applies only to numbers of its kind
+#[test]
+fn test_invalid_width() {
+    let source = "kāryakrama f(x: bool-t8, y: saṅkhyā-f32) {\n}\n";
+    let err = compile(source).expect_err("widths do not suit").message;
+    assert_eq!(
+        err.matches("Width affix `-t8` does not apply to `bool`")
+            .count(),
+        1,
+        "{}",
+        err
+    );
+    assert!(
+        err.contains("Width affix `-f32` does not apply to `i32`"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,191 @@
+//! Integration tests for `cala x : e` loops and the iterator protocol
+//!
+//! Arrays, slices, `Sūci` and ranges are iterated built in; any other type
+//! through its `next` method, which returns `Vikalpa`.
+
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompilerSession};
+use jagannath_compiler::parser::{ast::*, Parser};
+
+fn compile(source: &str) -> Result<String, CompileError> {
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    let mut session = CompilerSession::new(options);
+    session
+        .compile(source)
+        .map(|result| String::from_utf8_lossy(&result.output).into_owned())
+}
+
+const GINATI: &str = r#"
+prakāra Ginatī {
+    vartamāna: saṅkhyā,
+    sīmā: saṅkhyā,
+}
+
+kriyānvaya Iterator for Ginatī {
+    prakāra Item = saṅkhyā;
+
+    kāryakrama next(&mut sva) -> Vikalpa<saṅkhyā> {
+        yad sva.vartamāna >= sva.sīmā {
+            phera Vikalpa::Śūnya;
+        }
+        let x = sva.vartamāna;
+        sva.vartamāna += 1;
+        phera Vikalpa::Kiñcit(x);
+    }
+}
+"#;
+
+/// Test ranges parse as values outside of an index
+#[test]
+fn test_parse_range_values() {
+    let source = r#"
+kāryakrama f(n: saṅkhyā) {
+    let r = 1..=n + 1;
+    let s = ..n;
+    cala i : r {}
+}
+"#;
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function declaration");
+    };
+    assert!(matches!(
+        &func.body.stmts[0],
+        Stmt::Let {
+            value: Some(Expr::Range {
+                start: Some(_),
+                end: Some(end),
+                inclusive: true,
+                ..
+            }),
+            ..
+        } if matches!(**end, Expr::Binary { .. })
+    ));
+    assert!(matches!(
+        &func.body.stmts[1],
+        Stmt::Let {
+            value: Some(Expr::Range {
+                start: None,
+                end: Some(_),
+                inclusive: false,
+                ..
+            }),
+            ..
+        }
+    ));
+}
+
+/// Test a loop over a user type calls its `next` until `Śūnya`
+#[test]
+fn test_user_iterator() {
+    let source = format!(
+        "{}\n{}",
+        GINATI,
+        r#"
+kāryakrama yoga(g: &mut Ginatī) -> saṅkhyā {
+    let s = 0;
+    cala x : g {
+        s += x;
+    }
+    phera s;
+}
+"#
+    );
+    let asm = compile(&source).expect("Iterating a Ginatī should compile");
+    assert!(asm.contains("Ginatī__next:"), "{}", asm);
+    assert!(asm.contains("call Ginatī__next"), "{}", asm);
+}
+
+/// Test the loop binding has the item type `next` returns
+#[test]
+fn test_item_type() {
+    let source = format!(
+        "{}\n{}",
+        GINATI,
+        r#"
+kāryakrama f(g: &mut Ginatī) {
+    cala x : g {
+        let b: bool = x;
+    }
+}
+"#
+    );
+    let err = compile(&source).expect_err("the item is a saṅkhyā").message;
+    assert!(err.contains("'b'"), "{}", err);
+}
+
+/// Test iterating a type without a suitable `next` is an error
+#[test]
+fn test_not_iterable() {
+    let err = compile("kāryakrama f(n: saṅkhyā) {\n    cala x : n {}\n}\n")
+        .expect_err("an integer is not iterable")
+        .message;
+    assert!(
+        err.contains("is not iterable: it has no `next` method returning `Vikalpa`"),
+        "{}",
+        err
+    );
+
+    let source = r#"
+prakāra Ekaka {
+    n: saṅkhyā,
+}
+
+kriyānvaya Ekaka {
+    kāryakrama next(&mut sva) -> saṅkhyā {
+        phera sva.n;
+    }
+}
+
+kāryakrama f(e: &mut Ekaka) {
+    cala x : e {}
+}
+"#;
+    let err = compile(source)
+        .expect_err("next must return Vikalpa")
+        .message;
+    assert!(err.contains("`Ekaka` is not iterable"), "{}", err);
+}
+
+/// Test a range value is counted through from its start to its end
+#[test]
+fn test_range_value() {
+    let source = r#"
+kāryakrama f(n: saṅkhyā) -> saṅkhyā {
+    let r = 1..=n;
+    let s = 0;
+    cala i : r {
+        s += i;
+    }
+    phera s;
+}
+"#;
+    let asm = compile(source).expect("Iterating a range should compile");
+    assert!(!asm.contains("call"), "{}", asm);
+    // The end of the range is read from its second word
+    assert!(asm.contains("mov rax, QWORD PTR [rax+8]"), "{}", asm);
+}
+
+/// Test `Vikalpa` constructors are checked against their payload
+#[test]
+fn test_vikalpa_constructors() {
+    let source = r#"
+kāryakrama f(n: saṅkhyā) -> Vikalpa<saṅkhyā> {
+    let w: vikalpa<bool> = Vikalpa::Śūnya;
+    yad n > 0 {
+        phera Vikalpa::Kiñcit(n);
+    }
+    phera Vikalpa::Śūnya;
+}
+"#;
+    compile(source).expect("Vikalpa values should compile");
+
+    let source = "kāryakrama f() {\n    let v: Vikalpa<bool> = Vikalpa::Kiñcit(1, 2);\n}\n";
+    let err = compile(source).expect_err("Kiñcit takes one value").message;
+    assert!(
+        err.contains("'Vikalpa::Kiñcit' expects 1 arguments, found 2"),
+        "{}",
+        err
+    );
+}
@@ -222,3 +222,108 @@ fn test_source_locations() {
     assert_eq!(tokens[1].span.line, 2);
     assert_eq!(tokens[2].span.line, 3);
 }
+
+/// Test Devanagari, IAST, Harvard-Kyoto and ITRANS spell the same tokens
+#[test]
+fn test_script_equivalence() {
+    let spellings = [
+        "कार्यक्रम गणना(संख्या) फेर",
+        "kāryakrama gaṇanā(saṃkhyā) phera",
+        // NFD: combining macron and dot below
+        "ka\u{0304}ryakrama gan\u{0323}ana\u{0304}(sam\u{0323}khya\u{0304}) phera",
+        // ASCII identifiers are transliterated where the scheme is declared
+        "#![lipi = \"hk\"]\nkAryakrama gaNanA(saMkhyA) phera",
+        "#![lipi = \"itrans\"]\nkaaryakrama gaNanaa(saMkhyaa) phera",
+    ];
+    let expected: Vec<_> = Lexer::new(spellings[1]).map(|t| t.kind).collect();
+    assert!(expected.contains(&TokenKind::Identifier("gaṇanā".to_string())));
+    for source in spellings {
+        let kinds: Vec<_> = Lexer::new(source).map(|t| t.kind).collect();
+        assert_eq!(kinds, expected, "{}", source);
+    }
+}
+
+/// Test programs in different scripts compile to the same code
+#[test]
+fn test_script_equivalent_programs() {
+    use jagannath_compiler::driver::options::CompilerOptions;
+    use jagannath_compiler::driver::CompilerSession;
+
+    let compile = |source: &str| {
+        let mut options = CompilerOptions::new();
+        options.emit_asm = true;
+        let result = CompilerSession::new(options)
+            .compile(source)
+            .unwrap_or_else(|e| panic!("{}: {}", source, e.message));
+        String::from_utf8_lossy(&result.output).into_owned()
+    };
+    let iast = compile(
+        "kāryakrama yoga(x: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 { phera x + 1; }\n\
+         kāryakrama mukhya() -> saṅkhyā-a-k-t32 { phera yoga(41); }",
+    );
+    let devanagari = compile(
+        "कार्यक्रम योग(x: सङ्ख्या-a-k-t32) -> सङ्ख्या-a-k-t32 { फेर x + 1; }\n\
+         कार्यक्रम मुख्य() -> सङ्ख्या-a-k-t32 { फेर योग(41); }",
+    );
+    // ITRANS keywords, Harvard-Kyoto types
+    let ascii = compile(
+        "kaaryakrama yoga(x: saGkhyA-a-k-t32) -> saGkhyA-a-k-t32 { phera x + 1; }\n\
+         kAryakrama mukhya() -> saGkhyA-a-k-t32 { phera yoga(41); }",
+    );
+    assert_eq!(devanagari, iast);
+    assert_eq!(ascii, iast);
+}
+
+/// Test ASCII identifiers keep their spelling, even where they look like
+/// Harvard-Kyoto or ITRANS, while keywords are still recognized
+#[test]
+fn test_ascii_identifiers_kept() {
+    let kinds: Vec<_> = Lexer::new("kAryakrama getSize(SDL_GetTicks) kaaryakrama needsUpdate")
+        .map(|t| t.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            TokenKind::Karyakrama,
+            TokenKind::Identifier("getSize".to_string()),
+            TokenKind::LeftParen,
+            TokenKind::Identifier("SDL_GetTicks".to_string()),
+            TokenKind::RightParen,
+            TokenKind::Karyakrama,
+            TokenKind::Identifier("needsUpdate".to_string()),
+        ]
+    );
+
+    use jagannath_compiler::driver::options::CompilerOptions;
+    use jagannath_compiler::driver::CompilerSession;
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    let result = CompilerSession::new(options)
+        .compile(
+            "kāryakrama getSize() -> saṅkhyā { phera 8; }\n\
+             kāryakrama mukhya() -> saṅkhyā { phera getSize(); }",
+        )
+        .expect("should compile");
+    let asm = String::from_utf8_lossy(&result.output);
+    assert!(asm.contains("getSize:"), "{}", asm);
+    assert!(asm.contains("call getSize"), "{}", asm);
+}
+
+/// Test loop labels are told apart from character literals
+#[test]
+fn test_loop_labels() {
+    let kinds: Vec<TokenKind> = Lexer::new("'bāhya: cala { break 'bāhya; } 'a' 'ā'")
+        .map(|t| t.kind)
+        .collect();
+    assert_eq!(kinds[0], TokenKind::Label("bāhya".to_string()));
+    assert!(kinds.contains(&TokenKind::Break));
+    assert_eq!(
+        kinds
+            .iter()
+            .filter(|k| **k == TokenKind::Label("bāhya".to_string()))
+            .count(),
+        2
+    );
+    assert!(kinds.contains(&TokenKind::IntLiteral('a' as i64)));
+    assert!(kinds.contains(&TokenKind::IntLiteral('ā' as i64)));
+}
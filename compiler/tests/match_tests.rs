@@ -0,0 +1,135 @@
+//! Integration tests for match expressions (pratyabhijñā)
+
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompilerSession};
+
+fn compile(source: &str) -> Result<String, CompileError> {
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    let mut session = CompilerSession::new(options);
+    session
+        .compile(source)
+        .map(|result| String::from_utf8_lossy(&result.output).into_owned())
+}
+
+const RUPA: &str = r#"
+prakāra Rūpa {
+    Vṛtta(saṅkhyā-a-k-t32),
+    Varga { bhuja: saṅkhyā-a-k-t32 },
+    Śūnya,
+}
+"#;
+
+/// Compile `RUPA` and a function matching on `r: Rūpa` with `arms`
+fn compile_rupa(arms: &str) -> Result<String, CompileError> {
+    compile(&format!(
+        "{}\nkāryakrama kṣetra(r: Rūpa) -> saṅkhyā-a-k-t32 {{\n    phera pratyabhijñā r {{\n{}\n    }};\n}}\n",
+        RUPA, arms
+    ))
+}
+
+/// Test a match on integers yields the chosen arm's value
+#[test]
+fn test_match_value_with_ranges_or_and_guard() {
+    let source = r#"
+kāryakrama vargīkaraṇa(n: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    let k = pratyabhijñā n {
+        0 => 100,
+        1 | 2 => 200,
+        3..=9 yad n > 5 => 300,
+        3..=9 => 400,
+        _ => 500,
+    };
+    phera k + 1;
+}
+"#;
+    let asm = compile(source).expect("Match expression should compile");
+    for value in ["100", "200", "300", "400", "500"] {
+        assert!(asm.contains(&format!("mov rax, {}", value)), "{}", asm);
+    }
+    // Ranges are tested against both bounds
+    assert!(asm.contains("setge"), "{}", asm);
+    assert!(asm.contains("setle"), "{}", asm);
+}
+
+/// Test variant patterns with tuple and struct payloads
+#[test]
+fn test_match_on_enum() {
+    compile_rupa(
+        "        Rūpa::Vṛtta(t) => t * t * 3,\n        Rūpa::Varga { bhuja } => bhuja * bhuja,\n        Śūnya => 0,",
+    )
+    .expect("Exhaustive enum match should compile");
+}
+
+/// Test a missing variant is named in the error
+#[test]
+fn test_non_exhaustive_enum() {
+    let err = compile_rupa("        Rūpa::Vṛtta(t) => t,\n        Śūnya => 0,")
+        .expect_err("Varga is not covered");
+    assert!(err.message.contains("not exhaustive"), "{}", err.message);
+    assert!(
+        err.message.contains("Rūpa::Varga { bhuja: _ }"),
+        "{}",
+        err.message
+    );
+}
+
+/// Test missing integer ranges and booleans are named in the error
+#[test]
+fn test_non_exhaustive_values() {
+    let source = r#"
+kāryakrama f(n: saṅkhyā-a-k-t32, b: bool) -> saṅkhyā-a-k-t32 {
+    let x = pratyabhijñā n {
+        ..0 => 0,
+        0..=9 => 1,
+        20.. => 2,
+    };
+    phera pratyabhijñā b {
+        satya => x,
+    };
+}
+"#;
+    let err = compile(source).expect_err("Ranges and booleans are not covered");
+    assert!(err.message.contains("`10..=19`"), "{}", err.message);
+    assert!(err.message.contains("`asatya`"), "{}", err.message);
+}
+
+/// Test an arm after a catch-all, and an or-alternative already covered
+#[test]
+fn test_unreachable_arms() {
+    let err = compile_rupa("        Rūpa::Vṛtta(_) => 1,\n        _ => 0,\n        Śūnya => 2,")
+        .expect_err("Arm after `_` is unreachable");
+    assert!(
+        err.message.contains("Unreachable pattern `Śūnya`"),
+        "{}",
+        err.message
+    );
+
+    let err = compile_rupa(
+        "        Rūpa::Vṛtta(_) => 1,\n        Rūpa::Vṛtta(_) | Śūnya => 2,\n        _ => 0,",
+    )
+    .expect_err("Repeated alternative is unreachable");
+    assert!(err.message.contains("`Rūpa::Vṛtta(_)`"), "{}", err.message);
+}
+
+/// Test a guarded arm does not make a match exhaustive
+#[test]
+fn test_guard_not_exhaustive() {
+    let err = compile_rupa(
+        "        Rūpa::Vṛtta(t) yad t > 0 => t,\n        Rūpa::Varga { .. } | Śūnya => 0,",
+    )
+    .expect_err("Guard may fail");
+    assert!(err.message.contains("Rūpa::Vṛtta(_)"), "{}", err.message);
+}
+
+/// Test unknown variants and mismatched arm types are reported
+#[test]
+fn test_match_type_errors() {
+    let err = compile_rupa("        Rūpa::Tribhuja(_) => 1,\n        _ => 0,")
+        .expect_err("No such variant");
+    assert!(err.message.contains("Tribhuja"), "{}", err.message);
+
+    let err = compile_rupa("        Rūpa::Vṛtta(t) => t,\n        _ => satya,")
+        .expect_err("Arm types differ");
+    assert!(err.message.contains("Branch types"), "{}", err.message);
+}
@@ -0,0 +1,362 @@
+//! Integration tests for multi-file crates (Khaṇḍa)
+
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompilerSession};
+use std::path::Path;
+
+/// Write `files` into a fresh directory and compile `main.jag` from it
+fn compile_crate(files: &[(&str, &str)]) -> Result<String, CompileError> {
+    let dir = tempfile::tempdir().expect("temp dir");
+    for (name, source) in files {
+        let path = dir.path().join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, source).unwrap();
+    }
+    compile_root(&dir.path().join("main.jag"))
+}
+
+fn compile_root(root: &Path) -> Result<String, CompileError> {
+    let source = std::fs::read_to_string(root).unwrap();
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    options.inputs.push(root.display().to_string());
+    let mut session = CompilerSession::new(options);
+    session
+        .compile(&source)
+        .map(|result| String::from_utf8_lossy(&result.output).into_owned())
+}
+
+const GANITA: &str = r#"
+sārvajanika kāryakrama yoga(a: saṅkhyā-a-k-t32, b: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    phera a + b;
+}
+
+sārvajanika kāryakrama dviguṇa(a: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    phera sahāyaka(a);
+}
+
+kāryakrama sahāyaka(a: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    phera a * 2;
+}
+"#;
+
+/// Test a root file pulling items from a sibling module
+#[test]
+fn test_two_file_crate() {
+    let main = r#"
+vibhāga ganita;
+upayoga ganita::yoga;
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera yoga(1, ganita::dviguṇa(2));
+}
+"#;
+    let asm = compile_crate(&[("main.jag", main), ("ganita.jag", GANITA)])
+        .expect("Two-file crate should compile");
+    assert!(asm.contains("ganita__yoga"), "{}", asm);
+    assert!(asm.contains("ganita__sahāyaka"), "{}", asm);
+    assert!(asm.contains("mukhya"), "{}", asm);
+}
+
+/// Test glob imports and nested modules in a directory
+#[test]
+fn test_nested_modules() {
+    let main = r#"
+vibhāga ganita;
+upayoga ganita::*;
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera yoga(ganita::ankana::eka(), 2);
+}
+"#;
+    let ganita = format!("sārvajanika vibhāga ankana;\n{}", GANITA);
+    let ankana = r#"
+sārvajanika kāryakrama eka() -> saṅkhyā-a-k-t32 {
+    phera 1;
+}
+"#;
+    compile_crate(&[
+        ("main.jag", main),
+        ("ganita.jag", &ganita),
+        ("ganita/ankana.jag", ankana),
+    ])
+    .expect("Nested modules should compile");
+}
+
+/// Test private items cannot be imported
+#[test]
+fn test_private_import_rejected() {
+    let main = r#"
+vibhāga ganita;
+upayoga ganita::sahāyaka;
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera sahāyaka(1);
+}
+"#;
+    let err = compile_crate(&[("main.jag", main), ("ganita.jag", GANITA)])
+        .expect_err("Private import should fail");
+    assert!(err.message.contains("sahāyaka"), "{}", err.message);
+    let location = err.location.expect("location");
+    assert!(location.file.ends_with("main.jag"), "{}", location.file);
+    assert_eq!(location.line, 3);
+}
+
+/// Test private items cannot be reached through a path either
+#[test]
+fn test_private_path_rejected() {
+    let main = r#"
+vibhāga ganita;
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera ganita::sahāyaka(1);
+}
+"#;
+    let err = compile_crate(&[("main.jag", main), ("ganita.jag", GANITA)])
+        .expect_err("Private path should fail");
+    assert!(err.message.contains("sahāyaka"), "{}", err.message);
+}
+
+/// Test type errors are reported against the module's own file
+#[test]
+fn test_type_error_in_module() {
+    let main = r#"
+vibhāga ganita;
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera ganita::yoga(1, 2, 3);
+}
+"#;
+    let err = compile_crate(&[("main.jag", main), ("ganita.jag", GANITA)])
+        .expect_err("Arity mismatch across modules");
+    assert!(err.message.contains("Prakāra"), "{}", err.message);
+}
+
+/// Test a missing module file is reported
+#[test]
+fn test_missing_module() {
+    let main = r#"
+vibhāga lupta;
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera 0;
+}
+"#;
+    let err = compile_crate(&[("main.jag", main)]).expect_err("Module file is missing");
+    assert!(err.message.contains("lupta"), "{}", err.message);
+}
+
+/// Test import cycles are rejected (Cakravyūha)
+#[test]
+fn test_module_cycle() {
+    let main = r#"
+vibhāga a;
+vibhāga b;
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera a::f();
+}
+"#;
+    let a = r#"
+upayoga b::g;
+
+sārvajanika kāryakrama f() -> saṅkhyā-a-k-t32 {
+    phera g();
+}
+"#;
+    let b = r#"
+upayoga a::f;
+
+sārvajanika kāryakrama g() -> saṅkhyā-a-k-t32 {
+    phera f();
+}
+"#;
+    let err = compile_crate(&[("main.jag", main), ("a.jag", a), ("b.jag", b)])
+        .expect_err("Cycle should be rejected");
+    assert!(err.message.contains("Cakravyūha"), "{}", err.message);
+}
+
+/// Test modules are found through include paths
+#[test]
+fn test_include_paths() {
+    let lib = tempfile::tempdir().expect("temp dir");
+    std::fs::write(lib.path().join("ganita.jag"), GANITA).unwrap();
+    let dir = tempfile::tempdir().expect("temp dir");
+    let root = dir.path().join("main.jag");
+    std::fs::write(
+        &root,
+        r#"
+upayoga ganita::yoga;
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera yoga(1, 2);
+}
+"#,
+    )
+    .unwrap();
+
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    options.inputs.push(root.display().to_string());
+    options.include_paths.push(lib.path().display().to_string());
+    let mut session = CompilerSession::new(options);
+    let source = std::fs::read_to_string(&root).unwrap();
+    session
+        .compile(&source)
+        .expect("Module from include path should compile");
+}
+
+const INLINE: &str = r#"
+sthira ADHARA: saṅkhyā-a-k-t32 = 40;
+
+vibhāga ganita {
+    sārvajanika sthira EKA: saṅkhyā-a-k-t32 = 1;
+    static DVI: saṅkhyā-a-k-t32 = EKA + EKA;
+
+    sārvajanika prakāra Bindu {
+        sārvajanika x: saṅkhyā-a-k-t32,
+        gupta: saṅkhyā-a-k-t32,
+    }
+
+    kriyānvaya Bindu {
+        sārvajanika kāryakrama mūla(&sva) -> saṅkhyā-a-k-t32 {
+            phera sva.x + sva.gupta;
+        }
+
+        kāryakrama rahasya(&sva) -> saṅkhyā-a-k-t32 {
+            phera sva.gupta;
+        }
+    }
+
+    sārvajanika kāryakrama dvi() -> saṅkhyā-a-k-t32 {
+        phera DVI;
+    }
+
+    vibhāga antar {
+        sārvajanika kāryakrama tri() -> saṅkhyā-a-k-t32 {
+            phera 3;
+        }
+    }
+}
+
+kāryakrama paṭhana(b: ganita::Bindu) -> saṅkhyā-a-k-t32 {
+    phera b.x + b.mūla();
+}
+"#;
+
+fn compile_inline(mukhya: &str) -> Result<String, CompileError> {
+    let main = format!(
+        "{}\nkāryakrama mukhya() -> saṅkhyā-a-k-t32 {{\n    phera {};\n}}\n",
+        INLINE, mukhya
+    );
+    compile_crate(&[("main.jag", &main)])
+}
+
+/// Test inline modules with constants and statics in one file
+#[test]
+fn test_inline_module_and_constants() {
+    let asm = compile_inline("ADHARA + ganita::dvi() + ganita::EKA")
+        .expect("Inline module should compile");
+    assert!(asm.contains("ganita__dvi"), "{}", asm);
+}
+
+/// Test private constants, fields, methods and modules stay in their module
+#[test]
+fn test_inline_privacy() {
+    for (expr, name) in [("ganita::DVI", "DVI"), ("ganita::antar::tri()", "antar")] {
+        let err = compile_inline(expr).expect_err(expr);
+        assert!(err.message.contains(name), "{}", err.message);
+    }
+
+    for (body, name) in [("b.gupta", "gupta"), ("b.rahasya()", "rahasya")] {
+        let main = format!(
+            "{}\nkāryakrama mukhya(b: ganita::Bindu) -> saṅkhyā-a-k-t32 {{\n    phera {};\n}}\n",
+            INLINE, body
+        );
+        let err = compile_crate(&[("main.jag", &main)]).expect_err(body);
+        assert!(err.message.contains("private"), "{}", err.message);
+        assert!(err.message.contains(name), "{}", err.message);
+    }
+}
+
+/// Test a type of another module is named by path or imported, and only
+/// when visible
+#[test]
+fn test_type_visibility() {
+    let main = r#"
+vibhāga ganita {
+    prakāra Gupta {
+        x: saṅkhyā-a-k-t32,
+    }
+
+    sārvajanika prakāra Bindu {
+        sārvajanika x: saṅkhyā-a-k-t32,
+    }
+}
+
+upayoga ganita::Bindu;
+
+kāryakrama mukhya(g: TYPE) -> saṅkhyā-a-k-t32 {
+    phera 0;
+}
+"#;
+    for ty in ["Bindu", "ganita::Bindu"] {
+        compile_crate(&[("main.jag", &main.replace("TYPE", ty))]).expect(ty);
+    }
+
+    for (ty, message) in [
+        ("Gupta", "`Gupta` of module `ganita` is not in scope"),
+        ("ganita::Gupta", "symbol `Gupta` of module `ganita`"),
+    ] {
+        let err = compile_crate(&[("main.jag", &main.replace("TYPE", ty))]).expect_err(ty);
+        assert!(err.message.contains(message), "{}", err.message);
+    }
+}
+
+/// Test `pub(saṃkula)` items are visible anywhere in the crate
+#[test]
+fn test_crate_visibility() {
+    let main = r#"
+vibhāga ganita {
+    sārvajanika(saṃkula) vibhāga antar {
+        sārvajanika(saṃkula) kāryakrama tri() -> saṅkhyā-a-k-t32 {
+            phera 3;
+        }
+    }
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera ganita::antar::tri();
+}
+"#;
+    compile_crate(&[("main.jag", main)]).expect("Crate-visible path should compile");
+}
+
+/// Test constants defined in terms of themselves are rejected
+#[test]
+fn test_cyclic_constant() {
+    let main = r#"
+sthira ADHARA: saṅkhyā-a-k-t32 = ANTA + 1;
+sthira ANTA: saṅkhyā-a-k-t32 = ADHARA;
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera ADHARA;
+}
+"#;
+    let err = compile_crate(&[("main.jag", main)]).expect_err("Cycle should be rejected");
+    assert!(err.message.contains("itself"), "{}", err.message);
+}
+
+/// Test mutable statics are rejected by the parser
+#[test]
+fn test_mutable_static_rejected() {
+    let main = r#"
+static parivartya GANANA: saṅkhyā-a-k-t32 = 0;
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera 0;
+}
+"#;
+    let err = compile_crate(&[("main.jag", main)]).expect_err("static mut should fail");
+    assert!(err.message.contains("Mutable statics"), "{}", err.message);
+}
@@ -6,6 +6,7 @@
 //! - Control flow (yad, cala)
 //! - Expression parsing
 
+use jagannath_compiler::modules::Visibility;
 use jagannath_compiler::parser::{ast::*, Parser};
 
 /// Test function declaration parsing
@@ -290,3 +291,416 @@ kāryakrama compare(a: saṅkhyā, b: saṅkhyā) -> saṅkhyā {
         _ => panic!("Expected function"),
     }
 }
+
+/// Test trait definition parsing (guṇa)
+#[test]
+fn test_trait_declaration() {
+    let source = r#"
+guṇa Saṅgraha<T>: Gaṇanīya yatra T: Sadṛśa {
+    prakāra Aṃśa: Sadṛśa = T;
+    kāryakrama prāpti(&sva, i: saṅkhyā) -> T;
+    kāryakrama rikta(&sva) -> bool {
+        phera sva.dairghya() == 0
+    }
+}
+"#;
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+
+    match &ast.items[0] {
+        Item::Trait(t) => {
+            assert_eq!(t.name.name, "Saṅgraha");
+            assert_eq!(t.generics.len(), 1);
+            assert_eq!(t.supertraits.len(), 1);
+            assert_eq!(t.where_clauses.len(), 1);
+            assert_eq!(t.associated_types.len(), 1);
+            assert!(t.associated_types[0].default.is_some());
+            assert_eq!(t.methods.len(), 2);
+            assert_eq!(t.methods[0].receiver, Some(SelfParam::Ref));
+            assert!(t.methods[0].default_body.is_none());
+            assert!(t.methods[1].default_body.is_some());
+        }
+        _ => panic!("Expected trait declaration"),
+    }
+}
+
+/// Test const parameters and the values given for them
+#[test]
+fn test_const_generics() {
+    let source = r#"
+prakāra Sīmita<T, sthira NYŪNA: saṅkhyā, sthira ADHIKA: saṅkhyā> {
+    v: T,
+}
+
+kāryakrama f(s: Sīmita<bool, -3, { śata() + 1 }>) {
+}
+"#;
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+
+    let Item::TypeDef(t) = &ast.items[0] else {
+        panic!("Expected type declaration");
+    };
+    assert!(t.generics[0].ty.is_none());
+    assert!(matches!(
+        &t.generics[1].ty,
+        Some(Type::Named { name, .. }) if name.name == "saṅkhyā"
+    ));
+
+    let Item::Function(f) = &ast.items[1] else {
+        panic!("Expected function declaration");
+    };
+    let Type::Named { generics, .. } = &f.params[0].ty else {
+        panic!("Expected a named type");
+    };
+    assert!(matches!(&generics[0], Type::Named { name, .. } if name.name == "bool"));
+    assert_eq!(generics[1].const_value(), Some(-3));
+    assert!(
+        matches!(&generics[2], Type::Const(value) if matches!(**value, Expr::Block(_))),
+        "{:?}",
+        generics[2]
+    );
+}
+
+/// Test impl block parsing (kriyānvaya)
+#[test]
+fn test_impl_declaration() {
+    let source = r#"
+kriyānvaya<T: Sadṛśa> Saṅgraha<T> for Peṭī<T> {
+    prakāra Aṃśa = T;
+    kāryakrama prāpti(&sva, i: saṅkhyā) -> T {
+        phera sva.antar
+    }
+}
+
+kriyānvaya Bindu {
+    kāryakrama nava(x: saṅkhyā) -> Sva {
+        phera Bindu::śūnya()
+    }
+    kāryakrama cala_karo(&mut sva) {}
+}
+"#;
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+
+    assert_eq!(ast.items.len(), 2);
+    match &ast.items[0] {
+        Item::Impl(i) => {
+            assert_eq!(i.generics.len(), 1);
+            assert_eq!(i.generics[0].bounds.len(), 1);
+            let trait_ref = i.trait_ref.as_ref().expect("trait impl");
+            assert_eq!(trait_ref.trait_name.name, "Saṅgraha");
+            assert_eq!(trait_ref.generics.len(), 1);
+            assert_eq!(i.associated_types.len(), 1);
+            assert_eq!(i.methods[0].receiver, Some(SelfParam::Ref));
+            assert_eq!(i.methods[0].params.len(), 1);
+        }
+        _ => panic!("Expected impl block"),
+    }
+    match &ast.items[1] {
+        Item::Impl(i) => {
+            assert!(i.trait_ref.is_none());
+            assert_eq!(i.methods.len(), 2);
+            assert_eq!(i.methods[0].receiver, None);
+            assert_eq!(i.methods[1].receiver, Some(SelfParam::RefMut));
+        }
+        _ => panic!("Expected impl block"),
+    }
+}
+
+/// Test builtin assertion mantras lower to a guarded vikṣepa call
+#[test]
+fn test_assert_eq_lowering() {
+    let source = r#"
+kāryakrama mukhya() {
+    assert_eq!(1, 2);
+}
+"#;
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function declaration");
+    };
+    let Stmt::Expr(Expr::Block(block)) = &func.body.stmts[0] else {
+        panic!("Expected lowered assertion block");
+    };
+    match &block.stmts[0] {
+        Stmt::If {
+            condition: Expr::Binary { op, .. },
+            then_block,
+            ..
+        } => {
+            assert_eq!(*op, BinaryOp::Ne);
+            assert!(matches!(
+                &then_block.stmts[0],
+                Stmt::Expr(Expr::Call { callee, .. })
+                    if matches!(callee.as_ref(), Expr::Identifier(id) if id.name == "vikṣepa")
+            ));
+        }
+        other => panic!("Expected yad statement, got {:?}", other),
+    }
+}
+
+/// Test a mantra takes its arguments in any of `( )`, `[ ]` and `{ }`,
+/// and names them all when given none of them
+#[test]
+fn test_macro_delimiters() {
+    let source = r#"
+kāryakrama mukhya() {
+    assert![1 == 1];
+    assert_eq!{1, 1, "ekam"};
+    mudraṇa!("{}", 1);
+}
+"#;
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function declaration");
+    };
+    assert_eq!(func.body.stmts.len(), 3);
+    for stmt in &func.body.stmts[..2] {
+        assert!(
+            matches!(stmt, Stmt::Expr(Expr::Block(_))),
+            "Expected lowered assertion, got {:?}",
+            stmt
+        );
+    }
+
+    let errors = Parser::parse_str("kāryakrama mukhya() {\n    assert! 1 == 1;\n}\n")
+        .expect_err("an argument list is needed");
+    let err = &errors[0];
+    assert_eq!(
+        err.message,
+        "`assert!` takes its arguments in `( )`, `[ ]` or `{ }`"
+    );
+    assert_eq!(err.span.line, 2);
+}
+
+/// Test closures and function types (pāśa)
+#[test]
+fn test_lambda_and_function_type() {
+    let source = r#"
+kāryakrama lāgū(f: kāryakrama(saṅkhyā) -> saṅkhyā, x: saṅkhyā) -> saṅkhyā {
+    let g = |a: saṅkhyā, b| a + b;
+    let h = || { phera x; };
+    phera f(x);
+}
+"#;
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function declaration");
+    };
+    match &func.params[0].ty {
+        Type::Function {
+            params,
+            return_type,
+        } => {
+            assert_eq!(params.len(), 1);
+            assert!(
+                matches!(return_type.as_ref(), Type::Named { name, .. } if name.name == "saṅkhyā")
+            );
+        }
+        other => panic!("Expected function type, got {:?}", other),
+    }
+    match &func.body.stmts[0] {
+        Stmt::Let {
+            value: Some(Expr::Lambda { params, body, .. }),
+            ..
+        } => {
+            assert_eq!(params.len(), 2);
+            assert!(matches!(params[1].ty, Type::Inferred));
+            assert!(matches!(body.as_ref(), Expr::Binary { .. }));
+        }
+        other => panic!("Expected closure, got {:?}", other),
+    }
+    match &func.body.stmts[1] {
+        Stmt::Let {
+            value: Some(Expr::Lambda { params, body, .. }),
+            ..
+        } => {
+            assert!(params.is_empty());
+            assert!(matches!(body.as_ref(), Expr::Block(_)));
+        }
+        other => panic!("Expected closure, got {:?}", other),
+    }
+}
+
+/// Test visibility, inline modules and constants are recorded
+#[test]
+fn test_visibility_and_inline_module() {
+    let source = r#"
+sārvajanika(saṃkula) vibhāga ganita {
+    sārvajanika sthira EKA: saṅkhyā = 1;
+    static DVI: saṅkhyā = 2;
+
+    sārvajanika prakāra Bindu {
+        sārvajanika x: saṅkhyā,
+        gupta: saṅkhyā,
+    }
+}
+"#;
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::Module(module) = &ast.items[0] else {
+        panic!("Expected module, got {:?}", ast.items[0]);
+    };
+    assert!(module.inline);
+    assert_eq!(module.visibility, Visibility::Crate);
+    assert_eq!(module.items.len(), 3);
+    match (&module.items[0], &module.items[1]) {
+        (Item::Constant(eka), Item::Constant(dvi)) => {
+            assert_eq!(eka.visibility, Visibility::Public);
+            assert!(!eka.is_static);
+            assert_eq!(dvi.visibility, Visibility::Private);
+            assert!(dvi.is_static);
+        }
+        other => panic!("Expected constants, got {:?}", other),
+    }
+    match &module.items[2] {
+        Item::TypeDef(TypeDef {
+            body: TypeBody::Struct(fields),
+            ..
+        }) => {
+            assert_eq!(fields[0].visibility, Visibility::Public);
+            assert_eq!(fields[1].visibility, Visibility::Private);
+        }
+        other => panic!("Expected struct, got {:?}", other),
+    }
+}
+
+/// Test enum declarations and match expressions with guards (pratyabhijñā)
+#[test]
+fn test_enum_and_match_expression() {
+    let source = r#"
+prakāra Rūpa {
+    Vṛtta(saṅkhyā),
+    Varga { bhuja: saṅkhyā },
+    Śūnya,
+}
+
+kāryakrama kṣetra(r: Rūpa) -> saṅkhyā {
+    phera pratyabhijñā r {
+        Rūpa::Vṛtta(t) yad t > 0 => t * 3,
+        Rūpa::Varga { bhuja } => { bhuja * bhuja },
+        Vṛtta(_) | Śūnya => 0,
+    };
+}
+"#;
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::TypeDef(TypeDef {
+        body: TypeBody::Enum(variants),
+        ..
+    }) = &ast.items[0]
+    else {
+        panic!("Expected enum, got {:?}", ast.items[0]);
+    };
+    assert_eq!(variants.len(), 3);
+    assert_eq!(
+        variants[0].fields.as_ref().map(|f| f[0].name.name.as_str()),
+        Some("0")
+    );
+    assert_eq!(
+        variants[1].fields.as_ref().map(|f| f[0].name.name.as_str()),
+        Some("bhuja")
+    );
+    assert!(variants[2].fields.is_none());
+
+    let Item::Function(func) = &ast.items[1] else {
+        panic!("Expected function declaration");
+    };
+    let Stmt::Return {
+        value: Some(Expr::Match { arms, .. }),
+        ..
+    } = &func.body.stmts[0]
+    else {
+        panic!("Expected match expression, got {:?}", func.body.stmts[0]);
+    };
+    assert_eq!(arms.len(), 3);
+    assert!(matches!(
+        &arms[0].pattern,
+        Pattern::Variant { enum_name: Some(e), variant, .. }
+            if e.name == "Rūpa" && variant.name == "Vṛtta"
+    ));
+    assert!(arms[0].guard.is_some());
+    assert!(matches!(
+        &arms[1].pattern,
+        Pattern::Variant { fields: VariantFields::Struct(fields), .. } if fields.len() == 1
+    ));
+    assert!(matches!(arms[1].body, Expr::Block(_)));
+    assert!(matches!(&arms[2].pattern, Pattern::Or(alternatives) if alternatives.len() == 2));
+}
+
+/// Test that parsing recovers and keeps the rest of the file
+#[test]
+fn test_recovering_parse_keeps_partial_ast() {
+    let source = r#"
+kāryakrama pūrva(x: saṅkhyā) -> saṅkhyā {
+    let y = ;
+    let z = yoga(x, , 2);
+    phera z
+}
+
+kāryakrama (bhagna {
+
+vibhāga antara {
+    prakāra 5;
+    kāryakrama antima() {}
+"#;
+    assert!(Parser::parse_str(source).is_err());
+    let (ast, errors) = Parser::parse_str_recovering(source);
+    assert_eq!(errors.len(), 5, "{:#?}", errors);
+    assert_eq!(ast.items.len(), 3);
+
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function declaration, got {:?}", ast.items[0]);
+    };
+    assert_eq!(func.body.stmts.len(), 3);
+    assert!(matches!(
+        &func.body.stmts[0],
+        Stmt::Let { name, value: Some(Expr::Error(_)), .. } if name.name == "y"
+    ));
+    let Stmt::Let {
+        value: Some(Expr::Call { args, .. }),
+        ..
+    } = &func.body.stmts[1]
+    else {
+        panic!("Expected call, got {:?}", func.body.stmts[1]);
+    };
+    assert_eq!(args.len(), 3);
+    assert!(matches!(args[1], Expr::Error(_)));
+    assert!(matches!(func.body.stmts[2], Stmt::Return { .. }));
+
+    let Item::Error(span) = &ast.items[1] else {
+        panic!("Expected error item, got {:?}", ast.items[1]);
+    };
+    assert!(source[span.start..span.end].starts_with("kāryakrama (bhagna"));
+
+    // The unclosed module keeps its items; the broken one is skipped
+    let Item::Module(module) = &ast.items[2] else {
+        panic!("Expected module, got {:?}", ast.items[2]);
+    };
+    assert_eq!(module.items.len(), 2);
+    assert!(matches!(module.items[0], Item::Error(_)));
+    assert!(matches!(&module.items[1], Item::Function(f) if f.name.name == "antima"));
+}
+
+/// Test that errors record the tokens the parser would have accepted
+#[test]
+fn test_parse_error_expected_tokens() {
+    let errors = Parser::parse_str("kāryakrama f( {}").unwrap_err();
+    assert_eq!(errors[0].span.start, 15);
+    let expected = errors[0].expected_names();
+    assert!(
+        expected.contains(&"identifier".to_string()),
+        "{:?}",
+        expected
+    );
+    assert!(
+        expected.contains(&"RightParen".to_string()),
+        "{:?}",
+        expected
+    );
+
+    let errors = Parser::parse_str("sthira N: saṅkhyā = ;").unwrap_err();
+    let expected = errors[0].expected_names();
+    assert!(expected.contains(&"integer".to_string()), "{:?}", expected);
+    assert!(
+        expected.contains(&"LeftParen".to_string()),
+        "{:?}",
+        expected
+    );
+}
@@ -0,0 +1,250 @@
+//! Integration tests for `saha` tasks: what may cross into a task follows
+//! the `-sūtra`/`-eka` affixes and the fields of composite types
+
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompilerSession};
+use jagannath_compiler::parser::{ast::*, Parser};
+
+fn compile(source: &str) -> Result<String, CompileError> {
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    let mut session = CompilerSession::new(options);
+    session
+        .compile(source)
+        .map(|result| String::from_utf8_lossy(&result.output).into_owned())
+}
+
+/// A counter safe to share, and a cache tied to one task
+const TYPES: &str = r#"
+prakāra Gaṇaka {
+    mūlya: saṅkhyā-sūtra,
+}
+
+prakāra Kośa {
+    antima: saṅkhyā-eka,
+}
+
+prakāra Khātā {
+    nāma: saṅkhyā,
+    kośa: Kośa,
+}
+
+kāryakrama vṛddhi(g: &Gaṇaka) {
+    mudraṇa!("{}", g.mūlya);
+}
+
+kāryakrama paṭhana(k: &Kośa) -> saṅkhyā {
+    phera k.antima;
+}
+"#;
+
+#[test]
+fn test_parse_saha() {
+    let source = "kāryakrama f(n: saṅkhyā) {\n    saha {\n        mudraṇa!(\"{}\", n);\n    }\n    saha g(n);\n}\n";
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function declaration");
+    };
+    assert!(matches!(
+        &func.body.stmts[0],
+        Stmt::Expr(Expr::Saha { task, .. }) if matches!(**task, Expr::Block(_))
+    ));
+    assert!(matches!(
+        &func.body.stmts[1],
+        Stmt::Expr(Expr::Saha { task, .. }) if matches!(**task, Expr::Call { .. })
+    ));
+
+    let err = Parser::parse_str("kāryakrama f(n: saṅkhyā) {\n    saha n + 1;\n}\n")
+        .expect_err("saha takes a block or a call");
+    assert!(format!("{:?}", err).contains("`saha` runs a block or a function call"));
+}
+
+/// Test scalars, -sūtra values and types made of them cross freely
+#[test]
+fn test_thread_safe_values() {
+    let source = format!(
+        "{}\n{}",
+        TYPES,
+        r#"
+kāryakrama mukhya(g: &Gaṇaka, n: saṅkhyā) {
+    let gaṇanā: saṅkhyā-sūtra = 0;
+    saha {
+        vṛddhi(g);
+        gaṇanā = gaṇanā + n;
+    }
+    saha vṛddhi(g);
+    mudraṇa!("{}", gaṇanā);
+}
+"#
+    );
+    let asm = compile(&source).expect("thread-safe values may cross");
+    // The task runs where it is spawned
+    let mukhya = &asm[asm.find("mukhya:").expect("mukhya is emitted")..];
+    assert_eq!(mukhya.matches("call vṛddhi").count(), 2, "{}", mukhya);
+}
+
+/// Test a -eka field makes the type holding it unfit to share or send
+#[test]
+fn test_derived_from_fields() {
+    let source = format!(
+        "{}\n{}",
+        TYPES,
+        r#"
+kāryakrama mukhya(k: &Khātā) {
+    saha {
+        mudraṇa!("{}", k.nāma);
+    }
+}
+"#
+    );
+    let err = compile(&source).expect_err("Khātā holds a Kośa").message;
+    assert!(
+        err.contains("`k` cannot be sent to a `saha` task: field `antima` of `Kośa` is -eka"),
+        "{}",
+        err
+    );
+
+    // Spawned with a reference to it
+    let source = format!(
+        "{}\n{}",
+        TYPES, "kāryakrama mukhya(k: Kośa) {\n    saha paṭhana(&k);\n}\n"
+    );
+    let err = compile(&source).expect_err("Kośa is -eka inside").message;
+    assert!(err.contains("`saha` task"), "{}", err);
+    assert!(err.contains("field `antima` of `Kośa` is -eka"), "{}", err);
+}
+
+/// Test a task may only assign a variable the code around it can see when
+/// the variable is -sūtra
+#[test]
+fn test_written_while_shared() {
+    let source = r#"
+kāryakrama mukhya() {
+    let gaṇanā = 0;
+    saha {
+        gaṇanā = gaṇanā + 1;
+    }
+    mudraṇa!("{}", gaṇanā);
+}
+"#;
+    let err = compile(source).expect_err("a data race on gaṇanā").message;
+    assert!(
+        err.contains("`gaṇanā` cannot be assigned by a `saha` task: its type is not -sūtra"),
+        "{}",
+        err
+    );
+    assert_eq!(err.matches("saha").count(), 1, "{}", err);
+}
+
+/// Test a -eka value cannot be handed to a spawned task, reported where
+/// it is passed
+#[test]
+fn test_eka() {
+    let source = r#"
+kāryakrama lekha(n: saṅkhyā) {
+    mudraṇa!("{}", n);
+}
+
+kāryakrama mukhya(n: saṅkhyā-eka) {
+    saha lekha(n);
+}
+"#;
+    let err = compile(source).expect_err("n is tied to this task").message;
+    assert!(
+        err.contains("`n` cannot be sent to a `saha` task: its type is -eka"),
+        "{}",
+        err
+    );
+    let err = compile(source).expect_err("n is tied to this task");
+    assert_eq!(err.location.expect("location").line, 7);
+}
+
+/// Test `#[asurakṣita(sūtra)]` vouches for a type the fields would reject
+#[test]
+fn test_assumed_thread_safe() {
+    let source = r#"
+#[asurakṣita(sūtra)]
+prakāra Tālā {
+    mūlya: saṅkhyā-eka,
+}
+
+kāryakrama mukhya(t: Tālā, u: Tālā) {
+    saha {
+        t = u;
+    }
+}
+"#;
+    compile(source).expect("a Tālā may be assigned while shared");
+
+    let source = r#"
+#[asurakṣita(sūtra)]
+prakāra Tālā {
+    mūlya: saṅkhyā-eka,
+}
+
+kāryakrama paṭha_tālā(t: &Tālā) -> saṅkhyā {
+    phera t.mūlya;
+}
+
+kāryakrama mukhya(t: &Tālā) {
+    saha paṭha_tālā(t);
+}
+"#;
+    compile(source).expect("Tālā is taken to be thread-safe");
+
+    let source = "#[asurakṣita]\nprakāra Tālā {\n    mūlya: saṅkhyā,\n}\n";
+    let err = compile(source)
+        .expect_err("asurakṣita names what it vouches for")
+        .message;
+    assert!(err.contains("`#[asurakṣita(sūtra)]`"), "{}", err);
+}
+
+/// Test a generic argument decides for the type holding it
+#[test]
+fn test_generic_fields() {
+    let source = format!(
+        "{}\n{}",
+        TYPES,
+        r#"
+kāryakrama lekha(v: Vikalpa<Kośa>) {
+}
+
+kāryakrama gaṇa(w: Vikalpa<Gaṇaka>) {
+}
+
+kāryakrama mukhya(v: Vikalpa<Kośa>, w: Vikalpa<Gaṇaka>) {
+    saha {
+        lekha(v);
+        gaṇa(w);
+    }
+}
+"#
+    );
+    let err = compile(&source)
+        .expect_err("Vikalpa<Kośa> holds a Kośa")
+        .message;
+    assert!(
+        err.contains("`v` cannot be shared with a `saha` task: field `antima` of `Kośa` is -eka"),
+        "{}",
+        err
+    );
+    assert!(!err.contains("`w`"), "{}", err);
+}
+
+/// Test `break` cannot leave a task for a loop around it
+#[test]
+fn test_task_is_apart() {
+    let source = r#"
+kāryakrama mukhya() {
+    cala {
+        saha {
+            break;
+        }
+    }
+}
+"#;
+    let err = compile(source)
+        .expect_err("break cannot leave the task")
+        .message;
+    assert!(err.contains("break"), "{}", err);
+}
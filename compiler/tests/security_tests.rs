@@ -0,0 +1,237 @@
+//! Integration tests for information flow: `-guhya` data must not reach a
+//! `-sarvajnika` place or be printed, across the whole program
+
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompilerSession};
+
+fn compile(source: &str) -> Result<String, CompileError> {
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    let mut session = CompilerSession::new(options);
+    session
+        .compile(source)
+        .map(|result| String::from_utf8_lossy(&result.output).into_owned())
+}
+
+/// An account with a public name and a secret key
+const KHATA: &str = r#"
+prakāra Khātā {
+    nāma: saṅkhyā-sarvajnika,
+    kuñjī: saṅkhyā-guhya,
+}
+
+kriyānvaya Khātā {
+    kāryakrama kuñjī_mūlya(&sva) -> saṅkhyā {
+        phera sva.kuñjī;
+    }
+}
+"#;
+
+/// Test a secret passed through two calls and printed is reported with
+/// every step of its flow
+#[test]
+fn test_flow_through_calls() {
+    let source = r#"
+kāryakrama lekha(n: saṅkhyā) {
+    mudraṇa!("{}", n);
+}
+
+kāryakrama dviguṇa(n: saṅkhyā) -> saṅkhyā {
+    phera n * 2;
+}
+
+kāryakrama praveśa(p: saṅkhyā-guhya) {
+    let x = dviguṇa(p);
+    lekha(x);
+}
+"#;
+    let err = compile(source).expect_err("the secret is printed");
+    assert!(
+        err.message
+            .contains("a -guhya value is printed by `mudraṇa!`"),
+        "{}",
+        err.message
+    );
+    let path = [
+        "10:20: `p` is a -guhya parameter of `praveśa`",
+        "11:21: passed as `n` to `dviguṇa`",
+        "7:5: returned from `dviguṇa`",
+        "11:5: assigned to `x`",
+        "12:11: passed as `n` to `lekha`",
+        "3:5: printed by `mudraṇa!`",
+    ];
+    let mut rest = err.message.as_str();
+    for step in path {
+        let at = rest
+            .find(step)
+            .unwrap_or_else(|| panic!("missing `{}` in order:\n{}", step, err.message));
+        rest = &rest[at..];
+    }
+    assert_eq!(err.location.expect("location").line, 3);
+}
+
+/// Test unlabeled data and public data flow freely
+#[test]
+fn test_public_flows() {
+    let source = format!(
+        "{}\n{}",
+        KHATA,
+        r#"
+kāryakrama lekha(k: &Khātā, n: saṅkhyā) {
+    mudraṇa!("{} {}", k.nāma, n + 1);
+}
+"#
+    );
+    compile(&source).expect("nothing secret is printed");
+}
+
+/// Test a -guhya field read through a method cannot be returned as public
+#[test]
+fn test_field_and_return() {
+    let source = format!(
+        "{}\n{}",
+        KHATA,
+        r#"
+kāryakrama prakaṭa(k: &Khātā) -> saṅkhyā-sarvajnika {
+    phera k.kuñjī_mūlya();
+}
+"#
+    );
+    let err = compile(&source).expect_err("the key is returned").message;
+    assert!(
+        err.contains("returned as the -sarvajnika result of `prakaṭa`"),
+        "{}",
+        err
+    );
+    assert!(err.contains("field `kuñjī` is -guhya"), "{}", err);
+    assert!(
+        err.contains("returned by the call to `Khātā::kuñjī_mūlya`"),
+        "{}",
+        err
+    );
+
+    // Nor stored in a public field
+    let source = format!(
+        "{}\n{}",
+        KHATA, "kāryakrama nyāsa(k: &mut Khātā, g: saṅkhyā-guhya) {\n    k.nāma = g;\n}\n"
+    );
+    let err = compile(&source).expect_err("the key is stored").message;
+    assert!(
+        err.contains("stored in -sarvajnika field `nāma`"),
+        "{}",
+        err
+    );
+}
+
+/// Test a -sarvajnika parameter rejects secret arguments
+#[test]
+fn test_public_parameter() {
+    let source = r#"
+kāryakrama bhej(n: saṅkhyā-sarvajnika) -> saṅkhyā {
+    phera n;
+}
+
+kāryakrama f(p: saṅkhyā-guhya) -> saṅkhyā {
+    phera bhej(p + 1);
+}
+"#;
+    let err = compile(source).expect_err("a secret argument").message;
+    assert!(
+        err.contains("passed as -sarvajnika parameter `n` of `bhej`"),
+        "{}",
+        err
+    );
+}
+
+/// Test a branch on a secret makes what it assigns secret
+#[test]
+fn test_implicit_flow() {
+    let source = r#"
+kāryakrama f(p: saṅkhyā-guhya) {
+    let t = 0;
+    yad p > 5 {
+        t = 1;
+    }
+    mudraṇa!("{}", t);
+}
+"#;
+    let err = compile(source).expect_err("t reveals p").message;
+    assert!(err.contains("decides a branch"), "{}", err);
+    assert!(err.contains("5:9: assigned to `t`"), "{}", err);
+}
+
+/// Test `#[prakāśana]` on a function or binding declassifies
+#[test]
+fn test_declassify() {
+    let source = format!(
+        "{}\n{}",
+        KHATA,
+        r#"
+#[prakāśana]
+kāryakrama samāna(a: saṅkhyā, b: saṅkhyā) -> bool {
+    phera a == b;
+}
+
+kāryakrama jāñc(k: &Khātā, p: saṅkhyā) {
+    let milā = samāna(k.kuñjī, p);
+    mudraṇa!("{}", milā);
+    #[prakāśana]
+    let aṅka = k.kuñjī % 10;
+    mudraṇa!("{}", aṅka);
+}
+"#
+    );
+    compile(&source).expect("declassified values may be printed");
+
+    let source = "#[prakāśana(satya)]\nkāryakrama f() {}\n";
+    let err = compile(source)
+        .expect_err("prakāśana takes no arguments")
+        .message;
+    assert!(err.contains("`#[prakāśana]`"), "{}", err);
+}
+
+/// Test labels reach through the types holding them
+#[test]
+fn test_labeled_types() {
+    let source = "kāryakrama f(k: &saṅkhyā-guhya) {\n    mudraṇa!(\"{}\", k);\n}\n";
+    let err = compile(source)
+        .expect_err("a reference to a secret")
+        .message;
+    assert!(err.contains("`k` is a -guhya parameter of `f`"), "{}", err);
+
+    let source = r#"
+kāryakrama f(v: Vikalpa<saṅkhyā-guhya>) -> saṅkhyā {
+    pratyabhijñā v {
+        Vikalpa::Kiñcit(x) => x,
+        Vikalpa::Śūnya => 0,
+    }
+}
+
+kāryakrama g(v: Vikalpa<saṅkhyā-guhya>) {
+    mudraṇa!("{}", f(v));
+}
+"#;
+    let err = compile(source).expect_err("a secret in a Vikalpa").message;
+    assert!(err.contains("matched as `x`"), "{}", err);
+}
+
+/// Test recursive functions settle on a summary
+#[test]
+fn test_recursion() {
+    let source = r#"
+kāryakrama yoga(n: saṅkhyā, s: saṅkhyā) -> saṅkhyā {
+    yad n == 0 {
+        phera s;
+    }
+    phera yoga(n - 1, s + n);
+}
+
+kāryakrama f(p: saṅkhyā-guhya) {
+    mudraṇa!("{}", yoga(3, 0));
+    mudraṇa!("{}", yoga(3, p));
+}
+"#;
+    let err = compile(source).expect_err("p reaches the sum").message;
+    assert_eq!(err.matches("Illegal flow").count(), 1, "{}", err);
+    assert!(err.contains("passed as `s` to `yoga`"), "{}", err);
+}
@@ -101,3 +101,308 @@ kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
 "#;
     assert!(compiles_ok(source), "Function calls should type check");
 }
+
+/// Test trait method resolution through impls, including default methods
+#[test]
+fn test_trait_method_calls() {
+    let source = r#"
+prakāra Bindu {
+    x: saṅkhyā-a-k-t32,
+    y: saṅkhyā-a-k-t32,
+}
+
+guṇa Dūrī {
+    kāryakrama dūrī(&sva) -> saṅkhyā-a-k-t32;
+    kāryakrama dviguṇa_dūrī(&sva) -> saṅkhyā-a-k-t32 {
+        phera sva.dūrī() * 2
+    }
+}
+
+kriyānvaya Dūrī for Bindu {
+    kāryakrama dūrī(&sva) -> saṅkhyā-a-k-t32 {
+        phera sva.x + sva.y
+    }
+}
+
+kriyānvaya Bindu {
+    kāryakrama pratirūpa(b: Bindu) -> Bindu {
+        phera b
+    }
+}
+
+kāryakrama mukhya(b: Bindu) -> saṅkhyā-a-k-t32 {
+    phera b.dviguṇa_dūrī() + Bindu::pratirūpa(b).dūrī()
+}
+"#;
+    assert!(compiles_ok(source), "Trait method calls should type check");
+}
+
+/// Test a method two types implement is called on the receiver's type,
+/// whether the receiver is a value, an element or a field
+#[test]
+fn test_trait_method_per_receiver() {
+    let source = r#"
+guṇa Dūrī {
+    kāryakrama dūrī(&sva) -> saṅkhyā-a-k-t32;
+}
+
+prakāra A {
+    x: saṅkhyā-a-k-t32,
+}
+
+prakāra B {
+    y: saṅkhyā-a-k-t32,
+    a: A,
+}
+
+kriyānvaya Dūrī for A {
+    kāryakrama dūrī(&sva) -> saṅkhyā-a-k-t32 {
+        phera sva.x
+    }
+}
+
+kriyānvaya Dūrī for B {
+    kāryakrama dūrī(&sva) -> saṅkhyā-a-k-t32 {
+        phera sva.y * 2
+    }
+}
+
+kāryakrama mukhya(a: A, b: B) -> saṅkhyā-a-k-t32 {
+    let v = [a, a];
+    phera a.dūrī() + b.dūrī() + v[1].dūrī() + b.a.dūrī()
+}
+"#;
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    let mut session = CompilerSession::new(options);
+    let result = session
+        .compile(source)
+        .expect("Both impls should be called");
+    let asm = String::from_utf8_lossy(&result.output);
+    assert_eq!(asm.matches("call A__dūrī").count(), 3, "{}", asm);
+    assert_eq!(asm.matches("call B__dūrī").count(), 1, "{}", asm);
+}
+
+/// Test methods on generic parameters resolve through their bounds
+#[test]
+fn test_generic_bound_method() {
+    let source = r#"
+guṇa Gaṇya {
+    kāryakrama gaṇanā(&sva) -> saṅkhyā-a-k-t32;
+}
+
+kāryakrama dvi<T>(v: T) -> saṅkhyā-a-k-t32 yatra T: Gaṇya {
+    phera v.gaṇanā() * 2
+}
+"#;
+    assert!(
+        compiles_ok(source),
+        "Bounded generic methods should type check"
+    );
+}
+
+/// Test an impl missing a required trait method is rejected
+#[test]
+fn test_impl_missing_method() {
+    let source = r#"
+prakāra Bindu {
+    x: saṅkhyā-a-k-t32,
+}
+
+guṇa Dūrī {
+    kāryakrama dūrī(&sva) -> saṅkhyā-a-k-t32;
+}
+
+kriyānvaya Dūrī for Bindu {}
+"#;
+    assert!(
+        !compiles_ok(source),
+        "Missing trait methods should be rejected"
+    );
+}
+
+/// Test use of a moved value is rejected by the borrow checker
+#[test]
+fn test_use_after_move() {
+    let source = r#"
+kāryakrama upabhoga(s: sūtra) -> saṅkhyā-a-k-t32 {
+    phera 0
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    let s: sūtra = "namaste";
+    upabhoga(s);
+    phera upabhoga(s)
+}
+"#;
+    assert!(!compiles_ok(source), "Use after move should be rejected");
+}
+
+/// Test moving a borrowed value is reported at the move
+#[test]
+fn test_move_while_borrowed_location() {
+    let source = r#"
+kāryakrama upabhoga(s: sūtra) -> saṅkhyā-a-k-t32 {
+    phera 0
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    let s: sūtra = "namaste";
+    let r = &s;
+    phera upabhoga(s)
+}
+"#;
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    let mut session = CompilerSession::new(options);
+    let err = session.compile(source).expect_err("Move while borrowed");
+    assert!(
+        err.message
+            .contains("9:20: cannot move `s` while it is borrowed"),
+        "{}",
+        err.message
+    );
+    let location = err.location.expect("location");
+    assert_eq!((location.line, location.column), (9, 20));
+}
+
+/// Test copy types can be used after being passed by value
+#[test]
+fn test_copy_values_not_moved() {
+    let source = r#"
+kāryakrama dvi(x: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {
+    phera x * 2
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    let n = 21;
+    dvi(n);
+    phera dvi(n)
+}
+"#;
+    assert!(compiles_ok(source), "Copy values should not be moved");
+}
+
+/// Test returning a reference to a local is rejected
+#[test]
+fn test_dangling_borrow() {
+    let source = r#"
+kāryakrama lambita() -> &saṅkhyā-a-k-t32 {
+    let x: saṅkhyā-a-k-t32 = 42;
+    phera &x;
+}
+"#;
+    assert!(!compiles_ok(source), "Dangling borrow should be rejected");
+}
+
+/// Test a parameter can be borrowed anywhere in the body, which it outlives
+#[test]
+fn test_borrow_parameter() {
+    let source = r#"
+kāryakrama g(x: &saṅkhyā) -> saṅkhyā {
+    phera 1;
+}
+
+kāryakrama f(a: saṅkhyā, b: saṅkhyā) -> saṅkhyā {
+    let r = &b;
+    yad a > 0 {
+        phera g(&a) + g(r);
+    }
+    phera g(&b);
+}
+"#;
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    let mut session = CompilerSession::new(options);
+    if let Err(err) = session.compile(source) {
+        panic!("Borrowing a parameter should compile: {}", err.message);
+    }
+}
+
+/// Test user mantras expand with repetition and hygienic locals
+#[test]
+fn test_user_mantra_expansion() {
+    let source = r#"
+mantra! yoga {
+    ($($x:expr),+) => { 0 $(+ $x)+ };
+}
+
+mantra! dviguna {
+    ($x:expr, $out:ident) => { let t = $x; let $out = t + t; };
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    let t = 5;
+    dviguna!(t, d);
+    phera yoga!(t, d, 1);
+}
+"#;
+    assert!(
+        compiles_ok(source),
+        "User mantras should expand before parsing"
+    );
+}
+
+/// Test an invocation matching no rule is reported with a backtrace location
+#[test]
+fn test_mantra_no_matching_rule() {
+    let source = r#"
+mantra! eka {
+    ($x:ident) => { $x };
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera eka!(1 + 2);
+}
+"#;
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    let mut session = CompilerSession::new(options);
+    let err = session.compile(source).expect_err("No rule should match");
+    assert!(err.message.contains("eka!"), "{}", err.message);
+    assert_eq!(err.location.map(|l| l.line), Some(7));
+}
+
+/// Test type errors inside an expansion carry an expansion note
+#[test]
+fn test_mantra_expansion_backtrace() {
+    let source = r#"
+mantra! ayogya {
+    ($x:expr) => { $x + ajñāta };
+}
+
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    phera ayogya!(1);
+}
+"#;
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    let mut session = CompilerSession::new(options);
+    let err = session
+        .compile(source)
+        .expect_err("Unknown identifier in expansion");
+    assert!(
+        err.notes
+            .iter()
+            .any(|n| n.starts_with("in expansion of `ayogya!` at 7:11")),
+        "{:?}",
+        err.notes
+    );
+}
+
+/// Test builtin mantras lower to checked calls
+#[test]
+fn test_builtin_mantras() {
+    let source = r#"
+kāryakrama mukhya() -> saṅkhyā-a-k-t32 {
+    let n = 2 + 2;
+    assert_eq!(n, 4);
+    dṛḍha!(n > 0, "dhanātmaka");
+    mudraṇa!("n = {}", n);
+    let s: sūtra = format!("{}", n);
+    mudraṇa!(s);
+    phera n;
+}
+"#;
+    assert!(compiles_ok(source), "Builtin mantras should type check");
+}
@@ -0,0 +1,236 @@
+//! Integration tests for fixed arrays, slices and `Sūci`
+//!
+//! All three are the address of a `{data, len}` header, indexed with a
+//! bounds check and sliced with ranges.
+
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompilerSession};
+use jagannath_compiler::parser::{ast::*, Parser};
+
+fn compile(source: &str) -> Result<String, CompileError> {
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    let mut session = CompilerSession::new(options);
+    session
+        .compile(source)
+        .map(|result| String::from_utf8_lossy(&result.output).into_owned())
+}
+
+/// Compile a function `f(n)` with `body`, returning the error message
+fn compile_error(body: &str) -> String {
+    let source = format!(
+        "kāryakrama f(n: saṅkhyā-a-k-t32) -> saṅkhyā-a-k-t32 {{\n{}\n}}\n",
+        body
+    );
+    compile(&source).expect_err("should not type check").message
+}
+
+const QUICKSORT: &str = r#"
+kāryakrama vibhajana(a: &mut [saṅkhyā]) -> saṅkhyā {
+    let pīvoṭa = a[a.dīrghatā() - 1];
+    let i = 0;
+    cala j : 0..a.dīrghatā() - 1 {
+        yad a[j] < pīvoṭa {
+            let t = a[i];
+            a[i] = a[j];
+            a[j] = t;
+            i += 1;
+        }
+    }
+    let t = a[i];
+    a[i] = a[a.dīrghatā() - 1];
+    a[a.dīrghatā() - 1] = t;
+    phera i;
+}
+
+kāryakrama krama(a: &mut [saṅkhyā]) {
+    yad a.dīrghatā() < 2 {
+        phera;
+    }
+    let p = vibhajana(a);
+    krama(&mut a[..p]);
+    krama(&mut a[p + 1..]);
+}
+
+kāryakrama mukhya() -> saṅkhyā {
+    let a = [5, 3, 9, 1, 7];
+    krama(&mut a);
+    let v = Sūci::nava();
+    cala x : a {
+        v.yojaya(x * 2);
+    }
+    let yoga = 0;
+    cala x : v {
+        yoga += x;
+    }
+    phera yoga + v.niṣkāsaya();
+}
+"#;
+
+/// Test array and slice types and range indexing in the AST
+#[test]
+fn test_parse_arrays_and_slices() {
+    let source = r#"
+kāryakrama f(a: [saṅkhyā; 4], s: &[saṅkhyā]) -> saṅkhyā {
+    let t = s[1..=2];
+    phera a[..2][0];
+}
+"#;
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function declaration");
+    };
+    assert!(matches!(
+        &func.params[0].ty,
+        Type::Array { size: Some(4), .. }
+    ));
+    assert!(matches!(
+        &func.params[1].ty,
+        Type::Reference { inner, .. } if matches!(**inner, Type::Array { size: None, .. })
+    ));
+
+    let Stmt::Let {
+        value: Some(Expr::Index { index, .. }),
+        ..
+    } = &func.body.stmts[0]
+    else {
+        panic!("Expected an index, got {:?}", func.body.stmts[0]);
+    };
+    assert!(matches!(
+        &**index,
+        Expr::Range {
+            start: Some(_),
+            end: Some(_),
+            inclusive: true,
+            ..
+        }
+    ));
+
+    let Stmt::Return {
+        value: Some(Expr::Index { object, .. }),
+        ..
+    } = &func.body.stmts[1]
+    else {
+        panic!("Expected an index, got {:?}", func.body.stmts[1]);
+    };
+    assert!(matches!(
+        &**object,
+        Expr::Index { index, .. }
+            if matches!(**index, Expr::Range { start: None, end: Some(_), .. })
+    ));
+}
+
+/// Test array lengths must be known at compile time and `..=` needs an end
+#[test]
+fn test_parse_errors() {
+    let source = "kāryakrama f(a: [saṅkhyā; n]) {}\n";
+    let err = compile(source).expect_err("n names nothing").message;
+    assert!(
+        err.contains("`n` cannot be evaluated at compile time"),
+        "{}",
+        err
+    );
+
+    let source = "kāryakrama f(a: [saṅkhyā]) {\n    let s = a[1..=];\n}\n";
+    let errors = Parser::parse_str(source).expect_err("should not parse");
+    assert!(
+        errors
+            .iter()
+            .any(|e| e.message.contains("`..=` needs an end bound")),
+        "{:?}",
+        errors
+    );
+}
+
+/// Test misuse of sequences is caught by the type checker
+#[test]
+fn test_sequence_type_errors() {
+    let err = compile_error("    let a = [1, 2, 3];\n    phera a[3];");
+    assert!(
+        err.contains("Index 3 is out of bounds for an array of length 3"),
+        "{}",
+        err
+    );
+
+    let err = compile_error("    phera n[0];");
+    assert!(err.contains("index"), "{}", err);
+
+    let err = compile_error("    let a = [1, 2];\n    phera a[satya];");
+    assert!(err.contains("index"), "{}", err);
+
+    let err = compile_error("    let a = [1, 2];\n    phera a.niṣkāsaya();");
+    assert!(err.contains("niṣkāsaya"), "{}", err);
+
+    let err = compile_error("    let v = Sūci::nava();\n    v.yojaya(1, 2);\n    phera 0;");
+    assert!(err.contains("yojaya"), "{}", err);
+}
+
+/// Test a `Sūci` only holds values that fit the word each element is kept in
+#[test]
+fn test_suci_elements_are_one_word() {
+    let err =
+        compile_error("    let v = Sūci::nava();\n    v.yojaya(Vikalpa::Kiñcit(n));\n    phera 0;");
+    assert!(
+        err.contains("`Sūci` elements are one word each, but `Vikalpa<i32>` takes 2"),
+        "{}",
+        err
+    );
+
+    let source = "kāryakrama f(n: saṅkhyā) -> saṅkhyā {\n    let v = Sūci::nava();\n    v.yojaya(n > 0);\n    phera 0;\n}\n";
+    compile(source).expect("A bool is one word");
+}
+
+/// Test indexing, slicing and `dīrghatā` lower to checked header accesses
+#[test]
+fn test_indexing_is_bounds_checked() {
+    let source = r#"
+kāryakrama f(a: &[saṅkhyā], i: saṅkhyā) -> saṅkhyā {
+    let s = a[1..];
+    phera s[i] + s.dīrghatā();
+}
+"#;
+    let asm = compile(source).expect("Indexing should compile");
+    assert!(asm.contains("Array index out of bounds"), "{}", asm);
+    assert!(asm.contains("Slice end out of bounds"), "{}", asm);
+    assert!(asm.contains("ud2"), "{}", asm);
+    // Elements are read through the header's data pointer
+    assert!(asm.contains("mov rax, QWORD PTR [rcx+rdx*8]"), "{}", asm);
+    assert!(asm.contains("mov rax, QWORD PTR [rax+8]"), "{}", asm);
+}
+
+/// Test a fixed array gets its header and elements in the frame
+#[test]
+fn test_array_literal_layout() {
+    let source = r#"
+kāryakrama f() -> saṅkhyā {
+    let a = [10, 20, 30];
+    phera a[2];
+}
+"#;
+    let asm = compile(source).expect("Array literal should compile");
+    assert!(asm.contains(", 3\n"), "{}", asm);
+    assert!(asm.contains(", 30\n"), "{}", asm);
+}
+
+/// Test a quicksort over slices, and `Sūci` growth and iteration
+#[test]
+fn test_quicksort() {
+    let asm = compile(QUICKSORT).expect("Quicksort should compile");
+    assert!(asm.contains("call krama"), "{}", asm);
+    assert!(asm.contains("call vibhajana"), "{}", asm);
+    assert!(asm.contains("call jag_suci_nava"), "{}", asm);
+    assert!(asm.contains("call jag_suci_yojaya"), "{}", asm);
+    assert!(asm.contains("call jag_suci_nishkasaya"), "{}", asm);
+    // Swaps store through element addresses
+    assert!(asm.contains("Store through pointer"), "{}", asm);
+}
+
+/// Test the quicksort benchmark compiles as written
+#[test]
+fn test_quicksort_benchmark() {
+    let source = include_str!("../../benchmarks/jagannath/quicksort.jag");
+    let asm = compile(source).expect("The quicksort benchmark should compile");
+    assert!(asm.contains("call drutakrama_trimārga"), "{}", asm);
+    assert!(asm.contains("call jag_suci_kshamata"), "{}", asm);
+    assert!(asm.contains("Slice starts after its end"), "{}", asm);
+}
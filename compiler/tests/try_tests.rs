@@ -0,0 +1,226 @@
+//! Integration tests for the `?` operator: early return of `Viphala` or
+//! `Śūnya`, and conversion of errors through `From`
+
+use jagannath_compiler::codegen::asm::Target;
+use jagannath_compiler::driver::options::CompilerOptions;
+use jagannath_compiler::driver::{CompileError, CompilerSession};
+use jagannath_compiler::parser::{ast::*, Parser};
+
+fn compile_for_target(source: &str, target: Target) -> Result<String, CompileError> {
+    let mut options = CompilerOptions::new();
+    options.emit_asm = true;
+    options.target = target;
+    let mut session = CompilerSession::new(options);
+    session
+        .compile(source)
+        .map(|result| String::from_utf8_lossy(&result.output).into_owned())
+}
+
+fn compile(source: &str) -> Result<String, CompileError> {
+    compile_for_target(source, Target::X86_64)
+}
+
+/// Two error types, the second made from the first
+const DOSA: &str = r#"
+prakāra PaṭhanaDoṣa {
+    Ṛṇa(saṅkhyā),
+}
+
+prakāra Doṣa {
+    Saṅketa(saṅkhyā),
+}
+
+kriyānvaya From<PaṭhanaDoṣa> for Doṣa {
+    kāryakrama from(d: PaṭhanaDoṣa) -> Doṣa {
+        pratyabhijñā d {
+            PaṭhanaDoṣa::Ṛṇa(n) => Doṣa::Saṅketa(n),
+        }
+    }
+}
+
+kāryakrama gaṇa(n: saṅkhyā) -> Phala<saṅkhyā, PaṭhanaDoṣa> {
+    yad n < 0 {
+        phera Phala::Viphala(PaṭhanaDoṣa::Ṛṇa(n));
+    }
+    phera Phala::Saphala(n * 2);
+}
+"#;
+
+#[test]
+fn test_parse_try() {
+    let source = "kāryakrama f(p: Phala<saṅkhyā, bool>) -> Phala<saṅkhyā, bool> {\n    phera Phala::Saphala(p? + 1);\n}\n";
+    let ast = Parser::parse_str(source).expect("Failed to parse");
+    let Item::Function(func) = &ast.items[0] else {
+        panic!("Expected function declaration");
+    };
+    let Stmt::Return {
+        value: Some(Expr::Call { args, .. }),
+        ..
+    } = &func.body.stmts[0]
+    else {
+        panic!("Expected a return of a call");
+    };
+    assert!(matches!(
+        &args[0],
+        Expr::Binary { left, .. } if matches!(**left, Expr::Try { .. })
+    ));
+}
+
+/// Test `?` on the same error type returns the `Viphala` as it is
+#[test]
+fn test_propagate() {
+    let source = r#"
+kāryakrama bhaga(a: saṅkhyā, b: saṅkhyā) -> Phala<saṅkhyā, bool> {
+    yad b == 0 {
+        phera Phala::Viphala(satya);
+    }
+    phera Phala::Saphala(a / b);
+}
+
+kāryakrama dvi(a: saṅkhyā, b: saṅkhyā) -> Phala<saṅkhyā, bool> {
+    let x = bhaga(a, b)?;
+    phera Phala::Saphala(x + x);
+}
+"#;
+    let asm = compile(source).expect("`?` on a Phala should compile");
+    let dvi = &asm[asm.find("dvi:").expect("dvi is emitted")..];
+    assert!(dvi.contains("call bhaga"), "{}", dvi);
+    // The tag is tested, and the failure sets the result's tag to Viphala
+    assert!(dvi.contains("movzx rax, BYTE PTR"), "{}", dvi);
+    assert!(dvi.contains("mov BYTE PTR [rbp-16], 1"), "{}", dvi);
+    assert!(dvi.contains("jmp .Ldvi_epilogue"), "{}", dvi);
+    assert!(!dvi.contains("__from"), "{}", dvi);
+}
+
+/// Test a different error type is converted with its `From` impl
+#[test]
+fn test_convert_error() {
+    let source = format!(
+        "{}\n{}",
+        DOSA,
+        r#"
+kāryakrama yoga(a: saṅkhyā, b: saṅkhyā) -> Phala<saṅkhyā, Doṣa> {
+    let x = gaṇa(a)?;
+    let y = gaṇa(b)?;
+    phera Phala::Saphala(x + y);
+}
+"#
+    );
+    let asm = compile(&source).expect("`?` with a From impl should compile");
+    assert!(asm.contains("Doṣa__from__PaṭhanaDoṣa:"), "{}", asm);
+    let yoga = &asm[asm.find("yoga:").expect("yoga is emitted")..];
+    assert_eq!(
+        yoga.matches("call Doṣa__from__PaṭhanaDoṣa").count(),
+        2,
+        "{}",
+        yoga
+    );
+
+    for target in [Target::AArch64, Target::RiscV64] {
+        let asm = compile_for_target(&source, target).expect("`?` compiles on every target");
+        assert!(asm.contains("Doṣa__from__PaṭhanaDoṣa"), "{}", asm);
+    }
+}
+
+/// Test a three-word enum is written to the address the caller passes
+#[test]
+fn test_result_in_memory() {
+    let asm = compile(DOSA).expect("gaṇa should compile");
+    let gana = &asm[asm.find("gaṇa:").expect("gaṇa is emitted")..];
+    // The address arrives in RDI, the argument in RSI
+    assert!(gana.contains("mov QWORD PTR [rbp-72], rdi"), "{}", gana);
+    assert!(gana.contains("mov QWORD PTR [rbp-32], rsi"), "{}", gana);
+    assert!(gana.contains("mov QWORD PTR [r11+16], rax"), "{}", gana);
+
+    let asm = compile_for_target(DOSA, Target::AArch64).expect("gaṇa should compile");
+    assert!(asm.contains("str x0, [x8, #16]"), "{}", asm);
+    let asm = compile_for_target(DOSA, Target::RiscV64).expect("gaṇa should compile");
+    assert!(asm.contains("sd t0, 16(t1)"), "{}", asm);
+}
+
+/// Test `?` on a `Vikalpa` returns `Śūnya`
+#[test]
+fn test_vikalpa() {
+    let source = r#"
+kāryakrama prathama(v: Vikalpa<saṅkhyā>, w: Vikalpa<saṅkhyā>) -> Vikalpa<saṅkhyā> {
+    phera Vikalpa::Kiñcit(v? + w?);
+}
+"#;
+    let asm = compile(source).expect("`?` on a Vikalpa should compile");
+    assert!(asm.contains("mov BYTE PTR [rbp-16], 0"), "{}", asm);
+    assert!(asm.contains("mov BYTE PTR [rbp-16], 1"), "{}", asm);
+}
+
+/// Test `?` outside a function returning `Phala` or `Vikalpa` is an error
+/// at the function's signature
+#[test]
+fn test_try_outside_phala() {
+    let source = "\nkāryakrama f(p: Phala<saṅkhyā, bool>) -> saṅkhyā {\n    phera p?;\n}\n";
+    let err = compile(source).expect_err("f returns a saṅkhyā");
+    assert!(
+        err.message.contains(
+            "`?` needs function 'f' to return `Phala` or `Vikalpa`, but it returns `i32`"
+        ),
+        "{}",
+        err.message
+    );
+    assert_eq!(err.location.expect("location").line, 2);
+
+    // `Vikalpa` and `Phala` do not mix
+    let source = "kāryakrama f(v: Vikalpa<saṅkhyā>) -> Phala<saṅkhyā, bool> {\n    phera Phala::Saphala(v?);\n}\n";
+    let err = compile(source).expect_err("a Vikalpa has no error").message;
+    assert!(err.contains("`?` in function 'f'"), "{}", err);
+}
+
+#[test]
+fn test_not_tryable() {
+    let source =
+        "kāryakrama f(n: saṅkhyā) -> Vikalpa<saṅkhyā> {\n    phera Vikalpa::Kiñcit(n?);\n}\n";
+    let err = compile(source).expect_err("an integer has no `?`").message;
+    assert!(
+        err.contains("`?` applies to `Phala` and `Vikalpa` values, not `i32`"),
+        "{}",
+        err
+    );
+}
+
+/// Test an error type with no `From` impl for the function's is an error
+#[test]
+fn test_missing_conversion() {
+    let source = format!(
+        "{}\n{}",
+        DOSA,
+        r#"
+kāryakrama f(a: saṅkhyā) -> Phala<saṅkhyā, bool> {
+    phera Phala::Saphala(gaṇa(a)?);
+}
+"#
+    );
+    let err = compile(&source)
+        .expect_err("PaṭhanaDoṣa is not a bool")
+        .message;
+    assert!(
+        err.contains("`?` cannot convert error `PaṭhanaDoṣa` into `bool`"),
+        "{}",
+        err
+    );
+}
+
+/// Test `?` in a closure is an error, as it cannot return from the function
+#[test]
+fn test_try_in_closure() {
+    let source = r#"
+kāryakrama f(p: Phala<saṅkhyā, bool>) -> Phala<saṅkhyā, bool> {
+    let g = |q: Phala<saṅkhyā, bool>| q?;
+    phera p;
+}
+"#;
+    let err = compile(source)
+        .expect_err("closures cannot use `?`")
+        .message;
+    assert!(
+        err.contains("`?` cannot be used inside a closure"),
+        "{}",
+        err
+    );
+}
@@ -14,6 +14,10 @@
 //! ## I/O Operations (इनपुट/आउटपुट)
 //! - **Mudraya** - Console printing
 //! - **Kosha** - File I/O with streaming support
+//! - **Vinyāsa** - Formatting behind `mudraṇa!` and `vinyāsa!`
+//!
+//! ## Collections (संग्रह)
+//! - **Sūci** - Growable vectors behind `Sūci<T>`
 //!
 //! ## Sanskrit API
 //! All functions have Sanskrit aliases for authentic Jagannath usage.
@@ -38,6 +42,8 @@ pub mod io;
 pub mod panic;
 pub mod async_runtime;
 pub mod simd;
+pub mod suci;
+pub mod vinyasa;
 
 // Re-exports for convenience
 pub use allocator::{AllocatorStats, Kosha, PanchaKoshaAllocator, PretaState};
@@ -14,6 +14,10 @@
 #[cfg(feature = "std")]
 use std::backtrace::Backtrace;
 #[cfg(feature = "std")]
+use std::ffi::{c_char, CStr};
+#[cfg(feature = "std")]
+use std::io::Write;
+#[cfg(feature = "std")]
 use std::panic::{self, PanicHookInfo};
 
 /// Naraka (नरक) - Classification of programming sins
@@ -282,6 +286,36 @@ pub fn enter_naraka(naraka: Naraka, message: &str) -> ! {
     panic!("[{}] {}: {}", naraka.name(), naraka.sin(), message);
 }
 
+/// Exit code of a program that failed an assertion, as for a Rust panic
+pub const VIKSHEPA_NIRGAMA: i32 = 101;
+
+/// Fail an assertion (the `vikṣepa` builtin a failed `assert!` calls)
+///
+/// # Safety
+/// `message` must be null or point to a NUL-terminated string.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern "C" fn jag_viksepa(message: *const c_char) -> ! {
+    if message.is_null() {
+        viksepa("assertion failed")
+    } else {
+        viksepa(&CStr::from_ptr(message).to_string_lossy())
+    }
+}
+
+/// Fail the running program from inside the runtime
+///
+/// Flushes what was printed so far, reports `message` under Vajrakantaka
+/// on stderr and exits with [`VIKSHEPA_NIRGAMA`]. Unlike `panic!`, this is
+/// safe to reach from an `extern "C"` function, which cannot unwind.
+#[cfg(feature = "std")]
+pub fn viksepa(message: &str) -> ! {
+    let _ = std::io::stdout().flush();
+    let naraka = Naraka::Vajrakantaka;
+    eprintln!("[{}] {}: {}", naraka.name(), naraka.sin(), message);
+    std::process::exit(VIKSHEPA_NIRGAMA)
+}
+
 /// Panic function for no_std environments
 #[cfg(not(feature = "std"))]
 #[panic_handler]
@@ -0,0 +1,160 @@
+//! Growable Vectors (सूची - sūci, "list")
+//!
+//! Target of the compiler's lowering of `Sūci<T>`. A sūci value is the
+//! address of a [`SuciShira`] header on the heap:
+//!
+//! ```text
+//! v = jag_suci_nava()
+//! jag_suci_yojaya(v, x)
+//! x = jag_suci_nishkasaya(v)
+//! ```
+//!
+//! The header starts with the same `{data, len}` pair as the header of a
+//! fixed array or slice, so indexing, `dīrghatā()` and iteration compile
+//! the same way for all three. Every element is one 8-byte word; the type
+//! checker rejects a `Sūci` of anything wider. Header and elements come
+//! from the Pañca Kośa allocator.
+
+#[cfg(feature = "std")]
+use crate::allocator::{smriti_avantana, smriti_mukti, PANCHA_KOSHA_ALLOCATOR};
+#[cfg(feature = "std")]
+use crate::panic::viksepa;
+
+/// Capacity of the first allocation of elements
+const PRATHAMA_KSHAMATA: usize = 4;
+
+/// Header of a sūci (शिर - śira, "head")
+#[repr(C)]
+#[derive(Debug)]
+pub struct SuciShira {
+    /// Elements, null while the capacity is zero
+    pub data: *mut i64,
+    /// Number of elements
+    pub dirghata: usize,
+    /// Number of elements there is room for
+    pub kshamata: usize,
+}
+
+/// Allocate `words` 8-byte words in the Kośa suited to their size
+#[cfg(feature = "std")]
+fn avantana(words: usize) -> *mut u8 {
+    let size = words * 8;
+    let kosha = PANCHA_KOSHA_ALLOCATOR.select_tier(size, None);
+    match smriti_avantana(size, kosha) {
+        Some(ptr) => ptr,
+        None => viksepa(&format!("sūci: out of memory allocating {} bytes", size)),
+    }
+}
+
+#[cfg(feature = "std")]
+impl SuciShira {
+    /// Make room for at least one more element
+    ///
+    /// # Safety
+    /// `data` must have been allocated by [`avantana`] with `kshamata`
+    /// words, or be null with a capacity of zero.
+    unsafe fn vardhana(&mut self) {
+        let kshamata = (self.kshamata * 2).max(PRATHAMA_KSHAMATA);
+        let data = avantana(kshamata) as *mut i64;
+        if !self.data.is_null() {
+            core::ptr::copy_nonoverlapping(self.data, data, self.dirghata);
+            smriti_mukti(self.data as *mut u8, self.kshamata * 8);
+        }
+        self.data = data;
+        self.kshamata = kshamata;
+    }
+}
+
+/// New empty sūci (`Sūci::nava()`)
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern "C" fn jag_suci_nava() -> *mut SuciShira {
+    jag_suci_kshamata(0)
+}
+
+/// New empty sūci with room for `kshamata` elements (`Sūci::kṣamatā(n)`)
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern "C" fn jag_suci_kshamata(kshamata: i64) -> *mut SuciShira {
+    let kshamata = kshamata.max(0) as usize;
+    let header = avantana(3) as *mut SuciShira;
+    let data = if kshamata == 0 {
+        core::ptr::null_mut()
+    } else {
+        avantana(kshamata) as *mut i64
+    };
+    unsafe {
+        header.write(SuciShira {
+            data,
+            dirghata: 0,
+            kshamata,
+        });
+    }
+    header
+}
+
+/// Append `value` (`v.yojaya(x)`)
+///
+/// # Safety
+/// `suci` must come from [`jag_suci_nava`] or [`jag_suci_kshamata`].
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern "C" fn jag_suci_yojaya(suci: *mut SuciShira, value: i64) {
+    let suci = &mut *suci;
+    if suci.dirghata == suci.kshamata {
+        suci.vardhana();
+    }
+    suci.data.add(suci.dirghata).write(value);
+    suci.dirghata += 1;
+}
+
+/// Remove and return the last element (`v.niṣkāsaya()`)
+///
+/// Fails the program through [`viksepa`] on an empty sūci.
+///
+/// # Safety
+/// `suci` must come from [`jag_suci_nava`] or [`jag_suci_kshamata`].
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern "C" fn jag_suci_nishkasaya(suci: *mut SuciShira) -> i64 {
+    let suci = &mut *suci;
+    if suci.dirghata == 0 {
+        viksepa("niṣkāsaya on an empty sūci");
+    }
+    suci.dirghata -= 1;
+    suci.data.add(suci.dirghata).read()
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yojaya_grows() {
+        let suci = jag_suci_nava();
+        unsafe {
+            for i in 0..100 {
+                jag_suci_yojaya(suci, i * i);
+            }
+            let shira = &*suci;
+            assert_eq!(shira.dirghata, 100);
+            assert!(shira.kshamata >= 100);
+            assert_eq!(*shira.data.add(7), 49);
+            assert_eq!(*shira.data.add(99), 99 * 99);
+        }
+    }
+
+    #[test]
+    fn test_nishkasaya_is_last_in_first_out() {
+        let suci = jag_suci_kshamata(2);
+        unsafe {
+            assert_eq!((*suci).kshamata, 2);
+            jag_suci_yojaya(suci, 1);
+            jag_suci_yojaya(suci, 2);
+            jag_suci_yojaya(suci, 3);
+            assert_eq!(jag_suci_nishkasaya(suci), 3);
+            assert_eq!(jag_suci_nishkasaya(suci), 2);
+            assert_eq!((*suci).dirghata, 1);
+        }
+    }
+}
@@ -0,0 +1,452 @@
+//! String Formatting (विन्यास - vinyāsa, "arrangement")
+//!
+//! Target of the compiler's lowering of `vinyāsa!` and `mudraṇa!`: an
+//! interpolated string becomes
+//!
+//! ```text
+//! buf = jag_vinyasa_arambha()
+//! jag_vinyasa_sutra(buf, "nāma = ", spec)
+//! jag_vinyasa_int(buf, value, spec, 32)
+//! s = jag_vinyasa_samapti(buf)
+//! ```
+//!
+//! Each value comes with its format specification packed into one word
+//! (see [`Spec::decode`]). Sūtras cross the boundary as NUL-terminated
+//! UTF-8; floats as their bit patterns, since arguments are passed in
+//! integer registers.
+
+#[cfg(feature = "std")]
+use std::ffi::{c_char, CStr, CString};
+#[cfg(feature = "std")]
+use std::io::Write;
+
+/// Alignment within the field width
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sanrekhana {
+    Vama,
+    Madhya,
+    Dakshina,
+}
+
+/// How a value is rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rupa {
+    /// `{}`
+    Pradarshana,
+    /// `{:?}`
+    Vimarsha,
+    /// `{:x}`
+    LaghuShodasha,
+    /// `{:X}`
+    GuruShodasha,
+    /// `{:b}`
+    Dvimana,
+    /// `{:o}`
+    Ashtamana,
+    /// `{:e}`
+    Ghatanka,
+}
+
+/// Decoded format specification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spec {
+    pub fill: char,
+    pub align: Option<Sanrekhana>,
+    pub sign_plus: bool,
+    pub alternate: bool,
+    pub zero_pad: bool,
+    pub width: usize,
+    pub precision: Option<usize>,
+    pub rupa: Rupa,
+}
+
+impl Spec {
+    /// Unpack the word built by the compiler's `FormatSpec::encode`
+    ///
+    /// From the low bits: kind (3), align (2; none, `<`, `^`, `>`), `+`,
+    /// `#`, `0`, has-precision, width (16), precision (16), fill (21).
+    pub fn decode(word: i64) -> Self {
+        let word = word as u64;
+        let bit = |n: u32| word >> n & 1 == 1;
+        Self {
+            rupa: match word & 0b111 {
+                1 => Rupa::Vimarsha,
+                2 => Rupa::LaghuShodasha,
+                3 => Rupa::GuruShodasha,
+                4 => Rupa::Dvimana,
+                5 => Rupa::Ashtamana,
+                6 => Rupa::Ghatanka,
+                _ => Rupa::Pradarshana,
+            },
+            align: match word >> 3 & 0b11 {
+                1 => Some(Sanrekhana::Vama),
+                2 => Some(Sanrekhana::Madhya),
+                3 => Some(Sanrekhana::Dakshina),
+                _ => None,
+            },
+            sign_plus: bit(5),
+            alternate: bit(6),
+            zero_pad: bit(7),
+            precision: bit(8).then_some((word >> 25 & 0xffff) as usize),
+            width: (word >> 9 & 0xffff) as usize,
+            fill: char::from_u32((word >> 41) as u32).unwrap_or(' '),
+        }
+    }
+
+    /// Pad `body` to the field width; text aligns left by default
+    #[cfg(feature = "std")]
+    fn pad(&self, out: &mut String, body: &str, default: Sanrekhana) {
+        let len = body.chars().count();
+        if len >= self.width {
+            out.push_str(body);
+            return;
+        }
+        let gap = self.width - len;
+        let (before, after) = match self.align.unwrap_or(default) {
+            Sanrekhana::Vama => (0, gap),
+            Sanrekhana::Madhya => (gap / 2, gap - gap / 2),
+            Sanrekhana::Dakshina => (gap, 0),
+        };
+        out.extend(std::iter::repeat_n(self.fill, before));
+        out.push_str(body);
+        out.extend(std::iter::repeat_n(self.fill, after));
+    }
+
+    /// Pad a number: `0` puts zeros between the sign or prefix and the
+    /// digits, otherwise numbers align right
+    #[cfg(feature = "std")]
+    fn pad_number(&self, out: &mut String, sign: &str, digits: &str) {
+        if self.zero_pad {
+            let len = sign.chars().count() + digits.chars().count();
+            out.push_str(sign);
+            out.extend(std::iter::repeat_n('0', self.width.saturating_sub(len)));
+            out.push_str(digits);
+        } else {
+            self.pad(out, &format!("{}{}", sign, digits), Sanrekhana::Dakshina);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn sign(&self, negative: bool) -> &'static str {
+        match (negative, self.sign_plus) {
+            (true, _) => "-",
+            (false, true) => "+",
+            (false, false) => "",
+        }
+    }
+}
+
+/// Append a sūtra
+#[cfg(feature = "std")]
+pub fn sutra(out: &mut String, s: &str, spec: Spec) {
+    let s = match spec.precision {
+        Some(max) => s.char_indices().nth(max).map_or(s, |(end, _)| &s[..end]),
+        None => s,
+    };
+    match spec.rupa {
+        Rupa::Vimarsha => spec.pad(out, &format!("{:?}", s), Sanrekhana::Vama),
+        _ => spec.pad(out, s, Sanrekhana::Vama),
+    }
+}
+
+/// Append an integer of `bits` bits, signed or not
+///
+/// Radix forms show the two's complement bits, like Rust's `{:x}`.
+#[cfg(feature = "std")]
+pub fn integer(out: &mut String, value: i64, signed: bool, bits: u32, spec: Spec) {
+    let bits = bits.clamp(1, 64);
+    let shift = 64 - bits;
+    let raw = (value as u64) << shift >> shift;
+    let (negative, magnitude) = if signed {
+        let value = value << shift >> shift;
+        (value < 0, value.unsigned_abs())
+    } else {
+        (false, raw)
+    };
+    let (prefix, digits) = match spec.rupa {
+        Rupa::LaghuShodasha => ("0x", format!("{:x}", raw)),
+        Rupa::GuruShodasha => ("0x", format!("{:X}", raw)),
+        Rupa::Dvimana => ("0b", format!("{:b}", raw)),
+        Rupa::Ashtamana => ("0o", format!("{:o}", raw)),
+        Rupa::Ghatanka => ("", format!("{:e}", magnitude)),
+        Rupa::Pradarshana | Rupa::Vimarsha => ("", magnitude.to_string()),
+    };
+    let radix = !prefix.is_empty();
+    let mut sign = String::from(spec.sign(negative && !radix));
+    if radix && spec.alternate {
+        sign.push_str(prefix);
+    }
+    spec.pad_number(out, &sign, &digits);
+}
+
+/// Append a float
+#[cfg(feature = "std")]
+pub fn float(out: &mut String, value: f64, spec: Spec) {
+    let magnitude = value.abs();
+    let digits = match (spec.rupa, spec.precision) {
+        (Rupa::Ghatanka, Some(p)) => format!("{:.*e}", p, magnitude),
+        (Rupa::Ghatanka, None) => format!("{:e}", magnitude),
+        (_, Some(p)) => format!("{:.*}", p, magnitude),
+        (Rupa::Vimarsha, None) => format!("{:?}", magnitude),
+        (_, None) => format!("{}", magnitude),
+    };
+    let negative = value.is_sign_negative() && !value.is_nan();
+    spec.pad_number(out, spec.sign(negative), &digits);
+}
+
+/// Append a float, keeping single precision's shortest representation
+#[cfg(feature = "std")]
+pub fn float32(out: &mut String, value: f32, spec: Spec) {
+    match (spec.rupa, spec.precision) {
+        (Rupa::Pradarshana | Rupa::Vimarsha, None) => {
+            let digits = match spec.rupa {
+                Rupa::Vimarsha => format!("{:?}", value.abs()),
+                _ => format!("{}", value.abs()),
+            };
+            let negative = value.is_sign_negative() && !value.is_nan();
+            spec.pad_number(out, spec.sign(negative), &digits);
+        }
+        _ => float(out, value as f64, spec),
+    }
+}
+
+/// Append a truth value, spelled as in Jagannath source
+#[cfg(feature = "std")]
+pub fn satya(out: &mut String, value: bool, spec: Spec) {
+    let text = if value { "satya" } else { "asatya" };
+    spec.pad(out, text, Sanrekhana::Vama);
+}
+
+/// Append a character
+#[cfg(feature = "std")]
+pub fn akshara(out: &mut String, value: char, spec: Spec) {
+    match spec.rupa {
+        Rupa::Vimarsha => spec.pad(out, &format!("{:?}", value), Sanrekhana::Vama),
+        _ => spec.pad(out, value.encode_utf8(&mut [0; 4]), Sanrekhana::Vama),
+    }
+}
+
+// ============================================================================
+// C ABI (called from generated code)
+// ============================================================================
+
+/// Open a format buffer
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern "C" fn jag_vinyasa_arambha() -> *mut String {
+    Box::into_raw(Box::default())
+}
+
+/// Append a NUL-terminated sūtra
+///
+/// # Safety
+/// `buf` must come from [`jag_vinyasa_arambha`] and not yet be finished;
+/// `s` must be null or point to a NUL-terminated string.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern "C" fn jag_vinyasa_sutra(buf: *mut String, s: *const c_char, spec: i64) {
+    let text = if s.is_null() {
+        Default::default()
+    } else {
+        CStr::from_ptr(s).to_string_lossy()
+    };
+    sutra(&mut *buf, &text, Spec::decode(spec));
+}
+
+/// Append a signed integer of `bits` bits
+///
+/// # Safety
+/// `buf` must come from [`jag_vinyasa_arambha`] and not yet be finished.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern "C" fn jag_vinyasa_int(buf: *mut String, value: i64, spec: i64, bits: i64) {
+    integer(&mut *buf, value, true, bits as u32, Spec::decode(spec));
+}
+
+/// Append an unsigned integer of `bits` bits
+///
+/// # Safety
+/// `buf` must come from [`jag_vinyasa_arambha`] and not yet be finished.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern "C" fn jag_vinyasa_uint(buf: *mut String, value: i64, spec: i64, bits: i64) {
+    integer(&mut *buf, value, false, bits as u32, Spec::decode(spec));
+}
+
+/// Append an `f64` given by its bits
+///
+/// # Safety
+/// `buf` must come from [`jag_vinyasa_arambha`] and not yet be finished.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern "C" fn jag_vinyasa_float(buf: *mut String, bits: u64, spec: i64) {
+    float(&mut *buf, f64::from_bits(bits), Spec::decode(spec));
+}
+
+/// Append an `f32` given by its bits, in the low half of the word
+///
+/// # Safety
+/// `buf` must come from [`jag_vinyasa_arambha`] and not yet be finished.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern "C" fn jag_vinyasa_float32(buf: *mut String, bits: u64, spec: i64) {
+    float32(&mut *buf, f32::from_bits(bits as u32), Spec::decode(spec));
+}
+
+/// Append a truth value
+///
+/// # Safety
+/// `buf` must come from [`jag_vinyasa_arambha`] and not yet be finished.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern "C" fn jag_vinyasa_bool(buf: *mut String, value: i64, spec: i64) {
+    satya(&mut *buf, value != 0, Spec::decode(spec));
+}
+
+/// Append a character given by its code point
+///
+/// # Safety
+/// `buf` must come from [`jag_vinyasa_arambha`] and not yet be finished.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern "C" fn jag_vinyasa_char(buf: *mut String, code: i64, spec: i64) {
+    let value = char::from_u32(code as u32).unwrap_or(char::REPLACEMENT_CHARACTER);
+    akshara(&mut *buf, value, Spec::decode(spec));
+}
+
+/// Append `()`
+///
+/// # Safety
+/// `buf` must come from [`jag_vinyasa_arambha`] and not yet be finished.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern "C" fn jag_vinyasa_unit(buf: *mut String, _value: i64, spec: i64) {
+    sutra(&mut *buf, "()", Spec::decode(spec));
+}
+
+/// Finish a buffer into a NUL-terminated sūtra owned by the caller
+///
+/// # Safety
+/// `buf` must come from [`jag_vinyasa_arambha`] and not yet be finished.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern "C" fn jag_vinyasa_samapti(buf: *mut String) -> *mut c_char {
+    let mut text = *Box::from_raw(buf);
+    text.retain(|c| c != '\0');
+    CString::new(text).unwrap_or_default().into_raw()
+}
+
+/// Print a sūtra to stdout (the `mudrā` builtin)
+///
+/// # Safety
+/// `s` must be null or point to a NUL-terminated string.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern "C" fn jag_mudra(s: *const c_char) {
+    if !s.is_null() {
+        let mut stdout = std::io::stdout();
+        let _ = stdout.write_all(CStr::from_ptr(s).to_bytes());
+        let _ = stdout.flush();
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    /// Encode like the compiler: kind, align, flags, width, precision, fill
+    fn spec(
+        rupa: u64,
+        align: u64,
+        flags: u64,
+        width: u64,
+        precision: Option<u64>,
+        fill: char,
+    ) -> Spec {
+        let word = rupa
+            | align << 3
+            | flags << 5
+            | (precision.is_some() as u64) << 8
+            | width << 9
+            | precision.unwrap_or(0) << 25
+            | (fill as u64) << 41;
+        Spec::decode(word as i64)
+    }
+
+    fn plain() -> Spec {
+        spec(0, 0, 0, 0, None, ' ')
+    }
+
+    #[test]
+    fn test_decode() {
+        let s = spec(2, 3, 0b111, 10, Some(4), '*');
+        assert_eq!(s.rupa, Rupa::LaghuShodasha);
+        assert_eq!(s.align, Some(Sanrekhana::Dakshina));
+        assert!(s.sign_plus && s.alternate && s.zero_pad);
+        assert_eq!(s.width, 10);
+        assert_eq!(s.precision, Some(4));
+        assert_eq!(s.fill, '*');
+    }
+
+    #[test]
+    fn test_text_alignment() {
+        let mut out = String::new();
+        sutra(&mut out, "nāma", spec(0, 3, 0, 6, None, ' '));
+        sutra(&mut out, "|", plain());
+        sutra(&mut out, "ab", spec(0, 2, 0, 5, None, '-'));
+        sutra(&mut out, "|", plain());
+        sutra(&mut out, "abcdef", spec(0, 0, 0, 0, Some(3), ' '));
+        assert_eq!(out, "  nāma|-ab--|abc");
+
+        let mut out = String::new();
+        sutra(&mut out, "a\"b", spec(1, 0, 0, 0, None, ' '));
+        akshara(&mut out, 'क', spec(1, 0, 0, 0, None, ' '));
+        satya(&mut out, true, plain());
+        assert_eq!(out, "\"a\\\"b\"'क'satya");
+    }
+
+    #[test]
+    fn test_integers() {
+        let mut out = String::new();
+        integer(&mut out, 42, true, 32, spec(0, 0, 0, 5, None, ' '));
+        integer(&mut out, -7, true, 32, spec(0, 0, 0b100, 4, None, ' '));
+        integer(&mut out, 7, true, 32, spec(0, 1, 0b001, 3, None, ' '));
+        assert_eq!(out, "   42-007+7 ");
+
+        let mut out = String::new();
+        integer(&mut out, 255, true, 32, spec(2, 0, 0b010, 0, None, ' '));
+        out.push(' ');
+        integer(&mut out, -1, true, 16, spec(3, 0, 0, 0, None, ' '));
+        out.push(' ');
+        integer(&mut out, 5, false, 8, spec(4, 0, 0b110, 10, None, ' '));
+        assert_eq!(out, "0xff FFFF 0b00000101");
+    }
+
+    #[test]
+    fn test_floats() {
+        let mut out = String::new();
+        float(&mut out, 3.14159, spec(0, 0, 0, 0, Some(2), ' '));
+        out.push(' ');
+        float(&mut out, -2.5, spec(0, 0, 0b100, 7, Some(1), ' '));
+        out.push(' ');
+        float(&mut out, 1.0, spec(1, 0, 0, 0, None, ' '));
+        out.push(' ');
+        float(&mut out, 1500.0, spec(6, 0, 0, 0, None, ' '));
+        out.push(' ');
+        float32(&mut out, 0.1, plain());
+        assert_eq!(out, "3.14 -0002.5 1.0 1.5e3 0.1");
+    }
+
+    #[test]
+    fn test_c_abi_round_trip() {
+        unsafe {
+            let buf = jag_vinyasa_arambha();
+            jag_vinyasa_sutra(buf, c"n = ".as_ptr(), 0);
+            jag_vinyasa_int(buf, 12, 0, 64);
+            jag_vinyasa_float(buf, 0.5f64.to_bits(), 0);
+            let s = jag_vinyasa_samapti(buf);
+            assert_eq!(CStr::from_ptr(s).to_str().unwrap(), "n = 120.5");
+            drop(CString::from_raw(s));
+        }
+    }
+}
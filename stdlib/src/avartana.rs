@@ -27,6 +27,29 @@
 //! let tuesday = monday.agla();  // Next
 //! let sunday = monday.pichla(); // Previous
 //! ```
+//!
+//! ## In Jagannath
+//! The iterators here follow the language's iterator protocol, which
+//! `cala x : e` loops over user types desugar to: `next(&mut sva)` returns
+//! `Vikalpa::Kiñcit(x)` for each item and `Vikalpa::Śūnya` once done,
+//! where Rust returns `Some`/`None`. [`ChakraGati`] never returns `Śūnya`,
+//! so a loop over it ends only by `break`; [`EkaChakra`] returns it after
+//! `PURNA_CHAKRA` items.
+//!
+//! ```text
+//! kriyānvaya Iterator for EkaChakra {
+//!     prakāra Item = saṅkhyā;
+//!     kāryakrama next(&mut sva) -> Vikalpa<saṅkhyā> {
+//!         yad sva.śeṣa == 0 { phera Vikalpa::Śūnya; }
+//!         let x = sva.vartamāna;
+//!         sva.vartamāna = (x + 1) % PURNA_CHAKRA;
+//!         sva.śeṣa -= 1;
+//!         phera Vikalpa::Kiñcit(x);
+//!     }
+//! }
+//!
+//! cala dina : EkaChakra { vartamāna: 0, śeṣa: 7 } { ... }
+//! ```
 
 #![allow(dead_code)]
 
@@ -15,7 +15,7 @@
 
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
-use tracing::{error, info, Level};
+use tracing::{error, info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 
 /// Jagannath Compiler - संस्कृत-आधारित प्रणाली भाषा
@@ -49,6 +49,10 @@ struct Cli {
     #[arg(long, default_value = "rajas", global = true)]
     guna: String,
 
+    /// Features to enable, in addition to those in Jagannath.toml
+    #[arg(long, value_delimiter = ',', global = true)]
+    features: Vec<String>,
+
     /// Enable debug information
     #[arg(short = 'g', long, global = true)]
     debug: bool,
@@ -68,6 +72,10 @@ struct Cli {
     /// Produce executable (requires assembler/linker)
     #[arg(long, global = true)]
     emit_exe: bool,
+
+    /// Prove contracts at compile time, warning of those that cannot be
+    #[arg(long, global = true)]
+    prove_contracts: bool,
 }
 
 #[derive(Subcommand)]
@@ -207,7 +215,7 @@ fn compile_file(input: &PathBuf, cli: &Cli) -> Result<(), String> {
 
     // Create compiler options
     // When building exe, we want assembly output first, then link separately
-    let options = jagannath_compiler::driver::CompilerOptions {
+    let mut options = jagannath_compiler::driver::CompilerOptions {
         target,
         guna,
         opt_level: cli.opt_level,
@@ -221,8 +229,15 @@ fn compile_file(input: &PathBuf, cli: &Cli) -> Result<(), String> {
         libraries: Vec::new(),
         deterministic: true,
         emit_asm: cli.emit_asm || cli.emit_exe, // Always emit asm when building exe
-        security_check: true,                   // Nava Durga protection enabled by default
+        security_check: true,                   // -guhya information-flow check enabled by default
+        prove_contracts: cli.prove_contracts,
+        incremental_dir: Some("lakṣya".to_string()), // Reuse unchanged work between builds
+        test: false,
+        features: cli.features.clone(),
     };
+    if let Err(e) = options.add_manifest_features(input) {
+        warn!("Ignoring manifest features: {}", e);
+    }
 
     info!(
         "Target: {:?}, Guṇa: {:?}, Opt: O{}",
@@ -232,6 +247,12 @@ fn compile_file(input: &PathBuf, cli: &Cli) -> Result<(), String> {
     // Create compiler session and compile
     let mut session = jagannath_compiler::driver::CompilerSession::new(options);
     let result = session.compile(&source).map_err(|e| e.message)?;
+    for warning in &result.warnings {
+        match &warning.location {
+            Some(loc) => warn!("{}:{}: {}", loc.line, loc.column, warning.message),
+            None => warn!("{}", warning.message),
+        }
+    }
 
     // Write output if emit_asm is requested
     if cli.emit_asm || cli.emit_exe {
@@ -274,11 +295,15 @@ fn compile_file(input: &PathBuf, cli: &Cli) -> Result<(), String> {
 
     // Show timing if verbose
     if cli.verbose {
-        info!("Timing: lexing={}μs, parsing={}μs, typeck={}μs, mir={}μs, opt={}μs, codegen={}μs, total={}μs",
+        info!("Timing: lexing={}μs, expand={}μs, parsing={}μs, modules={}μs, typeck={}μs, mono={}μs, mir={}μs, borrowck={}μs, opt={}μs, codegen={}μs, total={}μs",
             result.timing.lexing_us,
+            result.timing.macro_expansion_us,
             result.timing.parsing_us,
+            result.timing.module_loading_us,
             result.timing.type_checking_us,
+            result.timing.monomorphization_us,
             result.timing.mir_building_us,
+            result.timing.borrow_checking_us,
             result.timing.optimization_us,
             result.timing.codegen_us,
             result.timing.total_us);
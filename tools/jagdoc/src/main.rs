@@ -12,6 +12,10 @@
 #![allow(unused_mut)]
 
 use clap::Parser;
+use jagannath_compiler::parser::cst::nodes::{item_header, Param};
+use jagannath_compiler::parser::cst::{
+    self, HasDocComments, HasName, HasVisibility, Item, SyntaxNode, TypedNode,
+};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use tracing::{error, info};
@@ -175,113 +179,15 @@ fn extract_documentation(path: &PathBuf, include_private: bool) -> Result<Module
         .map(|s| s.to_string_lossy().to_string())
         .unwrap_or_else(|| "unknown".to_string());
 
-    // TODO: Use actual parser to extract documentation
-    // For now, simple regex-based extraction
+    // The CST keeps comments, so docs come straight from the syntax tree;
+    // items after a parse error are still there
+    let file = cst::parse(&content).tree();
+    let module_docs = file.module_docs();
+    let module_doc = (!module_docs.is_empty()).then(|| module_docs.join("\n"));
 
-    let mut module_doc = None;
     let mut items = Vec::new();
-
-    let mut current_doc = Vec::new();
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        // Module doc (//!)
-        if trimmed.starts_with("//!") {
-            let doc_line = trimmed.strip_prefix("//!").unwrap_or("").trim();
-            if module_doc.is_none() {
-                module_doc = Some(String::new());
-            }
-            if let Some(ref mut doc) = module_doc {
-                if !doc.is_empty() {
-                    doc.push('\n');
-                }
-                doc.push_str(doc_line);
-            }
-            continue;
-        }
-
-        // Item doc (///)
-        if trimmed.starts_with("///") {
-            let doc_line = trimmed.strip_prefix("///").unwrap_or("").trim();
-            current_doc.push(doc_line.to_string());
-            continue;
-        }
-
-        // Check for item declarations
-        if trimmed.starts_with("pub ")
-            || trimmed.starts_with("kāryakrama ")
-            || trimmed.starts_with("prakāra ")
-            || trimmed.starts_with("gaṇa ")
-            || trimmed.starts_with("guṇa ")
-        {
-            let is_public = trimmed.starts_with("pub ");
-            if !is_public && !include_private {
-                current_doc.clear();
-                continue;
-            }
-
-            let (kind, rest) = if trimmed.contains("kāryakrama ") {
-                (ItemKind::Function, extract_after(trimmed, "kāryakrama "))
-            } else if trimmed.contains("prakāra ") {
-                (ItemKind::Struct, extract_after(trimmed, "prakāra "))
-            } else if trimmed.contains("gaṇa ") {
-                (ItemKind::Enum, extract_after(trimmed, "gaṇa "))
-            } else if trimmed.contains("guṇa ") {
-                (ItemKind::Trait, extract_after(trimmed, "guṇa "))
-            } else {
-                current_doc.clear();
-                continue;
-            };
-
-            let item_name = rest
-                .split(|c: char| {
-                    !c.is_alphanumeric()
-                        && c != '_'
-                        && c != 'ā'
-                        && c != 'ī'
-                        && c != 'ū'
-                        && c != 'ṛ'
-                        && c != 'ṅ'
-                        && c != 'ñ'
-                        && c != 'ṭ'
-                        && c != 'ḍ'
-                        && c != 'ṇ'
-                        && c != 'ś'
-                        && c != 'ṣ'
-                })
-                .next()
-                .unwrap_or("unknown")
-                .to_string();
-
-            let doc = if current_doc.is_empty() {
-                None
-            } else {
-                Some(current_doc.join("\n"))
-            };
-
-            items.push(ItemDoc {
-                kind,
-                name: item_name,
-                doc,
-                signature: trimmed.to_string(),
-                visibility: if is_public {
-                    Visibility::Public
-                } else {
-                    Visibility::Private
-                },
-                examples: Vec::new(),
-                params: Vec::new(),
-                returns: None,
-            });
-
-            current_doc.clear();
-        } else {
-            // Non-doc, non-item line - clear accumulated doc
-            if !trimmed.is_empty() && !trimmed.starts_with("//") {
-                current_doc.clear();
-            }
-        }
+    for item in file.items() {
+        collect_item(&item, include_private, &mut items);
     }
 
     Ok(ModuleDoc {
@@ -292,10 +198,73 @@ fn extract_documentation(path: &PathBuf, include_private: bool) -> Result<Module
     })
 }
 
-fn extract_after<'a>(s: &'a str, pattern: &str) -> &'a str {
-    s.find(pattern)
-        .map(|idx| &s[idx + pattern.len()..])
-        .unwrap_or("")
+/// Document `item`, and the items of an inline module
+fn collect_item(item: &Item, include_private: bool, items: &mut Vec<ItemDoc>) {
+    let is_public = item.is_public();
+    if !is_public && !include_private {
+        return;
+    }
+
+    let (kind, name, params, returns) = match item {
+        Item::Fn(func) => (
+            ItemKind::Function,
+            func.name(),
+            func.param_list()
+                .map(|list| list.params().iter().map(param_doc).collect())
+                .unwrap_or_default(),
+            func.ret_type()
+                .and_then(|ret| ret.ty())
+                .map(|ty| ty.syntax().text()),
+        ),
+        Item::Type(ty) if ty.is_enum() => (ItemKind::Enum, ty.name(), Vec::new(), None),
+        Item::Type(ty) => (ItemKind::Struct, ty.name(), Vec::new(), None),
+        Item::Trait(def) => (ItemKind::Trait, def.name(), Vec::new(), None),
+        Item::Const(def) => (ItemKind::Constant, def.name(), Vec::new(), None),
+        Item::Module(module) => {
+            for inner in module.items() {
+                collect_item(&inner, include_private, items);
+            }
+            return;
+        }
+        Item::Impl(_) | Item::Use(_) => return,
+    };
+
+    items.push(ItemDoc {
+        kind,
+        name: name.map_or_else(|| "unknown".to_string(), |name| name.text()),
+        doc: item.doc_string(),
+        signature: signature(item.syntax()),
+        visibility: if is_public {
+            Visibility::Public
+        } else {
+            Visibility::Private
+        },
+        examples: Vec::new(),
+        params,
+        returns,
+    });
+}
+
+fn param_doc(param: &Param) -> ParamDoc {
+    ParamDoc {
+        name: param.name().map(|name| name.text()).unwrap_or_default(),
+        ty: param.ty().map(|ty| ty.syntax().text()).unwrap_or_default(),
+        karaka: param.karaka(),
+        doc: None,
+    }
+}
+
+/// Item header on one line: comments dropped, whitespace collapsed
+fn signature(node: &SyntaxNode) -> String {
+    let header = item_header(node);
+    let mut text = String::new();
+    for piece in header.split_whitespace() {
+        if !text.is_empty() && !text.ends_with('(') && !piece.starts_with(')') {
+            text.push(' ');
+        }
+        text.push_str(piece);
+    }
+    text
 }
 
 fn generate_html(
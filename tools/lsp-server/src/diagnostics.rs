@@ -1,17 +1,93 @@
 //! Diagnostics handling
 
+use jagannath_compiler::parser::Parser;
+use jagannath_compiler::semantics::{configure, CfgContext};
+use jagannath_compiler::CompilerOptions;
 use tower_lsp::lsp_types::*;
 
 /// Generate diagnostics for a document
 pub fn generate_diagnostics(uri: &Url, source: &str) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
 
+    // Features come from the manifest of the package the file is in
+    let mut options = CompilerOptions::new();
+    if let Ok(path) = uri.to_file_path() {
+        let _ = options.add_manifest_features(&path);
+    }
+    diagnostics.extend(syntax_error_diagnostics(source));
+    diagnostics.extend(inactive_code_diagnostics(source, &options.cfg_context()));
+
     // TODO: Integrate with compiler for real diagnostics
-    // This is a placeholder
 
     diagnostics
 }
 
+/// Every parse error, not just the first
+pub fn syntax_error_diagnostics(source: &str) -> Vec<Diagnostic> {
+    let (_, errors) = Parser::parse_str_recovering(source);
+    errors
+        .into_iter()
+        .map(|error| {
+            let mut message = error.message.clone();
+            let expected = error.expected_names();
+            if !expected.is_empty() {
+                message.push_str(&format!("\nexpected one of: {}", expected.join(", ")));
+            }
+            Diagnostic {
+                range: Range {
+                    start: offset_to_position(source, error.span.start),
+                    end: offset_to_position(source, error.span.end),
+                },
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::Number(codes::SYNTAX_ERROR)),
+                code_description: None,
+                source: Some("jagannath".to_string()),
+                message,
+                related_information: None,
+                tags: None,
+                data: None,
+            }
+        })
+        .collect()
+}
+
+/// Gray out code that `#[cfg(...)]` leaves out of the build
+///
+/// Works on the partial AST while the file does not parse, so the rest of
+/// the file stays grayed out as the user types.
+pub fn inactive_code_diagnostics(source: &str, cfg: &CfgContext) -> Vec<Diagnostic> {
+    let (mut ast, _) = Parser::parse_str_recovering(source);
+    let Ok(inactive) = configure(&mut ast, cfg) else {
+        return Vec::new();
+    };
+    inactive
+        .into_iter()
+        .map(|span| Diagnostic {
+            range: Range {
+                start: offset_to_position(source, span.start),
+                end: offset_to_position(source, span.end),
+            },
+            severity: Some(DiagnosticSeverity::HINT),
+            code: Some(NumberOrString::Number(codes::INACTIVE_CODE)),
+            code_description: None,
+            source: Some("jagannath".to_string()),
+            message: "निष्क्रिय (inactive): left out of this build by `cfg`".to_string(),
+            related_information: None,
+            tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+            data: None,
+        })
+        .collect()
+}
+
+/// LSP position (UTF-16 columns) of a byte offset
+fn offset_to_position(source: &str, offset: usize) -> Position {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() as u32;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let character = before[line_start..].encode_utf16().count() as u32;
+    Position { line, character }
+}
+
 /// Convert compiler error to LSP diagnostic
 pub fn compiler_error_to_diagnostic(
     message: &str,
@@ -44,4 +120,47 @@ pub mod codes {
     pub const BORROW_ERROR: i32 = 5000;
     pub const AFFIX_ERROR: i32 = 6000;
     pub const SANDHI_WARNING: i32 = 7000;
+    pub const INACTIVE_CODE: i32 = 8000;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jagannath_compiler::codegen::asm::Target;
+
+    #[test]
+    fn test_inactive_code_is_grayed_out() {
+        let source =
+            "#[cfg(lakṣya = \"aarch64\")]\nkāryakrama vāstu() {}\n\nkāryakrama mukhya() {}\n";
+        let diagnostics = inactive_code_diagnostics(source, &CfgContext::new(Target::X86_64));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].range.start,
+            Position {
+                line: 0,
+                character: 0
+            }
+        );
+        assert_eq!(diagnostics[0].range.end.line, 1);
+        assert_eq!(diagnostics[0].tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+
+        let diagnostics = inactive_code_diagnostics(source, &CfgContext::new(Target::AArch64));
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_every_syntax_error_is_reported() {
+        let source = "kāryakrama a() {\n    let x = ;\n}\n\n#[cfg(lakṣya = \"aarch64\")]\nkāryakrama b() {\n    f(1, ,);\n}\n";
+        let diagnostics = syntax_error_diagnostics(source);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].range.start.line, 1);
+        assert_eq!(diagnostics[1].range.start.line, 6);
+        assert!(diagnostics[0].message.contains("expected one of:"));
+        assert!(diagnostics[0].message.contains("identifier"));
+
+        // The broken file is still grayed out
+        let inactive = inactive_code_diagnostics(source, &CfgContext::new(Target::X86_64));
+        assert_eq!(inactive.len(), 1);
+        assert_eq!(inactive[0].range.start.line, 4);
+    }
 }
@@ -28,6 +28,14 @@ impl JagannathLsp {
     pub fn new(client: Client) -> Self {
         Self { client }
     }
+
+    /// Publish diagnostics for the full text of a document
+    async fn check_document(&self, uri: Url, text: &str, version: i32) {
+        let diagnostics = diagnostics::generate_diagnostics(&uri, text);
+        self.client
+            .publish_diagnostics(uri, diagnostics, Some(version))
+            .await;
+    }
 }
 
 #[tower_lsp::async_trait]
@@ -35,8 +43,9 @@ impl LanguageServer for JagannathLsp {
     async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
+                // Whole documents: diagnostics re-check the full text
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::INCREMENTAL,
+                    TextDocumentSyncKind::FULL,
                 )),
                 completion_provider: Some(CompletionOptions {
                     trigger_characters: Some(vec![".".to_string(), ":".to_string(), "-".to_string()]),
@@ -93,6 +102,20 @@ impl LanguageServer for JagannathLsp {
         Ok(())
     }
 
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        let document = params.text_document;
+        self.check_document(document.uri, &document.text, document.version)
+            .await;
+    }
+
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        if let Some(change) = params.content_changes.into_iter().last() {
+            let document = params.text_document;
+            self.check_document(document.uri, &change.text, document.version)
+                .await;
+        }
+    }
+
     async fn completion(&self, _: CompletionParams) -> Result<Option<CompletionResponse>> {
         // TODO: Implement completion
         Ok(None)
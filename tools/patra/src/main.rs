@@ -125,6 +125,9 @@ struct BuildConfig {
     lakṣya: Option<String>,
     #[serde(default)]
     guṇa: Option<String>,
+    /// Features enabled when building this package
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    viśeṣatā: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]